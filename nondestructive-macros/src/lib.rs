@@ -0,0 +1,197 @@
+//! Macros for [nondestructive].
+//!
+//! These are re-exported by [nondestructive] when its `derive` feature is
+//! enabled, see the documentation there for how to use them.
+//!
+//! [nondestructive]: https://docs.rs/nondestructive
+
+#![allow(clippy::module_name_repetitions)]
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+
+/// Derive typed accessors over a YAML document.
+///
+/// See the `nondestructive::yaml::view` module for documentation.
+#[proc_macro_derive(YamlView, attributes(yaml))]
+pub fn yaml_view(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match expand(&input) {
+        Ok(stream) => stream.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// A field to generate accessors for.
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a syn::Type,
+    key: String,
+}
+
+fn expand(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let fields = fields(input)?;
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let view = format_ident!("{}View", name);
+    let view_mut = format_ident!("{}ViewMut", name);
+
+    let yaml = quote!(::nondestructive::yaml);
+
+    let getters = fields.iter().map(|field| {
+        let Field { ident, ty, key } = field;
+        let doc = format!("Get the value of the `{key}` key.");
+
+        quote! {
+            #[doc = #doc]
+            #[must_use]
+            #vis fn #ident(&self) -> ::core::option::Option<#ty> {
+                let value = self.value.as_mapping()?.get(#key)?;
+                <#ty as #yaml::view::FromValue>::from_value(&value)
+            }
+        }
+    });
+
+    let forwards = fields.iter().map(|field| {
+        let Field { ident, ty, key } = field;
+        let doc = format!("Get the value of the `{key}` key.");
+
+        quote! {
+            #[doc = #doc]
+            #[must_use]
+            #vis fn #ident(&self) -> ::core::option::Option<#ty> {
+                self.as_ref().#ident()
+            }
+        }
+    });
+
+    let setters = fields.iter().map(|field| {
+        let Field { ident, ty, key } = field;
+        let setter = format_ident!("set_{}", ident.unraw());
+        let doc = format!(
+            "Set the value of the `{key}` key, returning `false` if the value isn't a mapping or if the key is locked."
+        );
+
+        quote! {
+            #[doc = #doc]
+            #vis fn #setter(&mut self, value: #ty) -> bool {
+                let ::core::option::Option::Some(mut mapping) = self.value.as_mapping_mut() else {
+                    return false;
+                };
+
+                mapping.try_insert_value(#key, value).is_ok()
+            }
+        }
+    });
+
+    let view_doc = format!("A typed view over a YAML value, generated for [`{name}`].");
+    let view_mut_doc = format!("A typed mutable view over a YAML value, generated for [`{name}`].");
+
+    Ok(quote! {
+        #[doc = #view_doc]
+        #vis struct #view<'a> {
+            value: #yaml::Value<'a>,
+        }
+
+        impl<'a> #view<'a> {
+            /// Construct a new view over the given value.
+            #[must_use]
+            #vis fn new(value: #yaml::Value<'a>) -> Self {
+                Self { value }
+            }
+
+            #(#getters)*
+        }
+
+        #[doc = #view_mut_doc]
+        #vis struct #view_mut<'a> {
+            value: #yaml::ValueMut<'a>,
+        }
+
+        impl<'a> #view_mut<'a> {
+            /// Construct a new view over the given mutable value.
+            #[must_use]
+            #vis fn new(value: #yaml::ValueMut<'a>) -> Self {
+                Self { value }
+            }
+
+            /// Coerce into an immutable view.
+            #[must_use]
+            #vis fn as_ref(&self) -> #view<'_> {
+                #view::new(self.value.as_ref())
+            }
+
+            #(#forwards)*
+
+            #(#setters)*
+        }
+    })
+}
+
+/// Collect the fields of a struct to generate accessors for.
+fn fields(input: &syn::DeriveInput) -> syn::Result<Vec<Field<'_>>> {
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "YamlView can only be derived for structs",
+        ));
+    };
+
+    let syn::Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "YamlView can only be derived for structs with named fields",
+        ));
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "YamlView can't be derived for generic structs",
+        ));
+    }
+
+    let mut fields = Vec::new();
+
+    for field in &named.named {
+        let Some(ident) = &field.ident else {
+            continue;
+        };
+
+        fields.push(Field {
+            ident,
+            ty: &field.ty,
+            key: field_key(field, ident)?,
+        });
+    }
+
+    Ok(fields)
+}
+
+/// Get the key of a field, which is its name unless it's renamed with
+/// `#[yaml(rename = "...")]`.
+fn field_key(field: &syn::Field, ident: &Ident) -> syn::Result<String> {
+    let mut key = None;
+
+    for attr in &field.attrs {
+        if !attr.path().is_ident("yaml") {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                key = Some(value.value());
+                return Ok(());
+            }
+
+            Err(meta.error("unsupported yaml attribute"))
+        })?;
+    }
+
+    Ok(key.unwrap_or_else(|| ident.unraw().to_string()))
+}
@@ -175,16 +175,73 @@
 //! # Ok::<_, anyhow::Error>(())
 //! ```
 //!
+//! <br>
+//!
+//! ## Public dependencies
+//!
+//! Strings which might not be valid UTF-8 are exposed as [`BStr`] from the
+//! [`bstr`] crate. It's re-exported together with the [`ByteSlice`] extension
+//! trait, so that downstream crates can use them without having to depend on a
+//! compatible version of [`bstr`] themselves.
+//!
+//! Arbitrary precision numbers are similarly re-exported as `BigInt` and
+//! `BigDecimal` through the `bigint` and `bigdecimal` features, and
+//! timestamps as `OffsetDateTime` through the `time` feature. Errors implement
+//! `miette::Diagnostic` through the `diagnostics` feature.
+//!
+//! Types from public dependencies are only exposed through re-exports such as
+//! these. Upgrading a public dependency to a new major version is considered a
+//! breaking change of this crate.
+//!
+//! ```
+//! use anyhow::Context;
+//! use nondestructive::{yaml, BStr, ByteSlice};
+//!
+//! let doc = yaml::from_slice(b"- caf\xe9\n- caf\xc3\xa9\n")?;
+//! let root = doc.as_ref().as_sequence().context("missing root")?;
+//!
+//! let first = root.get(0).and_then(|v| v.as_bstr()).context("missing first")?;
+//! assert_eq!(first, BStr::new(b"caf\xe9"));
+//! assert!(first.to_str().is_err());
+//!
+//! let second = root.get(1).and_then(|v| v.as_bstr()).context("missing second")?;
+//! assert_eq!(second.to_str()?, "café");
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+//!
 //! [`Option::and_then`]: https://doc.rust-lang.org/std/option/enum.Option.html#method.and_then
 //! [`ValueMut::as_mapping_mut`]: https://docs.rs/nondestructive/latest/nondestructive/yaml/struct.ValueMut.html#method.as_mapping_mut
 //! [`ValueMut::into_mapping_mut`]: https://docs.rs/nondestructive/latest/nondestructive/yaml/struct.ValueMut.html#method.into_mapping_mut
 //! [`ValueMut`]: https://docs.rs/nondestructive/latest/nondestructive/yaml/struct.ValueMut.html
 //! [Id]: https://docs.rs/nondestructive/latest/nondestructive/yaml/struct.Id.html
 //! [yaml]: https://docs.rs/nondestructive/latest/nondestructive/yaml/index.html
+//! [`BStr`]: https://docs.rs/bstr/1/bstr/struct.BStr.html
+//! [`ByteSlice`]: https://docs.rs/bstr/1/bstr/trait.ByteSlice.html
+//! [`bstr`]: https://docs.rs/bstr/1
 
 #![deny(missing_docs)]
+#![deny(clippy::panic)]
+#![deny(clippy::indexing_slicing, clippy::unreachable)]
 #![allow(clippy::module_inception)]
 #![allow(clippy::module_name_repetitions)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod yaml;
+
+#[doc(no_inline)]
+pub use bstr::{BStr, ByteSlice};
+
+#[cfg(feature = "bigint")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bigint")))]
+#[doc(no_inline)]
+pub use num_bigint::BigInt;
+
+#[cfg(feature = "bigdecimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+#[doc(no_inline)]
+pub use bigdecimal::BigDecimal;
+
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+#[doc(no_inline)]
+pub use time::OffsetDateTime;
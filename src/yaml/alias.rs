@@ -0,0 +1,117 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+
+/// The key used to merge other mappings into a mapping.
+const MERGE_KEY: &[u8] = b"<<";
+
+/// The maximum depth of merges followed, which guards against merges which
+/// refer to themselves.
+const MAX_DEPTH: usize = 64;
+
+/// Resolve the alias `id` to the value it refers to, which is the closest
+/// preceding value in the document with an anchor of the same name.
+pub(crate) fn resolve(data: &Data, id: Id) -> Option<Id> {
+    let Raw::Alias(alias) = data.raw(id) else {
+        return None;
+    };
+
+    let mut root = id;
+
+    while let Some(parent) = data.layout(root).parent {
+        root = parent;
+    }
+
+    let mut found = None;
+    find(data, root, id, data.str(alias.name), &mut found);
+    found
+}
+
+/// Test if `id` is inside of the value `ancestor`.
+pub(crate) fn is_within(data: &Data, mut id: Id, ancestor: Id) -> bool {
+    while let Some(parent) = data.layout(id).parent {
+        if parent == ancestor {
+            return true;
+        }
+
+        id = parent;
+    }
+
+    false
+}
+
+/// Walk the value `id` in document order up until `target`, recording the
+/// last value with an anchor named `name` in `found`.
+///
+/// Returns `true` once `target` has been reached.
+fn find(data: &Data, id: Id, target: Id, name: &[u8], found: &mut Option<Id>) -> bool {
+    if id == target {
+        return true;
+    }
+
+    if let Some(anchor) = data.layout(id).anchor {
+        if data.str(anchor.name) == name {
+            *found = Some(id);
+        }
+    }
+
+    match data.raw(id) {
+        Raw::Mapping(raw) => raw
+            .items
+            .iter()
+            .any(|item| find(data, data.mapping_item(*item).value, target, name, found)),
+        Raw::Sequence(raw) => raw
+            .items
+            .iter()
+            .any(|item| find(data, data.sequence_item(*item).value, target, name, found)),
+        _ => false,
+    }
+}
+
+/// Get the value of `key` in the mapping `id`, including keys merged into it
+/// through `<<`.
+///
+/// Keys in the mapping take precedence over merged keys, and when merging a
+/// sequence of mappings, earlier mappings take precedence over later ones.
+pub(crate) fn get_merged(data: &Data, id: Id, key: &[u8]) -> Option<Id> {
+    lookup(data, id, key, 0)
+}
+
+fn lookup(data: &Data, id: Id, key: &[u8], depth: usize) -> Option<Id> {
+    let Raw::Mapping(raw) = data.raw(id) else {
+        return None;
+    };
+
+    let mut merge = None;
+
+    for item in &raw.items {
+        let item = data.mapping_item(*item);
+        let name = data.str(item.key.id);
+
+        if name == key {
+            return Some(item.value);
+        }
+
+        if name == MERGE_KEY && merge.is_none() {
+            merge = Some(item.value);
+        }
+    }
+
+    if depth == MAX_DEPTH {
+        return None;
+    }
+
+    let merge = target(data, merge?);
+
+    match data.raw(merge) {
+        Raw::Sequence(raw) => raw.items.iter().find_map(|item| {
+            let value = target(data, data.sequence_item(*item).value);
+            lookup(data, value, key, depth + 1)
+        }),
+        _ => lookup(data, merge, key, depth + 1),
+    }
+}
+
+/// Get the value `id` refers to if it's an alias, or the value itself.
+fn target(data: &Data, id: Id) -> Id {
+    resolve(data, id).unwrap_or(id)
+}
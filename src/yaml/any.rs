@@ -33,8 +33,7 @@ impl<'a> Any<'a> {
     ///
     /// ```
     /// use anyhow::Context;
-    /// use bstr::ByteSlice;
-    /// use nondestructive::yaml;
+    /// use nondestructive::{yaml, ByteSlice};
     ///
     /// let doc = yaml::from_slice("null")?;
     /// let doc = doc.as_ref();
@@ -53,8 +52,7 @@ impl<'a> Any<'a> {
     ///
     /// ```
     /// use anyhow::Context;
-    /// use bstr::ByteSlice;
-    /// use nondestructive::yaml;
+    /// use nondestructive::{yaml, ByteSlice};
     ///
     /// let doc = yaml::from_slice("true")?;
     /// let doc = doc.as_ref();
@@ -77,8 +75,7 @@ impl<'a> Any<'a> {
     ///
     /// ```
     /// use anyhow::Context;
-    /// use bstr::ByteSlice;
-    /// use nondestructive::yaml;
+    /// use nondestructive::{yaml, ByteSlice};
     ///
     /// let doc = yaml::from_slice(r#""Hello World""#)?;
     /// let doc = doc.as_ref();
@@ -101,8 +98,7 @@ impl<'a> Any<'a> {
     ///
     /// ```
     /// use anyhow::Context;
-    /// use bstr::ByteSlice;
-    /// use nondestructive::yaml;
+    /// use nondestructive::{yaml, ByteSlice};
     ///
     /// let doc = yaml::from_slice("42")?;
     /// let doc = doc.as_ref();
@@ -0,0 +1,238 @@
+use bstr::ByteSlice;
+
+use crate::yaml::alias;
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+
+/// Indentation used for each level in canonical output.
+const INDENT: usize = 2;
+
+/// The order in which mapping keys are emitted when producing a canonical
+/// rendering of a document.
+///
+/// See [`Document::to_canonical_string_with`].
+///
+/// [`Document::to_canonical_string_with`]: crate::yaml::Document::to_canonical_string_with
+#[derive(Default, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum KeyOrder {
+    /// Keys are emitted in the order in which they appear in the document.
+    #[default]
+    Preserve,
+    /// Keys are sorted by their byte-wise value.
+    Sorted,
+}
+
+/// Where a value is being emitted.
+#[derive(Clone, Copy)]
+enum Position {
+    /// At the root of the document.
+    Root,
+    /// Immediately after a `key:` at the given column.
+    Key(usize),
+    /// Immediately after a `-` at the given column.
+    Dash(usize),
+}
+
+/// Produce the canonical rendering of the given value.
+pub(crate) fn to_string(data: &Data, id: Id, order: KeyOrder) -> String {
+    let mut out = String::new();
+    emit(data, id, order, Position::Root, &mut out);
+    out.push('\n');
+    out
+}
+
+fn emit(data: &Data, id: Id, order: KeyOrder, pos: Position, out: &mut String) {
+    // Aliases are expanded into the value they refer to, unless that value
+    // contains the alias in which case it would never end.
+    if let Some(target) = alias::resolve(data, id) {
+        if !alias::is_within(data, id, target) {
+            emit(data, target, order, pos, out);
+            return;
+        }
+    }
+
+    match data.raw(id) {
+        Raw::Mapping(raw) if !raw.items.is_empty() => {
+            let mut items = raw
+                .items
+                .iter()
+                .map(|item| data.mapping_item(*item))
+                .collect::<Vec<_>>();
+
+            if let KeyOrder::Sorted = order {
+                items.sort_by(|a, b| data.str(a.key.id).cmp(data.str(b.key.id)));
+            }
+
+            let column = collection_column(pos);
+
+            for (index, item) in items.into_iter().enumerate() {
+                start_item(pos, column, index, out);
+                scalar(data.str(item.key.id), out);
+                out.push(':');
+                emit(data, item.value, order, Position::Key(column), out);
+            }
+        }
+        Raw::Sequence(raw) if !raw.items.is_empty() => {
+            let column = collection_column(pos);
+
+            for (index, item) in raw.items.iter().enumerate() {
+                start_item(pos, column, index, out);
+                out.push('-');
+                let value = data.sequence_item(*item).value;
+                emit(data, value, order, Position::Dash(column), out);
+            }
+        }
+        raw => {
+            if !matches!(pos, Position::Root) {
+                out.push(' ');
+            }
+
+            match raw {
+                Raw::Mapping(..) => out.push_str("{}"),
+                Raw::Sequence(..) => out.push_str("[]"),
+                Raw::Boolean(raw) => out.push_str(if raw.value { "true" } else { "false" }),
+                Raw::Number(raw) => number(data.str(raw.string), out),
+                Raw::String(raw) => scalar(data.str(raw.id), out),
+                Raw::Alias(raw) => {
+                    out.push('*');
+                    out.push_str(&data.str(raw.name).to_str_lossy());
+                }
+                _ => out.push_str("null"),
+            }
+        }
+    }
+}
+
+/// The column at which items in a collection at the given position start.
+fn collection_column(pos: Position) -> usize {
+    match pos {
+        Position::Root => 0,
+        Position::Key(column) | Position::Dash(column) => column.saturating_add(INDENT),
+    }
+}
+
+/// Start a new item in a collection.
+fn start_item(pos: Position, column: usize, index: usize, out: &mut String) {
+    match pos {
+        Position::Root if index == 0 => {}
+        // Compact notation, where the first item follows the dash.
+        Position::Dash(..) if index == 0 => {
+            out.push(' ');
+        }
+        _ => {
+            out.push('\n');
+            out.extend(std::iter::repeat(' ').take(column));
+        }
+    }
+}
+
+/// Emit a normalized number.
+///
+/// Numbers are emitted with all of their digits, so that integers and floats
+/// which don't fit in an `i128` or an `f64` aren't rounded.
+fn number(string: &[u8], out: &mut String) {
+    if let Some(value) = crate::yaml::number::parse::<i128>(string) {
+        out.push_str(itoa::Buffer::new().format(value));
+    } else if let Some(value) = crate::yaml::number::parse::<u128>(string) {
+        out.push_str(itoa::Buffer::new().format(value));
+    } else if let Some(value) = crate::yaml::number::normalize(string) {
+        out.push_str(&value);
+    } else if let Some(value) = crate::yaml::number::parse::<f64>(string) {
+        out.push_str(crate::yaml::number::format_float(
+            &mut ryu::Buffer::new(),
+            value + 0.0,
+        ));
+    } else {
+        scalar(string, out);
+    }
+}
+
+/// Emit a string, which is only left unquoted if it can't be mistaken for
+/// anything else.
+fn scalar(string: &[u8], out: &mut String) {
+    match string.to_str() {
+        Ok(plain) if is_plain(plain) => {
+            out.push_str(plain);
+        }
+        _ => {
+            double_quoted(string, out);
+        }
+    }
+}
+
+/// Test if the given string can be emitted without quotes.
+fn is_plain(string: &str) -> bool {
+    let Some(first) = string.chars().next() else {
+        return false;
+    };
+
+    if first.is_ascii_digit()
+        || first.is_whitespace()
+        || matches!(
+            first,
+            '-' | '.' | '+' | '?' | '&' | '*' | '!' | '|' | '>' | '\'' | '"' | '%' | '@' | '`'
+        )
+    {
+        return false;
+    }
+
+    if string.ends_with(char::is_whitespace) {
+        return false;
+    }
+
+    if ["null", "true", "false", "~"]
+        .iter()
+        .any(|keyword| string.eq_ignore_ascii_case(keyword))
+    {
+        return false;
+    }
+
+    !string
+        .chars()
+        .any(|c| c.is_control() || matches!(c, ':' | '#' | ',' | '[' | ']' | '{' | '}' | '\\'))
+}
+
+/// Emit a double-quoted string.
+fn double_quoted(string: &[u8], out: &mut String) {
+    out.push('"');
+
+    for chunk in ByteSlice::utf8_chunks(string) {
+        for c in chunk.valid().chars() {
+            match c {
+                '\0' => out.push_str("\\0"),
+                '\u{07}' => out.push_str("\\a"),
+                '\u{08}' => out.push_str("\\b"),
+                '\t' => out.push_str("\\t"),
+                '\n' => out.push_str("\\n"),
+                '\u{0b}' => out.push_str("\\v"),
+                '\u{0c}' => out.push_str("\\f"),
+                '\r' => out.push_str("\\r"),
+                '\u{1b}' => out.push_str("\\e"),
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                c if c.is_control() => {
+                    push_escape(u32::from(c), out);
+                }
+                c => out.push(c),
+            }
+        }
+
+        for &b in chunk.invalid() {
+            push_escape(u32::from(b), out);
+        }
+    }
+
+    out.push('"');
+}
+
+/// Push a numerical escape sequence.
+fn push_escape(c: u32, out: &mut String) {
+    use std::fmt::Write;
+
+    if c <= 0xff {
+        _ = write!(out, "\\x{c:02x}");
+    } else {
+        _ = write!(out, "\\u{c:04x}");
+    }
+}
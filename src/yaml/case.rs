@@ -0,0 +1,167 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::lock;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::replace;
+
+/// The case convention used by [`MappingMut::normalize_keys`].
+///
+/// Words in a key are separated by `-`, `_` and spaces, and by a change from a
+/// lowercase letter or digit to an uppercase letter like in `fooBar`. Runs of
+/// uppercase letters are kept as one word, so `HTTPServer` consists of the
+/// words `http` and `server`.
+///
+/// [`MappingMut::normalize_keys`]: crate::yaml::MappingMut::normalize_keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Case {
+    /// Lowercase words separated by dashes, like `max-connections`.
+    Kebab,
+    /// Lowercase words separated by underscores, like `max_connections`.
+    Snake,
+    /// Words joined with each word but the first capitalized, like
+    /// `maxConnections`.
+    Camel,
+}
+
+/// Rename the keys of the mapping `id` to follow the given `case`, returning
+/// the number of renamed keys.
+pub(crate) fn normalize_keys(data: &mut Data, id: Id, case: Case, recursive: bool) -> usize {
+    let mut renamed = 0;
+
+    match data.raw(id) {
+        Raw::Mapping(raw) => {
+            let items = raw.items.clone();
+
+            for &item in &items {
+                // Complex keys are written from their own value rather than
+                // the key string, and renaming a key changes the path to any
+                // locked region inside of its item, so both are left as-is.
+                if matches!(
+                    data.mapping_item(item).kind,
+                    raw::MappingItemKind::Complex { .. }
+                ) || lock::check(data, item).is_err()
+                {
+                    continue;
+                }
+
+                let key = &data.mapping_item(item).key;
+                let old = data.str(key.id);
+
+                let Some(new) = convert(old, case) else {
+                    continue;
+                };
+
+                if new == old {
+                    continue;
+                }
+
+                let exists = items.iter().any(|&other| {
+                    other != item && data.str(data.mapping_item(other).key.id) == new.as_slice()
+                });
+
+                // Renaming would result in duplicate keys, so leave it as-is.
+                if exists {
+                    continue;
+                }
+
+                let Some(kind) = replace::kind(data, key) else {
+                    continue;
+                };
+
+                let new = data.insert_str(new);
+                data.mapping_item_mut(item).key = raw::String::new(kind, new, new);
+                renamed += 1;
+            }
+
+            if recursive {
+                for item in items {
+                    let value = data.mapping_item(item).value;
+                    renamed += normalize_keys(data, value, case, recursive);
+                }
+            }
+        }
+        Raw::Sequence(raw) if recursive => {
+            for item in raw.items.clone() {
+                let value = data.sequence_item(item).value;
+                renamed += normalize_keys(data, value, case, recursive);
+            }
+        }
+        _ => {}
+    }
+
+    renamed
+}
+
+/// Convert a single key to the given case.
+///
+/// Returns `None` if the key doesn't contain any words.
+fn convert(key: &[u8], case: Case) -> Option<Vec<u8>> {
+    let words = words(key);
+
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(key.len());
+
+    for (index, word) in words.into_iter().enumerate() {
+        match case {
+            Case::Kebab | Case::Snake => {
+                if index > 0 {
+                    out.push(if case == Case::Kebab { b'-' } else { b'_' });
+                }
+
+                out.extend(word.iter().map(u8::to_ascii_lowercase));
+            }
+            Case::Camel => {
+                let mut word = word.iter().map(u8::to_ascii_lowercase);
+
+                if index > 0 {
+                    out.extend(word.next().map(|b| b.to_ascii_uppercase()));
+                }
+
+                out.extend(word);
+            }
+        }
+    }
+
+    Some(out)
+}
+
+/// Split a key into words.
+fn words(key: &[u8]) -> Vec<&[u8]> {
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    for (n, &b) in key.iter().enumerate() {
+        if matches!(b, b'-' | b'_' | b' ') {
+            if let Some(word) = key.get(start..n).filter(|w| !w.is_empty()) {
+                words.push(word);
+            }
+
+            start = n + 1;
+            continue;
+        }
+
+        if start == n || !b.is_ascii_uppercase() {
+            continue;
+        }
+
+        let prev = key.get(n - 1).copied().unwrap_or_default();
+        let next = key.get(n + 1);
+
+        if prev.is_ascii_lowercase()
+            || prev.is_ascii_digit()
+            || (prev.is_ascii_uppercase() && next.is_some_and(u8::is_ascii_lowercase))
+        {
+            words.push(key.get(start..n).unwrap_or_default());
+            start = n;
+        }
+    }
+
+    if let Some(word) = key.get(start..).filter(|w| !w.is_empty()) {
+        words.push(word);
+    }
+
+    words
+}
@@ -0,0 +1,227 @@
+//! Classification of scalars which contain embedded languages.
+//!
+//! Many configuration formats embed other languages in their scalars, like the
+//! shell scripts in the `run` keys of a CI workflow or the cron expressions of
+//! a schedule. A [`Classifier`] associates path patterns with classes naming
+//! such languages, so that tools built on top of this crate can route embedded
+//! content to other analyzers.
+//!
+//! Path patterns are sequences of segments from the root of a [`Document`],
+//! where the segment `*` matches every key of a mapping or every item of a
+//! sequence, just like in [`Migration`].
+//!
+//! [`Migration`]: crate::yaml::migrate::Migration
+//!
+//! # Examples
+//!
+//! ```
+//! use nondestructive::yaml;
+//! use nondestructive::yaml::classify::Classifier;
+//!
+//! let doc = yaml::from_slice(
+//!     r"
+//!     schedule: 0 * * * *
+//!     steps:
+//!       - name: build
+//!         run: cargo build
+//!       - name: test
+//!         run: cargo test
+//!     "
+//! )?;
+//!
+//! let classifier = Classifier::new()
+//!     .register(&["schedule"], "cron")
+//!     .register(&["steps", "*", "run"], "shell");
+//!
+//! let text = doc.to_string();
+//!
+//! let tokens = classifier.classify(&doc);
+//!
+//! let tokens = tokens
+//!     .iter()
+//!     .map(|token| (token.class(), &text[token.span()]))
+//!     .collect::<Vec<_>>();
+//!
+//! assert_eq!(
+//!     tokens,
+//!     [("cron", "0 * * * *"), ("shell", "cargo build"), ("shell", "cargo test")]
+//! );
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use core::ops::Range;
+use std::collections::HashMap;
+
+use crate::yaml::data::Id;
+use crate::yaml::dump;
+use crate::yaml::{Document, Outline, OutlineKind};
+
+/// The segment matching any key or index.
+const WILDCARD: &str = "*";
+
+/// A single rule in a [`Classifier`].
+struct Rule {
+    path: Vec<String>,
+    class: String,
+}
+
+/// A set of rules associating the scalars at given paths with a class.
+///
+/// See the [module-level documentation][self] for more information.
+#[derive(Default)]
+pub struct Classifier {
+    rules: Vec<Rule>,
+}
+
+impl Classifier {
+    /// Construct a new classifier without any rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule which classifies the scalars matched by `path` as `class`.
+    ///
+    /// If multiple rules match the same scalar, the rule which was added first
+    /// is used.
+    #[must_use]
+    pub fn register(mut self, path: &[&str], class: &str) -> Self {
+        self.rules.push(Rule {
+            path: path.iter().map(|s| (*s).to_owned()).collect(),
+            class: class.to_owned(),
+        });
+
+        self
+    }
+
+    /// Get the class of the value at the concrete `path`, such as the path of
+    /// an [`Outline`] entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::classify::Classifier;
+    ///
+    /// let doc = yaml::from_slice("steps:\n  - run: make\n")?;
+    /// let classifier = Classifier::new().register(&["steps", "*", "run"], "shell");
+    ///
+    /// let outline = doc.outline();
+    /// let step = &outline.children()[0].children()[0];
+    /// let run = &step.children()[0];
+    ///
+    /// assert_eq!(classifier.class(step.path()), None);
+    /// assert_eq!(classifier.class(run.path()), Some("shell"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn class<S>(&self, path: &[S]) -> Option<&str>
+    where
+        S: AsRef<str>,
+    {
+        let rule = self.rules.iter().find(|rule| matches(&rule.path, path))?;
+        Some(&rule.class)
+    }
+
+    /// Find every scalar in `doc` matched by a rule, in the order they appear
+    /// in the document.
+    ///
+    /// Collections matched by a rule are not classified.
+    #[must_use]
+    pub fn classify(&self, doc: &Document) -> Vec<Token> {
+        let mut classify = Classify {
+            classifier: self,
+            spans: dump::spans(&doc.data, doc.root),
+            offset: doc.header_len(),
+            tokens: Vec::new(),
+        };
+
+        classify.entry(&doc.outline());
+        classify.tokens
+    }
+}
+
+/// A scalar classified by a [`Classifier`].
+#[derive(Debug, Clone)]
+pub struct Token {
+    id: Id,
+    path: Vec<String>,
+    span: Range<usize>,
+    class: String,
+}
+
+impl Token {
+    /// The identifier of the classified scalar.
+    #[must_use]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The concrete path of the classified scalar.
+    ///
+    /// Wildcards are replaced with the keys or indexes they matched.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// The span of the scalar in the output of [`Document::write_to`].
+    ///
+    /// This only covers the scalar itself, including any quotes or block
+    /// indicators, but not the key it's associated with.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The class of the scalar, as registered with
+    /// [`Classifier::register`].
+    #[must_use]
+    pub fn class(&self) -> &str {
+        &self.class
+    }
+}
+
+struct Classify<'a> {
+    classifier: &'a Classifier,
+    spans: HashMap<Id, Range<usize>>,
+    offset: usize,
+    tokens: Vec<Token>,
+}
+
+impl Classify<'_> {
+    fn entry(&mut self, entry: &Outline) {
+        if matches!(entry.kind(), OutlineKind::Mapping | OutlineKind::Sequence) {
+            for child in entry.children() {
+                self.entry(child);
+            }
+
+            return;
+        }
+
+        let Some(class) = self.classifier.class(entry.path()) else {
+            return;
+        };
+
+        let span = self.spans.get(&entry.id()).cloned().unwrap_or_default();
+
+        self.tokens.push(Token {
+            id: entry.id(),
+            path: entry.path().to_vec(),
+            span: span.start + self.offset..span.end + self.offset,
+            class: class.to_owned(),
+        });
+    }
+}
+
+/// Test if the concrete `path` matches `pattern`.
+fn matches<S>(pattern: &[String], path: &[S]) -> bool
+where
+    S: AsRef<str>,
+{
+    pattern.len() == path.len()
+        && pattern
+            .iter()
+            .zip(path)
+            .all(|(pattern, segment)| pattern == WILDCARD || pattern == segment.as_ref())
+}
@@ -0,0 +1,58 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw;
+
+/// Style deciding what a mapping or a sequence is left as when it's cleared
+/// with [`MappingMut::clear_with`] or [`SequenceMut::clear_with`].
+///
+/// [`MappingMut::clear_with`]: crate::yaml::MappingMut::clear_with
+/// [`SequenceMut::clear_with`]: crate::yaml::SequenceMut::clear_with
+#[derive(Default, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Clear {
+    /// Keep the style of the collection. Block collections are left as a
+    /// null value, and inline collections as `{}` or `[]`.
+    #[default]
+    Preserve,
+    /// Leave an empty inline collection, like `{}` or `[]`. Values inserted
+    /// afterwards are written inline.
+    Flow,
+    /// Leave a null value. Inline collections are turned into block
+    /// collections, unless they are nested inside of another inline
+    /// collection, so that values inserted afterwards are written as blocks.
+    Null,
+}
+
+/// Drop all items in the given list.
+pub(crate) fn drop_items(data: &mut Data, items: &mut Vec<Id>) {
+    for item in items.drain(..) {
+        data.drop(item);
+    }
+}
+
+/// Construct the prefix used for a block collection turned into an empty
+/// inline collection.
+///
+/// Whitespace in front of a nested collection is collapsed into a single
+/// space, so that it's written on the same line as its key or `-`. Prefixes
+/// with comments, and the prefix of the root, are kept as they are.
+pub(crate) fn flow_prefix(data: &mut Data, id: Id) {
+    let layout = data.layout(id);
+
+    if layout.parent.is_none() {
+        return;
+    }
+
+    let prefix = data.str(layout.prefix);
+
+    if prefix.iter().all(u8::is_ascii_whitespace) {
+        let prefix = data.insert_str(" ");
+        data.set_prefix(id, prefix);
+    }
+}
+
+/// Test if the collection `id` is nested inside of an inline collection, in
+/// which case it can't be written as a block.
+pub(crate) fn in_flow(data: &Data, id: Id) -> bool {
+    let container = data.layout(id).parent.and_then(|id| data.layout(id).parent);
+    container.is_some_and(|id| raw::is_inline(data, id))
+}
@@ -0,0 +1,415 @@
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::raw::{self, Raw};
+
+/// A string of whitespace and comments in a document.
+#[derive(Clone, Copy)]
+pub(crate) enum Slot {
+    /// The prefix of a value or item.
+    Prefix(Id),
+    /// The suffix of an item, which is the comment following its value on the
+    /// same line in a block collection.
+    Suffix(Id),
+    /// The suffix inside of an inline collection, before its closing bracket.
+    Inline(Id),
+    /// The whitespace following the tag of a value.
+    Tag(Id),
+    /// The whitespace following the anchor of a value.
+    Anchor(Id),
+}
+
+impl Slot {
+    /// Get the string in the slot.
+    fn get(self, data: &Data) -> StringId {
+        match self {
+            Slot::Prefix(id) => data.layout(id).prefix,
+            Slot::Suffix(id) => match data.raw(id) {
+                Raw::MappingItem(raw) => raw.suffix,
+                Raw::SequenceItem(raw) => raw.suffix,
+                _ => None,
+            }
+            .unwrap_or_else(|| data.layout(id).prefix),
+            Slot::Inline(id) => match data.raw(id) {
+                Raw::Mapping(raw::Mapping {
+                    kind: raw::MappingKind::Inline { suffix, .. },
+                    ..
+                })
+                | Raw::Sequence(raw::Sequence {
+                    kind: raw::SequenceKind::Inline { suffix, .. },
+                    ..
+                }) => *suffix,
+                _ => data.layout(id).prefix,
+            },
+            Slot::Tag(id) => match data.raw(id) {
+                Raw::Mapping(raw::Mapping { tag: Some(tag), .. })
+                | Raw::Sequence(raw::Sequence { tag: Some(tag), .. }) => tag.suffix,
+                _ => match data.layout(id).tag {
+                    Some(tag) => tag.suffix,
+                    None => data.layout(id).prefix,
+                },
+            },
+            Slot::Anchor(id) => match data.layout(id).anchor {
+                Some(anchor) => anchor.suffix,
+                None => data.layout(id).prefix,
+            },
+        }
+    }
+
+    /// Replace the string in the slot.
+    fn set(self, data: &mut Data, string: StringId) {
+        match self {
+            Slot::Prefix(id) => {
+                data.set_prefix(id, string);
+            }
+            Slot::Suffix(id) => match data.raw_mut(id) {
+                Raw::MappingItem(raw) => raw.suffix = Some(string),
+                Raw::SequenceItem(raw) => raw.suffix = Some(string),
+                _ => {}
+            },
+            Slot::Inline(id) => match data.raw_mut(id) {
+                Raw::Mapping(raw::Mapping {
+                    kind: raw::MappingKind::Inline { suffix, .. },
+                    ..
+                })
+                | Raw::Sequence(raw::Sequence {
+                    kind: raw::SequenceKind::Inline { suffix, .. },
+                    ..
+                }) => *suffix = string,
+                _ => {}
+            },
+            Slot::Tag(id) => match data.raw_mut(id) {
+                Raw::Mapping(raw::Mapping { tag: Some(tag), .. })
+                | Raw::Sequence(raw::Sequence { tag: Some(tag), .. }) => tag.suffix = string,
+                _ => {
+                    if let Some(tag) = data.layout(id).tag {
+                        let tag = raw::Tag {
+                            suffix: string,
+                            ..tag
+                        };
+                        data.set_tag(id, Some(tag));
+                    }
+                }
+            },
+            Slot::Anchor(id) => {
+                if let Some(anchor) = data.layout(id).anchor {
+                    let anchor = raw::Anchor {
+                        suffix: string,
+                        ..anchor
+                    };
+                    data.set_anchor(id, Some(anchor));
+                }
+            }
+        }
+    }
+}
+
+/// A transformation applied to the comments in a string of whitespace.
+pub(crate) trait Transform {
+    /// Transform the comments in `string`, where `trailing` indicates if the
+    /// first line of the string follows other content, in which case `column`
+    /// is the column the string starts at. Returns `None` if nothing changed.
+    fn apply(&mut self, string: &[u8], trailing: bool, column: usize) -> Option<Vec<u8>>;
+}
+
+/// Apply a transformation to every string of whitespace in a document,
+/// returning the number of strings which were changed.
+pub(crate) fn apply<T>(data: &mut Data, root: Id, suffix: &mut StringId, mut transform: T) -> usize
+where
+    T: Transform,
+{
+    let mut slots = Vec::new();
+    let mut out = Vec::new();
+    prefix(data, root, &mut out, &mut slots);
+    value(data, root, &mut out, &mut slots);
+
+    let mut changed = 0;
+
+    for (slot, offset) in slots {
+        let (trailing, column) = position(&out, offset);
+
+        if let Some(new) = transform.apply(data.str(slot.get(data)), trailing, column) {
+            let new = data.insert_str(new);
+            slot.set(data, new);
+            changed += 1;
+        }
+    }
+
+    let (trailing, column) = position(&out, out.len());
+
+    if let Some(new) = transform.apply(data.str(*suffix), trailing, column) {
+        *suffix = data.insert_str(new);
+        changed += 1;
+    }
+
+    changed
+}
+
+/// Get the position at `offset` in the output, which is whether it follows
+/// content on the same line and its column.
+fn position(out: &[u8], offset: usize) -> (bool, usize) {
+    let before = out.get(..offset).unwrap_or_default();
+    let start = before.rfind_byte(raw::NEWLINE).map_or(0, |n| n + 1);
+    let line = before.get(start..).unwrap_or_default();
+    let trailing = line.iter().any(|b| !b.is_ascii_whitespace());
+    (trailing, line.chars().count())
+}
+
+/// Record a slot and write its string.
+fn slot(data: &Data, slot: Slot, out: &mut Vec<u8>, slots: &mut Vec<(Slot, usize)>) {
+    slots.push((slot, out.len()));
+    out.extend_from_slice(data.str(slot.get(data)));
+}
+
+/// Record the prefix of the value `id` and write it, followed by its anchor and
+/// tag if it has them.
+fn prefix(data: &Data, id: Id, out: &mut Vec<u8>, slots: &mut Vec<(Slot, usize)>) {
+    slot(data, Slot::Prefix(id), out, slots);
+
+    if let Some(anchor) = data.layout(id).anchor {
+        out.push(b'&');
+        out.extend_from_slice(data.str(anchor.name));
+        slot(data, Slot::Anchor(id), out, slots);
+    }
+
+    if let Some(tag) = data.layout(id).tag {
+        out.extend_from_slice(data.str(tag.name));
+        slot(data, Slot::Tag(id), out, slots);
+    }
+}
+
+/// Write the value `id`, recording the slots it contains.
+fn value(data: &Data, id: Id, out: &mut Vec<u8>, slots: &mut Vec<(Slot, usize)>) {
+    match data.raw(id) {
+        Raw::Mapping(raw) => {
+            let inline = match raw.kind {
+                raw::MappingKind::Inline { trailing, .. } => Some(trailing),
+                raw::MappingKind::Mapping => None,
+            };
+
+            collection(
+                data,
+                id,
+                &raw.items,
+                raw.tag.as_ref(),
+                inline,
+                false,
+                out,
+                slots,
+            );
+        }
+        Raw::Sequence(raw) => {
+            let inline = match raw.kind {
+                raw::SequenceKind::Inline { trailing, .. } => Some(trailing),
+                raw::SequenceKind::Mapping => None,
+            };
+
+            collection(
+                data,
+                id,
+                &raw.items,
+                raw.tag.as_ref(),
+                inline,
+                true,
+                out,
+                slots,
+            );
+        }
+        Raw::MappingItem(raw) => {
+            match raw.kind {
+                raw::MappingItemKind::Pair => {
+                    let _ = raw.key.write_to(data, out);
+                    out.push(b':');
+                }
+                raw::MappingItemKind::Explicit { separator } => {
+                    out.push(b'?');
+                    out.extend_from_slice(data.str(separator));
+                    let _ = raw.key.write_to(data, out);
+                }
+                raw::MappingItemKind::Key => {
+                    let _ = raw.key.write_to(data, out);
+                }
+                raw::MappingItemKind::Complex { key, colon } => {
+                    out.push(b'?');
+                    prefix(data, key, out, slots);
+                    value(data, key, out, slots);
+
+                    if let Some(colon) = colon {
+                        out.extend_from_slice(data.str(colon));
+                        out.push(b':');
+                    }
+                }
+            }
+
+            item(data, id, raw.value, raw.suffix.is_some(), out, slots);
+        }
+        Raw::SequenceItem(raw) => {
+            item(data, id, raw.value, raw.suffix.is_some(), out, slots);
+        }
+        raw => {
+            // Writing into a vector can't fail.
+            let _ = raw.write_to(data, out);
+        }
+    }
+}
+
+/// Write the value of an item, recording the slots it contains.
+fn item(
+    data: &Data,
+    id: Id,
+    value: Id,
+    suffix: bool,
+    out: &mut Vec<u8>,
+    slots: &mut Vec<(Slot, usize)>,
+) {
+    self::prefix(data, value, out, slots);
+    self::value(data, value, out, slots);
+
+    if suffix {
+        self::slot(data, Slot::Suffix(id), out, slots);
+    }
+}
+
+/// Write a collection, recording the slots it contains.
+#[allow(clippy::too_many_arguments)]
+fn collection(
+    data: &Data,
+    id: Id,
+    items: &[Id],
+    tag: Option<&raw::Tag>,
+    inline: Option<bool>,
+    marker: bool,
+    out: &mut Vec<u8>,
+    slots: &mut Vec<(Slot, usize)>,
+) {
+    if let Some(tag) = tag {
+        out.extend_from_slice(data.str(tag.name));
+        slot(data, Slot::Tag(id), out, slots);
+    }
+
+    let (open, close) = if marker { (b'[', b']') } else { (b'{', b'}') };
+
+    if inline.is_some() {
+        out.push(open);
+    }
+
+    for (index, &item) in items.iter().enumerate() {
+        if index > 0 && inline.is_some() {
+            out.push(b',');
+        }
+
+        slot(data, Slot::Prefix(item), out, slots);
+
+        if marker && inline.is_none() {
+            out.push(b'-');
+        }
+
+        value(data, item, out, slots);
+    }
+
+    if let Some(trailing) = inline {
+        if trailing && !items.is_empty() {
+            out.push(b',');
+        }
+
+        slot(data, Slot::Inline(id), out, slots);
+        out.push(close);
+    }
+}
+
+/// Insert a space after the `#` of comments which lack one.
+pub(crate) struct Space;
+
+impl Transform for Space {
+    fn apply(&mut self, string: &[u8], _: bool, _: usize) -> Option<Vec<u8>> {
+        let mut out = Vec::with_capacity(string.len());
+        let mut changed = false;
+
+        for (index, line) in string.split(|&b| b == raw::NEWLINE).enumerate() {
+            if index > 0 {
+                out.push(raw::NEWLINE);
+            }
+
+            let Some(start) = line.find_byte(b'#') else {
+                out.extend_from_slice(line);
+                continue;
+            };
+
+            let (head, rest) = line.split_at(start);
+            let hashes = rest.iter().take_while(|&&b| b == b'#').count();
+            let (hashes, rest) = rest.split_at(hashes);
+            out.extend_from_slice(head);
+            out.extend_from_slice(hashes);
+
+            if rest
+                .first()
+                .is_some_and(|&b| !matches!(b, b' ' | b'\t' | b'\r' | b'!'))
+            {
+                out.push(b' ');
+                changed = true;
+            }
+
+            out.extend_from_slice(rest);
+        }
+
+        changed.then_some(out)
+    }
+}
+
+/// Align trailing comments to a column.
+pub(crate) struct Align(pub(crate) usize);
+
+impl Transform for Align {
+    fn apply(&mut self, string: &[u8], trailing: bool, column: usize) -> Option<Vec<u8>> {
+        if !trailing {
+            return None;
+        }
+
+        let end = string.find_byte(raw::NEWLINE).unwrap_or(string.len());
+        let first = string.get(..end).unwrap_or_default();
+        let start = first.find_byte(b'#')?;
+        let (indent, comment) = string.split_at(start);
+
+        if indent.iter().any(|b| !matches!(b, b' ' | b'\t')) {
+            return None;
+        }
+
+        let spaces = self.0.saturating_sub(column).max(1);
+
+        if indent.iter().all(|&b| b == raw::SPACE) && start == spaces {
+            return None;
+        }
+
+        let mut out = Vec::with_capacity(string.len());
+        out.resize(spaces, raw::SPACE);
+        out.extend_from_slice(comment);
+        Some(out)
+    }
+}
+
+/// Remove all comments.
+pub(crate) struct Strip;
+
+impl Transform for Strip {
+    fn apply(&mut self, string: &[u8], trailing: bool, _: usize) -> Option<Vec<u8>> {
+        string.find_byte(b'#')?;
+
+        let mut kept = Vec::new();
+
+        for (index, line) in string.split(|&b| b == raw::NEWLINE).enumerate() {
+            if line.find_byte(b'#').is_none() {
+                kept.push(line);
+                continue;
+            }
+
+            // A trailing comment leaves the line it shares with the preceding
+            // content intact, while a line only containing a comment is
+            // removed together with its newline.
+            if index == 0 && trailing {
+                kept.push(&[]);
+            }
+        }
+
+        Some(kept.join(&raw::NEWLINE))
+    }
+}
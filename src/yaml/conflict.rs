@@ -0,0 +1,419 @@
+//! Tolerant parsing of documents containing version control conflict markers.
+//!
+//! When a merge fails, tools such as git leave conflict regions in the file
+//! which look like this:
+//!
+//! ```text
+//! <<<<<<< HEAD
+//! replicas: 3
+//! =======
+//! replicas: 5
+//! >>>>>>> feature
+//! ```
+//!
+//! Such a file is not valid YAML, but can be parsed with [`parse`] into a
+//! [`Conflicted`] document. Each conflict region is represented by a
+//! [`Region`], for which a [`Side`] can be chosen programmatically before the
+//! clean output is produced. Regions in the `diff3` style, which include the
+//! common ancestor after a `|||||||` marker, are also supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use nondestructive::yaml;
+//! use nondestructive::yaml::conflict::Side;
+//!
+//! const INPUT: &str = r"name: app
+//! <<<<<<< HEAD
+//! replicas: 3
+//! =======
+//! replicas: 5
+//! >>>>>>> feature
+//! image: app:1.0
+//! ";
+//!
+//! let mut conflicted = yaml::conflict::parse(INPUT)?;
+//! assert_eq!(conflicted.regions().len(), 1);
+//!
+//! for region in conflicted.regions_mut() {
+//!     let ours = yaml::from_slice(region.ours())?;
+//!     let theirs = yaml::from_slice(region.theirs())?;
+//!
+//!     let ours = ours.as_ref().as_mapping().and_then(|m| m.get("replicas")?.as_u32());
+//!     let theirs = theirs.as_ref().as_mapping().and_then(|m| m.get("replicas")?.as_u32());
+//!
+//!     // Pick the side with the most replicas.
+//!     region.choose(if ours >= theirs { Side::Ours } else { Side::Theirs });
+//! }
+//!
+//! let doc = conflicted.to_document()?;
+//! assert_eq!(doc.to_string(), "name: app\nreplicas: 5\nimage: app:1.0\n");
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use core::ops::Range;
+
+use bstr::BStr;
+
+use crate::yaml::error::{Error, ErrorKind};
+use crate::yaml::Document;
+
+/// Marker starting a conflict region and our side of it.
+const OURS: &[u8] = b"<<<<<<<";
+/// Marker starting the common ancestor of a conflict region.
+const BASE: &[u8] = b"|||||||";
+/// Marker starting their side of a conflict region.
+const THEIRS: &[u8] = b"=======";
+/// Marker ending a conflict region.
+const END: &[u8] = b">>>>>>>";
+
+/// Parse a document which might contain conflict regions.
+///
+/// Input without any conflict markers is accepted as well, in which case the
+/// resulting document has no regions. The content outside and inside of
+/// conflict regions is not parsed as YAML until a document is produced.
+///
+/// # Errors
+///
+/// Errors with [`ErrorKind::UnterminatedConflict`] if a conflict region is
+/// missing its `=======` or `>>>>>>>` marker.
+pub fn parse<D>(input: D) -> Result<Conflicted, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let input = input.as_ref();
+
+    let mut parts = Vec::new();
+    let mut regions = Vec::new();
+    let mut text = 0;
+    let mut lines = Lines::new(input);
+
+    while let Some(line) = lines.next() {
+        let Some(ours_label) = input.get(line.clone()).and_then(|l| marker(l, OURS)) else {
+            continue;
+        };
+
+        if let Some(string) = input.get(text..line.start).filter(|s| !s.is_empty()) {
+            parts.push(Part::Text(string.to_vec()));
+        }
+
+        let start = line.start;
+        let mut ours = line.end..line.end;
+        let mut base = None;
+        let mut theirs = None;
+        let mut theirs_label = None;
+
+        for line in lines.by_ref() {
+            let string = input.get(line.clone()).unwrap_or_default();
+
+            if theirs.is_none() {
+                if marker(string, BASE).is_some() {
+                    base = Some(line.end..line.end);
+                    continue;
+                }
+
+                if marker(string, THEIRS).is_some() {
+                    theirs = Some(line.end..line.end);
+                    continue;
+                }
+
+                match &mut base {
+                    Some(base) => base.end = line.end,
+                    None => ours.end = line.end,
+                }
+
+                continue;
+            }
+
+            if let Some(label) = marker(string, END) {
+                theirs_label = Some((label.to_vec(), line.end));
+                break;
+            }
+
+            if let Some(theirs) = &mut theirs {
+                theirs.end = line.end;
+            }
+        }
+
+        let (Some(theirs), Some((theirs_label, end))) = (theirs, theirs_label) else {
+            return Err(Error::new(
+                start..input.len(),
+                ErrorKind::UnterminatedConflict,
+            ));
+        };
+
+        parts.push(Part::Region(regions.len()));
+
+        regions.push(Region {
+            span: start..end,
+            ours_label: ours_label.to_vec(),
+            theirs_label,
+            ours: input.get(ours).unwrap_or_default().to_vec(),
+            base: base.map(|base| input.get(base).unwrap_or_default().to_vec()),
+            theirs: input.get(theirs).unwrap_or_default().to_vec(),
+            choice: None,
+        });
+
+        text = end;
+    }
+
+    if let Some(string) = input.get(text..).filter(|s| !s.is_empty()) {
+        parts.push(Part::Text(string.to_vec()));
+    }
+
+    Ok(Conflicted { parts, regions })
+}
+
+/// The side of a conflict region to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Side {
+    /// Keep our side of the region, which is the one following `<<<<<<<`.
+    Ours,
+    /// Keep their side of the region, which is the one following `=======`.
+    Theirs,
+    /// Keep both sides of the region, with our side first.
+    Both,
+    /// Keep the common ancestor of the region, which is the one following
+    /// `|||||||`. If the region doesn't include the common ancestor, nothing
+    /// is kept.
+    Base,
+}
+
+/// A single conflict region in a [`Conflicted`] document.
+#[derive(Debug, Clone)]
+pub struct Region {
+    span: Range<usize>,
+    ours_label: Vec<u8>,
+    theirs_label: Vec<u8>,
+    ours: Vec<u8>,
+    base: Option<Vec<u8>>,
+    theirs: Vec<u8>,
+    choice: Option<Side>,
+}
+
+impl Region {
+    /// The span of the region in the input, including its markers.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The label following the `<<<<<<<` marker, such as `HEAD`.
+    #[must_use]
+    pub fn ours_label(&self) -> &BStr {
+        BStr::new(&self.ours_label)
+    }
+
+    /// The label following the `>>>>>>>` marker, such as the name of the
+    /// branch being merged.
+    #[must_use]
+    pub fn theirs_label(&self) -> &BStr {
+        BStr::new(&self.theirs_label)
+    }
+
+    /// Our side of the region.
+    #[must_use]
+    pub fn ours(&self) -> &[u8] {
+        &self.ours
+    }
+
+    /// The common ancestor of the region, if it was included.
+    #[must_use]
+    pub fn base(&self) -> Option<&[u8]> {
+        self.base.as_deref()
+    }
+
+    /// Their side of the region.
+    #[must_use]
+    pub fn theirs(&self) -> &[u8] {
+        &self.theirs
+    }
+
+    /// The side which has been chosen for this region, if any.
+    #[must_use]
+    pub fn choice(&self) -> Option<Side> {
+        self.choice
+    }
+
+    /// Choose which side of the region to keep.
+    pub fn choose(&mut self, side: Side) {
+        self.choice = Some(side);
+    }
+
+    /// Write the chosen side of the region.
+    fn write(&self, side: Side, out: &mut Vec<u8>) {
+        match side {
+            Side::Ours => out.extend_from_slice(&self.ours),
+            Side::Theirs => out.extend_from_slice(&self.theirs),
+            Side::Both => {
+                out.extend_from_slice(&self.ours);
+                out.extend_from_slice(&self.theirs);
+            }
+            Side::Base => out.extend_from_slice(self.base.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+/// Part of a conflicted document.
+#[derive(Debug, Clone)]
+enum Part {
+    /// Text outside of any conflict region.
+    Text(Vec<u8>),
+    /// A conflict region, by index.
+    Region(usize),
+}
+
+/// A document which might contain conflict regions, as returned by [`parse`].
+#[derive(Debug, Clone)]
+pub struct Conflicted {
+    parts: Vec<Part>,
+    regions: Vec<Region>,
+}
+
+impl Conflicted {
+    /// The conflict regions of the document, in the order they appear.
+    #[must_use]
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// The conflict regions of the document mutably, in the order they appear.
+    pub fn regions_mut(&mut self) -> &mut [Region] {
+        &mut self.regions
+    }
+
+    /// Choose the given side for every region.
+    pub fn choose_all(&mut self, side: Side) {
+        for region in &mut self.regions {
+            region.choose(side);
+        }
+    }
+
+    /// Test if a side has been chosen for every region.
+    #[must_use]
+    pub fn is_resolved(&self) -> bool {
+        self.regions.iter().all(|region| region.choice.is_some())
+    }
+
+    /// Produce the clean output of the document, with every region replaced
+    /// by its chosen side.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::UnresolvedConflict`] if no side has been
+    /// chosen for a region, where the span of the error is the span of the
+    /// region in the input.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.write(None)
+    }
+
+    /// Produce the clean output of the document, using `side` for regions
+    /// which have not been resolved.
+    ///
+    /// This is useful to inspect what the document would look like if one
+    /// side was picked before making a choice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::conflict::Side;
+    ///
+    /// let conflicted = yaml::conflict::parse("<<<<<<< HEAD\na: 1\n=======\na: 2\n>>>>>>> main\nb: 3\n")?;
+    ///
+    /// assert_eq!(conflicted.to_bytes_with(Side::Ours), b"a: 1\nb: 3\n");
+    /// assert_eq!(conflicted.to_bytes_with(Side::Theirs), b"a: 2\nb: 3\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_bytes_with(&self, side: Side) -> Vec<u8> {
+        // Every region has a side when a default is provided, so this never
+        // errors.
+        self.write(Some(side)).unwrap_or_default()
+    }
+
+    /// Parse the clean output of the document as a [`Document`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::UnresolvedConflict`] if no side has been
+    /// chosen for a region. Errors while parsing the clean output have spans
+    /// which refer to the clean output rather than the original input.
+    pub fn to_document(&self) -> Result<Document, Error> {
+        crate::yaml::from_slice(self.to_bytes()?)
+    }
+
+    fn write(&self, default: Option<Side>) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.extend_from_slice(text),
+                Part::Region(index) => {
+                    let Some(region) = self.regions.get(*index) else {
+                        continue;
+                    };
+
+                    let Some(side) = region.choice.or(default) else {
+                        return Err(Error::new(
+                            region.span.clone(),
+                            ErrorKind::UnresolvedConflict,
+                        ));
+                    };
+
+                    region.write(side, &mut out);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// Test if the line is the given marker, returning the label following it.
+fn marker<'a>(line: &'a [u8], marker: &[u8]) -> Option<&'a [u8]> {
+    let rest = line.strip_prefix(marker)?;
+    let rest = rest.strip_suffix(b"\n").unwrap_or(rest);
+    let rest = rest.strip_suffix(b"\r").unwrap_or(rest);
+
+    match rest {
+        [] => Some(rest),
+        [b' ', label @ ..] => Some(label),
+        _ => None,
+    }
+}
+
+/// Iterator over the ranges of lines in the input, including their line
+/// endings.
+struct Lines<'a> {
+    input: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self { input, at: 0 }
+    }
+}
+
+impl Iterator for Lines<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.at >= self.input.len() {
+            return None;
+        }
+
+        let start = self.at;
+
+        let rest = self.input.get(start..).unwrap_or_default();
+
+        self.at = match memchr::memchr(b'\n', rest) {
+            Some(n) => start + n + 1,
+            None => self.input.len(),
+        };
+
+        Some(start..self.at)
+    }
+}
@@ -0,0 +1,308 @@
+use std::iter;
+
+use bstr::ByteSlice;
+
+use crate::yaml::clear;
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw, RawStringKind};
+use crate::yaml::serde_hint::RawNumberHint;
+use crate::yaml::{Block, Chomp, MappingMut, Separator, SequenceMut, ValueMut};
+
+/// An owned copy of a value which is independent of the document it was taken
+/// from, so that it can be written into any document.
+#[derive(Debug, Clone)]
+pub(crate) enum Node {
+    Null(Null),
+    Boolean(bool, Vec<u8>),
+    Number(Vec<u8>, RawNumberHint),
+    String(Str),
+    Sequence(bool, Vec<Node>),
+    Mapping(bool, Vec<(Str, Node)>),
+    Alias(Vec<u8>),
+}
+
+/// A copied null value.
+#[derive(Debug, Clone)]
+pub(crate) enum Null {
+    Keyword(Vec<u8>),
+    Tilde,
+    Empty,
+}
+
+/// A copied string, which remembers how it was quoted.
+#[derive(Debug, Clone)]
+pub(crate) struct Str {
+    kind: RawStringKind,
+    /// The style of the block the string was copied from, if any.
+    block: Option<Block>,
+    string: Vec<u8>,
+    original: Vec<u8>,
+}
+
+impl Str {
+    /// Copy the given string.
+    pub(crate) fn new(data: &Data, string: &raw::String) -> Self {
+        let value = data.str(string.id);
+
+        // Block strings depend on the indentation of where they are used, so
+        // only their style is copied and they are rebuilt when written. The
+        // quoted kind is used where blocks can't be written.
+        let block = raw::block_style(data, string);
+
+        let kind = match string.kind {
+            RawStringKind::Multiline { .. } => RawStringKind::detect(&value.to_str_lossy(), false),
+            _ if block.is_some() => RawStringKind::detect(&value.to_str_lossy(), false),
+            kind => kind,
+        };
+
+        Self {
+            kind,
+            block,
+            string: value.to_vec(),
+            original: data.str(string.original).to_vec(),
+        }
+    }
+
+    /// Copy the text of a complex key, which is quoted in case it can't be
+    /// written as a plain key.
+    fn complex(data: &Data, string: &raw::String) -> Self {
+        let value = data.str(string.id);
+
+        Self {
+            kind: RawStringKind::detect(&value.to_str_lossy(), false),
+            block: None,
+            string: value.to_vec(),
+            original: value.to_vec(),
+        }
+    }
+
+    /// Convert into a raw string, which is written inside of an inline
+    /// collection if `flow` is set.
+    fn to_raw(&self, data: &mut Data, flow: bool) -> raw::String {
+        // Bare strings copied from block collections might have to be quoted
+        // inside of inline collections.
+        let kind = match self.kind {
+            RawStringKind::Bare if flow => RawStringKind::detect(&self.string.to_str_lossy(), true),
+            kind => kind,
+        };
+
+        let string = data.insert_str(&self.string);
+        let original = data.insert_str(&self.original);
+        raw::String::new(kind, string, original)
+    }
+
+    /// Rebuild a copied block string as a block in the same style over the
+    /// value `id`.
+    fn to_block(&self, data: &mut Data, id: Id, block: Block) -> Raw {
+        let string = self.string.to_str_lossy();
+        let trailing = string.bytes().rev().take_while(|&b| b == b'\n').count();
+        let content = &string[..string.len() - trailing];
+
+        // The chomping indicator has to agree with the trailing newlines of
+        // the string, which are written as empty lines when kept.
+        let chomp = |chomp| match trailing {
+            0 => Chomp::Strip,
+            1 if !matches!(chomp, Chomp::Keep) => Chomp::Clip,
+            _ => Chomp::Keep,
+        };
+
+        // Folded blocks can't express line breaks in their content without
+        // empty lines, so they are written as literal blocks instead.
+        let block = match block {
+            Block::Folded(c) if !content.contains('\n') => Block::Folded(chomp(c)),
+            Block::Folded(c) | Block::Literal(c) => Block::Literal(chomp(c)),
+        };
+
+        let empty = iter::repeat("").take(trailing.saturating_sub(1));
+        let lines = content.split('\n').chain(empty).collect::<Vec<_>>();
+
+        // Blocks inside of collections are indented relative to the
+        // collection, the same as when they are inserted.
+        let collection = data
+            .layout(id)
+            .parent
+            .and_then(|item| data.layout(item).parent);
+
+        match collection {
+            Some(collection) => raw::new_block(data, collection, lines, block, None, true),
+            None => {
+                data.replace(id, Raw::Null(raw::Null::Empty));
+                raw::new_block(data, id, lines, block, None, false)
+            }
+        }
+    }
+}
+
+/// Take an owned copy of the value `id`.
+pub(crate) fn snapshot(data: &Data, id: Id) -> Node {
+    match data.raw(id) {
+        Raw::Null(raw::Null::Keyword(string)) => {
+            Node::Null(Null::Keyword(data.str(*string).to_vec()))
+        }
+        Raw::Null(raw::Null::Tilde) => Node::Null(Null::Tilde),
+        Raw::Null(raw::Null::Empty) => Node::Null(Null::Empty),
+        Raw::Boolean(raw) => Node::Boolean(raw.value, data.str(raw.string).to_vec()),
+        Raw::Number(raw) => Node::Number(data.str(raw.string).to_vec(), raw.hint),
+        Raw::String(raw) => Node::String(Str::new(data, raw)),
+        Raw::Sequence(raw) => Node::Sequence(
+            matches!(raw.kind, raw::SequenceKind::Inline { .. }),
+            raw.items
+                .iter()
+                .map(|item| snapshot(data, data.sequence_item(*item).value))
+                .collect(),
+        ),
+        Raw::Mapping(raw) => Node::Mapping(
+            matches!(raw.kind, raw::MappingKind::Inline { .. }),
+            raw.items
+                .iter()
+                .map(|item| {
+                    let item = data.mapping_item(*item);
+
+                    let key = match item.kind {
+                        raw::MappingItemKind::Complex { .. } => Str::complex(data, &item.key),
+                        _ => Str::new(data, &item.key),
+                    };
+
+                    (key, snapshot(data, item.value))
+                })
+                .collect(),
+        ),
+        Raw::SequenceItem(raw) => snapshot(data, raw.value),
+        Raw::MappingItem(raw) => snapshot(data, raw.value),
+        Raw::Alias(raw) => Node::Alias(data.str(raw.name).to_vec()),
+    }
+}
+
+/// Write a copied value over the value `id`.
+///
+/// Collections are written in the same style as they were copied, except that
+/// block collections are written inline when `id` is inside of an inline
+/// collection.
+pub(crate) fn write(data: &mut Data, id: Id, node: &Node) {
+    // A value replacing a block collection starts on the same line as its key
+    // or dash, unless it's a block collection itself.
+    if data.raw(id).is_tabular()
+        && data.layout(id).parent.is_some()
+        && !matches!(node, Node::Sequence(false, _) | Node::Mapping(false, _))
+    {
+        let prefix = data.insert_str(" ");
+        data.set_prefix(id, prefix);
+    }
+
+    match node {
+        Node::Null(null) => {
+            let null = match null {
+                Null::Keyword(string) => raw::Null::Keyword(data.insert_str(string)),
+                Null::Tilde => raw::Null::Tilde,
+                Null::Empty => raw::Null::Empty,
+            };
+
+            data.replace(id, Raw::Null(null));
+        }
+        Node::Boolean(value, string) => {
+            let string = data.insert_str(string);
+            data.replace(id, Raw::Boolean(raw::Boolean::new(*value, string)));
+        }
+        Node::Number(string, hint) => {
+            let string = data.insert_str(string);
+            data.replace(id, Raw::Number(raw::Number::new(string, *hint)));
+        }
+        Node::String(string) => {
+            let flow = clear::in_flow(data, id);
+            let tagged = data.layout(id).tag.is_some();
+
+            let value = match string.block {
+                Some(block) if !flow && !tagged && !string.string.is_empty() => {
+                    string.to_block(data, id, block)
+                }
+                _ => Raw::String(string.to_raw(data, flow)),
+            };
+
+            data.replace(id, value);
+        }
+        Node::Sequence(inline, items) => {
+            data.replace(id, Raw::Null(raw::Null::Empty));
+
+            if *inline || clear::in_flow(data, id) {
+                let (indent, _) = raw::make_indent(data, id, 0);
+                let suffix = data.insert_str("");
+
+                data.replace(
+                    id,
+                    Raw::Sequence(raw::Sequence {
+                        indent,
+                        kind: raw::SequenceKind::Inline {
+                            trailing: false,
+                            suffix,
+                        },
+                        items: Vec::new(),
+                        tag: None,
+                    }),
+                );
+            } else {
+                let _ = ValueMut::new(data, id).make_sequence();
+            }
+
+            for item in items {
+                let value = SequenceMut::new(data, id).push(Separator::Auto).id;
+                write(data, value, item);
+            }
+        }
+        Node::Mapping(inline, items) => {
+            data.replace(id, Raw::Null(raw::Null::Empty));
+
+            if *inline || clear::in_flow(data, id) {
+                let (indent, _) = raw::make_indent(data, id, 0);
+                let suffix = data.insert_str("");
+
+                data.replace(
+                    id,
+                    Raw::Mapping(raw::Mapping {
+                        indent,
+                        kind: raw::MappingKind::Inline {
+                            trailing: false,
+                            suffix,
+                        },
+                        items: Vec::new(),
+                        tag: None,
+                    }),
+                );
+            } else {
+                let _ = ValueMut::new(data, id).make_mapping();
+            }
+
+            for (key, value) in items {
+                insert(data, id, key, value);
+            }
+        }
+        Node::Alias(name) => {
+            let name = data.insert_str(name);
+            data.replace(id, Raw::Alias(raw::Alias::new(name)));
+        }
+    }
+}
+
+/// Insert a copied value under a copied key into the mapping `id`, returning
+/// the identifier of the inserted value or `None` if the key has a value which
+/// is locked.
+pub(crate) fn insert(data: &mut Data, id: Id, key: &Str, node: &Node) -> Option<Id> {
+    let child = MappingMut::new(data, id)
+        .insert(&key.string, Separator::Auto)
+        .ok()?
+        .id;
+
+    let item = data
+        .mapping(id)
+        .items
+        .iter()
+        .copied()
+        .find(|&item| data.mapping_item(item).value == child);
+
+    if let Some(item) = item {
+        let key = key.to_raw(data, raw::is_inline(data, id));
+        data.mapping_item_mut(item).key = key;
+    }
+
+    write(data, child, node);
+    Some(child)
+}
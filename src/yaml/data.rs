@@ -6,10 +6,22 @@ use std::num::NonZeroUsize;
 
 use bstr::BStr;
 #[cfg(feature = "serde-edits")]
-use serde::{Deserialize, Serialize};
+use serde::de::Error as _;
+#[cfg(feature = "serde-edits")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use twox_hash::xxh3::{Hash128, HasherExt};
 
+use crate::yaml::copy;
+use crate::yaml::lock::Actor;
+use crate::yaml::metadata::Tables;
+use crate::yaml::parsing::Options;
+use crate::yaml::placeholder::Placeholder;
+use crate::yaml::quoting::Quoting;
 use crate::yaml::raw;
+#[cfg(feature = "serde-edits")]
+use crate::yaml::session::{Compression, Packed};
 
 /// The unique hash of a string.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -63,6 +75,10 @@ impl fmt::Debug for Hex<'_> {
 /// [`Document`]: crate::yaml::Document
 /// [`Document::value`]: crate::yaml::Document::value
 /// [`Document::value_mut`]: crate::yaml::Document::value_mut
+///
+/// The layout of an identifier is not part of the public API. To store an
+/// identifier outside of the document it belongs to, such as in an external
+/// index, convert it with [`Id::to_u64`] and back again with [`Id::from_u64`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde-edits", serde(transparent))]
@@ -70,10 +86,58 @@ impl fmt::Debug for Hex<'_> {
 pub struct Id(NonZeroUsize);
 
 impl Id {
+    /// Construct a new identifier from a slab index.
+    #[inline]
+    fn new(index: usize) -> Self {
+        let index = NonZeroUsize::new(index.wrapping_add(1)).expect("ran out of ids");
+        Self(index)
+    }
+
     #[inline]
     fn get(self) -> usize {
         self.0.get().wrapping_sub(1)
     }
+
+    /// Convert the identifier into a `u64`, suitable for persisting it.
+    ///
+    /// The returned number can be converted back into an identifier with
+    /// [`Id::from_u64`]. Parsing the same input with the same version of this
+    /// crate assigns the same identifiers, but they may change between
+    /// versions, so persisted identifiers should be paired with a persisted
+    /// document, such as a session saved with the `serde-edits` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("{name: app, replicas: 3}")?;
+    ///
+    /// let id = doc.as_ref().as_mapping().and_then(|m| m.get("replicas")).context("missing replicas")?.id();
+    /// let stored = id.to_u64();
+    ///
+    /// let id = yaml::Id::from_u64(stored).context("bad id")?;
+    /// assert_eq!(doc.value(id).as_u32(), Some(3));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_u64(self) -> u64 {
+        self.get() as u64
+    }
+
+    /// Convert a number produced by [`Id::to_u64`] back into an identifier.
+    ///
+    /// Returns `None` if the number can't possibly be an identifier on this
+    /// platform. Note that the identifier is not checked against any document,
+    /// use [`Document::contains`] to test that it's present before using it.
+    ///
+    /// [`Document::contains`]: crate::yaml::Document::contains
+    #[must_use]
+    pub fn from_u64(value: u64) -> Option<Self> {
+        let index = usize::try_from(value).ok()?;
+        Some(Self(NonZeroUsize::new(index.checked_add(1)?)?))
+    }
 }
 
 impl fmt::Display for Id {
@@ -82,6 +146,23 @@ impl fmt::Display for Id {
     }
 }
 
+/// Raise a panic for an identifier which doesn't refer to the expected kind of
+/// entry.
+///
+/// This is the only way the crate panics, which happens if an [`Id`] is used
+/// with a document it doesn't belong to, or after the value it refers to has
+/// been removed. Any such path in the public API is documented under a
+/// `# Panics` section, and [`Document::contains`] can be used to guard
+/// against it.
+///
+/// [`Document::contains`]: crate::yaml::Document::contains
+#[cold]
+#[track_caller]
+#[allow(clippy::panic)]
+fn invalid(what: &str, id: impl fmt::Display) -> ! {
+    panic!("expected {what} at {id}")
+}
+
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub(crate) struct Entry {
@@ -90,11 +171,136 @@ pub(crate) struct Entry {
 }
 
 /// Strings cache.
+///
+/// With the `serde-edits` feature, only the strings, the slab and the default
+/// separator are serialized. Strings are compressed according to
+/// [`Compression`], and are checked against their identifiers when
+/// deserialized.
 #[derive(Clone, Default)]
-#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub(crate) struct Data {
     strings: HashMap<StringId, Box<[u8]>>,
     slab: slab::Slab<Entry>,
+    /// The default separator used by `Separator::Auto` for scalars.
+    separator: Option<StringId>,
+    /// Prefixes of items inserted into block collections, by indentation.
+    prefixes: HashMap<usize, StringId>,
+    /// Whether synthesized line breaks are written as `\r\n`, which is the
+    /// case for documents which predominantly use them.
+    crlf: bool,
+    /// Incremented whenever mappings or their items might have been
+    /// modified, which invalidates `keys`.
+    generation: u64,
+    /// The keys of the mapping which was most recently inserted into.
+    keys: Option<Keys>,
+    /// The policy used to quote inserted strings.
+    quoting: Quoting,
+    /// The configuration the document was parsed with, which is used when
+    /// parts of it are parsed again.
+    options: Options,
+    /// The style of placeholder scalars.
+    placeholder: Placeholder,
+    /// Metadata associated with values.
+    metadata: Tables,
+    /// Who is editing the document, used to check locks.
+    actor: Actor,
+    /// How strings are compressed when serialized.
+    #[cfg(feature = "serde-edits")]
+    compression: Compression,
+}
+
+/// A cache of the keys of a single mapping, which avoids scanning every item
+/// of the mapping when inserting many keys into it.
+#[derive(Clone)]
+struct Keys {
+    /// The mapping the keys belong to.
+    mapping: Id,
+    /// The generation of the data the cache is valid for.
+    generation: u64,
+    /// The value of the first item with each key.
+    values: HashMap<StringId, Id>,
+}
+
+/// Mappings smaller than this are scanned instead of having their keys cached.
+const KEYS_THRESHOLD: usize = 16;
+
+#[cfg(feature = "serde-edits")]
+impl Serialize for Data {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Data", 4)?;
+
+        match self.compression {
+            Compression::None => {
+                s.serialize_field("strings", &self.strings)?;
+                s.skip_field("packed")?;
+            }
+            Compression::Deflate => {
+                s.skip_field("strings")?;
+                s.serialize_field("packed", &Packed::new(self.strings.values()))?;
+            }
+        }
+
+        s.serialize_field("slab", &self.slab)?;
+        s.serialize_field("separator", &self.separator)?;
+
+        if self.crlf {
+            s.serialize_field("crlf", &self.crlf)?;
+        } else {
+            s.skip_field("crlf")?;
+        }
+
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde-edits")]
+impl<'de> Deserialize<'de> for Data {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Repr {
+            #[serde(default)]
+            strings: HashMap<StringId, Box<[u8]>>,
+            #[serde(default)]
+            packed: Option<Packed>,
+            slab: slab::Slab<Entry>,
+            #[serde(default)]
+            separator: Option<StringId>,
+            #[serde(default)]
+            crlf: bool,
+        }
+
+        let repr = Repr::deserialize(deserializer)?;
+
+        let mut data = Data {
+            slab: repr.slab,
+            separator: repr.separator,
+            crlf: repr.crlf,
+            ..Data::default()
+        };
+
+        for (id, string) in repr.strings {
+            if data.insert_str(string) != id {
+                return Err(D::Error::custom(format_args!(
+                    "string {id} doesn't match its contents"
+                )));
+            }
+        }
+
+        if let Some(packed) = repr.packed {
+            for string in packed.unpack().map_err(D::Error::custom)? {
+                data.insert_str(string);
+            }
+
+            data.compression = Compression::Deflate;
+        }
+
+        Ok(data)
+    }
 }
 
 impl Data {
@@ -103,7 +309,7 @@ impl Data {
     #[must_use]
     pub(crate) fn str(&self, id: StringId) -> &BStr {
         let Some(string) = self.strings.get(&id) else {
-            panic!("missing string with id {id}");
+            invalid("string", id);
         };
 
         BStr::new(string.as_ref())
@@ -127,13 +333,215 @@ impl Data {
         id
     }
 
+    /// Get the line break used when synthesizing whitespace.
+    pub(crate) fn newline(&self) -> &'static [u8] {
+        if self.crlf {
+            raw::CRLF
+        } else {
+            &[raw::NEWLINE]
+        }
+    }
+
+    /// Set whether synthesized line breaks are written as `\r\n`.
+    pub(crate) fn set_crlf(&mut self, crlf: bool) {
+        if self.crlf != crlf {
+            self.crlf = crlf;
+            self.prefixes.clear();
+        }
+    }
+
+    /// Get the prefix of an item inserted into a block collection with the
+    /// given indentation, which is a line break followed by `indent` spaces.
+    ///
+    /// Prefixes are cached, so that inserting many items into the same
+    /// collection doesn't construct and hash the same prefix for each of them.
+    pub(crate) fn block_prefix(&mut self, indent: usize) -> StringId {
+        if let Some(&id) = self.prefixes.get(&indent) {
+            return id;
+        }
+
+        let newline = self.newline();
+        let mut out = Vec::with_capacity(indent.saturating_add(newline.len()));
+        out.extend_from_slice(newline);
+        out.resize(indent.saturating_add(newline.len()), raw::SPACE);
+
+        let id = self.insert_str(out);
+        self.prefixes.insert(indent, id);
+        id
+    }
+
+    /// Find the value of the first item in `mapping` with the given `key`.
+    ///
+    /// Also returns a token which can be passed to [`Data::push_key`] once an
+    /// item with the key has been pushed to the mapping, which keeps the keys
+    /// of the mapping cached.
+    pub(crate) fn find_key(&mut self, mapping: Id, key: StringId) -> (Option<Id>, u64) {
+        let items = &self.mapping(mapping).items;
+
+        if items.len() < KEYS_THRESHOLD {
+            let value = items
+                .iter()
+                .map(|id| self.mapping_item(*id))
+                .find(|item| item.key.id == key)
+                .map(|item| item.value);
+
+            return (value, self.generation);
+        }
+
+        let valid = matches!(
+            &self.keys,
+            Some(keys) if keys.mapping == mapping && keys.generation == self.generation
+        );
+
+        if !valid {
+            let mut values = HashMap::with_capacity(items.len());
+
+            for id in items {
+                let item = self.mapping_item(*id);
+                values.entry(item.key.id).or_insert(item.value);
+            }
+
+            self.keys = Some(Keys {
+                mapping,
+                generation: self.generation,
+                values,
+            });
+        }
+
+        let value = self
+            .keys
+            .as_ref()
+            .and_then(|keys| keys.values.get(&key).copied());
+
+        (value, self.generation)
+    }
+
+    /// Record that an item with `key` and `value` has been pushed to
+    /// `mapping`, where `token` was returned by [`Data::find_key`].
+    ///
+    /// The only modifications made since the token was returned must be the
+    /// ones which pushed the item.
+    pub(crate) fn push_key(&mut self, mapping: Id, token: u64, key: StringId, value: Id) {
+        let Some(keys) = &mut self.keys else {
+            return;
+        };
+
+        if keys.mapping != mapping || keys.generation != token {
+            return;
+        }
+
+        keys.values.entry(key).or_insert(value);
+        keys.generation = self.generation;
+    }
+
+    /// Get the metadata associated with values.
+    #[inline]
+    pub(crate) fn metadata(&self) -> &Tables {
+        &self.metadata
+    }
+
+    /// Get the metadata associated with values mutably.
+    #[inline]
+    pub(crate) fn metadata_mut(&mut self) -> &mut Tables {
+        &mut self.metadata
+    }
+
+    /// Get who is editing the document.
+    #[inline]
+    pub(crate) fn actor(&self) -> &Actor {
+        &self.actor
+    }
+
+    /// Get who is editing the document mutably.
+    #[inline]
+    pub(crate) fn actor_mut(&mut self) -> &mut Actor {
+        &mut self.actor
+    }
+
+    /// Get the default separator used for scalars.
+    #[inline]
+    pub(crate) fn separator(&self) -> Option<StringId> {
+        self.separator
+    }
+
+    /// Set the default separator used for scalars.
+    #[inline]
+    pub(crate) fn set_separator(&mut self, separator: Option<StringId>) {
+        self.separator = separator;
+    }
+
+    /// Get how strings are compressed when serialized.
+    #[cfg(feature = "serde-edits")]
+    #[inline]
+    pub(crate) fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Set how strings are compressed when serialized.
+    #[cfg(feature = "serde-edits")]
+    #[inline]
+    pub(crate) fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Test if the given string is present.
+    #[cfg(feature = "serde-edits")]
+    #[inline]
+    pub(crate) fn contains_str(&self, id: StringId) -> bool {
+        self.strings.contains_key(&id)
+    }
+
+    /// Get the raw value and layout of the given identifier, if it's present.
+    #[cfg(feature = "serde-edits")]
+    #[inline]
+    pub(crate) fn try_entry(&self, id: Id) -> Option<(&raw::Raw, &raw::Layout)> {
+        let entry = self.slab.get(id.get())?;
+        Some((&entry.raw, &entry.layout))
+    }
+
+    /// Get the policy used to quote inserted strings.
+    #[inline]
+    pub(crate) fn quoting(&self) -> &Quoting {
+        &self.quoting
+    }
+
+    /// Set the policy used to quote inserted strings.
+    #[inline]
+    pub(crate) fn set_quoting(&mut self, quoting: Quoting) {
+        self.quoting = quoting;
+    }
+
+    /// Get the configuration the document was parsed with.
+    #[inline]
+    pub(crate) fn options(&self) -> Options {
+        self.options
+    }
+
+    /// Set the configuration the document was parsed with.
+    #[inline]
+    pub(crate) fn set_options(&mut self, options: Options) {
+        self.options = options;
+    }
+
+    /// Get the style of placeholder scalars.
+    #[inline]
+    pub(crate) fn placeholder(&self) -> &Placeholder {
+        &self.placeholder
+    }
+
+    /// Set the style of placeholder scalars.
+    #[inline]
+    pub(crate) fn set_placeholder(&mut self, placeholder: Placeholder) {
+        self.placeholder = placeholder;
+    }
+
     #[inline]
     pub(crate) fn layout(&self, id: Id) -> &raw::Layout {
         if let Some(raw) = self.slab.get(id.get()) {
             return &raw.layout;
         }
 
-        panic!("expected layout at {id}")
+        invalid("layout", id)
     }
 
     #[inline]
@@ -141,13 +549,37 @@ impl Data {
         self.str(self.layout(id).prefix)
     }
 
+    /// Set the prefix of a value.
+    #[inline]
+    pub(crate) fn set_prefix(&mut self, id: Id, prefix: StringId) {
+        if let Some(raw) = self.slab.get_mut(id.get()) {
+            raw.layout.prefix = prefix;
+        }
+    }
+
+    /// Set the tag of a value which is not a collection.
+    #[inline]
+    pub(crate) fn set_tag(&mut self, id: Id, tag: Option<raw::Tag>) {
+        if let Some(raw) = self.slab.get_mut(id.get()) {
+            raw.layout.tag = tag;
+        }
+    }
+
+    /// Set the anchor of a value.
+    #[inline]
+    pub(crate) fn set_anchor(&mut self, id: Id, anchor: Option<raw::Anchor>) {
+        if let Some(raw) = self.slab.get_mut(id.get()) {
+            raw.layout.anchor = anchor;
+        }
+    }
+
     #[inline]
     pub(crate) fn pair(&self, id: Id) -> (&raw::Raw, &raw::Layout) {
         if let Some(raw) = self.slab.get(id.get()) {
             return (&raw.raw, &raw.layout);
         }
 
-        panic!("expected raw at {id}")
+        invalid("raw", id)
     }
 
     #[inline]
@@ -156,16 +588,18 @@ impl Data {
             return &raw.raw;
         }
 
-        panic!("expected raw at {id}")
+        invalid("raw", id)
     }
 
     #[inline]
     pub(crate) fn raw_mut(&mut self, id: Id) -> &mut raw::Raw {
+        self.generation = self.generation.wrapping_add(1);
+
         if let Some(raw) = self.slab.get_mut(id.get()) {
             return &mut raw.raw;
         }
 
-        panic!("expected raw at {id}")
+        invalid("raw", id)
     }
 
     #[inline]
@@ -178,7 +612,7 @@ impl Data {
             return raw;
         }
 
-        panic!("expected sequence at {id}")
+        invalid("sequence", id)
     }
 
     #[inline]
@@ -191,7 +625,7 @@ impl Data {
             return raw;
         }
 
-        panic!("expected sequence at {id}")
+        invalid("sequence", id)
     }
 
     #[inline]
@@ -204,7 +638,7 @@ impl Data {
             return raw;
         }
 
-        panic!("expected mapping at {id}")
+        invalid("mapping", id)
     }
 
     #[inline]
@@ -217,7 +651,20 @@ impl Data {
             return raw;
         }
 
-        panic!("expected sequence item at {id}")
+        invalid("sequence item", id)
+    }
+
+    #[inline]
+    pub(crate) fn sequence_item_mut(&mut self, id: Id) -> &mut raw::SequenceItem {
+        if let Some(Entry {
+            raw: raw::Raw::SequenceItem(raw),
+            ..
+        }) = self.slab.get_mut(id.get())
+        {
+            return raw;
+        }
+
+        invalid("sequence item", id)
     }
 
     #[inline]
@@ -230,11 +677,28 @@ impl Data {
             return raw;
         }
 
-        panic!("expected mapping item at {id}")
+        invalid("mapping item", id)
+    }
+
+    #[inline]
+    pub(crate) fn mapping_item_mut(&mut self, id: Id) -> &mut raw::MappingItem {
+        self.generation = self.generation.wrapping_add(1);
+
+        if let Some(Entry {
+            raw: raw::Raw::MappingItem(raw),
+            ..
+        }) = self.slab.get_mut(id.get())
+        {
+            return raw;
+        }
+
+        invalid("mapping item", id)
     }
 
     #[inline]
     pub(crate) fn mapping_mut(&mut self, id: Id) -> &mut raw::Mapping {
+        self.generation = self.generation.wrapping_add(1);
+
         if let Some(Entry {
             raw: raw::Raw::Mapping(raw),
             ..
@@ -243,7 +707,7 @@ impl Data {
             return raw;
         }
 
-        panic!("expected mapping at {id}")
+        invalid("mapping", id)
     }
 
     /// Insert a raw value and return its identifier.
@@ -251,10 +715,20 @@ impl Data {
     pub(crate) fn insert(&mut self, raw: raw::Raw, prefix: StringId, parent: Option<Id>) -> Id {
         let index = self.slab.insert(Entry {
             raw,
-            layout: raw::Layout { prefix, parent },
+            layout: raw::Layout {
+                prefix,
+                parent,
+                anchor: None,
+                tag: None,
+            },
         });
-        let index = NonZeroUsize::new(index.wrapping_add(1)).expect("ran out of ids");
-        Id(index)
+        Id::new(index)
+    }
+
+    /// Test if the given identifier refers to a value.
+    #[inline]
+    pub(crate) fn contains(&self, id: Id) -> bool {
+        self.slab.contains(id.get())
     }
 
     /// Drop a value recursively.
@@ -264,12 +738,17 @@ impl Data {
             return;
         };
 
+        self.metadata.remove(id);
         self.drop_kind(value.raw);
     }
 
     /// Drop a raw value recursively.
     #[inline]
     pub(crate) fn drop_kind(&mut self, raw: raw::Raw) {
+        if let raw::Raw::Mapping(..) | raw::Raw::MappingItem(..) = raw {
+            self.generation = self.generation.wrapping_add(1);
+        }
+
         match raw {
             raw::Raw::Mapping(raw) => {
                 for item in raw.items {
@@ -277,7 +756,12 @@ impl Data {
                 }
             }
             raw::Raw::MappingItem(raw) => {
+                if let raw::MappingItemKind::Complex { key, .. } = raw.kind {
+                    self.drop(key);
+                }
+
                 let item = self.slab.remove(raw.value.get());
+                self.metadata.remove(raw.value);
                 self.drop_kind(item.raw);
             }
             raw::Raw::Sequence(raw) => {
@@ -287,6 +771,7 @@ impl Data {
             }
             raw::Raw::SequenceItem(raw) => {
                 let item = self.slab.remove(raw.value.get());
+                self.metadata.remove(raw.value);
                 self.drop_kind(item.raw);
             }
             _ => {}
@@ -298,16 +783,26 @@ impl Data {
     where
         T: Into<raw::Raw>,
     {
+        let raw = raw.into();
+
+        if let raw::Raw::Mapping(..) | raw::Raw::MappingItem(..) = raw {
+            self.generation = self.generation.wrapping_add(1);
+        }
+
         let Some(value) = self.slab.get_mut(id.get()) else {
             return;
         };
 
-        let removed = mem::replace(&mut value.raw, raw.into());
+        let removed = mem::replace(&mut value.raw, raw);
         self.drop_kind(removed);
     }
 
     /// Replace with indentation.
     pub(crate) fn replace_with(&mut self, id: Id, prefix: StringId, raw: raw::Raw) {
+        if let raw::Raw::Mapping(..) | raw::Raw::MappingItem(..) = raw {
+            self.generation = self.generation.wrapping_add(1);
+        }
+
         let Some(value) = self.slab.get_mut(id.get()) else {
             return;
         };
@@ -316,4 +811,156 @@ impl Data {
         let removed = mem::replace(&mut value.raw, raw);
         self.drop_kind(removed);
     }
+
+    /// Swap the values `a` and `b`, which must not contain each other.
+    ///
+    /// Values which are written the same regardless of where they are placed
+    /// are moved, so that their contents keep their identifiers. Other values
+    /// are copied. Either way, each value keeps its prefix.
+    pub(crate) fn swap(&mut self, a: Id, b: Id) {
+        self.generation = self.generation.wrapping_add(1);
+
+        if self.is_portable(a) && self.is_portable(b) {
+            if let Some((a, b)) = self.slab.get2_mut(a.get(), b.get()) {
+                mem::swap(&mut a.raw, &mut b.raw);
+            }
+
+            self.adopt(a);
+            self.adopt(b);
+        } else {
+            let first = copy::snapshot(self, a);
+            let second = copy::snapshot(self, b);
+            copy::write(self, a, &second);
+            copy::write(self, b, &first);
+        }
+
+        self.separate(a);
+        self.separate(b);
+    }
+
+    /// Copy the value `from` over the value `to`, keeping the prefix of `to`.
+    pub(crate) fn copy(&mut self, from: Id, to: Id) {
+        if self.is_portable(from) && !self.raw(to).is_tabular() {
+            let raw = self.clone_raw(from, to);
+            self.replace(to, raw);
+        } else {
+            let node = copy::snapshot(self, from);
+            copy::write(self, to, &node);
+        }
+
+        self.separate(to);
+    }
+
+    /// Test if the value `id` is written the same regardless of where it's
+    /// placed in the document, which is the case for values which fit on a
+    /// single line.
+    fn is_portable(&self, id: Id) -> bool {
+        let line = |string: StringId| !self.str(string).contains(&b'\n');
+        let tag = |tag: &Option<raw::Tag>| tag.as_ref().map_or(true, |tag| line(tag.suffix));
+        let decorations = |id: Id| {
+            let layout = self.layout(id);
+            layout.anchor.map_or(true, |a| line(a.suffix)) && tag(&layout.tag)
+        };
+        let entry =
+            |id: Id| line(self.layout(id).prefix) && decorations(id) && self.is_portable(id);
+
+        match self.raw(id) {
+            raw::Raw::String(raw) => {
+                !matches!(raw.kind, raw::RawStringKind::Multiline { .. }) && line(raw.original)
+            }
+            raw::Raw::Mapping(raw) => match raw.kind {
+                raw::MappingKind::Inline { suffix, .. } => {
+                    line(suffix) && tag(&raw.tag) && raw.items.iter().all(|item| entry(*item))
+                }
+                raw::MappingKind::Mapping => false,
+            },
+            raw::Raw::Sequence(raw) => match raw.kind {
+                raw::SequenceKind::Inline { suffix, .. } => {
+                    line(suffix) && tag(&raw.tag) && raw.items.iter().all(|item| entry(*item))
+                }
+                raw::SequenceKind::Mapping => false,
+            },
+            raw::Raw::MappingItem(raw) => {
+                line(raw.key.original)
+                    && raw.suffix.map_or(true, line)
+                    && !matches!(
+                        raw.kind,
+                        raw::MappingItemKind::Explicit { .. }
+                            | raw::MappingItemKind::Complex { .. }
+                    )
+                    && entry(raw.value)
+            }
+            raw::Raw::SequenceItem(raw) => raw.suffix.map_or(true, line) && entry(raw.value),
+            _ => true,
+        }
+    }
+
+    /// Deep clone the value `id` into a raw value to be stored at `at`.
+    fn clone_raw(&mut self, id: Id, at: Id) -> raw::Raw {
+        let mut raw = self.raw(id).clone();
+
+        match &mut raw {
+            raw::Raw::Mapping(raw::Mapping { items, .. })
+            | raw::Raw::Sequence(raw::Sequence { items, .. }) => {
+                for item in items {
+                    *item = self.clone_entry(*item, at);
+                }
+            }
+            raw::Raw::MappingItem(raw::MappingItem { value, kind, .. }) => {
+                if let raw::MappingItemKind::Complex { key, .. } = kind {
+                    *key = self.clone_entry(*key, at);
+                }
+
+                *value = self.clone_entry(*value, at);
+            }
+            raw::Raw::SequenceItem(raw::SequenceItem { value, .. }) => {
+                *value = self.clone_entry(*value, at);
+            }
+            _ => {}
+        }
+
+        raw
+    }
+
+    /// Deep clone the entry `id` into a new entry with the given parent.
+    fn clone_entry(&mut self, id: Id, parent: Id) -> Id {
+        let prefix = self.layout(id).prefix;
+        let new = self.insert(raw::Raw::Null(raw::Null::Empty), prefix, Some(parent));
+        let raw = self.clone_raw(id, new);
+        self.replace(new, raw);
+        new
+    }
+
+    /// Point the items of the collection `id` back at it after it's been
+    /// moved.
+    fn adopt(&mut self, id: Id) {
+        let items = match self.raw(id) {
+            raw::Raw::Mapping(raw) => raw.items.clone(),
+            raw::Raw::Sequence(raw) => raw.items.clone(),
+            _ => return,
+        };
+
+        for item in items {
+            if let Some(entry) = self.slab.get_mut(item.get()) {
+                entry.layout.parent = Some(id);
+            }
+        }
+    }
+
+    /// Separate the value `id` from the `:` of its mapping item in case it
+    /// doesn't have a prefix, such as when it replaced an empty value.
+    fn separate(&mut self, id: Id) {
+        if !self.prefix(id).is_empty() || matches!(self.raw(id), raw::Raw::Null(raw::Null::Empty)) {
+            return;
+        }
+
+        let Some(parent) = self.layout(id).parent else {
+            return;
+        };
+
+        if matches!(self.raw(parent), raw::Raw::MappingItem(..)) {
+            let prefix = self.insert_str(" ");
+            self.set_prefix(id, prefix);
+        }
+    }
 }
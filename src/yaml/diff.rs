@@ -0,0 +1,255 @@
+//! Line-based diffs between two versions of a document.
+
+use core::ops::Range;
+use std::io::Write;
+
+use bstr::ByteSlice;
+
+/// Number of unchanged lines to show around each change.
+const CONTEXT: usize = 3;
+
+/// An operation in a line diff.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// A group of changed lines together with the unchanged lines surrounding
+/// them.
+pub(crate) struct Hunk<'a> {
+    /// The range of lines in the original input covered by the hunk.
+    pub(crate) a: Range<usize>,
+    /// The range of lines in the modified input covered by the hunk.
+    pub(crate) b: Range<usize>,
+    /// The lines of the hunk. Lines include their line endings, if any.
+    pub(crate) lines: Vec<(Op, &'a [u8])>,
+}
+
+impl Hunk<'_> {
+    /// The one-based line where the hunk starts in the original input. By
+    /// convention, an empty hunk refers to the line preceding it.
+    pub(crate) fn a_start(&self) -> usize {
+        self.a.start + usize::from(!self.a.is_empty())
+    }
+
+    /// The one-based line where the hunk starts in the modified input.
+    pub(crate) fn b_start(&self) -> usize {
+        self.b.start + usize::from(!self.b.is_empty())
+    }
+}
+
+/// Compute the hunks which differ between `a` and `b`, line by line.
+///
+/// Lines are compared including their line endings, so a missing trailing
+/// newline or a `\r\n` which has turned into `\n` counts as a changed line.
+pub(crate) fn hunks<'a>(a: &'a [u8], b: &'a [u8]) -> Vec<Hunk<'a>> {
+    let a = a.lines_with_terminator().collect::<Vec<_>>();
+    let b = b.lines_with_terminator().collect::<Vec<_>>();
+    let ops = ops(&a, &b);
+
+    // Line numbers in `a` and `b` at the start of each op.
+    let mut starts = Vec::with_capacity(ops.len() + 1);
+    let (mut i, mut j) = (0, 0);
+
+    for &op in &ops {
+        starts.push((i, j));
+
+        match op {
+            Op::Keep => {
+                i += 1;
+                j += 1;
+            }
+            Op::Remove => i += 1,
+            Op::Add => j += 1,
+        }
+    }
+
+    starts.push((i, j));
+
+    let mut hunks = Vec::new();
+    let mut n = 0;
+
+    while let Some(first) = ops
+        .get(n..)
+        .and_then(|ops| ops.iter().position(|op| *op != Op::Keep))
+    {
+        let start = (n + first).saturating_sub(CONTEXT);
+        let mut end = n + first;
+
+        // Extend the hunk for as long as the next change is close enough that
+        // the context would overlap.
+        loop {
+            while ops.get(end).is_some_and(|op| *op != Op::Keep) {
+                end += 1;
+            }
+
+            let keep = ops.get(end..).unwrap_or_default();
+            let keep = keep.iter().take_while(|op| **op == Op::Keep).count();
+
+            if end + keep < ops.len() && keep <= CONTEXT * 2 {
+                end += keep;
+                continue;
+            }
+
+            end = (end + keep.min(CONTEXT)).min(ops.len());
+            break;
+        }
+
+        let (a_start, b_start) = starts.get(start).copied().unwrap_or_default();
+        let (a_end, b_end) = starts.get(end).copied().unwrap_or_default();
+
+        let lines = ops
+            .get(start..end)
+            .unwrap_or_default()
+            .iter()
+            .zip(starts.get(start..end).unwrap_or_default())
+            .filter_map(|(&op, &(i, j))| match op {
+                Op::Keep | Op::Remove => Some((op, *a.get(i)?)),
+                Op::Add => Some((op, *b.get(j)?)),
+            })
+            .collect();
+
+        hunks.push(Hunk {
+            a: a_start..a_end,
+            b: b_start..b_end,
+            lines,
+        });
+
+        n = end;
+    }
+
+    hunks
+}
+
+/// Render the difference between `original` and `modified` as a unified
+/// diff, or an empty string if they are the same.
+pub(crate) fn unified(original: &[u8], modified: &[u8]) -> String {
+    let hunks = hunks(original, modified);
+
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"--- original\n+++ modified\n");
+
+    for hunk in &hunks {
+        out.extend_from_slice(b"@@ -");
+        range_to(&mut out, hunk.a_start(), hunk.a.len());
+        out.extend_from_slice(b" +");
+        range_to(&mut out, hunk.b_start(), hunk.b.len());
+        out.extend_from_slice(b" @@\n");
+
+        for &(op, line) in &hunk.lines {
+            out.push(match op {
+                Op::Keep => b' ',
+                Op::Remove => b'-',
+                Op::Add => b'+',
+            });
+
+            out.extend_from_slice(line);
+
+            if !line.ends_with(b"\n") {
+                out.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Write a range of lines in a hunk header, leaving out the length if it's
+/// a single line.
+fn range_to(out: &mut Vec<u8>, start: usize, len: usize) {
+    // Writing to a vector never fails.
+    _ = if len == 1 {
+        write!(out, "{start}")
+    } else {
+        write!(out, "{start},{len}")
+    };
+}
+
+/// Compute the shortest sequence of operations which turns `a` into `b`
+/// using the greedy algorithm by Eugene W. Myers, which is fast when the
+/// inputs are mostly the same.
+fn ops(a: &[&[u8]], b: &[&[u8]]) -> Vec<Op> {
+    let (n, m) = (a.len(), b.len());
+    let max = n + m;
+
+    // The furthest reaching x on each diagonal k after d differences, where
+    // diagonal k is stored at offset `k + max`. One is saved for every d so
+    // that the path can be traced back.
+    let mut v = vec![0usize; 2 * max + 2];
+    let mut trace = Vec::new();
+
+    'outer: for d in 0..=max {
+        trace.push(v.clone());
+
+        for k in (max - d..=max + d).step_by(2) {
+            let mut x = if d == 0 {
+                0
+            } else if k == max - d || (k != max + d && at(&v, k - 1) < at(&v, k + 1)) {
+                at(&v, k + 1)
+            } else {
+                at(&v, k - 1) + 1
+            };
+
+            let mut y = x + max - k;
+
+            while x < n && y < m && a.get(x) == b.get(y) {
+                x += 1;
+                y += 1;
+            }
+
+            if let Some(slot) = v.get_mut(k) {
+                *slot = x;
+            }
+
+            if x >= n && y >= m {
+                break 'outer;
+            }
+        }
+    }
+
+    let mut ops = Vec::with_capacity(max);
+    let (mut x, mut y) = (n, m);
+
+    for (d, v) in trace.iter().enumerate().rev() {
+        let k = x + max - y;
+
+        let (prev_x, prev_y) = if d == 0 {
+            (0, 0)
+        } else {
+            let prev_k = if k == max - d || (k != max + d && at(v, k - 1) < at(v, k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+
+            let prev_x = at(v, prev_k);
+            (prev_x, prev_x + max - prev_k)
+        };
+
+        while x > prev_x && y > prev_y {
+            ops.push(Op::Keep);
+            x -= 1;
+            y -= 1;
+        }
+
+        if d > 0 {
+            ops.push(if x == prev_x { Op::Add } else { Op::Remove });
+        }
+
+        (x, y) = (prev_x, prev_y);
+    }
+
+    ops.reverse();
+    ops
+}
+
+/// Get the furthest reaching x on diagonal `k`.
+fn at(v: &[usize], k: usize) -> usize {
+    v.get(k).copied().unwrap_or_default()
+}
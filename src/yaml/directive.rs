@@ -0,0 +1,164 @@
+use std::fmt;
+
+use bstr::{BStr, ByteSlice};
+
+/// The name of the directive declaring the YAML version of a document.
+const YAML: &[u8] = b"YAML";
+
+/// A directive in the header of a document, like `%YAML 1.2` or
+/// `%TAG !e! tag:example.com,2000:`.
+///
+/// Directives are accessed through [`Document::directives`].
+///
+/// [`Document::directives`]: crate::yaml::Document::directives
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("%YAML 1.2\n%TAG !e! tag:example.com,2000: # tags\n---\na: 1\n")?;
+///
+/// let directives = doc.directives().collect::<Vec<_>>();
+/// assert_eq!(directives.len(), 2);
+///
+/// assert_eq!(directives[0].name(), "YAML");
+/// assert_eq!(directives[0].parameters().collect::<Vec<_>>(), ["1.2"]);
+///
+/// assert_eq!(directives[1].name(), "TAG");
+/// assert_eq!(directives[1].parameters().collect::<Vec<_>>(), ["!e!", "tag:example.com,2000:"]);
+/// assert_eq!(directives[1].as_raw(), "%TAG !e! tag:example.com,2000: # tags");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Clone, Copy)]
+pub struct Directive<'a> {
+    raw: &'a BStr,
+}
+
+impl<'a> Directive<'a> {
+    /// Get the name of the directive, like `YAML` or `TAG`.
+    #[must_use]
+    pub fn name(&self) -> &'a BStr {
+        self.fields().next().unwrap_or_default()
+    }
+
+    /// Get the parameters of the directive, like `1.2` in `%YAML 1.2`.
+    pub fn parameters(&self) -> impl Iterator<Item = &'a BStr> {
+        self.fields().skip(1)
+    }
+
+    /// Get the raw line of the directive, including its leading `%`.
+    #[must_use]
+    pub fn as_raw(&self) -> &'a BStr {
+        self.raw
+    }
+
+    /// Iterate over the whitespace-separated fields of the directive, ending
+    /// at any trailing comment.
+    fn fields(&self) -> impl Iterator<Item = &'a BStr> {
+        let line = self.raw.get(1..).unwrap_or_default();
+
+        line.fields()
+            .take_while(|field| !field.starts_with(b"#"))
+            .map(BStr::new)
+    }
+}
+
+impl fmt::Debug for Directive<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.raw.fmt(f)
+    }
+}
+
+/// Iterate over the directives in the given document header.
+pub(crate) fn directives(header: &BStr) -> impl Iterator<Item = Directive<'_>> {
+    header
+        .lines()
+        .filter(|line| line.starts_with(b"%"))
+        .map(|line| Directive {
+            raw: line.as_bstr(),
+        })
+}
+
+/// Test if the given line is a `%YAML` directive.
+fn is_yaml(line: &[u8]) -> bool {
+    line.starts_with(b"%")
+        && Directive {
+            raw: line.as_bstr(),
+        }
+        .name()
+        .as_bytes()
+            == YAML
+}
+
+/// Construct a header where the version of the `%YAML` directive is set to
+/// `version`, adding the directive if it's missing.
+///
+/// Everything else in the header is left untouched.
+pub(crate) fn set_yaml(header: Option<&BStr>, version: &str) -> Vec<u8> {
+    let Some(header) = header else {
+        return format!("%YAML {version}\n---").into_bytes();
+    };
+
+    let mut out = Vec::with_capacity(header.len());
+    let mut added = false;
+
+    for line in header.lines_with_terminator() {
+        if added {
+            out.extend_from_slice(line);
+            continue;
+        }
+
+        if is_yaml(line) {
+            // Replace the version while keeping the surrounding whitespace
+            // and any trailing comment.
+            let name = line.find(YAML).map_or(0, |n| n + YAML.len());
+            let (head, rest) = line.split_at(name);
+            let start = rest.len() - rest.trim_start_with(|c| matches!(c, ' ' | '\t')).len();
+            let end = rest
+                .get(start..)
+                .unwrap_or_default()
+                .find_byteset(b" \t\r\n")
+                .map_or(rest.len(), |n| start + n);
+
+            out.extend_from_slice(head);
+
+            if start == 0 {
+                out.push(b' ');
+            }
+
+            out.extend_from_slice(rest.get(..start).unwrap_or_default());
+            out.extend_from_slice(version.as_bytes());
+            out.extend_from_slice(rest.get(end..).unwrap_or_default());
+            added = true;
+            continue;
+        }
+
+        if line.starts_with(b"%") || line.starts_with(b"---") {
+            out.extend_from_slice(format!("%YAML {version}\n").as_bytes());
+            added = true;
+        }
+
+        out.extend_from_slice(line);
+    }
+
+    out
+}
+
+/// Construct a header without the `%YAML` directive, or `None` if the header
+/// doesn't have one.
+pub(crate) fn remove_yaml(header: &BStr) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(header.len());
+    let mut removed = false;
+
+    for line in header.lines_with_terminator() {
+        if !removed && is_yaml(line) {
+            removed = true;
+            continue;
+        }
+
+        out.extend_from_slice(line);
+    }
+
+    removed.then_some(out)
+}
@@ -0,0 +1,81 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::{Value, ValueMut};
+
+/// Mutable access to a fixed set of values in a document, none of which
+/// contains another.
+///
+/// All values of a document share its storage, so only one of them can be
+/// borrowed mutably at a time. Since the values are known not to overlap,
+/// editing one of them never affects the others, and their identifiers stay
+/// valid for as long as the access is held. This means that the values can be
+/// edited in turn, swapped and copied between each other by index without any
+/// intermediate buffers.
+///
+/// See [`Document::values_mut_disjoint`].
+///
+/// [`Document::values_mut_disjoint`]: crate::yaml::Document::values_mut_disjoint
+pub struct DisjointMut<'a, const N: usize> {
+    data: &'a mut Data,
+    ids: [Id; N],
+}
+
+impl<'a, const N: usize> DisjointMut<'a, N> {
+    pub(crate) fn new(data: &'a mut Data, ids: [Id; N]) -> Self {
+        Self { data, ids }
+    }
+
+    /// Get the identifiers of the values.
+    #[must_use]
+    pub fn ids(&self) -> [Id; N] {
+        self.ids
+    }
+
+    /// Get the value at `index`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Value<'_>> {
+        let id = *self.ids.get(index)?;
+        Some(Value::new(self.data, id))
+    }
+
+    /// Get the value at `index` mutably.
+    pub fn get_mut(&mut self, index: usize) -> Option<ValueMut<'_>> {
+        let id = *self.ids.get(index)?;
+        Some(ValueMut::new(self.data, id))
+    }
+
+    /// Swap the values at index `a` and `b`, returning `false` if either index
+    /// is out of bounds.
+    ///
+    /// Like [`Document::swap`], each value keeps its prefix.
+    ///
+    /// [`Document::swap`]: crate::yaml::Document::swap
+    pub fn swap(&mut self, a: usize, b: usize) -> bool {
+        let (Some(&a), Some(&b)) = (self.ids.get(a), self.ids.get(b)) else {
+            return false;
+        };
+
+        if a != b {
+            self.data.swap(a, b);
+        }
+
+        true
+    }
+
+    /// Copy the value at index `from` over the value at index `to`, returning
+    /// `false` if either index is out of bounds.
+    ///
+    /// Like [`Document::copy_value`], the value at `to` keeps its prefix.
+    ///
+    /// [`Document::copy_value`]: crate::yaml::Document::copy_value
+    pub fn copy(&mut self, from: usize, to: usize) -> bool {
+        let (Some(&from), Some(&to)) = (self.ids.get(from), self.ids.get(to)) else {
+            return false;
+        };
+
+        if from != to {
+            self.data.copy(from, to);
+        }
+
+        true
+    }
+}
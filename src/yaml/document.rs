@@ -1,11 +1,46 @@
+#[cfg(feature = "serde-edits")]
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::io;
 
+use bstr::{BStr, ByteSlice};
+#[cfg(feature = "serde-edits")]
+use serde::de::Error as _;
 #[cfg(feature = "serde-edits")]
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::yaml::canonical;
+use crate::yaml::clear;
+use crate::yaml::comments;
 use crate::yaml::data::{Data, Id, StringId};
-use crate::yaml::{Value, ValueMut};
+use crate::yaml::diff;
+use crate::yaml::directive;
+use crate::yaml::dump;
+use crate::yaml::encoding;
+use crate::yaml::json;
+use crate::yaml::key_index;
+use crate::yaml::lock;
+use crate::yaml::outline;
+use crate::yaml::parsing::Options;
+use crate::yaml::placeholder;
+use crate::yaml::pointer;
+use crate::yaml::prune;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::reindent;
+use crate::yaml::replace;
+#[cfg(feature = "serde-edits")]
+use crate::yaml::session;
+use crate::yaml::stats;
+use crate::yaml::stream;
+use crate::yaml::value;
+#[cfg(feature = "serde-edits")]
+use crate::yaml::Compression;
+use crate::yaml::{
+    Bom, Directive, DisjointMut, Error, FrozenDocument, KeyIndex, KeyOrder, Metadata, Outline,
+    Parser, Placeholder, Prune, Quoting, Reload, Separator, Value, ValueMut, WriteOptions,
+};
 
 /// A whitespace preserving YAML document.
 ///
@@ -28,17 +63,260 @@ use crate::yaml::{Value, ValueMut};
 /// # Ok::<_, anyhow::Error>(())
 /// ```
 #[derive(Clone)]
-#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub struct Document {
+    /// The directives and `---` marker preceding the root value, if the
+    /// document has any.
+    header: Option<StringId>,
     suffix: StringId,
+    /// The whitespace following an explicit `...` end marker, if the document
+    /// has one.
+    end: Option<StringId>,
+    /// The byte order mark the document started with, if any.
+    bom: Option<Bom>,
+    /// The output of the document when it was last snapshotted.
+    original: Option<StringId>,
     pub(crate) root: Id,
     pub(crate) data: Data,
 }
 
+/// The names a value is bookmarked under.
+///
+/// Bookmarks are stored as metadata, so that they are dropped together with
+/// the value they reference and never resolve to a value which later reuses
+/// its identifier.
+#[cfg(feature = "serde-edits")]
+#[derive(Clone, Default)]
+struct Bookmarks(BTreeSet<Box<str>>);
+
+/// Documents are serialized along with a checksum of their output, which is
+/// validated when they are deserialized together with the references between
+/// their values. Documents serialized by earlier versions of this crate don't
+/// have a checksum, so it's optional.
+#[cfg(feature = "serde-edits")]
+impl Serialize for Document {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut s = serializer.serialize_struct("Document", 10)?;
+        s.serialize_field("header", &self.header)?;
+        s.serialize_field("suffix", &self.suffix)?;
+        s.serialize_field("end", &self.end)?;
+
+        if self.bom.is_none() {
+            s.skip_field("bom")?;
+        } else {
+            s.serialize_field("bom", &self.bom)?;
+        }
+
+        if self.original.is_none() {
+            s.skip_field("original")?;
+        } else {
+            s.serialize_field("original", &self.original)?;
+        }
+
+        if self.data.options() == Options::default() {
+            s.skip_field("options")?;
+        } else {
+            s.serialize_field("options", &self.data.options())?;
+        }
+
+        s.serialize_field("root", &self.root)?;
+        s.serialize_field("data", &self.data)?;
+
+        let bookmarks = self.bookmarks().collect::<BTreeMap<_, _>>();
+
+        if bookmarks.is_empty() {
+            s.skip_field("bookmarks")?;
+        } else {
+            s.serialize_field("bookmarks", &bookmarks)?;
+        }
+
+        s.serialize_field("checksum", &session::checksum(self))?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde-edits")]
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Repr {
+            #[serde(default)]
+            header: Option<StringId>,
+            suffix: StringId,
+            #[serde(default)]
+            end: Option<StringId>,
+            #[serde(default)]
+            bom: Option<Bom>,
+            #[serde(default)]
+            original: Option<StringId>,
+            #[serde(default)]
+            options: Options,
+            root: Id,
+            data: Data,
+            #[serde(default)]
+            bookmarks: BTreeMap<String, Id>,
+            #[serde(default)]
+            checksum: Option<u64>,
+        }
+
+        let repr = Repr::deserialize(deserializer)?;
+
+        session::check(&repr.data, repr.root).map_err(D::Error::custom)?;
+
+        for string in [Some(repr.suffix), repr.header, repr.end, repr.original]
+            .into_iter()
+            .flatten()
+        {
+            if !repr.data.contains_str(string) {
+                return Err(D::Error::custom("reference to a missing string"));
+            }
+        }
+
+        let mut data = repr.data;
+        data.set_options(repr.options);
+
+        let mut document = Self {
+            header: repr.header,
+            suffix: repr.suffix,
+            end: repr.end,
+            bom: repr.bom,
+            original: repr.original,
+            root: repr.root,
+            data,
+        };
+
+        for (name, id) in repr.bookmarks {
+            document.bookmark(&name, id);
+        }
+
+        if repr
+            .checksum
+            .is_some_and(|checksum| checksum != session::checksum(&document))
+        {
+            return Err(D::Error::custom("checksum mismatch"));
+        }
+
+        Ok(document)
+    }
+}
+
 impl Document {
     /// Construct a new document.
     pub(crate) fn new(suffix: StringId, root: Id, data: Data) -> Self {
-        Self { suffix, root, data }
+        Self {
+            header: None,
+            suffix,
+            end: None,
+            bom: None,
+            original: None,
+            root,
+            data,
+        }
+    }
+
+    /// Set the directives and `---` marker preceding the root value.
+    pub(crate) fn with_header(self, header: Option<StringId>) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Test if the document has a header, such as a `---` marker.
+    pub(crate) fn has_header(&self) -> bool {
+        self.header.is_some()
+    }
+
+    /// Give the document a `---` marker if it doesn't already have a header,
+    /// so that it can follow another document in a stream.
+    pub(crate) fn ensure_start_marker(&mut self) {
+        if self.header.is_none() {
+            let header = self.start_header(stream::START);
+            self.header = Some(self.data.insert_str(header));
+        }
+    }
+
+    /// Construct a header ending with `header`, separated from the root value
+    /// by a newline unless its prefix already separates them.
+    fn start_header(&self, header: &[u8]) -> Vec<u8> {
+        let mut header = header.to_vec();
+
+        if !self
+            .data
+            .prefix(self.root)
+            .first()
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            header.extend_from_slice(self.data.newline());
+        }
+
+        header
+    }
+
+    /// Set the whitespace following the explicit end marker of the document.
+    pub(crate) fn with_end(self, end: Option<StringId>) -> Self {
+        Self { end, ..self }
+    }
+
+    /// Set the configuration the document was parsed with.
+    pub(crate) fn with_options(mut self, options: Options) -> Self {
+        self.data.set_options(options);
+        self
+    }
+
+    /// Set the byte order mark the document starts with.
+    pub(crate) fn with_bom(self, bom: Option<Bom>) -> Self {
+        Self { bom, ..self }
+    }
+
+    /// Get the byte order mark the document starts with, if any.
+    ///
+    /// See [`Bom`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("\u{feff}hello")?;
+    /// assert_eq!(doc.bom(), Some(yaml::Bom::Utf8));
+    /// assert_eq!(doc.as_ref().as_str(), Some("hello"));
+    /// assert_eq!(doc.to_string(), "\u{feff}hello");
+    ///
+    /// let doc = yaml::from_slice("hello")?;
+    /// assert_eq!(doc.bom(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn bom(&self) -> Option<Bom> {
+        self.bom
+    }
+
+    /// Set the byte order mark the document starts with.
+    ///
+    /// Setting a UTF-16 byte order mark causes the document to be encoded as
+    /// UTF-16 when it's written using [`Document::write_to`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("\u{feff}a: 1\n")?;
+    /// doc.set_bom(None);
+    /// assert_eq!(doc.to_string(), "a: 1\n");
+    ///
+    /// doc.set_bom(Some(yaml::Bom::Utf16Be));
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"\xfe\xff\x00a\x00:\x00 \x001\x00\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_bom(&mut self, bom: Option<Bom>) {
+        self.bom = bom;
     }
 
     /// Get the document as a [`Value`].
@@ -78,6 +356,33 @@ impl Document {
         ValueMut::new(&mut self.data, self.root)
     }
 
+    /// Test if the document is terminated with an explicit `...` end marker.
+    ///
+    /// The end marker and anything following it, like comments, is preserved
+    /// when the document is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: app\n... # end\n")?;
+    /// assert!(doc.has_end_marker());
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.insert_u32("replicas", 3);
+    /// assert_eq!(doc.to_string(), "name: app\nreplicas: 3\n... # end\n");
+    ///
+    /// let doc = yaml::from_slice("name: app\n")?;
+    /// assert!(!doc.has_end_marker());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn has_end_marker(&self) -> bool {
+        self.end.is_some()
+    }
+
     /// Get the given value.
     ///
     /// If [`Id`]'s are shared between documents, this might also result in
@@ -88,6 +393,7 @@ impl Document {
     ///
     /// Values constructed from identifiers might cause panics if used
     /// incorrectly, such as when it refers to a value which has been deleted.
+    /// Use [`Document::try_value`] to avoid this.
     ///
     /// ```should_panic
     /// use anyhow::Context;
@@ -139,6 +445,32 @@ impl Document {
         Value::new(&self.data, id)
     }
 
+    /// Test if the given identifier refers to a value in this document.
+    ///
+    /// Identifiers of removed values might be reused by values inserted later,
+    /// so this only tests that the identifier can be safely used with
+    /// [`Document::value`] and [`Document::value_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("[1, 2, 3]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing sequence")?;
+    /// let id = root.get(1).context("missing item")?.id();
+    ///
+    /// assert!(doc.contains(id));
+    /// assert!(!doc.contains(yaml::Id::from_u64(1000).context("bad id")?));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn contains(&self, id: Id) -> bool {
+        self.data.contains(id)
+    }
+
     /// Get the given value mutably.
     ///
     /// If [`Id`]'s are shared between documents, this might also result in
@@ -149,6 +481,7 @@ impl Document {
     ///
     /// Values constructed from identifiers might cause panics if used
     /// incorrectly, such as when it refers to a value which has been deleted.
+    /// Use [`Document::try_value_mut`] to avoid this.
     ///
     /// ```should_panic
     /// use anyhow::Context;
@@ -206,12 +539,145 @@ impl Document {
         ValueMut::new(&mut self.data, id)
     }
 
-    /// Write the bytes of the document to the given `output`.
+    /// Get the given value, or `None` if the identifier doesn't refer to a
+    /// value in this document.
     ///
-    /// # Errors
+    /// Unlike [`Document::value`], using the returned value never panics. This
+    /// is useful when identifiers come from an untrusted source, such as an
+    /// external index populated through [`Id::from_u64`].
     ///
-    /// Raises an I/O error if the underlying resource being written to raises
-    /// it.
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{first: 32, second: [1, 2, 3]}")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing mapping")?;
+    /// let id = root.get("second").context("missing second")?.id();
+    /// assert_eq!(doc.try_value(id).map(|v| v.to_string()), Some(String::from("[1, 2, 3]")));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing mapping")?;
+    /// assert!(root.remove("second"));
+    /// assert!(doc.try_value(id).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn try_value(&self, id: Id) -> Option<Value<'_>> {
+        self.is_value(id).then(|| Value::new(&self.data, id))
+    }
+
+    /// Get the given value mutably, or `None` if the identifier doesn't refer
+    /// to a value in this document or if the value is inside of a region
+    /// [locked][Document::lock] by someone else.
+    ///
+    /// Unlike [`Document::value_mut`], using the returned value never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{first: 32}")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing mapping")?;
+    /// let id = root.get("first").context("missing first")?.id();
+    ///
+    /// doc.try_value_mut(id).context("missing value")?.set_u32(64);
+    /// assert_eq!(doc.to_string(), "{first: 64}");
+    ///
+    /// let unknown = yaml::Id::from_u64(1000).context("bad id")?;
+    /// assert!(doc.try_value_mut(unknown).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn try_value_mut(&mut self, id: Id) -> Option<ValueMut<'_>> {
+        if !self.is_value(id) || lock::is_locked(&self.data, id) {
+            return None;
+        }
+
+        Some(ValueMut::new(&mut self.data, id))
+    }
+
+    /// Get the [JSON pointer] to the value `id`, like `/spec/ports/0`, or
+    /// `None` if `id` doesn't refer to a value in the document.
+    ///
+    /// The pointer to the root of the document is the empty string. Mapping
+    /// keys are escaped so that the pointer can be passed to
+    /// [`Document::resolve_json_pointer`], or to tools such as JSON Schema
+    /// validators which report errors by pointer.
+    ///
+    /// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("paths:\n  /users/{id}:\n    tags: [a, b]\n")?;
+    ///
+    /// let tag = doc.resolve_json_pointer("/paths/~1users~1{id}/tags/1").context("missing tag")?;
+    /// assert_eq!(doc.value(tag).as_str(), Some("b"));
+    /// assert_eq!(doc.json_pointer(tag).as_deref(), Some("/paths/~1users~1{id}/tags/1"));
+    /// assert_eq!(doc.json_pointer(doc.as_ref().id()).as_deref(), Some(""));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn json_pointer(&self, id: Id) -> Option<std::string::String> {
+        if !self.is_value(id) {
+            return None;
+        }
+
+        pointer::to_pointer(&self.data, self.root, id)
+    }
+
+    /// Resolve a [JSON pointer], like `/spec/ports/0`, to the identifier of
+    /// the value it points to.
+    ///
+    /// The empty pointer refers to the root of the document. Returns `None` if
+    /// the pointer is malformed or doesn't point to a value.
+    ///
+    /// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("spec:\n  ports: [80, 443]\n")?;
+    ///
+    /// let port = doc.resolve_json_pointer("/spec/ports/1").context("missing port")?;
+    /// doc.value_mut(port).set_u32(8443);
+    /// assert_eq!(doc.to_string(), "spec:\n  ports: [80, 8443]\n");
+    ///
+    /// assert!(doc.resolve_json_pointer("/spec/ports/2").is_none());
+    /// assert!(doc.resolve_json_pointer("/spec/ports/01").is_none());
+    /// assert!(doc.resolve_json_pointer("spec").is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn resolve_json_pointer(&self, pointer: &str) -> Option<Id> {
+        pointer::resolve(self, self.root, pointer)
+    }
+
+    /// Swap the values `a` and `b` in place, returning `false` without
+    /// modifying the document if either doesn't refer to a value, if one of
+    /// them contains the other, or if either touches a region
+    /// [locked][Document::lock] by someone else.
+    ///
+    /// Each position keeps its prefix, such as the separator following the
+    /// `:` of a mapping item, while the values themselves are moved. The
+    /// identifiers `a` and `b` keep referring to the same positions in the
+    /// document.
+    ///
+    /// Values which span multiple lines depend on their indentation, so they
+    /// are rewritten for their new position instead of being moved. Such
+    /// collections are written in the style they had, except that block
+    /// collections are written inline when they are moved into an inline
+    /// collection, and their contents are given new identifiers.
     ///
     /// # Examples
     ///
@@ -221,65 +687,1979 @@ impl Document {
     ///
     /// let mut doc = yaml::from_slice(
     ///     r"
-    ///     string
+    ///     primary: {host: a.example.com, port: 80}
+    ///     fallback: b.example.com
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().make_mapping();
-    /// mapping.insert_u32("first", 1);
-    /// mapping.insert_u32("second", 2);
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let primary = root.get("primary").context("missing primary")?.id();
+    /// let fallback = root.get("fallback").context("missing fallback")?.id();
+    /// let host = root
+    ///     .get("primary")
+    ///     .and_then(|v| v.as_mapping()?.get("host"))
+    ///     .context("missing host")?
+    ///     .id();
     ///
-    /// let mut out = Vec::new();
-    /// doc.write_to(&mut out)?;
+    /// assert!(!doc.swap(primary, host));
+    /// assert!(doc.swap(primary, fallback));
     ///
     /// assert_eq!(
-    ///     &out[..],
-    ///     br"
-    ///     first: 1
-    ///     second: 2
+    ///     doc.to_string(),
+    ///     r"
+    ///     primary: b.example.com
+    ///     fallback: {host: a.example.com, port: 80}
     ///     "
     /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn swap(&mut self, a: Id, b: Id) -> bool {
+        if !self.is_value(a) || !self.is_value(b) || self.overlaps(a, b) {
+            return false;
+        }
+
+        if lock::contains_locked(&self.data, a) || lock::contains_locked(&self.data, b) {
+            return false;
+        }
+
+        self.data.swap(a, b);
+        true
+    }
+
+    /// Copy the value `from` over the value `to`, returning `false` without
+    /// modifying the document if either doesn't refer to a value, or if `to`
+    /// touches a region [locked][Document::lock] by someone else.
+    ///
+    /// The value at `to` keeps its prefix, such as the separator following the
+    /// `:` of a mapping item. The copy is taken before `to` is overwritten, so
+    /// a value may be copied into one of its own descendants and the other way
+    /// around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
     ///
     /// let mut doc = yaml::from_slice(
     ///     r"
-    ///     first: second
+    ///     defaults:
+    ///       replicas: 3
+    ///       image: app:1.0
+    ///     staging: {}
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| Some(m.get_into_mut("first")?.make_mapping())).context("missing first")?;
-    /// mapping.insert_u32("second", 2);
-    /// mapping.insert_u32("third", 3);
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let defaults = root.get("defaults").context("missing defaults")?.id();
+    /// let staging = root.get("staging").context("missing staging")?.id();
     ///
-    /// let mut out = Vec::new();
-    /// doc.write_to(&mut out)?;
+    /// assert!(doc.copy_value(defaults, staging));
     ///
     /// assert_eq!(
-    ///     &out[..],
-    ///     br"
-    ///     first:
-    ///       second: 2
-    ///       third: 3
+    ///     doc.to_string(),
+    ///     r"
+    ///     defaults:
+    ///       replicas: 3
+    ///       image: app:1.0
+    ///     staging:
+    ///       replicas: 3
+    ///       image: app:1.0
     ///     "
     /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn write_to<O>(&self, mut output: O) -> io::Result<()>
-    where
-        O: io::Write,
-    {
-        output.write_all(self.data.prefix(self.root))?;
-        self.data.raw(self.root).write_to(&self.data, &mut output)?;
-        output.write_all(self.data.str(self.suffix))?;
-        Ok(())
-    }
+    pub fn copy_value(&mut self, from: Id, to: Id) -> bool {
+        if !self.is_value(from) || !self.is_value(to) || lock::contains_locked(&self.data, to) {
+            return false;
+        }
 
-    // Display helper for document.
-    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        use std::fmt::Display;
+        self.data.copy(from, to);
+        true
+    }
+
+    /// Get mutable access to several values at once, or `None` if any of them
+    /// doesn't refer to a value, if any of them contains another or is
+    /// repeated, or if any of them touches a region [locked][Document::lock]
+    /// by someone else.
+    ///
+    /// Since the values don't overlap, they can be edited in turn, swapped and
+    /// copied between each other through the returned [`DisjointMut`] without
+    /// having to look them up again. See [`DisjointMut`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     blue: {replicas: 3}
+    ///     green: {replicas: 1}
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let blue = root.get("blue").context("missing blue")?.id();
+    /// let green = root.get("green").context("missing green")?.id();
+    /// let replicas = root.get("blue").and_then(|v| v.as_mapping()?.get("replicas"));
+    /// let replicas = replicas.context("missing replicas")?.id();
+    ///
+    /// // A value can't be accessed together with one of its descendants.
+    /// assert!(doc.values_mut_disjoint([blue, replicas]).is_none());
+    /// assert!(doc.values_mut_disjoint([blue, blue]).is_none());
+    ///
+    /// let mut values = doc.values_mut_disjoint([blue, green]).context("overlapping values")?;
+    /// assert!(values.swap(0, 1));
+    ///
+    /// let mut green = values.get_mut(1).and_then(|v| v.into_mapping_mut()).context("missing green")?;
+    /// green.insert_u32("replicas", 4);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     blue: {replicas: 1}
+    ///     green: {replicas: 4}
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn values_mut_disjoint<const N: usize>(
+        &mut self,
+        ids: [Id; N],
+    ) -> Option<DisjointMut<'_, N>> {
+        for (n, &id) in ids.iter().enumerate() {
+            if !self.is_value(id) || lock::contains_locked(&self.data, id) {
+                return None;
+            }
+
+            let rest = ids.get(..n).unwrap_or_default();
+
+            if rest.iter().any(|&other| self.overlaps(id, other)) {
+                return None;
+            }
+        }
+
+        Some(DisjointMut::new(&mut self.data, ids))
+    }
+
+    /// Test if one of the values `a` and `b` contains the other.
+    fn overlaps(&self, a: Id, b: Id) -> bool {
+        let contains = |outer: Id, mut id: Id| loop {
+            if id == outer {
+                return true;
+            }
+
+            let Some(parent) = self.data.layout(id).parent else {
+                return false;
+            };
+
+            id = parent;
+        };
+
+        contains(a, b) || contains(b, a)
+    }
+
+    /// Test if the given identifier refers to a value, as opposed to a missing
+    /// entry or an item in a collection.
+    fn is_value(&self, id: Id) -> bool {
+        self.data.contains(id)
+            && !matches!(
+                self.data.raw(id),
+                Raw::MappingItem(..) | Raw::SequenceItem(..)
+            )
+    }
+
+    /// Set the default separator used by [`Separator::Auto`] when inserting
+    /// scalar values into block mappings and block sequences.
+    ///
+    /// By default, [`Separator::Auto`] copies the separator of the last item
+    /// in the collection, which means that accidental one-off spacing is
+    /// propagated. Setting a default separator means that it is used instead.
+    /// Values which are placed on their own lines, such as block mappings
+    /// inside of a mapping, are not affected.
+    ///
+    /// Setting the separator to [`Separator::Auto`] restores the default
+    /// behavior.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml::{self, Separator};
+    ///
+    /// let mut doc = yaml::from_slice("name:  app\nimage: app:1.0")?;
+    /// doc.set_separator(Separator::spaces(2));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.insert_u32("replicas", 3);
+    ///
+    /// assert_eq!(doc.to_string(), "name:  app\nimage: app:1.0\nreplicas:  3");
+    ///
+    /// let mut doc = yaml::from_slice("- one\n-    two")?;
+    /// doc.set_separator(Separator::spaces(1));
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    /// root.push_string("three");
+    ///
+    /// assert_eq!(doc.to_string(), "- one\n-    two\n- three");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_separator(&mut self, separator: Separator<'_>) {
+        let separator = match separator {
+            Separator::Auto => None,
+            Separator::Custom(separator) => Some(self.data.insert_str(separator)),
+            Separator::Spaces(n) => Some(self.data.insert_str(" ".repeat(n.max(1)))),
+        };
+
+        self.data.set_separator(separator);
+    }
+
+    /// Set the policy used to quote strings inserted into the document, such
+    /// as through [`ValueMut::set_string`] or [`MappingMut::insert_str`].
+    ///
+    /// Strings which are already in the document are left untouched. Setting
+    /// the policy to [`Quoting::new`] restores the default behavior.
+    ///
+    /// [`MappingMut::insert_str`]: crate::yaml::MappingMut::insert_str
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("- 'a: b'\n- 1.0")?;
+    /// doc.set_quoting(yaml::Quoting::new().prefer(yaml::QuoteStyle::Double));
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    /// root.push_string("c: d");
+    /// root.push_string("2.0");
+    ///
+    /// assert_eq!(doc.to_string(), "- 'a: b'\n- 1.0\n- \"c: d\"\n- \"2.0\"");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_quoting(&mut self, quoting: Quoting) {
+        self.data.set_quoting(quoting);
+    }
+
+    /// Set the style of placeholder scalars, which are inserted through
+    /// [`ValueMut::set_placeholder`] and found through
+    /// [`Document::placeholders`].
+    ///
+    /// Placeholders already in the document which use a different style are
+    /// no longer recognized as such.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[<A>, '%B%']")?;
+    /// assert_eq!(doc.placeholders().count(), 1);
+    ///
+    /// doc.set_placeholder(yaml::Placeholder::new().delimiters("%", "%"));
+    /// let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["B"]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_placeholder(&mut self, placeholder: Placeholder) {
+        self.data.set_placeholder(placeholder);
+    }
+
+    /// Iterate over the placeholder scalars in the document, like
+    /// `<REPLACE_ME>`, along with their names.
+    ///
+    /// Only string values are considered, regardless of how they are quoted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     image: <IMAGE>
+    ///     replicas: 3
+    ///     env:
+    ///       - "<TOKEN>"
+    ///       - <>
+    ///     "#
+    /// )?;
+    ///
+    /// let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["IMAGE", "TOKEN"]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn placeholders(&self) -> impl Iterator<Item = (Id, &str)> {
+        placeholder::find(&self.data, self.root).into_iter()
+    }
+
+    /// Fill in placeholder scalars in the document using the given closure,
+    /// returning the number of placeholders which were filled.
+    ///
+    /// The closure is called with the name of every placeholder and returns
+    /// the string to replace it with, or `None` if it should be left in
+    /// place. Replacements are quoted according to the policy set through
+    /// [`Document::set_quoting`]. Placeholders which are
+    /// [locked][Document::lock] by someone else are left in place, and the
+    /// closure isn't called for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("image: <IMAGE>\ntag: <TAG>\nlatest: <LATEST>\n")?;
+    ///
+    /// let count = doc.fill_placeholders(|name| match name {
+    ///     "IMAGE" => Some(String::from("nginx")),
+    ///     "TAG" => Some(String::from("1.25")),
+    ///     _ => None,
+    /// });
+    ///
+    /// assert_eq!(count, 2);
+    /// assert_eq!(doc.to_string(), "image: nginx\ntag: '1.25'\nlatest: <LATEST>\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn fill_placeholders<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&str) -> Option<std::string::String>,
+    {
+        let fills = placeholder::find(&self.data, self.root)
+            .into_iter()
+            .filter(|&(id, _)| !lock::is_locked(&self.data, id))
+            .filter_map(|(id, name)| Some((id, f(name)?)))
+            .collect::<Vec<_>>();
+
+        for (id, string) in &fills {
+            let flow = clear::in_flow(&self.data, *id);
+            let value = raw::new_string(&mut self.data, string, flow);
+            self.data.replace(*id, value);
+        }
+
+        fills.len()
+    }
+
+    /// Iterate over the directives in the header of the document, like
+    /// `%YAML 1.2` or `%TAG !e! tag:example.com,2000:`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("%YAML 1.2\n---\na: 1\n")?;
+    /// let names = doc.directives().map(|d| d.name()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["YAML"]);
+    ///
+    /// let doc = yaml::from_slice("a: 1\n")?;
+    /// assert_eq!(doc.directives().count(), 0);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn directives(&self) -> impl Iterator<Item = Directive<'_>> {
+        let header = self.header.map(|header| self.data.str(header));
+        header.into_iter().flat_map(directive::directives)
+    }
+
+    /// Set the version declared by the `%YAML` directive of the document,
+    /// adding the directive and a `---` marker if they're missing.
+    ///
+    /// The rest of the header, such as other directives and comments, is left
+    /// untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: 1\n")?;
+    /// doc.set_yaml_directive("1.2");
+    /// assert_eq!(doc.to_string(), "%YAML 1.2\n---\na: 1\n");
+    ///
+    /// let mut doc = yaml::from_slice("%YAML   1.1 # old\n%TAG ! tag:example.com:\n---\na: 1\n")?;
+    /// doc.set_yaml_directive("1.2");
+    /// assert_eq!(doc.to_string(), "%YAML   1.2 # old\n%TAG ! tag:example.com:\n---\na: 1\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_yaml_directive(&mut self, version: &str) {
+        let header = match self.header {
+            Some(header) => directive::set_yaml(Some(self.data.str(header)), version),
+            None => self.start_header(&directive::set_yaml(None, version)),
+        };
+
+        self.header = Some(self.data.insert_str(header));
+    }
+
+    /// Remove the `%YAML` directive from the header of the document,
+    /// returning `true` if it was present.
+    ///
+    /// The `---` marker and any other directives are left in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("%YAML 1.2\n---\na: 1\n")?;
+    /// assert!(doc.remove_yaml_directive());
+    /// assert_eq!(doc.to_string(), "---\na: 1\n");
+    /// assert!(!doc.remove_yaml_directive());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn remove_yaml_directive(&mut self) -> bool {
+        let Some(header) = self.header else {
+            return false;
+        };
+
+        let Some(header) = directive::remove_yaml(self.data.str(header)) else {
+            return false;
+        };
+
+        self.header = (!header.is_empty()).then(|| self.data.insert_str(header));
+        true
+    }
+
+    /// Get the metadata of type `T` associated with values in the document,
+    /// or `None` if no such metadata has been added through
+    /// [`Document::metadata_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: app")?;
+    /// assert!(doc.metadata::<u32>().is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn metadata<T>(&self) -> Option<&Metadata<T>>
+    where
+        T: 'static,
+    {
+        self.data.metadata().get::<T>()
+    }
+
+    /// Access the metadata of type `T` associated with values in the
+    /// document, which allows analysis passes to annotate values without
+    /// wrapping them.
+    ///
+    /// Each type of metadata is stored in its own table, which is created on
+    /// first access. Metadata is dropped together with the value it is
+    /// associated with, and is carried over by [`Document::reload_from`] for
+    /// values which survive the reload. It is not part of the serialized state
+    /// of the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Secret;
+    ///
+    /// let mut doc = yaml::from_slice("user: admin\npassword: hunter2\ntoken: abc")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let password = root.get("password").context("missing password")?.id();
+    /// let token = root.get("token").context("missing token")?.id();
+    ///
+    /// doc.metadata_mut().insert(password, Secret);
+    /// doc.metadata_mut().insert(token, Secret);
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.remove("token");
+    ///
+    /// let secrets = doc.metadata::<Secret>().context("missing metadata")?;
+    /// assert_eq!(secrets.len(), 1);
+    /// assert_eq!(secrets.get(password), Some(&Secret));
+    /// assert!(!secrets.contains(token));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn metadata_mut<T>(&mut self) -> &mut Metadata<T>
+    where
+        T: 'static + Clone + Send + Sync,
+    {
+        self.data.metadata_mut().get_mut::<T>()
+    }
+
+    /// Lock the region rooted at the value `id` on behalf of `owner`.
+    ///
+    /// Locks are advisory, and are intended for tools which cooperate on the
+    /// same document, such as a human-owned section and a bot-owned section in
+    /// the same file. While the [actor][Document::set_actor] editing the
+    /// document is someone other than the owner of a region:
+    /// * Mutable access to values inside of the region is refused by
+    ///   [`Document::try_value_mut`], [`MappingMut::get_mut`] and
+    ///   [`SequenceMut::get_mut`] along with their variants, which return
+    ///   `None`.
+    /// * Removing, swapping or overwriting values which touch the region is
+    ///   refused by [`MappingMut::remove`], [`SequenceMut::remove`],
+    ///   [`Document::swap`] and [`Document::copy_value`], which return
+    ///   `false`.
+    /// * Overwriting or clearing values which touch the region is refused by
+    ///   [`MappingMut::insert`], [`MappingMut::clear`],
+    ///   [`SequenceMut::clear`], [`ValueMut::make_mapping`] and
+    ///   [`ValueMut::make_sequence`] along with their variants, which error
+    ///   with [`ErrorKind::Locked`]. Methods which insert scalars, such as
+    ///   [`MappingMut::insert_u32`], leave the values untouched and have
+    ///   `try_` variants which error instead.
+    /// * Replacing values which touch the region through [`ValueMut`], such
+    ///   as with [`ValueMut::set_u32`], leaves them untouched.
+    /// * Keys whose items touch the region are not renamed by
+    ///   [`MappingMut::normalize_keys`].
+    /// * Truncating, sorting or deduplicating collections in a way which
+    ///   touches the region is refused by [`SequenceMut::truncate`],
+    ///   [`SequenceMut::keep_last`], [`SequenceMut::sort`] and
+    ///   [`SequenceMut::dedup`] along with their variants, which error with
+    ///   [`ErrorKind::Locked`].
+    /// * Document-wide edits which would touch the region are refused by
+    ///   [`Document::prune_empty`], [`Document::replace_in_strings`] and
+    ///   [`Document::reindent_all`], which error with [`ErrorKind::Locked`]
+    ///   without modifying anything.
+    ///
+    /// Locks can be ignored by [forcing][Document::set_forced] edits.
+    ///
+    /// Regions can be nested, in which case the innermost lock determines the
+    /// owner of a value. Returns `false` without locking anything if `id`
+    /// doesn't refer to a value or if it is already inside of a region locked
+    /// by someone else.
+    ///
+    /// [`MappingMut::get_mut`]: crate::yaml::MappingMut::get_mut
+    /// [`MappingMut::remove`]: crate::yaml::MappingMut::remove
+    /// [`SequenceMut::get_mut`]: crate::yaml::SequenceMut::get_mut
+    /// [`SequenceMut::remove`]: crate::yaml::SequenceMut::remove
+    /// [`MappingMut::insert`]: crate::yaml::MappingMut::insert
+    /// [`MappingMut::insert_u32`]: crate::yaml::MappingMut::insert_u32
+    /// [`MappingMut::clear`]: crate::yaml::MappingMut::clear
+    /// [`MappingMut::normalize_keys`]: crate::yaml::MappingMut::normalize_keys
+    /// [`SequenceMut::clear`]: crate::yaml::SequenceMut::clear
+    /// [`SequenceMut::truncate`]: crate::yaml::SequenceMut::truncate
+    /// [`SequenceMut::keep_last`]: crate::yaml::SequenceMut::keep_last
+    /// [`SequenceMut::sort`]: crate::yaml::SequenceMut::sort
+    /// [`SequenceMut::dedup`]: crate::yaml::SequenceMut::dedup
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     manual:
+    ///       replicas: 3
+    ///     generated:
+    ///       image: app:1.0
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let manual = root.get("manual").context("missing manual")?.id();
+    /// assert!(doc.lock(manual, "human"));
+    ///
+    /// doc.set_actor(Some("bot"));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// assert!(root.get_mut("manual").is_none());
+    /// assert!(!root.remove("manual"));
+    ///
+    /// let generated = root.get_into_mut("generated").context("missing generated")?;
+    /// let mut generated = generated.into_mapping_mut().context("missing generated")?;
+    /// generated.insert_str("image", "app:2.0");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     manual:
+    ///       replicas: 3
+    ///     generated:
+    ///       image: app:2.0
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn lock<O>(&mut self, id: Id, owner: O) -> bool
+    where
+        O: AsRef<str>,
+    {
+        if !self.is_value(id) || lock::is_locked(&self.data, id) {
+            return false;
+        }
+
+        let owner = lock::Owner(owner.as_ref().into());
+        self.data.metadata_mut().get_mut().insert(id, owner);
+        true
+    }
+
+    /// Remove the lock rooted at the value `id`, returning `false` if there
+    /// is no such lock or if it is owned by someone other than the current
+    /// [actor][Document::set_actor].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{manual: 3, generated: 4}")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let manual = root.get("manual").context("missing manual")?.id();
+    /// assert!(doc.lock(manual, "human"));
+    ///
+    /// doc.set_actor(Some("bot"));
+    /// assert!(!doc.unlock(manual));
+    ///
+    /// doc.set_actor(Some("human"));
+    /// assert!(doc.unlock(manual));
+    /// assert_eq!(doc.owner(manual), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn unlock(&mut self, id: Id) -> bool {
+        match lock::owner(&self.data, id) {
+            Some((root, _)) if root == id && !lock::is_locked(&self.data, id) => {
+                self.data.metadata_mut().get_mut::<lock::Owner>().remove(id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Get the owner of the innermost [locked][Document::lock] region which
+    /// contains the value `id`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("manual: {replicas: 3}\ngenerated: 4\n")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let manual = root.get("manual").context("missing manual")?;
+    /// let replicas = manual.as_mapping().and_then(|m| m.get("replicas")).context("missing replicas")?.id();
+    /// let manual = manual.id();
+    /// let generated = root.get("generated").context("missing generated")?.id();
+    ///
+    /// assert!(doc.lock(manual, "human"));
+    /// assert_eq!(doc.owner(replicas), Some("human"));
+    /// assert_eq!(doc.owner(generated), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn owner(&self, id: Id) -> Option<&str> {
+        let (_, owner) = lock::owner(&self.data, id)?;
+        Some(owner)
+    }
+
+    /// Test if the value `id` is inside of a region [locked][Document::lock]
+    /// by someone other than the current [actor][Document::set_actor].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{manual: 3}")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let manual = root.get("manual").context("missing manual")?.id();
+    ///
+    /// assert!(doc.lock(manual, "human"));
+    /// assert!(doc.is_locked(manual));
+    ///
+    /// doc.set_actor(Some("human"));
+    /// assert!(!doc.is_locked(manual));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_locked(&self, id: Id) -> bool {
+        lock::is_locked(&self.data, id)
+    }
+
+    /// Set who is editing the document, which determines which
+    /// [locks][Document::lock] apply. Regions owned by the actor can be
+    /// edited freely, while regions owned by anyone else are protected.
+    ///
+    /// If no actor is set, which is the default, every locked region is
+    /// protected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{generated: 1}")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let generated = root.get("generated").context("missing generated")?.id();
+    ///
+    /// assert!(doc.lock(generated, "bot"));
+    /// assert!(doc.try_value_mut(generated).is_none());
+    ///
+    /// doc.set_actor(Some("bot"));
+    /// doc.try_value_mut(generated).context("missing generated")?.set_u32(2);
+    /// assert_eq!(doc.to_string(), "{generated: 2}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_actor(&mut self, actor: Option<&str>) {
+        self.data.actor_mut().name = actor.map(Into::into);
+    }
+
+    /// Force edits, which causes all [locks][Document::lock] to be ignored
+    /// until forcing is disabled again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("manual: 1\ngenerated: 2\n")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let generated = root.get("generated").context("missing generated")?.id();
+    /// assert!(doc.lock(generated, "bot"));
+    ///
+    /// doc.set_forced(true);
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// assert!(root.remove("generated"));
+    /// doc.set_forced(false);
+    ///
+    /// assert_eq!(doc.to_string(), "manual: 1\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_forced(&mut self, forced: bool) {
+        self.data.actor_mut().forced = forced;
+    }
+
+    /// Freeze the document, turning it into a [`FrozenDocument`].
+    ///
+    /// A frozen document only provides read access, but can be cheaply cloned
+    /// and shared between threads without any additional locking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     greeting: Hello World!
+    ///     "
+    /// )?;
+    ///
+    /// let frozen = doc.freeze();
+    /// let other = frozen.clone();
+    ///
+    /// let root = other.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("greeting").and_then(|v| v.as_str()), Some("Hello World!"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn freeze(self) -> FrozenDocument {
+        FrozenDocument::new(self)
+    }
+
+    /// Reload the document from the given `input`.
+    ///
+    /// Since reloading replaces every value in the document, any [`Id`] handed
+    /// out before the reload is no longer valid. The returned [`Reload`] maps
+    /// old identifiers to values in the reloaded document which can be reached
+    /// through the same path of mapping keys and sequence indexes.
+    ///
+    /// The input is parsed with the same configuration as the document was
+    /// originally, such as the [schema][Parser::schema] and whether
+    /// [escapes are lenient][Parser::lenient_escapes].
+    ///
+    /// Settings of the document are kept, such as the separator, the quoting
+    /// policy and the placeholder style configured through
+    /// [`Document::set_separator`], [`Document::set_quoting`] and
+    /// [`Document::set_placeholder`].
+    ///
+    /// # Errors
+    ///
+    /// Errors in case the input cannot be parsed as YAML, in which case the
+    /// document is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     server:
+    ///       port: 8080
+    ///     "
+    /// )?;
+    ///
+    /// let port = doc
+    ///     .as_ref()
+    ///     .as_mapping()
+    ///     .and_then(|m| m.get("server")?.as_mapping()?.get("port"))
+    ///     .context("missing port")?
+    ///     .id();
+    ///
+    /// let reload = doc.reload_from(
+    ///     r"
+    ///     server:
+    ///       host: localhost
+    ///       port: 9090
+    ///     "
+    /// )?;
+    ///
+    /// let port = reload.get(port).context("port did not survive")?;
+    /// assert_eq!(doc.value(port).as_u32(), Some(9090));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn reload_from<D>(&mut self, input: D) -> Result<Reload, Error>
+    where
+        D: AsRef<[u8]>,
+    {
+        let document = Parser::new(input.as_ref())
+            .with_options(self.data.options())
+            .parse()?;
+        let reload = Reload::new(&self.data, self.root, &document.data, document.root);
+
+        #[cfg(feature = "serde-edits")]
+        let compression = self.data.compression();
+
+        let mut metadata = std::mem::take(self.data.metadata_mut());
+        metadata.remap(&reload);
+        let actor = std::mem::take(self.data.actor_mut());
+        let separator = self.data.separator().map(|id| self.data.str(id).to_vec());
+        let quoting = self.data.quoting().clone();
+        let placeholder = self.data.placeholder().clone();
+
+        *self = document;
+        *self.data.metadata_mut() = metadata;
+        *self.data.actor_mut() = actor;
+        self.data.set_quoting(quoting);
+        self.data.set_placeholder(placeholder);
+
+        if let Some(separator) = separator {
+            let separator = self.data.insert_str(separator);
+            self.data.set_separator(Some(separator));
+        }
+
+        #[cfg(feature = "serde-edits")]
+        self.data.set_compression(compression);
+
+        Ok(reload)
+    }
+
+    /// Set how strings are compressed when the document is serialized with the
+    /// `serde-edits` feature.
+    ///
+    /// See [`Compression`] for details.
+    #[cfg(feature = "serde-edits")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.data.set_compression(compression);
+    }
+
+    /// Bookmark the value `id` under the given `name`, replacing any value
+    /// previously bookmarked under that name.
+    ///
+    /// Bookmarks are part of the state of the document when it is serialized
+    /// with the `serde-edits` feature, so they resolve to the same values after
+    /// it has been deserialized. This allows long-lived state, such as that of
+    /// an automation tool, to durably reference values in a document by name.
+    /// Bookmarks are also carried over by [`Document::reload_from`] for values
+    /// which survive the reload, and are dropped when the value they reference
+    /// is removed.
+    ///
+    /// Returns `false` without bookmarking anything if `id` doesn't refer to a
+    /// value in the document. Use [`Document::bookmarked`] beforehand to get
+    /// the value which is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("spec:\n  replicas: 3\n")?;
+    ///
+    /// let replicas = doc
+    ///     .as_ref()
+    ///     .as_mapping()
+    ///     .and_then(|m| m.get("spec")?.as_mapping()?.get("replicas"))
+    ///     .context("missing replicas")?
+    ///     .id();
+    ///
+    /// assert!(doc.bookmark("replicas", replicas));
+    ///
+    /// let state = serde_yaml::to_string(&doc)?;
+    /// let mut doc: yaml::Document = serde_yaml::from_str(&state)?;
+    ///
+    /// let replicas = doc.bookmarked("replicas").context("missing bookmark")?;
+    /// doc.value_mut(replicas).set_u32(5);
+    /// assert_eq!(doc.to_string(), "spec:\n  replicas: 5\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "serde-edits")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+    pub fn bookmark(&mut self, name: &str, id: Id) -> bool {
+        if !self.data.contains(id) {
+            return false;
+        }
+
+        self.remove_bookmark(name);
+        let table = self.data.metadata_mut().get_mut::<Bookmarks>();
+
+        if let Some(bookmarks) = table.get_mut(id) {
+            bookmarks.0.insert(name.into());
+        } else {
+            table.insert(id, Bookmarks(BTreeSet::from([name.into()])));
+        }
+
+        true
+    }
+
+    /// Get the value bookmarked under the given `name` with
+    /// [`Document::bookmark`].
+    ///
+    /// Returns `None` if there is no such bookmark, or if the bookmarked value
+    /// has been removed from the document.
+    #[cfg(feature = "serde-edits")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+    #[must_use]
+    pub fn bookmarked(&self, name: &str) -> Option<Id> {
+        let table = self.data.metadata().get::<Bookmarks>()?;
+
+        table
+            .iter()
+            .find(|(_, bookmarks)| bookmarks.0.contains(name))
+            .map(|(id, _)| id)
+    }
+
+    /// Remove the bookmark with the given `name`, returning the value it
+    /// referenced if any.
+    #[cfg(feature = "serde-edits")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+    pub fn remove_bookmark(&mut self, name: &str) -> Option<Id> {
+        let id = self.bookmarked(name)?;
+        let table = self.data.metadata_mut().get_mut::<Bookmarks>();
+
+        if let Some(bookmarks) = table.get_mut(id) {
+            bookmarks.0.remove(name);
+
+            if bookmarks.0.is_empty() {
+                table.remove(id);
+            }
+        }
+
+        Some(id)
+    }
+
+    /// Iterate over all bookmarks in the document, ordered by name.
+    #[cfg(feature = "serde-edits")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+    pub fn bookmarks(&self) -> impl Iterator<Item = (&str, Id)> {
+        let mut bookmarks = Vec::new();
+
+        if let Some(table) = self.data.metadata().get::<Bookmarks>() {
+            for (id, names) in table {
+                bookmarks.extend(names.0.iter().map(|name| (name.as_ref(), id)));
+            }
+        }
+
+        bookmarks.sort_by_key(|&(name, _)| name);
+        bookmarks.into_iter()
+    }
+
+    /// Remove empty values from the document according to the given [`Prune`]
+    /// policy, returning the number of values which were removed.
+    ///
+    /// Pruning is recursive, so collections which only become empty because
+    /// their contents were pruned are removed as well. Entries are removed
+    /// from their parent mapping or sequence, the root of the document is
+    /// never removed.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`][crate::yaml::ErrorKind::Locked]
+    /// without modifying anything if a value which would be removed is
+    /// [locked][Document::lock] by another actor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: overlay
+    ///     labels: {}
+    ///     replicas:
+    ///     spec:
+    ///       volumes: []
+    ///       ports:
+    ///         - []
+    ///     image: nginx
+    ///     "
+    /// )?;
+    ///
+    /// assert_eq!(doc.prune_empty(yaml::Prune::Collections)?, 5);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: overlay
+    ///     replicas:
+    ///     image: nginx
+    ///     "
+    /// );
+    ///
+    /// assert_eq!(doc.prune_empty(yaml::Prune::CollectionsAndNulls)?, 1);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: overlay
+    ///     image: nginx
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn prune_empty(&mut self, policy: Prune) -> Result<usize, Error> {
+        prune::prune(&mut self.data, self.root, policy)
+    }
+
+    /// Replace every occurrence of `pattern` with `replacement` in all string
+    /// values of the document, returning the number of strings which were
+    /// modified.
+    ///
+    /// Modified strings keep their quoting style, so a single-quoted string
+    /// stays single-quoted and a double-quoted string stays double-quoted. A
+    /// bare string stays bare unless the replacement requires it to be quoted.
+    /// Mapping keys and block strings are left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`][crate::yaml::ErrorKind::Locked]
+    /// without modifying anything if a value which would be modified is
+    /// [locked][Document::lock] by another actor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     image: old.registry.io/app:1.0
+    ///     sidecars:
+    ///       - 'old.registry.io/proxy'
+    ///       - "old.registry.io/logger"
+    ///     registry: old.registry.io
+    ///     "#
+    /// )?;
+    ///
+    /// assert_eq!(doc.replace_in_strings("old.registry.io", "new.registry.io")?, 4);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     image: new.registry.io/app:1.0
+    ///     sidecars:
+    ///       - 'new.registry.io/proxy'
+    ///       - "new.registry.io/logger"
+    ///     registry: new.registry.io
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// Bare strings are quoted if they would otherwise be read as a different
+    /// kind of value:
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("enabled: yes")?;
+    /// assert_eq!(doc.replace_in_strings("yes", "true")?, 1);
+    /// assert_eq!(doc.to_string(), "enabled: 'true'");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn replace_in_strings<P, R>(&mut self, pattern: P, replacement: R) -> Result<usize, Error>
+    where
+        P: AsRef<[u8]>,
+        R: AsRef<[u8]>,
+    {
+        let pattern = pattern.as_ref();
+        let replacement = replacement.as_ref();
+
+        if pattern.is_empty() {
+            return Ok(0);
+        }
+
+        self.replace_in_strings_with(|string| {
+            string
+                .contains_str(pattern)
+                .then(|| string.replace(pattern, replacement))
+        })
+    }
+
+    /// Replace string values in the document using the given closure,
+    /// returning the number of strings which were modified.
+    ///
+    /// The closure is called with the contents of every string value and
+    /// returns the new contents, or `None` if the string should be left
+    /// unchanged. This can be used to perform replacements with a regular
+    /// expression engine of your choice.
+    ///
+    /// Like [`Document::replace_in_strings`], modified strings keep their
+    /// quoting style.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`][crate::yaml::ErrorKind::Locked]
+    /// without modifying anything if a value which would be modified is
+    /// [locked][Document::lock] by another actor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[app:1.0, 'db:2.3', proxy]")?;
+    ///
+    /// let count = doc.replace_in_strings_with(|string| {
+    ///     let (name, _) = string.split_at(string.iter().position(|&b| b == b':')?);
+    ///     Some([name, b":latest"].concat())
+    /// })?;
+    ///
+    /// assert_eq!(count, 2);
+    /// assert_eq!(doc.to_string(), "[app:latest, 'db:latest', proxy]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn replace_in_strings_with<F>(&mut self, f: F) -> Result<usize, Error>
+    where
+        F: FnMut(&[u8]) -> Option<Vec<u8>>,
+    {
+        replace::replace(&mut self.data, self.root, f)
+    }
+
+    /// Reindent the whole document from levels of `from_width` spaces to
+    /// levels of `to_width` spaces.
+    ///
+    /// Only indentation is rewritten, which includes the indentation of
+    /// comments and of block strings. The contents of scalars, comments and
+    /// the quoting of strings are left untouched. Indentation which isn't a
+    /// whole number of levels keeps its remainder, so it stays aligned within
+    /// its level. Block collections which start on the same line as the dash
+    /// of a sequence item are realigned by adjusting the spacing after the
+    /// dash.
+    ///
+    /// Nothing is changed if `from_width` is zero.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`][crate::yaml::ErrorKind::Locked]
+    /// without modifying anything if any part of the document is
+    /// [locked][Document::lock] by another actor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    /// server:
+    ///   ## the port to listen on
+    ///   port: 8080
+    ///   routes:
+    ///     - path: /
+    ///       methods: [GET,  POST]
+    ///   banner: |
+    ///     Hello
+    ///       World
+    /// "
+    /// )?;
+    ///
+    /// let banner = |doc: &yaml::Document| {
+    ///     let server = doc.as_ref().as_mapping()?.get("server")?;
+    ///     Some(server.as_mapping()?.get("banner")?.as_str()?.to_owned())
+    /// };
+    ///
+    /// let before = banner(&doc);
+    /// doc.reindent_all(2, 4)?;
+    /// assert_eq!(banner(&doc), before);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    /// server:
+    ///     ## the port to listen on
+    ///     port: 8080
+    ///     routes:
+    ///         -   path: /
+    ///             methods: [GET,  POST]
+    ///     banner: |
+    ///         Hello
+    ///           World
+    /// "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn reindent_all(&mut self, from_width: usize, to_width: usize) -> Result<(), Error> {
+        lock::check(&self.data, self.root)?;
+
+        if from_width == 0 {
+            return Ok(());
+        }
+
+        let widths = reindent::Widths::new(from_width, to_width);
+        reindent::value(&mut self.data, self.root, None, widths);
+        self.suffix = reindent::lines(&mut self.data, self.suffix, widths);
+        Ok(())
+    }
+
+    /// Insert a space after the `#` of every comment in the document which
+    /// lacks one, so that `#comment` becomes `# comment`.
+    ///
+    /// Comments made up of several `#` characters like `##comment` get their
+    /// space after the last one, and comments starting with `#!` are left
+    /// alone. Returns the number of whitespace strings which were modified.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("#top\nname: app\n#name\nversion: 1\n")?;
+    /// assert_eq!(doc.space_comments(), 2);
+    /// assert_eq!(doc.to_string(), "# top\nname: app\n# name\nversion: 1\n");
+    /// assert_eq!(doc.space_comments(), 0);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn space_comments(&mut self) -> usize {
+        comments::apply(&mut self.data, self.root, &mut self.suffix, comments::Space)
+    }
+
+    /// Align trailing comments, which are comments following other content on
+    /// the same line, so that they start at the given `column`.
+    ///
+    /// Comments whose content already extends past the column are separated
+    /// from it by a single space. Comments on lines of their own are left
+    /// alone. Returns the number of whitespace strings which were modified.
+    ///
+    /// Note that comments following a plain scalar on the same line are
+    /// treated as part of that scalar, and are therefore not aligned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(r"
+    /// server: ## the server
+    ///   port: 8080
+    ///   routes:     ## the routes
+    ///     - /
+    /// ")?;
+    ///
+    /// assert_eq!(doc.align_comments(12), 2);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    /// server:     ## the server
+    ///   port: 8080
+    ///   routes:   ## the routes
+    ///     - /
+    /// "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn align_comments(&mut self, column: usize) -> usize {
+        comments::apply(
+            &mut self.data,
+            self.root,
+            &mut self.suffix,
+            comments::Align(column),
+        )
+    }
+
+    /// Remove every comment in the document.
+    ///
+    /// Lines which only contain a comment are removed entirely, while trailing
+    /// comments are removed together with the whitespace preceding them.
+    /// Returns the number of whitespace strings which were modified.
+    ///
+    /// Note that comments following a plain scalar on the same line are
+    /// treated as part of that scalar, and are therefore not removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(r"
+    /// ## the server
+    /// server: ## trailing
+    ///   ## the port
+    ///   port: 8080
+    ///   tags: [a, ## first
+    ///     b]
+    /// ## the end
+    /// ")?;
+    ///
+    /// assert_eq!(doc.strip_comments(), 4);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    /// server:
+    ///   port: 8080
+    ///   tags: [a,
+    ///     b]
+    /// "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn strip_comments(&mut self) -> usize {
+        comments::apply(&mut self.data, self.root, &mut self.suffix, comments::Strip)
+    }
+
+    /// Write the bytes of the document to the given `output`.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     string
+    ///     "
+    /// )?;
+    ///
+    /// let mut mapping = doc.as_mut().make_mapping()?;
+    /// mapping.insert_u32("first", 1);
+    /// mapping.insert_u32("second", 2);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    ///
+    /// assert_eq!(
+    ///     &out[..],
+    ///     br"
+    ///     first: 1
+    ///     second: 2
+    ///     "
+    /// );
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     first: second
+    ///     "
+    /// )?;
+    ///
+    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| m.get_into_mut("first")?.make_mapping().ok()).context("missing first")?;
+    /// mapping.insert_u32("second", 2);
+    /// mapping.insert_u32("third", 3);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    ///
+    /// assert_eq!(
+    ///     &out[..],
+    ///     br"
+    ///     first:
+    ///       second: 2
+    ///       third: 3
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to<O>(&self, output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        encoding::write(self.bom, output, |output| self.write_body(output))
+    }
+
+    /// Write the document without its byte order mark.
+    fn write_body(&self, output: &mut dyn io::Write) -> io::Result<()> {
+        if let Some(header) = self.header {
+            output.write_all(self.data.str(header))?;
+        }
+
+        if !raw::is_hidden_root_prefix(&self.data, self.root) {
+            raw::write_prefix(&self.data, self.root, &mut *output)?;
+        }
+
+        self.data
+            .raw(self.root)
+            .write_to(&self.data, &mut *output)?;
+        output.write_all(self.data.str(self.suffix))?;
+
+        if let Some(end) = self.end {
+            output.write_all(stream::END)?;
+            output.write_all(self.data.str(end))?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the bytes of the document to the given `output` using the given
+    /// [`WriteOptions`], without modifying the document.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     database:
+    ///       host: localhost
+    ///       password: hunter2
+    ///     replicas:
+    ///       - password: swordfish
+    ///     "
+    /// )?;
+    ///
+    /// let options = yaml::WriteOptions::new().scalar_renderer(|_, path| {
+    ///     (path.last().map(String::as_str) == Some("password")).then(|| String::from("'***'"))
+    /// });
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to_with(&mut out, options)?;
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(out)?,
+    ///     r"
+    ///     database:
+    ///       host: localhost
+    ///       password: '***'
+    ///     replicas:
+    ///       - password: '***'
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to_with<O>(&self, output: O, mut options: WriteOptions<'_>) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        let mut hook = options.hook();
+
+        encoding::write(self.bom, output, |output| {
+            if let Some(header) = self.header {
+                output.write_all(self.data.str(header))?;
+            }
+
+            if !raw::is_hidden_root_prefix(&self.data, self.root) {
+                raw::write_prefix(&self.data, self.root, &mut *output)?;
+            }
+
+            hook.write_value(&self.data, self.root, &mut *output)?;
+            output.write_all(self.data.str(self.suffix))?;
+
+            if let Some(end) = self.end {
+                output.write_all(stream::END)?;
+                output.write_all(self.data.str(end))?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Record the current output of the document as its original, which is
+    /// what [`Document::diff_against_original`] compares against.
+    ///
+    /// Documents don't keep a copy of their input unless asked to, since it
+    /// would be held in memory and serialized with the `serde-edits` feature
+    /// for as long as the document is. Taking another snapshot replaces the
+    /// previous one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: 1\n")?;
+    /// doc.snapshot_original();
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.insert_u32("b", 2);
+    /// assert_ne!(doc.diff_against_original(), "");
+    ///
+    /// doc.snapshot_original();
+    /// assert_eq!(doc.diff_against_original(), "");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn snapshot_original(&mut self) {
+        let mut output = Vec::new();
+        // Writing to a vector never fails.
+        _ = self.write_body(&mut output);
+        self.original = Some(self.data.insert_str(output));
+    }
+
+    /// Render the changes made to the document since it was
+    /// [snapshotted][Document::snapshot_original] as a line-based unified diff
+    /// of the original output against the current output, such as for
+    /// previewing edits before they are written.
+    ///
+    /// Returns an empty string if the output is the same as the original. If
+    /// no snapshot has been taken, the original is empty. A byte order mark is
+    /// not considered part of the document, and documents encoded as UTF-16
+    /// are compared after they've been decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("name: app\nversion: 1.0.0\nreplicas: 2\n")?;
+    /// doc.snapshot_original();
+    /// assert_eq!(doc.diff_against_original(), "");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.get_mut("replicas").context("missing replicas")?.set_u32(3);
+    /// root.insert_bool("debug", true);
+    ///
+    /// assert_eq!(
+    ///     doc.diff_against_original(),
+    ///     "--- original\n+++ modified\n@@ -1,3 +1,4 @@\n name: app\n version: 1.0.0\n-replicas: 2\n+replicas: 3\n+debug: true\n"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn diff_against_original(&self) -> String {
+        let original = self.original.map_or(&b""[..], |id| self.data.str(id));
+        let mut output = Vec::new();
+        // Writing to a vector never fails.
+        _ = self.write_body(&mut output);
+        diff::unified(original, &output)
+    }
+
+    /// Write the document as compact JSON to the given `output`.
+    ///
+    /// This re-renders the document from its tree, so none of the original
+    /// formatting is preserved. Numbers are normalized so that they are valid
+    /// JSON, without rounding integers or floats which don't fit in an `i128`
+    /// or an `f64`.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it, or an error of the kind [`io::ErrorKind::InvalidData`] if the
+    /// document contains values which cannot be represented in JSON, such as
+    /// strings which are not valid UTF-8 or recursive aliases. Other aliases
+    /// are expanded into the value they refer to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     name: Descartes
+    ///     year: 01596
+    ///     alive: false
+    ///     spouse: ~
+    ///     books:
+    ///       - "Musicae Compendium"
+    ///       - 'La "Géométrie"'
+    ///     "#
+    /// )?;
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_json_to(&mut out)?;
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(out)?,
+    ///     r#"{"name":"Descartes","year":1596,"alive":false,"spouse":null,"books":["Musicae Compendium","La \"Géométrie\""]}"#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    ///
+    /// Values which can't be represented in JSON result in an error:
+    ///
+    /// ```
+    /// use std::io;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(b"key: \xff")?;
+    /// let error = doc.write_json_to(Vec::new()).unwrap_err();
+    /// assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_json_to<O>(&self, mut output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        json::write_to(&self.data, self.root, &mut output)
+    }
+
+    /// Dump the internal tree structure of the document.
+    ///
+    /// Every node is written on its own line, indented by its depth in the
+    /// tree. Each line contains the [`Id`] of the node, its kind, its span in
+    /// the output of [`Document::write_to`] excluding its prefix, and the
+    /// whitespace prefix preceding it. Scalars also include their text,
+    /// collections their style and tag, and mapping items their key.
+    ///
+    /// The dump is deterministic for a given input and series of edits, which
+    /// makes it useful to include in bug reports about formatting. The format
+    /// is intended for humans and might change between versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: 1\nb: [x, 'y']\n")?;
+    ///
+    /// assert_eq!(
+    ///     doc.dump_tree(),
+    ///     r#"00000000 mapping 0..16 prefix="" style=block indent=0
+    ///   00000001 mapping-item 0..4 prefix="" key="a"
+    ///     00000002 number 3..4 prefix=" " text="1"
+    ///   00000003 mapping-item 5..16 prefix="\n" key="b"
+    ///     00000004 sequence 8..16 prefix=" " style=inline
+    ///       00000005 sequence-item 9..10 prefix=""
+    ///         00000006 string 9..10 prefix="" style=bare text="x"
+    ///       00000007 sequence-item 12..15 prefix=" "
+    ///         00000008 string 12..15 prefix="" style=original text="\'y\'"
+    /// "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn dump_tree(&self) -> std::string::String {
+        dump::dump(&self.data, self.root)
+    }
+
+    /// Build an index of every mapping key in the document, which can be
+    /// searched by prefix.
+    ///
+    /// See [`KeyIndex`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a:\n  name: x\nb:\n  - name: y\n    namespace: z\n")?;
+    /// let index = doc.key_index();
+    ///
+    /// let keys = index.with_prefix("name").map(|(key, _)| key).collect::<Vec<_>>();
+    /// assert_eq!(keys, ["name", "namespace"]);
+    ///
+    /// let names = index.get("name").iter().flat_map(|id| doc.value(*id).as_str()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["x", "y"]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn key_index(&self) -> KeyIndex {
+        key_index::build(&self.data, self.root)
+    }
+
+    /// Build the outline of the document.
+    ///
+    /// The outline is a tree of [`Outline`] entries, one for every mapping item
+    /// and sequence item, each with its path, its span in the output of
+    /// [`Document::write_to`] and the kind of its value. The returned entry
+    /// refers to the root of the document and has an empty path.
+    ///
+    /// This is intended for editor tooling, such as building a symbol outline
+    /// or computing folding ranges by mapping spans to lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: app\nports:\n  - 80\n  - 443\n")?;
+    /// let outline = doc.outline();
+    ///
+    /// assert_eq!(outline.kind(), yaml::OutlineKind::Mapping);
+    /// assert!(outline.path().is_empty());
+    ///
+    /// let [name, ports] = outline.children() else {
+    ///     panic!("expected two entries");
+    /// };
+    ///
+    /// assert_eq!(name.name(), Some("name"));
+    /// assert_eq!(name.span(), 0..9);
+    ///
+    /// assert_eq!(ports.kind(), yaml::OutlineKind::Sequence);
+    /// assert_eq!(ports.span(), 10..31);
+    ///
+    /// let paths = ports.children().iter().map(|e| e.path()).collect::<Vec<_>>();
+    /// assert_eq!(paths, [["ports", "0"], ["ports", "1"]]);
+    /// assert_eq!(ports.children()[1].span(), 26..31);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn outline(&self) -> Outline {
+        outline::outline(&self.data, self.root, self.header_len())
+    }
+
+    /// Count the number of values in the document, including the keys of
+    /// mappings.
+    ///
+    /// Together with [`Document::max_depth`] and [`Document::scalar_bytes`]
+    /// this can be used to enforce quotas on documents provided by users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: app\nports: [80, 443]\n")?;
+    /// assert_eq!(doc.node_count(), 7);
+    ///
+    /// let doc = yaml::from_slice("hello")?;
+    /// assert_eq!(doc.node_count(), 1);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        stats::stats(&self.data, self.root).nodes
+    }
+
+    /// Get the deepest nesting of mappings and sequences in the document.
+    ///
+    /// A document which only consists of a scalar has a depth of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: app\nports: [80, 443]\n")?;
+    /// assert_eq!(doc.max_depth(), 2);
+    ///
+    /// let doc = yaml::from_slice("hello")?;
+    /// assert_eq!(doc.max_depth(), 0);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn max_depth(&self) -> usize {
+        stats::stats(&self.data, self.root).depth
+    }
+
+    /// Count the number of bytes of scalar content in the document, including
+    /// the keys of mappings.
+    ///
+    /// This counts the content of scalars after quotes and escapes have been
+    /// processed, so `"a\tb"` counts as three bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("name: \"a\\tb\"\nports: [80, 443]\n")?;
+    /// assert_eq!(doc.scalar_bytes(), 4 + 3 + 5 + 2 + 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn scalar_bytes(&self) -> usize {
+        stats::stats(&self.data, self.root).scalar_bytes
+    }
+
+    /// Get the length of the header preceding the root value in the output.
+    pub(crate) fn header_len(&self) -> usize {
+        self.header.map_or(0, |header| self.data.str(header).len())
+    }
+
+    /// Produce a canonical rendering of the document.
+    ///
+    /// **This is destructive**, none of the original formatting of the
+    /// document is preserved. Comments are removed, collections are emitted
+    /// in block style with two spaces of indentation, numbers are normalized
+    /// without rounding them and strings are only left unquoted if they can't
+    /// be mistaken for anything else, otherwise they are double-quoted. Bytes
+    /// which are not valid UTF-8 are escaped. Aliases are replaced with the
+    /// value they refer to, while aliases which can't be replaced because they
+    /// refer to a missing anchor or to a value containing the alias are kept as
+    /// `*name`.
+    ///
+    /// Two documents which are structurally equal produce the same canonical
+    /// rendering, which makes it suitable for hashing, signing and comparing
+    /// configurations. Keys are emitted in the order they appear in the
+    /// document, use [`Document::to_canonical_string_with`] to sort them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r#"
+    ///     name:    'Descartes'
+    ///     born: 001596
+    ///     books: [Musicae Compendium, "1637"]
+    ///     empty: {}
+    ///     "#
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_canonical_string(),
+    ///     "name: Descartes\nborn: 1596\nbooks:\n  - Musicae Compendium\n  - \"1637\"\nempty: {}\n"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_canonical_string(&self) -> std::string::String {
+        canonical::to_string(&self.data, self.root, KeyOrder::Preserve)
+    }
+
+    /// Produce a canonical rendering of the document using a custom
+    /// [`KeyOrder`].
+    ///
+    /// **This is destructive**, see [`Document::to_canonical_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice(
+    ///     r"
+    ///     second: 2
+    ///     first:
+    ///     - {b: 2, a: 1}
+    ///     "
+    /// )?;
+    ///
+    /// let b = yaml::from_slice("{first: [{a: 1, b: 2}], second: 2}")?;
+    ///
+    /// let a = a.to_canonical_string_with(yaml::KeyOrder::Sorted);
+    /// let b = b.to_canonical_string_with(yaml::KeyOrder::Sorted);
+    ///
+    /// assert_eq!(a, "first:\n  - a: 1\n    b: 2\nsecond: 2\n");
+    /// assert_eq!(a, b);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_canonical_string_with(&self, order: KeyOrder) -> std::string::String {
+        canonical::to_string(&self.data, self.root, order)
+    }
+
+    /// Calculate a fingerprint of the document, which combines its
+    /// [structural hash][Value::structural_hash] with the style it is written
+    /// in.
+    ///
+    /// The style covers how scalars are spelled, how strings are quoted,
+    /// whether collections are inline, tags and the order of keys. Whitespace,
+    /// indentation and comments do not affect the fingerprint.
+    ///
+    /// This is intended for generators which want to know if the content they
+    /// would produce is already present in a file, so that it can be left
+    /// alone even if it has been reformatted or commented by a user. See
+    /// [`Document::matches_fingerprint`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let generated = yaml::from_slice("name: app\nports: [80, 443]\n")?;
+    ///
+    /// let edited = yaml::from_slice(
+    ///     r"
+    ///     ## edited by hand
+    ///     name:   app
+    ///     ports: [ 80,  443 ]
+    ///     "
+    /// )?;
+    ///
+    /// assert_eq!(generated.fingerprint(), edited.fingerprint());
+    ///
+    /// let quoted = yaml::from_slice("name: 'app'\nports: [80, 443]\n")?;
+    /// assert_ne!(generated.fingerprint(), quoted.fingerprint());
+    ///
+    /// let reordered = yaml::from_slice("ports: [80, 443]\nname: app\n")?;
+    /// assert_ne!(generated.fingerprint(), reordered.fingerprint());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        value::fingerprint(&self.data, self.root)
+    }
+
+    /// Test if the document matches a fingerprint previously produced by
+    /// [`Document::fingerprint`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let generated = yaml::from_slice("version: 1\n")?;
+    /// let fingerprint = generated.fingerprint();
+    ///
+    /// let mut doc = yaml::from_slice("## do not edit\nversion:    1\n")?;
+    /// assert!(doc.matches_fingerprint(fingerprint));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.insert_u32("version", 2);
+    /// assert!(!doc.matches_fingerprint(fingerprint));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn matches_fingerprint(&self, fingerprint: u64) -> bool {
+        self.fingerprint() == fingerprint
+    }
+
+    // Display helper for document.
+    fn display(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use std::fmt::Display;
+
+        if self.bom.is_some() {
+            '\u{feff}'.fmt(f)?;
+        }
+
+        if let Some(header) = self.header {
+            self.data.str(header).fmt(f)?;
+        }
+
+        if !raw::is_hidden_root_prefix(&self.data, self.root) {
+            raw::display_prefix(&self.data, self.root, f)?;
+        }
+
+        self.data.raw(self.root).display(&self.data, f, None)?;
+        self.data.str(self.suffix).fmt(f)?;
+
+        if let Some(end) = self.end {
+            BStr::new(stream::END).fmt(f)?;
+            self.data.str(end).fmt(f)?;
+        }
 
-        self.data.prefix(self.root).fmt(f)?;
-        self.data.raw(self.root).display(&self.data, f, None)?;
-        self.data.str(self.suffix).fmt(f)?;
         Ok(())
     }
 }
@@ -290,8 +2670,9 @@ impl fmt::Display for Document {
         // If we're running in debug mode, assert that the produced document
         // matches whatever would've been produced through `Document::write_to`.
         //
-        // This is only enabled with `--cfg nondestructive_write_to_eq`.
-        if cfg!(nondestructive_write_to_eq) {
+        // This is only enabled with `--cfg nondestructive_write_to_eq`, and
+        // doesn't apply to documents which are written as UTF-16.
+        if cfg!(nondestructive_write_to_eq) && !self.bom.is_some_and(Bom::is_utf16) {
             use bstr::BStr;
             use std::fmt::Write;
 
@@ -330,7 +2711,9 @@ impl fmt::Display for Document {
 impl fmt::Debug for Document {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Document")
+            .field("header", &self.header)
             .field("suffix", &self.suffix)
+            .field("end", &self.end)
             .field("root", &self.root)
             .finish_non_exhaustive()
     }
@@ -0,0 +1,206 @@
+use core::fmt::{self, Write};
+use core::ops::Range;
+use std::collections::HashMap;
+
+use bstr::BStr;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+
+/// Dump the tree structure of the value `id`.
+pub(crate) fn dump(data: &Data, id: Id) -> String {
+    let mut out = Out::default();
+    node(data, id, data.prefix(id).len(), 0, &mut out);
+    out.text
+}
+
+/// Compute the span of every node in the value `id`, in the output of writing
+/// it excluding its prefix.
+///
+/// Spans are the same as the ones included in [`dump`].
+pub(crate) fn spans(data: &Data, id: Id) -> HashMap<Id, Range<usize>> {
+    let mut out = Out::default();
+    node(data, id, data.prefix(id).len(), 0, &mut out);
+    out.spans
+}
+
+/// The output of a dump.
+#[derive(Default)]
+struct Out {
+    text: String,
+    spans: HashMap<Id, Range<usize>>,
+}
+
+impl Write for Out {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.text.push_str(s);
+        Ok(())
+    }
+}
+
+/// Dump the node `id` which starts at `start` in the output, returning where
+/// it ends.
+///
+/// The node starts with its anchor and tag, if it has them.
+fn node(data: &Data, id: Id, start: usize, depth: usize, out: &mut Out) -> usize {
+    let raw = data.raw(id);
+    let text = render(|o| raw.write_to(data, o));
+    let anchor = data.layout(id).anchor;
+
+    let tag = data.layout(id).tag;
+
+    let anchor_len = anchor.map_or(0, |anchor| {
+        1 + data.str(anchor.name).len() + data.str(anchor.suffix).len()
+    });
+
+    let tag_len = tag.map_or(0, |tag| {
+        data.str(tag.name).len() + data.str(tag.suffix).len()
+    });
+
+    let end = start + anchor_len + tag_len + text.len();
+    out.spans.insert(id, start..end);
+
+    let kind = match raw {
+        Raw::Null(..) => "null",
+        Raw::Boolean(..) => "boolean",
+        Raw::Number(..) => "number",
+        Raw::String(..) => "string",
+        Raw::Mapping(..) => "mapping",
+        Raw::MappingItem(..) => "mapping-item",
+        Raw::Sequence(..) => "sequence",
+        Raw::SequenceItem(..) => "sequence-item",
+        Raw::Alias(..) => "alias",
+    };
+
+    let _ = write!(
+        out,
+        "{:indent$}{id} {kind} {start}..{end} prefix={:?}",
+        "",
+        data.prefix(id),
+        indent = depth * 2,
+    );
+
+    if let Some(anchor) = anchor {
+        let _ = write!(out, " anchor={:?}", data.str(anchor.name));
+    }
+
+    if let Some(tag) = tag {
+        let _ = write!(out, " tag={:?}", data.str(tag.name));
+    }
+
+    let start = start + anchor_len + tag_len;
+    let depth = depth + 1;
+
+    match raw {
+        Raw::Null(..) | Raw::Boolean(..) | Raw::Number(..) | Raw::Alias(..) => {
+            let _ = writeln!(out, " text={:?}", BStr::new(&text));
+        }
+        Raw::String(raw) => {
+            let style = match raw.kind {
+                raw::RawStringKind::Bare => "bare",
+                raw::RawStringKind::Single => "single",
+                raw::RawStringKind::Double => "double",
+                raw::RawStringKind::Original => "original",
+                raw::RawStringKind::Multiline { .. } => "multiline",
+            };
+
+            let _ = writeln!(out, " style={style} text={:?}", BStr::new(&text));
+        }
+        Raw::Mapping(raw) => {
+            let inline = matches!(raw.kind, raw::MappingKind::Inline { .. });
+            let cursor = collection(data, raw.tag.as_ref(), raw.indent, inline, start, out);
+            items(data, &raw.items, cursor, depth, inline, false, out);
+        }
+        Raw::MappingItem(raw) => {
+            let key = render(|o| raw.key.write_to(data, o));
+            let _ = writeln!(out, " key={:?}", BStr::new(&key));
+
+            let key = match raw.kind {
+                raw::MappingItemKind::Pair => start + key.len() + 1,
+                raw::MappingItemKind::Explicit { separator } => {
+                    start + 1 + data.str(separator).len() + key.len()
+                }
+                raw::MappingItemKind::Key => start + key.len(),
+                raw::MappingItemKind::Complex { key, colon } => {
+                    let cursor = start + 1 + data.prefix(key).len();
+                    let end = node(data, key, cursor, depth, out);
+                    end + colon.map_or(0, |colon| data.str(colon).len() + 1)
+                }
+            };
+
+            let cursor = key + data.prefix(raw.value).len();
+            node(data, raw.value, cursor, depth, out);
+        }
+        Raw::Sequence(raw) => {
+            let inline = matches!(raw.kind, raw::SequenceKind::Inline { .. });
+            let cursor = collection(data, raw.tag.as_ref(), raw.indent, inline, start, out);
+            items(data, &raw.items, cursor, depth, inline, !inline, out);
+        }
+        Raw::SequenceItem(raw) => {
+            let _ = writeln!(out);
+            let cursor = start + data.prefix(raw.value).len();
+            node(data, raw.value, cursor, depth, out);
+        }
+    }
+
+    end
+}
+
+/// Finish the line of a collection, returning where its first item starts.
+fn collection(
+    data: &Data,
+    tag: Option<&raw::Tag>,
+    indent: usize,
+    inline: bool,
+    start: usize,
+    out: &mut Out,
+) -> usize {
+    let mut cursor = start;
+
+    if inline {
+        let _ = write!(out, " style=inline");
+    } else {
+        let _ = write!(out, " style=block indent={indent}");
+    }
+
+    if let Some(tag) = tag {
+        let _ = write!(out, " tag={:?}", data.str(tag.name));
+        cursor += data.str(tag.name).len() + data.str(tag.suffix).len();
+    }
+
+    let _ = writeln!(out);
+    cursor + usize::from(inline)
+}
+
+/// Dump the items of a collection, where block sequence items are preceded by
+/// a `-` marker and inline items are separated by a `,`.
+fn items(
+    data: &Data,
+    items: &[Id],
+    mut cursor: usize,
+    depth: usize,
+    inline: bool,
+    marker: bool,
+    out: &mut Out,
+) {
+    for (index, &item) in items.iter().enumerate() {
+        if index > 0 && inline {
+            cursor += 1;
+        }
+
+        cursor += data.prefix(item).len() + usize::from(marker);
+        cursor = node(data, item, cursor, depth, out);
+    }
+}
+
+/// Render something into a buffer.
+fn render<F>(f: F) -> Vec<u8>
+where
+    F: FnOnce(&mut Vec<u8>) -> std::io::Result<()>,
+{
+    let mut out = Vec::new();
+    // Writing into a vector can't fail.
+    let _ = f(&mut out);
+    out
+}
@@ -0,0 +1,152 @@
+//! Detection of byte order marks and encoding of UTF-16 documents.
+
+use core::ops::Range;
+use std::io;
+
+use bstr::ByteSlice;
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Serialize};
+
+/// The byte order mark a [`Document`] starts with.
+///
+/// A byte order mark is detected when a document is parsed. Documents
+/// starting with a UTF-16 byte order mark are decoded before they are parsed
+/// and encoded as UTF-16 again when they are written using
+/// [`Document::write_to`].
+///
+/// [`Document`]: crate::yaml::Document
+/// [`Document::write_to`]: crate::yaml::Document::write_to
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice(b"\xef\xbb\xbfa: 1\n")?;
+/// assert_eq!(doc.bom(), Some(yaml::Bom::Utf8));
+/// assert_eq!(doc.as_ref().as_mapping().and_then(|m| m.get("a")?.as_u32()), Some(1));
+///
+/// let doc = yaml::from_slice(b"\xff\xfea\x00:\x00 \x001\x00\n\x00")?;
+/// assert_eq!(doc.bom(), Some(yaml::Bom::Utf16Le));
+/// assert_eq!(doc.as_ref().as_mapping().and_then(|m| m.get("a")?.as_u32()), Some(1));
+///
+/// let mut out = Vec::new();
+/// doc.write_to(&mut out)?;
+/// assert_eq!(out, b"\xff\xfea\x00:\x00 \x001\x00\n\x00");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Bom {
+    /// A UTF-8 byte order mark, `EF BB BF`.
+    Utf8,
+    /// A little-endian UTF-16 byte order mark, `FF FE`.
+    Utf16Le,
+    /// A big-endian UTF-16 byte order mark, `FE FF`.
+    Utf16Be,
+}
+
+impl Bom {
+    /// Detect the byte order mark at the start of `input`.
+    pub(crate) fn detect(input: &[u8]) -> Option<Self> {
+        match input {
+            [0xef, 0xbb, 0xbf, ..] => Some(Bom::Utf8),
+            [0xff, 0xfe, ..] => Some(Bom::Utf16Le),
+            [0xfe, 0xff, ..] => Some(Bom::Utf16Be),
+            _ => None,
+        }
+    }
+
+    /// Get the bytes of the byte order mark.
+    #[must_use]
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Bom::Utf8 => b"\xef\xbb\xbf",
+            Bom::Utf16Le => b"\xff\xfe",
+            Bom::Utf16Be => b"\xfe\xff",
+        }
+    }
+
+    /// Test if the byte order mark indicates that the document is encoded as
+    /// UTF-16.
+    #[must_use]
+    pub fn is_utf16(self) -> bool {
+        matches!(self, Bom::Utf16Le | Bom::Utf16Be)
+    }
+
+    fn unit(self, bytes: [u8; 2]) -> u16 {
+        match self {
+            Bom::Utf16Be => u16::from_be_bytes(bytes),
+            _ => u16::from_le_bytes(bytes),
+        }
+    }
+
+    fn unit_bytes(self, unit: u16) -> [u8; 2] {
+        match self {
+            Bom::Utf16Be => unit.to_be_bytes(),
+            _ => unit.to_le_bytes(),
+        }
+    }
+
+    /// Decode UTF-16 `input` following the byte order mark into UTF-8.
+    ///
+    /// On errors, returns the range of the offending bytes in `input`.
+    pub(crate) fn decode(self, input: &[u8]) -> Result<Vec<u8>, Range<usize>> {
+        let mut chunks = input.chunks_exact(2);
+        let units = chunks
+            .by_ref()
+            .map(|c| self.unit(c.try_into().unwrap_or_default()));
+
+        let mut output = Vec::with_capacity(input.len());
+        let mut buf = [0; 4];
+        let mut n = 0;
+
+        for c in char::decode_utf16(units) {
+            let Ok(c) = c else {
+                return Err(n..n + 2);
+            };
+
+            output.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            n += c.len_utf16() * 2;
+        }
+
+        if !chunks.remainder().is_empty() {
+            return Err(input.len() - 1..input.len());
+        }
+
+        Ok(output)
+    }
+}
+
+/// Write the output produced by `f` prefixed with `bom`, encoding it as
+/// UTF-16 if the byte order mark calls for it.
+pub(crate) fn write<O, F>(bom: Option<Bom>, mut output: O, f: F) -> io::Result<()>
+where
+    O: io::Write,
+    F: FnOnce(&mut dyn io::Write) -> io::Result<()>,
+{
+    let Some(bom) = bom else {
+        return f(&mut output);
+    };
+
+    output.write_all(bom.as_bytes())?;
+
+    if !bom.is_utf16() {
+        return f(&mut output);
+    }
+
+    let mut buf = Vec::new();
+    f(&mut buf)?;
+
+    let mut encoded = Vec::with_capacity(buf.len() * 2);
+    let mut units = [0; 2];
+
+    for c in buf.chars() {
+        for &unit in c.encode_utf16(&mut units).iter() {
+            encoded.extend_from_slice(&bom.unit_bytes(unit));
+        }
+    }
+
+    output.write_all(&encoded)
+}
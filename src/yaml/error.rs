@@ -2,19 +2,44 @@
 
 use core::fmt;
 use core::ops::Range;
+use std::io;
 
 /// An error raised by the YAML module.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Error {
     span: Range<usize>,
     kind: ErrorKind,
+    suggestion: Option<&'static str>,
 }
 
 impl Error {
     /// Construct a new error.
     #[inline]
     pub(crate) const fn new(span: Range<usize>, kind: ErrorKind) -> Self {
-        Self { span, kind }
+        Self {
+            span,
+            kind,
+            suggestion: None,
+        }
+    }
+
+    /// Attach a suggestion to the error.
+    #[inline]
+    pub(crate) const fn with_suggestion(self, suggestion: &'static str) -> Self {
+        Self {
+            suggestion: Some(suggestion),
+            ..self
+        }
+    }
+
+    /// Move the span of the error forward by `n` bytes, for errors raised
+    /// while parsing a part of a larger input.
+    #[inline]
+    pub(crate) fn offset(self, n: usize) -> Self {
+        Self {
+            span: self.span.start.saturating_add(n)..self.span.end.saturating_add(n),
+            ..self
+        }
     }
 
     /// Get the range of the input span.
@@ -30,6 +55,35 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
+
+    /// Get a suggestion for how to fix the input, if the error was caused by
+    /// a common mistake.
+    ///
+    /// Suggestions are intended to be shown to users as hints, and are not
+    /// part of the [`Display`][fmt::Display] implementation of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let error = yaml::from_slice("name:app\nimage: app:1.0\n").unwrap_err();
+    /// assert_eq!(error.suggestion(), Some("add a space after `:` to separate the key from its value"));
+    ///
+    /// let error = yaml::from_slice("- a\n\t- b\n").unwrap_err();
+    /// assert_eq!(error.suggestion(), Some("indent with spaces instead of tabs"));
+    ///
+    /// let error = yaml::from_slice("tags: [a, b\n").unwrap_err();
+    /// assert_eq!(error.suggestion(), Some("add a closing `]` to the sequence"));
+    ///
+    /// let error = yaml::from_slice("name: 'app\n").unwrap_err();
+    /// assert_eq!(error.suggestion(), Some("add a closing `'` to the string"));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn suggestion(&self) -> Option<&'static str> {
+        self.suggestion
+    }
 }
 
 impl fmt::Display for Error {
@@ -45,6 +99,83 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+/// Report errors as [`miette`] diagnostics, where the span of the error is
+/// labeled with its kind and the [suggestion][Error::suggestion] is used as
+/// help.
+///
+/// The input the error was raised for has to be attached as source code, such
+/// as through [`miette::Report::with_source_code`], for the label to be shown.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Diagnostic;
+/// use nondestructive::yaml;
+///
+/// let error = yaml::from_slice("name:app\nimage: app:1.0\n").unwrap_err();
+///
+/// let labels = error.labels().into_iter().flatten().collect::<Vec<_>>();
+/// assert_eq!(labels.len(), 1);
+/// assert_eq!(labels[0].offset(), error.span().start);
+/// assert_eq!(labels[0].len(), error.span().len());
+///
+/// let help = error.help().map(|help| help.to_string());
+/// assert_eq!(help.as_deref(), error.suggestion());
+///
+/// let report = miette::Report::new(error).with_source_code("name:app\nimage: app:1.0\n");
+/// assert!(report.source_code().is_some());
+/// ```
+#[cfg(feature = "diagnostics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "diagnostics")))]
+impl miette::Diagnostic for Error {
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let suggestion = self.suggestion?;
+        Some(Box::new(suggestion))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let label = miette::LabeledSpan::new_with_span(Some(self.kind.to_string()), self.span());
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+/// Convert an [`Error`] into an [`io::Error`] of the kind
+/// [`io::ErrorKind::InvalidData`].
+///
+/// This makes it possible to use `?` on both parsing and writing in functions
+/// which return [`io::Result`]. The original error can be recovered through
+/// [`io::Error::get_ref`] or [`io::Error::into_inner`].
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+///
+/// use nondestructive::yaml;
+///
+/// fn rewrite(input: &str) -> io::Result<Vec<u8>> {
+///     let doc = yaml::from_slice(input)?;
+///     let mut out = Vec::new();
+///     doc.write_to(&mut out)?;
+///     Ok(out)
+/// }
+///
+/// assert_eq!(rewrite("a: 1")?, b"a: 1");
+///
+/// let error = rewrite("{a: 1").unwrap_err();
+/// assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+///
+/// let inner = error.get_ref().and_then(|e| e.downcast_ref::<yaml::Error>());
+/// assert_eq!(inner.map(|e| e.kind()), Some(&yaml::ErrorKind::BadMappingTerminator));
+/// # Ok::<_, io::Error>(())
+/// ```
+impl From<Error> for io::Error {
+    #[inline]
+    fn from(error: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
 /// The kind of an [`Error`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ErrorKind {
@@ -108,6 +239,56 @@ pub enum ErrorKind {
     /// assert_eq!(&INPUT[error.span()], "age # end");
     /// ```
     BadMappingSeparator,
+    /// A separator constructed through [`Separator::custom`] which is empty
+    /// or contains something other than spaces and tabs.
+    ///
+    /// [`Separator::custom`]: crate::yaml::Separator::custom
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let error = yaml::Separator::custom("\n  ").unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadSeparator);
+    /// assert_eq!(error.span(), 0..1);
+    /// ```
+    BadSeparator,
+    /// The name of an anchor or an alias which is empty or contains
+    /// whitespace or a flow indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: 1")?;
+    ///
+    /// let error = doc.as_mut().set_anchor("a b").unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadAnchor);
+    /// assert_eq!(error.span(), 1..2);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    BadAnchor,
+    /// A tag which doesn't start with `!`, or which contains whitespace or a
+    /// flow indicator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: 1")?;
+    ///
+    /// let error = doc.as_mut().set_tag("Ref").unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadTag);
+    /// assert_eq!(error.span(), 0..1);
+    ///
+    /// let error = doc.as_mut().set_tag("!a,b").unwrap_err();
+    /// assert_eq!(error.span(), 2..3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    BadTag,
     /// Bad a mapping terminator.
     ///
     /// # Examples
@@ -157,13 +338,129 @@ pub enum ErrorKind {
     /// ```
     /// use nondestructive::yaml;
     ///
-    /// const INPUT: &str = r#""hello \ud800 world""#;
+    /// const INPUT: &str = r#""hello \U00110000 world""#;
     ///
     /// let error = yaml::from_slice(INPUT).unwrap_err();
     /// assert_eq!(*error.kind(), yaml::ErrorKind::BadUnicodeEscape);
-    /// assert_eq!(&INPUT[error.span()], "\\ud800");
+    /// assert_eq!(&INPUT[error.span()], "\\U00110000");
     /// ```
     BadUnicodeEscape,
+    /// A `\u` escape of a UTF-16 surrogate which is not part of a high and low
+    /// surrogate pair.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(r#""crab \ud83e\udd80""#)?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("crab 🦀"));
+    ///
+    /// const INPUT: &str = r#""hello \ud800\u0041 world""#;
+    ///
+    /// let error = yaml::from_slice(INPUT).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::UnpairedSurrogate);
+    /// assert_eq!(&INPUT[error.span()], "\\ud800");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    UnpairedSurrogate,
+    /// A conflict region which is missing its `=======` or `>>>>>>>` marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = "a: 1\n<<<<<<< HEAD\nb: 2\n";
+    ///
+    /// let error = yaml::conflict::parse(INPUT).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::UnterminatedConflict);
+    /// assert_eq!(&INPUT[error.span()], "<<<<<<< HEAD\nb: 2\n");
+    /// ```
+    UnterminatedConflict,
+    /// A conflict region for which no side has been chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = "<<<<<<< HEAD\na: 1\n=======\na: 2\n>>>>>>> main\n";
+    ///
+    /// let conflicted = yaml::conflict::parse(INPUT)?;
+    /// let error = conflicted.to_document().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::UnresolvedConflict);
+    /// assert_eq!(&INPUT[error.span()], INPUT);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    UnresolvedConflict,
+    /// A quoted string which is missing its closing quote.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = "a: \"hello\nb: 2\n";
+    ///
+    /// let error = yaml::from_slice(INPUT).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::UnterminatedString);
+    /// assert_eq!(&INPUT[error.span()], "\"hello\nb: 2\n");
+    /// ```
+    UnterminatedString,
+    /// A document starting with a UTF-16 byte order mark which isn't valid
+    /// UTF-16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &[u8] = b"\xff\xfea\x00\x00\xd8b\x00";
+    ///
+    /// let error = yaml::from_slice(INPUT).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadUtf16);
+    /// assert_eq!(&INPUT[error.span()], b"\x00\xd8");
+    /// ```
+    BadUtf16,
+    /// An edit which would modify a region [locked][crate::yaml::Document::lock]
+    /// by someone other than the current actor.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("manual: 1\ngenerated: 2\n")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let manual = root.get("manual").context("missing manual")?.id();
+    /// assert!(doc.lock(manual, "human"));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let error = root.try_insert_u32("manual", 2).unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::Locked);
+    /// assert_eq!(doc.to_string(), "manual: 1\ngenerated: 2\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    Locked,
+    /// A tag set on a block scalar like `|`, which is refused since tags are
+    /// only supported on the same line as a scalar.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: |\n  x\n")?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let error = root.get_mut("a").context("missing a")?.set_tag("!y").unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::TaggedBlock);
+    /// assert_eq!(doc.to_string(), "a: |\n  x\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    TaggedBlock,
 }
 
 impl fmt::Display for ErrorKind {
@@ -172,10 +469,20 @@ impl fmt::Display for ErrorKind {
             ErrorKind::ExpectedEof => write!(f, "expected end-of-file"),
             ErrorKind::BadSequenceTerminator => write!(f, "bad sequence terminator"),
             ErrorKind::BadMappingSeparator => write!(f, "bad mapping separator"),
+            ErrorKind::BadSeparator => write!(f, "bad separator"),
+            ErrorKind::BadAnchor => write!(f, "bad anchor"),
+            ErrorKind::BadTag => write!(f, "bad tag"),
             ErrorKind::BadMappingTerminator => write!(f, "bad mapping terminator"),
             ErrorKind::BadEscape => write!(f, "bad escape"),
             ErrorKind::BadHexEscape => write!(f, "bad hex escape"),
             ErrorKind::BadUnicodeEscape => write!(f, "bad unicode escape"),
+            ErrorKind::UnpairedSurrogate => write!(f, "unpaired surrogate"),
+            ErrorKind::UnterminatedConflict => write!(f, "unterminated conflict"),
+            ErrorKind::UnresolvedConflict => write!(f, "unresolved conflict"),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::BadUtf16 => write!(f, "bad UTF-16"),
+            ErrorKind::Locked => write!(f, "locked by another actor"),
+            ErrorKind::TaggedBlock => write!(f, "tag on block scalar"),
         }
     }
 }
@@ -0,0 +1,170 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use crate::yaml::{Document, Id, Value, WriteOptions};
+
+/// A read-only view of a [`Document`] which can be cheaply cloned and shared
+/// between threads.
+///
+/// This is constructed through [`Document::freeze`].
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice(
+///     r"
+///     name: Descartes
+///     year: 1596
+///     "
+/// )?;
+///
+/// let frozen = doc.freeze();
+///
+/// let handles = (0..4).map(|_| {
+///     let frozen = frozen.clone();
+///
+///     thread::spawn(move || {
+///         let root = frozen.as_ref().as_mapping()?;
+///         root.get("year")?.as_u32()
+///     })
+/// }).collect::<Vec<_>>();
+///
+/// for handle in handles {
+///     assert_eq!(handle.join().unwrap(), Some(1596));
+/// }
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct FrozenDocument {
+    inner: Arc<Document>,
+}
+
+impl FrozenDocument {
+    /// Construct a new frozen document.
+    pub(crate) fn new(document: Document) -> Self {
+        Self {
+            inner: Arc::new(document),
+        }
+    }
+
+    /// Get the document as a [`Value`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("32")?.freeze();
+    /// assert_eq!(doc.as_ref().as_u32(), Some(32));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn as_ref(&self) -> Value<'_> {
+        Document::as_ref(&self.inner)
+    }
+
+    /// Get the given value.
+    ///
+    /// See [`Document::value`] for more information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     first: 32
+    ///     second: [1, 2, 3]
+    ///     "
+    /// )?;
+    ///
+    /// let id = doc.as_ref().as_mapping().and_then(|m| m.get("second")).context("missing second")?.id();
+    /// let doc = doc.freeze();
+    ///
+    /// assert_eq!(doc.value(id).to_string(), "[1, 2, 3]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn value(&self, id: Id) -> Value<'_> {
+        self.inner.value(id)
+    }
+
+    /// Write the bytes of the document to the given `output`.
+    ///
+    /// See [`Document::write_to`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it.
+    #[inline]
+    pub fn write_to<O>(&self, output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        self.inner.write_to(output)
+    }
+
+    /// Write the bytes of the document to the given `output` using the given
+    /// [`WriteOptions`].
+    ///
+    /// See [`Document::write_to_with`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it.
+    #[inline]
+    pub fn write_to_with<O>(&self, output: O, options: WriteOptions<'_>) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        self.inner.write_to_with(output, options)
+    }
+
+    /// Construct a mutable copy of the frozen document.
+    ///
+    /// If this is the only reference to the document, no copying takes place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let frozen = yaml::from_slice("  32")?.freeze();
+    /// let mut doc = frozen.clone().thaw();
+    /// doc.as_mut().set_u32(42);
+    ///
+    /// assert_eq!(frozen.to_string(), "  32");
+    /// assert_eq!(doc.to_string(), "  42");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn thaw(self) -> Document {
+        Arc::try_unwrap(self.inner).unwrap_or_else(|inner| (*inner).clone())
+    }
+}
+
+impl fmt::Display for FrozenDocument {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Debug for FrozenDocument {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenDocument").field(&self.inner).finish()
+    }
+}
@@ -0,0 +1,141 @@
+use std::io;
+
+use bstr::ByteSlice;
+
+use crate::yaml::alias;
+use crate::yaml::data::{Data, Id};
+use crate::yaml::number;
+use crate::yaml::raw::Raw;
+
+/// Write the value `id` as compact JSON to the given output.
+pub(crate) fn write_to<O>(data: &Data, id: Id, o: &mut O) -> io::Result<()>
+where
+    O: ?Sized + io::Write,
+{
+    match data.raw(id) {
+        Raw::Null(..) => {
+            o.write_all(b"null")?;
+        }
+        Raw::Boolean(raw) => {
+            o.write_all(if raw.value { b"true" } else { b"false" })?;
+        }
+        Raw::Number(raw) => {
+            let string = data.str(raw.string);
+
+            if let Some(value) = number::parse::<i128>(string) {
+                o.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+            } else if let Some(value) = number::parse::<u128>(string) {
+                o.write_all(itoa::Buffer::new().format(value).as_bytes())?;
+            } else if let Some(value) = number::normalize(string) {
+                // Integers and floats which don't fit in an `i128` or an `f64`
+                // are written with all of their digits.
+                o.write_all(value.as_bytes())?;
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("number `{string}` cannot be represented in JSON"),
+                ));
+            }
+        }
+        Raw::String(raw) => {
+            write_string(data.str(raw.id), o)?;
+        }
+        Raw::Alias(raw) => {
+            // Aliases are expanded into the value they refer to, unless that
+            // value contains the alias in which case it would never end.
+            let name = data.str(raw.name);
+
+            let message = match alias::resolve(data, id) {
+                Some(target) if !alias::is_within(data, id, target) => {
+                    return write_to(data, target, o);
+                }
+                Some(..) => format!("recursive alias `*{name}` cannot be represented in JSON"),
+                None => format!("alias `*{name}` doesn't refer to an anchored value"),
+            };
+
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Raw::Mapping(raw) => {
+            o.write_all(b"{")?;
+
+            for (index, item) in raw.items.iter().enumerate() {
+                if index > 0 {
+                    o.write_all(b",")?;
+                }
+
+                let item = data.mapping_item(*item);
+                write_string(data.str(item.key.id), o)?;
+                o.write_all(b":")?;
+                write_to(data, item.value, o)?;
+            }
+
+            o.write_all(b"}")?;
+        }
+        Raw::Sequence(raw) => {
+            o.write_all(b"[")?;
+
+            for (index, item) in raw.items.iter().enumerate() {
+                if index > 0 {
+                    o.write_all(b",")?;
+                }
+
+                write_to(data, data.sequence_item(*item).value, o)?;
+            }
+
+            o.write_all(b"]")?;
+        }
+        Raw::MappingItem(raw) => {
+            write_to(data, raw.value, o)?;
+        }
+        Raw::SequenceItem(raw) => {
+            write_to(data, raw.value, o)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a JSON string.
+fn write_string<O>(string: &[u8], o: &mut O) -> io::Result<()>
+where
+    O: ?Sized + io::Write,
+{
+    let Ok(string) = string.to_str() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "string which is not valid UTF-8 cannot be represented in JSON",
+        ));
+    };
+
+    o.write_all(b"\"")?;
+    let mut start = 0;
+
+    for (index, c) in string.char_indices() {
+        let esc: &[u8] = match c {
+            '"' => b"\\\"",
+            '\\' => b"\\\\",
+            '\n' => b"\\n",
+            '\r' => b"\\r",
+            '\t' => b"\\t",
+            '\u{08}' => b"\\b",
+            '\u{0c}' => b"\\f",
+            c if c.is_control() && u32::from(c) < 0x20 => {
+                o.write_all(string.as_bytes().get(start..index).unwrap_or_default())?;
+                write!(o, "\\u{:04x}", u32::from(c))?;
+                start = index.saturating_add(c.len_utf8());
+                continue;
+            }
+            _ => {
+                continue;
+            }
+        };
+
+        o.write_all(string.as_bytes().get(start..index).unwrap_or_default())?;
+        o.write_all(esc)?;
+        start = index.saturating_add(c.len_utf8());
+    }
+
+    o.write_all(string.as_bytes().get(start..).unwrap_or_default())?;
+    o.write_all(b"\"")?;
+    Ok(())
+}
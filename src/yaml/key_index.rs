@@ -0,0 +1,131 @@
+use core::ops::Bound;
+use std::collections::BTreeMap;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+
+/// An index of every mapping key in a document, as returned by
+/// [`Document::key_index`].
+///
+/// Keys are stored in sorted order, which allows them to be searched by
+/// prefix without visiting every value in the document. This is intended for
+/// completion engines which need to offer key suggestions on every keystroke.
+///
+/// The index is a snapshot of the document when it was built, so it has to be
+/// rebuilt after the document has been modified.
+///
+/// [`Document::key_index`]: crate::yaml::Document::key_index
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("server:\n  port: 80\nservices:\n  - server: a\n")?;
+/// let index = doc.key_index();
+///
+/// let keys = index.with_prefix("ser").map(|(key, ids)| (key.to_string(), ids.len())).collect::<Vec<_>>();
+/// assert_eq!(keys, [("server".to_owned(), 2), ("services".to_owned(), 1)]);
+///
+/// let ports = index.get("port");
+/// assert_eq!(ports.len(), 1);
+/// assert_eq!(doc.value(ports[0]).as_u32(), Some(80));
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KeyIndex {
+    keys: BTreeMap<Vec<u8>, Vec<Id>>,
+}
+
+impl KeyIndex {
+    /// Get the number of distinct keys in the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Test if the index doesn't contain any keys.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Get the identifiers of every value associated with `key`, in the order
+    /// they appear in the document.
+    #[must_use]
+    pub fn get(&self, key: &str) -> &[Id] {
+        self.get_bytes(key)
+    }
+
+    /// Get the identifiers of every value associated with the raw `key`, in
+    /// the order they appear in the document.
+    #[must_use]
+    pub fn get_bytes<K>(&self, key: K) -> &[Id]
+    where
+        K: AsRef<[u8]>,
+    {
+        self.keys.get(key.as_ref()).map_or(&[], Vec::as_slice)
+    }
+
+    /// Iterate over the keys starting with `prefix` in sorted order, together
+    /// with the identifiers of every value associated with them.
+    pub fn with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'a BStr, &'a [Id])> {
+        self.with_prefix_bytes(prefix.as_bytes())
+    }
+
+    /// Iterate over the keys starting with the raw `prefix` in sorted order,
+    /// together with the identifiers of every value associated with them.
+    pub fn with_prefix_bytes<'a>(
+        &'a self,
+        prefix: &'a [u8],
+    ) -> impl Iterator<Item = (&'a BStr, &'a [Id])> {
+        self.keys
+            .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(move |(key, _)| key.starts_with(prefix))
+            .map(|(key, ids)| (key.as_bstr(), ids.as_slice()))
+    }
+
+    /// Iterate over every key in sorted order, together with the identifiers
+    /// of every value associated with them.
+    pub fn iter(&self) -> impl Iterator<Item = (&BStr, &[Id])> {
+        self.keys
+            .iter()
+            .map(|(key, ids)| (key.as_bstr(), ids.as_slice()))
+    }
+}
+
+/// Build the key index of every mapping reachable from the value `id`.
+pub(crate) fn build(data: &Data, id: Id) -> KeyIndex {
+    let mut index = KeyIndex::default();
+    collect(data, id, &mut index);
+    index
+}
+
+fn collect(data: &Data, id: Id, index: &mut KeyIndex) {
+    match data.raw(id) {
+        Raw::Mapping(raw) => {
+            for item in &raw.items {
+                let item = data.mapping_item(*item);
+
+                index
+                    .keys
+                    .entry(data.str(item.key.id).to_vec())
+                    .or_default()
+                    .push(item.value);
+
+                collect(data, item.value, index);
+            }
+        }
+        Raw::Sequence(raw) => {
+            for item in &raw.items {
+                collect(data, data.sequence_item(*item).value, index);
+            }
+        }
+        _ => {}
+    }
+}
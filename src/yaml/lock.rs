@@ -0,0 +1,108 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::error::{Error, ErrorKind};
+
+/// The owner of a locked region, associated with the value at its root.
+#[derive(Clone)]
+pub(crate) struct Owner(pub(crate) Box<str>);
+
+/// Who is currently editing a document.
+#[derive(Clone, Default)]
+pub(crate) struct Actor {
+    /// The name of the actor, if one has been set.
+    pub(crate) name: Option<Box<str>>,
+    /// Ignore all locks.
+    pub(crate) forced: bool,
+}
+
+/// Get the owner of the innermost region containing `id`, together with the
+/// value at the root of that region.
+pub(crate) fn owner(data: &Data, mut id: Id) -> Option<(Id, &str)> {
+    let owners = data.metadata().get::<Owner>()?;
+
+    loop {
+        if let Some(owner) = owners.get(id) {
+            return Some((id, &owner.0));
+        }
+
+        id = data.layout(id).parent?;
+    }
+}
+
+/// Test if `id` is inside of a region locked by someone other than the
+/// current actor.
+pub(crate) fn is_locked(data: &Data, id: Id) -> bool {
+    owner(data, id).is_some_and(|(_, owner)| is_foreign(data, owner))
+}
+
+/// Test if `id` is inside of, or contains, a region locked by someone other
+/// than the current actor.
+pub(crate) fn contains_locked(data: &Data, id: Id) -> bool {
+    if is_locked(data, id) {
+        return true;
+    }
+
+    let Some(owners) = data.metadata().get::<Owner>() else {
+        return false;
+    };
+
+    owners
+        .iter()
+        .any(|(locked, owner)| is_foreign(data, &owner.0) && is_within(data, locked, id))
+}
+
+/// Check that the value `id` can be modified or removed, which is refused
+/// with [`ErrorKind::Locked`] if it's inside of or contains a region locked by
+/// someone other than the current actor.
+pub(crate) fn check(data: &Data, id: Id) -> Result<(), Error> {
+    if contains_locked(data, id) {
+        return Err(Error::new(0..0, ErrorKind::Locked));
+    }
+
+    Ok(())
+}
+
+/// Check that values can be added to or changed on `id` itself, which is
+/// refused with [`ErrorKind::Locked`] if it's inside of a region locked by
+/// someone other than the current actor.
+///
+/// Unlike [`check`], locked regions nested inside of `id` are allowed.
+pub(crate) fn check_inside(data: &Data, id: Id) -> Result<(), Error> {
+    if is_locked(data, id) {
+        return Err(Error::new(0..0, ErrorKind::Locked));
+    }
+
+    Ok(())
+}
+
+/// Check that all of the given values can be modified or removed.
+pub(crate) fn check_all<'a, I>(data: &Data, ids: I) -> Result<(), Error>
+where
+    I: IntoIterator<Item = &'a Id>,
+{
+    for &id in ids {
+        check(data, id)?;
+    }
+
+    Ok(())
+}
+
+/// Test if `owner` is someone other than the current actor.
+fn is_foreign(data: &Data, owner: &str) -> bool {
+    let actor = data.actor();
+    !actor.forced && actor.name.as_deref() != Some(owner)
+}
+
+/// Test if `id` is `outer` or inside of it.
+fn is_within(data: &Data, mut id: Id, outer: Id) -> bool {
+    loop {
+        if id == outer {
+            return true;
+        }
+
+        let Some(parent) = data.layout(id).parent else {
+            return false;
+        };
+
+        id = parent;
+    }
+}
@@ -2,8 +2,11 @@ use core::fmt;
 
 use bstr::BStr;
 
+use crate::yaml::alias;
 use crate::yaml::data::{Data, Id};
 use crate::yaml::mapping::Iter;
+use crate::yaml::raw;
+use crate::yaml::value;
 use crate::yaml::Value;
 
 /// Accessor for a mapping.
@@ -193,6 +196,33 @@ impl<'a> Mapping<'a> {
     /// ```
     #[must_use]
     pub fn get(&self, key: &str) -> Option<Value<'a>> {
+        self.get_bytes(key)
+    }
+
+    /// Get a value from the mapping by a key which is not required to be valid
+    /// UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// // Keys and values encoded as Windows-1252.
+    /// let doc = yaml::from_slice(b"caf\xe9: cr\xe8me")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let value = root.get_bytes(b"caf\xe9").context("missing key")?;
+    /// assert_eq!(value.as_bytes(), Some(&b"cr\xe8me"[..]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_bytes<K>(&self, key: K) -> Option<Value<'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+
         for item in &self.data.mapping(self.id).items {
             let item = self.data.mapping_item(*item);
 
@@ -204,6 +234,199 @@ impl<'a> Mapping<'a> {
         None
     }
 
+    /// Get a value from the mapping by a key which is compared structurally
+    /// to `key`, like with [`Value::total_cmp`].
+    ///
+    /// This can be used to look up values by keys which are not strings, like
+    /// numbers, booleans or the complex keys described in
+    /// [`Mapping::complex_keys`]. Numbers are compared by their numerical
+    /// value, and strings are compared by their contents regardless of how
+    /// they are quoted. Plain keys are resolved with the
+    /// [schema][crate::yaml::Parser::schema] the document was parsed with, so
+    /// `yes` is equal to `true` with [`Schema::Yaml11`]. The key can belong to
+    /// any document.
+    ///
+    /// [`Schema::Yaml11`]: crate::yaml::Schema::Yaml11
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("1: one\n'1': string\ntrue: yes\n? [a, b]\n: pair\n")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let keys = yaml::from_slice("[1.0, '1', true, [a, b], 2]")?;
+    /// let keys = keys.as_ref().as_sequence().context("missing keys")?;
+    /// let values = keys
+    ///     .iter()
+    ///     .map(|key| root.get_value(&key).and_then(|v| v.as_str()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(values, [Some("one"), Some("string"), Some("yes"), Some("pair"), None]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_value(&self, key: &Value<'_>) -> Option<Value<'a>> {
+        for item in &self.data.mapping(self.id).items {
+            let item = self.data.mapping_item(*item);
+
+            if value::key_eq(self.data, item, key) {
+                return Some(Value::new(self.data, item.value));
+            }
+        }
+
+        None
+    }
+
+    /// Get a value from the mapping, including values inherited from other
+    /// mappings through merge keys like `<<: *base`.
+    ///
+    /// Keys in the mapping itself take precedence over merged keys. When a
+    /// sequence of mappings is merged, like `<<: [*a, *b]`, earlier mappings
+    /// take precedence over later ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &base
+    ///       image: app
+    ///       restart: always
+    ///     web:
+    ///       <<: *base
+    ///       restart: never
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let web = root.get("web").and_then(|v| v.as_mapping()).context("missing web")?;
+    ///
+    /// assert!(web.get("image").is_none());
+    /// assert_eq!(web.get_merged("image").and_then(|v| v.as_str()), Some("app"));
+    /// assert_eq!(web.get_merged("restart").and_then(|v| v.as_str()), Some("never"));
+    /// assert!(web.get_merged("missing").is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_merged(&self, key: &str) -> Option<Value<'a>> {
+        self.get_bytes_merged(key)
+    }
+
+    /// Get a value from the mapping by a key which is not required to be valid
+    /// UTF-8, including values inherited from other mappings through merge
+    /// keys.
+    ///
+    /// See [`Mapping::get_merged`] for how merged keys are resolved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(b"base: &base {caf\xe9: 1}\nother:\n  <<: *base\n")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let other = root.get("other").and_then(|v| v.as_mapping()).context("missing other")?;
+    /// assert_eq!(other.get_bytes_merged(b"caf\xe9").and_then(|v| v.as_u32()), Some(1));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_bytes_merged<K>(&self, key: K) -> Option<Value<'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let value = alias::get_merged(self.data, self.id, key.as_ref())?;
+        Some(Value::new(self.data, value))
+    }
+
+    /// Get the key and value of the first entry in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     build: cargo build
+    ///     test: cargo test
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let (key, value) = root.first().context("missing first")?;
+    /// assert_eq!(key, "build");
+    /// assert_eq!(value.as_str(), Some("cargo build"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn first(&self) -> Option<(&'a BStr, Value<'a>)> {
+        self.iter().next()
+    }
+
+    /// Get the key and value of the last entry in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     build: cargo build
+    ///     test: cargo test
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let (key, value) = root.last().context("missing last")?;
+    /// assert_eq!(key, "test");
+    /// assert_eq!(value.as_str(), Some("cargo test"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn last(&self) -> Option<(&'a BStr, Value<'a>)> {
+        self.iter().next_back()
+    }
+
+    /// Get the key and value of the entry at position `n` in the mapping,
+    /// counting from zero in the order entries appear in the document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("{build: cargo build, test: cargo test, lint: cargo clippy}")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let (key, value) = root.nth(1).context("missing entry")?;
+    /// assert_eq!(key, "test");
+    /// assert_eq!(value.as_str(), Some("cargo test"));
+    /// assert!(root.nth(3).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn nth(&self, n: usize) -> Option<(&'a BStr, Value<'a>)> {
+        self.iter().nth(n)
+    }
+
     /// Returns an iterator over the [Mapping].
     ///
     /// # Examples
@@ -229,6 +452,92 @@ impl<'a> Mapping<'a> {
     pub fn iter(&self) -> Iter<'a> {
         Iter::new(self.data, &self.data.mapping(self.id).items)
     }
+
+    /// Iterate over the entries of the mapping whose keys start with `prefix`,
+    /// in the order they appear in the mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("server: a\nport: 80\nservices: [b]\n")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let keys = root.keys_with_prefix("ser").map(|(key, _)| key).collect::<Vec<_>>();
+    /// assert_eq!(keys, ["server", "services"]);
+    /// assert_eq!(root.keys_with_prefix("").count(), 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn keys_with_prefix<'p>(
+        &self,
+        prefix: &'p str,
+    ) -> impl Iterator<Item = (&'a BStr, Value<'a>)> + 'p
+    where
+        'a: 'p,
+    {
+        self.iter()
+            .filter(move |(key, _)| key.starts_with(prefix.as_bytes()))
+    }
+
+    /// Iterate over the entries of the mapping which have complex keys,
+    /// yielding both the key and the value as a [`Value`].
+    ///
+    /// Complex keys follow an explicit `?` indicator and are values of their
+    /// own, like collections or block strings. The text of a complex key is
+    /// also what it's looked up and iterated by through methods such as
+    /// [`Mapping::get`] and [`Mapping::iter`], where block strings are
+    /// represented by their contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     ? - x
+    ///       - y
+    ///     : point
+    ///     ? [0, 0]
+    ///     : origin
+    ///     name: plain
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.len(), 3);
+    /// assert_eq!(root.get("[0, 0]").and_then(|v| v.as_str()), Some("origin"));
+    ///
+    /// let mut keys = root.complex_keys();
+    ///
+    /// let (key, value) = keys.next().context("missing first")?;
+    /// let key = key.as_sequence().context("expected sequence")?;
+    /// assert_eq!(key.iter().flat_map(|v| v.as_str()).collect::<Vec<_>>(), ["x", "y"]);
+    /// assert_eq!(value.as_str(), Some("point"));
+    ///
+    /// let (key, value) = keys.next().context("missing second")?;
+    /// assert_eq!(key.as_sequence().map(|s| s.len()), Some(2));
+    /// assert_eq!(value.as_str(), Some("origin"));
+    ///
+    /// assert!(keys.next().is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn complex_keys(&self) -> impl Iterator<Item = (Value<'a>, Value<'a>)> + 'a {
+        let data = self.data;
+
+        data.mapping(self.id).items.iter().filter_map(move |item| {
+            let item = data.mapping_item(*item);
+
+            let raw::MappingItemKind::Complex { key, .. } = item.kind else {
+                return None;
+            };
+
+            Some((Value::new(data, key), Value::new(data, item.value)))
+        })
+    }
 }
 
 impl fmt::Display for Mapping<'_> {
@@ -1,8 +1,14 @@
 use core::mem;
 
+use crate::yaml::case;
+use crate::yaml::clear;
 use crate::yaml::data::{Data, Id, StringId};
-use crate::yaml::raw::{self, new_bool, new_string, Raw};
-use crate::yaml::{Block, Mapping, Separator, ValueMut};
+use crate::yaml::lock;
+use crate::yaml::raw::{self, new_bool, new_bytes, new_string, Raw};
+use crate::yaml::value;
+use crate::yaml::{
+    Block, Case, Clear, Error, Mapping, Scalar, Separator, StringKind, Value, ValueMut,
+};
 
 /// Mutator for a mapping.
 ///
@@ -51,9 +57,14 @@ pub struct MappingMut<'a> {
 }
 
 macro_rules! insert_float {
-    ($name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
+    ($name:ident, $try_name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
         #[doc = concat!("Set the value as a ", $string, ".")]
         ///
+        /// Values which are not finite are written as `.inf`, `-.inf` or `.nan`.
+        ///
+        /// An existing value which is [locked][crate::yaml::Document::lock] by
+        /// someone else is left untouched.
+        ///
         /// # Examples
         ///
         /// ```
@@ -79,21 +90,42 @@ macro_rules! insert_float {
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         pub fn $name<K>(&mut self, key: K, value: $ty)
+        where
+            K: AsRef<[u8]>,
+        {
+            _ = self.$try_name(key, value);
+        }
+
+        #[doc = concat!("Like [`MappingMut::", stringify!($name), "`], but errors instead of leaving a locked value untouched.")]
+        ///
+        /// # Errors
+        ///
+        /// Errors with [`ErrorKind::Locked`] if `key` already has a value which
+        /// is [locked][crate::yaml::Document::lock] by someone else.
+        ///
+        /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+        pub fn $try_name<K>(&mut self, key: K, value: $ty) -> Result<(), Error>
         where
             K: AsRef<[u8]>,
         {
             let mut buffer = ryu::Buffer::new();
-            let number = self.data.insert_str(buffer.format(value));
+            let number = self
+                .data
+                .insert_str(crate::yaml::number::format_float(&mut buffer, value));
             let value = Raw::Number(raw::Number::new(number, crate::yaml::serde_hint::$hint));
-            self.inner_insert(key.as_ref(), Separator::Auto, value);
+            self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+            Ok(())
         }
     };
 }
 
 macro_rules! insert_number {
-    ($name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
+    ($name:ident, $try_name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
         #[doc = concat!("Set the value as a ", $string, ".")]
         ///
+        /// An existing value which is [locked][crate::yaml::Document::lock] by
+        /// someone else is left untouched.
+        ///
         /// # Examples
         ///
         /// ```
@@ -120,13 +152,29 @@ macro_rules! insert_number {
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         pub fn $name<K>(&mut self, key: K, value: $ty)
+        where
+            K: AsRef<[u8]>,
+        {
+            _ = self.$try_name(key, value);
+        }
+
+        #[doc = concat!("Like [`MappingMut::", stringify!($name), "`], but errors instead of leaving a locked value untouched.")]
+        ///
+        /// # Errors
+        ///
+        /// Errors with [`ErrorKind::Locked`] if `key` already has a value which
+        /// is [locked][crate::yaml::Document::lock] by someone else.
+        ///
+        /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+        pub fn $try_name<K>(&mut self, key: K, value: $ty) -> Result<(), Error>
         where
             K: AsRef<[u8]>,
         {
             let mut buffer = itoa::Buffer::new();
             let number = self.data.insert_str(buffer.format(value));
             let value = Raw::Number(raw::Number::new(number, crate::yaml::serde_hint::$hint));
-            self.inner_insert(key.as_ref(), Separator::Auto, value);
+            self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+            Ok(())
         }
     };
 }
@@ -138,38 +186,77 @@ impl<'a> MappingMut<'a> {
 
     /// Make insertion prefix.
     fn make_prefix(&mut self) -> StringId {
-        let mut out = Vec::new();
-        out.push(raw::NEWLINE);
-        out.resize(
-            self.data.mapping(self.id).indent.saturating_add(1),
-            raw::SPACE,
-        );
-        self.data.insert_str(out)
+        let mapping = self.data.mapping(self.id);
+
+        if let raw::MappingKind::Inline { .. } = mapping.kind {
+            return match raw::inline_prefix(self.data, &mapping.items) {
+                Some(prefix) => raw::without_comments(self.data, prefix),
+                None => self.data.insert_str(" "),
+            };
+        }
+
+        let (last, indent) = (mapping.items.last().copied(), mapping.indent);
+        raw::block_item_prefix(self.data, self.id, last, indent)
+    }
+
+    /// Make insertion prefix for the first item.
+    fn make_first_prefix(&mut self) -> StringId {
+        if let raw::MappingKind::Inline { suffix, .. } = self.data.mapping(self.id).kind {
+            if let Some(prefix) = raw::empty_inline_prefix(self.data, suffix) {
+                return prefix;
+            }
+        }
+
+        self.data.insert_str("")
     }
 
     /// Insert a value into the mapping.
-    fn inner_insert(&mut self, key: &[u8], separator: Separator<'_>, value: Raw) -> Id {
+    fn inner_insert(
+        &mut self,
+        key: &[u8],
+        separator: Separator<'_>,
+        value: Raw,
+    ) -> Result<Id, Error> {
+        self.inner_insert_with(key, raw::RawStringKind::Bare, separator, value)
+    }
+
+    /// Insert a value into the mapping, writing a new key using the given
+    /// kind of string.
+    ///
+    /// Inserting is refused if the mapping is locked, and replacing an
+    /// existing value is refused if it's locked.
+    fn inner_insert_with(
+        &mut self,
+        key: &[u8],
+        kind: raw::RawStringKind,
+        separator: Separator<'_>,
+        value: Raw,
+    ) -> Result<Id, Error> {
+        if let Err(error) = lock::check_inside(self.data, self.id) {
+            self.data.drop_kind(value);
+            return Err(error);
+        }
+
         let key = self.data.insert_str(key);
+        let (existing, token) = self.data.find_key(self.id, key);
+
+        if let Some(id) = existing {
+            if let Err(error) = lock::check(self.data, id) {
+                self.data.drop_kind(value);
+                return Err(error);
+            }
 
-        if let Some(id) = self
-            .data
-            .mapping(self.id)
-            .items
-            .iter()
-            .map(|id| self.data.mapping_item(*id))
-            .find(|item| item.key.id == key)
-            .map(|item| item.value)
-        {
             self.data.replace(id, value);
-            return id;
+            return Ok(id);
         }
 
-        let key = raw::String::new(raw::RawStringKind::Bare, key, key);
+        let key_id = key;
+        let key = raw::String::new(kind, key, key);
 
         let item_prefix = if self.data.mapping(self.id).items.last().is_some() {
             self.make_prefix()
         } else {
-            self.data.insert_str("")
+            self.make_first_prefix()
         };
 
         let item_id = self
@@ -178,31 +265,48 @@ impl<'a> MappingMut<'a> {
 
         let value_prefix = match separator {
             Separator::Auto => {
-                if value.is_tabular() {
+                let mapping = self.data.mapping(self.id);
+
+                if let raw::MappingKind::Inline { .. } = mapping.kind {
+                    match mapping.items.last() {
+                        Some(last) => self.data.layout(self.data.mapping_item(*last).value).prefix,
+                        None => self.data.insert_str(" "),
+                    }
+                } else if let (Some(separator), false) = (self.data.separator(), value.is_tabular())
+                {
+                    separator
+                } else if value.is_tabular() {
                     let mapping = self.data.mapping(self.id);
 
                     if let Some(last) = mapping.items.last() {
                         self.data.layout(self.data.mapping_item(*last).value).prefix
                     } else {
-                        let mut value_prefix = Vec::new();
-                        value_prefix.push(raw::NEWLINE);
-                        value_prefix.resize(mapping.indent.saturating_add(2), raw::SPACE);
-                        self.data.insert_str(&value_prefix)
+                        let indent = mapping.indent.saturating_add(1);
+                        self.data.block_prefix(indent)
                     }
                 } else {
                     self.data.insert_str(" ")
                 }
             }
             Separator::Custom(separator) => self.data.insert_str(separator),
+            Separator::Spaces(n) => self.data.insert_str(" ".repeat(n.max(1))),
         };
 
         let value = self.data.insert(value, value_prefix, Some(item_id));
 
-        self.data
-            .replace(item_id, Raw::MappingItem(raw::MappingItem { key, value }));
+        self.data.replace(
+            item_id,
+            Raw::MappingItem(raw::MappingItem {
+                key,
+                value,
+                suffix: None,
+                kind: raw::MappingItemKind::Pair,
+            }),
+        );
 
         self.data.mapping_mut(self.id).items.push(item_id);
-        value
+        self.data.push_key(self.id, token, key_id, value);
+        Ok(value)
     }
 
     /// Coerce a mutable mapping as an immutable [Mapping].
@@ -280,6 +384,42 @@ impl<'a> MappingMut<'a> {
         Mapping::new(self.data, self.id)
     }
 
+    /// Get the identifiers of every value in the mapping, in order.
+    ///
+    /// Since the returned identifiers don't borrow from the mapping, this
+    /// allows for iterating over and modifying each value in turn through
+    /// [`Document::value_mut`].
+    ///
+    /// [`Document::value_mut`]: crate::yaml::Document::value_mut
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{a: 1, b: 2}")?;
+    ///
+    /// let ids = doc.as_mut().into_mapping_mut().context("missing root mapping")?.ids();
+    ///
+    /// for id in ids {
+    ///     let n = doc.value(id).as_u32().context("not a number")?;
+    ///     doc.value_mut(id).set_u32(n + 1);
+    /// }
+    ///
+    /// assert_eq!(doc.to_string(), "{a: 2, b: 3}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn ids(&self) -> Vec<Id> {
+        self.data
+            .mapping(self.id)
+            .items
+            .iter()
+            .map(|&item| self.data.mapping_item(item).value)
+            .collect()
+    }
+
     /// Get a value mutably from the mapping.
     ///
     /// # Examples
@@ -314,10 +454,79 @@ impl<'a> MappingMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn get_mut(&mut self, key: &str) -> Option<ValueMut<'_>> {
+        self.get_bytes_mut(key)
+    }
+
+    /// Get a value mutably from the mapping by a key which is not required to
+    /// be valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// // Keys encoded as Windows-1252.
+    /// let mut doc = yaml::from_slice(b"caf\xe9: 1\nd\xe9j\xe0: 2")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.get_bytes_mut(b"d\xe9j\xe0").context("missing key")?.set_u32(3);
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"caf\xe9: 1\nd\xe9j\xe0: 3");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_bytes_mut<K>(&mut self, key: K) -> Option<ValueMut<'_>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+
         for item in &self.data.mapping(self.id).items {
             let item = self.data.mapping_item(*item);
 
             if self.data.str(item.key.id) == key {
+                if lock::is_locked(self.data, item.value) {
+                    return None;
+                }
+
+                return Some(ValueMut::new(self.data, item.value));
+            }
+        }
+
+        None
+    }
+
+    /// Get a value mutably from the mapping by a key which is compared
+    /// structurally to `key`.
+    ///
+    /// See [`Mapping::get_value`] for how keys are compared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("200: ok\n404: not found\n")?;
+    /// let key = yaml::from_slice("404")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.get_value_mut(&key.as_ref()).context("missing key")?.set_string("gone");
+    ///
+    /// assert_eq!(doc.to_string(), "200: ok\n404: gone\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_value_mut(&mut self, key: &Value<'_>) -> Option<ValueMut<'_>> {
+        for item in &self.data.mapping(self.id).items {
+            let item = self.data.mapping_item(*item);
+
+            if value::key_eq(self.data, item, key) {
+                if lock::is_locked(self.data, item.value) {
+                    return None;
+                }
+
                 return Some(ValueMut::new(self.data, item.value));
             }
         }
@@ -362,10 +571,49 @@ impl<'a> MappingMut<'a> {
     /// ```
     #[must_use]
     pub fn get_into_mut(self, key: &str) -> Option<ValueMut<'a>> {
+        self.get_bytes_into_mut(key)
+    }
+
+    /// Get a value mutably from the mutable mapping with the lifetime of the
+    /// current reference by a key which is not required to be valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// // Keys encoded as Windows-1252.
+    /// let mut doc = yaml::from_slice(b"caf\xe9: 1")?;
+    ///
+    /// let mut value = doc
+    ///     .as_mut()
+    ///     .into_mapping_mut()
+    ///     .and_then(|m| m.get_bytes_into_mut(b"caf\xe9"))
+    ///     .context("missing key")?;
+    ///
+    /// value.set_bytes(b"cr\xe8me");
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"caf\xe9: cr\xe8me");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get_bytes_into_mut<K>(self, key: K) -> Option<ValueMut<'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+
         for item in &self.data.mapping(self.id).items {
             let item = self.data.mapping_item(*item);
 
             if self.data.str(item.key.id) == key {
+                if lock::is_locked(self.data, item.value) {
+                    return None;
+                }
+
                 return Some(ValueMut::new(self.data, item.value));
             }
         }
@@ -410,21 +658,55 @@ impl<'a> MappingMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn remove(&mut self, key: &str) -> bool {
-        let mut index = None;
+        self.remove_bytes(key)
+    }
 
-        for (i, item) in self.data.mapping(self.id).items.iter().enumerate() {
-            let item = self.data.mapping_item(*item);
+    /// Remove the given value from the mapping by a key which is not required
+    /// to be valid UTF-8, returning a boolean indicating if it existed in the
+    /// mapping or not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// // Keys encoded as Windows-1252.
+    /// let mut doc = yaml::from_slice(b"caf\xe9: 1\nd\xe9j\xe0: 2")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert!(root.remove_bytes(b"d\xe9j\xe0"));
+    /// assert!(!root.remove_bytes(b"d\xe9j\xe0"));
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"caf\xe9: 1");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn remove_bytes<K>(&mut self, key: K) -> bool
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        let mut found = None;
 
-            if self.data.str(item.key.id) == key {
-                index = Some(i);
+        for (i, &item) in self.data.mapping(self.id).items.iter().enumerate() {
+            let raw = self.data.mapping_item(item);
+
+            if self.data.str(raw.key.id) == key {
+                found = Some((i, item));
                 break;
             }
         }
 
-        let Some(index) = index else {
+        let Some((index, item)) = found else {
             return false;
         };
 
+        if lock::contains_locked(self.data, item) {
+            return false;
+        }
+
         let item = self.data.mapping_mut(self.id).items.remove(index);
         self.data.drop(item);
         true
@@ -432,6 +714,13 @@ impl<'a> MappingMut<'a> {
 
     /// Clear all the elements in a mapping.
     ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if the mapping contains a value which
+    /// is [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
     /// # Examples
     ///
     /// ```
@@ -451,19 +740,211 @@ impl<'a> MappingMut<'a> {
     /// let mut root = doc.as_mut();
     /// let mut root = root.as_mapping_mut().context("missing root mapping")?;
     ///
-    /// root.clear();
+    /// root.clear()?;
+    /// assert_eq!(doc.to_string(), "\n    ");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert_u32("number4", 40);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     number4: 40
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.clear_with(Clear::default())
+    }
+
+    /// Clear all the elements in a mapping, using the given [`Clear`] style
+    /// to decide what the mapping is left as.
+    ///
+    /// # Errors
     ///
-    /// assert_eq!(doc.to_string(), "\n    \n    ");
+    /// Errors with [`ErrorKind::Locked`] like [`MappingMut::clear`].
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a:\n  b: 1\n  c: 2\nd: 3\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_mapping_mut()).context("missing a")?;
+    /// a.clear_with(yaml::Clear::Flow)?;
+    /// assert_eq!(doc.to_string(), "a: {}\nd: 3\n");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_mapping_mut()).context("missing a")?;
+    /// a.insert_u32("e", 4);
+    /// assert_eq!(doc.to_string(), "a: {e: 4}\nd: 3\n");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_mapping_mut()).context("missing a")?;
+    /// a.clear_with(yaml::Clear::Null)?;
+    /// assert_eq!(doc.to_string(), "a:\nd: 3\n");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_mapping_mut()).context("missing a")?;
+    /// a.insert_u32("f", 5);
+    /// assert_eq!(doc.to_string(), "a:\n  f: 5\nd: 3\n");
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn clear(&mut self) {
+    pub fn clear_with(&mut self, style: Clear) -> Result<(), Error> {
+        lock::check(self.data, self.id)?;
+
         let mut items = mem::take(&mut self.data.mapping_mut(self.id).items);
+        clear::drop_items(self.data, &mut items);
+        self.data.mapping_mut(self.id).items = items;
+
+        let inline = matches!(
+            self.data.mapping(self.id).kind,
+            raw::MappingKind::Inline { .. }
+        );
+
+        match style {
+            Clear::Flow if !inline => {
+                clear::flow_prefix(self.data, self.id);
+                let suffix = self.data.insert_str("");
+
+                self.data.mapping_mut(self.id).kind = raw::MappingKind::Inline {
+                    trailing: false,
+                    suffix,
+                };
+            }
+            Clear::Null if inline && !clear::in_flow(self.data, self.id) => {
+                let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+                self.data.set_prefix(self.id, prefix);
 
-        for item in items.drain(..) {
-            self.data.drop(item);
+                let mapping = self.data.mapping_mut(self.id);
+                mapping.indent = indent;
+                mapping.kind = raw::MappingKind::Mapping;
+            }
+            _ => {}
         }
 
-        self.data.mapping_mut(self.id).items = items;
+        Ok(())
+    }
+
+    /// Set whether an inline mapping has a trailing comma after its last
+    /// item, such as in `{a: 1, b: 2,}`.
+    ///
+    /// Values inserted into the mapping afterwards keep the configured style.
+    /// This has no effect on mappings which are not inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("{a: 1, b: 2}")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.set_trailing_comma(true);
+    /// assert_eq!(doc.to_string(), "{a: 1, b: 2,}");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.set_trailing_comma(false);
+    /// assert_eq!(doc.to_string(), "{a: 1, b: 2}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_trailing_comma(&mut self, trailing: bool) {
+        if let raw::MappingKind::Inline {
+            trailing: current, ..
+        } = &mut self.data.mapping_mut(self.id).kind
+        {
+            *current = trailing;
+        }
+    }
+
+    /// Rename the keys of this mapping in place to follow the given [`Case`],
+    /// returning the number of renamed keys.
+    ///
+    /// The order of keys, their quoting and any comments are preserved. A key
+    /// is left as-is if renaming it would collide with another key in the
+    /// mapping, if it's a complex key like `? key`, or if its item contains a
+    /// locked region. Use [`MappingMut::normalize_keys_recursive`] to also
+    /// rename the keys of nested mappings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r#"
+    ///     maxConnections: 10 # per host
+    ///     "idle_timeout": 30
+    ///     TLSConfig:
+    ///       certFile: cert.pem
+    ///     "#
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert_eq!(root.normalize_keys(yaml::Case::Kebab), 3);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     max-connections: 10 # per host
+    ///     "idle-timeout": 30
+    ///     tls-config:
+    ///       certFile: cert.pem
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn normalize_keys(&mut self, case: Case) -> usize {
+        case::normalize_keys(self.data, self.id, case, false)
+    }
+
+    /// Rename the keys of this mapping and of all nested mappings in place to
+    /// follow the given [`Case`], returning the number of renamed keys.
+    ///
+    /// Mappings nested inside of sequences are also renamed. See
+    /// [`MappingMut::normalize_keys`] for how keys are renamed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     server-config:
+    ///       listen-port: 80
+    ///       routes:
+    ///         - path-prefix: /api
+    ///           strip_prefix: true
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert_eq!(root.normalize_keys_recursive(yaml::Case::Camel), 4);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     serverConfig:
+    ///       listenPort: 80
+    ///       routes:
+    ///         - pathPrefix: /api
+    ///           stripPrefix: true
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn normalize_keys_recursive(&mut self, case: Case) -> usize {
+        case::normalize_keys(self.data, self.id, case, true)
     }
 
     /// Insert a new null value and return a [`ValueMut`] to the newly inserted
@@ -471,6 +952,14 @@ impl<'a> MappingMut<'a> {
     ///
     /// This allows for setting a custom [`Separator`].
     ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else, or if the mapping
+    /// itself is locked.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
     /// # Examples
     ///
     /// ```
@@ -485,7 +974,7 @@ impl<'a> MappingMut<'a> {
     /// )?;
     ///
     /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
-    /// root.insert("three", yaml::Separator::Custom("   ")).set_u32(3);
+    /// root.insert("three", yaml::Separator::Custom("   "))?.set_u32(3);
     ///
     /// assert_eq!(
     ///     doc.to_string(),
@@ -497,12 +986,91 @@ impl<'a> MappingMut<'a> {
     /// );
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn insert<K>(&mut self, key: K, separator: Separator<'_>) -> ValueMut<'_>
+    ///
+    /// Inserting over a locked value:
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("manual: 1\nauto: 2\n")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let manual = root.get("manual").context("missing manual")?.id();
+    /// doc.lock(manual, "human");
+    /// doc.set_actor(Some("bot"));
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let error = root.insert("manual", yaml::Separator::Auto).err().context("expected error")?;
+    /// assert_eq!(error.kind(), &yaml::ErrorKind::Locked);
+    ///
+    /// root.insert("auto", yaml::Separator::Auto)?.set_u32(3);
+    /// assert_eq!(doc.to_string(), "manual: 1\nauto: 3\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert<K>(&mut self, key: K, separator: Separator<'_>) -> Result<ValueMut<'_>, Error>
     where
         K: AsRef<[u8]>,
     {
-        let value = self.inner_insert(key.as_ref(), separator, Raw::Null(raw::Null::Empty));
-        ValueMut::new(self.data, value)
+        let value = self.inner_insert(key.as_ref(), separator, Raw::Null(raw::Null::Empty))?;
+        Ok(ValueMut::new(self.data, value))
+    }
+
+    /// Insert a new null value with a key written as a custom [`StringKind`]
+    /// and return a [`ValueMut`] to the newly inserted value.
+    ///
+    /// This allows for inserting keys which have to be quoted. If the key
+    /// already exists, its value is replaced and the style of the key is left
+    /// as-is.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] like [`MappingMut::insert`].
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("\"foo bar\": 1\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert_with("a: b", yaml::StringKind::Single, yaml::Separator::Auto)?.set_u32(2);
+    /// root.insert_with("tab\there", yaml::StringKind::Double, yaml::Separator::Auto)?.set_u32(3);
+    /// root.insert_with("foo bar", yaml::StringKind::Single, yaml::Separator::Auto)?.set_u32(4);
+    ///
+    /// assert_eq!(doc.to_string(), "\"foo bar\": 4\n'a: b': 2\n\"tab\\there\": 3\n");
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("a: b").and_then(|v| v.as_u32()), Some(2));
+    /// assert_eq!(root.get("tab\there").and_then(|v| v.as_u32()), Some(3));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_with<K>(
+        &mut self,
+        key: K,
+        kind: StringKind,
+        separator: Separator<'_>,
+    ) -> Result<ValueMut<'_>, Error>
+    where
+        K: AsRef<str>,
+    {
+        let value = self.inner_insert_with_kind(key.as_ref(), kind, separator)?;
+        Ok(ValueMut::new(self.data, value))
+    }
+
+    /// Insert a null value under a key written as the given [`StringKind`].
+    fn inner_insert_with_kind(
+        &mut self,
+        key: &str,
+        kind: StringKind,
+        separator: Separator<'_>,
+    ) -> Result<Id, Error> {
+        let kind = raw::RawStringKind::from_kind(kind);
+        self.inner_insert_with(key.as_bytes(), kind, separator, Raw::Null(raw::Null::Empty))
     }
 
     /// Insert a string.
@@ -536,8 +1104,72 @@ impl<'a> MappingMut<'a> {
         K: AsRef<[u8]>,
         S: AsRef<str>,
     {
-        let string = new_string(self.data, string);
-        self.inner_insert(key.as_ref(), Separator::Auto, string);
+        _ = self.try_insert_str(key, string);
+    }
+
+    /// Like [`MappingMut::insert_str`], but errors instead of leaving a locked value
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    pub fn try_insert_str<K, S>(&mut self, key: K, string: S) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        S: AsRef<str>,
+    {
+        let string = new_string(self.data, string, raw::is_inline(self.data, self.id));
+        self.inner_insert(key.as_ref(), Separator::Auto, string)?;
+        Ok(())
+    }
+
+    /// Insert a string which is not required to be valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("number1: 10")?;
+    ///
+    /// let mut value = doc.as_mut().into_mapping_mut().context("not a mapping")?;
+    /// // Key and value encoded as Windows-1252.
+    /// value.insert_bytes(b"caf\xe9", b"cr\xe8me");
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"number1: 10\ncaf\xe9: cr\xe8me");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_bytes<K, B>(&mut self, key: K, bytes: B)
+    where
+        K: AsRef<[u8]>,
+        B: AsRef<[u8]>,
+    {
+        _ = self.try_insert_bytes(key, bytes);
+    }
+
+    /// Like [`MappingMut::insert_bytes`], but errors instead of leaving a locked value
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    pub fn try_insert_bytes<K, B>(&mut self, key: K, bytes: B) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        B: AsRef<[u8]>,
+    {
+        let string = new_bytes(self.data, bytes, raw::is_inline(self.data, self.id));
+        self.inner_insert(key.as_ref(), Separator::Auto, string)?;
+        Ok(())
     }
 
     /// Insert a value as a literal block.
@@ -546,6 +1178,9 @@ impl<'a> MappingMut<'a> {
     /// underlying value type produced is in fact a string, and can be read
     /// through methods such as [`Value::as_str`][crate::yaml::Value::as_str].
     ///
+    /// Like [`ValueMut::set_block`], the value is written as a double-quoted
+    /// string if this is an inline mapping.
+    ///
     /// # Examples
     ///
     /// ```
@@ -558,8 +1193,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Clip));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo\nbar\nbaz\n"));
     ///
@@ -573,8 +1208,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Keep));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo\nbar\nbaz\n"));
     ///
@@ -588,8 +1223,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Strip));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo\nbar\nbaz"));
     ///
@@ -603,8 +1238,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Clip));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo bar baz\n"));
     ///
@@ -618,8 +1253,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Keep));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo bar baz\n"));
     ///
@@ -633,8 +1268,8 @@ impl<'a> MappingMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_mapping();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_mapping()?;
+    /// sequence.clear()?;
     /// sequence.insert_block("key", ["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Strip));
     /// assert_eq!(sequence.as_ref().get("key").and_then(|v| v.as_str()), Some("foo bar baz"));
     ///
@@ -655,8 +1290,27 @@ impl<'a> MappingMut<'a> {
         I: IntoIterator,
         I::Item: AsRef<str>,
     {
-        let value = raw::new_block(self.data, self.id, iter, block);
-        self.inner_insert(key.as_ref(), Separator::Auto, value);
+        _ = self.try_insert_block(key, iter, block);
+    }
+
+    /// Like [`MappingMut::insert_block`], but errors instead of leaving a locked value
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    pub fn try_insert_block<K, I>(&mut self, key: K, iter: I, block: Block) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let value = raw::new_block(self.data, self.id, iter, block, None, true);
+        self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+        Ok(())
     }
 
     /// Insert a bool.
@@ -686,23 +1340,231 @@ impl<'a> MappingMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn insert_bool<K>(&mut self, key: K, value: bool)
+    where
+        K: AsRef<[u8]>,
+    {
+        _ = self.try_insert_bool(key, value);
+    }
+
+    /// Like [`MappingMut::insert_bool`], but errors instead of leaving a locked value
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    pub fn try_insert_bool<K>(&mut self, key: K, value: bool) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
     {
         let value = new_bool(self.data, value);
-        self.inner_insert(key.as_ref(), Separator::Auto, value);
-    }
-
-    insert_float!(insert_f32, f32, "32-bit float", 10.42, F32);
-    insert_float!(insert_f64, f64, "64-bit float", 10.42, F64);
-    insert_number!(insert_u8, u8, "8-bit unsigned integer", 42, U8);
-    insert_number!(insert_i8, i8, "8-bit signed integer", -42, I8);
-    insert_number!(insert_u16, u16, "16-bit unsigned integer", 42, U16);
-    insert_number!(insert_i16, i16, "16-bit signed integer", -42, I16);
-    insert_number!(insert_u32, u32, "32-bit unsigned integer", 42, U32);
-    insert_number!(insert_i32, i32, "32-bit signed integer", -42, I32);
-    insert_number!(insert_u64, u64, "64-bit unsigned integer", 42, U64);
-    insert_number!(insert_i64, i64, "64-bit signed integer", -42, I64);
-    insert_number!(insert_u128, u128, "128-bit unsigned integer", 42, U128);
-    insert_number!(insert_i128, i128, "128-bit signed integer", -42, I128);
+        self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+        Ok(())
+    }
+
+    insert_float!(insert_f32, try_insert_f32, f32, "32-bit float", 10.42, F32);
+    insert_float!(insert_f64, try_insert_f64, f64, "64-bit float", 10.42, F64);
+    insert_number!(
+        insert_u8,
+        try_insert_u8,
+        u8,
+        "8-bit unsigned integer",
+        42,
+        U8
+    );
+    insert_number!(
+        insert_i8,
+        try_insert_i8,
+        i8,
+        "8-bit signed integer",
+        -42,
+        I8
+    );
+    insert_number!(
+        insert_u16,
+        try_insert_u16,
+        u16,
+        "16-bit unsigned integer",
+        42,
+        U16
+    );
+    insert_number!(
+        insert_i16,
+        try_insert_i16,
+        i16,
+        "16-bit signed integer",
+        -42,
+        I16
+    );
+    insert_number!(
+        insert_u32,
+        try_insert_u32,
+        u32,
+        "32-bit unsigned integer",
+        42,
+        U32
+    );
+    insert_number!(
+        insert_i32,
+        try_insert_i32,
+        i32,
+        "32-bit signed integer",
+        -42,
+        I32
+    );
+    insert_number!(
+        insert_u64,
+        try_insert_u64,
+        u64,
+        "64-bit unsigned integer",
+        42,
+        U64
+    );
+    insert_number!(
+        insert_i64,
+        try_insert_i64,
+        i64,
+        "64-bit signed integer",
+        -42,
+        I64
+    );
+    insert_number!(
+        insert_u128,
+        try_insert_u128,
+        u128,
+        "128-bit unsigned integer",
+        42,
+        U128
+    );
+    insert_number!(
+        insert_i128,
+        try_insert_i128,
+        i128,
+        "128-bit signed integer",
+        -42,
+        I128
+    );
+
+    /// Insert any value which can be converted into a [`Scalar`].
+    ///
+    /// Numbers and booleans are formatted the same way as by the methods
+    /// specific to their types, like [`MappingMut::insert_u32`], and strings
+    /// the same way as by [`MappingMut::insert_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     number1: 10
+    ///     "
+    /// )?;
+    ///
+    /// let mut value = doc.as_mut().into_mapping_mut().context("not a mapping")?;
+    /// value.insert_value("number2", 20u64);
+    /// value.insert_value("enabled", true);
+    /// value.insert_value("name", String::from("app"));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     number1: 10
+    ///     number2: 20
+    ///     enabled: true
+    ///     name: app
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_value<'s, K, T>(&mut self, key: K, value: T)
+    where
+        K: AsRef<[u8]>,
+        T: Into<Scalar<'s>>,
+    {
+        _ = self.try_insert_value(key, value);
+    }
+
+    /// Like [`MappingMut::insert_value`], but errors instead of leaving a locked value
+    /// untouched.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if `key` already has a value which is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    pub fn try_insert_value<'s, K, T>(&mut self, key: K, value: T) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        T: Into<Scalar<'s>>,
+    {
+        let value = value
+            .into()
+            .into_raw(self.data, raw::is_inline(self.data, self.id));
+        self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+        Ok(())
+    }
+
+    /// Insert an alias of an anchored value, like `*name`.
+    ///
+    /// Anchors can be added to values through [`ValueMut::set_anchor`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::BadAnchor`] if `name` is empty or contains
+    /// whitespace or a flow indicator like `,` or `[`.
+    ///
+    /// [`ErrorKind::BadAnchor`]: crate::yaml::ErrorKind::BadAnchor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     web:
+    ///       image: app
+    ///       restart: always
+    ///     worker:
+    ///       image: app
+    ///       restart: always
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.get_mut("web").context("missing web")?.set_anchor("defaults")?;
+    ///
+    /// let mut worker = root.get_mut("worker").and_then(|v| v.into_mapping_mut()).context("missing worker")?;
+    /// worker.clear()?;
+    /// worker.insert_alias("<<", "defaults")?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     web: &defaults
+    ///       image: app
+    ///       restart: always
+    ///     worker:
+    ///       <<: *defaults
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert_alias<K>(&mut self, key: K, name: &str) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        raw::check_anchor_name(name)?;
+        let name = self.data.insert_str(name);
+        let value = Raw::Alias(raw::Alias::new(name));
+        self.inner_insert(key.as_ref(), Separator::Auto, value)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,433 @@
+use std::cmp::Ordering;
+
+use bstr::ByteSlice;
+
+use crate::yaml::copy::{self, Str};
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::{Document, Value};
+
+/// Perform a structural three-way merge of the changes made in `ours` and
+/// `theirs` relative to their common ancestor `base`.
+///
+/// The merged document starts out as a copy of `ours`, so its formatting is
+/// preserved, and the changes made in `theirs` are applied on top of it:
+/// * Mappings are merged key by key, so keys added, modified or removed on
+///   only one side are merged cleanly.
+/// * Any other value, including sequences, is merged as a whole. A value
+///   modified on only one side takes that side, and a value modified the same
+///   way on both sides is merged cleanly.
+///
+/// Values are compared structurally with [`Value::total_cmp`], so changes to
+/// formatting alone are not considered modifications. When both sides modify
+/// the same value differently, the value from `ours` is kept and a
+/// [`Conflict`] is recorded in the returned [`Merge`].
+///
+/// This can be used to implement a YAML-aware merge driver for version
+/// control systems such as git.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let base = yaml::from_slice(
+///     r"
+///     name: app
+///     replicas: 1
+///     image: app:1.0
+///     "
+/// )?;
+///
+/// let ours = yaml::from_slice(
+///     r"
+///     name: app
+///     replicas: 3 # scaled up
+///     image: app:1.0
+///     "
+/// )?;
+///
+/// let theirs = yaml::from_slice(
+///     r"
+///     name: app
+///     replicas: 1
+///     image: app:2.0
+///     debug: true
+///     "
+/// )?;
+///
+/// let merge = yaml::merge3(&base, &ours, &theirs);
+/// assert!(merge.is_clean());
+///
+/// assert_eq!(
+///     merge.document().to_string(),
+///     r"
+///     name: app
+///     replicas: 3 # scaled up
+///     image: app:2.0
+///     debug: true
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[must_use]
+pub fn merge3(base: &Document, ours: &Document, theirs: &Document) -> Merge {
+    let mut document = ours.clone();
+    let mut conflicts = Vec::new();
+
+    let mut cx = Merger {
+        base: &base.data,
+        theirs: &theirs.data,
+        conflicts: &mut conflicts,
+        path: Vec::new(),
+    };
+
+    cx.merge(
+        &mut document.data,
+        None,
+        document.root,
+        Some(base.root),
+        theirs.root,
+    );
+
+    Merge {
+        document,
+        conflicts,
+    }
+}
+
+/// The result of a three-way merge performed with [`merge3`].
+#[derive(Debug, Clone)]
+pub struct Merge {
+    document: Document,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merge {
+    /// Get the merged document.
+    #[must_use]
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    /// Convert into the merged document.
+    #[must_use]
+    pub fn into_document(self) -> Document {
+        self.document
+    }
+
+    /// Get the conflicts which were encountered during the merge, in the
+    /// order they appear in the merged document.
+    #[must_use]
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.conflicts
+    }
+
+    /// Test if the merge completed without any conflicts.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Mark conflicts in the merged document with comments, returning the
+    /// number of conflicts which were marked.
+    ///
+    /// A comment describing both sides of the conflict is added on the line
+    /// above each conflicting value. Only values in block mappings and block
+    /// sequences can be marked, other conflicts are only available through
+    /// [`Merge::conflicts`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let base = yaml::from_slice("name: app\nreplicas: 1\n")?;
+    /// let ours = yaml::from_slice("name: app\nreplicas: 2\n")?;
+    /// let theirs = yaml::from_slice("name: app\nreplicas: 3\n")?;
+    ///
+    /// let mut merge = yaml::merge3(&base, &ours, &theirs);
+    ///
+    /// assert_eq!(merge.conflicts().len(), 1);
+    /// assert_eq!(merge.conflicts()[0].path(), ["replicas"]);
+    /// assert_eq!(merge.annotate(), 1);
+    ///
+    /// assert_eq!(
+    ///     merge.document().to_string(),
+    ///     "name: app\n# conflict: ours: 2, theirs: 3\nreplicas: 2\n"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn annotate(&mut self) -> usize {
+        let mut annotated = 0;
+
+        for conflict in &self.conflicts {
+            let Some(item) = conflict.item else {
+                continue;
+            };
+
+            let data = &mut self.document.data;
+
+            let Some(parent) = data.layout(item).parent else {
+                continue;
+            };
+
+            if !data.raw(parent).is_tabular() {
+                continue;
+            }
+
+            let prefix = data.prefix(item);
+
+            // The first item in a collection starts on the line of the
+            // collection itself, so it can only be marked if the collection
+            // starts on its own line.
+            let indent = if prefix.contains(&raw::NEWLINE) {
+                raw::indent(prefix)
+            } else {
+                let parent_prefix = data.prefix(parent);
+
+                if !prefix.is_empty()
+                    || (data.layout(parent).parent.is_some()
+                        && !parent_prefix.contains(&raw::NEWLINE))
+                {
+                    continue;
+                }
+
+                raw::indent(parent_prefix)
+            };
+
+            let mut out = prefix.to_vec();
+            out.extend_from_slice(b"# conflict: ours: ");
+            out.extend_from_slice(conflict.ours.as_deref().unwrap_or("(deleted)").as_bytes());
+            out.extend_from_slice(b", theirs: ");
+            out.extend_from_slice(conflict.theirs.as_deref().unwrap_or("(deleted)").as_bytes());
+            out.push(raw::NEWLINE);
+            out.extend_from_slice(indent);
+
+            let prefix = data.insert_str(out);
+            data.set_prefix(item, prefix);
+            annotated += 1;
+        }
+
+        annotated
+    }
+}
+
+/// A conflict encountered during a three-way merge, where both sides modified
+/// the same value in different ways.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    path: Vec<String>,
+    ours: Option<String>,
+    theirs: Option<String>,
+    /// The item in the merged document containing the conflicting value.
+    item: Option<Id>,
+}
+
+impl Conflict {
+    /// The path of the conflicting value.
+    ///
+    /// Mapping keys are used as segments, and sequence items are never
+    /// merged separately.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// A summary of our side of the conflict, or `None` if we removed the
+    /// value.
+    #[must_use]
+    pub fn ours(&self) -> Option<&str> {
+        self.ours.as_deref()
+    }
+
+    /// A summary of their side of the conflict, or `None` if they removed the
+    /// value.
+    #[must_use]
+    pub fn theirs(&self) -> Option<&str> {
+        self.theirs.as_deref()
+    }
+}
+
+struct Merger<'a> {
+    base: &'a Data,
+    theirs: &'a Data,
+    conflicts: &'a mut Vec<Conflict>,
+    path: Vec<String>,
+}
+
+impl Merger<'_> {
+    /// Merge their value `theirs` into our value `ours` in `data`, where
+    /// `item` is the item containing our value.
+    fn merge(&mut self, data: &mut Data, item: Option<Id>, ours: Id, base: Option<Id>, theirs: Id) {
+        let base_value = base.map(|id| Value::new(self.base, id));
+        let theirs_value = Value::new(self.theirs, theirs);
+        let ours_value = Value::new(data, ours);
+
+        if base_value
+            .as_ref()
+            .is_some_and(|b| b.total_cmp(&theirs_value) == Ordering::Equal)
+            || ours_value.total_cmp(&theirs_value) == Ordering::Equal
+        {
+            return;
+        }
+
+        if base_value
+            .as_ref()
+            .is_some_and(|b| b.total_cmp(&ours_value) == Ordering::Equal)
+        {
+            copy::write(data, ours, &copy::snapshot(self.theirs, theirs));
+            return;
+        }
+
+        let base_mapping = match base.map(|id| self.base.raw(id)) {
+            Some(Raw::Mapping(raw)) => Some(raw),
+            None => None,
+            Some(_) => {
+                self.conflict(data, item, Some(ours), Some(theirs));
+                return;
+            }
+        };
+
+        let (Raw::Mapping(..), Raw::Mapping(theirs_mapping)) =
+            (data.raw(ours), self.theirs.raw(theirs))
+        else {
+            self.conflict(data, item, Some(ours), Some(theirs));
+            return;
+        };
+
+        for &their_item in &theirs_mapping.items {
+            let their_item = self.theirs.mapping_item(their_item);
+            let key = self.theirs.str(their_item.key.id);
+            let base_value = base_mapping.and_then(|raw| get(self.base, &raw.items, key));
+
+            self.path.push(key.to_str_lossy().into_owned());
+
+            match get_item(data, ours, key) {
+                Some((our_item, our_value)) => {
+                    self.merge(
+                        data,
+                        Some(our_item),
+                        our_value,
+                        base_value,
+                        their_item.value,
+                    );
+                }
+                None => match base_value {
+                    // Added by them.
+                    None => {
+                        let key = Str::new(self.theirs, &their_item.key);
+                        let node = copy::snapshot(self.theirs, their_item.value);
+                        copy::insert(data, ours, &key, &node);
+                    }
+                    // Removed by us and modified by them.
+                    Some(base_value)
+                        if Value::new(self.base, base_value)
+                            .total_cmp(&Value::new(self.theirs, their_item.value))
+                            != Ordering::Equal =>
+                    {
+                        self.conflict(data, None, None, Some(their_item.value));
+                    }
+                    Some(..) => {}
+                },
+            }
+
+            self.path.pop();
+        }
+
+        let Some(base_mapping) = base_mapping else {
+            return;
+        };
+
+        for &base_item in &base_mapping.items {
+            let base_item = self.base.mapping_item(base_item);
+            let key = self.base.str(base_item.key.id);
+
+            if get(self.theirs, &theirs_mapping.items, key).is_some() {
+                continue;
+            }
+
+            let Some((our_item, our_value)) = get_item(data, ours, key) else {
+                continue;
+            };
+
+            // Removed by them.
+            if Value::new(self.base, base_item.value).total_cmp(&Value::new(data, our_value))
+                == Ordering::Equal
+            {
+                let items = &mut data.mapping_mut(ours).items;
+
+                if let Some(index) = items.iter().position(|&item| item == our_item) {
+                    items.remove(index);
+
+                    if let (0, Some(&next)) = (index, items.first()) {
+                        let prefix = data.layout(our_item).prefix;
+                        data.set_prefix(next, prefix);
+                    }
+
+                    data.drop(our_item);
+                }
+            } else {
+                // Modified by us and removed by them.
+                self.path.push(key.to_str_lossy().into_owned());
+                self.conflict(data, Some(our_item), Some(our_value), None);
+                self.path.pop();
+            }
+        }
+    }
+
+    /// Record a conflict at the current path.
+    fn conflict(&mut self, data: &Data, item: Option<Id>, ours: Option<Id>, theirs: Option<Id>) {
+        self.conflicts.push(Conflict {
+            path: self.path.clone(),
+            ours: ours.map(|id| summary(data, id)),
+            theirs: theirs.map(|id| summary(self.theirs, id)),
+            item,
+        });
+    }
+}
+
+/// Get the value of the given key among mapping items.
+fn get(data: &Data, items: &[Id], key: &[u8]) -> Option<Id> {
+    items
+        .iter()
+        .map(|&item| data.mapping_item(item))
+        .find(|item| data.str(item.key.id) == key)
+        .map(|item| item.value)
+}
+
+/// Get the item and value of the given key in the mapping `id`.
+fn get_item(data: &Data, id: Id, key: &[u8]) -> Option<(Id, Id)> {
+    data.mapping(id)
+        .items
+        .iter()
+        .map(|&item| (item, data.mapping_item(item)))
+        .find(|(_, raw)| data.str(raw.key.id) == key)
+        .map(|(item, raw)| (item, raw.value))
+}
+
+/// Produce a single-line summary of the given value.
+fn summary(data: &Data, id: Id) -> String {
+    match data.raw(id) {
+        Raw::Mapping(raw::Mapping {
+            kind: raw::MappingKind::Mapping,
+            ..
+        }) => String::from("(mapping)"),
+        Raw::Sequence(raw::Sequence {
+            kind: raw::SequenceKind::Mapping,
+            ..
+        }) => String::from("(sequence)"),
+        Raw::String(string) if raw::block_style(data, string).is_some() => {
+            String::from("(block scalar)")
+        }
+        // Values spanning several lines, like multiline quoted strings, are
+        // folded onto one line to fit in a comment.
+        _ => Value::new(data, id)
+            .to_string()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
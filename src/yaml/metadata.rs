@@ -0,0 +1,259 @@
+//! Typed metadata associated with values in a document.
+
+use std::any::{Any, TypeId};
+use std::collections::btree_map::{self, BTreeMap};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::yaml::{Id, Reload};
+
+/// A typed side table associating user data with the values of a
+/// [`Document`][crate::yaml::Document].
+///
+/// Entries are dropped when the value they are associated with is removed
+/// from the document, and are carried over by
+/// [`Document::reload_from`][crate::yaml::Document::reload_from] for values
+/// which survive the reload.
+///
+/// See [`Document::metadata`][crate::yaml::Document::metadata].
+pub struct Metadata<T> {
+    values: BTreeMap<Id, T>,
+}
+
+impl<T> Metadata<T> {
+    /// Get the metadata associated with the value `id`.
+    #[must_use]
+    pub fn get(&self, id: Id) -> Option<&T> {
+        self.values.get(&id)
+    }
+
+    /// Get the metadata associated with the value `id` mutably.
+    pub fn get_mut(&mut self, id: Id) -> Option<&mut T> {
+        self.values.get_mut(&id)
+    }
+
+    /// Associate metadata with the value `id`, returning the metadata which
+    /// was previously associated with it if any.
+    pub fn insert(&mut self, id: Id, value: T) -> Option<T> {
+        self.values.insert(id, value)
+    }
+
+    /// Remove the metadata associated with the value `id`.
+    pub fn remove(&mut self, id: Id) -> Option<T> {
+        self.values.remove(&id)
+    }
+
+    /// Test if there is metadata associated with the value `id`.
+    #[must_use]
+    pub fn contains(&self, id: Id) -> bool {
+        self.values.contains_key(&id)
+    }
+
+    /// Get the number of values which have metadata associated with them.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Test if no values have metadata associated with them.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Remove all metadata.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// Iterate over all values which have metadata associated with them, in
+    /// the order of their identifiers.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            iter: self.values.iter(),
+        }
+    }
+}
+
+impl<T> Default for Metadata<T> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            values: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Clone for Metadata<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Metadata<T>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Metadata<T> {
+    type Item = (Id, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over [`Metadata`].
+///
+/// See [`Metadata::iter`].
+pub struct Iter<'a, T> {
+    iter: btree_map::Iter<'a, Id, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = (Id, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, value) = self.iter.next()?;
+        Some((*id, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (id, value) = self.iter.next_back()?;
+        Some((*id, value))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+/// A type-erased metadata table.
+trait Table: Send + Sync {
+    fn remove(&mut self, id: Id);
+
+    fn remap(&mut self, reload: &Reload);
+
+    fn clone_table(&self) -> Box<dyn Table>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T> Table for Metadata<T>
+where
+    T: 'static + Clone + Send + Sync,
+{
+    fn remove(&mut self, id: Id) {
+        self.values.remove(&id);
+    }
+
+    fn remap(&mut self, reload: &Reload) {
+        self.values = std::mem::take(&mut self.values)
+            .into_iter()
+            .filter_map(|(id, value)| Some((reload.get(id)?, value)))
+            .collect();
+    }
+
+    fn clone_table(&self) -> Box<dyn Table> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The metadata tables of a document, keyed by the type of their values.
+#[derive(Default)]
+pub(crate) struct Tables {
+    tables: HashMap<TypeId, Box<dyn Table>>,
+}
+
+impl Tables {
+    /// Get the table for values of type `T`.
+    pub(crate) fn get<T>(&self) -> Option<&Metadata<T>>
+    where
+        T: 'static,
+    {
+        self.tables.get(&TypeId::of::<T>())?.as_any().downcast_ref()
+    }
+
+    /// Get the table for values of type `T` mutably, creating it if it
+    /// doesn't exist.
+    ///
+    /// Tables are keyed by the type of their values, so the downcast never
+    /// fails.
+    #[allow(clippy::unreachable)]
+    pub(crate) fn get_mut<T>(&mut self) -> &mut Metadata<T>
+    where
+        T: 'static + Clone + Send + Sync,
+    {
+        let table = self
+            .tables
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::<Metadata<T>>::default());
+
+        match table.as_any_mut().downcast_mut() {
+            Some(table) => table,
+            None => unreachable!("table has the wrong type"),
+        }
+    }
+
+    /// Remove all metadata associated with the value `id`.
+    pub(crate) fn remove(&mut self, id: Id) {
+        for table in self.tables.values_mut() {
+            table.remove(id);
+        }
+    }
+
+    /// Remap all metadata to the identifiers of a reloaded document, dropping
+    /// metadata for values which didn't survive.
+    pub(crate) fn remap(&mut self, reload: &Reload) {
+        for table in self.tables.values_mut() {
+            table.remap(reload);
+        }
+    }
+}
+
+impl Clone for Tables {
+    fn clone(&self) -> Self {
+        Self {
+            tables: self
+                .tables
+                .iter()
+                .map(|(id, table)| (*id, table.clone_table()))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Declarative rewrite rules for upgrading the format of a document.
+//!
+//! A [`Migration`] is an ordered list of rules, each of which consists of a
+//! path pattern and an action to perform on the values it matches. Rules are
+//! applied one after another, so later rules see the effects of earlier ones.
+//!
+//! Path patterns are sequences of segments from the root of a [`Document`]. A
+//! segment is used as a key when traversing a mapping, and is parsed as an
+//! index when traversing a sequence. The segment `*` matches every key of a
+//! mapping or every item of a sequence.
+//!
+//! Like all other editing performed by this crate, only the values touched by
+//! a rule are modified and the formatting of the rest of the document is left
+//! intact.
+//!
+//! # Examples
+//!
+//! ```
+//! use nondestructive::yaml;
+//! use nondestructive::yaml::migrate::{ChangeKind, Migration};
+//!
+//! let mut doc = yaml::from_slice(
+//!     r"
+//!     version: 1
+//!     server:
+//!       host: localhost
+//!       ssl: true
+//!     routes:
+//!       - path: /
+//!         legacy: yes
+//!       - path: /api
+//!     "
+//! )?;
+//!
+//! let timeout = yaml::from_slice("30")?;
+//!
+//! let migration = Migration::new()
+//!     .transform(&["version"], |mut value| value.set_u32(2))
+//!     .rename(&["server", "ssl"], "tls")
+//!     .default(&["server", "timeout"], timeout.as_ref())
+//!     .delete(&["routes", "*", "legacy"]);
+//!
+//! let report = migration.apply(&mut doc);
+//!
+//! assert_eq!(
+//!     doc.to_string(),
+//!     r"
+//!     version: 2
+//!     server:
+//!       host: localhost
+//!       tls: true
+//!       timeout: 30
+//!     routes:
+//!       - path: /
+//!       - path: /api
+//!     "
+//! );
+//!
+//! assert_eq!(report.changes().len(), 4);
+//! assert_eq!(report.changes()[3].kind(), ChangeKind::Deleted);
+//! assert_eq!(report.changes()[3].path(), ["routes", "0", "legacy"]);
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use std::fmt;
+
+use bstr::ByteSlice;
+
+use crate::yaml::copy::{self, Node};
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::{Document, MappingMut, Separator, Value, ValueMut};
+
+/// The segment matching any key or index.
+const WILDCARD: &str = "*";
+
+/// The action performed by a rule.
+enum Action {
+    Rename(String),
+    Move(Vec<String>),
+    Default(Node),
+    Delete,
+    Transform(Box<dyn Fn(ValueMut<'_>)>),
+}
+
+/// A single rule in a [`Migration`].
+struct Rule {
+    path: Vec<String>,
+    action: Action,
+}
+
+/// An ordered list of rewrite rules which can be applied to a [`Document`].
+///
+/// See the [module-level documentation][self] for more information.
+pub struct Migration {
+    rules: Vec<Rule>,
+}
+
+impl Migration {
+    /// Construct a new empty migration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule which renames the key matched by `path` to `to`.
+    ///
+    /// The value and its position in the mapping are preserved. A key is not
+    /// renamed if `to` already exists in the same mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::migrate::Migration;
+    ///
+    /// let mut doc = yaml::from_slice("{name: app, image: nginx}")?;
+    /// Migration::new().rename(&["image"], "container").apply(&mut doc);
+    /// assert_eq!(doc.to_string(), "{name: app, container: nginx}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn rename(self, path: &[&str], to: &str) -> Self {
+        self.rule(path, Action::Rename(to.to_owned()))
+    }
+
+    /// Add a rule which moves the value matched by `from` to the path `to`.
+    ///
+    /// Mappings leading up to `to` are created if they are missing, and any
+    /// existing value at `to` is replaced. A `*` segment in `to` is
+    /// substituted with the key or index it matched at the same position in
+    /// `from`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::migrate::Migration;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     name: app
+    ///     port: 8080
+    ///     "
+    /// )?;
+    ///
+    /// Migration::new().move_to(&["port"], &["server", "port"]).apply(&mut doc);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     name: app
+    ///     server:
+    ///       port: 8080
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn move_to(self, from: &[&str], to: &[&str]) -> Self {
+        let to = to.iter().map(|s| (*s).to_owned()).collect();
+        self.rule(from, Action::Move(to))
+    }
+
+    /// Add a rule which inserts a copy of `value` at `path` into every
+    /// matching mapping where it is missing.
+    ///
+    /// The value may come from any document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::migrate::Migration;
+    ///
+    /// let mut doc = yaml::from_slice("[{name: a}, {name: b, replicas: 3}]")?;
+    /// let replicas = yaml::from_slice("1")?;
+    ///
+    /// Migration::new().default(&["*", "replicas"], replicas.as_ref()).apply(&mut doc);
+    /// assert_eq!(doc.to_string(), "[{name: a, replicas: 1}, {name: b, replicas: 3}]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn default(self, path: &[&str], value: Value<'_>) -> Self {
+        let node = copy::snapshot(value.data, value.id);
+        self.rule(path, Action::Default(node))
+    }
+
+    /// Add a rule which deletes the values matched by `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::migrate::Migration;
+    ///
+    /// let mut doc = yaml::from_slice("{name: app, debug: true, tags: [a, b]}")?;
+    /// Migration::new().delete(&["debug"]).delete(&["tags", "*"]).apply(&mut doc);
+    /// assert_eq!(doc.to_string(), "{name: app, tags: []}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn delete(self, path: &[&str]) -> Self {
+        self.rule(path, Action::Delete)
+    }
+
+    /// Add a rule which calls `transform` with every value matched by `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::migrate::Migration;
+    ///
+    /// let mut doc = yaml::from_slice("ports: [80, 443]")?;
+    ///
+    /// Migration::new()
+    ///     .transform(&["ports", "*"], |mut value| {
+    ///         if let Some(port) = value.as_ref().as_u32() {
+    ///             value.set_u32(port + 8000);
+    ///         }
+    ///     })
+    ///     .apply(&mut doc);
+    ///
+    /// assert_eq!(doc.to_string(), "ports: [8080, 8443]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn transform<F>(self, path: &[&str], transform: F) -> Self
+    where
+        F: 'static + Fn(ValueMut<'_>),
+    {
+        self.rule(path, Action::Transform(Box::new(transform)))
+    }
+
+    fn rule(mut self, path: &[&str], action: Action) -> Self {
+        let path = path.iter().map(|s| (*s).to_owned()).collect();
+        self.rules.push(Rule { path, action });
+        self
+    }
+
+    /// Apply all rules in order to the given document, returning a report of
+    /// the changes which were made.
+    ///
+    /// Rules whose paths don't match anything are ignored.
+    pub fn apply(&self, doc: &mut Document) -> Report {
+        let mut report = Report::default();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            let Some((last, parents)) = rule.path.split_last() else {
+                continue;
+            };
+
+            let mut matches = Vec::new();
+
+            for (path, parent) in find(&doc.data, doc.root, parents) {
+                for (segment, item, value) in children(&doc.data, parent, last) {
+                    let mut path = path.clone();
+                    path.push(segment);
+                    matches.push((path, parent, item, value));
+                }
+
+                if let (Action::Default(..), Raw::Mapping(..)) =
+                    (&rule.action, doc.data.raw(parent))
+                {
+                    if last != WILDCARD && children(&doc.data, parent, last).is_empty() {
+                        let mut path = path.clone();
+                        path.push(last.clone());
+                        matches.push((path, parent, None, None));
+                    }
+                }
+            }
+
+            for (path, parent, item, value) in matches {
+                let kind = match &rule.action {
+                    Action::Rename(to) => {
+                        let (Some(item), Raw::Mapping(..)) = (item, doc.data.raw(parent)) else {
+                            continue;
+                        };
+
+                        if !children(&doc.data, parent, to).is_empty() {
+                            continue;
+                        }
+
+                        let flow = raw::is_inline(&doc.data, parent);
+                        let kind = raw::RawStringKind::detect(to, flow);
+                        let to = doc.data.insert_str(to);
+                        doc.data.mapping_item_mut(item).key = raw::String::new(kind, to, to);
+                        ChangeKind::Renamed
+                    }
+                    Action::Move(to) => {
+                        let (Some(item), Some(value)) = (item, value) else {
+                            continue;
+                        };
+
+                        let to = to
+                            .iter()
+                            .enumerate()
+                            .map(|(n, segment)| match path.get(n) {
+                                Some(matched) if segment == WILDCARD => matched.as_str(),
+                                _ => segment.as_str(),
+                            })
+                            .collect::<Vec<_>>();
+
+                        let node = copy::snapshot(&doc.data, value);
+
+                        let Some(target) = make_path(&mut doc.data, doc.root, &to) else {
+                            continue;
+                        };
+
+                        copy::write(&mut doc.data, target, &node);
+                        remove(&mut doc.data, parent, item);
+                        ChangeKind::Moved
+                    }
+                    Action::Default(node) => {
+                        if item.is_some() {
+                            continue;
+                        }
+
+                        let Ok(value) = MappingMut::new(&mut doc.data, parent)
+                            .insert(last, Separator::Auto)
+                            .map(|value| value.id)
+                        else {
+                            continue;
+                        };
+
+                        copy::write(&mut doc.data, value, node);
+                        ChangeKind::Defaulted
+                    }
+                    Action::Delete => {
+                        let Some(item) = item else {
+                            continue;
+                        };
+
+                        remove(&mut doc.data, parent, item);
+                        ChangeKind::Deleted
+                    }
+                    Action::Transform(transform) => {
+                        let Some(value) = value else {
+                            continue;
+                        };
+
+                        transform(ValueMut::new(&mut doc.data, value));
+                        ChangeKind::Transformed
+                    }
+                };
+
+                report.changes.push(Change {
+                    rule: index,
+                    path,
+                    kind,
+                });
+            }
+        }
+
+        report
+    }
+}
+
+impl Default for Migration {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Migration {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("rules", &self.rules.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The kind of a [`Change`] made by a [`Migration`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// A key was renamed.
+    Renamed,
+    /// A value was moved.
+    Moved,
+    /// A missing value was inserted.
+    Defaulted,
+    /// A value was deleted.
+    Deleted,
+    /// A value was transformed.
+    Transformed,
+}
+
+/// A single change made by a [`Migration`].
+#[derive(Debug, Clone)]
+pub struct Change {
+    rule: usize,
+    path: Vec<String>,
+    kind: ChangeKind,
+}
+
+impl Change {
+    /// The index of the rule which made the change, in the order in which
+    /// rules were added to the [`Migration`].
+    #[must_use]
+    pub fn rule(&self) -> usize {
+        self.rule
+    }
+
+    /// The path of the value which was changed, before the change was made.
+    ///
+    /// Wildcards are replaced with the keys or indexes they matched, so this
+    /// is always a concrete path.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// The kind of change which was made.
+    #[must_use]
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+}
+
+/// A report of the changes made when applying a [`Migration`].
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    changes: Vec<Change>,
+}
+
+impl Report {
+    /// The changes which were made, in the order they were made.
+    #[must_use]
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Test if no changes were made.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Find all values matching the given pattern, together with the concrete path
+/// which matched them.
+fn find(data: &Data, root: Id, pattern: &[String]) -> Vec<(Vec<String>, Id)> {
+    let mut current = vec![(Vec::new(), root)];
+
+    for segment in pattern {
+        let mut next = Vec::new();
+
+        for (path, id) in current {
+            for (matched, _, value) in children(data, id, segment) {
+                if let Some(value) = value {
+                    let mut path = path.clone();
+                    path.push(matched);
+                    next.push((path, value));
+                }
+            }
+        }
+
+        current = next;
+    }
+
+    current
+}
+
+/// Find the children of `id` matching the given segment, as the matched
+/// segment, the item, and the value of the item.
+fn children(data: &Data, id: Id, segment: &str) -> Vec<(String, Option<Id>, Option<Id>)> {
+    let mut out = Vec::new();
+
+    match data.raw(id) {
+        Raw::Mapping(raw) => {
+            for &item in &raw.items {
+                let entry = data.mapping_item(item);
+                let key = data.str(entry.key.id);
+
+                if segment == WILDCARD || key == segment.as_bytes() {
+                    out.push((
+                        key.to_str_lossy().into_owned(),
+                        Some(item),
+                        Some(entry.value),
+                    ));
+                }
+            }
+        }
+        Raw::Sequence(raw) => {
+            for (index, &item) in raw.items.iter().enumerate() {
+                if segment == WILDCARD || segment.parse() == Ok(index) {
+                    let value = data.sequence_item(item).value;
+                    out.push((index.to_string(), Some(item), Some(value)));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    out
+}
+
+/// Get or create the value at the given path, creating mappings along the way.
+pub(crate) fn make_path(data: &mut Data, root: Id, path: &[&str]) -> Option<Id> {
+    let mut id = root;
+
+    for segment in path {
+        if let Some((_, _, Some(value))) = children(data, id, segment).into_iter().next() {
+            id = value;
+            continue;
+        }
+
+        if !matches!(data.raw(id), Raw::Mapping(..) | Raw::Null(..)) {
+            return None;
+        }
+
+        id = ValueMut::new(data, id)
+            .make_mapping()
+            .ok()?
+            .insert(segment, Separator::Auto)
+            .ok()?
+            .id;
+    }
+
+    Some(id)
+}
+
+/// Remove the given item from its parent collection.
+fn remove(data: &mut Data, parent: Id, item: Id) {
+    let items = match data.raw_mut(parent) {
+        Raw::Mapping(raw) => &mut raw.items,
+        Raw::Sequence(raw) => &mut raw.items,
+        _ => return,
+    };
+
+    let Some(index) = items.iter().position(|&other| other == item) else {
+        return;
+    };
+
+    items.remove(index);
+
+    // The first item carries the prefix leading up to the collection, which is
+    // inherited by the item taking its place.
+    if let (0, Some(&next)) = (index, items.first()) {
+        let prefix = data.layout(item).prefix;
+        data.set_prefix(next, prefix);
+    }
+
+    data.drop(item);
+}
@@ -2,7 +2,8 @@
 //!
 //! YAML is parsed with [`from_slice`], which returns a [`Document`]. Documents
 //! are serialized through their [`std::fmt::Display`] and
-//! [`Document::write_to`] implementations.
+//! [`Document::write_to`] implementations. A document can also be re-rendered
+//! as JSON through [`Document::write_json_to`].
 //!
 //! With `serde` support enabled, [`Document`] can also be serialized through
 //! [`serde`].
@@ -16,8 +17,23 @@
 //! * Neither input nor output is not required to be UTF-8.
 //! * Keys in [Mappings][Mapping] can be anything, the only requirement is that
 //!   they are succeeded by a colon (`:`).
+//! * Keys in inline mappings extend up until the first colon, so `{a:1}` is
+//!   parsed as a mapping. This can be disabled with
+//!   [`Parser::strict_flow_keys`].
+//! * Unknown escape sequences in double-quoted strings can be preserved
+//!   verbatim instead of rejected with [`Parser::lenient_escapes`].
+//! * Plain scalars are resolved using the YAML 1.2 core schema, so `yes` and
+//!   `no` are strings. The YAML 1.1 booleans can be enabled with
+//!   [`Parser::schema`].
 //! * [Sequences][Sequence] can also be anything, everything after the `-` is
 //!   used as a value.
+//! * Tags are only supported on mappings and sequences, see [`Value::tag`].
+//!   Tags on other values are treated as part of the value. Explicit `? key`
+//!   entries can't have values, since they are only used for [sets][Set].
+//! * Anchors and aliases are preserved, but aliases are only resolved when
+//!   looking up merged keys through [`Mapping::get_merged`]. See
+//!   [`Value::anchor`] and [`Value::alias`]. An anchor has to come before the
+//!   tag of a value, and anchors on keys are not supported.
 //!
 //! This means that we will validly parse both spec and non-spec compliant YAML.
 //! They key here is that editing performed by this crate is non-destructive. So
@@ -32,11 +48,78 @@
 //!
 //! <br>
 //!
+//! ## Panics
+//!
+//! Parsing and editing documents never panics. The only way to cause a panic
+//! is to misuse an [`Id`], either by using it with a document it doesn't belong
+//! to, or after the value it refers to has been removed. Methods which might
+//! panic because of this, such as [`Document::value`] and
+//! [`Document::value_mut`], document it under a `# Panics` section. Identifiers
+//! from untrusted sources can be checked with [`Document::contains`], or used
+//! through the non-panicking [`Document::try_value`] and
+//! [`Document::try_value_mut`].
+//!
+//! <br>
+//!
+//! ## Documents which are not UTF-8
+//!
+//! Documents in legacy encodings such as Windows-1252 or Latin-1 can be
+//! processed as long as the encoding is ASCII-compatible. Bytes which are not
+//! valid UTF-8 are preserved exactly as they appear in the input, and any
+//! values which have not been modified are written back byte-for-byte through
+//! [`Document::write_to`].
+//!
+//! Note that the [`std::fmt::Display`] implementation of [`Document`] is lossy
+//! for such documents, since it replaces invalid UTF-8 with the replacement
+//! character. Always use [`Document::write_to`] when the output must be
+//! byte-exact.
+//!
+//! Values can be accessed and modified without going through [`str`] with
+//! methods such as [`Value::as_bytes`], [`Mapping::get_bytes`],
+//! [`MappingMut::get_bytes_mut`], [`MappingMut::insert_bytes`],
+//! [`SequenceMut::push_bytes`] and [`ValueMut::set_bytes`].
+//!
+//! ```
+//! use anyhow::Context;
+//! use nondestructive::yaml;
+//!
+//! // Encoded as Windows-1252.
+//! const INPUT: &[u8] = b"name: Ren\xe9 Descartes\ncity: La Haye en Touraine\n";
+//!
+//! let mut doc = yaml::from_slice(INPUT)?;
+//! let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+//! root.get_mut("city").context("missing city")?.set_bytes(b"Par\xeds");
+//!
+//! let mut out = Vec::new();
+//! doc.write_to(&mut out)?;
+//! assert_eq!(out, b"name: Ren\xe9 Descartes\ncity: Par\xeds\n");
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+//!
+//! <br>
+//!
 //! ## Serde support
 //!
 //! Serde is supported for [`Document`] and [`Value`] through the `serde`
 //! feature, see the [`serde` module][serde] for documentation.
 //!
+//! <br>
+//!
+//! ## Typed views
+//!
+//! The `derive` feature provides the `YamlView` derive, which generates typed
+//! getters and setters for the fields of a struct that edit a [`Document`] in
+//! place. See the [`view` module][view] for documentation.
+//!
+//! <br>
+//!
+//! ## Test support
+//!
+//! The `test-support` feature provides the `assert_roundtrip!` and
+//! `assert_doc_eq!` macros, which print a unified diff of the expected and
+//! actual document on failure. It's intended to be enabled for
+//! `[dev-dependencies]`.
+//!
 //! # Examples
 //!
 //! ```
@@ -57,7 +140,9 @@ mod tests;
 
 #[macro_use]
 mod parsing;
-pub use self::parsing::Parser;
+pub use self::parsing::{Parser, StreamParser};
+
+mod alias;
 
 mod any;
 pub use self::any::Any;
@@ -65,29 +150,155 @@ pub use self::any::Any;
 mod any_mut;
 pub use self::any_mut::AnyMut;
 
+mod canonical;
+pub use self::canonical::KeyOrder;
+
+mod case;
+pub use self::case::Case;
+
+pub mod classify;
+
+mod clear;
+pub use self::clear::Clear;
+
+mod comments;
+
+pub mod conflict;
+
+mod copy;
+
 mod data;
 pub use self::data::Id;
 
+mod diff;
+
+mod disjoint;
+pub use self::disjoint::DisjointMut;
+
 mod error;
 pub use self::error::{Error, ErrorKind};
 
+mod directive;
+pub use self::directive::Directive;
+
 mod document;
 pub use self::document::Document;
 
+mod encoding;
+pub use self::encoding::Bom;
+
+mod dump;
+
+mod frozen;
+pub use self::frozen::FrozenDocument;
+
+mod json;
+
+mod key_index;
+pub use self::key_index::KeyIndex;
+
+mod lock;
+
+pub mod migrate;
+
+mod outline;
+pub use self::outline::{Outline, OutlineKind};
+
+mod placeholder;
+pub use self::placeholder::Placeholder;
+
+mod pointer;
+
+mod project;
+pub use self::project::Project;
+
+mod prune;
+pub use self::prune::Prune;
+
+mod quoting;
+pub use self::quoting::{QuoteStyle, Quoting};
+
 mod raw;
 
+pub mod recipes;
+
+pub mod refs;
+
+mod reindent;
+
+mod reload;
+
+mod replace;
+pub use self::reload::Reload;
+
 mod value;
 pub use self::value::{Block, Chomp, Null, Separator, StringKind, Value};
 
 mod value_mut;
 pub use self::value_mut::ValueMut;
 
+pub mod view;
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use nondestructive_macros::YamlView;
+
+mod write;
+pub use self::write::WriteOptions;
+
+mod merge;
+pub use self::merge::{merge3, Conflict, Merge};
+
+pub mod metadata;
+#[doc(inline)]
+pub use self::metadata::Metadata;
+
+mod overlay;
+pub use self::overlay::Overlay;
+
 mod number;
 pub use self::number::Number;
 
+mod schema;
+pub use self::schema::Schema;
+
+mod scalar;
+pub use self::scalar::{OwnedScalar, Scalar};
+
+pub mod omap;
+#[doc(inline)]
+pub use self::omap::Omap;
+
+pub mod set;
+#[doc(inline)]
+pub use self::set::Set;
+
+#[cfg(feature = "serde-edits")]
+mod session;
+#[cfg(feature = "serde-edits")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-edits")))]
+pub use self::session::Compression;
+
+mod stats;
+
+mod stream;
+pub use self::stream::Stream;
+
 mod string;
 pub use self::string::String;
 
+#[cfg(feature = "time")]
+mod timestamp;
+
+#[cfg(feature = "test-support")]
+#[doc(hidden)]
+pub mod testing;
+#[cfg(feature = "test-support")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-support")))]
+#[doc(inline)]
+pub use crate::{
+    __yaml_assert_doc_eq as assert_doc_eq, __yaml_assert_roundtrip as assert_roundtrip,
+};
+
 pub mod sequence;
 #[doc(inline)]
 pub use self::sequence::{Sequence, SequenceMut};
@@ -183,3 +394,33 @@ where
     let parser = Parser::new(input.as_ref());
     parser.parse()
 }
+
+/// Parse a stream of YAML documents separated by `---` markers.
+///
+/// # Errors
+///
+/// Errors in case any document in the stream cannot be parsed as YAML.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let stream = yaml::stream_from_slice("%YAML 1.2\n---\na: 1\n---\nb: 2\n")?;
+/// assert_eq!(stream.len(), 2);
+///
+/// let doc = stream.get(1).context("missing document")?;
+/// let root = doc.as_ref().as_mapping().context("missing root")?;
+/// assert_eq!(root.get("b").and_then(|v| v.as_u32()), Some(2));
+///
+/// assert_eq!(stream.to_string(), "%YAML 1.2\n---\na: 1\n---\nb: 2\n");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn stream_from_slice<D>(input: D) -> Result<Stream, Error>
+where
+    D: AsRef<[u8]>,
+{
+    let parser = Parser::new(input.as_ref());
+    parser.parse_stream()
+}
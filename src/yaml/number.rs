@@ -1,4 +1,6 @@
+use core::cmp::Ordering;
 use core::fmt;
+use std::borrow::Cow;
 
 use bstr::{BStr, ByteSlice};
 
@@ -24,11 +26,426 @@ macro_rules! as_number {
         #[must_use]
         pub fn $name(&self) -> Option<$ty> {
             let string = self.data.str(self.raw.string);
-            lexical_core::parse(string).ok()
+            parse(string)
         }
     };
 }
 
+/// The spellings of positive infinity, which can be preceded by `-` to
+/// spell negative infinity.
+pub(crate) const INFINITY: [&[u8]; 3] = [b".inf", b".Inf", b".INF"];
+
+/// The spellings of not-a-number.
+pub(crate) const NAN: [&[u8]; 3] = [b".nan", b".NaN", b".NAN"];
+
+/// Get the value of a special float literal such as `.inf`, `-.inf` or
+/// `.nan`.
+pub(crate) fn special(string: &[u8]) -> Option<f64> {
+    if NAN.contains(&string) {
+        return Some(f64::NAN);
+    }
+
+    if let Some(string) = string.strip_prefix(b"-") {
+        return INFINITY.contains(&string).then_some(f64::NEG_INFINITY);
+    }
+
+    let string = string.strip_prefix(b"+").unwrap_or(string);
+
+    INFINITY.contains(&string).then_some(f64::INFINITY)
+}
+
+/// Get the length of the special float literal at the start of `input`,
+/// where `negative` indicates that it has been preceded by a `-` sign.
+pub(crate) fn special_len(input: &[u8], negative: bool) -> Option<usize> {
+    let nan = if negative { &[][..] } else { &NAN[..] };
+
+    INFINITY
+        .iter()
+        .chain(nan)
+        .find(|literal| input.starts_with(literal))
+        .map(|literal| literal.len())
+}
+
+/// A number type which can be parsed from the text of a number.
+pub(crate) trait Parse: Sized {
+    /// Parse the text of a number.
+    fn parse(string: &[u8]) -> Option<Self>;
+}
+
+macro_rules! parse_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl Parse for $ty {
+                #[inline]
+                fn parse(string: &[u8]) -> Option<Self> {
+                    // Octal and hexadecimal integers of the core schema.
+                    for (prefix, radix) in [(b"0o", 8), (b"0x", 16)] {
+                        if let Some(digits) = string.strip_prefix(prefix) {
+                            return <$ty>::from_str_radix(digits.to_str().ok()?, radix).ok();
+                        }
+                    }
+
+                    lexical_core::parse(string).ok()
+                }
+            }
+        )*
+    };
+}
+
+parse_integer!(u8, i8, u16, i16, u32, i32, u64, i64, u128, i128);
+
+impl Parse for f32 {
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn parse(string: &[u8]) -> Option<Self> {
+        match lexical_core::parse(string) {
+            Ok(value) => Some(value),
+            // Special values are exactly representable as an f32.
+            Err(..) => special(string).map(|value| value as f32),
+        }
+    }
+}
+
+impl Parse for f64 {
+    #[inline]
+    fn parse(string: &[u8]) -> Option<Self> {
+        match lexical_core::parse(string) {
+            Ok(value) => Some(value),
+            Err(..) => special(string),
+        }
+    }
+}
+
+/// Strip the underscores which separate the digits of a number in the YAML 1.1
+/// schema, like `1_000_000`.
+pub(crate) fn strip_underscores(string: &[u8]) -> Cow<'_, [u8]> {
+    if !string.contains(&b'_') {
+        return Cow::Borrowed(string);
+    }
+
+    Cow::Owned(string.iter().copied().filter(|&b| b != b'_').collect())
+}
+
+/// Parse the text of a number, including special float literals such as
+/// `.inf` and digits separated by underscores.
+#[inline]
+pub(crate) fn parse<T>(string: &[u8]) -> Option<T>
+where
+    T: Parse,
+{
+    T::parse(&strip_underscores(string))
+}
+
+/// A float which can be formatted as a YAML number.
+pub(crate) trait Float: ryu::Float + Copy {
+    /// Get the special float literal for this value, if it isn't finite.
+    fn special(self) -> Option<&'static str>;
+}
+
+macro_rules! float {
+    ($($ty:ty),*) => {
+        $(
+            impl Float for $ty {
+                #[inline]
+                fn special(self) -> Option<&'static str> {
+                    if self.is_nan() {
+                        Some(".nan")
+                    } else if self.is_infinite() {
+                        Some(if self.is_sign_negative() { "-.inf" } else { ".inf" })
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float!(f32, f64);
+
+/// Format a float, where values which are not finite are formatted as the
+/// special float literals `.inf`, `-.inf` and `.nan`.
+#[inline]
+pub(crate) fn format_float<T>(buffer: &mut ryu::Buffer, value: T) -> &str
+where
+    T: Float,
+{
+    match value.special() {
+        Some(special) => special,
+        None => buffer.format_finite(value),
+    }
+}
+
+/// The exact value of a decimal number, normalized so that the text of equal
+/// numbers like `1.50` and `15e-1` has the same digits.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Decimal {
+    negative: bool,
+    /// Significant digits without leading or trailing zeros, which are empty
+    /// for zero.
+    digits: Vec<u8>,
+    /// The position of the decimal point relative to the first digit.
+    point: i64,
+}
+
+impl Decimal {
+    /// Parse the text of a decimal number, which excludes octal and
+    /// hexadecimal integers and special float literals.
+    pub(crate) fn parse(string: &[u8]) -> Option<Self> {
+        let string = strip_underscores(string);
+
+        let (negative, string) = match string.split_first() {
+            Some((b'-', rest)) => (true, rest),
+            Some((b'+', rest)) => (false, rest),
+            _ => (false, &string[..]),
+        };
+
+        let (mantissa, exponent) = match string.iter().position(|&b| matches!(b, b'e' | b'E')) {
+            Some(n) => {
+                let (mantissa, exponent) = string.split_at(n);
+                let exponent = exponent.get(1..)?;
+                let digits = exponent.strip_prefix(b"-").unwrap_or(exponent);
+                let digits = digits.strip_prefix(b"+").unwrap_or(digits);
+
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return None;
+                }
+
+                (mantissa, exponent.to_str().ok()?.parse::<i64>().ok()?)
+            }
+            None => (string, 0),
+        };
+
+        let (integer, fraction) = match mantissa.iter().position(|&b| b == b'.') {
+            Some(n) => (mantissa.get(..n)?, mantissa.get(n + 1..)?),
+            None => (mantissa, &[][..]),
+        };
+
+        if integer.is_empty() && fraction.is_empty()
+            || !integer.iter().chain(fraction).all(u8::is_ascii_digit)
+        {
+            return None;
+        }
+
+        let mut digits = integer.iter().chain(fraction).copied().collect::<Vec<u8>>();
+        let leading = digits.iter().take_while(|&&b| b == b'0').count();
+        digits.drain(..leading);
+
+        while digits.last() == Some(&b'0') {
+            digits.pop();
+        }
+
+        if digits.is_empty() {
+            return Some(Self {
+                negative: false,
+                digits,
+                point: 0,
+            });
+        }
+
+        let point = i64::try_from(integer.len())
+            .ok()?
+            .checked_sub(i64::try_from(leading).ok()?)?
+            .checked_add(exponent)?;
+
+        Some(Self {
+            negative,
+            digits,
+            point,
+        })
+    }
+
+    /// Write the number as an integer, or `None` if it has a fractional part.
+    pub(crate) fn to_integer(&self) -> Option<String> {
+        let zeros = usize::try_from(self.point)
+            .ok()?
+            .checked_sub(self.digits.len())?;
+
+        let mut out = String::new();
+
+        if self.negative {
+            out.push('-');
+        }
+
+        out.extend(self.digits.iter().map(|&b| char::from(b)));
+        out.extend(std::iter::repeat('0').take(zeros));
+
+        if out.is_empty() {
+            out.push('0');
+        }
+
+        Some(out)
+    }
+}
+
+impl PartialOrd for Decimal {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |decimal: &Self| match (decimal.digits.is_empty(), decimal.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+
+        let magnitude = self
+            .point
+            .cmp(&other.point)
+            .then_with(|| self.digits.cmp(&other.digits));
+
+        if self.negative {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Write the number as a float in the same notation as [`ryu`], which is
+    /// valid both in YAML and JSON.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        if self.digits.is_empty() {
+            return f.write_str("0.0");
+        }
+
+        if self.negative {
+            f.write_char('-')?;
+        }
+
+        let mut digits = self.digits.iter().map(|&b| char::from(b));
+        let len = i64::try_from(self.digits.len()).unwrap_or(i64::MAX);
+
+        match self.point {
+            point @ -4..=0 => {
+                f.write_str("0.")?;
+
+                for _ in point..0 {
+                    f.write_char('0')?;
+                }
+
+                digits.try_for_each(|c| f.write_char(c))
+            }
+            point @ 1..=16 => {
+                for (n, c) in (0..).zip(digits) {
+                    if n == point {
+                        f.write_char('.')?;
+                    }
+
+                    f.write_char(c)?;
+                }
+
+                if point >= len {
+                    for _ in len..point {
+                        f.write_char('0')?;
+                    }
+
+                    f.write_str(".0")?;
+                }
+
+                Ok(())
+            }
+            point => {
+                if let Some(c) = digits.next() {
+                    f.write_char(c)?;
+                }
+
+                if len > 1 {
+                    f.write_char('.')?;
+                    digits.try_for_each(|c| f.write_char(c))?;
+                }
+
+                write!(f, "e{}", point - 1)
+            }
+        }
+    }
+}
+
+/// The exact value of a number, which orders and hashes numbers by their value
+/// without rounding them.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Exact {
+    NegativeInfinity,
+    Finite(Decimal),
+    Infinity,
+    NaN,
+}
+
+impl Exact {
+    /// Get the exact value of the text of a number, or `None` if it can't be
+    /// parsed.
+    pub(crate) fn parse(string: &[u8]) -> Option<Self> {
+        if let Some(decimal) = Decimal::parse(string) {
+            return Some(Self::Finite(decimal));
+        }
+
+        // Octal and hexadecimal integers.
+        let integer = if let Some(value) = parse::<i128>(string) {
+            Some(Decimal::parse(itoa::Buffer::new().format(value).as_bytes()))
+        } else {
+            parse::<u128>(string)
+                .map(|value| Decimal::parse(itoa::Buffer::new().format(value).as_bytes()))
+        };
+
+        if let Some(decimal) = integer {
+            return decimal.map(Self::Finite);
+        }
+
+        let value = special(&strip_underscores(string))?;
+
+        Some(if value.is_nan() {
+            Self::NaN
+        } else if value.is_sign_negative() {
+            Self::NegativeInfinity
+        } else {
+            Self::Infinity
+        })
+    }
+}
+
+/// Normalize the text of a number, so that equal numbers are written the same
+/// way without losing precision.
+///
+/// Integers are written with all of their digits, and floats are written the
+/// same way as an `f64` unless that would round them. Returns `None` for
+/// special float literals and octal or hexadecimal integers.
+pub(crate) fn normalize(string: &[u8]) -> Option<String> {
+    let decimal = Decimal::parse(string)?;
+
+    let is_float = strip_underscores(string)
+        .iter()
+        .any(|b| matches!(b, b'.' | b'e' | b'E'));
+
+    if !is_float {
+        if let Some(integer) = decimal.to_integer() {
+            return Some(integer);
+        }
+    }
+
+    if let Some(value) = parse::<f64>(string) {
+        let mut buffer = ryu::Buffer::new();
+        let float = format_float(&mut buffer, value + 0.0);
+
+        if Decimal::parse(float.as_bytes()).as_ref() == Some(&decimal) {
+            return Some(float.to_owned());
+        }
+    }
+
+    Some(decimal.to_string())
+}
+
 /// A YAML number.
 ///
 /// The value of the number can be accessed through the various `as_*` methods.
@@ -37,8 +454,7 @@ macro_rules! as_number {
 ///
 /// ```
 /// use anyhow::Context;
-/// use bstr::ByteSlice;
-/// use nondestructive::yaml;
+/// use nondestructive::{yaml, ByteSlice};
 ///
 /// let a = yaml::from_slice("42")?;
 /// let a = a.as_ref();
@@ -89,6 +505,58 @@ impl<'a> Number<'a> {
     as_number!(as_i64, i64, "64-bit signed integer", -42);
     as_number!(as_u128, u128, "16-bit unsigned integer", 42);
     as_number!(as_i128, i128, "128-bit signed integer", -42);
+
+    /// Try and get the value as an arbitrary precision integer.
+    ///
+    /// Unlike the fixed-size accessors such as [`Number::as_i128`], this can
+    /// represent integers of any size exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::{yaml, BigInt};
+    ///
+    /// let doc = yaml::from_slice("-340282366920938463463374607431768211456")?;
+    /// let value = doc.as_ref().into_number().context("expected a number")?;
+    /// assert_eq!(value.as_i128(), None);
+    ///
+    /// let expected = "-340282366920938463463374607431768211456".parse::<BigInt>()?;
+    /// assert_eq!(value.as_bigint(), Some(expected));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "bigint")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigint")))]
+    #[must_use]
+    pub fn as_bigint(&self) -> Option<num_bigint::BigInt> {
+        strip_underscores(self.as_raw()).to_str().ok()?.parse().ok()
+    }
+
+    /// Try and get the value as an arbitrary precision decimal.
+    ///
+    /// Unlike [`Number::as_f64`], this represents decimals exactly without
+    /// rounding them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::{yaml, BigDecimal};
+    ///
+    /// let doc = yaml::from_slice("0.1000000000000000000000000001")?;
+    /// let value = doc.as_ref().into_number().context("expected a number")?;
+    /// assert_eq!(value.as_f64(), Some(0.1));
+    ///
+    /// let expected = "0.1000000000000000000000000001".parse::<BigDecimal>()?;
+    /// assert_eq!(value.as_bigdecimal(), Some(expected));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "bigdecimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+    #[must_use]
+    pub fn as_bigdecimal(&self) -> Option<bigdecimal::BigDecimal> {
+        strip_underscores(self.as_raw()).to_str().ok()?.parse().ok()
+    }
 }
 
 impl fmt::Debug for Number<'_> {
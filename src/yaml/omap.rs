@@ -0,0 +1,220 @@
+//! An [`Omap`], which is an ordered mapping represented by a sequence of
+//! single-entry mappings tagged with `!!omap`.
+//!
+//! # Examples
+//!
+//! ```
+//! use anyhow::Context;
+//! use nondestructive::yaml;
+//!
+//! let doc = yaml::from_slice(
+//!     r"
+//!     --- !!omap
+//!     - Mark McGwire: 65
+//!     - Sammy Sosa: 63
+//!     - Ken Griffy: 58
+//!     "
+//! )?;
+//!
+//! let omap = doc.as_ref().as_omap().context("missing root omap")?;
+//! assert_eq!(omap.len(), 3);
+//! assert_eq!(omap.get("Ken Griffy").and_then(|v| v.as_u32()), Some(58));
+//!
+//! let values = omap.iter().flat_map(|(_, value)| value.as_u32()).collect::<Vec<_>>();
+//! assert_eq!(values, [65, 63, 58]);
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+use core::slice;
+
+use bstr::BStr;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{MappingItem, Raw};
+use crate::yaml::{Sequence, Value};
+
+/// Accessor for an ordered mapping.
+///
+/// Ordered mappings are sequences tagged with `!!omap`, where every item is a
+/// mapping with a single entry.
+///
+/// ```yaml
+/// !!omap
+/// - one: 1
+/// - two: 2
+/// ```
+///
+/// See [`Value::as_omap`][crate::yaml::Value::as_omap].
+pub struct Omap<'a> {
+    data: &'a Data,
+    id: Id,
+}
+
+impl<'a> Omap<'a> {
+    /// Construct an ordered mapping, as long as every item in the sequence
+    /// `id` is a single-entry mapping.
+    pub(crate) fn new(data: &'a Data, id: Id) -> Option<Self> {
+        for item in &data.sequence(id).items {
+            pair(data, *item)?;
+        }
+
+        Some(Self { data, id })
+    }
+
+    /// Get the opaque [`Id`] associated with this ordered mapping.
+    #[must_use]
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Get the number of entries in the ordered mapping.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.sequence(self.id).items.len()
+    }
+
+    /// Test if the ordered mapping is empty.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.sequence(self.id).items.is_empty()
+    }
+
+    /// Get the value of the first entry with the given `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Value<'a>> {
+        self.get_bytes(key)
+    }
+
+    /// Get the value of the first entry with the given `key`, which doesn't
+    /// have to be valid UTF-8.
+    #[must_use]
+    pub fn get_bytes<K>(&self, key: K) -> Option<Value<'a>>
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        self.iter().find(|(k, _)| *k == key).map(|(_, value)| value)
+    }
+
+    /// Get the ordered mapping as the [`Sequence`] it's represented by.
+    #[must_use]
+    #[inline]
+    pub fn as_sequence(&self) -> Sequence<'a> {
+        Sequence::new(self.data, self.id)
+    }
+
+    /// Get an iterator over the entries of the ordered mapping, in the order
+    /// they appear.
+    #[must_use]
+    #[inline]
+    pub fn iter(&self) -> Iter<'a> {
+        Iter {
+            data: self.data,
+            iter: self.data.sequence(self.id).items.iter(),
+        }
+    }
+}
+
+impl fmt::Display for Omap<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_sequence().fmt(f)
+    }
+}
+
+impl fmt::Debug for Omap<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for Omap<'a> {
+    type Item = (&'a BStr, Value<'a>);
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an [`Omap`].
+///
+/// See [`Omap::iter`].
+pub struct Iter<'a> {
+    data: &'a Data,
+    iter: slice::Iter<'a, Id>,
+}
+
+impl<'a> Iter<'a> {
+    fn entry(&self, item: Id) -> Option<(&'a BStr, Value<'a>)> {
+        let pair = pair(self.data, item)?;
+        Some((
+            self.data.str(pair.key.id),
+            Value::new(self.data, pair.value),
+        ))
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a BStr, Value<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = *self.iter.next()?;
+        self.entry(item)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        let item = *self.iter.nth(n)?;
+        self.entry(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = *self.iter.next_back()?;
+        self.entry(item)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        let item = *self.iter.nth_back(n)?;
+        self.entry(item)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl FusedIterator for Iter<'_> {}
+
+/// Get the single entry of the mapping in the sequence item `item`.
+fn pair(data: &Data, item: Id) -> Option<&MappingItem> {
+    let Raw::Mapping(mapping) = data.raw(data.sequence_item(item).value) else {
+        return None;
+    };
+
+    let [entry] = mapping.items[..] else {
+        return None;
+    };
+
+    Some(data.mapping_item(entry))
+}
@@ -0,0 +1,188 @@
+use core::ops::Range;
+use std::collections::HashMap;
+
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::dump;
+use crate::yaml::raw::{self, Raw};
+
+/// The kind of value an [`Outline`] entry refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutlineKind {
+    /// A null value.
+    Null,
+    /// A boolean value.
+    Boolean,
+    /// A number.
+    Number,
+    /// A string.
+    String,
+    /// A mapping.
+    Mapping,
+    /// A sequence.
+    Sequence,
+    /// An alias to an anchored value.
+    Alias,
+}
+
+/// An entry in the outline of a document, as returned by
+/// [`Document::outline`].
+///
+/// Every mapping item and sequence item in the document is an entry, nested
+/// under the entry of the collection it belongs to. This makes it suitable for
+/// building document outlines and folding ranges in editors.
+///
+/// [`Document::outline`]: crate::yaml::Document::outline
+#[derive(Debug, Clone)]
+pub struct Outline {
+    path: Vec<String>,
+    id: Id,
+    span: Range<usize>,
+    kind: OutlineKind,
+    children: Vec<Outline>,
+}
+
+impl Outline {
+    /// The path of the entry.
+    ///
+    /// Mapping keys are used as segments, and sequence items use their index.
+    /// The path of the root of the document is empty.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// The name of the entry, which is the last segment of its
+    /// [path][Outline::path], or `None` for the root of the document.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.path.last().map(String::as_str)
+    }
+
+    /// The identifier of the value of the entry.
+    #[must_use]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The span of the entry in the output of [`Document::write_to`].
+    ///
+    /// For mapping items this starts at the key, and for items in block
+    /// sequences at the `-` marker. It ends where the value ends, excluding
+    /// any trailing whitespace or comments.
+    ///
+    /// [`Document::write_to`]: crate::yaml::Document::write_to
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// The kind of the value of the entry.
+    #[must_use]
+    pub fn kind(&self) -> OutlineKind {
+        self.kind
+    }
+
+    /// The entries nested under this one, in the order they appear in the
+    /// document.
+    #[must_use]
+    pub fn children(&self) -> &[Outline] {
+        &self.children
+    }
+}
+
+/// Build the outline of the value `root`, where every span is offset by
+/// `offset`.
+pub(crate) fn outline(data: &Data, root: Id, offset: usize) -> Outline {
+    let spans = dump::spans(data, root);
+
+    let mut builder = Builder {
+        data,
+        spans: &spans,
+        offset,
+        path: Vec::new(),
+    };
+
+    let span = builder.span(root);
+    builder.entry(root, span)
+}
+
+struct Builder<'a> {
+    data: &'a Data,
+    spans: &'a HashMap<Id, Range<usize>>,
+    offset: usize,
+    path: Vec<String>,
+}
+
+impl Builder<'_> {
+    /// Construct the entry for the value `id`.
+    fn entry(&mut self, id: Id, span: Range<usize>) -> Outline {
+        let mut children = Vec::new();
+
+        let kind = match self.data.raw(id) {
+            Raw::Null(..) => OutlineKind::Null,
+            Raw::Boolean(..) => OutlineKind::Boolean,
+            Raw::Number(..) => OutlineKind::Number,
+            Raw::String(..) => OutlineKind::String,
+            Raw::Alias(..) => OutlineKind::Alias,
+            Raw::Mapping(mapping) => {
+                for &item in &mapping.items {
+                    let Raw::MappingItem(raw) = self.data.raw(item) else {
+                        continue;
+                    };
+
+                    let key = self.data.str(raw.key.id).to_str_lossy().into_owned();
+                    let span = self.span(item);
+                    children.push(self.child(key, raw.value, span));
+                }
+
+                OutlineKind::Mapping
+            }
+            Raw::Sequence(sequence) => {
+                let block = !matches!(sequence.kind, raw::SequenceKind::Inline { .. });
+
+                for (index, &item) in sequence.items.iter().enumerate() {
+                    let Raw::SequenceItem(raw) = self.data.raw(item) else {
+                        continue;
+                    };
+
+                    let mut span = self.span(item);
+
+                    // The `-` marker precedes the span of block items.
+                    if block {
+                        span.start = span.start.saturating_sub(1);
+                    }
+
+                    children.push(self.child(index.to_string(), raw.value, span));
+                }
+
+                OutlineKind::Sequence
+            }
+            Raw::MappingItem(..) | Raw::SequenceItem(..) => OutlineKind::Null,
+        };
+
+        Outline {
+            path: self.path.clone(),
+            id,
+            span,
+            kind,
+            children,
+        }
+    }
+
+    /// Construct the entry for the child `id` under the segment `segment`.
+    fn child(&mut self, segment: String, id: Id, span: Range<usize>) -> Outline {
+        self.path.push(segment);
+        let entry = self.entry(id, span);
+        self.path.pop();
+        entry
+    }
+
+    /// Get the span of `id` adjusted by the offset.
+    fn span(&self, id: Id) -> Range<usize> {
+        let span = self.spans.get(&id).cloned().unwrap_or_default();
+        span.start + self.offset..span.end + self.offset
+    }
+}
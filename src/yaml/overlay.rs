@@ -0,0 +1,312 @@
+use bstr::ByteSlice;
+
+use crate::yaml::copy::{self, Str};
+use crate::yaml::data::{Data, Id};
+use crate::yaml::migrate;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::recipes;
+use crate::yaml::{Document, Value, ValueMut};
+
+/// A layered view of a base [`Document`] with overrides from another
+/// [`Document`] applied on top of it.
+///
+/// Mappings are merged key by key, so that a value in the overrides replaces
+/// the value at the same path in the base document while other keys are left
+/// as-is. Any other kind of value in the overrides, including sequences,
+/// replaces the base value entirely. An overrides document which is empty has
+/// no effect.
+///
+/// Reads see the merged view, while writes are always made to the overrides
+/// document, leaving the base document untouched. Use [`Overlay::flatten`] to
+/// produce a single document with the overrides applied.
+///
+/// Values are addressed by a path of segments from the root of the documents.
+/// A segment is used as a key when traversing a mapping, and is parsed as an
+/// index when traversing a sequence.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let base = yaml::from_slice(
+///     r"
+///     server:
+///       host: localhost
+///       port: 8080
+///     debug: false
+///     "
+/// )?;
+///
+/// let overrides = yaml::from_slice("server: {port: 9090}")?;
+///
+/// let mut overlay = yaml::Overlay::new(base, overrides);
+///
+/// assert_eq!(overlay.get(&["server", "host"]).and_then(|v| v.as_str()), Some("localhost"));
+/// assert_eq!(overlay.get(&["server", "port"]).and_then(|v| v.as_u32()), Some(9090));
+///
+/// overlay.insert(&["debug"]).context("missing debug")?.set_bool(true);
+/// assert_eq!(overlay.overrides().to_string(), "server: {port: 9090}\ndebug: true");
+///
+/// assert_eq!(
+///     overlay.flatten().to_string(),
+///     r"
+///     server:
+///       host: localhost
+///       port: 9090
+///     debug: true
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Overlay {
+    base: Document,
+    overrides: Document,
+}
+
+impl Overlay {
+    /// Construct a new overlay of `overrides` on top of `base`.
+    #[must_use]
+    pub fn new(base: Document, overrides: Document) -> Self {
+        Self { base, overrides }
+    }
+
+    /// Get the base document.
+    #[must_use]
+    pub fn base(&self) -> &Document {
+        &self.base
+    }
+
+    /// Get the overrides document.
+    #[must_use]
+    pub fn overrides(&self) -> &Document {
+        &self.overrides
+    }
+
+    /// Convert the overlay into its base and overrides documents.
+    #[must_use]
+    pub fn into_parts(self) -> (Document, Document) {
+        (self.base, self.overrides)
+    }
+
+    /// Get the value at the given `path` in the merged view.
+    ///
+    /// The value is taken from the overrides if it's present there, otherwise
+    /// from the base document. Since mappings are merged key by key, use
+    /// [`Overlay::keys`] to list the keys of a mapping in the merged view.
+    #[must_use]
+    pub fn get(&self, path: &[&str]) -> Option<Value<'_>> {
+        match self.lookup(path)? {
+            Lookup::Override(id, _) => Some(self.overrides.value(id)),
+            Lookup::Base(id) => Some(self.base.value(id)),
+        }
+    }
+
+    /// Get the keys of the mapping at the given `path` in the merged view.
+    ///
+    /// Keys from the base document come first, followed by keys which are
+    /// only present in the overrides. Returns `None` if there is no mapping
+    /// at the given path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let base = yaml::from_slice("{a: 1, b: 2}")?;
+    /// let overrides = yaml::from_slice("{c: 3, a: 4}")?;
+    /// let overlay = yaml::Overlay::new(base, overrides);
+    ///
+    /// assert_eq!(overlay.keys(&[]), Some(vec![String::from("a"), String::from("b"), String::from("c")]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn keys(&self, path: &[&str]) -> Option<Vec<String>> {
+        let (base, overrides) = match self.lookup(path)? {
+            Lookup::Override(id, merged) => {
+                let overrides = self.overrides.value(id).as_mapping()?;
+
+                let base = recipes::find(&self.base, path)
+                    .filter(|_| merged)
+                    .and_then(|id| self.base.value(id).as_mapping());
+
+                (base, Some(overrides))
+            }
+            Lookup::Base(id) => (Some(self.base.value(id).as_mapping()?), None),
+        };
+
+        let mut keys = Vec::<String>::new();
+
+        for mapping in base.into_iter().chain(overrides) {
+            for (key, _) in mapping.iter() {
+                let key = key.to_str_lossy();
+
+                if !keys.iter().any(|k| *k == key) {
+                    keys.push(key.into_owned());
+                }
+            }
+        }
+
+        Some(keys)
+    }
+
+    /// Get the value at the given `path` mutably.
+    ///
+    /// If the value is only present in the base document, it's first copied
+    /// into the overrides, creating any missing mappings leading up to it.
+    /// Returns `None` if the value doesn't exist in either document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let base = yaml::from_slice("features: [a, b]")?;
+    /// let mut overlay = yaml::Overlay::new(base, yaml::from_slice("")?);
+    ///
+    /// let features = overlay.get_mut(&["features"]).context("missing features")?;
+    /// features.into_sequence_mut().context("not a sequence")?.push_string("c");
+    ///
+    /// assert_eq!(overlay.base().to_string(), "features: [a, b]");
+    /// assert_eq!(overlay.overrides().to_string(), "features: [a, b, c]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_mut(&mut self, path: &[&str]) -> Option<ValueMut<'_>> {
+        let base = match self.lookup(path)? {
+            Lookup::Override(id, _) => return Some(self.overrides.value_mut(id)),
+            Lookup::Base(id) => id,
+        };
+
+        let node = copy::snapshot(&self.base.data, base);
+        let id = self.make_override(path)?;
+        copy::write(&mut self.overrides.data, id, &node);
+        Some(self.overrides.value_mut(id))
+    }
+
+    /// Insert a value at the given `path` into the overrides, returning it
+    /// mutably so that it can be set.
+    ///
+    /// Any missing mappings leading up to the value are created. Returns
+    /// `None` if the path can't be created, such as if it passes through a
+    /// value which is not a mapping.
+    pub fn insert(&mut self, path: &[&str]) -> Option<ValueMut<'_>> {
+        let id = self.make_override(path)?;
+        Some(self.overrides.value_mut(id))
+    }
+
+    /// Materialize the merged view into a single document.
+    ///
+    /// The formatting of the base document is preserved, and values from the
+    /// overrides are written in the style they have in the overrides.
+    #[must_use]
+    pub fn flatten(&self) -> Document {
+        let mut document = self.base.clone();
+
+        if !self.is_empty_override() {
+            merge(
+                &mut document.data,
+                document.root,
+                &self.overrides.data,
+                self.overrides.root,
+            );
+        }
+
+        document
+    }
+
+    /// Test if the overrides document is empty.
+    fn is_empty_override(&self) -> bool {
+        match self.overrides.data.raw(self.overrides.root) {
+            Raw::Null(raw::Null::Empty) => true,
+            Raw::String(string) => {
+                matches!(string.kind, raw::RawStringKind::Bare)
+                    && self.overrides.data.str(string.id).is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Look up the value at the given path in the merged view.
+    fn lookup(&self, path: &[&str]) -> Option<Lookup> {
+        if self.is_empty_override() {
+            return Some(Lookup::Base(recipes::find(&self.base, path)?));
+        }
+
+        let mut id = self.overrides.root;
+
+        for (n, segment) in path.iter().enumerate() {
+            let Some(mapping) = self.overrides.value(id).as_mapping() else {
+                // Values other than mappings replace the base entirely.
+                let id =
+                    recipes::find_from(&self.overrides, id, path.get(n..).unwrap_or_default())?;
+                return Some(Lookup::Override(id, false));
+            };
+
+            if let Some(value) = mapping.get(segment) {
+                id = value.id();
+                continue;
+            }
+
+            // The base is only visible through mappings in both documents.
+            let base = recipes::find(&self.base, path.get(..n).unwrap_or_default())?;
+            self.base.value(base).as_mapping()?;
+            let id = recipes::find_from(&self.base, base, path.get(n..).unwrap_or_default())?;
+            return Some(Lookup::Base(id));
+        }
+
+        Some(Lookup::Override(id, true))
+    }
+
+    /// Get or create the value at the given path in the overrides.
+    fn make_override(&mut self, path: &[&str]) -> Option<Id> {
+        if self.is_empty_override() {
+            let root = self.overrides.root;
+            self.overrides
+                .data
+                .replace(root, Raw::Null(raw::Null::Empty));
+        }
+
+        migrate::make_path(&mut self.overrides.data, self.overrides.root, path)
+    }
+}
+
+/// Where a value in the merged view of an [`Overlay`] was found.
+enum Lookup {
+    /// The value is in the overrides, and is merged with the base if it's
+    /// reached only through mappings.
+    Override(Id, bool),
+    /// The value is in the base.
+    Base(Id),
+}
+
+/// Merge the value `from` in `other` into the value `id`.
+fn merge(data: &mut Data, id: Id, other: &Data, from: Id) {
+    let (Raw::Mapping(..), Raw::Mapping(raw)) = (data.raw(id), other.raw(from)) else {
+        copy::write(data, id, &copy::snapshot(other, from));
+        return;
+    };
+
+    for &item in &raw.items {
+        let item = other.mapping_item(item);
+        let key = other.str(item.key.id);
+
+        let existing = data
+            .mapping(id)
+            .items
+            .iter()
+            .map(|&item| data.mapping_item(item))
+            .find(|item| data.str(item.key.id) == key)
+            .map(|item| item.value);
+
+        match existing {
+            Some(existing) => merge(data, existing, other, item.value),
+            None => {
+                let key = Str::new(other, &item.key);
+                copy::insert(data, id, &key, &copy::snapshot(other, item.value));
+            }
+        }
+    }
+}
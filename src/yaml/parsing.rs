@@ -1,21 +1,26 @@
 use std::array;
+use std::ops::Range;
 
 use bstr::ByteSlice;
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Serialize};
 
 use crate::yaml::data::{Data, Id, StringId};
 use crate::yaml::error::{Error, ErrorKind};
+use crate::yaml::number;
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::serde_hint;
-use crate::yaml::Document;
+use crate::yaml::serde_hint::{self, RawNumberHint};
+use crate::yaml::stream::{self, Stream};
+use crate::yaml::{Bom, Chomp, Document, Schema};
 
 type Result<T, E = Error> = std::result::Result<T, E>;
 
 const EOF: u8 = b'\0';
 
-/// Inline control characters which splits up data.
-macro_rules! ctl {
+/// Characters which terminate a plain scalar in flow context.
+macro_rules! flow_end {
     () => {
-        b',' | b':' | b']' | b'}' | EOF
+        b',' | b']' | b'}' | EOF
     };
 }
 
@@ -32,6 +37,35 @@ macro_rules! ws {
     };
 }
 
+/// Capacity of the scratch buffer which is retained between strings.
+const SCRATCH_RETAIN: usize = 4096;
+
+/// The content of a string which is being built from the input.
+///
+/// Content borrows from the input for as long as it's contiguous with it, so
+/// that large strings such as long single lines don't have to be copied
+/// through the scratch buffer.
+enum Content {
+    /// No content.
+    Empty,
+    /// Content which is the given range of the input.
+    Input(Range<usize>),
+    /// Content which is stored in the scratch buffer.
+    Scratch,
+}
+
+/// A key in a block mapping.
+enum Key {
+    /// A key followed by a colon, like `key: value`.
+    Implicit(raw::String),
+    /// An explicit key without a value, like `? key`, and the whitespace
+    /// following the `?` indicator.
+    Explicit(StringId, raw::String),
+    /// An explicit key which is parsed as a value of its own, like a
+    /// collection or a key which is followed by a `:` on a separate line.
+    Complex,
+}
+
 struct State {
     prefix: StringId,
     parent: Option<Id>,
@@ -85,29 +119,465 @@ impl State {
     }
 }
 
+/// A YAML parser for a stream of documents which is fed input chunk by chunk.
+///
+/// This is useful when input arrives in pieces, such as when it's read from a
+/// network stream or produced by an async runtime. Documents are separated by
+/// `---` markers like in [`Parser::parse_stream`], and each document is parsed
+/// and returned by [`StreamParser::feed`] as soon as the marker of the
+/// document following it has been fed. Only the input of the document which
+/// is currently being fed is buffered, so a long stream can be parsed without
+/// keeping all of it in memory.
+///
+/// Since the meaning of a line in a block collection depends on the lines
+/// which follow it, and since a [`Document`] retains all of its input to be
+/// able to reproduce it, each individual document is kept in memory until it
+/// has been completed.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut parser = yaml::StreamParser::new();
+///
+/// assert!(parser.feed(b"name: a\n-")?.is_empty());
+///
+/// let docs = parser.feed(b"--\nname: ")?;
+/// assert_eq!(docs.len(), 1);
+/// assert_eq!(docs[0].to_string(), "name: a\n");
+/// assert_eq!(parser.buffered(), 10);
+///
+/// let docs = parser.finish()?;
+/// let root = docs[0].as_ref().as_mapping().context("missing root")?;
+/// assert_eq!(root.get("name").and_then(|v| v.as_str()), Some(""));
+/// assert_eq!(docs[0].to_string(), "---\nname: ");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamParser {
+    /// The input of the document which is currently being fed.
+    buf: Vec<u8>,
+    /// The number of bytes of input which preceded `buf`.
+    offset: usize,
+    /// Where the first line of `buf` which hasn't been scanned for markers
+    /// starts.
+    line: usize,
+    /// Where the body of the current document starts after its `---` marker,
+    /// or `None` if no marker has been found in the first document.
+    body: Option<usize>,
+    options: Options,
+    warnings: Vec<Error>,
+}
+
+impl StreamParser {
+    /// Construct a new stream parser without any input.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configure how keys in inline mappings are parsed.
+    ///
+    /// See [`Parser::strict_flow_keys`] for details.
+    #[must_use]
+    pub fn strict_flow_keys(mut self, strict_flow_keys: bool) -> Self {
+        self.options.strict_flow_keys = strict_flow_keys;
+        self
+    }
+
+    /// Configure how unknown escape sequences in double-quoted strings are
+    /// handled.
+    ///
+    /// See [`Parser::lenient_escapes`] for details.
+    #[must_use]
+    pub fn lenient_escapes(mut self, lenient_escapes: bool) -> Self {
+        self.options.lenient_escapes = lenient_escapes;
+        self
+    }
+
+    /// Configure the schema used to resolve plain scalars.
+    ///
+    /// See [`Parser::schema`] for details.
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.options.schema = schema;
+        self
+    }
+
+    /// Feed a chunk of input to the parser, returning the documents which
+    /// were completed by it.
+    ///
+    /// Chunks may be split anywhere, including in the middle of a multi-byte
+    /// character or a `---` marker.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case a completed document cannot be parsed as YAML. The span
+    /// of the error refers to the input as if all chunks were concatenated.
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut parser = yaml::StreamParser::new();
+    /// parser.feed(b"a: 1\n---\n[1, ")?;
+    ///
+    /// let error = parser.feed(b"2\n---\n").unwrap_err();
+    /// assert_eq!(error.span(), 14..15);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Document>> {
+        self.buf.extend_from_slice(chunk);
+
+        let mut documents = Vec::new();
+
+        while let Some(n) = self
+            .buf
+            .get(self.line..)
+            .and_then(|b| b.find_byte(raw::NEWLINE))
+        {
+            let start = self.line;
+            self.line = start.wrapping_add(n).wrapping_add(1);
+            documents.extend(self.scan(start)?);
+        }
+
+        Ok(documents)
+    }
+
+    /// Get the number of bytes which are buffered for the document which is
+    /// currently being fed.
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Finish feeding input, returning the remaining documents.
+    ///
+    /// At least one document is always returned, since the input following
+    /// the last `---` marker is a document even if it's empty.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case a remaining document cannot be parsed as YAML.
+    pub fn finish(self) -> Result<Vec<Document>> {
+        let (documents, _) = self.finish_with_warnings()?;
+        Ok(documents)
+    }
+
+    /// Finish feeding input, returning the remaining documents and the
+    /// warnings for every document in the stream.
+    ///
+    /// See [`Parser::parse_with_warnings`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case a remaining document cannot be parsed as YAML.
+    pub fn finish_with_warnings(mut self) -> Result<(Vec<Document>, Vec<Error>)> {
+        let mut documents = Vec::new();
+
+        // The last line doesn't have to end with a newline.
+        if self.line < self.buf.len() {
+            let start = self.line;
+            self.line = self.buf.len();
+            documents.extend(self.scan(start)?);
+        }
+
+        documents.push(self.parse(self.buf.len())?);
+        Ok((documents, self.warnings))
+    }
+
+    /// Scan the line starting at `start` for a `---` marker, returning the
+    /// current document if the marker completes it.
+    fn scan(&mut self, start: usize) -> Result<Option<Document>> {
+        let line = self.buf.get(start..self.line).unwrap_or_default();
+
+        if !stream::is_marker(line, stream::START) {
+            return Ok(None);
+        }
+
+        let header = match self.body {
+            // The first document only has a header if nothing but comments and
+            // directives precede its marker.
+            None if stream::is_preamble(self.buf.get(..start).unwrap_or_default()) => {
+                self.body = Some(start.wrapping_add(stream::START.len()));
+                return Ok(None);
+            }
+            body => stream::header_start(&self.buf, body.unwrap_or_default(), start),
+        };
+
+        let document = self.parse(header)?;
+        self.buf.drain(..header);
+        self.offset = self.offset.wrapping_add(header);
+        self.line = self.line.wrapping_sub(header);
+        self.body = Some(start.wrapping_sub(header).wrapping_add(stream::START.len()));
+        Ok(Some(document))
+    }
+
+    /// Parse the buffered input up until `end` as a document.
+    fn parse(&mut self, end: usize) -> Result<Document> {
+        let input = self.buf.get(..end).unwrap_or_default();
+
+        let (document, warnings) = Parser::new(input)
+            .with_options(self.options)
+            .parse_with_warnings()
+            .map_err(|error| error.offset(self.offset))?;
+
+        let offset = self.offset;
+        self.warnings
+            .extend(warnings.into_iter().map(|error| error.offset(offset)));
+        Ok(document)
+    }
+}
+
 /// A YAML parser.
+///
+/// Most users should use [`from_slice`][crate::yaml::from_slice], the parser
+/// only needs to be constructed directly to change how input is parsed.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::Parser::new(b"{a: 1}").strict_flow_keys(true).parse()?;
+/// let root = doc.as_ref().as_mapping().context("missing root")?;
+/// assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+/// # Ok::<_, anyhow::Error>(())
+/// ```
 #[derive(Clone)]
 pub struct Parser<'a> {
     scratch: Vec<u8>,
     data: Data,
     input: &'a [u8],
     n: usize,
+    options: Options,
+    warnings: Vec<Error>,
+}
+
+/// The configuration of a [`Parser`], which is retained by the documents it
+/// parses so that they can be parsed again the same way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Options {
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    strict_flow_keys: bool,
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    lenient_escapes: bool,
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    schema: Schema,
 }
 
 impl<'a> Parser<'a> {
     /// Construct a new default parser.
-    pub(crate) fn new(input: &'a [u8]) -> Self {
+    #[must_use]
+    pub fn new(input: &'a [u8]) -> Self {
         Self {
             scratch: Vec::new(),
             data: Data::default(),
             input,
             n: 0,
+            options: Options::default(),
+            warnings: Vec::new(),
         }
     }
 
-    /// Parses a single value, and returns its kind.
-    pub(crate) fn parse(mut self) -> Result<Document> {
-        let prefix = self.start_of_document();
+    /// Configure how keys in inline mappings such as `{a:1}` are parsed.
+    ///
+    /// By default a key in an inline mapping extends up until the first colon
+    /// (`:`), regardless of what follows it. So `{a:1, b:2}` is parsed as a
+    /// mapping with the keys `a` and `b`, which is what most files in the wild
+    /// intend.
+    ///
+    /// With strict flow keys enabled the specification is followed, and the
+    /// colon only separates a key from its value if it's followed by
+    /// whitespace, a flow indicator, or if it immediately follows a quoted key
+    /// such as in `{"a":1}`. Since keys without values are not supported,
+    /// `{a:1}` is rejected with [`ErrorKind::BadMappingSeparator`] since it
+    /// contains the single key `a:1`.
+    ///
+    /// [`ErrorKind::BadMappingSeparator`]: crate::yaml::ErrorKind::BadMappingSeparator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::Parser::new(b"{a:1, b:2}").parse()?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    /// assert_eq!(root.get("b").and_then(|v| v.as_u32()), Some(2));
+    /// assert_eq!(doc.to_string(), "{a:1, b:2}");
+    ///
+    /// let error = yaml::Parser::new(b"{a:1, b:2}").strict_flow_keys(true).parse().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadMappingSeparator);
+    ///
+    /// let doc = yaml::Parser::new(br#"{"a":1, b: c:d}"#).strict_flow_keys(true).parse()?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    /// assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("c:d"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn strict_flow_keys(mut self, strict_flow_keys: bool) -> Self {
+        self.options.strict_flow_keys = strict_flow_keys;
+        self
+    }
+
+    /// Configure how unknown escape sequences in double-quoted strings such
+    /// as `"\q"` are handled.
+    ///
+    /// By default they are rejected with [`ErrorKind::BadEscape`]. With
+    /// lenient escapes enabled, unknown escape sequences are instead preserved
+    /// verbatim in the parsed string, and reported as warnings by
+    /// [`Parser::parse_with_warnings`]. This allows files containing
+    /// technically invalid escapes to still be edited nondestructively.
+    ///
+    /// Malformed hex and unicode escapes such as `"\xZZ"` are always rejected.
+    ///
+    /// [`ErrorKind::BadEscape`]: crate::yaml::ErrorKind::BadEscape
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = r#"path: "C:\qux\tmp""#;
+    ///
+    /// let error = yaml::Parser::new(INPUT.as_bytes()).parse().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadEscape);
+    ///
+    /// let (doc, warnings) = yaml::Parser::new(INPUT.as_bytes())
+    ///     .lenient_escapes(true)
+    ///     .parse_with_warnings()?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("path").and_then(|v| v.as_str()), Some("C:\\qux\tmp"));
+    /// assert_eq!(doc.to_string(), INPUT);
+    ///
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(*warnings[0].kind(), yaml::ErrorKind::BadEscape);
+    /// assert_eq!(&INPUT[warnings[0].span()], "\\q");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn lenient_escapes(mut self, lenient_escapes: bool) -> Self {
+        self.options.lenient_escapes = lenient_escapes;
+        self
+    }
+
+    /// Configure the schema used to resolve plain scalars such as `yes`.
+    ///
+    /// By default the YAML 1.2 core schema is used, where only `true` and
+    /// `false` are booleans. With [`Schema::Yaml11`], plain `yes`, `no`, `on`
+    /// and `off` are also parsed as booleans, so they can be accessed through
+    /// [`Value::as_bool`] and deserialized as such through [`serde`]. Numbers
+    /// like `1_000_000` are also parsed as numbers. Their original spelling is
+    /// preserved when the document is written. With [`Schema::Core`], plain
+    /// scalars are resolved exactly as the core schema specifies, so that
+    /// `1.6.2` is a string and `+1e2` is a number. [`Schema::Json`] only
+    /// resolves `true`, `false`, `null` and numbers spelled like in JSON.
+    ///
+    /// Mapping keys are not affected, so the `on` key in a GitHub Actions
+    /// workflow is still a string. Only looking up keys structurally through
+    /// [`Mapping::get_value`] resolves them with the schema.
+    ///
+    /// [`Value::as_bool`]: crate::yaml::Value::as_bool
+    /// [`Mapping::get_value`]: crate::yaml::Mapping::get_value
+    /// [`serde`]: https://docs.rs/serde
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// const INPUT: &str = "enabled: yes\nverbose: Off\n";
+    ///
+    /// let doc = yaml::from_slice(INPUT)?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("enabled").and_then(|v| v.as_bool()), None);
+    /// assert_eq!(root.get("enabled").and_then(|v| v.as_str()), Some("yes"));
+    ///
+    /// let doc = yaml::Parser::new(INPUT.as_bytes()).schema(yaml::Schema::Yaml11).parse()?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("enabled").and_then(|v| v.as_bool()), Some(true));
+    /// assert_eq!(root.get("verbose").and_then(|v| v.as_bool()), Some(false));
+    /// assert_eq!(doc.to_string(), INPUT);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn schema(mut self, schema: Schema) -> Self {
+        self.options.schema = schema;
+        self
+    }
+
+    /// Parse the input as a single document.
+    ///
+    /// If the input starts with a byte order mark it is not considered part
+    /// of the document, and input starting with a UTF-16 byte order mark is
+    /// decoded before it's parsed. See [`Bom`] for details.
+    ///
+    /// [`Bom`]: crate::yaml::Bom
+    ///
+    /// # Errors
+    ///
+    /// Errors in case the document cannot be parsed as YAML. The spans of
+    /// errors in documents encoded as UTF-16 refer to the decoded input.
+    pub fn parse(self) -> Result<Document> {
+        let (document, _) = self.parse_with_warnings()?;
+        Ok(document)
+    }
+
+    /// Parse the input as a single document, returning any warnings about
+    /// input which was tolerated but is not valid YAML.
+    ///
+    /// Warnings are only produced when a lenient mode such as
+    /// [`Parser::lenient_escapes`] is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case the document cannot be parsed as YAML.
+    pub fn parse_with_warnings(mut self) -> Result<(Document, Vec<Error>)> {
+        let Some(bom) = Bom::detect(self.input) else {
+            return self.parse_document();
+        };
+
+        let start = bom.as_bytes().len();
+        let input = self.input.get(start..).unwrap_or_default();
+
+        let (document, warnings) = if bom.is_utf16() {
+            let decoded = bom
+                .decode(input)
+                .map_err(|span| Error::new(span, ErrorKind::BadUtf16).offset(start))?;
+            self.with_input(&decoded).parse_document()?
+        } else {
+            self.input = input;
+            let (document, warnings) = self.parse_document().map_err(|e| e.offset(start))?;
+            let warnings = warnings.into_iter().map(|e| e.offset(start)).collect();
+            (document, warnings)
+        };
+
+        Ok((document.with_bom(Some(bom)), warnings))
+    }
+
+    /// Parse the input as a single document which doesn't start with a byte
+    /// order mark.
+    fn parse_document(mut self) -> Result<(Document, Vec<Error>)> {
+        let input = self.input;
+
+        // Content can't follow an explicit end marker, so the document is
+        // parsed up until it.
+        let marker = stream::markers(input, stream::END).next();
+
+        if let Some(marker) = marker {
+            self.input = input.get(..marker).unwrap_or_default();
+        }
+
+        let (header, prefix) = self.start_of_document();
 
         let (root, suffix) = self.value(&State::new(prefix).with_tabular())?;
 
@@ -117,28 +587,97 @@ impl<'a> Parser<'a> {
         };
 
         if !self.is_eof() {
-            return Err(Error::new(self.n..self.input.len(), ErrorKind::ExpectedEof));
+            let error = Error::new(self.n..self.input.len(), ErrorKind::ExpectedEof);
+
+            return Err(match self.eof_suggestion() {
+                Some(suggestion) => error.with_suggestion(suggestion),
+                None => error,
+            });
         }
 
-        Ok(Document::new(suffix, root, self.data))
+        let end = match marker {
+            Some(marker) => {
+                self.input = input;
+                self.n = marker.saturating_add(stream::END.len());
+                let end = self.ws();
+
+                if !self.is_eof() {
+                    return Err(Error::new(self.n..self.input.len(), ErrorKind::ExpectedEof));
+                }
+
+                Some(end)
+            }
+            None => None,
+        };
+
+        // Line breaks inserted by edits follow the style of the input.
+        self.data.set_crlf(raw::is_crlf(input));
+
+        let document = Document::new(suffix, root, self.data)
+            .with_header(header)
+            .with_end(end)
+            .with_options(self.options);
+        Ok((document, self.warnings))
     }
 
-    /// Process document delimiter.
+    /// Parse the input as a stream of documents separated by `---` markers.
     ///
-    /// This is a `---` that is allowed to exist at the beginning of the document.
-    fn start_of_document(&mut self) -> StringId {
+    /// Each document is parsed using the configuration of this parser, see
+    /// [`Stream`] for details.
+    ///
+    /// # Errors
+    ///
+    /// Errors in case any document in the stream cannot be parsed as YAML.
+    /// The span of the error refers to the whole input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let parser = yaml::Parser::new(b"a: 1\n---\n{a:1}").strict_flow_keys(true);
+    ///
+    /// let error = parser.parse_stream().unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadMappingSeparator);
+    /// assert_eq!(error.span(), 10..13);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn parse_stream(self) -> Result<Stream> {
+        stream::parse(&self, self.input)
+    }
+
+    /// Construct a parser with the same configuration for the given input.
+    pub(crate) fn with_input<'b>(&self, input: &'b [u8]) -> Parser<'b> {
+        Parser::new(input).with_options(self.options)
+    }
+
+    /// Use the given configuration for the parser.
+    pub(crate) fn with_options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    /// Process the header of the document, returning the header and the
+    /// prefix of the root value.
+    ///
+    /// The header consists of any directives like `%YAML 1.2` followed by a
+    /// `---`, which are allowed to exist at the beginning of the document.
+    fn start_of_document(&mut self) -> (Option<StringId>, StringId) {
+        let start = self.n;
+        let mut end = None;
         let mut prefix = self.ws();
 
         loop {
             match self.peek() {
-                // Process headers.
+                // Process directives.
                 [b'%', _, _] => {
                     self.find(raw::NEWLINE);
+                    end = Some(self.n);
                     prefix = self.ws();
                 }
                 // Process start-of-document.
                 [b'-', b'-', b'-'] => {
                     self.bump(3);
+                    end = Some(self.n);
                     prefix = self.ws();
                     break;
                 }
@@ -148,7 +687,44 @@ impl<'a> Parser<'a> {
             }
         }
 
-        prefix
+        let header = end.map(|end| {
+            let header = self.input.get(start..end).unwrap_or_default();
+            self.data.insert_str(header)
+        });
+
+        (header, prefix)
+    }
+
+    /// Suggest a fix for trailing input which couldn't be parsed, based on
+    /// the line it starts on and the line before it.
+    fn eof_suggestion(&self) -> Option<&'static str> {
+        let before = self.input.get(..self.n).unwrap_or_default();
+        let start = memchr::memrchr(raw::NEWLINE, before).map_or(0, |n| n.wrapping_add(1));
+
+        let after = self.input.get(self.n..).unwrap_or_default();
+        let end = memchr::memchr(raw::NEWLINE, after)
+            .map_or(self.input.len(), |n| self.n.wrapping_add(n));
+
+        let line = self.input.get(start..end).unwrap_or_default();
+
+        let before = self
+            .input
+            .get(..start.saturating_sub(1))
+            .unwrap_or_default();
+        let previous = match memchr::memrchr(raw::NEWLINE, before) {
+            Some(n) => before.get(n.wrapping_add(1)..).unwrap_or_default(),
+            None => before,
+        };
+
+        if has_tab_indent(line) || has_tab_indent(previous) {
+            return Some("indent with spaces instead of tabs");
+        }
+
+        if has_unspaced_colon(line) || has_unspaced_colon(previous) {
+            return Some("add a space after `:` to separate the key from its value");
+        }
+
+        None
     }
 
     /// Test if eof.
@@ -189,6 +765,127 @@ impl<'a> Parser<'a> {
         self.input.get(start..self.n).unwrap_or_default()
     }
 
+    /// Construct a plain string from the input between `start` and the cursor.
+    ///
+    /// Plain strings which span multiple lines in flow collections are
+    /// folded, so that each line break reads as a space unless it's followed
+    /// by empty lines, which read as line breaks. Leading whitespace on
+    /// continuation lines is not part of the string. The input is written
+    /// back as-is.
+    fn plain(&mut self, start: usize) -> raw::String {
+        let original = self.string(start);
+
+        if !original.contains(&raw::NEWLINE) {
+            let string = self.data.insert_str(original);
+            return raw::String::new(raw::RawStringKind::Bare, string, string);
+        }
+
+        let mut lines = original.split(|&b| b == raw::NEWLINE).peekable();
+        let first = lines.next().unwrap_or_default();
+        self.scratch
+            .extend_from_slice(first.trim_end_with(|c| matches!(c, ' ' | '\t' | '\r')));
+
+        let mut breaks = 0;
+
+        while let Some(line) = lines.next() {
+            let line = line.trim_start_with(|c| matches!(c, ' ' | '\t'));
+
+            let line = if lines.peek().is_some() {
+                line.trim_end_with(|c| matches!(c, ' ' | '\t' | '\r'))
+            } else {
+                line
+            };
+
+            if line.is_empty() {
+                breaks += 1;
+                continue;
+            }
+
+            if breaks == 0 {
+                self.scratch.push(raw::SPACE);
+            } else {
+                self.scratch
+                    .extend(std::iter::repeat(raw::NEWLINE).take(breaks));
+            }
+
+            self.scratch.extend_from_slice(line);
+            breaks = 0;
+        }
+
+        let string = self.insert_scratch();
+        let original = self.data.insert_str(original);
+        raw::String::new(raw::RawStringKind::Original, string, original)
+    }
+
+    /// Insert the contents of the scratch buffer as a string and clear it.
+    ///
+    /// Capacity beyond [`SCRATCH_RETAIN`] is released, so that a single large
+    /// string doesn't keep its memory around for the rest of parsing.
+    fn insert_scratch(&mut self) -> StringId {
+        let string = self.data.insert_str(&self.scratch);
+        self.scratch.clear();
+        self.scratch.shrink_to(SCRATCH_RETAIN);
+        string
+    }
+
+    /// Extend `content` with the input in `range`.
+    fn content_extend(&mut self, content: &mut Content, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+
+        *content = match *content {
+            Content::Empty => Content::Input(range),
+            Content::Input(ref current) if current.end == range.start => {
+                Content::Input(current.start..range.end)
+            }
+            Content::Input(ref current) => {
+                let current = self.input.get(current.clone()).unwrap_or_default();
+                self.scratch.extend_from_slice(current);
+                self.scratch
+                    .extend_from_slice(self.input.get(range).unwrap_or_default());
+                Content::Scratch
+            }
+            Content::Scratch => {
+                self.scratch
+                    .extend_from_slice(self.input.get(range).unwrap_or_default());
+                Content::Scratch
+            }
+        };
+    }
+
+    /// Push a byte to `content`, which keeps borrowing from the input if the
+    /// byte is the one which follows it.
+    fn content_push(&mut self, content: &mut Content, b: u8) {
+        match *content {
+            Content::Input(ref current) if self.input.get(current.end) == Some(&b) => {
+                *content = Content::Input(current.start..current.end.wrapping_add(1));
+            }
+            Content::Input(ref current) => {
+                let current = self.input.get(current.clone()).unwrap_or_default();
+                self.scratch.extend_from_slice(current);
+                self.scratch.push(b);
+                *content = Content::Scratch;
+            }
+            Content::Empty | Content::Scratch => {
+                self.scratch.push(b);
+                *content = Content::Scratch;
+            }
+        }
+    }
+
+    /// Insert `content` as a string.
+    fn content_insert(&mut self, content: Content) -> StringId {
+        match content {
+            Content::Empty => self.data.insert_str(""),
+            Content::Input(range) => {
+                let string = self.input.get(range).unwrap_or_default();
+                self.data.insert_str(string)
+            }
+            Content::Scratch => self.insert_scratch(),
+        }
+    }
+
     /// Find the given character.
     fn find(&mut self, a: u8) {
         let input = self.input.get(self.n..).unwrap_or_default();
@@ -200,6 +897,46 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Find the end of the current line, which excludes the carriage return
+    /// of a `\r\n` line break.
+    fn find_eol(&mut self) {
+        let start = self.n;
+        self.find(raw::NEWLINE);
+        self.rewind_cr(start);
+    }
+
+    /// Rewind over a carriage return preceding the current position, as long
+    /// as it's not before `start`.
+    fn rewind_cr(&mut self, start: usize) {
+        if self.n > start && self.input.get(self.n.wrapping_sub(1)) == Some(&b'\r') {
+            self.n = self.n.wrapping_sub(1);
+        }
+    }
+
+    /// Rewind over whitespace preceding the current position, as long as it's
+    /// not before `start`.
+    fn rewind_ws(&mut self, start: usize) {
+        while self.n > start && self.is_ws_before() {
+            self.n = self.n.wrapping_sub(1);
+        }
+    }
+
+    /// Test if the character preceding the current position is whitespace.
+    fn is_ws_before(&self) -> bool {
+        matches!(self.input.get(self.n.wrapping_sub(1)).copied(), Some(ws!()))
+    }
+
+    /// Find any of the given characters.
+    fn find3(&mut self, a: u8, b: u8, c: u8) {
+        let input = self.input.get(self.n..).unwrap_or_default();
+
+        if let Some(n) = memchr::memchr3(a, b, c, input) {
+            self.bump(n);
+        } else {
+            self.n = self.input.len();
+        }
+    }
+
     /// Find the given character.
     fn find2(&mut self, a: u8, b: u8) {
         let input = self.input.get(self.n..).unwrap_or_default();
@@ -237,7 +974,35 @@ impl<'a> Parser<'a> {
         self.ws_nl().0
     }
 
-    /// Test if current position contains nothing but whitespace until we reach a line end.
+    /// Split a comment following a value on the same line from the whitespace
+    /// `ws` which was just consumed, so that it can be stored as the suffix of
+    /// the item which owns the value.
+    fn trailing_comment(&mut self, ws: StringId) -> (Option<StringId>, StringId) {
+        let string = self.data.str(ws);
+        let start = self.n.wrapping_sub(string.len());
+
+        // The whitespace must directly precede the current position and follow
+        // content on the same line, which is not the case after block scalars.
+        if self.input.get(start..self.n) != Some(string)
+            || matches!(
+                self.input.get(start.wrapping_sub(1)),
+                None | Some(&raw::NEWLINE)
+            )
+        {
+            return (None, ws);
+        }
+
+        let Some(comment) = raw::trailing_comment(string) else {
+            return (None, ws);
+        };
+
+        let rest = string.get(comment.len()..).unwrap_or_default().to_vec();
+        let comment = self.data.insert_str(comment);
+        (Some(comment), self.data.insert_str(rest))
+    }
+
+    /// Test if current position contains nothing but whitespace until we reach
+    /// a line end or a comment.
     fn is_eol(&self) -> bool {
         let mut n = self.n;
 
@@ -246,6 +1011,9 @@ impl<'a> Parser<'a> {
                 raw::NEWLINE => {
                     return true;
                 }
+                b'#' if n > self.n => {
+                    return true;
+                }
                 other_ws!() => {
                     n = n.wrapping_add(1);
                 }
@@ -258,15 +1026,64 @@ impl<'a> Parser<'a> {
         true
     }
 
+    /// Test if the cursor is at the end of a scalar in a flow collection,
+    /// which excludes plain scalars continuing after whitespace or on the
+    /// following lines.
+    fn is_flow_end(&self) -> bool {
+        let mut n = self.n;
+
+        loop {
+            match self.input.get(n..).unwrap_or_default() {
+                [] | [flow_end!(), ..] | [b':', ws!(flow_end!()), ..] | [b':'] => return true,
+                [b'#', ..] => return n > self.n,
+                [ws!(), ..] => {}
+                _ => return false,
+            }
+
+            n = n.wrapping_add(1);
+        }
+    }
+
     /// Consume a single number.
     fn number(&mut self, s: &State, start: usize) -> Option<Raw> {
         let mut hint = serde_hint::U64;
 
-        if matches!(self.peek1(), b'-') {
+        let negative = matches!(self.peek1(), b'-');
+
+        if negative {
             hint = serde_hint::I64;
             self.bump(1);
         }
 
+        let rest = self.input.get(self.n..).unwrap_or_default();
+
+        let any = if let Some(len) = number::special_len(rest, negative) {
+            hint = serde_hint::F64;
+            self.bump(len);
+            true
+        } else {
+            self.digits(&mut hint)
+        };
+
+        if !any {
+            return None;
+        }
+
+        if s.tabular && !self.is_eol() {
+            return None;
+        }
+
+        if s.inline && !self.is_flow_end() {
+            return None;
+        }
+
+        let string = self.data.insert_str(self.string(start));
+        Some(Raw::Number(raw::Number::new(string, hint)))
+    }
+
+    /// Consume the digits of a number, returning `true` if anything was
+    /// consumed.
+    fn digits(&mut self, hint: &mut RawNumberHint) -> bool {
         let mut wants_dot = true;
         let mut wants_e = true;
         let mut has_number = false;
@@ -275,17 +1092,20 @@ impl<'a> Parser<'a> {
         loop {
             match self.peek1() {
                 b'.' if wants_dot => {
-                    hint = serde_hint::F64;
+                    *hint = serde_hint::F64;
                     wants_dot = false;
                 }
                 b'e' | b'E' if has_number && wants_e => {
-                    hint = serde_hint::F64;
+                    *hint = serde_hint::F64;
                     wants_dot = false;
                     wants_e = false;
                 }
                 b'0'..=b'9' => {
                     has_number = true;
                 }
+                // YAML 1.1 allows digits to be separated by underscores,
+                // like `1_000`.
+                b'_' if has_number && wants_e && self.options.schema == Schema::Yaml11 => {}
                 _ => {
                     break;
                 }
@@ -295,16 +1115,7 @@ impl<'a> Parser<'a> {
             self.bump(1);
         }
 
-        if !any {
-            return None;
-        }
-
-        if s.tabular && !self.is_eol() {
-            return None;
-        }
-
-        let string = self.data.insert_str(self.string(start));
-        Some(Raw::Number(raw::Number::new(string, hint)))
+        any
     }
 
     /// Insert a null value as a placeholder.
@@ -314,59 +1125,68 @@ impl<'a> Parser<'a> {
     }
 
     /// Read a double-quoted string.
-    fn single_quoted(&mut self) -> raw::String {
+    fn single_quoted(&mut self) -> Result<raw::String> {
         let original = self.n;
         self.bump(1);
         let start = self.n;
 
-        loop {
-            match self.peek() {
-                [b'\'', b'\''] => {
-                    return self.single_quoted_escaped(start, original);
-                }
-                [b'\'', _] => {
-                    break;
-                }
-                _ => {
-                    self.bump(1);
-                }
+        self.find(b'\'');
+
+        match self.peek() {
+            _ if self.is_eof() => {
+                return Err(self.unterminated_string(original, b'\''));
+            }
+            [b'\'', b'\''] => {
+                return self.single_quoted_escaped(start, original);
             }
+            _ => {}
         }
 
         let string = self.data.insert_str(self.string(start));
-        self.bump(usize::from(!self.is_eof()));
+        self.bump(1);
         let original = self.data.insert_str(self.string(original));
 
-        raw::String::new(raw::RawStringKind::Original, string, original)
+        Ok(raw::String::new(
+            raw::RawStringKind::Original,
+            string,
+            original,
+        ))
     }
 
     /// Read a single-quoted escaped string.
-    fn single_quoted_escaped(&mut self, start: usize, original: usize) -> raw::String {
+    fn single_quoted_escaped(&mut self, start: usize, original: usize) -> Result<raw::String> {
         self.scratch.extend(self.string(start));
 
         loop {
+            let start = self.n;
+            self.find(b'\'');
+            self.scratch.extend(self.string(start));
+
             match self.peek() {
+                _ if self.is_eof() => {
+                    self.scratch.clear();
+                    return Err(self.unterminated_string(original, b'\''));
+                }
                 [b'\'', b'\''] => {
                     self.bump(2);
                     self.scratch.push(b'\'');
                 }
-                [b'\'', _] => {
+                _ => {
                     break;
                 }
-                [b, _] => {
-                    self.bump(1);
-                    self.scratch.push(b);
-                }
             }
         }
 
-        let string = self.data.insert_str(&self.scratch);
-        self.scratch.clear();
+        let string = self.insert_scratch();
         self.bump(1);
 
         let original = self.data.insert_str(self.string(original));
 
-        raw::String::new(raw::RawStringKind::Original, string, original)
+        Ok(raw::String::new(
+            raw::RawStringKind::Original,
+            string,
+            original,
+        ))
     }
 
     /// Read a double-quoted string.
@@ -375,20 +1195,20 @@ impl<'a> Parser<'a> {
         self.bump(1);
         let start = self.n;
 
-        loop {
-            match self.peek1() {
-                b'"' | EOF => break,
-                b'\\' => {
-                    return self.double_quoted_escaped(start, original);
-                }
-                _ => {
-                    self.bump(1);
-                }
+        self.find2(b'"', b'\\');
+
+        match self.peek1() {
+            _ if self.is_eof() => {
+                return Err(self.unterminated_string(original, b'"'));
+            }
+            b'\\' => {
+                return self.double_quoted_escaped(start, original);
             }
+            _ => {}
         }
 
         let string = self.data.insert_str(self.string(start));
-        self.bump(usize::from(!self.is_eof()));
+        self.bump(1);
         let original = self.data.insert_str(self.string(original));
 
         Ok(raw::String::new(
@@ -403,22 +1223,29 @@ impl<'a> Parser<'a> {
         self.scratch.extend(self.string(start));
 
         loop {
+            let start = self.n;
+            self.find2(b'"', b'\\');
+            self.scratch.extend(self.string(start));
+
             match self.peek1() {
-                b'"' | EOF => break,
+                _ if self.is_eof() => {
+                    self.scratch.clear();
+                    return Err(self.unterminated_string(original, b'"'));
+                }
                 b'\\' => {
                     let start = self.n;
                     self.bump(1);
-                    self.unescape(start)?;
-                }
-                b => {
-                    self.scratch.push(b);
-                    self.bump(1);
+
+                    if let Err(error) = self.unescape(start) {
+                        self.scratch.clear();
+                        return Err(error);
+                    }
                 }
+                _ => break,
             }
         }
 
-        let string = self.data.insert_str(&self.scratch);
-        self.scratch.clear();
+        let string = self.insert_scratch();
         self.bump(1);
 
         let original = self.data.insert_str(self.string(original));
@@ -430,40 +1257,115 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    /// Construct an error for a string starting at `start` which is missing
+    /// its closing `quote`.
+    fn unterminated_string(&self, start: usize, quote: u8) -> Error {
+        let error = Error::new(start..self.n, ErrorKind::UnterminatedString);
+
+        if quote == b'"' {
+            error.with_suggestion("add a closing `\"` to the string")
+        } else {
+            error.with_suggestion("add a closing `'` to the string")
+        }
+    }
+
     /// Unescape into the scratch buffer.
     fn unescape(&mut self, start: usize) -> Result<()> {
-        let b = match self.peek1() {
-            b'n' => raw::NEWLINE,
-            b'0' => b'\x00',
-            b'a' => b'\x07',
-            b'b' => b'\x08',
-            b't' => b'\x09',
-            b'v' => b'\x0b',
-            b'f' => b'\x0c',
-            b'r' => b'\r',
-            b'e' => b'\x1b',
-            b'\\' => b'\"',
-            b'x' => {
+        let b = self.peek1();
+
+        let count = match b {
+            b'x' => 2,
+            b'u' => 4,
+            b'U' => 8,
+            // An escaped line break continues the string on the next line,
+            // leaving out the line break and the indentation which follows.
+            raw::NEWLINE | b'\r' => {
                 self.bump(1);
-                return self.unescape_unicode(start, 2, ErrorKind::BadHexEscape);
-            }
-            b'u' => {
-                self.bump(1);
-                return self.unescape_unicode(start, 4, ErrorKind::BadUnicodeEscape);
+
+                if b == b'\r' && self.peek1() == raw::NEWLINE {
+                    self.bump(1);
+                }
+
+                while matches!(self.peek1(), b' ' | b'\t') {
+                    self.bump(1);
+                }
+
+                return Ok(());
             }
             _ => {
                 self.bump(1);
-                return Err(Error::new(start..self.n, ErrorKind::BadEscape));
+
+                if let Some(c) = raw::unescape(b) {
+                    self.scratch.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    return Ok(());
+                }
+
+                let error = Error::new(start..self.n, ErrorKind::BadEscape);
+
+                if !self.options.lenient_escapes || self.is_eof() {
+                    return Err(error);
+                }
+
+                self.scratch.extend([b'\\', b]);
+                self.warnings.push(error);
+                return Ok(());
             }
         };
 
-        self.scratch.push(b);
+        let err = if count == 2 {
+            ErrorKind::BadHexEscape
+        } else {
+            ErrorKind::BadUnicodeEscape
+        };
+
         self.bump(1);
-        Ok(())
+        self.unescape_unicode(start, count, err)
     }
 
     /// Unescape a unicode character into the scratch buffer.
+    ///
+    /// A `\u` escape of a UTF-16 high surrogate must be immediately followed
+    /// by a `\u` escape of a low surrogate, in which case they are combined
+    /// into a single character.
     fn unescape_unicode(&mut self, start: usize, count: usize, err: ErrorKind) -> Result<()> {
+        let mut c = self.hex(start, count, &err)?;
+
+        if count == 4 {
+            match c {
+                0xd800..=0xdbff => {
+                    let high = start..self.n;
+
+                    if self.peek() != [b'\\', b'u'] {
+                        return Err(Error::new(high, ErrorKind::UnpairedSurrogate));
+                    }
+
+                    let low_start = self.n;
+                    self.bump(2);
+                    let low = self.hex(low_start, 4, &err)?;
+
+                    if !(0xdc00..=0xdfff).contains(&low) {
+                        return Err(Error::new(high, ErrorKind::UnpairedSurrogate));
+                    }
+
+                    c = 0x10000 + ((c - 0xd800) << 10) + (low - 0xdc00);
+                }
+                0xdc00..=0xdfff => {
+                    return Err(Error::new(start..self.n, ErrorKind::UnpairedSurrogate));
+                }
+                _ => {}
+            }
+        }
+
+        let Some(c) = char::from_u32(c) else {
+            return Err(Error::new(start..self.n, err));
+        };
+
+        self.scratch.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    /// Parse `count` hex digits of an escape sequence starting at `start`.
+    fn hex(&mut self, start: usize, count: usize, err: &ErrorKind) -> Result<u32> {
         let mut c: u32 = 0;
 
         for _ in 0..count {
@@ -475,19 +1377,14 @@ impl<'a> Parser<'a> {
                 b @ b'A'..=b'F' => u32::from(b - b'A') + 0xa,
                 _ => {
                     self.bump(1);
-                    return Err(Error::new(start..self.n, err));
+                    return Err(Error::new(start..self.n, err.clone()));
                 }
             };
 
             self.bump(1);
         }
 
-        let Some(c) = char::from_u32(c) else {
-            return Err(Error::new(start..self.n, err));
-        };
-
-        self.scratch.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
-        Ok(())
+        Ok(c)
     }
 
     /// Parse an inline sequence.
@@ -497,42 +1394,54 @@ impl<'a> Parser<'a> {
         self.bump(1);
 
         let mut items = Vec::new();
-        let mut last = false;
         let mut trailing = false;
         let mut item_prefix = self.ws();
 
         while !matches!(self.peek1(), b']' | EOF) {
-            trailing = false;
-
             let item_id = self.placeholder(item_prefix, Some(id));
             let value_prefix = self.ws();
             let (value, next_prefix) =
                 self.value(&State::new(value_prefix).with_parent(item_id).with_inline())?;
 
-            self.data.replace(item_id, raw::SequenceItem { value });
-
+            let suffix = next_prefix.unwrap_or_else(|| self.ws());
             items.push(item_id);
 
-            if last {
-                item_prefix = next_prefix.unwrap_or_else(|| self.ws());
+            if !matches!(self.peek1(), b',') {
+                trailing = false;
+                item_prefix = suffix;
+                self.data.replace(
+                    item_id,
+                    raw::SequenceItem {
+                        value,
+                        suffix: None,
+                    },
+                );
                 break;
             }
 
-            if matches!(self.peek1(), b',') {
-                self.bump(1);
-                trailing = true;
-            } else {
-                last = true;
-            }
-
-            item_prefix = next_prefix.unwrap_or_else(|| self.ws());
+            self.bump(1);
+            trailing = true;
+            item_prefix = self.ws();
+            self.data.replace(
+                item_id,
+                raw::SequenceItem {
+                    value,
+                    suffix: Some(suffix),
+                },
+            );
         }
 
         if !matches!(self.peek1(), b']') {
-            return Err(Error::new(
+            let error = Error::new(
                 self.span_back(item_prefix)..self.n,
                 ErrorKind::BadSequenceTerminator,
-            ));
+            );
+
+            return Err(if self.is_eof() {
+                error.with_suggestion("add a closing `]` to the sequence")
+            } else {
+                error
+            });
         }
 
         self.bump(1);
@@ -546,6 +1455,7 @@ impl<'a> Parser<'a> {
                     suffix: item_prefix,
                 },
                 items,
+                tag: None,
             },
         );
 
@@ -553,51 +1463,90 @@ impl<'a> Parser<'a> {
     }
 
     /// Parse an inline mapping.
-    fn inline_mapping(&mut self, s: &State) -> Result<Id> {
+    ///
+    /// If `keys_only` is set, keys are also terminated by `,` and `}`, in
+    /// which case they don't have a value. This is used for sets.
+    fn inline_mapping(&mut self, s: &State, keys_only: bool) -> Result<Id> {
         let id = self.placeholder(s.prefix, s.parent);
         self.bump(1);
 
         let mut items = Vec::new();
-        let mut last = false;
         let mut trailing = false;
         let mut start = self.n;
         let mut item_prefix = self.ws();
 
         while !matches!(self.peek1(), b'}' | EOF) {
-            trailing = false;
+            let key = if keys_only {
+                self.flow_key(self.n)
+            } else {
+                match self.quoted_flow_key() {
+                    Some(key) => Some(key),
+                    None => self.until_colon(self.n),
+                }
+            };
 
-            let Some(key) = self.until_colon(self.n) else {
+            let Some(key) = key else {
                 return Err(Error::new(start..self.n, ErrorKind::BadMappingSeparator));
             };
 
             let item_id = self.placeholder(item_prefix, Some(id));
-            self.bump(1);
-            let value_prefix = self.ws();
-            let (value, next_prefix) =
-                self.value(&State::new(value_prefix).with_parent(item_id).with_inline())?;
 
-            self.data.replace(item_id, raw::MappingItem { key, value });
+            let (kind, value, next_prefix) = if self.peek1() == b':' {
+                self.bump(1);
+                let value_prefix = self.ws();
+                let (value, next_prefix) =
+                    self.value(&State::new(value_prefix).with_parent(item_id).with_inline())?;
+                (raw::MappingItemKind::Pair, value, next_prefix)
+            } else {
+                let empty = self.data.insert_str("");
+                let value = self
+                    .data
+                    .insert(Raw::Null(raw::Null::Empty), empty, Some(item_id));
+                (raw::MappingItemKind::Key, value, None)
+            };
+
+            start = self.n;
+            let suffix = next_prefix.unwrap_or_else(|| self.ws());
             items.push(item_id);
 
-            item_prefix = next_prefix.unwrap_or_else(|| self.ws());
-
-            if last {
+            if !matches!(self.peek1(), b',') {
+                trailing = false;
+                item_prefix = suffix;
+                self.data.replace(
+                    item_id,
+                    raw::MappingItem {
+                        key,
+                        value,
+                        suffix: None,
+                        kind,
+                    },
+                );
                 break;
             }
 
-            if matches!(self.peek1(), b',') {
-                self.bump(1);
-                trailing = true;
-            } else {
-                last = true;
-            }
-
+            self.bump(1);
+            trailing = true;
             start = self.n;
             item_prefix = self.ws();
+            self.data.replace(
+                item_id,
+                raw::MappingItem {
+                    key,
+                    value,
+                    suffix: Some(suffix),
+                    kind,
+                },
+            );
         }
 
         if !matches!(self.peek1(), b'}') {
-            return Err(Error::new(start..self.n, ErrorKind::BadMappingTerminator));
+            let error = Error::new(start..self.n, ErrorKind::BadMappingTerminator);
+
+            return Err(if self.is_eof() {
+                error.with_suggestion("add a closing `}` to the mapping")
+            } else {
+                error
+            });
         }
 
         self.bump(1);
@@ -611,6 +1560,7 @@ impl<'a> Parser<'a> {
                     trailing,
                     suffix: item_prefix,
                 },
+                tag: None,
             },
         );
 
@@ -636,10 +1586,18 @@ impl<'a> Parser<'a> {
             let (value, ws) =
                 self.value(&State::new(value_prefix).with_parent(item_id).with_tabular())?;
 
-            self.data.replace(item_id, raw::SequenceItem { value });
+            self.data.replace(
+                item_id,
+                raw::SequenceItem {
+                    value,
+                    suffix: None,
+                },
+            );
             items.push(item_id);
 
             let ws = ws.unwrap_or_else(|| self.ws());
+            let (suffix, ws) = self.trailing_comment(ws);
+            self.data.sequence_item_mut(item_id).suffix = suffix;
             previous_ws = Some(ws);
 
             if self.indent() != indent || !matches!(self.peek1(), b'-') {
@@ -653,6 +1611,7 @@ impl<'a> Parser<'a> {
                 indent,
                 kind: raw::SequenceKind::Mapping,
                 items,
+                tag: None,
             },
         );
 
@@ -666,7 +1625,7 @@ impl<'a> Parser<'a> {
         &mut self,
         s: &State,
         mut start: usize,
-        key: raw::String,
+        key: Key,
     ) -> Result<(Id, Option<StringId>)> {
         let empty = self.data.insert_str("");
         let mapping_id = self.placeholder(s.prefix, s.parent);
@@ -687,7 +1646,7 @@ impl<'a> Parser<'a> {
         }
 
         while let Some(key) = current_key.take() {
-            if !matches!(self.peek1(), b':') {
+            if matches!(key, Key::Implicit(..)) && !matches!(self.peek1(), b':') {
                 self.bump(1);
                 return Err(Error::new(start..self.n, ErrorKind::BadMappingSeparator));
             }
@@ -695,20 +1654,48 @@ impl<'a> Parser<'a> {
             let item_prefix = previous_ws.take().unwrap_or(empty);
             let item_id = self.placeholder(item_prefix, Some(mapping_id));
 
-            self.bump(1);
+            let (key, kind, value, ws) = match key {
+                Key::Implicit(key) => {
+                    self.bump(1);
 
-            let value_prefix = self.ws();
-            let (value, ws) = self.value(
-                &State::new(value_prefix)
-                    .with_parent(item_id)
-                    .with_tabular()
-                    .with_parent_indent(indent),
-            )?;
+                    let value_prefix = self.ws();
+                    let (value, ws) = self.value(
+                        &State::new(value_prefix)
+                            .with_parent(item_id)
+                            .with_tabular()
+                            .with_parent_indent(indent),
+                    )?;
+
+                    (key, raw::MappingItemKind::Pair, value, ws)
+                }
+                Key::Explicit(separator, key) => {
+                    let value = self
+                        .data
+                        .insert(Raw::Null(raw::Null::Empty), empty, Some(item_id));
+                    (
+                        key,
+                        raw::MappingItemKind::Explicit { separator },
+                        value,
+                        None,
+                    )
+                }
+                Key::Complex => self.complex_key(item_id, indent)?,
+            };
 
-            self.data.replace(item_id, raw::MappingItem { key, value });
+            self.data.replace(
+                item_id,
+                raw::MappingItem {
+                    key,
+                    value,
+                    suffix: None,
+                    kind,
+                },
+            );
             items.push(item_id);
 
             let ws = ws.unwrap_or_else(|| self.ws());
+            let (suffix, ws) = self.trailing_comment(ws);
+            self.data.mapping_item_mut(item_id).suffix = suffix;
             previous_ws = Some(ws);
 
             if self.indent() != indent {
@@ -716,7 +1703,7 @@ impl<'a> Parser<'a> {
             }
 
             start = self.n;
-            current_key = self.next_mapping_key();
+            current_key = self.next_key()?;
         }
 
         self.data.replace(
@@ -725,6 +1712,7 @@ impl<'a> Parser<'a> {
                 indent,
                 kind: raw::MappingKind::Mapping,
                 items,
+                tag: None,
             },
         );
 
@@ -744,12 +1732,22 @@ impl<'a> Parser<'a> {
     }
 
     /// Process a key up until `:` or end of the current line.
+    ///
+    /// A comment following the value on the same line is not part of it, and
+    /// is left to be consumed with the whitespace which follows.
     fn key_or_eol(&mut self, start: usize) -> Option<raw::String> {
         loop {
-            self.find2(b':', raw::NEWLINE);
+            self.find3(b':', b'#', raw::NEWLINE);
 
             match self.peek() {
+                // Trailing whitespace is not part of the value, so that
+                // values like `~ ` are still recognized.
                 [raw::NEWLINE | EOF, _] => {
+                    self.rewind_ws(start);
+                    return None;
+                }
+                [b'#', _] if self.n > start && self.is_ws_before() => {
+                    self.rewind_ws(start);
                     return None;
                 }
                 // Only treat something as a key if it's a colon immediately
@@ -765,8 +1763,37 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Process a quoted key in an inline mapping which is immediately
+    /// followed by `:`.
+    ///
+    /// Like in block mappings, quoted keys keep their style and are looked up
+    /// by their unescaped contents. Anything else is left to be read as a
+    /// plain key.
+    fn quoted_flow_key(&mut self) -> Option<raw::String> {
+        let start = self.n;
+
+        let key = match self.peek1() {
+            b'"' => self.double_quoted(),
+            b'\'' => self.single_quoted(),
+            _ => return None,
+        };
+
+        if let Ok(key) = key {
+            if self.peek1() == b':' {
+                return Some(key);
+            }
+        }
+
+        self.n = start;
+        None
+    }
+
     /// Process a key up until `:`.
     fn until_colon(&mut self, start: usize) -> Option<raw::String> {
+        if self.options.strict_flow_keys {
+            return self.until_flow_separator(start);
+        }
+
         while !matches!(self.peek1(), b':' | EOF) {
             self.bump(1);
         }
@@ -775,46 +1802,440 @@ impl<'a> Parser<'a> {
             return None;
         }
 
+        Some(self.plain(start))
+    }
+
+    /// Process a key up until a `:` which is followed by spacing or a flow
+    /// indicator, or which immediately follows a quoted key.
+    fn until_flow_separator(&mut self, start: usize) -> Option<raw::String> {
+        let mut quote = None;
+        let mut quoted = false;
+
+        loop {
+            match (quote, self.peek()) {
+                (_, [EOF, _]) if self.is_eof() => return None,
+                (Some(q), [b, _]) => {
+                    if b == q {
+                        quote = None;
+                        quoted = true;
+                    }
+                }
+                (None, [q @ (b'"' | b'\''), _]) if self.n == start => {
+                    quote = Some(q);
+                }
+                (None, [b':', ws!(flow_end!())]) => break,
+                (None, [b':', _]) if quoted => break,
+                (None, [b',' | b']' | b'}', _]) => return None,
+                _ => {
+                    quoted = false;
+                }
+            }
+
+            self.bump(1);
+        }
+
+        if matches!(self.input.get(start), Some(b'"' | b'\'')) {
+            let key = self.data.insert_str(self.string(start));
+            return Some(raw::String::new(raw::RawStringKind::Bare, key, key));
+        }
+
+        Some(self.plain(start))
+    }
+
+    /// Process a key in an inline set, up until a `:` which is followed by
+    /// spacing or a flow indicator, or up until the end of the entry.
+    ///
+    /// Trailing whitespace is not included in keys without a value.
+    fn flow_key(&mut self, start: usize) -> Option<raw::String> {
+        let mut end = self.n;
+
+        loop {
+            match self.peek() {
+                [EOF, _] if self.is_eof() => return None,
+                [b':', ws!(flow_end!())] => {
+                    end = self.n;
+                    break;
+                }
+                [b',' | b'}', _] => break,
+                [ws!(), _] => {}
+                _ => {
+                    end = self.n.wrapping_add(1);
+                }
+            }
+
+            self.bump(1);
+        }
+
+        self.n = end;
+        Some(self.plain(start))
+    }
+
+    /// Process an explicit `? key` in a block mapping.
+    ///
+    /// Keys which can't be represented as a single line string, or which have
+    /// a value, are processed as a [`Key::Complex`] by rewinding to the `?`.
+    fn explicit_key(&mut self) -> Result<Key> {
+        let indicator = self.n;
+        let indent = self.indent_from(indicator);
+
+        self.bump(1);
+        let separator = self.ws();
+        let start = self.n;
+
+        let complex = self.data.str(separator).contains(&raw::NEWLINE)
+            || matches!(
+                self.peek(),
+                [b'-' | b'?', ws!(EOF)] | [b'[' | b'{' | b'|' | b'>' | b'!' | b'&' | b'*', _]
+            );
+
+        if complex {
+            self.n = indicator;
+            return Ok(Key::Complex);
+        }
+
+        let key = match self.peek1() {
+            b'"' => Some(self.double_quoted()?),
+            b'\'' => Some(self.single_quoted()?),
+            _ => None,
+        };
+
+        if let Some(key) = key {
+            if self.is_complex_key(indent) {
+                self.n = indicator;
+                return Ok(Key::Complex);
+            }
+
+            return Ok(Key::Explicit(separator, key));
+        }
+
+        self.n = start;
+        let mut end = self.n;
+
+        while !self.is_eof() {
+            match self.peek() {
+                [raw::NEWLINE, _] | [other_ws!(), b'#'] => break,
+                [other_ws!(), _] => {}
+                _ => {
+                    end = self.n.wrapping_add(1);
+                }
+            }
+
+            self.bump(1);
+        }
+
+        self.n = end;
+
+        if self.is_complex_key(indent) {
+            self.n = indicator;
+            return Ok(Key::Complex);
+        }
+
         let key = self.data.insert_str(self.string(start));
-        Some(raw::String::new(raw::RawStringKind::Bare, key, key))
+        Ok(Key::Explicit(
+            separator,
+            raw::String::new(raw::RawStringKind::Bare, key, key),
+        ))
+    }
+
+    /// Test if the explicit key ending at the cursor continues on the
+    /// following lines, or is followed by a `:` value at the indentation
+    /// `indent` of its `?` indicator.
+    fn is_complex_key(&self, indent: usize) -> bool {
+        let rest = self.input.get(self.n..).unwrap_or_default();
+
+        for line in rest.lines().skip(1) {
+            let content = line.trim_start_with(|c| c == ' ');
+
+            if content.trim().is_empty() || content.starts_with(b"#") {
+                continue;
+            }
+
+            let line_indent = line.len() - content.len();
+
+            return line_indent > indent
+                || line_indent == indent
+                    && content.starts_with(b":")
+                    && content.get(1).map_or(true, u8::is_ascii_whitespace);
+        }
+
+        false
     }
 
-    /// Process a block as a string.
-    fn block(
+    /// Process a [`Key::Complex`] starting at its `?` indicator, along with
+    /// the value of the item if it has one.
+    fn complex_key(
         &mut self,
-        n: usize,
-        join: u8,
-        folded: bool,
-        chomp: bool,
-        clip: bool,
-    ) -> (Raw, Option<StringId>) {
+        item_id: Id,
+        indent: usize,
+    ) -> Result<(raw::String, raw::MappingItemKind, Id, Option<StringId>)> {
+        self.bump(1);
+        let separator = self.ws();
+
+        let (key, ws) = if self.is_eof()
+            || self.data.str(separator).contains(&raw::NEWLINE) && self.indent() <= indent
+        {
+            // Empty values don't have a prefix, so the separator precedes the
+            // `:` instead.
+            let empty = self.data.insert_str("");
+            let key = self
+                .data
+                .insert(Raw::Null(raw::Null::Empty), empty, Some(item_id));
+            (key, Some(separator))
+        } else {
+            self.value(
+                &State::new(separator)
+                    .with_parent(item_id)
+                    .with_tabular()
+                    .with_parent_indent(indent),
+            )?
+        };
+
+        // The key is also stored as a string, which is what it's looked up
+        // and iterated by. Block strings are stored by their contents.
+        let text = match self.data.raw(key) {
+            Raw::String(
+                string @ raw::String {
+                    kind: raw::RawStringKind::Multiline { .. },
+                    ..
+                },
+            ) => string.id,
+            raw => {
+                let mut text = Vec::new();
+                let _ = raw.write_to(&self.data, &mut text);
+                self.data.insert_str(text.trim())
+            }
+        };
+
+        let text = raw::String::new(raw::RawStringKind::Bare, text, text);
+
+        let colon = ws.unwrap_or_else(|| self.ws());
+
+        if self.indent() != indent || !matches!(self.peek(), [b':', ws!(EOF)]) {
+            let empty = self.data.insert_str("");
+            let value = self
+                .data
+                .insert(Raw::Null(raw::Null::Empty), empty, Some(item_id));
+            let kind = raw::MappingItemKind::Complex { key, colon: None };
+            return Ok((text, kind, value, Some(colon)));
+        }
+
+        self.bump(1);
+
+        let value_prefix = self.ws();
+        let (value, ws) = self.value(
+            &State::new(value_prefix)
+                .with_parent(item_id)
+                .with_tabular()
+                .with_parent_indent(indent),
+        )?;
+
+        let kind = raw::MappingItemKind::Complex {
+            key,
+            colon: Some(colon),
+        };
+
+        Ok((text, kind, value, ws))
+    }
+
+    /// Parse a tagged collection, like `!!set` or `!!omap`.
+    ///
+    /// Only tags on collections are supported. Other tags are treated as part
+    /// of the value they're attached to, in which case the input is rewound
+    /// and `None` is returned.
+    fn tagged(&mut self, s: &State) -> Result<Option<(Id, Option<StringId>)>> {
+        let start = self.n;
+
+        while !matches!(self.peek1(), ws!(flow_end!())) {
+            self.bump(1);
+        }
+
+        let name = self.data.insert_str(self.string(start));
+        let (suffix, nl) = self.ws_nl();
+
+        let inline = match self.peek() {
+            [b'[', _] => Some(self.inline_sequence(s)),
+            [b'{', _] => {
+                let set = self.data.str(name) == "!!set";
+                Some(self.inline_mapping(s, set))
+            }
+            _ => None,
+        };
+
+        let (id, ws) = match inline {
+            Some(Ok(id)) => (id, None),
+            // Inline collections we can't parse, like flow pairs, are treated
+            // as part of a plain value.
+            Some(Err(..)) => {
+                self.n = start;
+                return Ok(None);
+            }
+            None => match self.peek() {
+                [b'-' | b'?', ws!()] if !s.inline => self.value(s)?,
+                _ if !s.inline && nl > 0 => self.value(s)?,
+                _ => {
+                    self.n = start;
+                    return Ok(None);
+                }
+            },
+        };
+
+        match self.data.raw_mut(id) {
+            Raw::Mapping(raw::Mapping { tag, .. }) | Raw::Sequence(raw::Sequence { tag, .. }) => {
+                *tag = Some(raw::Tag { name, suffix });
+                Ok(Some((id, ws)))
+            }
+            _ => {
+                self.data.drop(id);
+                self.n = start;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Parse a value with an anchor, like `&name value`.
+    ///
+    /// Anchors without a name are treated as part of a plain value, in which
+    /// case the input is rewound and `None` is returned.
+    fn anchored(&mut self, s: &State) -> Result<Option<(Id, Option<StringId>)>> {
+        let start = self.n;
+        self.bump(1);
+
+        let Some(name) = self.anchor_name() else {
+            self.n = start;
+            return Ok(None);
+        };
+
+        let suffix = self.ws();
+        let (id, ws) = self.value(&State {
+            prefix: suffix,
+            ..*s
+        })?;
+
+        // Whatever was parsed as the prefix of the value is whitespace
+        // following the anchor.
+        let suffix = self.data.layout(id).prefix;
+        self.data.set_prefix(id, s.prefix);
+        self.data.set_anchor(id, Some(raw::Anchor { name, suffix }));
+        Ok(Some((id, ws)))
+    }
+
+    /// Parse an alias, like `*name`.
+    ///
+    /// Aliases which are followed by anything other than whitespace or a
+    /// comment are treated as part of a plain value, in which case the input
+    /// is rewound and `None` is returned.
+    fn alias(&mut self, s: &State) -> Option<Raw> {
+        let start = self.n;
+        self.bump(1);
+
+        let Some(name) = self.anchor_name() else {
+            self.n = start;
+            return None;
+        };
+
+        let end = self.n;
+
+        while matches!(self.peek1(), other_ws!()) || s.inline && self.peek1() == raw::NEWLINE {
+            self.bump(1);
+        }
+
+        let valid = match self.peek1() {
+            raw::NEWLINE | EOF => true,
+            b'#' => self.n > end,
+            b',' | b']' | b'}' => s.inline,
+            _ => false,
+        };
+
+        if !valid {
+            self.n = start;
+            return None;
+        }
+
+        self.n = end;
+        Some(Raw::Alias(raw::Alias::new(name)))
+    }
+
+    /// Read the name of an anchor or an alias.
+    fn anchor_name(&mut self) -> Option<StringId> {
+        let start = self.n;
+
+        while !matches!(self.peek1(), ws!(flow_end!())) {
+            self.bump(1);
+        }
+
+        if self.n == start {
+            return None;
+        }
+
+        Some(self.data.insert_str(self.string(start)))
+    }
+
+    /// Process a block as a string, starting at its `|` or `>` indicator.
+    ///
+    /// The header can contain a chomping indicator and an explicit
+    /// indentation indicator in any order, like `|2-`. The explicit
+    /// indentation is relative to `parent_indent`, and lines which are
+    /// indented beyond the indentation of the block keep their leading
+    /// whitespace.
+    fn block(&mut self, folded: bool, parent_indent: Option<usize>) -> (Raw, Option<StringId>) {
         let start = self.n;
-        self.bump(n);
+        self.bump(1);
+
+        let mut chomp = Chomp::Clip;
+        let mut explicit = None;
+
+        for _ in 0..2 {
+            match self.peek1() {
+                b'-' if matches!(chomp, Chomp::Clip) => chomp = Chomp::Strip,
+                b'+' if matches!(chomp, Chomp::Clip) => chomp = Chomp::Keep,
+                b @ b'1'..=b'9' if explicit.is_none() => explicit = Some(usize::from(b - b'0')),
+                _ => break,
+            }
+
+            self.bump(1);
+        }
+
+        let join = if folded { raw::SPACE } else { raw::NEWLINE };
+
+        let parent_indent = parent_indent.unwrap_or_else(|| {
+            let line = raw::indent(self.input.get(..start).unwrap_or_default());
+            line.iter()
+                .take_while(|&&b| matches!(b, other_ws!()))
+                .count()
+        });
+
         let prefix = self.data.insert_str(self.string(start));
 
         let start = self.n;
+        let mut content = Content::Empty;
         let (mut ws, mut nl) = self.ws_nl();
 
         if nl == 0 {
-            self.find(raw::NEWLINE);
-            let out = self.input.get(start..self.n).unwrap_or_default().trim();
-            self.scratch.extend_from_slice(out);
+            let s = self.n;
+            self.find_eol();
+            let range = trim_end(self.input, s..self.n);
+            self.content_extend(&mut content, range);
 
             (ws, nl) = self.ws_nl();
 
             if !self.is_eof() {
-                self.scratch.push(join);
+                self.content_push(&mut content, join);
             }
         }
 
         let mut end = self.n;
-        let indent = self.indent();
 
-        while !self.is_eof() {
-            let s = self.n;
-            self.find(raw::NEWLINE);
-            let out = self.input.get(s..self.n).unwrap_or_default().trim();
-            self.scratch.extend_from_slice(out);
+        let indent = match explicit {
+            Some(n) => parent_indent.wrapping_add(n),
+            None => self.indent(),
+        };
+
+        while !self.is_eof() && self.indent() >= indent {
+            let extra = self.indent().wrapping_sub(indent);
+            let s = self.n.wrapping_sub(extra);
+            self.find_eol();
+            let range = trim_end(self.input, s..self.n);
+            self.content_extend(&mut content, range);
 
             end = self.n;
             (ws, nl) = self.ws_nl();
@@ -824,20 +2245,21 @@ impl<'a> Parser<'a> {
             }
 
             for _ in 0..if folded { 1 } else { nl } {
-                self.scratch.push(join);
+                self.content_push(&mut content, join);
             }
         }
 
-        for _ in 0..chomp.then_some(nl).unwrap_or_default() {
-            self.scratch.push(raw::NEWLINE);
+        let trailing = match chomp {
+            Chomp::Strip => 0,
+            Chomp::Clip => nl.min(1),
+            Chomp::Keep => nl,
+        };
 
-            if clip {
-                break;
-            }
+        for _ in 0..trailing {
+            self.content_push(&mut content, raw::NEWLINE);
         }
 
-        let string = self.data.insert_str(&self.scratch);
-        self.scratch.clear();
+        let string = self.content_insert(content);
 
         let out = self.input.get(start..end).unwrap_or_default();
         let original = self.data.insert_str(out);
@@ -851,55 +2273,118 @@ impl<'a> Parser<'a> {
 
     /// Consume a single value.
     fn value(&mut self, s: &State) -> Result<(Id, Option<StringId>)> {
+        match self.peek1() {
+            b'!' => {
+                if let Some(tagged) = self.tagged(s)? {
+                    return Ok(tagged);
+                }
+            }
+            b'&' => {
+                if let Some(anchored) = self.anchored(s)? {
+                    return Ok(anchored);
+                }
+            }
+            b'*' => {
+                if let Some(alias) = self.alias(s) {
+                    let value = self.data.insert(alias, s.prefix, s.parent);
+                    return Ok((value, None));
+                }
+            }
+            _ => {}
+        }
+
         let (raw, ws) = match self.peek() {
             [b'-', ws!()] if !s.inline => {
                 return self.sequence(s);
             }
+            [b'?', ws!()] if !s.inline => {
+                let start = self.n;
+                let key = self.explicit_key()?;
+                return self.mapping_or_nul(s, start, key);
+            }
             [b'"', _] => {
                 let start = self.n;
                 let string = self.double_quoted()?;
 
                 if !s.inline && self.peek1() == b':' {
-                    return self.mapping_or_nul(s, start, string);
+                    return self.mapping_or_nul(s, start, Key::Implicit(string));
                 }
 
                 (Raw::String(string), None)
             }
             [b'\'', _] => {
                 let start = self.n;
-                let string = self.single_quoted();
+                let string = self.single_quoted()?;
 
                 if !s.inline && self.peek1() == b':' {
-                    return self.mapping_or_nul(s, start, string);
+                    return self.mapping_or_nul(s, start, Key::Implicit(string));
                 }
 
                 (Raw::String(string), None)
             }
             [b'[', _] => return Ok((self.inline_sequence(s)?, None)),
-            [b'{', _] => return Ok((self.inline_mapping(s)?, None)),
-            [a @ (b'>' | b'|'), b] => self.block(
-                matches!(b, b'-' | b'+').then_some(2).unwrap_or(1),
-                if a == b'>' { raw::SPACE } else { raw::NEWLINE },
-                a == b'>',
-                b != b'-',
-                b == b'-',
-            ),
+            [b'{', _] => return Ok((self.inline_mapping(s, false)?, None)),
+            [a @ (b'>' | b'|'), _] => self.block(a == b'>', s.parent_indent),
             _ => {
                 'default: {
                     let start = self.n;
 
-                    if let Some(number) = self.number(s, start) {
-                        break 'default (number, None);
+                    // Strict schemas resolve numbers once the whole plain
+                    // scalar has been read.
+                    if !self.options.schema.is_strict() {
+                        if let Some(number) = self.number(s, start) {
+                            break 'default (number, None);
+                        }
                     }
 
                     if s.inline {
                         // Seek until we find a control character, since we're
-                        // simply treating the current segment as a string.
-                        while !matches!(self.peek1(), ctl!()) {
+                        // simply treating the current segment as a string. A
+                        // colon is only a control character if it's followed
+                        // by spacing or a flow indicator, so `a:b` is read as
+                        // a single string.
+                        //
+                        // Trailing whitespace and comments are not part of
+                        // the string, so we rewind to the last non-whitespace
+                        // character once we're done.
+                        let mut end = self.n;
+
+                        loop {
+                            // Skip over runs of bytes which can't end the
+                            // string in one go, since they might be long.
+                            let rest = self.input.get(self.n..).unwrap_or_default();
+
+                            let run = rest
+                                .iter()
+                                .position(|&b| matches!(b, flow_end!() | b':' | ws!()))
+                                .unwrap_or(rest.len());
+
+                            if run > 0 {
+                                self.bump(run);
+                                end = self.n;
+                                continue;
+                            }
+
+                            match self.peek() {
+                                [flow_end!(), _] | [b':', ws!(flow_end!())] | [ws!(), b'#'] => {
+                                    break
+                                }
+                                [ws!(), _] => {}
+                                _ => {
+                                    end = self.n.wrapping_add(1);
+                                }
+                            }
+
                             self.bump(1);
                         }
+
+                        self.n = end;
+
+                        if self.string(start).contains(&raw::NEWLINE) {
+                            break 'default (Raw::String(self.plain(start)), None);
+                        }
                     } else if let Some(key) = self.key_or_eol(start) {
-                        return self.mapping_or_nul(s, start, key);
+                        return self.mapping_or_nul(s, start, Key::Implicit(key));
                     }
 
                     // NB: calling `key_or_eol` will have consumed up until end
@@ -907,14 +2392,14 @@ impl<'a> Parser<'a> {
                     // string.
                     let string = self.string(start);
                     (
-                        if string == b"~" {
+                        if string == b"~" && self.options.schema.is_tilde_null() {
                             Raw::Null(raw::Null::Tilde)
-                        } else if string.eq_ignore_ascii_case(b"null") {
+                        } else if self.options.schema.is_null(string) {
                             Raw::Null(raw::Null::Keyword(self.data.insert_str(string)))
-                        } else if string.eq_ignore_ascii_case(b"true") {
-                            Raw::Boolean(raw::Boolean::new(true, self.data.insert_str(string)))
-                        } else if string.eq_ignore_ascii_case(b"false") {
-                            Raw::Boolean(raw::Boolean::new(false, self.data.insert_str(string)))
+                        } else if let Some(value) = self.options.schema.boolean(string) {
+                            Raw::Boolean(raw::Boolean::new(value, self.data.insert_str(string)))
+                        } else if let Some(hint) = self.options.schema.number(string) {
+                            Raw::Number(raw::Number::new(self.data.insert_str(string), hint))
                         } else {
                             let string = self.data.insert_str(string);
                             Raw::String(raw::String::new(raw::RawStringKind::Bare, string, string))
@@ -929,8 +2414,30 @@ impl<'a> Parser<'a> {
         Ok((value, ws))
     }
 
+    /// Parse next key in a block mapping.
+    fn next_key(&mut self) -> Result<Option<Key>> {
+        if let [b'?', ws!()] = self.peek() {
+            return Ok(Some(self.explicit_key()?));
+        }
+
+        Ok(self.next_mapping_key()?.map(Key::Implicit))
+    }
+
     /// Parse next mapping key.
-    fn next_mapping_key(&mut self) -> Option<raw::String> {
+    ///
+    /// Quoted keys keep their style and are looked up by their unescaped
+    /// contents.
+    fn next_mapping_key(&mut self) -> Result<Option<raw::String>> {
+        let key = match self.peek1() {
+            b'"' => Some(self.double_quoted()?),
+            b'\'' => Some(self.single_quoted()?),
+            _ => None,
+        };
+
+        if let Some(key) = key {
+            return Ok(Some(key));
+        }
+
         let start = self.n;
 
         let string = loop {
@@ -939,7 +2446,7 @@ impl<'a> Parser<'a> {
                     let string = self.string(start);
 
                     if string.is_empty() {
-                        return None;
+                        return Ok(None);
                     }
 
                     break string;
@@ -951,6 +2458,51 @@ impl<'a> Parser<'a> {
         };
 
         let string = self.data.insert_str(string);
-        Some(raw::String::new(raw::RawStringKind::Bare, string, string))
+        Ok(Some(raw::String::new(
+            raw::RawStringKind::Bare,
+            string,
+            string,
+        )))
+    }
+}
+
+/// Trim trailing whitespace from the given range of the input.
+fn trim_end(input: &[u8], range: Range<usize>) -> Range<usize> {
+    let len = input
+        .get(range.clone())
+        .unwrap_or_default()
+        .trim_end()
+        .len();
+    range.start..range.start.wrapping_add(len)
+}
+
+/// Test if the line is indented with tabs, or has a tab following a sequence
+/// marker.
+fn has_tab_indent(line: &[u8]) -> bool {
+    let content = line.trim_start_with(|c| matches!(c, ' ' | '\t'));
+    let indent = line
+        .get(..line.len().saturating_sub(content.len()))
+        .unwrap_or_default();
+    indent.contains(&b'\t') || content.starts_with(b"-\t")
+}
+
+/// Test if the line looks like a key which is immediately followed by its
+/// value, like `key:value`.
+fn has_unspaced_colon(line: &[u8]) -> bool {
+    let line = line.trim();
+
+    if matches!(line.first(), Some(b'#' | b'"' | b'\'' | b'[' | b'{')) {
+        return false;
     }
+
+    let Some(n) = memchr::memchr(b':', line) else {
+        return false;
+    };
+
+    let key = line.get(..n).unwrap_or_default();
+    let value = line.get(n.wrapping_add(1)..).unwrap_or_default();
+
+    !key.is_empty()
+        && !key.contains(&b' ')
+        && matches!(value.first(), Some(b) if !b.is_ascii_whitespace() && *b != b':')
 }
@@ -0,0 +1,117 @@
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+use crate::yaml::replace;
+
+/// The style of placeholder scalars, like `<REPLACE_ME>`, set through
+/// [`Document::set_placeholder`].
+///
+/// A placeholder is a string value consisting of a name wrapped in an opening
+/// and a closing delimiter. Placeholders are inserted through
+/// [`ValueMut::set_placeholder`], found through [`Document::placeholders`] and
+/// filled in through [`Document::fill_placeholders`]. Since they are regular
+/// strings, they survive being written and parsed again, which allows a
+/// document to be generated in one step and completed in another.
+///
+/// [`Document::set_placeholder`]: crate::yaml::Document::set_placeholder
+/// [`Document::placeholders`]: crate::yaml::Document::placeholders
+/// [`Document::fill_placeholders`]: crate::yaml::Document::fill_placeholders
+/// [`ValueMut::set_placeholder`]: crate::yaml::ValueMut::set_placeholder
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("image: app\ntoken: x\n")?;
+/// doc.set_placeholder(yaml::Placeholder::new().delimiters("${", "}"));
+///
+/// let id = doc.as_ref().as_mapping().and_then(|m| m.get("token")).context("missing token")?.id();
+/// doc.value_mut(id).set_placeholder("TOKEN");
+/// assert_eq!(doc.to_string(), "image: app\ntoken: ${TOKEN}\n");
+///
+/// let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+/// assert_eq!(names, ["TOKEN"]);
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placeholder {
+    open: Box<str>,
+    close: Box<str>,
+}
+
+impl Placeholder {
+    /// Construct the default placeholder style, which looks like
+    /// `<REPLACE_ME>`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delimiters which open and close a placeholder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("")?;
+    /// doc.set_placeholder(yaml::Placeholder::new().delimiters("{{ ", " }}"));
+    ///
+    /// doc.as_mut().set_placeholder("name");
+    /// assert_eq!(doc.to_string(), "'{{ name }}'");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn delimiters(mut self, open: &str, close: &str) -> Self {
+        self.open = open.into();
+        self.close = close.into();
+        self
+    }
+
+    /// Format a placeholder with the given name.
+    pub(crate) fn format(&self, name: &str) -> std::string::String {
+        format!("{}{name}{}", self.open, self.close)
+    }
+
+    /// Get the name of the placeholder in `string`, if it is one.
+    pub(crate) fn name<'a>(&self, string: &'a [u8]) -> Option<&'a str> {
+        let name = string
+            .strip_prefix(self.open.as_bytes())?
+            .strip_suffix(self.close.as_bytes())?;
+
+        if name.is_empty() || name.contains_str(self.close.as_bytes()) {
+            return None;
+        }
+
+        name.to_str().ok()
+    }
+}
+
+impl Default for Placeholder {
+    fn default() -> Self {
+        Self {
+            open: "<".into(),
+            close: ">".into(),
+        }
+    }
+}
+
+/// Find placeholders in the string values reachable from the given value.
+pub(crate) fn find(data: &Data, id: Id) -> Vec<(Id, &str)> {
+    let mut strings = Vec::new();
+    replace::collect(data, id, &mut strings);
+
+    strings
+        .into_iter()
+        .filter_map(|id| {
+            let Raw::String(string) = data.raw(id) else {
+                return None;
+            };
+
+            Some((id, data.placeholder().name(data.str(string.id))?))
+        })
+        .collect()
+}
@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+use crate::yaml::Document;
+
+/// Construct the [JSON pointer] to the value `id` from the value `root`, or
+/// `None` if `id` isn't contained in `root`.
+///
+/// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+pub(crate) fn to_pointer(data: &Data, root: Id, mut id: Id) -> Option<String> {
+    let mut segments = Vec::new();
+
+    while id != root {
+        let item = data.layout(id).parent?;
+        let parent = data.layout(item).parent?;
+
+        let segment = match (data.raw(parent), data.raw(item)) {
+            (Raw::Mapping(..), Raw::MappingItem(item)) if item.value == id => {
+                data.str(item.key.id).to_str_lossy()
+            }
+            (Raw::Sequence(sequence), Raw::SequenceItem(..)) => {
+                let index = sequence.items.iter().position(|&i| i == item)?;
+                Cow::Owned(index.to_string())
+            }
+            _ => return None,
+        };
+
+        segments.push(segment);
+        id = parent;
+    }
+
+    let mut pointer = String::new();
+
+    for segment in segments.iter().rev() {
+        pointer.push('/');
+        pointer.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    }
+
+    Some(pointer)
+}
+
+/// Resolve the [JSON pointer] `pointer` starting at the value `id`.
+///
+/// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+pub(crate) fn resolve(document: &Document, mut id: Id, pointer: &str) -> Option<Id> {
+    if pointer.is_empty() {
+        return Some(id);
+    }
+
+    for segment in pointer.strip_prefix('/')?.split('/') {
+        id = child(document, id, &unescape(segment))?;
+    }
+
+    Some(id)
+}
+
+/// Unescape a single segment of a JSON pointer.
+pub(crate) fn unescape(segment: &str) -> Cow<'_, str> {
+    if segment.contains('~') {
+        Cow::Owned(segment.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
+
+/// Get the child of the given value matching `segment`.
+pub(crate) fn child(document: &Document, id: Id, segment: &str) -> Option<Id> {
+    let value = document.value(id);
+
+    match document.data.raw(id) {
+        Raw::Mapping(..) => Some(value.as_mapping()?.get(segment)?.id()),
+        Raw::Sequence(..) => Some(value.as_sequence()?.get(index(segment)?)?.id()),
+        _ => None,
+    }
+}
+
+/// Parse an array index, which is not allowed to have leading zeros or a
+/// sign.
+fn index(segment: &str) -> Option<usize> {
+    if !segment.bytes().all(|b| b.is_ascii_digit()) || segment.len() > 1 && segment.starts_with('0')
+    {
+        return None;
+    }
+
+    segment.parse().ok()
+}
@@ -0,0 +1,269 @@
+use std::collections::btree_map::{BTreeMap, Entry};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use twox_hash::xxh3;
+
+use crate::yaml::{self, Document, Value, ValueMut};
+
+/// A set of documents keyed by the path they are read from and written to,
+/// such as the manifests in a repository.
+///
+/// The project keeps track of what each document looked like when it was
+/// opened or last saved, so that [`Project::save`] only writes back the files
+/// which have been modified.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let mut project = yaml::Project::new();
+/// project.insert("a/Chart.yaml", yaml::from_slice("name: a\nversion: 1.0.0\n")?);
+/// project.insert("b/Chart.yaml", yaml::from_slice("name: b\nversion: 1.0.0\n")?);
+/// project.insert("c/values.yaml", yaml::from_slice("replicas: 2\n")?);
+/// project.mark_saved();
+///
+/// let updated = project.update_json_pointer("/version", |_, mut value| {
+///     value.set_string("1.1.0");
+/// });
+///
+/// assert_eq!(updated, 2);
+///
+/// let modified = project.modified().map(|path| path.to_string_lossy()).collect::<Vec<_>>();
+/// assert_eq!(modified, ["a/Chart.yaml", "b/Chart.yaml"]);
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Default)]
+pub struct Project {
+    files: BTreeMap<PathBuf, File>,
+}
+
+struct File {
+    document: Document,
+    /// A checksum of the contents of the file when it was last read or
+    /// written, or `None` if it hasn't been.
+    saved: Option<u64>,
+}
+
+impl File {
+    fn is_modified(&self) -> bool {
+        self.saved != Some(checksum(&self.document))
+    }
+}
+
+impl Project {
+    /// Construct an empty project.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read and parse the file at `path`, adding it to the project.
+    ///
+    /// If the file has already been opened, the document which is already in
+    /// the project is returned without reading the file again.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the file can't be read, or if it can't be parsed as YAML, in
+    /// which case the error is of the kind [`io::ErrorKind::InvalidData`].
+    pub fn open<P>(&mut self, path: P) -> io::Result<&mut Document>
+    where
+        P: AsRef<Path>,
+    {
+        let file = match self.files.entry(path.as_ref().to_owned()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let input = fs::read(e.key())?;
+                let document = yaml::from_slice(&input)?;
+
+                e.insert(File {
+                    document,
+                    saved: Some(xxh3::hash64(&input)),
+                })
+            }
+        };
+
+        Ok(&mut file.document)
+    }
+
+    /// Add a document to the project which is written to `path` when the
+    /// project is saved, replacing any existing document with the same path.
+    ///
+    /// The document counts as modified until the project is saved, or until
+    /// [`Project::mark_saved`] is called.
+    pub fn insert<P>(&mut self, path: P, document: Document) -> Option<Document>
+    where
+        P: Into<PathBuf>,
+    {
+        let file = File {
+            document,
+            saved: None,
+        };
+
+        let old = self.files.insert(path.into(), file)?;
+        Some(old.document)
+    }
+
+    /// Remove the document at `path` from the project without touching the
+    /// file it was read from.
+    pub fn remove<P>(&mut self, path: P) -> Option<Document>
+    where
+        P: AsRef<Path>,
+    {
+        Some(self.files.remove(path.as_ref())?.document)
+    }
+
+    /// Get the document at `path`.
+    #[must_use]
+    pub fn get<P>(&self, path: P) -> Option<&Document>
+    where
+        P: AsRef<Path>,
+    {
+        Some(&self.files.get(path.as_ref())?.document)
+    }
+
+    /// Get the document at `path` mutably.
+    #[must_use]
+    pub fn get_mut<P>(&mut self, path: P) -> Option<&mut Document>
+    where
+        P: AsRef<Path>,
+    {
+        Some(&mut self.files.get_mut(path.as_ref())?.document)
+    }
+
+    /// Iterate over the paths and documents in the project, ordered by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Document)> {
+        self.files
+            .iter()
+            .map(|(path, file)| (path.as_path(), &file.document))
+    }
+
+    /// Iterate mutably over the paths and documents in the project, ordered by
+    /// path.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Path, &mut Document)> {
+        self.files
+            .iter_mut()
+            .map(|(path, file)| (path.as_path(), &mut file.document))
+    }
+
+    /// Find the value at the given [JSON pointer] in every document which
+    /// has one, ordered by path.
+    ///
+    /// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut project = yaml::Project::new();
+    /// project.insert("a.yaml", yaml::from_slice("image: {tag: v1}\n")?);
+    /// project.insert("b.yaml", yaml::from_slice("image: nginx\n")?);
+    /// project.insert("c.yaml", yaml::from_slice("image: {tag: v2}\n")?);
+    ///
+    /// let tags = project
+    ///     .resolve_json_pointer("/image/tag")
+    ///     .map(|(path, value)| (path.to_string_lossy(), value.as_str()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(tags, [("a.yaml".into(), Some("v1")), ("c.yaml".into(), Some("v2"))]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn resolve_json_pointer<'a>(
+        &'a self,
+        pointer: &'a str,
+    ) -> impl Iterator<Item = (&'a Path, Value<'a>)> + 'a {
+        self.iter().filter_map(move |(path, document)| {
+            let id = document.resolve_json_pointer(pointer)?;
+            Some((path, document.value(id)))
+        })
+    }
+
+    /// Call `f` with the value at the given [JSON pointer] in every document
+    /// which has one, ordered by path, returning the number of values it was
+    /// called with.
+    ///
+    /// [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+    pub fn update_json_pointer<F>(&mut self, pointer: &str, mut f: F) -> usize
+    where
+        F: FnMut(&Path, ValueMut<'_>),
+    {
+        let mut count = 0;
+
+        for (path, document) in self.iter_mut() {
+            if let Some(id) = document.resolve_json_pointer(pointer) {
+                f(path, document.value_mut(id));
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Test if the document at `path` has been modified since it was opened
+    /// or last saved.
+    #[must_use]
+    pub fn is_modified<P>(&self, path: P) -> bool
+    where
+        P: AsRef<Path>,
+    {
+        self.files.get(path.as_ref()).is_some_and(File::is_modified)
+    }
+
+    /// Iterate over the paths of documents which have been modified since
+    /// they were opened or last saved, ordered by path.
+    pub fn modified(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, file)| file.is_modified())
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// Consider every document in the project to be saved as it currently is,
+    /// without writing anything.
+    pub fn mark_saved(&mut self) {
+        for file in self.files.values_mut() {
+            file.saved = Some(checksum(&file.document));
+        }
+    }
+
+    /// Write every document which has been modified since it was opened or
+    /// last saved back to its path, returning the number of files written.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a file can't be written. Files written before the error are
+    /// considered saved.
+    pub fn save(&mut self) -> io::Result<usize> {
+        let mut count = 0;
+        let mut output = Vec::new();
+
+        for (path, file) in &mut self.files {
+            output.clear();
+            file.document.write_to(&mut output)?;
+
+            let checksum = xxh3::hash64(&output);
+
+            if file.saved == Some(checksum) {
+                continue;
+            }
+
+            fs::write(path, &output)?;
+            file.saved = Some(checksum);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+/// Compute a checksum of the output of a document.
+fn checksum(document: &Document) -> u64 {
+    let mut output = Vec::new();
+    // Writing to a vector never fails.
+    _ = document.write_to(&mut output);
+    xxh3::hash64(&output)
+}
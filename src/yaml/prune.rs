@@ -0,0 +1,92 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::error::Error;
+use crate::yaml::lock;
+use crate::yaml::raw::Raw;
+
+/// Policy deciding which values are removed by [`Document::prune_empty`].
+///
+/// [`Document::prune_empty`]: crate::yaml::Document::prune_empty
+#[derive(Default, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Prune {
+    /// Only remove empty mappings and sequences, like `{}` and `[]`.
+    #[default]
+    Collections,
+    /// Remove empty mappings and sequences, and null values such as `key:` or
+    /// `key: ~`.
+    CollectionsAndNulls,
+}
+
+/// Prune empty values inside of the given value, returning the number of
+/// values removed.
+///
+/// Nothing is removed if any of the values which would be removed is locked.
+pub(crate) fn prune(data: &mut Data, id: Id, policy: Prune) -> Result<usize, Error> {
+    let mut removed = Vec::new();
+    plan(data, id, policy, &mut removed);
+    lock::check_all(data, &removed)?;
+
+    // Items are planned for removal before the collections containing them,
+    // so they are always removed from collections which are still alive.
+    for &item in &removed {
+        let items = match data.layout(item).parent.map(|id| data.raw_mut(id)) {
+            Some(Raw::Mapping(raw)) => Some(&mut raw.items),
+            Some(Raw::Sequence(raw)) => Some(&mut raw.items),
+            _ => None,
+        };
+
+        if let Some(items) = items {
+            let first = items.first() == Some(&item);
+            items.retain(|&id| id != item);
+
+            // The item which takes the place of the first one inherits its
+            // prefix, so that it's written where the first one was.
+            if let (true, Some(&next)) = (first, items.first()) {
+                let prefix = data.layout(item).prefix;
+                data.set_prefix(next, prefix);
+            }
+        }
+
+        data.drop(item);
+    }
+
+    Ok(removed.len())
+}
+
+/// Plan which items to remove inside of the given value, returning `true` if
+/// the value itself is empty once they've been removed.
+fn plan(data: &Data, id: Id, policy: Prune, removed: &mut Vec<Id>) -> bool {
+    let items = match data.raw(id) {
+        Raw::Mapping(raw) => &raw.items,
+        Raw::Sequence(raw) => &raw.items,
+        _ => return is_empty(data, id, policy),
+    };
+
+    let mut retained = 0usize;
+
+    for &item in items {
+        let value = match data.raw(item) {
+            Raw::MappingItem(item) => item.value,
+            Raw::SequenceItem(item) => item.value,
+            _ => continue,
+        };
+
+        if plan(data, value, policy, removed) {
+            removed.push(item);
+        } else {
+            retained += 1;
+        }
+    }
+
+    retained == 0
+}
+
+/// Test if the given value should be pruned.
+fn is_empty(data: &Data, id: Id, policy: Prune) -> bool {
+    match data.raw(id) {
+        Raw::Mapping(raw) => raw.items.is_empty(),
+        Raw::Sequence(raw) => raw.items.is_empty(),
+        Raw::Null(..) => matches!(policy, Prune::CollectionsAndNulls),
+        _ => false,
+    }
+}
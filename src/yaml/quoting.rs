@@ -0,0 +1,175 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::yaml::raw::RawStringKind;
+
+/// A predicate deciding which strings are always quoted, see
+/// [`Quoting::always_quote`].
+type Predicate = dyn Fn(&str) -> bool + Send + Sync;
+
+/// The quote style preferred by [`Quoting`] when a string has to be quoted.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QuoteStyle {
+    /// Prefer single quotes, like `'1.0'`.
+    #[default]
+    Single,
+    /// Prefer double quotes, like `"1.0"`.
+    Double,
+}
+
+/// Policy deciding how strings are quoted when they are inserted into a
+/// document, set through [`Document::set_quoting`].
+///
+/// The policy is consulted by methods such as [`ValueMut::set_string`] and
+/// [`MappingMut::insert_str`]. Strings are only quoted if they would otherwise
+/// be read back as something else, or if they match the predicate set through
+/// [`Quoting::always_quote`]. Strings containing control characters are always
+/// double-quoted, since they can only be written as escape sequences.
+///
+/// [`Document::set_quoting`]: crate::yaml::Document::set_quoting
+/// [`ValueMut::set_string`]: crate::yaml::ValueMut::set_string
+/// [`MappingMut::insert_str`]: crate::yaml::MappingMut::insert_str
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("name: app")?;
+///
+/// doc.set_quoting(
+///     yaml::Quoting::new()
+///         .prefer(yaml::QuoteStyle::Double)
+///         .always_quote(|s| s.starts_with('v') && s.contains('.')),
+/// );
+///
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+/// root.insert_str("version", "v1.2");
+/// root.insert_str("replicas", "3");
+/// root.insert_str("image", "nginx");
+///
+/// assert_eq!(doc.to_string(), "name: app\nversion: \"v1.2\"\nreplicas: \"3\"\nimage: nginx");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Default, Clone)]
+pub struct Quoting {
+    prefer: QuoteStyle,
+    always: Option<Arc<Predicate>>,
+}
+
+impl Quoting {
+    /// Construct the default quoting policy, which prefers single quotes and
+    /// only quotes strings when needed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the quote style to prefer when a string has to be quoted.
+    ///
+    /// The other style is still used if it avoids having to escape or double
+    /// characters, like for a string containing a `'` which is written as
+    /// `"it's"` regardless of the preference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("")?;
+    /// doc.set_quoting(yaml::Quoting::new().prefer(yaml::QuoteStyle::Double));
+    ///
+    /// doc.as_mut().set_string("true");
+    /// assert_eq!(doc.to_string(), "\"true\"");
+    ///
+    /// doc.as_mut().set_string("C:\\ #1");
+    /// assert_eq!(doc.to_string(), "'C:\\ #1'");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn prefer(mut self, prefer: QuoteStyle) -> Self {
+        self.prefer = prefer;
+        self
+    }
+
+    /// Always quote strings for which `predicate` returns `true`, even if they
+    /// could be written without quotes.
+    ///
+    /// This can be used to quote strings matching a regular expression engine
+    /// of your choice, such as version-like strings which some tools would
+    /// otherwise misread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("")?;
+    /// doc.set_quoting(yaml::Quoting::new().always_quote(|s| s.contains('*')));
+    ///
+    /// doc.as_mut().set_string("src/*.rs");
+    /// assert_eq!(doc.to_string(), "'src/*.rs'");
+    ///
+    /// doc.as_mut().set_string("src/lib.rs");
+    /// assert_eq!(doc.to_string(), "src/lib.rs");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn always_quote<F>(mut self, predicate: F) -> Self
+    where
+        F: 'static + Fn(&str) -> bool + Send + Sync,
+    {
+        self.always = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Detect the appropriate kind to use for the given string, which is
+    /// written inside of an inline collection if `flow` is set.
+    pub(crate) fn detect(&self, string: &str, flow: bool) -> RawStringKind {
+        let kind = RawStringKind::detect(string, flow);
+
+        match kind {
+            RawStringKind::Bare if !self.always.as_ref().is_some_and(|f| f(string)) => kind,
+            RawStringKind::Double if string.chars().any(char::is_control) => kind,
+            RawStringKind::Bare | RawStringKind::Single | RawStringKind::Double => {
+                self.quote(string)
+            }
+            kind => kind,
+        }
+    }
+
+    /// Pick the quote style for a string which has to be quoted, avoiding
+    /// the preferred style if it means escaping characters which the other
+    /// style doesn't have to.
+    fn quote(&self, string: &str) -> RawStringKind {
+        let (preferred, other, avoid) = match self.prefer {
+            QuoteStyle::Single => (
+                RawStringKind::Single,
+                RawStringKind::Double,
+                string.contains('\'') && !string.contains('\\'),
+            ),
+            QuoteStyle::Double => (
+                RawStringKind::Double,
+                RawStringKind::Single,
+                string.contains(['"', '\\']) && !string.contains('\''),
+            ),
+        };
+
+        if avoid {
+            other
+        } else {
+            preferred
+        }
+    }
+}
+
+impl fmt::Debug for Quoting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quoting")
+            .field("prefer", &self.prefer)
+            .field("always", &self.always.is_some())
+            .finish()
+    }
+}
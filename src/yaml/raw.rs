@@ -1,21 +1,42 @@
 use std::fmt::{self, Write};
 use std::io;
 use std::iter;
-use std::mem;
 
 use bstr::{BStr, ByteSlice};
 #[cfg(feature = "serde-edits")]
 use serde::{Deserialize, Serialize};
 
+use crate::yaml::clear;
 use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::error::{Error, ErrorKind};
+use crate::yaml::number;
 use crate::yaml::serde_hint::RawNumberHint;
+use crate::yaml::write::Hook;
 use crate::yaml::{Block, Chomp, StringKind};
 
 /// Newline character used in YAML.
 pub(crate) const NEWLINE: u8 = b'\n';
+/// Line break used by documents which predominantly use carriage returns.
+pub(crate) const CRLF: &[u8] = b"\r\n";
 /// Space character used in YAML.
 pub(crate) const SPACE: u8 = b' ';
 
+/// Test if the line breaks in `input` are predominantly `\r\n`.
+pub(crate) fn is_crlf(input: &[u8]) -> bool {
+    let mut crlf = 0usize;
+    let mut lf = 0usize;
+
+    for n in memchr::memchr_iter(NEWLINE, input) {
+        if n > 0 && input.get(n - 1) == Some(&b'\r') {
+            crlf += 1;
+        } else {
+            lf += 1;
+        }
+    }
+
+    crlf > lf
+}
+
 /// Get the indentation for the given string.
 pub(crate) fn indent(string: &[u8]) -> &[u8] {
     match memchr::memrchr(NEWLINE, string) {
@@ -38,16 +59,138 @@ pub(crate) fn new_bool(data: &mut Data, value: bool) -> Raw {
     Raw::Boolean(Boolean::new(value, string))
 }
 
-/// Construct a raw kind associated with a string.
-pub(crate) fn new_string<S>(data: &mut Data, string: S) -> Raw
+/// Construct a raw kind associated with a string, which is written inside of
+/// an inline collection if `flow` is set.
+pub(crate) fn new_string<S>(data: &mut Data, string: S, flow: bool) -> Raw
 where
     S: AsRef<str>,
 {
-    let kind = RawStringKind::detect(string.as_ref());
+    let kind = data.quoting().detect(string.as_ref(), flow);
     let string = data.insert_str(string.as_ref());
     Raw::String(String::new(kind, string, string))
 }
 
+/// Construct a raw kind associated with a byte string, which is not required
+/// to be valid UTF-8.
+pub(crate) fn new_bytes<B>(data: &mut Data, bytes: B, flow: bool) -> Raw
+where
+    B: AsRef<[u8]>,
+{
+    let kind = data.quoting().detect(&bytes.as_ref().to_str_lossy(), flow);
+    let string = data.insert_str(bytes.as_ref());
+    Raw::String(String::new(kind, string, string))
+}
+
+/// Find an existing prefix to reuse for an item added to an inline
+/// collection, which follows the separating comma.
+///
+/// This reuses the spacing of the last item, unless there's only one item in
+/// which case its prefix is only used if it's non-empty, like in `[ 1 ]`.
+pub(crate) fn inline_prefix(data: &Data, items: &[Id]) -> Option<StringId> {
+    match items {
+        [.., _, last] => Some(data.layout(*last).prefix),
+        [first] if !data.prefix(*first).is_empty() => Some(data.layout(*first).prefix),
+        _ => None,
+    }
+}
+
+/// Get a prefix with the same spacing as `prefix` but without any comments,
+/// so that comments preceding an existing item aren't copied when its prefix
+/// is reused.
+///
+/// Only the indentation following the last comment is kept, like `\n  ` for
+/// the prefix ` # comment\n  `.
+pub(crate) fn without_comments(data: &mut Data, prefix: StringId) -> StringId {
+    let string = data.str(prefix);
+
+    if !string.contains(&b'#') {
+        return prefix;
+    }
+
+    let indent = match string.iter().rposition(|&b| b == NEWLINE) {
+        Some(n) => string.get(n.saturating_add(1)..).unwrap_or_default(),
+        None => &[],
+    };
+
+    let mut out = data.newline().to_vec();
+    out.extend_from_slice(indent);
+    data.insert_str(out)
+}
+
+/// Find an existing prefix to reuse for the first item added to an empty
+/// inline collection.
+///
+/// Padding inside of the collection like in `[ ]` is mirrored, so that the
+/// first item becomes `[ 1 ]`.
+pub(crate) fn empty_inline_prefix(data: &Data, suffix: StringId) -> Option<StringId> {
+    let string = data.str(suffix);
+
+    if !string.is_empty() && string.iter().all(|&b| matches!(b, SPACE | b'\t')) {
+        return Some(suffix);
+    }
+
+    None
+}
+
+/// Extract a comment from the start of the given prefix which trails the
+/// previous value on the same line, such as `  # comment` in `  # comment\n  `.
+pub(crate) fn trailing_comment(prefix: &[u8]) -> Option<Vec<u8>> {
+    let end = prefix
+        .iter()
+        .position(|&b| b == NEWLINE)
+        .unwrap_or(prefix.len());
+
+    let line = prefix.get(..end).unwrap_or_default();
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+    if line.iter().find(|&&b| !matches!(b, SPACE | b'\t')) != Some(&b'#') {
+        return None;
+    }
+
+    Some(line.to_vec())
+}
+
+/// The parts of the prefix of a collection item.
+pub(crate) struct PrefixParts<'a> {
+    /// A comment trailing the previous item on the same line, like
+    /// ` # comment` in `[a, # comment\n  b]`.
+    pub(crate) trail: &'a [u8],
+    /// Line breaks and blank lines preceding any comments.
+    pub(crate) lead: &'a [u8],
+    /// Full lines of comments preceding the item, including their
+    /// indentation.
+    pub(crate) comments: &'a [u8],
+    /// The indentation of the item following the last line break.
+    pub(crate) indent: &'a [u8],
+}
+
+/// Split the prefix of a collection item into comments which belong to the
+/// neighbouring items and the whitespace which belongs to its position.
+pub(crate) fn prefix_parts(prefix: &[u8]) -> PrefixParts<'_> {
+    let trail = trailing_comment(prefix).map_or(0, |line| line.len());
+    let (trail, rest) = prefix.split_at(trail);
+
+    let indent = indent(rest);
+    let body = rest.get(..rest.len() - indent.len()).unwrap_or_default();
+
+    let start = match body.iter().position(|&b| b == b'#') {
+        Some(n) => body
+            .get(..n)
+            .and_then(|b| memchr::memrchr(NEWLINE, b))
+            .map_or(0, |n| n.saturating_add(1)),
+        None => body.len(),
+    };
+
+    let (lead, comments) = body.split_at(start);
+
+    PrefixParts {
+        trail,
+        lead,
+        comments,
+        indent,
+    }
+}
+
 /// Construct an indentation prefix.
 pub(crate) fn make_indent(data: &mut Data, id: Id, extra: usize) -> (usize, StringId) {
     let parent = data.layout(id).parent;
@@ -97,56 +240,162 @@ pub(crate) fn make_indent(data: &mut Data, id: Id, extra: usize) -> (usize, Stri
         }
     }
 
-    let indent = indent.saturating_add(2);
-    // Take some pains to preserve the existing suffix, synthesize extra spaces characters where needed.
-    let mut existing = self::indent(data.str(layout.prefix)).chars();
-
-    let mut prefix = Vec::new();
+    let line = self::indent(data.str(layout.prefix));
+    let (unit, width) = indent_unit(data, parent.and_then(|id| data.layout(id).parent), line);
+    let indent = indent.saturating_add(width);
+    // Take some pains to preserve the existing suffix, synthesize extra
+    // indentation where needed.
+    let mut existing = line.chars();
 
-    prefix.push(NEWLINE);
+    let mut prefix = data.newline().to_vec();
 
     for _ in 0..indent {
         if let Some(c) = existing.next() {
             prefix.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
         } else {
-            prefix.push(SPACE);
+            prefix.push(unit);
         }
     }
 
     (indent, data.insert_str(prefix))
 }
 
+/// Detect the unit of indentation used by the block collection `collection`,
+/// whose line is indented by `line`.
+///
+/// Collections indented with tabs are nested using a single tab, otherwise
+/// they are nested as deep as the collection is relative to its parent.
+fn indent_unit(data: &Data, collection: Option<Id>, line: &[u8]) -> (u8, usize) {
+    if line.contains(&b'\t') {
+        return (b'\t', 1);
+    }
+
+    let indent = |id: Id| match data.raw(id) {
+        Raw::Mapping(raw) if matches!(raw.kind, MappingKind::Mapping) => Some(raw.indent),
+        Raw::Sequence(raw) if matches!(raw.kind, SequenceKind::Mapping) => Some(raw.indent),
+        _ => None,
+    };
+
+    let width = collection.and_then(|id| {
+        let inner = indent(id)?;
+        let item = data.layout(id).parent?;
+        let outer = indent(data.layout(item).parent?)?;
+        inner.checked_sub(outer).filter(|&n| n > 0)
+    });
+
+    (SPACE, width.unwrap_or(2))
+}
+
+/// Construct the prefix of an item inserted into the block collection `id`
+/// with the given indentation after `last`, which is the last existing item.
+///
+/// If the items of the collection are indented with tabs, the indentation of
+/// the existing items is reused.
+pub(crate) fn block_item_prefix(
+    data: &mut Data,
+    id: Id,
+    last: Option<Id>,
+    indent: usize,
+) -> StringId {
+    let prefix = [last, Some(id)]
+        .into_iter()
+        .flatten()
+        .map(|id| data.prefix(id))
+        .find(|prefix| prefix.contains(&NEWLINE));
+
+    if let Some(prefix) = prefix {
+        let line = self::indent(prefix);
+
+        if line.contains(&b'\t') && line.chars().count() == indent {
+            let mut prefix = data.newline().to_vec();
+            prefix.extend_from_slice(line);
+            return data.insert_str(prefix);
+        }
+    }
+
+    data.block_prefix(indent)
+}
+
 /// Construct a raw kind associated with a string with a custom string kind.
 pub(crate) fn new_string_with<S>(data: &mut Data, string: S, kind: StringKind) -> Raw
 where
     S: AsRef<str>,
 {
-    let kind = match kind {
-        StringKind::Bare => RawStringKind::Bare,
-        StringKind::Single => RawStringKind::Single,
-        StringKind::Double => RawStringKind::Double,
-    };
-
     let string = data.insert_str(string.as_ref());
-    Raw::String(String::new(kind, string, string))
+    Raw::String(String::new(RawStringKind::from_kind(kind), string, string))
 }
 
 /// Construct a block with the given configuration.
-pub(crate) fn new_block<I>(data: &mut Data, id: Id, iter: I, block: Block) -> Raw
+///
+/// If `child` is set, the block is constructed as a new child of the
+/// collection `id`, otherwise it replaces the value `id`.
+///
+/// The block is written with an explicit indentation indicator if one is
+/// requested through `indicator`, or if the first line with any content starts
+/// with whitespace, since its indentation can't be detected otherwise.
+///
+/// Inside of inline collections, or after the tag of a scalar, the value is
+/// written as a double-quoted string instead, since blocks can't be written
+/// there.
+pub(crate) fn new_block<I>(
+    data: &mut Data,
+    id: Id,
+    iter: I,
+    block: Block,
+    indicator: Option<u8>,
+    child: bool,
+) -> Raw
 where
     I: IntoIterator,
     I::Item: AsRef<str>,
 {
-    let (indent, prefix) = match data.raw(id) {
-        Raw::Mapping(raw) => (raw.indent.wrapping_add(2), BStr::new(b"")),
-        Raw::Sequence(raw) => (raw.indent.wrapping_add(2), BStr::new(b"")),
+    let parts = iter.into_iter().collect::<Vec<_>>();
+
+    let in_flow = if child {
+        is_inline(data, id)
+    } else {
+        clear::in_flow(data, id) || data.layout(id).tag.is_some()
+    };
+
+    let (indent, prefix, owner) = match data.raw(id) {
+        Raw::Mapping(raw) => (raw.indent.wrapping_add(2), BStr::new(b""), raw.indent),
+        Raw::Sequence(raw) => (raw.indent.wrapping_add(2), BStr::new(b""), raw.indent),
         _ => {
             let prefix = data.str(data.layout(id).prefix);
 
             let n = prefix.rfind([NEWLINE]).map_or(0, |i| i.wrapping_add(1));
+            let prefix = BStr::new(prefix.get(n..).unwrap_or_default());
+
+            (2, prefix, prefix.chars().count())
+        }
+    };
+
+    let owner = if child {
+        owner
+    } else {
+        block_owner_indent(data, id).unwrap_or(owner)
+    };
+
+    let indicator = indicator.or_else(|| {
+        let first = parts
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|p| !p.trim().is_empty())?;
 
-            (2, &prefix[n..])
+        if !first.starts_with(char::is_whitespace) {
+            return None;
         }
+
+        let column = prefix.chars().count().wrapping_add(indent);
+        let n = column.saturating_sub(owner).clamp(1, 9);
+        Some(u8::try_from(n).unwrap_or(2))
+    });
+
+    // With an explicit indentation indicator the content is placed relative
+    // to the owning collection, rather than to the prefix of the value.
+    let (indent, prefix) = match indicator {
+        Some(n) => (owner.wrapping_add(usize::from(n)), BStr::new(b"")),
+        None => (indent, prefix),
     };
 
     let mut original = Vec::new();
@@ -158,12 +407,18 @@ where
     };
 
     original.push(mark);
+
+    if let Some(n) = indicator {
+        original.push(b'0'.wrapping_add(n));
+    }
+
     original.extend(chomp.as_byte());
 
-    let mut it = iter.into_iter().peekable();
+    let newline = data.newline();
+    let mut it = parts.iter().peekable();
 
     while let Some(part) = it.next() {
-        original.push(NEWLINE);
+        original.extend_from_slice(newline);
         original.extend_from_slice(prefix.as_bytes());
         original.resize(original.len() + indent, SPACE);
         original.extend(part.as_ref().as_bytes());
@@ -178,12 +433,333 @@ where
         out.push(NEWLINE);
     }
 
-    let original = data.insert_str(&original);
     let string = data.insert_str(out);
 
+    // Blocks can't be written inside of inline collections or after a tag, so
+    // the value is written as a double-quoted string instead.
+    if in_flow {
+        return Raw::String(self::String::new(RawStringKind::Double, string, string));
+    }
+
+    let original = data.insert_str(&original);
     Raw::String(self::String::new(RawStringKind::Original, string, original))
 }
 
+/// Test if the collection `id` is an inline collection, like `[a, b]` or
+/// `{a: b}`.
+pub(crate) fn is_inline(data: &Data, id: Id) -> bool {
+    match data.raw(id) {
+        Raw::Mapping(raw) => matches!(raw.kind, MappingKind::Inline { .. }),
+        Raw::Sequence(raw) => matches!(raw.kind, SequenceKind::Inline { .. }),
+        _ => false,
+    }
+}
+
+/// Get the indentation of the collection which the value `id` belongs to,
+/// which is what explicit indentation indicators of blocks are relative to.
+fn block_owner_indent(data: &Data, id: Id) -> Option<usize> {
+    let item = data.layout(id).parent?;
+    let collection = data.layout(item).parent?;
+
+    match data.raw(collection) {
+        Raw::Mapping(raw) => Some(raw.indent),
+        Raw::Sequence(raw) => Some(raw.indent),
+        _ => None,
+    }
+}
+
+/// Get the indicator of a block, like `|-`, and its original string following
+/// the indicator, or `None` if the string is not a block.
+fn block_parts<'a>(data: &'a Data, string: &String) -> Option<(&'a BStr, &'a BStr)> {
+    // The indicator is stored separately for parsed blocks, while blocks
+    // constructed through `new_block` include it in the original string.
+    let (indicator, original) = match string.kind {
+        RawStringKind::Multiline { prefix } => (data.str(prefix), data.str(string.original)),
+        RawStringKind::Original => {
+            let original = data.str(string.original);
+            let mut n = memchr::memchr(NEWLINE, original).unwrap_or(original.len());
+
+            if original.get(..n).is_some_and(|line| line.ends_with(b"\r")) {
+                n -= 1;
+            }
+
+            let (indicator, original) = original.split_at(n);
+            (BStr::new(indicator), BStr::new(original))
+        }
+        _ => return None,
+    };
+
+    matches!(indicator.first(), Some(b'|' | b'>')).then_some((indicator, original))
+}
+
+/// Get the chomping mode of a block from its indicator.
+fn block_chomp(indicator: &[u8]) -> Chomp {
+    if indicator.contains(&b'-') {
+        Chomp::Strip
+    } else if indicator.contains(&b'+') {
+        Chomp::Keep
+    } else {
+        Chomp::Clip
+    }
+}
+
+/// Get the style of the string if it is a block, like `|-` or `>`.
+pub(crate) fn block_style(data: &Data, string: &String) -> Option<Block> {
+    let (indicator, _) = block_parts(data, string)?;
+    let chomp = block_chomp(indicator);
+
+    match indicator.first() {
+        Some(b'>') => Some(Block::Folded(chomp)),
+        _ => Some(Block::Literal(chomp)),
+    }
+}
+
+/// Get the indentation of the content of the block `id`.
+fn block_indent(data: &Data, id: Id, indicator: &[u8], original: &[u8]) -> Vec<u8> {
+    // An explicit indentation indicator is relative to the owning collection,
+    // otherwise indentation is detected from the first line with any content.
+    let explicit = indicator.iter().find(|b| b.is_ascii_digit()).map(|b| {
+        let owner = block_owner_indent(data, id).unwrap_or_else(|| {
+            let line = indent(data.prefix(id));
+            line.iter().take_while(|b| b.is_ascii_whitespace()).count()
+        });
+
+        vec![SPACE; owner.wrapping_add(usize::from(b - b'0'))]
+    });
+
+    let detected = explicit.or_else(|| {
+        original
+            .split(|&b| b == NEWLINE)
+            .skip(1)
+            .find(|line| !line.trim().is_empty())
+            .map(|line| {
+                let n = line
+                    .iter()
+                    .position(|b| !b.is_ascii_whitespace())
+                    .unwrap_or(line.len());
+                line.get(..n).unwrap_or_default().to_vec()
+            })
+    });
+
+    match detected {
+        Some(indent) => indent,
+        None => {
+            let mut indent = indent(data.prefix(id)).to_vec();
+            indent.extend_from_slice(b"  ");
+            indent
+        }
+    }
+}
+
+/// Construct a string which replaces the value `id`, keeping the style of the
+/// existing string where the new string permits it.
+///
+/// Blocks keep their indicator and indentation, with the chomping indicator
+/// adjusted to the trailing line breaks of the new string. Folded blocks can
+/// only hold a single line, so they become literal blocks otherwise. Quoted
+/// strings keep their quotes, and bare strings stay bare unless the new string
+/// has to be quoted.
+pub(crate) fn new_string_preserving(data: &mut Data, id: Id, string: &str) -> Raw {
+    let flow = clear::in_flow(data, id);
+
+    let Raw::String(existing) = data.raw(id) else {
+        return new_string(data, string, flow);
+    };
+
+    if let Some((indicator, original)) = block_parts(data, existing) {
+        let Some(original) = preserving_block(data, id, indicator, original, string) else {
+            return new_string(data, string, flow);
+        };
+
+        let original = data.insert_str(original);
+        let string = data.insert_str(string);
+        return Raw::String(self::String::new(RawStringKind::Original, string, original));
+    }
+
+    let kind = match crate::yaml::replace::kind(data, existing) {
+        Some(RawStringKind::Bare)
+            if matches!(RawStringKind::detect(string, flow), RawStringKind::Bare) =>
+        {
+            RawStringKind::Bare
+        }
+        Some(RawStringKind::Single) if !string.chars().any(char::is_control) => {
+            RawStringKind::Single
+        }
+        Some(RawStringKind::Double) => RawStringKind::Double,
+        _ => return new_string(data, string, flow),
+    };
+
+    let string = data.insert_str(string);
+    Raw::String(self::String::new(kind, string, string))
+}
+
+/// Write a block in the style of an existing block, or `None` if the string
+/// can't be written as a block which reads back the same.
+fn preserving_block(
+    data: &Data,
+    id: Id,
+    indicator: &BStr,
+    original: &BStr,
+    string: &str,
+) -> Option<Vec<u8>> {
+    let trailing = string.bytes().rev().take_while(|&b| b == NEWLINE).count();
+    let content = &string[..string.len() - trailing];
+
+    if content.is_empty() {
+        return None;
+    }
+
+    for line in content.split('\n') {
+        let trimmed = line.trim_start();
+
+        if line.trim_end() != line
+            || line.chars().any(|c| c.is_control() && c != '\t')
+            || trimmed.starts_with('#')
+            || !line.is_empty() && trimmed.is_empty()
+        {
+            return None;
+        }
+    }
+
+    let mark = match indicator.first() {
+        Some(b'>') if !content.contains('\n') => b'>',
+        _ => b'|',
+    };
+
+    let chomp = match (block_chomp(indicator), trailing) {
+        (Chomp::Strip, 0) => Chomp::Strip,
+        (Chomp::Clip, 1) => Chomp::Clip,
+        (Chomp::Keep, 1..) => Chomp::Keep,
+        (_, 0) => Chomp::Strip,
+        (_, 1) => Chomp::Clip,
+        _ => Chomp::Keep,
+    };
+
+    let indent = block_indent(data, id, indicator, original);
+
+    let mut out = vec![mark];
+
+    // Keep an explicit indentation indicator, which is also needed if the
+    // first line starts with whitespace.
+    if let Some(&n) = indicator.iter().find(|b| b.is_ascii_digit()) {
+        out.push(n);
+    } else if content.starts_with(char::is_whitespace) {
+        let owner = block_owner_indent(data, id).unwrap_or_else(|| {
+            let line = self::indent(data.prefix(id));
+            line.iter().take_while(|b| b.is_ascii_whitespace()).count()
+        });
+
+        let n = indent
+            .len()
+            .checked_sub(owner)
+            .filter(|n| (1..=9).contains(n))?;
+        out.push(b'0'.wrapping_add(u8::try_from(n).ok()?));
+    }
+
+    out.extend(chomp.as_byte());
+
+    let newline = data.newline();
+
+    for line in content.split('\n') {
+        out.extend_from_slice(newline);
+
+        if !line.is_empty() {
+            out.extend_from_slice(&indent);
+            out.extend_from_slice(line.as_bytes());
+        }
+    }
+
+    for _ in 1..trailing {
+        out.extend_from_slice(newline);
+    }
+
+    Some(out)
+}
+
+/// Append a line to the literal block `id`, returning `false` if it's not a
+/// literal block.
+pub(crate) fn push_block_line(data: &mut Data, id: Id, line: &str) -> bool {
+    let Raw::String(string) = data.raw(id) else {
+        return false;
+    };
+
+    let Some((indicator, original)) = block_parts(data, string) else {
+        return false;
+    };
+
+    let Some((b'|', _)) = indicator.split_first() else {
+        return false;
+    };
+
+    // Content on the same line as the indicator is not supported.
+    if !original.starts_with(&[NEWLINE]) && !original.starts_with(CRLF) {
+        return false;
+    }
+
+    let chomp = block_chomp(indicator);
+    let indent = block_indent(data, id, indicator, original);
+
+    let mut new_original = original.to_vec();
+    let value = data.str(string.id);
+    let trailing = value.iter().rev().take_while(|&&b| b == NEWLINE).count();
+    let newline = data.newline();
+
+    // Trailing blank lines are part of the value of a block which keeps its
+    // line breaks, so the line is appended after them.
+    let (mut new_value, mut separate) = match chomp {
+        Chomp::Keep => {
+            for _ in 1..trailing {
+                new_original.extend_from_slice(newline);
+            }
+
+            (value.to_vec(), !value.is_empty() && trailing == 0)
+        }
+        _ => {
+            let content = value.get(..value.len() - trailing).unwrap_or_default();
+            (content.to_vec(), !content.is_empty())
+        }
+    };
+
+    for part in line.split('\n') {
+        new_original.extend_from_slice(newline);
+
+        if !part.is_empty() {
+            new_original.extend_from_slice(&indent);
+            new_original.extend_from_slice(part.as_bytes());
+        }
+
+        if separate {
+            new_value.push(NEWLINE);
+        }
+
+        new_value.extend_from_slice(part.as_bytes());
+        separate = true;
+    }
+
+    let trailing = match chomp {
+        Chomp::Strip => 0,
+        Chomp::Clip => trailing.min(1),
+        Chomp::Keep => trailing,
+    };
+
+    new_value.resize(new_value.len() + trailing, NEWLINE);
+
+    let new_original = match string.kind {
+        RawStringKind::Original => [indicator.as_bytes(), &new_original].concat(),
+        _ => new_original,
+    };
+
+    let kind = string.kind;
+    let id_string = data.insert_str(new_value);
+    let original = data.insert_str(new_original);
+
+    data.replace(
+        id,
+        Raw::String(self::String::new(kind, id_string, original)),
+    );
+    true
+}
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub(crate) struct Layout {
@@ -192,6 +768,314 @@ pub(crate) struct Layout {
     /// Reference to the parent of a value.
     #[allow(unused)]
     pub(crate) parent: Option<Id>,
+    /// The anchor of the value, like `&name`, which is written after the
+    /// prefix.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) anchor: Option<Anchor>,
+    /// The tag of a value which is not a collection, like `!name`, which is
+    /// written after the anchor. Tags of collections are stored in the
+    /// collection.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) tag: Option<Tag>,
+}
+
+/// An anchor attached to a value, like `&name`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Anchor {
+    /// The name of the anchor, without the leading `&`.
+    pub(crate) name: StringId,
+    /// Whitespace and comments following the anchor.
+    pub(crate) suffix: StringId,
+}
+
+/// Write the prefix of the value `id`, followed by its anchor and tag if it
+/// has them.
+pub(crate) fn write_prefix<O>(data: &Data, id: Id, o: &mut O) -> io::Result<()>
+where
+    O: ?Sized + io::Write,
+{
+    let layout = data.layout(id);
+    o.write_all(data.str(layout.prefix))?;
+
+    if let Some(anchor) = layout.anchor {
+        o.write_all(b"&")?;
+        o.write_all(data.str(anchor.name))?;
+        o.write_all(data.str(anchor.suffix))?;
+    }
+
+    if let Some(tag) = &layout.tag {
+        tag.write_to(data, o)?;
+    }
+
+    Ok(())
+}
+
+/// Display the prefix of the value `id`, followed by its anchor and tag if it
+/// has them.
+pub(crate) fn display_prefix(data: &Data, id: Id, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let layout = data.layout(id);
+    write!(f, "{}", data.str(layout.prefix))?;
+
+    if let Some(anchor) = layout.anchor {
+        write!(f, "&{}{}", data.str(anchor.name), data.str(anchor.suffix))?;
+    }
+
+    if let Some(tag) = &layout.tag {
+        tag.display(data, f)?;
+    }
+
+    Ok(())
+}
+
+/// Test if the prefix of the root `id` should be left out, which is the case
+/// for an empty block collection preceded only by whitespace. Such a
+/// collection is written as nothing, like it would be if it was nested.
+pub(crate) fn is_hidden_root_prefix(data: &Data, id: Id) -> bool {
+    let empty = match data.raw(id) {
+        Raw::Mapping(raw) => {
+            matches!(raw.kind, MappingKind::Mapping) && raw.items.is_empty() && raw.tag.is_none()
+        }
+        Raw::Sequence(raw) => {
+            matches!(raw.kind, SequenceKind::Mapping) && raw.items.is_empty() && raw.tag.is_none()
+        }
+        _ => false,
+    };
+
+    empty
+        && !is_decorated(data, Some(id))
+        && data
+            .str(data.layout(id).prefix)
+            .iter()
+            .all(u8::is_ascii_whitespace)
+}
+
+/// Test if the value `id` has an anchor or a tag stored in its layout.
+fn is_decorated(data: &Data, id: Option<Id>) -> bool {
+    id.is_some_and(|id| {
+        let layout = data.layout(id);
+        layout.anchor.is_some() || layout.tag.is_some()
+    })
+}
+
+/// Check that `name` can be used as the name of an anchor or an alias.
+pub(crate) fn check_anchor_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        return Err(Error::new(0..0, ErrorKind::BadAnchor));
+    }
+
+    if let Some((n, c)) = name.char_indices().find(|&(_, c)| is_separator(c)) {
+        return Err(Error::new(n..n + c.len_utf8(), ErrorKind::BadAnchor));
+    }
+
+    Ok(())
+}
+
+/// Check that `name` can be used as a tag.
+///
+/// Tags have to start with a `!`, like `!Ref` or `!!str`.
+pub(crate) fn check_tag_name(name: &str) -> Result<(), Error> {
+    if !name.starts_with('!') {
+        let end = name.chars().next().map_or(0, char::len_utf8);
+        return Err(Error::new(0..end, ErrorKind::BadTag));
+    }
+
+    if let Some((n, c)) = name.char_indices().find(|&(_, c)| is_separator(c)) {
+        return Err(Error::new(n..n + c.len_utf8(), ErrorKind::BadTag));
+    }
+
+    Ok(())
+}
+
+/// Test if `c` ends the name of an anchor, an alias or a tag.
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | '[' | ']' | '{' | '}')
+}
+
+/// Place a decoration like an anchor or a tag in front of the content of the
+/// value `id`, where `lead` is the whitespace currently preceding the content.
+///
+/// `root` indicates that nothing precedes `lead` on the line, and `block` that
+/// the content is a block collection which has to start on the line following
+/// the decoration.
+///
+/// Returns the new whitespace preceding the decoration and the whitespace
+/// following it.
+fn decorate(
+    data: &mut Data,
+    id: Id,
+    lead: StringId,
+    root: bool,
+    block: bool,
+) -> (StringId, StringId) {
+    let string = data.str(lead);
+
+    if block {
+        let indent = match data.raw(id) {
+            Raw::Mapping(raw) => raw.indent,
+            Raw::Sequence(raw) => raw.indent,
+            _ => 0,
+        };
+
+        if root {
+            let start = string
+                .rfind_byte(NEWLINE)
+                .map_or(0, |n| n.saturating_add(1));
+            let mut suffix = data.newline().to_vec();
+            suffix.extend_from_slice(string.get(start..).unwrap_or_default());
+            return (lead, data.insert_str(suffix));
+        }
+
+        if string.contains(&NEWLINE) {
+            return (data.insert_str(" "), lead);
+        }
+
+        let mut suffix = data.newline().to_vec();
+        suffix.resize(suffix.len().saturating_add(indent), SPACE);
+        return (lead, data.insert_str(suffix));
+    }
+
+    let lead = if !root && string.is_empty() {
+        data.insert_str(" ")
+    } else {
+        lead
+    };
+
+    let suffix = if matches!(data.raw(id), Raw::Null(Null::Empty)) {
+        ""
+    } else {
+        " "
+    };
+
+    (lead, data.insert_str(suffix))
+}
+
+/// Get the tag of the collection `id`, if it's a collection.
+fn collection_tag(data: &mut Data, id: Id) -> Option<&mut Option<Tag>> {
+    match data.raw_mut(id) {
+        Raw::Mapping(raw) => Some(&mut raw.tag),
+        Raw::Sequence(raw) => Some(&mut raw.tag),
+        _ => None,
+    }
+}
+
+/// Set the anchor of the value `id` to `name`.
+///
+/// A block collection is moved to the line following its anchor, since the
+/// anchor has to be written before its first entry.
+pub(crate) fn set_anchor(data: &mut Data, id: Id, name: StringId) {
+    let layout = *data.layout(id);
+
+    if let Some(anchor) = layout.anchor {
+        data.set_anchor(id, Some(Anchor { name, ..anchor }));
+        return;
+    }
+
+    let tagged = layout.tag.is_some() || collection_tag(data, id).is_some_and(|t| t.is_some());
+    let block = data.raw(id).is_tabular() && !tagged;
+    let root = layout.parent.is_none();
+    let (prefix, suffix) = decorate(data, id, layout.prefix, root, block);
+
+    data.set_prefix(id, prefix);
+    data.set_anchor(id, Some(Anchor { name, suffix }));
+}
+
+/// Remove the anchor of the value `id`, returning `true` if it had one.
+///
+/// A block collection which was written on the line following its anchor
+/// stays there.
+pub(crate) fn remove_anchor(data: &mut Data, id: Id) -> bool {
+    let layout = data.layout(id);
+
+    let Some(anchor) = layout.anchor else {
+        return false;
+    };
+
+    if layout.parent.is_some() && data.raw(id).is_tabular() {
+        data.set_prefix(id, anchor.suffix);
+    }
+
+    data.set_anchor(id, None);
+    true
+}
+
+/// Set the tag of the value `id` to `name`.
+///
+/// A block collection is moved to the line following its tag, since the tag
+/// has to be written before its first entry.
+pub(crate) fn set_tag(data: &mut Data, id: Id, name: StringId) {
+    let layout = *data.layout(id);
+
+    let existing = match collection_tag(data, id) {
+        Some(tag) => tag.as_mut(),
+        None => None,
+    };
+
+    if let Some(tag) = existing {
+        tag.name = name;
+        return;
+    }
+
+    if let Some(tag) = layout.tag {
+        data.set_tag(id, Some(Tag { name, ..tag }));
+        return;
+    }
+
+    let lead = layout.anchor.map_or(layout.prefix, |anchor| anchor.suffix);
+    let root = layout.parent.is_none() && layout.anchor.is_none();
+    let block = data.raw(id).is_tabular();
+    let (lead, suffix) = decorate(data, id, lead, root, block);
+
+    match layout.anchor {
+        Some(anchor) => data.set_anchor(
+            id,
+            Some(Anchor {
+                suffix: lead,
+                ..anchor
+            }),
+        ),
+        None => data.set_prefix(id, lead),
+    }
+
+    let tag = Tag { name, suffix };
+
+    match collection_tag(data, id) {
+        Some(existing) => *existing = Some(tag),
+        None => data.set_tag(id, Some(tag)),
+    }
+}
+
+/// Remove the tag of the value `id`, returning `true` if it had one.
+///
+/// A block collection which was written on the line following its tag stays
+/// there.
+pub(crate) fn remove_tag(data: &mut Data, id: Id) -> bool {
+    let layout = *data.layout(id);
+
+    if layout.tag.is_some() {
+        data.set_tag(id, None);
+        return true;
+    }
+
+    let Some(tag) = collection_tag(data, id).and_then(Option::take) else {
+        return false;
+    };
+
+    if data.raw(id).is_tabular() {
+        match layout.anchor {
+            Some(anchor) => data.set_anchor(
+                id,
+                Some(Anchor {
+                    suffix: tag.suffix,
+                    ..anchor
+                }),
+            ),
+            None if layout.parent.is_some() => data.set_prefix(id, tag.suffix),
+            None => {}
+        }
+    }
+
+    true
 }
 
 /// A raw value.
@@ -215,6 +1099,8 @@ pub(crate) enum Raw {
     Sequence(Sequence),
     /// A single item inside of a sequence.
     SequenceItem(SequenceItem),
+    /// An alias referencing an anchored value, like `*name`.
+    Alias(Alias),
 }
 
 impl Raw {
@@ -230,21 +1116,21 @@ impl Raw {
             }
             Raw::Boolean(raw) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 raw.display(data, f)?;
             }
             Raw::Number(raw) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 raw.display(data, f)?;
             }
             Raw::String(raw) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 raw.display(data, f)?;
@@ -254,7 +1140,7 @@ impl Raw {
             }
             Raw::MappingItem(raw) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 raw.display(data, f)?;
@@ -264,7 +1150,14 @@ impl Raw {
             }
             Raw::SequenceItem(raw) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
+                }
+
+                raw.display(data, f)?;
+            }
+            Raw::Alias(raw) => {
+                if let Some(id) = prefix {
+                    display_prefix(data, id, f)?;
                 }
 
                 raw.display(data, f)?;
@@ -275,6 +1168,19 @@ impl Raw {
     }
 
     pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    where
+        O: ?Sized + io::Write,
+    {
+        self.write_with(data, o, &mut Hook::default())
+    }
+
+    /// Write the value, using `hook` to write nested values.
+    pub(crate) fn write_with<O>(
+        &self,
+        data: &Data,
+        o: &mut O,
+        hook: &mut Hook<'_>,
+    ) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
@@ -292,15 +1198,18 @@ impl Raw {
                 raw.write_to(data, o)?;
             }
             Raw::Mapping(raw) => {
-                raw.write_to(data, o)?;
+                raw.write_to(data, o, hook)?;
             }
             Raw::MappingItem(raw) => {
-                raw.write_to(data, o)?;
+                raw.write_to(data, o, hook)?;
             }
             Raw::Sequence(raw) => {
-                raw.write_to(data, o)?;
+                raw.write_to(data, o, hook)?;
             }
             Raw::SequenceItem(raw) => {
+                raw.write_to(data, o, hook)?;
+            }
+            Raw::Alias(raw) => {
                 raw.write_to(data, o)?;
             }
         }
@@ -360,20 +1269,23 @@ impl Null {
         match self {
             Null::Keyword(string) => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 write!(f, "{}", data.str(*string))?;
             }
             Null::Tilde => {
                 if let Some(id) = prefix {
-                    write!(f, "{}", data.prefix(id))?;
+                    display_prefix(data, id, f)?;
                 }
 
                 write!(f, "~")?;
             }
             Null::Empty => {
-                // empty values count as null.
+                // empty values count as null, unless they are anchored.
+                if let (Some(id), true) = (prefix, is_decorated(data, prefix)) {
+                    display_prefix(data, id, f)?;
+                }
             }
         }
 
@@ -475,37 +1387,103 @@ pub(crate) enum RawStringKind {
 }
 
 impl RawStringKind {
-    /// Detect the appropriate kind to use for the given string.
-    pub(crate) fn detect(string: &str) -> RawStringKind {
-        if matches!(string, "true" | "false" | "null") {
-            return RawStringKind::Single;
+    /// Convert a public [`StringKind`] into a raw string kind.
+    pub(crate) fn from_kind(kind: StringKind) -> Self {
+        match kind {
+            StringKind::Bare => RawStringKind::Bare,
+            StringKind::Single => RawStringKind::Single,
+            StringKind::Double => RawStringKind::Double,
         }
+    }
 
-        let mut kind = RawStringKind::Bare;
-        let mut first = true;
+    /// Detect the appropriate kind to use for the given string.
+    ///
+    /// Strings are only quoted if they would otherwise be read back as
+    /// something else. Backslashes are not special in bare or single-quoted
+    /// strings, so strings like Windows paths are only double-quoted if they
+    /// contain characters which have to be written as escape sequences.
+    ///
+    /// If `flow` is set, the string is written inside of an inline collection.
+    pub(crate) fn detect(string: &str, flow: bool) -> RawStringKind {
+        if string.chars().any(char::is_control) {
+            return RawStringKind::Double;
+        }
 
-        for c in string.chars() {
-            let first = mem::take(&mut first);
+        if !needs_quotes(string, flow) {
+            return RawStringKind::Bare;
+        }
 
-            match c {
-                '0'..='9' if first => {
-                    kind = RawStringKind::Single;
-                }
-                '\'' => {
-                    return RawStringKind::Double;
-                }
-                ':' => {
-                    kind = RawStringKind::Single;
-                }
-                b if b.is_control() => {
-                    return RawStringKind::Double;
-                }
-                _ => {}
-            }
+        // Prefer not having to double single quotes, unless that would mean
+        // escaping backslashes instead.
+        if string.contains('\'') && !string.contains('\\') {
+            RawStringKind::Double
+        } else {
+            RawStringKind::Single
         }
+    }
+}
+
+/// Test if a string which doesn't contain any control characters has to be
+/// quoted to be read back as the same string.
+///
+/// Inside of inline collections, which is the case if `flow` is set, bare
+/// strings can't contain any of the indicators which separate or end items.
+pub(crate) fn needs_quotes(string: &str, flow: bool) -> bool {
+    const KEYWORDS: [&str; 3] = ["true", "false", "null"];
 
-        kind
+    if string == "~" || KEYWORDS.iter().any(|k| string.eq_ignore_ascii_case(k)) {
+        return true;
     }
+
+    if flow && string.contains([',', '[', ']', '{', '}']) {
+        return true;
+    }
+
+    // Special floats such as `.inf` would be read back as numbers.
+    if number::special(string.as_bytes()).is_some() {
+        return true;
+    }
+
+    let mut it = string.chars();
+
+    let (Some(first), Some(last)) = (it.next(), string.chars().next_back()) else {
+        return true;
+    };
+
+    if first.is_whitespace() || last.is_whitespace() {
+        return true;
+    }
+
+    let second = it.next();
+
+    match first {
+        // Indicators which can't start a bare string.
+        '\'' | '"' | '&' | '*' | '!' | '#' | '|' | '>' | '%' | '@' | '`' | '[' | ']' | '{'
+        | '}' | ',' => return true,
+        // Indicators which only start a bare string if they're followed by
+        // something other than whitespace, like `-a`.
+        '-' | '?' | ':' if second.map_or(true, char::is_whitespace) => return true,
+        // Strings which would be read back as numbers.
+        '0'..='9' => return true,
+        '-' | '+' | '.' if second.is_some_and(|c| c.is_ascii_digit()) => return true,
+        _ => {}
+    }
+
+    let mut it = string.chars().peekable();
+
+    while let Some(c) = it.next() {
+        match (c, it.peek()) {
+            // A colon is only ambiguous if it's followed by whitespace or
+            // ends the string, like in `key: value` or `key:`.
+            (':', None) => return true,
+            (':', Some(c)) if c.is_whitespace() => return true,
+            // A hash preceded by whitespace starts a comment.
+            (c, Some('#')) if c.is_whitespace() => return true,
+            _ => {}
+        }
+    }
+
+    false
 }
 
 /// A YAML string.
@@ -520,6 +1498,47 @@ pub(crate) struct String {
     pub(crate) original: StringId,
 }
 
+/// Escape sequences in double-quoted strings, as pairs of the character
+/// following the backslash and the character it represents:
+/// <https://yaml.org/spec/1.2.2/#escaped-characters>.
+///
+/// The `\x`, `\u` and `\U` escapes which are followed by hex digits are not
+/// included, neither are the aliases handled by [`unescape`].
+const ESCAPES: [(u8, char); 15] = [
+    (b'0', '\0'),
+    (b'a', '\x07'),
+    (b'b', '\x08'),
+    (b't', '\t'),
+    (b'n', '\n'),
+    (b'v', '\x0b'),
+    (b'f', '\x0c'),
+    (b'r', '\r'),
+    (b'e', '\x1b'),
+    (b'"', '"'),
+    (b'\\', '\\'),
+    (b'N', '\u{85}'),
+    (b'_', '\u{a0}'),
+    (b'L', '\u{2028}'),
+    (b'P', '\u{2029}'),
+];
+
+/// Get the character represented by the escape sequence `\` followed by `b`.
+pub(crate) fn unescape(b: u8) -> Option<char> {
+    match b {
+        // Aliases which are never produced when escaping.
+        b'\t' => Some('\t'),
+        b' ' => Some(' '),
+        b'/' => Some('/'),
+        _ => ESCAPES.iter().find(|(e, _)| *e == b).map(|&(_, c)| c),
+    }
+}
+
+/// Get the character following a backslash used to escape `c` in a
+/// double-quoted string, if it has a dedicated escape sequence.
+fn escape(c: char) -> Option<u8> {
+    ESCAPES.iter().find(|(_, e)| *e == c).map(|&(b, _)| b)
+}
+
 impl String {
     /// A simple number.
     pub(crate) fn new(kind: RawStringKind, id: StringId, original: StringId) -> Self {
@@ -538,9 +1557,10 @@ impl String {
                     break;
                 };
 
-                write!(f, "{}", &string[..n])?;
+                let (head, tail) = string.split_at(n);
+                write!(f, "{}", BStr::new(head))?;
                 f.write_str("''")?;
-                string = &string[n.saturating_add(1)..];
+                string = BStr::new(tail.get(1..).unwrap_or_default());
             }
 
             f.write_char('\'')?;
@@ -555,32 +1575,30 @@ impl String {
             let mut start = 0;
 
             for (index, end, c) in string.char_indices() {
-                let esc = match c {
-                    '\u{00}' => "\\0",
-                    '\u{07}' => "\\a",
-                    '\u{08}' => "\\b",
-                    '\u{09}' => "\\t",
-                    '\n' => "\\n",
-                    '\u{0b}' => "\\v",
-                    '\u{0c}' => "\\f",
-                    '\r' => "\\r",
-                    '\u{1b}' => "\\e",
-                    '\"' => "\\\"",
-                    c if c.is_ascii_control() => {
-                        write!(f, "{}\\x{:02x}", &string[start..index], c as u8)?;
+                let Some(esc) = escape(c) else {
+                    if c.is_ascii_control() {
+                        write!(
+                            f,
+                            "{}\\x{:02x}",
+                            BStr::new(string.get(start..index).unwrap_or_default()),
+                            c as u8
+                        )?;
                         start = end;
-                        continue;
-                    }
-                    _ => {
-                        continue;
                     }
+
+                    continue;
                 };
 
-                write!(f, "{}{esc}", &string[start..index])?;
+                write!(
+                    f,
+                    "{}\\{}",
+                    BStr::new(string.get(start..index).unwrap_or_default()),
+                    char::from(esc)
+                )?;
                 start = end;
             }
 
-            write!(f, "{}", &string[start..])?;
+            write!(f, "{}", BStr::new(string.get(start..).unwrap_or_default()))?;
             f.write_char('"')?;
             Ok(())
         }
@@ -611,7 +1629,7 @@ impl String {
         Ok(())
     }
 
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    pub(crate) fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
@@ -629,9 +1647,10 @@ impl String {
                     break;
                 };
 
-                f.write_all(&string[..index])?;
+                let (head, tail) = string.split_at(index);
+                f.write_all(head)?;
                 f.write_all(b"''")?;
-                string = &string[index.saturating_add(1)..];
+                string = BStr::new(tail.get(1..).unwrap_or_default());
             }
 
             f.write_all(b"\'")?;
@@ -647,35 +1666,23 @@ impl String {
             o.write_all(b"\"")?;
             let mut s = 0;
 
-            for (index, b) in string.bytes().enumerate() {
-                let esc = match b {
-                    b'\0' => b"\\0",
-                    0x07 => b"\\a",
-                    0x08 => b"\\b",
-                    0x09 => b"\\t",
-                    b'\n' => b"\\n",
-                    0x0b => b"\\v",
-                    0x0c => b"\\f",
-                    b'\r' => b"\\r",
-                    0x1b => b"\\e",
-                    b'\"' => b"\\\"",
-                    c if c.is_ascii_control() => {
-                        o.write_all(&string[s..index])?;
-                        write!(o, "\\x{c:02x}")?;
-                        s = index.saturating_add(1);
-                        continue;
-                    }
-                    _ => {
-                        continue;
+            for (index, end, c) in string.char_indices() {
+                let Some(esc) = escape(c) else {
+                    if c.is_ascii_control() {
+                        o.write_all(string.get(s..index).unwrap_or_default())?;
+                        write!(o, "\\x{:02x}", c as u8)?;
+                        s = end;
                     }
+
+                    continue;
                 };
 
-                o.write_all(&string[s..index])?;
-                o.write_all(esc)?;
-                s = index.saturating_add(1);
+                o.write_all(string.get(s..index).unwrap_or_default())?;
+                o.write_all(&[b'\\', esc])?;
+                s = end;
             }
 
-            o.write_all(&string[s..])?;
+            o.write_all(string.get(s..).unwrap_or_default())?;
             o.write_all(b"\"")?;
             Ok(())
         }
@@ -741,6 +1748,9 @@ pub(crate) struct Sequence {
     pub(crate) kind: SequenceKind,
     /// Items in the sequence.
     pub(crate) items: Vec<Id>,
+    /// The tag of the sequence, like `!!omap`.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) tag: Option<Tag>,
 }
 
 impl Sequence {
@@ -751,12 +1761,20 @@ impl Sequence {
         f: &mut fmt::Formatter,
         prefix: Option<Id>,
     ) -> fmt::Result {
-        if matches!(self.kind, SequenceKind::Inline { .. }) || !self.items.is_empty() {
+        if matches!(self.kind, SequenceKind::Inline { .. })
+            || !self.items.is_empty()
+            || self.tag.is_some()
+            || is_decorated(data, prefix)
+        {
             if let Some(id) = prefix {
-                write!(f, "{}", data.prefix(id))?;
+                display_prefix(data, id, f)?;
             }
         }
 
+        if let Some(tag) = &self.tag {
+            tag.display(data, f)?;
+        }
+
         if let SequenceKind::Inline { .. } = &self.kind {
             write!(f, "[")?;
         }
@@ -780,7 +1798,7 @@ impl Sequence {
         }
 
         if let SequenceKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
+            if *trailing && !self.items.is_empty() {
                 write!(f, ",")?;
             }
 
@@ -790,24 +1808,30 @@ impl Sequence {
         Ok(())
     }
 
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O, hook: &mut Hook<'_>) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
+        if let Some(tag) = &self.tag {
+            tag.write_to(data, o)?;
+        }
+
         if let SequenceKind::Inline { .. } = &self.kind {
             write!(o, "[")?;
         }
 
-        let mut it = self.items.iter().peekable();
+        let mut it = self.items.iter().enumerate().peekable();
 
-        while let Some(item) = it.next() {
+        while let Some((index, item)) = it.next() {
             write!(o, "{}", data.prefix(*item))?;
 
             if let SequenceKind::Mapping = self.kind {
                 write!(o, "-")?;
             }
 
-            data.sequence_item(*item).write_to(data, o)?;
+            hook.enter_index(index);
+            data.sequence_item(*item).write_to(data, o, hook)?;
+            hook.leave();
 
             if it.peek().is_some() {
                 if let SequenceKind::Inline { .. } = self.kind {
@@ -817,7 +1841,7 @@ impl Sequence {
         }
 
         if let SequenceKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
+            if *trailing && !self.items.is_empty() {
                 write!(o, ",")?;
             }
 
@@ -834,24 +1858,64 @@ impl Sequence {
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
 pub(crate) struct SequenceItem {
     pub(crate) value: Id,
+    /// Whitespace and comments following the value in an inline sequence,
+    /// before the separating comma, or the comment following the value on the
+    /// same line in a block sequence.
+    pub(crate) suffix: Option<StringId>,
 }
 
 impl SequenceItem {
     fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
         data.raw(self.value).display(data, f, Some(self.value))?;
+
+        if let Some(suffix) = self.suffix {
+            write!(f, "{}", data.str(suffix))?;
+        }
+
         Ok(())
     }
 
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O, hook: &mut Hook<'_>) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
-        o.write_all(data.prefix(self.value))?;
-        data.raw(self.value).write_to(data, o)?;
+        write_prefix(data, self.value, o)?;
+        hook.write_value(data, self.value, o)?;
+
+        if let Some(suffix) = self.suffix {
+            o.write_all(data.str(suffix))?;
+        }
+
         Ok(())
     }
 }
 
+/// An alias referencing an anchored value, like `*name`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Alias {
+    /// The name of the referenced anchor, without the leading `*`.
+    pub(crate) name: StringId,
+}
+
+impl Alias {
+    pub(crate) fn new(name: StringId) -> Self {
+        Self { name }
+    }
+
+    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "*{}", data.str(self.name))
+    }
+
+    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    where
+        O: ?Sized + io::Write,
+    {
+        o.write_all(b"*")?;
+        o.write_all(data.str(self.name))
+    }
+}
+
 /// The kind of a raw mapping.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
@@ -887,6 +1951,9 @@ pub(crate) struct Mapping {
     pub(crate) kind: MappingKind,
     /// Items inside of the mapping.
     pub(crate) items: Vec<Id>,
+    /// The tag of the mapping, like `!!set`.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) tag: Option<Tag>,
 }
 
 impl Mapping {
@@ -897,12 +1964,20 @@ impl Mapping {
         f: &mut fmt::Formatter,
         prefix: Option<Id>,
     ) -> fmt::Result {
-        if matches!(self.kind, MappingKind::Inline { .. }) || !self.items.is_empty() {
+        if matches!(self.kind, MappingKind::Inline { .. })
+            || !self.items.is_empty()
+            || self.tag.is_some()
+            || is_decorated(data, prefix)
+        {
             if let Some(id) = prefix {
-                write!(f, "{}", data.prefix(id))?;
+                display_prefix(data, id, f)?;
             }
         }
 
+        if let Some(tag) = &self.tag {
+            tag.display(data, f)?;
+        }
+
         if let MappingKind::Inline { .. } = &self.kind {
             write!(f, "{{")?;
         }
@@ -922,7 +1997,7 @@ impl Mapping {
         }
 
         if let MappingKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
+            if *trailing && !self.items.is_empty() {
                 write!(f, ",")?;
             }
 
@@ -933,10 +2008,14 @@ impl Mapping {
         Ok(())
     }
 
-    fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
+    fn write_to<O>(&self, data: &Data, o: &mut O, hook: &mut Hook<'_>) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
+        if let Some(tag) = &self.tag {
+            tag.write_to(data, o)?;
+        }
+
         if let MappingKind::Inline { .. } = &self.kind {
             write!(o, "{{")?;
         }
@@ -945,7 +2024,7 @@ impl Mapping {
 
         while let Some(id) = it.next() {
             o.write_all(data.prefix(*id))?;
-            data.mapping_item(*id).write_to(data, o)?;
+            data.mapping_item(*id).write_to(data, o, hook)?;
 
             if it.peek().is_some() {
                 if let MappingKind::Inline { .. } = &self.kind {
@@ -955,7 +2034,7 @@ impl Mapping {
         }
 
         if let MappingKind::Inline { trailing, suffix } = &self.kind {
-            if *trailing {
+            if *trailing && !self.items.is_empty() {
                 write!(o, ",")?;
             }
 
@@ -973,24 +2052,156 @@ impl Mapping {
 pub(crate) struct MappingItem {
     pub(crate) key: String,
     pub(crate) value: Id,
+    /// Whitespace and comments following the value in an inline mapping,
+    /// before the separating comma, or the comment following the value on the
+    /// same line in a block mapping.
+    pub(crate) suffix: Option<StringId>,
+    /// How the key of the item is written.
+    #[cfg_attr(feature = "serde-edits", serde(default))]
+    pub(crate) kind: MappingItemKind,
 }
 
 impl MappingItem {
     fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
-        self.key.display(data, f)?;
-        write!(f, ":")?;
+        match self.kind {
+            MappingItemKind::Pair => {
+                self.key.display(data, f)?;
+                write!(f, ":")?;
+            }
+            MappingItemKind::Explicit { separator } => {
+                write!(f, "?{}", data.str(separator))?;
+                self.key.display(data, f)?;
+            }
+            MappingItemKind::Key => {
+                self.key.display(data, f)?;
+            }
+            MappingItemKind::Complex { key, colon } => {
+                write!(f, "?")?;
+                data.raw(key).display(data, f, Some(key))?;
+
+                if let Some(colon) = colon {
+                    write!(f, "{}:", data.str(colon))?;
+                }
+            }
+        }
+
         data.raw(self.value).display(data, f, Some(self.value))?;
+
+        if let Some(suffix) = self.suffix {
+            write!(f, "{}", data.str(suffix))?;
+        }
+
+        Ok(())
+    }
+
+    fn write_to<O>(&self, data: &Data, o: &mut O, hook: &mut Hook<'_>) -> io::Result<()>
+    where
+        O: ?Sized + io::Write,
+    {
+        match self.kind {
+            MappingItemKind::Pair => {
+                self.key.write_to(data, o)?;
+                write!(o, ":")?;
+            }
+            MappingItemKind::Explicit { separator } => {
+                write!(o, "?")?;
+                o.write_all(data.str(separator))?;
+                self.key.write_to(data, o)?;
+            }
+            MappingItemKind::Key => {
+                self.key.write_to(data, o)?;
+            }
+            MappingItemKind::Complex { key, colon } => {
+                write!(o, "?")?;
+                write_prefix(data, key, o)?;
+                data.raw(key).write_to(data, o)?;
+
+                if let Some(colon) = colon {
+                    o.write_all(data.str(colon))?;
+                    write!(o, ":")?;
+                }
+            }
+        }
+
+        write_prefix(data, self.value, o)?;
+        hook.enter_key(data.str(self.key.id));
+        hook.write_value(data, self.value, o)?;
+        hook.leave();
+
+        if let Some(suffix) = self.suffix {
+            o.write_all(data.str(suffix))?;
+        }
+
         Ok(())
     }
+}
+
+/// How the key of a mapping item is written.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-edits", serde(tag = "kind"))]
+pub(crate) enum MappingItemKind {
+    /// A key followed by a colon and a value.
+    ///
+    /// ```yaml
+    /// one: 1
+    /// ```
+    #[default]
+    Pair,
+    /// An explicit key without a value, as used in block sets.
+    ///
+    /// ```yaml
+    /// ? one
+    /// ```
+    Explicit {
+        /// Whitespace following the `?` indicator.
+        separator: StringId,
+    },
+    /// A key without a value, as used in inline sets.
+    ///
+    /// ```yaml
+    /// {one, two}
+    /// ```
+    Key,
+    /// An explicit key which is a value of its own, like a collection or a
+    /// key which is followed by its value on a separate line. The whitespace
+    /// following the `?` indicator is the prefix of the key.
+    ///
+    /// ```yaml
+    /// ? - one
+    ///   - two
+    /// : value
+    /// ```
+    Complex {
+        /// The key.
+        key: Id,
+        /// Whitespace preceding the `:` indicator, or `None` if the key
+        /// doesn't have a value.
+        colon: Option<StringId>,
+    },
+}
+
+/// The tag of a value, like `!!set` or `!!omap`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+pub(crate) struct Tag {
+    /// The tag, including its leading `!` indicators.
+    pub(crate) name: StringId,
+    /// Whitespace following the tag, before the value.
+    pub(crate) suffix: StringId,
+}
+
+impl Tag {
+    fn display(&self, data: &Data, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", data.str(self.name), data.str(self.suffix))
+    }
 
     fn write_to<O>(&self, data: &Data, o: &mut O) -> io::Result<()>
     where
         O: ?Sized + io::Write,
     {
-        self.key.write_to(data, o)?;
-        write!(o, ":")?;
-        o.write_all(data.prefix(self.value))?;
-        data.raw(self.value).write_to(data, o)?;
+        o.write_all(data.str(self.name))?;
+        o.write_all(data.str(self.suffix))?;
         Ok(())
     }
 }
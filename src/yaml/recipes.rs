@@ -0,0 +1,273 @@
+//! High-level helpers for common automation tasks.
+//!
+//! Values are addressed by a path of segments from the root of a
+//! [`Document`]. A segment is used as a key when traversing a mapping, and is
+//! parsed as an index when traversing a sequence.
+//!
+//! Like all other editing performed by this crate, the helpers here only
+//! modify the values they touch and leave the formatting of the rest of the
+//! document intact.
+//!
+//! # Examples
+//!
+//! ```
+//! use nondestructive::yaml;
+//! use nondestructive::yaml::recipes;
+//!
+//! let mut doc = yaml::from_slice(
+//!     r"
+//!     spec:
+//!       replicas: 2
+//!       containers:
+//!         - image: nginx:1.25
+//!     registries: [docker.io]
+//!     "
+//! )?;
+//!
+//! assert_eq!(recipes::bump_mapping_number(&mut doc, &["spec", "replicas"], 1), Some(3));
+//! assert!(recipes::set_image_tag(&mut doc, &["spec", "containers", "0", "image"], "1.27"));
+//! assert!(recipes::append_unique_to_sequence(&mut doc, &["registries"], "ghcr.io"));
+//! assert!(!recipes::append_unique_to_sequence(&mut doc, &["registries"], "docker.io"));
+//!
+//! assert_eq!(
+//!     doc.to_string(),
+//!     r"
+//!     spec:
+//!       replicas: 3
+//!       containers:
+//!         - image: nginx:1.27
+//!     registries: [docker.io, ghcr.io]
+//!     "
+//! );
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use crate::yaml::data::Id;
+use crate::yaml::replace;
+use crate::yaml::{Document, Value, ValueMut};
+
+/// Get the value at the given `path`.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::recipes;
+///
+/// let doc = yaml::from_slice(
+///     r"
+///     servers:
+///       - name: alpha
+///         port: 8080
+///     "
+/// )?;
+///
+/// let port = recipes::get(&doc, &["servers", "0", "port"]);
+/// assert_eq!(port.and_then(|v| v.as_u32()), Some(8080));
+/// assert!(recipes::get(&doc, &["servers", "1"]).is_none());
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[must_use]
+pub fn get<'a>(doc: &'a Document, path: &[&str]) -> Option<Value<'a>> {
+    let id = find(doc, path)?;
+    Some(doc.value(id))
+}
+
+/// Get the value at the given `path` mutably.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::recipes;
+///
+/// let mut doc = yaml::from_slice("server: {port: 8080}")?;
+///
+/// recipes::get_mut(&mut doc, &["server", "port"]).context("missing port")?.set_u32(9090);
+/// assert_eq!(doc.to_string(), "server: {port: 9090}");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[must_use]
+pub fn get_mut<'a>(doc: &'a mut Document, path: &[&str]) -> Option<ValueMut<'a>> {
+    let id = find(doc, path)?;
+    Some(doc.value_mut(id))
+}
+
+/// Add `delta` to the integer at the given `path`, returning the new value.
+///
+/// Returns `None` and leaves the document unchanged if the value doesn't
+/// exist, isn't an integer, or if the addition overflows.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::recipes;
+///
+/// let mut doc = yaml::from_slice(
+///     r"
+///     version: 41
+///     name: app
+///     "
+/// )?;
+///
+/// assert_eq!(recipes::bump_mapping_number(&mut doc, &["version"], 1), Some(42));
+/// assert_eq!(recipes::bump_mapping_number(&mut doc, &["name"], 1), None);
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     r"
+///     version: 42
+///     name: app
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn bump_mapping_number(doc: &mut Document, path: &[&str], delta: i64) -> Option<i64> {
+    let mut value = get_mut(doc, path)?;
+    let number = value.as_ref().as_i64()?.checked_add(delta)?;
+    value.set_i64(number);
+    Some(number)
+}
+
+/// Append the string `value` to the sequence at the given `path` unless it's
+/// already present, returning `true` if it was appended.
+///
+/// Returns `false` if the value already exists in the sequence, or if there is
+/// no sequence at the given path.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::recipes;
+///
+/// let mut doc = yaml::from_slice(
+///     r"
+///     features:
+///       - tls
+///     "
+/// )?;
+///
+/// assert!(recipes::append_unique_to_sequence(&mut doc, &["features"], "http2"));
+/// assert!(!recipes::append_unique_to_sequence(&mut doc, &["features"], "tls"));
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     r"
+///     features:
+///       - tls
+///       - http2
+///     "
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn append_unique_to_sequence(doc: &mut Document, path: &[&str], value: &str) -> bool {
+    let Some(mut sequence) = get_mut(doc, path).and_then(ValueMut::into_sequence_mut) else {
+        return false;
+    };
+
+    if sequence
+        .as_ref()
+        .iter()
+        .any(|item| item.as_bytes() == Some(value.as_bytes()))
+    {
+        return false;
+    }
+
+    sequence.push_string(value);
+    true
+}
+
+/// Set the tag of the container image reference at the given `path`,
+/// returning `true` if it was updated.
+///
+/// The tag is the part following the last colon (`:`) after the final slash
+/// (`/`), so registry ports such as in `localhost:5000/app` are left intact. A
+/// tag is added if the reference doesn't have one, and any digest (`@...`) is
+/// removed since it would no longer match. The image keeps its quoting style.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::recipes;
+///
+/// let mut doc = yaml::from_slice(
+///     r#"
+///     app: "localhost:5000/app:1.0"
+///     db: postgres
+///     cache: redis:7@sha256:abcd
+///     "#
+/// )?;
+///
+/// assert!(recipes::set_image_tag(&mut doc, &["app"], "1.1"));
+/// assert!(recipes::set_image_tag(&mut doc, &["db"], "16"));
+/// assert!(recipes::set_image_tag(&mut doc, &["cache"], "7.2"));
+/// assert!(!recipes::set_image_tag(&mut doc, &["missing"], "1.0"));
+///
+/// assert_eq!(
+///     doc.to_string(),
+///     r#"
+///     app: "localhost:5000/app:1.1"
+///     db: postgres:16
+///     cache: redis:7.2
+///     "#
+/// );
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub fn set_image_tag(doc: &mut Document, path: &[&str], tag: &str) -> bool {
+    let Some(id) = find(doc, path) else {
+        return false;
+    };
+
+    if doc.value(id).as_bytes().is_none() {
+        return false;
+    }
+
+    let replaced = replace::replace(&mut doc.data, id, |image| {
+        let image = match image.iter().position(|&b| b == b'@') {
+            Some(n) => image.get(..n).unwrap_or_default(),
+            None => image,
+        };
+
+        let name_start = image
+            .iter()
+            .rposition(|&b| b == b'/')
+            .map_or(0, |n| n.wrapping_add(1));
+
+        let name = image.get(name_start..).unwrap_or_default();
+
+        let name = match name.iter().position(|&b| b == b':') {
+            Some(n) => image.get(..name_start.wrapping_add(n)).unwrap_or_default(),
+            None => image,
+        };
+
+        Some([name, b":", tag.as_bytes()].concat())
+    });
+
+    matches!(replaced, Ok(1))
+}
+
+/// Find the identifier of the value at the given path.
+pub(crate) fn find(doc: &Document, path: &[&str]) -> Option<Id> {
+    find_from(doc, doc.root, path)
+}
+
+/// Find the identifier of the value at the given path relative to `id`.
+pub(crate) fn find_from(doc: &Document, mut id: Id, path: &[&str]) -> Option<Id> {
+    for segment in path {
+        let value = doc.value(id);
+
+        id = if let Some(mapping) = value.as_mapping() {
+            mapping.get(segment)?.id()
+        } else if let Some(sequence) = value.as_sequence() {
+            sequence.get(segment.parse().ok()?)?.id()
+        } else {
+            return None;
+        };
+    }
+
+    Some(id)
+}
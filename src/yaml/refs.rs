@@ -0,0 +1,270 @@
+//! Opt-in resolution of references between documents.
+//!
+//! A reference is a mapping with a `$ref` key whose value is a string such as
+//! `other.yaml#/path/to/value`. The part before the `#` names a document which
+//! is loaded through a user-provided callback, and the part after it is a
+//! [JSON pointer] into that document. If the name is empty the reference
+//! points into the document containing it, and if the pointer is empty the
+//! reference points to the root of the named document. Any other keys in a
+//! mapping containing a reference are ignored. The key used to recognize
+//! references can be changed with [`Resolver::with_key`].
+//!
+//! References are followed transparently while looking up values, and values
+//! are always read from and written to the document in which they are defined.
+//! Documents are never modified by resolving references, so each one can be
+//! written back to where it was loaded from.
+//!
+//! [JSON pointer]: https://www.rfc-editor.org/rfc/rfc6901
+//!
+//! # Examples
+//!
+//! ```
+//! use anyhow::Context;
+//! use nondestructive::yaml;
+//! use nondestructive::yaml::refs::Resolver;
+//!
+//! let root = yaml::from_slice(
+//!     r"
+//!     name: app
+//!     database:
+//!       $ref: common.yaml#/databases/primary
+//!     "
+//! )?;
+//!
+//! let mut resolver = Resolver::new("app.yaml", root, |name| match name {
+//!     "common.yaml" => yaml::from_slice("databases:\n  primary: {host: db, port: 5432}\n").ok(),
+//!     _ => None,
+//! });
+//!
+//! let port = resolver.get(&["database", "port"]).context("missing port")?;
+//! assert_eq!(port.as_u32(), Some(5432));
+//!
+//! resolver.get_mut(&["database", "port"]).context("missing port")?.set_u32(6432);
+//!
+//! let common = resolver.document("common.yaml").context("missing document")?;
+//! assert_eq!(common.to_string(), "databases:\n  primary: {host: db, port: 6432}\n");
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use std::fmt;
+
+use bstr::ByteSlice;
+
+use crate::yaml::data::Id;
+use crate::yaml::pointer;
+use crate::yaml::{Document, Value, ValueMut};
+
+/// The default key used to recognize references.
+const DEFAULT_KEY: &str = "$ref";
+
+/// The maximum number of nested references followed, which protects against
+/// cyclical references.
+const MAX_DEPTH: usize = 64;
+
+/// Resolves references across a collection of documents which are loaded on
+/// demand.
+///
+/// See the [module-level documentation][self] for more information.
+pub struct Resolver<F> {
+    key: String,
+    documents: Vec<(String, Document)>,
+    load: F,
+}
+
+impl<F> Resolver<F>
+where
+    F: FnMut(&str) -> Option<Document>,
+{
+    /// Construct a new resolver with the root document `root` named `name`.
+    ///
+    /// The `load` callback is called with the name of any other document the
+    /// first time it is referenced, and should return `None` if it can't be
+    /// loaded.
+    pub fn new(name: &str, root: Document, load: F) -> Self {
+        Self {
+            key: DEFAULT_KEY.to_owned(),
+            documents: vec![(name.to_owned(), root)],
+            load,
+        }
+    }
+
+    /// Use the given `key` to recognize references instead of `$ref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::refs::Resolver;
+    ///
+    /// let root = yaml::from_slice("defaults: {include: '#/base'}\nbase: {retries: 3}")?;
+    /// let mut resolver = Resolver::new("root.yaml", root, |_| None).with_key("include");
+    ///
+    /// let retries = resolver.get(&["defaults", "retries"]).and_then(|v| v.as_u32());
+    /// assert_eq!(retries, Some(3));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = key.to_owned();
+        self
+    }
+
+    /// Resolve the value at the given `path`, returning the name of the
+    /// document it is defined in and its identifier in that document.
+    ///
+    /// A segment is used as a key when traversing a mapping, and is parsed as
+    /// an index when traversing a sequence. Returns `None` if the value doesn't
+    /// exist, if a referenced document can't be loaded, or if references are
+    /// cyclical.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::refs::Resolver;
+    ///
+    /// let root = yaml::from_slice("service: {$ref: 'service.yaml'}")?;
+    ///
+    /// let mut resolver = Resolver::new("root.yaml", root, |name| {
+    ///     yaml::from_slice(format!("name: {name}")).ok()
+    /// });
+    ///
+    /// let (name, id) = resolver.resolve(&["service", "name"]).context("missing name")?;
+    /// assert_eq!(name, "service.yaml");
+    ///
+    /// let doc = resolver.document("service.yaml").context("missing document")?;
+    /// assert_eq!(doc.value(id).as_str(), Some("service.yaml"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn resolve(&mut self, path: &[&str]) -> Option<(&str, Id)> {
+        let root = self.documents.first()?.1.root;
+        let (document, id) = self.walk(0, root, path.iter().copied(), 0)?;
+        Some((self.documents.get(document)?.0.as_str(), id))
+    }
+
+    /// Get the value at the given `path`, following references.
+    ///
+    /// See [`Resolver::resolve`] for how paths are resolved.
+    pub fn get(&mut self, path: &[&str]) -> Option<Value<'_>> {
+        let root = self.documents.first()?.1.root;
+        let (document, id) = self.walk(0, root, path.iter().copied(), 0)?;
+        Some(self.documents.get(document)?.1.value(id))
+    }
+
+    /// Get the value at the given `path` mutably, following references.
+    ///
+    /// Modifications are made to the document in which the value is defined.
+    /// See [`Resolver::resolve`] for how paths are resolved.
+    pub fn get_mut(&mut self, path: &[&str]) -> Option<ValueMut<'_>> {
+        let root = self.documents.first()?.1.root;
+        let (document, id) = self.walk(0, root, path.iter().copied(), 0)?;
+        Some(self.documents.get_mut(document)?.1.value_mut(id))
+    }
+
+    /// Get a document which has been loaded by name.
+    #[must_use]
+    pub fn document(&self, name: &str) -> Option<&Document> {
+        let (_, document) = self.documents.iter().find(|(n, _)| n == name)?;
+        Some(document)
+    }
+
+    /// Iterate over all loaded documents and their names, starting with the
+    /// root document.
+    pub fn documents(&self) -> impl Iterator<Item = (&str, &Document)> {
+        self.documents
+            .iter()
+            .map(|(name, document)| (name.as_str(), document))
+    }
+
+    /// Convert into all loaded documents and their names, starting with the
+    /// root document.
+    #[must_use]
+    pub fn into_documents(self) -> Vec<(String, Document)> {
+        self.documents
+    }
+
+    /// Walk the given segments starting at `id`, following references along
+    /// the way.
+    fn walk<'s, I>(
+        &mut self,
+        mut document: usize,
+        mut id: Id,
+        segments: I,
+        depth: usize,
+    ) -> Option<(usize, Id)>
+    where
+        I: IntoIterator<Item = &'s str>,
+    {
+        for segment in segments {
+            (document, id) = self.follow(document, id, depth)?;
+            id = pointer::child(&self.documents.get(document)?.1, id, segment)?;
+        }
+
+        self.follow(document, id, depth)
+    }
+
+    /// Follow any references starting at the given value.
+    fn follow(&mut self, mut document: usize, mut id: Id, depth: usize) -> Option<(usize, Id)> {
+        let mut depth = depth;
+
+        while let Some(reference) = self.reference(document, id) {
+            depth += 1;
+
+            if depth > MAX_DEPTH {
+                return None;
+            }
+
+            let (name, pointer) = match reference.split_once('#') {
+                Some((name, pointer)) => (name, pointer),
+                None => (reference.as_str(), ""),
+            };
+
+            if !name.is_empty() {
+                document = self.load(name)?;
+            }
+
+            let root = self.documents.get(document)?.1.root;
+
+            let segments = pointer
+                .split('/')
+                .skip(1)
+                .map(|s| pointer::unescape(s).into_owned())
+                .collect::<Vec<_>>();
+
+            (document, id) =
+                self.walk(document, root, segments.iter().map(String::as_str), depth)?;
+        }
+
+        Some((document, id))
+    }
+
+    /// Get the reference stored in the given value, if it is a reference.
+    fn reference(&self, document: usize, id: Id) -> Option<String> {
+        let value = self.documents.get(document)?.1.value(id);
+        let reference = value.as_mapping()?.get(&self.key)?.as_bytes()?;
+        Some(reference.to_str_lossy().into_owned())
+    }
+
+    /// Load the document with the given name unless it's already loaded,
+    /// returning its index.
+    fn load(&mut self, name: &str) -> Option<usize> {
+        if let Some(index) = self.documents.iter().position(|(n, _)| n == name) {
+            return Some(index);
+        }
+
+        let document = (self.load)(name)?;
+        self.documents.push((name.to_owned(), document));
+        Some(self.documents.len() - 1)
+    }
+}
+
+impl<F> fmt::Debug for Resolver<F> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resolver")
+            .field("key", &self.key)
+            .field("documents", &self.documents)
+            .finish_non_exhaustive()
+    }
+}
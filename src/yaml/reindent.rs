@@ -0,0 +1,238 @@
+use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::raw::{self, Raw, RawStringKind};
+
+/// Widths used when reindenting.
+#[derive(Clone, Copy)]
+pub(crate) struct Widths {
+    from: usize,
+    to: usize,
+}
+
+impl Widths {
+    pub(crate) fn new(from: usize, to: usize) -> Self {
+        Self { from, to }
+    }
+
+    /// Map an indentation of `n` spaces, keeping any indentation which isn't
+    /// a whole level.
+    fn map(self, n: usize) -> usize {
+        (n / self.from)
+            .saturating_mul(self.to)
+            .saturating_add(n % self.from)
+    }
+}
+
+/// Reindent the value `id` and everything it contains.
+///
+/// `dash` is the column of the dash of the block sequence item the value
+/// belongs to, if any.
+pub(crate) fn value(data: &mut Data, id: Id, dash: Option<usize>, widths: Widths) {
+    let prefix = data.layout(id).prefix;
+    let anchor = data.layout(id).anchor;
+    let tag = data.layout(id).tag;
+
+    // A block collection which starts on the same line as the dash of its
+    // sequence item is indented by the spacing following the dash.
+    let compact = match (data.raw(id), dash) {
+        (Raw::Mapping(raw), Some(dash)) if matches!(raw.kind, raw::MappingKind::Mapping) => {
+            Some((raw.indent, dash))
+        }
+        (Raw::Sequence(raw), Some(dash)) if matches!(raw.kind, raw::SequenceKind::Mapping) => {
+            Some((raw.indent, dash))
+        }
+        _ => None,
+    };
+
+    let newline = data.str(prefix).contains(&raw::NEWLINE)
+        || anchor.is_some_and(|anchor| data.str(anchor.suffix).contains(&raw::NEWLINE));
+
+    match compact {
+        Some((indent, dash)) if !newline => {
+            let n = widths
+                .map(indent)
+                .saturating_sub(widths.map(dash))
+                .saturating_sub(1)
+                .max(1);
+            let prefix = data.insert_str(" ".repeat(n));
+            data.set_prefix(id, prefix);
+        }
+        _ => {
+            let prefix = lines(data, prefix, widths);
+            data.set_prefix(id, prefix);
+        }
+    }
+
+    if let Some(anchor) = anchor {
+        let suffix = lines(data, anchor.suffix, widths);
+        data.set_anchor(id, Some(raw::Anchor { suffix, ..anchor }));
+    }
+
+    if let Some(tag) = tag {
+        let suffix = lines(data, tag.suffix, widths);
+        data.set_tag(id, Some(raw::Tag { suffix, ..tag }));
+    }
+
+    match data.raw(id) {
+        Raw::Mapping(raw) => {
+            let items = raw.items.clone();
+
+            for item in items {
+                let prefix = lines(data, data.layout(item).prefix, widths);
+                data.set_prefix(item, prefix);
+
+                let (value, suffix) = {
+                    let item = data.mapping_item(item);
+                    (item.value, item.suffix)
+                };
+
+                let suffix = suffix.map(|suffix| lines(data, suffix, widths));
+                data.mapping_item_mut(item).suffix = suffix;
+                self::value(data, value, None, widths);
+            }
+
+            let (kind, tag) = {
+                let raw = data.mapping(id);
+                (raw.kind.clone(), raw.tag.as_ref().map(|tag| tag.suffix))
+            };
+
+            let kind = match kind {
+                raw::MappingKind::Inline { trailing, suffix } => raw::MappingKind::Inline {
+                    trailing,
+                    suffix: lines(data, suffix, widths),
+                },
+                kind => kind,
+            };
+
+            let tag = tag.map(|suffix| lines(data, suffix, widths));
+            let raw = data.mapping_mut(id);
+            raw.indent = widths.map(raw.indent);
+            raw.kind = kind;
+
+            if let (Some(tag), Some(suffix)) = (&mut raw.tag, tag) {
+                tag.suffix = suffix;
+            }
+        }
+        Raw::Sequence(raw) => {
+            let items = raw.items.clone();
+            let dash = matches!(raw.kind, raw::SequenceKind::Mapping).then_some(raw.indent);
+
+            for item in items {
+                let prefix = lines(data, data.layout(item).prefix, widths);
+                data.set_prefix(item, prefix);
+
+                let (value, suffix) = {
+                    let item = data.sequence_item(item);
+                    (item.value, item.suffix)
+                };
+
+                let suffix = suffix.map(|suffix| lines(data, suffix, widths));
+                data.sequence_item_mut(item).suffix = suffix;
+                self::value(data, value, dash, widths);
+            }
+
+            let (kind, tag) = {
+                let raw = data.sequence(id);
+                (raw.kind.clone(), raw.tag.as_ref().map(|tag| tag.suffix))
+            };
+
+            let kind = match kind {
+                raw::SequenceKind::Inline { trailing, suffix } => raw::SequenceKind::Inline {
+                    trailing,
+                    suffix: lines(data, suffix, widths),
+                },
+                kind => kind,
+            };
+
+            let tag = tag.map(|suffix| lines(data, suffix, widths));
+            let raw = data.sequence_mut(id);
+            raw.indent = widths.map(raw.indent);
+            raw.kind = kind;
+
+            if let (Some(tag), Some(suffix)) = (&mut raw.tag, tag) {
+                tag.suffix = suffix;
+            }
+        }
+        Raw::String(string) => {
+            let string = string.clone();
+
+            if let Some(original) = multiline(data, &string, widths) {
+                let original = data.insert_str(original);
+                data.replace(
+                    id,
+                    Raw::String(raw::String::new(string.kind, string.id, original)),
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Map the indentation of every line following a newline in `string`.
+pub(crate) fn lines(data: &mut Data, string: StringId, widths: Widths) -> StringId {
+    let input = data.str(string);
+
+    if !input.contains(&raw::NEWLINE) {
+        return string;
+    }
+
+    let mut out = Vec::with_capacity(input.len());
+    let mut it = input.split(|&b| b == raw::NEWLINE);
+
+    if let Some(first) = it.next() {
+        out.extend_from_slice(first);
+    }
+
+    for line in it {
+        let n = spaces(line);
+        out.push(raw::NEWLINE);
+        out.resize(out.len() + widths.map(n), raw::SPACE);
+        out.extend_from_slice(line.get(n..).unwrap_or_default());
+    }
+
+    data.insert_str(out)
+}
+
+/// Shift the continuation lines of a string which spans multiple lines,
+/// keeping the indentation of lines relative to each other so that the value
+/// of the string is unchanged.
+fn multiline(data: &Data, string: &raw::String, widths: Widths) -> Option<Vec<u8>> {
+    if !matches!(
+        string.kind,
+        RawStringKind::Original | RawStringKind::Multiline { .. }
+    ) {
+        return None;
+    }
+
+    let original = data.str(string.original);
+    let (head, rest) = original.split_at(memchr::memchr(raw::NEWLINE, original)?);
+
+    let base = rest
+        .split(|&b| b == raw::NEWLINE)
+        .filter(|line| line.iter().any(|b| !b.is_ascii_whitespace()))
+        .map(spaces)
+        .min()?;
+
+    let new = widths.map(base);
+
+    let mut out = head.to_vec();
+
+    for line in rest.split(|&b| b == raw::NEWLINE).skip(1) {
+        let n = spaces(line);
+        out.push(raw::NEWLINE);
+
+        if n >= base {
+            out.resize(out.len() + new, raw::SPACE);
+            out.extend_from_slice(line.get(base..).unwrap_or_default());
+        } else {
+            out.resize(out.len() + n.min(new), raw::SPACE);
+            out.extend_from_slice(line.get(n..).unwrap_or_default());
+        }
+    }
+
+    Some(out)
+}
+
+/// Count the leading spaces of a line.
+fn spaces(line: &[u8]) -> usize {
+    line.iter().take_while(|&&b| b == raw::SPACE).count()
+}
@@ -0,0 +1,155 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+
+/// The result of reloading a [`Document`] through [`Document::reload_from`].
+///
+/// This maps identifiers which were valid in the document before it was
+/// reloaded to the structurally matching value in the reloaded document, if
+/// one exists.
+///
+/// Values are matched by their path from the root of the document, so a value
+/// survives a reload if the same sequence of mapping keys and sequence indexes
+/// still leads to a value in the new document.
+///
+/// [`Document`]: crate::yaml::Document
+/// [`Document::reload_from`]: crate::yaml::Document::reload_from
+#[derive(Debug, Clone)]
+pub struct Reload {
+    /// Remapped identifiers, sorted by the old identifier.
+    ids: Vec<(Id, Option<Id>)>,
+}
+
+impl Reload {
+    /// Construct a reload report by matching `old` with `new`.
+    pub(crate) fn new(old: &Data, old_root: Id, new: &Data, new_root: Id) -> Self {
+        let mut ids = Vec::new();
+        remap(old, old_root, Some((new, new_root)), &mut ids);
+        ids.sort_by_key(|&(id, _)| id);
+        Self { ids }
+    }
+
+    /// Get the identifier in the reloaded document which corresponds to the
+    /// given identifier in the document before it was reloaded.
+    ///
+    /// Returns `None` if the value did not survive the reload, or if the
+    /// identifier was not valid before the reload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     first: 32
+    ///     second: [1, 2, 3]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let first = root.get("first").context("missing first")?.id();
+    /// let second = root.get("second").context("missing second")?.id();
+    ///
+    /// let reload = doc.reload_from("second: [4, 5]")?;
+    ///
+    /// assert_eq!(reload.get(first), None);
+    /// let second = reload.get(second).context("second did not survive")?;
+    /// assert_eq!(doc.value(second).to_string(), "[4, 5]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get(&self, id: Id) -> Option<Id> {
+        let index = self.ids.binary_search_by_key(&id, |&(id, _)| id).ok()?;
+        self.ids.get(index)?.1
+    }
+
+    /// Iterate over all identifiers which survived the reload, as pairs of the
+    /// old and the new identifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[1, 2, 3]")?;
+    /// let reload = doc.reload_from("[1, 2]")?;
+    ///
+    /// // The root sequence and its first two elements.
+    /// assert_eq!(reload.survived().count(), 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn survived(&self) -> impl Iterator<Item = (Id, Id)> + '_ {
+        self.ids.iter().filter_map(|&(old, new)| Some((old, new?)))
+    }
+
+    /// Iterate over all identifiers which did not survive the reload.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[1, 2, 3]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root")?;
+    /// let third = root.get(2).context("missing third")?.id();
+    ///
+    /// let reload = doc.reload_from("[1, 2]")?;
+    /// assert!(reload.lost().eq([third]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn lost(&self) -> impl Iterator<Item = Id> + '_ {
+        self.ids
+            .iter()
+            .filter_map(|&(old, new)| new.is_none().then_some(old))
+    }
+}
+
+/// Recursively match the value `id` in `old` to `new`.
+fn remap(old: &Data, id: Id, new: Option<(&Data, Id)>, out: &mut Vec<(Id, Option<Id>)>) {
+    out.push((id, new.map(|(_, id)| id)));
+
+    match old.raw(id) {
+        Raw::Mapping(raw) => {
+            for item in &raw.items {
+                let item = old.mapping_item(*item);
+                let key = old.str(item.key.id);
+
+                let matched = new.and_then(|(data, id)| {
+                    let Raw::Mapping(raw) = data.raw(id) else {
+                        return None;
+                    };
+
+                    let value = raw
+                        .items
+                        .iter()
+                        .map(|item| data.mapping_item(*item))
+                        .find(|item| data.str(item.key.id) == key)?
+                        .value;
+
+                    Some((data, value))
+                });
+
+                remap(old, item.value, matched, out);
+            }
+        }
+        Raw::Sequence(raw) => {
+            for (index, item) in raw.items.iter().enumerate() {
+                let item = old.sequence_item(*item);
+
+                let matched = new.and_then(|(data, id)| {
+                    let Raw::Sequence(raw) = data.raw(id) else {
+                        return None;
+                    };
+
+                    let item = raw.items.get(index)?;
+                    Some((data, data.sequence_item(*item).value))
+                });
+
+                remap(old, item.value, matched, out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,133 @@
+use bstr::ByteSlice;
+
+use crate::yaml::clear;
+use crate::yaml::data::{Data, Id};
+use crate::yaml::error::Error;
+use crate::yaml::lock;
+use crate::yaml::parsing::{Options, Parser};
+use crate::yaml::raw::{self, Raw, RawStringKind};
+
+/// Replace string values inside of the given value using `f`, returning the
+/// number of strings which were modified.
+///
+/// Nothing is replaced if any of the strings which would be modified is
+/// locked.
+pub(crate) fn replace<F>(data: &mut Data, id: Id, mut f: F) -> Result<usize, Error>
+where
+    F: FnMut(&[u8]) -> Option<Vec<u8>>,
+{
+    let mut strings = Vec::new();
+    collect(data, id, &mut strings);
+
+    let mut replacements = Vec::new();
+
+    for id in strings {
+        let Raw::String(string) = data.raw(id) else {
+            continue;
+        };
+
+        let Some(kind) = kind(data, string) else {
+            continue;
+        };
+
+        let old = data.str(string.id);
+
+        let Some(new) = f(old) else {
+            continue;
+        };
+
+        // A bare string stays bare as long as the replacement is read back as
+        // the same string, so that `1.0.x` can become `1.0.y` but not `1.0`.
+        let kind = match kind {
+            RawStringKind::Bare => {
+                let flow = clear::in_flow(data, id);
+
+                if is_plain(data.options(), &new, flow) {
+                    RawStringKind::Bare
+                } else {
+                    RawStringKind::detect(&new.to_str_lossy(), flow)
+                }
+            }
+            kind => kind,
+        };
+
+        lock::check(data, id)?;
+        replacements.push((id, kind, new));
+    }
+
+    let replaced = replacements.len();
+
+    for (id, kind, new) in replacements {
+        let new = data.insert_str(new);
+        data.replace(id, Raw::String(raw::String::new(kind, new, new)));
+    }
+
+    Ok(replaced)
+}
+
+/// Test if `string` is read back as the same string when it's written without
+/// quotes, using the given parser `options`.
+///
+/// If `flow` is set, the string is written inside of an inline collection.
+fn is_plain(options: Options, string: &[u8], flow: bool) -> bool {
+    if string.is_empty() {
+        return false;
+    }
+
+    if flow
+        && string
+            .iter()
+            .any(|b| matches!(b, b',' | b'[' | b']' | b'{' | b'}'))
+    {
+        return false;
+    }
+
+    let Ok(doc) = Parser::new(string).with_options(options).parse() else {
+        return false;
+    };
+
+    match doc.data.raw(doc.root) {
+        Raw::String(raw) => {
+            matches!(raw.kind, RawStringKind::Bare) && doc.data.str(raw.id) == string
+        }
+        _ => false,
+    }
+}
+
+/// Collect all string values reachable from the given value.
+pub(crate) fn collect(data: &Data, id: Id, out: &mut Vec<Id>) {
+    match data.raw(id) {
+        Raw::String(..) => {
+            out.push(id);
+        }
+        Raw::Mapping(raw) => {
+            for item in &raw.items {
+                collect(data, data.mapping_item(*item).value, out);
+            }
+        }
+        Raw::Sequence(raw) => {
+            for item in &raw.items {
+                collect(data, data.sequence_item(*item).value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Determine the kind to use for a replaced string, so that it retains the
+/// quoting style of the original string.
+///
+/// Block strings are not supported and return `None`.
+pub(crate) fn kind(data: &Data, string: &raw::String) -> Option<RawStringKind> {
+    match &string.kind {
+        RawStringKind::Bare => Some(RawStringKind::Bare),
+        RawStringKind::Single => Some(RawStringKind::Single),
+        RawStringKind::Double => Some(RawStringKind::Double),
+        RawStringKind::Original => match data.str(string.original).first() {
+            Some(b'\'') => Some(RawStringKind::Single),
+            Some(b'"') => Some(RawStringKind::Double),
+            _ => Some(RawStringKind::Bare),
+        },
+        RawStringKind::Multiline { .. } => None,
+    }
+}
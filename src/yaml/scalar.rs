@@ -0,0 +1,229 @@
+use std::borrow::Cow;
+
+use crate::yaml::data::Data;
+use crate::yaml::number;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::serde_hint::{self, RawNumberHint};
+use crate::yaml::Null;
+
+macro_rules! from_float {
+    ($ty:ty, $hint:ident) => {
+        impl From<$ty> for Scalar<'_> {
+            #[inline]
+            fn from(value: $ty) -> Self {
+                let mut buffer = ryu::Buffer::new();
+                let string = number::format_float(&mut buffer, value).to_owned();
+                Self::new(Kind::Number(string, serde_hint::$hint))
+            }
+        }
+    };
+}
+
+macro_rules! from_number {
+    ($ty:ty, $hint:ident) => {
+        impl From<$ty> for Scalar<'_> {
+            #[inline]
+            fn from(value: $ty) -> Self {
+                let mut buffer = itoa::Buffer::new();
+                let string = buffer.format(value).to_owned();
+                Self::new(Kind::Number(string, serde_hint::$hint))
+            }
+        }
+    };
+}
+
+/// A scalar value, which can be constructed from Rust values through [`From`].
+///
+/// This is accepted by [`SequenceMut::push_value`], [`MappingMut::insert_value`]
+/// and [`ValueMut::set_value`], which are useful in generic code and macros
+/// where calling the method specific to a type is inconvenient.
+///
+/// [`SequenceMut::push_value`]: crate::yaml::SequenceMut::push_value
+/// [`MappingMut::insert_value`]: crate::yaml::MappingMut::insert_value
+/// [`ValueMut::set_value`]: crate::yaml::ValueMut::set_value
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("[]")?;
+/// let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+///
+/// let values: [yaml::Scalar<'_>; 5] = [
+///     42u32.into(),
+///     (-1.5f64).into(),
+///     true.into(),
+///     "hello".into(),
+///     yaml::Null::Tilde.into(),
+/// ];
+///
+/// for value in values {
+///     root.push_value(value);
+/// }
+///
+/// assert_eq!(doc.to_string(), "[42, -1.5, true, hello, ~]");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scalar<'a> {
+    kind: Kind<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum Kind<'a> {
+    Null(Null),
+    Bool(bool),
+    Number(std::string::String, RawNumberHint),
+    String(Cow<'a, str>),
+}
+
+impl<'a> Scalar<'a> {
+    #[inline]
+    const fn new(kind: Kind<'a>) -> Self {
+        Self { kind }
+    }
+
+    /// Convert the scalar into a raw value, which is written inside of an
+    /// inline collection if `flow` is set.
+    pub(crate) fn into_raw(self, data: &mut Data, flow: bool) -> Raw {
+        match self.kind {
+            Kind::Null(Null::Keyword) => Raw::Null(raw::Null::Keyword(data.insert_str("null"))),
+            Kind::Null(Null::Tilde) => Raw::Null(raw::Null::Tilde),
+            Kind::Null(Null::Empty) => Raw::Null(raw::Null::Empty),
+            Kind::Bool(value) => raw::new_bool(data, value),
+            Kind::Number(string, hint) => {
+                let string = data.insert_str(string);
+                Raw::Number(raw::Number::new(string, hint))
+            }
+            Kind::String(string) => raw::new_string(data, string, flow),
+        }
+    }
+}
+
+/// A scalar value which has been copied out of a document, as returned by
+/// [`Value::to_owned_scalar`].
+///
+/// Unlike [`Value`], this doesn't borrow from the document, so it can be
+/// returned to callers after the document has been dropped.
+///
+/// [`Value`]: crate::yaml::Value
+/// [`Value::to_owned_scalar`]: crate::yaml::Value::to_owned_scalar
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::OwnedScalar;
+///
+/// fn image(input: &[u8]) -> anyhow::Result<Option<OwnedScalar>> {
+///     let doc = yaml::from_slice(input)?;
+///     let root = doc.as_ref().as_mapping().context("missing root")?;
+///     Ok(root.get("image").and_then(|v| v.to_owned_scalar()))
+/// }
+///
+/// assert_eq!(image(b"image: app")?, Some(OwnedScalar::String(String::from("app"))));
+/// assert_eq!(image(b"image: caf\xe9")?, Some(OwnedScalar::Bytes(b"caf\xe9".to_vec())));
+/// assert_eq!(image(b"image: {name: app}")?, None);
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum OwnedScalar {
+    /// A string.
+    String(std::string::String),
+    /// A string which is not valid UTF-8.
+    Bytes(Vec<u8>),
+    /// A boolean.
+    Bool(bool),
+    /// A negative integer.
+    Signed(i128),
+    /// A non-negative integer.
+    Unsigned(u128),
+    /// A floating point number, or an integer which doesn't fit in 128 bits.
+    Float(f64),
+    /// A null value, like `null`, `~` or an empty value.
+    Null,
+}
+
+impl OwnedScalar {
+    /// Construct a scalar from the text of a number.
+    ///
+    /// Numbers which can't be parsed are kept as strings.
+    pub(crate) fn from_number(string: &[u8]) -> Self {
+        if let Some(value) = number::parse::<u128>(string) {
+            Self::Unsigned(value)
+        } else if let Some(value) = number::parse::<i128>(string) {
+            Self::Signed(value)
+        } else if let Some(value) = number::parse::<f64>(string) {
+            Self::Float(value)
+        } else {
+            Self::from_bytes(string)
+        }
+    }
+
+    /// Construct a scalar from a string which might not be valid UTF-8.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(string) => Self::String(string.to_owned()),
+            Err(..) => Self::Bytes(bytes.to_vec()),
+        }
+    }
+}
+
+impl From<Null> for Scalar<'_> {
+    #[inline]
+    fn from(value: Null) -> Self {
+        Self::new(Kind::Null(value))
+    }
+}
+
+impl From<bool> for Scalar<'_> {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::new(Kind::Bool(value))
+    }
+}
+
+impl<'a> From<&'a str> for Scalar<'a> {
+    #[inline]
+    fn from(value: &'a str) -> Self {
+        Self::new(Kind::String(Cow::Borrowed(value)))
+    }
+}
+
+impl<'a> From<&'a std::string::String> for Scalar<'a> {
+    #[inline]
+    fn from(value: &'a std::string::String) -> Self {
+        Self::new(Kind::String(Cow::Borrowed(value)))
+    }
+}
+
+impl From<std::string::String> for Scalar<'_> {
+    #[inline]
+    fn from(value: std::string::String) -> Self {
+        Self::new(Kind::String(Cow::Owned(value)))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Scalar<'a> {
+    #[inline]
+    fn from(value: Cow<'a, str>) -> Self {
+        Self::new(Kind::String(value))
+    }
+}
+
+from_float!(f32, F32);
+from_float!(f64, F64);
+from_number!(u8, U8);
+from_number!(i8, I8);
+from_number!(u16, U16);
+from_number!(i16, I16);
+from_number!(u32, U32);
+from_number!(i32, I32);
+from_number!(u64, U64);
+from_number!(i64, I64);
+from_number!(u128, U128);
+from_number!(i128, I128);
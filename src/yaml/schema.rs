@@ -0,0 +1,246 @@
+#[cfg(feature = "serde-edits")]
+use serde::{Deserialize, Serialize};
+
+use crate::yaml::serde_hint::{self, RawNumberHint};
+
+/// The schema used to resolve plain scalars, set through [`Parser::schema`].
+///
+/// [`Parser::schema`]: crate::yaml::Parser::schema
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-edits", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Schema {
+    /// A lenient take on the YAML 1.2 core schema, where only `true` and
+    /// `false` are booleans.
+    ///
+    /// Booleans and nulls are matched without regard to case, so `tRuE` and
+    /// `NuLL` are also recognized. Numbers are limited to their plain decimal
+    /// forms, so a leading `+`, an exponent with a sign like `1e-3`, and
+    /// octal and hexadecimal integers like `0o17` and `0x1f` are strings. Use
+    /// [`Schema::Core`] to resolve plain scalars exactly as the YAML 1.2 core
+    /// schema specifies.
+    #[default]
+    Yaml12,
+    /// The YAML 1.1 schema, where `yes`, `no`, `on` and `off` are also
+    /// booleans.
+    ///
+    /// Like `true` and `false`, these are matched without regard to case. The
+    /// single letter forms `y` and `n` are treated as strings, since they are
+    /// more commonly intended as such.
+    ///
+    /// The digits of numbers can also be separated by underscores, like
+    /// `1_000_000`. The underscores are preserved unless the number is
+    /// modified.
+    Yaml11,
+    /// The YAML 1.2 core schema, where plain scalars are resolved exactly as
+    /// the schema specifies.
+    ///
+    /// Unlike [`Schema::Yaml12`], booleans and nulls are only recognized in
+    /// their lowercase, capitalized and uppercase spellings, like `true`,
+    /// `True` and `TRUE`. Numbers can have a leading `+` sign, an exponent with
+    /// a sign like `1e-3`, and integers can be written in octal and
+    /// hexadecimal, like `0o17` and `0x1f`. Anything else, like `1.6.2` or
+    /// `.`, is a string.
+    Core,
+    /// The YAML JSON schema, where only `true`, `false`, `null` and numbers
+    /// spelled like in JSON are resolved as anything other than strings.
+    ///
+    /// So `~`, `True` and `0x1f` are all strings. The original text of scalars
+    /// is preserved when the document is written.
+    Json,
+}
+
+impl Schema {
+    /// Resolve a plain scalar as a boolean.
+    pub(crate) fn boolean(self, string: &[u8]) -> Option<bool> {
+        if self == Schema::Json {
+            return match string {
+                b"true" => Some(true),
+                b"false" => Some(false),
+                _ => None,
+            };
+        }
+
+        if self == Schema::Core {
+            return match string {
+                b"true" | b"True" | b"TRUE" => Some(true),
+                b"false" | b"False" | b"FALSE" => Some(false),
+                _ => None,
+            };
+        }
+
+        if string.eq_ignore_ascii_case(b"true") {
+            return Some(true);
+        }
+
+        if string.eq_ignore_ascii_case(b"false") {
+            return Some(false);
+        }
+
+        if self != Schema::Yaml11 {
+            return None;
+        }
+
+        if string.eq_ignore_ascii_case(b"yes") || string.eq_ignore_ascii_case(b"on") {
+            return Some(true);
+        }
+
+        if string.eq_ignore_ascii_case(b"no") || string.eq_ignore_ascii_case(b"off") {
+            return Some(false);
+        }
+
+        None
+    }
+
+    /// Test if `~` resolves as a null.
+    pub(crate) fn is_tilde_null(self) -> bool {
+        self != Schema::Json
+    }
+
+    /// Test if a plain scalar other than `~` resolves as a null.
+    pub(crate) fn is_null(self, string: &[u8]) -> bool {
+        match self {
+            Schema::Core => matches!(string, b"null" | b"Null" | b"NULL"),
+            Schema::Json => string == b"null",
+            _ => string.eq_ignore_ascii_case(b"null"),
+        }
+    }
+
+    /// Test if numbers are resolved from whole plain scalars through
+    /// [`Schema::number`], rather than while they're being parsed.
+    pub(crate) fn is_strict(self) -> bool {
+        matches!(self, Schema::Core | Schema::Json)
+    }
+
+    /// Resolve a plain scalar as a number, returning the hint of its type.
+    pub(crate) fn number(self, string: &[u8]) -> Option<RawNumberHint> {
+        match self {
+            Schema::Core => core_number(string),
+            Schema::Json => json_number(string),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve a number according to the YAML JSON schema, which are numbers
+/// spelled like `-12`, `0.5` or `1e10`.
+fn json_number(string: &[u8]) -> Option<RawNumberHint> {
+    let digits = |s: &[u8]| s.iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let (negative, rest) = match string {
+        [b'-', rest @ ..] => (true, rest),
+        rest => (false, rest),
+    };
+
+    let int = match rest {
+        [b'0', ..] => 1,
+        [b'1'..=b'9', ..] => digits(rest),
+        _ => return None,
+    };
+
+    let mut rest = rest.get(int..).unwrap_or_default();
+
+    if rest.is_empty() {
+        return Some(if negative {
+            serde_hint::I64
+        } else {
+            serde_hint::U64
+        });
+    }
+
+    if let Some(tail) = rest.strip_prefix(b".") {
+        rest = tail.get(digits(tail)..).unwrap_or_default();
+    }
+
+    if let [b'e' | b'E', tail @ ..] = rest {
+        let tail = match tail {
+            [b'-' | b'+', tail @ ..] => tail,
+            tail => tail,
+        };
+
+        let n = digits(tail);
+
+        if n == 0 {
+            return None;
+        }
+
+        rest = tail.get(n..).unwrap_or_default();
+    }
+
+    rest.is_empty().then_some(serde_hint::F64)
+}
+
+/// Resolve a number according to the YAML 1.2 core schema.
+fn core_number(string: &[u8]) -> Option<RawNumberHint> {
+    fn digits(string: &[u8], f: fn(&u8) -> bool) -> usize {
+        string.iter().take_while(|b| f(b)).count()
+    }
+
+    if let Some(rest) = string.strip_prefix(b"0o") {
+        let n = digits(rest, |b| matches!(b, b'0'..=b'7'));
+        return (n > 0 && n == rest.len()).then_some(serde_hint::U64);
+    }
+
+    if let Some(rest) = string.strip_prefix(b"0x") {
+        let n = digits(rest, u8::is_ascii_hexdigit);
+        return (n > 0 && n == rest.len()).then_some(serde_hint::U64);
+    }
+
+    if matches!(string, b".nan" | b".NaN" | b".NAN") {
+        return Some(serde_hint::F64);
+    }
+
+    let (negative, rest) = match string {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        rest => (false, rest),
+    };
+
+    if matches!(rest, b".inf" | b".Inf" | b".INF") {
+        return Some(serde_hint::F64);
+    }
+
+    let int = digits(rest, u8::is_ascii_digit);
+    let mut rest = rest.get(int..).unwrap_or_default();
+
+    if rest.is_empty() {
+        if int == 0 {
+            return None;
+        }
+
+        return Some(if negative {
+            serde_hint::I64
+        } else {
+            serde_hint::U64
+        });
+    }
+
+    let mut fraction = 0;
+
+    if let Some(tail) = rest.strip_prefix(b".") {
+        fraction = digits(tail, u8::is_ascii_digit);
+        rest = tail.get(fraction..).unwrap_or_default();
+    }
+
+    // Either the integer or the fractional part needs digits.
+    if int == 0 && fraction == 0 {
+        return None;
+    }
+
+    if let [b'e' | b'E', tail @ ..] = rest {
+        let tail = match tail {
+            [b'-' | b'+', tail @ ..] => tail,
+            tail => tail,
+        };
+
+        let n = digits(tail, u8::is_ascii_digit);
+
+        if n == 0 {
+            return None;
+        }
+
+        rest = tail.get(n..).unwrap_or_default();
+    }
+
+    rest.is_empty().then_some(serde_hint::F64)
+}
@@ -1,4 +1,5 @@
 use core::fmt;
+use core::ops::{Bound, RangeBounds};
 
 use crate::yaml::data::{Data, Id};
 use crate::yaml::sequence::Iter;
@@ -226,6 +227,39 @@ impl<'a> Sequence<'a> {
         Some(Value::new(self.data, item.value))
     }
 
+    /// Get the value at the given `index` counted from the back of the
+    /// sequence, where `0` refers to the last value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - one
+    ///     - two
+    ///     - three
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert_eq!(root.get_back(0).and_then(|v| v.as_str()), Some("three"));
+    /// assert_eq!(root.get_back(2).and_then(|v| v.as_str()), Some("one"));
+    /// assert!(root.get_back(3).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn get_back(&self, index: usize) -> Option<Value<'_>> {
+        let items = &self.data.sequence(self.id).items;
+        let item = items.get(items.len().checked_sub(index)?.checked_sub(1)?)?;
+        let item = self.data.sequence_item(*item);
+        Some(Value::new(self.data, item.value))
+    }
+
     /// Get the first value of a sequence.
     ///
     /// # Examples
@@ -309,6 +343,59 @@ impl<'a> Sequence<'a> {
     pub fn iter(&self) -> Iter<'a> {
         Iter::new(self.data, &self.data.sequence(self.id).items)
     }
+
+    /// Returns an iterator over the values in the given `range` of the
+    /// sequence.
+    ///
+    /// Unlike slicing, a range which extends past the end of the sequence is
+    /// truncated to it, so this never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - 1.0.0
+    ///     - 1.1.0
+    ///     - 1.2.0
+    ///     - 2.0.0
+    ///     ",
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    ///
+    /// assert!(root.slice(1..3).flat_map(|v| v.as_str()).eq(["1.1.0", "1.2.0"]));
+    /// assert!(root.slice(root.len().saturating_sub(2)..).flat_map(|v| v.as_str()).eq(["1.2.0", "2.0.0"]));
+    /// assert!(root.slice(..10).flat_map(|v| v.as_str()).eq(["1.0.0", "1.1.0", "1.2.0", "2.0.0"]));
+    /// assert_eq!(root.slice(3..1).count(), 0);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn slice<R>(&self, range: R) -> Iter<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let items = &self.data.sequence(self.id).items;
+
+        let start = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.saturating_add(1),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => items.len(),
+        };
+
+        let end = end.min(items.len());
+        let start = start.min(end);
+        Iter::new(self.data, items.get(start..end).unwrap_or_default())
+    }
 }
 
 impl fmt::Display for Sequence<'_> {
@@ -1,8 +1,11 @@
+use core::cmp::Ordering;
 use core::mem;
 
+use crate::yaml::clear;
 use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::lock;
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::{Block, Separator, Sequence, ValueMut};
+use crate::yaml::{Block, Clear, Error, Scalar, Separator, Sequence, Value, ValueMut};
 
 /// Mutator for a sequence.
 pub struct SequenceMut<'a> {
@@ -14,6 +17,8 @@ macro_rules! push_float {
     ($name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
         #[doc = concat!("Push the value as a ", $string, ".")]
         ///
+        /// Values which are not finite are written as `.inf`, `-.inf` or `.nan`.
+        ///
         /// # Examples
         ///
         /// ```
@@ -40,7 +45,9 @@ macro_rules! push_float {
         /// ```
         pub fn $name(&mut self, value: $ty) {
             let mut buffer = ryu::Buffer::new();
-            let number = self.data.insert_str(buffer.format(value));
+            let number = self
+                .data
+                .insert_str(crate::yaml::number::format_float(&mut buffer, value));
             let value = Raw::Number(raw::Number::new(number, crate::yaml::serde_hint::$hint));
             self.inner_push(Separator::Auto, value);
         }
@@ -92,13 +99,58 @@ impl<'a> SequenceMut<'a> {
 
     /// Make insertion prefix.
     fn make_prefix(&mut self) -> StringId {
-        let mut out = Vec::new();
-        out.push(raw::NEWLINE);
-        out.resize(
-            self.data.sequence(self.id).indent.saturating_add(1),
-            raw::SPACE,
-        );
-        self.data.insert_str(out)
+        let sequence = self.data.sequence(self.id);
+
+        if let raw::SequenceKind::Inline { .. } = sequence.kind {
+            return match raw::inline_prefix(self.data, &sequence.items) {
+                Some(prefix) => raw::without_comments(self.data, prefix),
+                None => self.data.insert_str(" "),
+            };
+        }
+
+        let (last, indent) = (sequence.items.last().copied(), sequence.indent);
+        raw::block_item_prefix(self.data, self.id, last, indent)
+    }
+
+    /// Make insertion prefix for the first item.
+    fn make_first_prefix(&mut self) -> StringId {
+        if let raw::SequenceKind::Inline { suffix, .. } = self.data.sequence(self.id).kind {
+            if let Some(prefix) = raw::empty_inline_prefix(self.data, suffix) {
+                return prefix;
+            }
+        }
+
+        self.data.insert_str("")
+    }
+
+    /// Remove every item for which `is_duplicate` returns `true` when called
+    /// with the items retained so far, returning the number of removed items.
+    fn inner_dedup<F>(&mut self, mut is_duplicate: F) -> Result<usize, Error>
+    where
+        F: FnMut(&Data, &[Id], Id) -> bool,
+    {
+        let items = &self.data.sequence(self.id).items;
+        let mut retained = Vec::with_capacity(items.len());
+        let mut removed = Vec::new();
+
+        for &item in items {
+            if is_duplicate(self.data, &retained, item) {
+                removed.push(item);
+            } else {
+                retained.push(item);
+            }
+        }
+
+        lock::check_all(self.data, &removed)?;
+
+        let count = removed.len();
+
+        for item in removed {
+            self.data.drop(item);
+        }
+
+        self.data.sequence_mut(self.id).items = retained;
+        Ok(count)
     }
 
     /// Push a value on the sequence.
@@ -106,7 +158,7 @@ impl<'a> SequenceMut<'a> {
         let item_prefix = if self.data.sequence(self.id).items.last().is_some() {
             self.make_prefix()
         } else {
-            self.data.insert_str("")
+            self.make_first_prefix()
         };
 
         let item_id = self
@@ -114,21 +166,34 @@ impl<'a> SequenceMut<'a> {
             .insert(Raw::Null(raw::Null::Empty), item_prefix, Some(self.id));
 
         let value_prefix = match separator {
-            Separator::Auto => match self.data.sequence(self.id).items.last() {
-                Some(last) => {
-                    self.data
-                        .layout(self.data.sequence_item(*last).value)
-                        .prefix
+            Separator::Auto => {
+                let sequence = self.data.sequence(self.id);
+                let block = matches!(sequence.kind, raw::SequenceKind::Mapping);
+
+                match (self.data.separator(), sequence.items.last()) {
+                    (Some(separator), _) if block && !value.is_tabular() => separator,
+                    (_, Some(last)) => {
+                        self.data
+                            .layout(self.data.sequence_item(*last).value)
+                            .prefix
+                    }
+                    (_, None) if block => self.data.insert_str(" "),
+                    (_, None) => self.data.insert_str(""),
                 }
-                None => self.data.insert_str(" "),
-            },
+            }
             Separator::Custom(separator) => self.data.insert_str(separator),
+            Separator::Spaces(n) => self.data.insert_str(" ".repeat(n.max(1))),
         };
 
         let value = self.data.insert(value, value_prefix, Some(item_id));
 
-        self.data
-            .replace(item_id, Raw::SequenceItem(raw::SequenceItem { value }));
+        self.data.replace(
+            item_id,
+            Raw::SequenceItem(raw::SequenceItem {
+                value,
+                suffix: None,
+            }),
+        );
         self.data.sequence_mut(self.id).items.push(item_id);
         value
     }
@@ -196,6 +261,42 @@ impl<'a> SequenceMut<'a> {
         Sequence::new(self.data, self.id)
     }
 
+    /// Get the identifiers of every value in the sequence, in order.
+    ///
+    /// Since the returned identifiers don't borrow from the sequence, this
+    /// allows for iterating over and modifying each value in turn through
+    /// [`Document::value_mut`].
+    ///
+    /// [`Document::value_mut`]: crate::yaml::Document::value_mut
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[1, 2, 3]")?;
+    ///
+    /// let ids = doc.as_mut().into_sequence_mut().context("missing root sequence")?.ids();
+    ///
+    /// for id in ids {
+    ///     let n = doc.value(id).as_u32().context("not a number")?;
+    ///     doc.value_mut(id).set_u32(n * 10);
+    /// }
+    ///
+    /// assert_eq!(doc.to_string(), "[10, 20, 30]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn ids(&self) -> Vec<Id> {
+        self.data
+            .sequence(self.id)
+            .items
+            .iter()
+            .map(|&item| self.data.sequence_item(item).value)
+            .collect()
+    }
+
     /// Get a value mutably from the sequence.
     ///
     /// # Examples
@@ -231,6 +332,11 @@ impl<'a> SequenceMut<'a> {
     pub fn get_mut(&mut self, index: usize) -> Option<ValueMut<'_>> {
         if let Some(item) = self.data.sequence(self.id).items.get(index) {
             let item = self.data.sequence_item(*item);
+
+            if lock::is_locked(self.data, item.value) {
+                return None;
+            }
+
             return Some(ValueMut::new(self.data, item.value));
         }
 
@@ -273,6 +379,11 @@ impl<'a> SequenceMut<'a> {
     pub fn get_into_mut(self, index: usize) -> Option<ValueMut<'a>> {
         if let Some(item) = self.data.sequence(self.id).items.get(index) {
             let item = self.data.sequence_item(*item);
+
+            if lock::is_locked(self.data, item.value) {
+                return None;
+            }
+
             return Some(ValueMut::new(self.data, item.value));
         }
 
@@ -314,19 +425,146 @@ impl<'a> SequenceMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn remove(&mut self, index: usize) -> bool {
-        let raw = self.data.sequence_mut(self.id);
+        let Some(&item) = self.data.sequence(self.id).items.get(index) else {
+            return false;
+        };
 
-        if raw.items.len() <= index {
+        if lock::contains_locked(self.data, item) {
             return false;
         }
 
-        let item = raw.items.remove(index);
+        self.data.sequence_mut(self.id).items.remove(index);
         self.data.drop(item);
         true
     }
 
+    /// Shorten the sequence to the first `len` values, dropping the rest and
+    /// returning the number of values which were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - 3.0.0
+    ///     - 2.0.0
+    ///     - 1.0.0
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    ///
+    /// assert_eq!(root.truncate(2)?, 1);
+    /// assert_eq!(root.truncate(2)?, 0);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - 3.0.0
+    ///     - 2.0.0
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn truncate(&mut self, len: usize) -> Result<usize, Error> {
+        let items = &self.data.sequence(self.id).items;
+
+        let Some(removed) = items.get(len..).filter(|removed| !removed.is_empty()) else {
+            return Ok(0);
+        };
+
+        lock::check_all(self.data, removed)?;
+
+        let removed = self.data.sequence_mut(self.id).items.split_off(len);
+        let count = removed.len();
+
+        for item in removed {
+            self.data.drop(item);
+        }
+
+        Ok(count)
+    }
+
+    /// Keep only the last `n` values of the sequence, dropping the rest and
+    /// returning the number of values which were removed.
+    ///
+    /// The first remaining value takes over the prefix of the first value in
+    /// the sequence, so that the sequence keeps its layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     releases:
+    ///       ## newest last
+    ///       - 1.0.0
+    ///       - 1.1.0 # yanked
+    ///       - 1.2.0
+    ///       - 2.0.0
+    ///     tags: [a, b, c]
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    ///
+    /// let mut releases = root.get_mut("releases").and_then(|v| v.into_sequence_mut()).context("missing releases")?;
+    /// assert_eq!(releases.keep_last(2)?, 2);
+    ///
+    /// let mut tags = root.get_mut("tags").and_then(|v| v.into_sequence_mut()).context("missing tags")?;
+    /// assert_eq!(tags.keep_last(1)?, 2);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     releases:
+    ///       ## newest last
+    ///       - 1.2.0
+    ///       - 2.0.0
+    ///     tags: [c]
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn keep_last(&mut self, n: usize) -> Result<usize, Error> {
+        let items = &self.data.sequence(self.id).items;
+
+        let Some(count) = items.len().checked_sub(n).filter(|&count| count > 0) else {
+            return Ok(0);
+        };
+
+        lock::check_all(self.data, items.get(..count).unwrap_or_default())?;
+
+        let raw = self.data.sequence_mut(self.id);
+        let removed = raw.items.drain(..count).collect::<Vec<_>>();
+
+        if let (Some(&first), Some(&item)) = (removed.first(), raw.items.first()) {
+            let prefix = self.data.layout(first).prefix;
+            self.data.set_prefix(item, prefix);
+        }
+
+        for item in removed {
+            self.data.drop(item);
+        }
+
+        Ok(count)
+    }
+
     /// Clear all the elements in a sequence.
     ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if the sequence contains a value
+    /// which is [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
     /// # Examples
     ///
     /// ```
@@ -345,19 +583,414 @@ impl<'a> SequenceMut<'a> {
     /// let mut root = doc.as_mut();
     /// let mut root = root.as_sequence_mut().context("missing root sequence")?;
     ///
-    /// root.clear();
+    /// root.clear()?;
+    /// assert_eq!(doc.to_string(), "\n    ");
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.push_u32(30);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"
+    ///     - 30
+    ///     "#
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn clear(&mut self) -> Result<(), Error> {
+        self.clear_with(Clear::default())
+    }
+
+    /// Clear all the elements in a sequence, using the given [`Clear`] style
+    /// to decide what the sequence is left as.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] like [`SequenceMut::clear`].
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("- 1\n- 2\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.clear_with(yaml::Clear::Flow)?;
+    /// assert_eq!(doc.to_string(), "[]\n");
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.push_u32(3);
+    /// assert_eq!(doc.to_string(), "[3]\n");
     ///
-    /// assert_eq!(doc.to_string(), "\n    \n    ");
+    /// let mut doc = yaml::from_slice("a: [1, 2]\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_sequence_mut()).context("missing a")?;
+    /// a.clear_with(yaml::Clear::Null)?;
+    /// assert_eq!(doc.to_string(), "a:\n");
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// let mut a = root.get_mut("a").and_then(|v| v.into_sequence_mut()).context("missing a")?;
+    /// a.push_u32(3);
+    /// assert_eq!(doc.to_string(), "a:\n  - 3\n");
     /// # Ok::<_, anyhow::Error>(())
     /// ```
-    pub fn clear(&mut self) {
+    pub fn clear_with(&mut self, style: Clear) -> Result<(), Error> {
+        lock::check(self.data, self.id)?;
+
         let mut items = mem::take(&mut self.data.sequence_mut(self.id).items);
+        clear::drop_items(self.data, &mut items);
+        self.data.sequence_mut(self.id).items = items;
 
-        for item in items.drain(..) {
-            self.data.drop(item);
+        let inline = matches!(
+            self.data.sequence(self.id).kind,
+            raw::SequenceKind::Inline { .. }
+        );
+
+        match style {
+            Clear::Flow if !inline => {
+                clear::flow_prefix(self.data, self.id);
+                let suffix = self.data.insert_str("");
+
+                self.data.sequence_mut(self.id).kind = raw::SequenceKind::Inline {
+                    trailing: false,
+                    suffix,
+                };
+            }
+            Clear::Null if inline && !clear::in_flow(self.data, self.id) => {
+                let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+                self.data.set_prefix(self.id, prefix);
+
+                let sequence = self.data.sequence_mut(self.id);
+                sequence.indent = indent;
+                sequence.kind = raw::SequenceKind::Mapping;
+            }
+            _ => {}
         }
 
-        self.data.sequence_mut(self.id).items = items;
+        Ok(())
+    }
+
+    /// Sort the sequence using [`Value::total_cmp`].
+    ///
+    /// The sort is stable. See [`SequenceMut::sort_by`] for how formatting is
+    /// preserved.
+    ///
+    /// [`Value::total_cmp`]: crate::yaml::Value::total_cmp
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[b, 10, ~, 2.5, true, a]")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.sort()?;
+    ///
+    /// assert_eq!(doc.to_string(), "[~, true, 2.5, 10, a, b]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn sort(&mut self) -> Result<(), Error> {
+        self.sort_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Sort the sequence using the comparator function `compare`.
+    ///
+    /// The sort is stable. Comments preceding or trailing an item move together
+    /// with it, while line breaks and indentation stay in their original
+    /// positions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - name: gamma
+    ///       port: 3
+    ///     - name: alpha
+    ///       port: 1
+    ///     - name: beta # the second
+    ///       port: 2
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    ///
+    /// root.sort_by(|a, b| {
+    ///     let a = a.as_mapping().and_then(|m| m.get("name"));
+    ///     let b = b.as_mapping().and_then(|m| m.get("name"));
+    ///     a.map(|a| a.as_bytes()).cmp(&b.map(|b| b.as_bytes()))
+    /// })?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - name: alpha
+    ///       port: 1
+    ///     - name: beta # the second
+    ///       port: 2
+    ///     - name: gamma
+    ///       port: 3
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn sort_by<F>(&mut self, mut compare: F) -> Result<(), Error>
+    where
+        F: FnMut(&Value<'_>, &Value<'_>) -> Ordering,
+    {
+        lock::check(self.data, self.id)?;
+
+        let items = mem::take(&mut self.data.sequence_mut(self.id).items);
+
+        let prefixes = items
+            .iter()
+            .map(|&item| self.data.prefix(item).to_vec())
+            .collect::<Vec<_>>();
+
+        let suffixes = items
+            .iter()
+            .map(|&item| self.data.sequence_item(item).suffix)
+            .collect::<Vec<_>>();
+
+        let data = &*self.data;
+        let mut order = items.iter().copied().enumerate().collect::<Vec<_>>();
+
+        order.sort_by(|&(_, a), &(_, b)| {
+            let a = Value::new(data, data.sequence_item(a).value);
+            let b = Value::new(data, data.sequence_item(b).value);
+            compare(&a, &b)
+        });
+
+        let has_comment = |suffix: Option<StringId>| {
+            suffix.is_some_and(|suffix| data.str(suffix).contains(&b'#'))
+        };
+
+        let mut layout = Vec::with_capacity(items.len());
+
+        // Comments move together with the item they belong to, while line
+        // breaks and indentation stay in their original positions.
+        for (n, (&(index, item), (at, &at_suffix))) in
+            order.iter().zip(prefixes.iter().zip(&suffixes)).enumerate()
+        {
+            let at = raw::prefix_parts(at);
+            let own = raw::prefix_parts(prefixes.get(index).map_or(&[][..], Vec::as_slice));
+            let own_suffix = suffixes.get(index).copied().flatten();
+
+            // A comment trailing an item is stored in the prefix of the item
+            // following it.
+            let trail = match n.checked_sub(1).and_then(|n| order.get(n)) {
+                Some(&(prev, _)) => prefixes
+                    .get(prev + 1)
+                    .map_or(&[][..], |p| raw::prefix_parts(p).trail),
+                None => at.trail,
+            };
+
+            let mut prefix = trail.to_vec();
+
+            if at.lead.is_empty() && at.comments.is_empty() {
+                // Without line breaks in this position, such as for the first
+                // item of a block sequence, comments start where the item
+                // would and the item is indented again after them.
+                if trail.is_empty() && own.comments.is_empty() {
+                    prefix.extend_from_slice(at.indent);
+                } else {
+                    if trail.is_empty() {
+                        let n = own.comments.iter().take_while(|&&b| b == b' ').count();
+                        prefix.extend_from_slice(at.indent);
+                        prefix.extend_from_slice(own.comments.get(n..).unwrap_or_default());
+                    } else {
+                        prefix.extend_from_slice(data.newline());
+                        prefix.extend_from_slice(own.comments);
+                    }
+
+                    if own.lead.is_empty() && own.comments.is_empty() {
+                        prefix.extend_from_slice(at.indent);
+                    } else {
+                        prefix.extend_from_slice(own.indent);
+                    }
+                }
+            } else {
+                prefix.extend_from_slice(at.lead);
+                prefix.extend_from_slice(own.comments);
+                prefix.extend_from_slice(at.indent);
+            }
+
+            let suffix = if has_comment(own_suffix) || has_comment(at_suffix) {
+                own_suffix
+            } else {
+                at_suffix
+            };
+
+            layout.push((item, prefix, suffix));
+        }
+
+        let mut sorted = Vec::with_capacity(layout.len());
+
+        for (item, prefix, suffix) in layout {
+            let prefix = self.data.insert_str(prefix);
+            self.data.set_prefix(item, prefix);
+            self.data.sequence_item_mut(item).suffix = suffix;
+            sorted.push(item);
+        }
+
+        self.data.sequence_mut(self.id).items = sorted;
+        Ok(())
+    }
+
+    /// Remove consecutive values which are structurally equal according to
+    /// [`Value::total_cmp`], returning the number of removed values.
+    ///
+    /// The first value in each run of duplicates is kept together with its
+    /// comments and formatting. Use [`SequenceMut::dedup_all`] to also remove
+    /// duplicates which are not adjacent.
+    ///
+    /// [`Value::total_cmp`]: crate::yaml::Value::total_cmp
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - 'one' # the first one
+    ///     - one
+    ///     - two
+    ///     - one
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert_eq!(root.dedup()?, 1);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - 'one' # the first one
+    ///     - two
+    ///     - one
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn dedup(&mut self) -> Result<usize, Error> {
+        self.dedup_by(|a, b| a.total_cmp(b).is_eq())
+    }
+
+    /// Remove consecutive values for which `same` returns `true`, returning
+    /// the number of removed values.
+    ///
+    /// The first argument to `same` is the previously retained value, and the
+    /// second is the value which is removed if `same` returns `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[Foo, foo, bar, FOO, Bar]")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    ///
+    /// let removed = root.dedup_by(|a, b| match (a.as_bytes(), b.as_bytes()) {
+    ///     (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+    ///     _ => false,
+    /// })?;
+    ///
+    /// assert_eq!(removed, 1);
+    /// assert_eq!(doc.to_string(), "[Foo, bar, FOO, Bar]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn dedup_by<F>(&mut self, mut same: F) -> Result<usize, Error>
+    where
+        F: FnMut(&Value<'_>, &Value<'_>) -> bool,
+    {
+        self.inner_dedup(|data, retained, item| {
+            let Some(&last) = retained.last() else {
+                return false;
+            };
+
+            let last = Value::new(data, data.sequence_item(last).value);
+            let item = Value::new(data, data.sequence_item(item).value);
+            same(&last, &item)
+        })
+    }
+
+    /// Remove all values which are structurally equal to an earlier value
+    /// according to [`Value::total_cmp`], returning the number of removed
+    /// values.
+    ///
+    /// The first occurrence of each value is kept together with its comments
+    /// and formatting.
+    ///
+    /// [`Value::total_cmp`]: crate::yaml::Value::total_cmp
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[1, {a: 2}, 1.0, \"x\", {a: 2}, x]")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// assert_eq!(root.dedup_all()?, 3);
+    /// assert_eq!(doc.to_string(), "[1, {a: 2}, \"x\"]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn dedup_all(&mut self) -> Result<usize, Error> {
+        self.inner_dedup(|data, retained, item| {
+            let item = Value::new(data, data.sequence_item(item).value);
+
+            retained.iter().any(|&other| {
+                let other = Value::new(data, data.sequence_item(other).value);
+                other.total_cmp(&item).is_eq()
+            })
+        })
+    }
+
+    /// Set whether an inline sequence has a trailing comma after its last
+    /// item, such as in `[1, 2,]`.
+    ///
+    /// Values pushed onto the sequence afterwards keep the configured style.
+    /// This has no effect on sequences which are not inline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("[1, 2]")?;
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.set_trailing_comma(true);
+    /// assert_eq!(doc.to_string(), "[1, 2,]");
+    ///
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.set_trailing_comma(false);
+    /// assert_eq!(doc.to_string(), "[1, 2]");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_trailing_comma(&mut self, trailing: bool) {
+        if let raw::SequenceKind::Inline {
+            trailing: current, ..
+        } = &mut self.data.sequence_mut(self.id).kind
+        {
+            *current = trailing;
+        }
     }
 
     /// Push a new null value and return a [`ValueMut`] to the newly pushed value.
@@ -426,7 +1059,34 @@ impl<'a> SequenceMut<'a> {
     where
         S: AsRef<str>,
     {
-        let string = raw::new_string(self.data, string);
+        let string = raw::new_string(self.data, string, raw::is_inline(self.data, self.id));
+        self.inner_push(Separator::Auto, string);
+    }
+
+    /// Push a string which is not required to be valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(b"- caf\xe9")?;
+    ///
+    /// let mut value = doc.as_mut().into_sequence_mut().context("not a sequence")?;
+    /// // Encoded as Windows-1252.
+    /// value.push_bytes(b"cr\xe8me");
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"- caf\xe9\n- cr\xe8me");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn push_bytes<B>(&mut self, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        let string = raw::new_bytes(self.data, bytes, raw::is_inline(self.data, self.id));
         self.inner_push(Separator::Auto, string);
     }
 
@@ -436,6 +1096,9 @@ impl<'a> SequenceMut<'a> {
     /// underlying value type produced is in fact a string, and can be read
     /// through methods such as [`Value::as_str`][crate::yaml::Value::as_str].
     ///
+    /// Like [`ValueMut::set_block`], the value is written as a double-quoted
+    /// string if this is an inline sequence.
+    ///
     /// # Examples
     ///
     /// ```
@@ -448,8 +1111,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Clip));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo\nbar\nbaz\n"));
     ///
@@ -463,8 +1126,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Keep));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo\nbar\nbaz\n"));
     ///
@@ -478,8 +1141,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Literal(yaml::Chomp::Strip));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo\nbar\nbaz"));
     ///
@@ -493,8 +1156,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Clip));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo bar baz\n"));
     ///
@@ -508,8 +1171,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Keep));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo bar baz\n"));
     ///
@@ -523,8 +1186,8 @@ impl<'a> SequenceMut<'a> {
     ///     "
     /// );
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
-    /// sequence.clear();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
+    /// sequence.clear()?;
     /// sequence.push_block(["foo", "bar", "baz"], yaml::Block::Folded(yaml::Chomp::Strip));
     /// assert_eq!(sequence.as_ref().last().and_then(|v| v.as_str()), Some("foo bar baz"));
     ///
@@ -544,7 +1207,7 @@ impl<'a> SequenceMut<'a> {
         I: IntoIterator,
         I::Item: AsRef<str>,
     {
-        let value = raw::new_block(self.data, self.id, iter, block);
+        let value = raw::new_block(self.data, self.id, iter, block, None, true);
         self.inner_push(Separator::Auto, value);
     }
 
@@ -592,4 +1255,48 @@ impl<'a> SequenceMut<'a> {
     push_number!(push_i64, i64, "64-bit signed integer", -42, I64);
     push_number!(push_u128, u128, "128-bit unsigned integer", 42, U128);
     push_number!(push_i128, i128, "128-bit signed integer", -42, I128);
+
+    /// Push any value which can be converted into a [`Scalar`].
+    ///
+    /// Numbers and booleans are formatted the same way as by the methods
+    /// specific to their types, like [`SequenceMut::push_u32`], and strings
+    /// the same way as by [`SequenceMut::push_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     - 10
+    ///     "
+    /// )?;
+    ///
+    /// let mut value = doc.as_mut().into_sequence_mut().context("not a sequence")?;
+    /// value.push_value(42u32);
+    /// value.push_value("hello");
+    /// value.push_value(false);
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     - 10
+    ///     - 42
+    ///     - hello
+    ///     - false
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn push_value<'s, T>(&mut self, value: T)
+    where
+        T: Into<Scalar<'s>>,
+    {
+        let value = value
+            .into()
+            .into_raw(self.data, raw::is_inline(self.data, self.id));
+        self.inner_push(Separator::Auto, value);
+    }
 }
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::io;
 
 use serde::de;
 
@@ -30,3 +31,13 @@ impl fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+/// Convert an [`Error`] into an [`io::Error`] of the kind
+/// [`io::ErrorKind::InvalidData`], like the conversion for
+/// [`yaml::Error`][crate::yaml::Error].
+impl From<Error> for io::Error {
+    #[inline]
+    fn from(error: Error) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
@@ -0,0 +1,274 @@
+//! Compression and validation of documents serialized with the `serde-edits`
+//! feature.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use twox_hash::xxh3;
+
+use crate::yaml::data::{Data, Id, StringId};
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::Document;
+
+/// The level used when compressing strings, which is the default level of
+/// zlib.
+const LEVEL: u8 = 6;
+
+/// How the strings of a [`Document`] are compressed when it's serialized with
+/// the `serde-edits` feature, set through [`Document::set_compression`].
+///
+/// Strings make up the bulk of a serialized document, since they hold all of
+/// its text. A document which is deserialized from compressed strings keeps
+/// compressing them when it's serialized again.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("run: |\n  cargo build\n  cargo test\n")?;
+/// doc.set_compression(yaml::Compression::Deflate);
+///
+/// let state = serde_yaml::to_string(&doc)?;
+/// let doc: yaml::Document = serde_yaml::from_str(&state)?;
+/// assert_eq!(doc.to_string(), "run: |\n  cargo build\n  cargo test\n");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Strings are serialized as they are.
+    #[default]
+    None,
+    /// Strings are concatenated and compressed with deflate.
+    Deflate,
+}
+
+/// The strings of a document, compressed as a single block.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Packed {
+    /// The length of each string.
+    lengths: Vec<usize>,
+    /// The concatenated strings, compressed with deflate.
+    bytes: Vec<u8>,
+}
+
+impl Packed {
+    /// Compress the given strings.
+    pub(crate) fn new<'a, I>(strings: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Box<[u8]>>,
+    {
+        let mut lengths = Vec::new();
+        let mut buf = Vec::new();
+
+        for string in strings {
+            lengths.push(string.len());
+            buf.extend_from_slice(string);
+        }
+
+        let bytes = miniz_oxide::deflate::compress_to_vec(&buf, LEVEL);
+        Self { lengths, bytes }
+    }
+
+    /// Decompress the strings.
+    ///
+    /// Decompression is limited to the total length of the strings, so that
+    /// corrupt input can't cause unbounded allocations.
+    pub(crate) fn unpack(&self) -> Result<Vec<Box<[u8]>>, &'static str> {
+        let total = self
+            .lengths
+            .iter()
+            .try_fold(0usize, |total, &len| total.checked_add(len))
+            .ok_or("packed strings are too large")?;
+
+        let Ok(buf) = miniz_oxide::inflate::decompress_to_vec_with_limit(&self.bytes, total) else {
+            return Err("packed strings are corrupt");
+        };
+
+        if buf.len() != total {
+            return Err("packed strings have the wrong length");
+        }
+
+        let mut rest = &buf[..];
+        let mut strings = Vec::new();
+
+        for &len in &self.lengths {
+            let (string, tail) = rest.split_at(len);
+            strings.push(string.into());
+            rest = tail;
+        }
+
+        Ok(strings)
+    }
+}
+
+/// Calculate the checksum of a document, which covers its output as written
+/// through [`Document::write_to`].
+pub(crate) fn checksum(doc: &Document) -> u64 {
+    let mut out = Vec::new();
+    // Writing to a vector never fails.
+    _ = doc.write_to(&mut out);
+    xxh3::hash64(&out)
+}
+
+/// The role a value is expected to have when checked.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Value,
+    MappingItem,
+    SequenceItem,
+}
+
+/// Check that all values reachable from `root` refer to values and strings
+/// which are present in `data`, that every value is reachable only once, and
+/// that the parent of every value is the value it's reachable from.
+///
+/// Following the parents of a value therefore always ends at `root`, which
+/// ensures that a deserialized document can be used without panicking.
+pub(crate) fn check(data: &Data, root: Id) -> Result<(), &'static str> {
+    let mut seen = HashSet::new();
+    let mut queue = vec![(root, Role::Value, None)];
+
+    let str = |id: StringId| {
+        if data.contains_str(id) {
+            Ok(())
+        } else {
+            Err("reference to a missing string")
+        }
+    };
+
+    while let Some((id, role, parent)) = queue.pop() {
+        let Some((raw, layout)) = data.try_entry(id) else {
+            return Err("reference to a missing value");
+        };
+
+        if !seen.insert(id) {
+            return Err("value is referenced more than once");
+        }
+
+        str(layout.prefix)?;
+
+        if layout.parent != parent {
+            return Err("value has an unexpected parent");
+        }
+
+        if let Some(anchor) = &layout.anchor {
+            str(anchor.name)?;
+            str(anchor.suffix)?;
+        }
+
+        if let Some(tag) = &layout.tag {
+            str(tag.name)?;
+            str(tag.suffix)?;
+        }
+
+        let actual = match raw {
+            Raw::MappingItem(..) => Role::MappingItem,
+            Raw::SequenceItem(..) => Role::SequenceItem,
+            _ => Role::Value,
+        };
+
+        if actual != role {
+            return Err("value has an unexpected kind");
+        }
+
+        match raw {
+            Raw::Null(raw::Null::Keyword(string)) => {
+                str(*string)?;
+            }
+            Raw::Null(..) => {}
+            Raw::Boolean(raw) => {
+                str(raw.string)?;
+            }
+            Raw::Number(raw) => {
+                str(raw.string)?;
+            }
+            Raw::String(raw) => {
+                check_string(raw, str)?;
+            }
+            Raw::Alias(raw) => {
+                str(raw.name)?;
+            }
+            Raw::Mapping(raw) => {
+                if let raw::MappingKind::Inline { suffix, .. } = raw.kind {
+                    str(suffix)?;
+                }
+
+                if let Some(tag) = &raw.tag {
+                    str(tag.name)?;
+                    str(tag.suffix)?;
+                }
+
+                queue.extend(
+                    raw.items
+                        .iter()
+                        .map(|&item| (item, Role::MappingItem, Some(id))),
+                );
+            }
+            Raw::MappingItem(raw) => {
+                check_string(&raw.key, str)?;
+
+                if let Some(suffix) = raw.suffix {
+                    str(suffix)?;
+                }
+
+                match raw.kind {
+                    raw::MappingItemKind::Explicit { separator } => {
+                        str(separator)?;
+                    }
+                    raw::MappingItemKind::Complex { key, colon } => {
+                        if let Some(colon) = colon {
+                            str(colon)?;
+                        }
+
+                        queue.push((key, Role::Value, Some(id)));
+                    }
+                    _ => {}
+                }
+
+                queue.push((raw.value, Role::Value, Some(id)));
+            }
+            Raw::Sequence(raw) => {
+                if let raw::SequenceKind::Inline { suffix, .. } = raw.kind {
+                    str(suffix)?;
+                }
+
+                if let Some(tag) = &raw.tag {
+                    str(tag.name)?;
+                    str(tag.suffix)?;
+                }
+
+                queue.extend(
+                    raw.items
+                        .iter()
+                        .map(|&item| (item, Role::SequenceItem, Some(id))),
+                );
+            }
+            Raw::SequenceItem(raw) => {
+                if let Some(suffix) = raw.suffix {
+                    str(suffix)?;
+                }
+
+                queue.push((raw.value, Role::Value, Some(id)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the strings referenced by a raw string.
+fn check_string<F>(raw: &raw::String, str: F) -> Result<(), &'static str>
+where
+    F: Fn(StringId) -> Result<(), &'static str>,
+{
+    str(raw.id)?;
+    str(raw.original)?;
+
+    if let raw::RawStringKind::Multiline { prefix } = raw.kind {
+        str(prefix)?;
+    }
+
+    Ok(())
+}
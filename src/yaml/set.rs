@@ -0,0 +1,193 @@
+//! A [`Set`] of keys, which is a mapping tagged with `!!set`.
+//!
+//! # Examples
+//!
+//! ```
+//! use anyhow::Context;
+//! use nondestructive::yaml;
+//!
+//! let doc = yaml::from_slice(
+//!     r"
+//!     ? Mark McGwire
+//!     ? Sammy Sosa
+//!     ? Ken Griffy
+//!     "
+//! )?;
+//!
+//! // Untagged mappings are not sets.
+//! assert!(doc.as_ref().as_set().is_none());
+//!
+//! let doc = yaml::from_slice(
+//!     r"
+//!     --- !!set
+//!     ? Mark McGwire
+//!     ? Sammy Sosa
+//!     ? Ken Griffy
+//!     "
+//! )?;
+//!
+//! let set = doc.as_ref().as_set().context("missing root set")?;
+//! assert_eq!(set.len(), 3);
+//! assert!(set.contains("Ken Griffy"));
+//! assert!(!set.contains("Hank Aaron"));
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use core::fmt;
+use core::iter::FusedIterator;
+
+use bstr::BStr;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::mapping;
+use crate::yaml::Mapping;
+
+/// Accessor for a set.
+///
+/// Sets are mappings tagged with `!!set`, where every key has an empty value.
+/// They are either written in a block form using explicit keys, or in an
+/// inline form where keys are separated by commas.
+///
+/// ```yaml
+/// block: !!set
+///   ? one
+///   ? two
+/// inline: !!set {one, two}
+/// ```
+///
+/// See [`Value::as_set`][crate::yaml::Value::as_set].
+pub struct Set<'a> {
+    data: &'a Data,
+    id: Id,
+}
+
+impl<'a> Set<'a> {
+    pub(crate) fn new(data: &'a Data, id: Id) -> Self {
+        Self { data, id }
+    }
+
+    /// Get the opaque [`Id`] associated with this set.
+    #[must_use]
+    #[inline]
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Get the number of keys in the set.
+    #[must_use]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.mapping(self.id).items.len()
+    }
+
+    /// Test if the set is empty.
+    #[must_use]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.mapping(self.id).items.is_empty()
+    }
+
+    /// Test if the set contains the given `key`.
+    #[must_use]
+    pub fn contains(&self, key: &str) -> bool {
+        self.contains_bytes(key)
+    }
+
+    /// Test if the set contains the given `key`, which doesn't have to be
+    /// valid UTF-8.
+    #[must_use]
+    pub fn contains_bytes<K>(&self, key: K) -> bool
+    where
+        K: AsRef<[u8]>,
+    {
+        let key = key.as_ref();
+        self.iter().any(|k| k == key)
+    }
+
+    /// Get the set as the [`Mapping`] it's represented by.
+    #[must_use]
+    #[inline]
+    pub fn as_mapping(&self) -> Mapping<'a> {
+        Mapping::new(self.data, self.id)
+    }
+
+    /// Get an iterator over the keys of the set, in the order they appear.
+    #[must_use]
+    #[inline]
+    pub fn iter(&self) -> Iter<'a> {
+        Iter {
+            iter: self.as_mapping().iter(),
+        }
+    }
+}
+
+impl fmt::Display for Set<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_mapping().fmt(f)
+    }
+}
+
+impl fmt::Debug for Set<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for Set<'a> {
+    type Item = &'a BStr;
+    type IntoIter = Iter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the keys of a [`Set`].
+///
+/// See [`Set::iter`].
+pub struct Iter<'a> {
+    iter: mapping::Iter<'a>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a BStr;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next()?.0)
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        Some(self.iter.nth(n)?.0)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        Some(self.iter.next_back()?.0)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        Some(self.iter.nth_back(n)?.0)
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl FusedIterator for Iter<'_> {}
@@ -0,0 +1,61 @@
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::{self, Raw};
+
+/// Statistics about the values reachable from a value.
+#[derive(Default)]
+pub(crate) struct Stats {
+    /// The number of values, including keys.
+    pub(crate) nodes: usize,
+    /// The deepest nesting of mappings and sequences.
+    pub(crate) depth: usize,
+    /// The number of bytes of scalar content, including keys.
+    pub(crate) scalar_bytes: usize,
+}
+
+/// Collect statistics about the values reachable from `id`.
+///
+/// This doesn't recurse, so it can be used on arbitrarily deep documents.
+pub(crate) fn stats(data: &Data, id: Id) -> Stats {
+    let mut stats = Stats::default();
+    let mut queue = vec![(id, 0)];
+
+    while let Some((id, depth)) = queue.pop() {
+        stats.nodes += 1;
+
+        let bytes = match data.raw(id) {
+            Raw::Null(raw::Null::Keyword(string)) => data.str(*string).len(),
+            Raw::Null(raw::Null::Tilde) => 1,
+            Raw::Null(raw::Null::Empty) | Raw::Alias(..) => 0,
+            Raw::Boolean(raw) => data.str(raw.string).len(),
+            Raw::Number(raw) => data.str(raw.string).len(),
+            Raw::String(raw) => data.str(raw.id).len(),
+            Raw::Mapping(raw) => {
+                stats.depth = stats.depth.max(depth + 1);
+
+                for &item in &raw.items {
+                    let item = data.mapping_item(item);
+                    stats.nodes += 1;
+                    stats.scalar_bytes += data.str(item.key.id).len();
+                    queue.push((item.value, depth + 1));
+                }
+
+                0
+            }
+            Raw::Sequence(raw) => {
+                stats.depth = stats.depth.max(depth + 1);
+
+                for &item in &raw.items {
+                    queue.push((data.sequence_item(item).value, depth + 1));
+                }
+
+                0
+            }
+            // Items are visited through the collections they belong to.
+            Raw::MappingItem(..) | Raw::SequenceItem(..) => 0,
+        };
+
+        stats.scalar_bytes += bytes;
+    }
+
+    stats
+}
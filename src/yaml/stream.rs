@@ -0,0 +1,372 @@
+use std::fmt;
+use std::io;
+use std::iter;
+use std::ops::Range;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::yaml::raw;
+use crate::yaml::{Document, Error, Parser};
+
+/// The marker which starts a document in a stream.
+pub(crate) const START: &[u8] = b"---";
+
+/// The marker which explicitly ends a document.
+pub(crate) const END: &[u8] = b"...";
+
+/// A stream of YAML documents separated by `---` markers.
+///
+/// This is constructed through [`stream_from_slice`] or
+/// [`Parser::parse_stream`]. The header of each document, such as its `---`
+/// marker and any directives before it, is preserved as-is and is part of the
+/// document.
+///
+/// [`stream_from_slice`]: crate::yaml::stream_from_slice
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut stream = yaml::stream_from_slice("name: a\n--- # second\nname: b\n")?;
+/// assert_eq!(stream.len(), 2);
+///
+/// let doc = stream.get_mut(1).context("missing document")?;
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+/// root.insert_str("name", "c");
+///
+/// assert_eq!(stream.to_string(), "name: a\n--- # second\nname: c\n");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Stream {
+    documents: Vec<Document>,
+}
+
+impl Stream {
+    /// Construct an empty stream.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of documents in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::stream_from_slice("a\n---\nb\n---\nc\n")?;
+    /// assert_eq!(stream.len(), 3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Test if the stream contains no documents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::stream_from_slice("a")?;
+    /// assert!(!stream.is_empty());
+    ///
+    /// stream.remove(0);
+    /// assert!(stream.is_empty());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Get the document at `index`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::stream_from_slice("a\n---\nb\n")?;
+    /// let doc = stream.get(1).context("missing document")?;
+    /// assert_eq!(doc.as_ref().as_str(), Some("b"));
+    /// assert!(stream.get(2).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Document> {
+        self.documents.get(index)
+    }
+
+    /// Get the document at `index` mutably.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::stream_from_slice("a\n---\nb\n")?;
+    /// stream.get_mut(0).context("missing document")?.as_mut().set_u32(1);
+    /// assert_eq!(stream.to_string(), "1\n---\nb\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Document> {
+        self.documents.get_mut(index)
+    }
+
+    /// Iterate over the documents in the stream.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::stream_from_slice("1\n---\n2\n")?;
+    /// let values = stream.iter().map(|doc| doc.as_ref().as_u32()).collect::<Vec<_>>();
+    /// assert_eq!(values, [Some(1), Some(2)]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &Document> + DoubleEndedIterator {
+        self.documents.iter()
+    }
+
+    /// Add a document to the end of the stream, giving it a `---` marker if it
+    /// doesn't have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::stream_from_slice("a: 1")?;
+    /// stream.push_document(yaml::from_slice("b: 2\n")?);
+    /// assert_eq!(stream.to_string(), "a: 1\n---\nb: 2\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn push_document(&mut self, document: Document) {
+        self.insert(self.documents.len(), document);
+    }
+
+    /// Insert a document at `index`, giving it a `---` marker if it doesn't
+    /// have one.
+    ///
+    /// If `index` is larger than the number of documents, the document is
+    /// added to the end of the stream. A document which is inserted before a
+    /// first document without a marker gives it one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::stream_from_slice("a: 1\n--- # last\nc: 3\n")?;
+    /// stream.insert(1, yaml::from_slice("b: 2\n")?);
+    /// stream.insert(0, yaml::from_slice("z: 0\n")?);
+    ///
+    /// assert_eq!(stream.to_string(), "---\nz: 0\n---\na: 1\n---\nb: 2\n--- # last\nc: 3\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn insert(&mut self, index: usize, mut document: Document) {
+        let index = index.min(self.documents.len());
+
+        if let Some(next) = self.documents.get_mut(index) {
+            next.ensure_start_marker();
+        }
+
+        document.ensure_start_marker();
+        self.documents.insert(index, document);
+    }
+
+    /// Remove the document at `index`, returning it if it exists.
+    ///
+    /// The header of the removed document, such as its `---` marker, is
+    /// removed along with it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut stream = yaml::stream_from_slice("a: 1\n---\nb: 2\n--- # last\nc: 3\n")?;
+    ///
+    /// let doc = stream.remove(1).context("missing document")?;
+    /// assert_eq!(doc.to_string(), "---\nb: 2\n");
+    /// assert_eq!(stream.to_string(), "a: 1\n--- # last\nc: 3\n");
+    ///
+    /// assert!(stream.remove(0).is_some());
+    /// assert_eq!(stream.to_string(), "--- # last\nc: 3\n");
+    /// assert!(stream.remove(1).is_none());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn remove(&mut self, index: usize) -> Option<Document> {
+        if index >= self.documents.len() {
+            return None;
+        }
+
+        Some(self.documents.remove(index))
+    }
+
+    /// Write the bytes of the stream to the given `output`.
+    ///
+    /// # Errors
+    ///
+    /// Raises an I/O error if the underlying resource being written to raises
+    /// it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let stream = yaml::stream_from_slice("a\n---\nb\n")?;
+    ///
+    /// let mut out = Vec::new();
+    /// stream.write_to(&mut out)?;
+    /// assert_eq!(out, b"a\n---\nb\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn write_to<O>(&self, mut output: O) -> io::Result<()>
+    where
+        O: io::Write,
+    {
+        let mut buf = Vec::new();
+        let mut line_start = true;
+
+        for document in &self.documents {
+            buf.clear();
+            document.write_to(&mut buf)?;
+
+            // Headers of documents have to start on a new line.
+            if !line_start && document.has_header() {
+                output.write_all(&[raw::NEWLINE])?;
+            }
+
+            output.write_all(&buf)?;
+            line_start = buf.last().map_or(line_start, |&b| b == raw::NEWLINE);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Stream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf).map_err(|_| fmt::Error)?;
+        BStr::new(&buf).fmt(f)
+    }
+}
+
+/// Parse a stream of documents using the configuration of `parser`.
+pub(crate) fn parse(parser: &Parser<'_>, input: &[u8]) -> Result<Stream, Error> {
+    let mut documents = Vec::new();
+
+    for range in split(input) {
+        let document = parser
+            .with_input(input.get(range.clone()).unwrap_or_default())
+            .parse()
+            .map_err(|error| error.offset(range.start))?;
+
+        documents.push(document);
+    }
+
+    Ok(Stream { documents })
+}
+
+/// Split `input` into the ranges of documents, including their headers.
+///
+/// Since `---` can't start a line in the content of a document, every line
+/// starting with one followed by whitespace starts a new document.
+fn split(input: &[u8]) -> Vec<Range<usize>> {
+    let markers = markers(input, START).collect::<Vec<_>>();
+    let mut documents = Vec::new();
+    let mut header = 0;
+
+    // The first document only has a header if nothing but comments and
+    // directives precede its marker.
+    match markers.first() {
+        Some(&marker) if is_preamble(input.get(..marker).unwrap_or_default()) => {}
+        first => {
+            header = first.map_or(input.len(), |&marker| header_start(input, 0, marker));
+            documents.push(0..header);
+        }
+    }
+
+    for (n, &marker) in markers.iter().enumerate() {
+        let body = marker + START.len();
+
+        let end = markers
+            .get(n + 1)
+            .map_or(input.len(), |&next| header_start(input, body, next));
+
+        documents.push(header..end);
+        header = end;
+    }
+
+    documents
+}
+
+/// Find where the header of the document whose marker is at `marker` starts,
+/// given that the body of the previous document starts at `body`.
+///
+/// Directives and comments following an explicit `...` end marker of the
+/// previous document belong to the header.
+pub(crate) fn header_start(input: &[u8], body: usize, marker: usize) -> usize {
+    let Some(end) = markers(input.get(body..marker).unwrap_or_default(), END).next() else {
+        return marker;
+    };
+
+    let end = body + end;
+
+    let start = input
+        .get(end..marker)
+        .unwrap_or_default()
+        .find_byte(raw::NEWLINE)
+        .map_or(marker, |n| end + n + 1);
+
+    if is_preamble(input.get(start..marker).unwrap_or_default()) {
+        start
+    } else {
+        marker
+    }
+}
+
+/// Iterate over the positions of lines in `input` which start with `marker`,
+/// followed by whitespace or the end of input.
+pub(crate) fn markers<'a>(input: &'a [u8], marker: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    let mut next = Some(0);
+
+    iter::from_fn(move || loop {
+        let start = next?;
+        let line = input.get(start..)?;
+        next = line.find_byte(raw::NEWLINE).map(|n| start + n + 1);
+
+        if is_marker(line, marker) {
+            return Some(start);
+        }
+    })
+}
+
+/// Test if `line` starts with `marker`, followed by whitespace or the end of
+/// input.
+pub(crate) fn is_marker(line: &[u8], marker: &[u8]) -> bool {
+    line.starts_with(marker) && line.get(marker.len()).map_or(true, u8::is_ascii_whitespace)
+}
+
+/// Test if the input only consists of blank lines, comments and directives.
+pub(crate) fn is_preamble(input: &[u8]) -> bool {
+    input.lines().all(|line| {
+        let line = line.trim_start();
+        line.is_empty() || line.starts_with(b"#") || line.starts_with(b"%")
+    })
+}
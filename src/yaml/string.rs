@@ -1,7 +1,8 @@
 use core::fmt;
 use core::ops::Deref;
+use std::borrow::Cow;
 
-use bstr::BStr;
+use bstr::{BStr, ByteSlice};
 
 use crate::yaml::data::Data;
 use crate::yaml::raw;
@@ -11,15 +12,15 @@ use crate::yaml::raw;
 /// The string is accessed through a [`BStr`] coercion, since strings might
 /// contain non-utf8 data.
 ///
-/// Use utilities such as [`bstr::ByteSlice::to_str`] to coerce it into a
-/// [`str`].
+/// Use utilities such as [`ByteSlice::to_str`] to coerce it into a [`str`].
+///
+/// [`ByteSlice::to_str`]: crate::ByteSlice::to_str
 ///
 /// # Examples
 ///
 /// ```
 /// use anyhow::Context;
-/// use bstr::ByteSlice;
-/// use nondestructive::yaml;
+/// use nondestructive::{yaml, ByteSlice};
 ///
 /// let a = yaml::from_slice(r#""Hello\n World""#)?;
 /// let a = a.as_ref();
@@ -69,6 +70,45 @@ impl<'a> String<'a> {
     pub fn as_raw(&self) -> &BStr {
         self.data.str(self.raw.original)
     }
+
+    /// Get the contents of the string as a byte slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice(r#""Hello\n World""#)?;
+    /// let a = a.as_ref().into_any().into_string().context("expected string")?;
+    /// assert_eq!(a.as_bytes(), b"Hello\n World");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data.str(self.raw.id).as_ref()
+    }
+
+    /// Get the contents of the string as a [`str`], replacing any invalid
+    /// UTF-8 sequences with the replacement character `U+FFFD`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice(b"\"caf\xe9\"")?;
+    /// let a = a.as_ref().into_any().into_string().context("expected string")?;
+    /// assert_eq!(a.as_str_lossy(), "caf\u{fffd}");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn as_str_lossy(&self) -> Cow<'a, str> {
+        self.data.str(self.raw.id).to_str_lossy()
+    }
 }
 
 impl Deref for String<'_> {
@@ -0,0 +1,194 @@
+//! Support for the [`assert_roundtrip!`] and [`assert_doc_eq!`] macros,
+//! enabled through the `test-support` feature.
+//!
+//! [`assert_roundtrip!`]: crate::yaml::assert_roundtrip
+//! [`assert_doc_eq!`]: crate::yaml::assert_doc_eq
+
+use core::fmt::Write;
+
+use bstr::ByteSlice;
+
+use crate::yaml::diff::{self, Op};
+use crate::yaml::Document;
+
+/// Assert that the given input is written back byte-for-byte after being
+/// parsed.
+///
+/// Accepts anything which implements `AsRef<[u8]>`. Panics if the input can't
+/// be parsed, or with a unified diff if the output differs from the input.
+///
+/// An optional format string can be provided after the input, which is
+/// included in the panic message.
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// yaml::assert_roundtrip!("a: 1 # one\nb: [2, 3]\n");
+/// yaml::assert_roundtrip!(b"name: Ren\xe9\n", "latin-1 input");
+/// ```
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __yaml_assert_roundtrip {
+    ($input:expr $(,)?) => {
+        $crate::yaml::testing::roundtrip($input, ::core::option::Option::None)
+    };
+
+    ($input:expr, $($arg:tt)+) => {
+        $crate::yaml::testing::roundtrip(
+            $input,
+            ::core::option::Option::Some(::core::format_args!($($arg)+)),
+        )
+    };
+}
+
+/// Assert that a [`Document`] is written out as the expected string.
+///
+/// Accepts anything which implements `AsRef<[u8]>` as the expected output, and
+/// compares it against what [`Document::write_to`] produces. On failure, panics
+/// with a unified diff from the expected to the actual document.
+///
+/// An optional format string can be provided after the expected output, which
+/// is included in the panic message.
+///
+/// [`Document`]: crate::yaml::Document
+/// [`Document::write_to`]: crate::yaml::Document::write_to
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Context;
+/// use nondestructive::yaml;
+///
+/// let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+/// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+/// root.insert_u32("c", 3);
+///
+/// yaml::assert_doc_eq!(doc, "a: 1\nb: 2\nc: 3\n");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[macro_export]
+#[doc(hidden)]
+macro_rules! __yaml_assert_doc_eq {
+    ($doc:expr, $expected:expr $(,)?) => {
+        $crate::yaml::testing::doc_eq(&$doc, $expected, ::core::option::Option::None)
+    };
+
+    ($doc:expr, $expected:expr, $($arg:tt)+) => {
+        $crate::yaml::testing::doc_eq(
+            &$doc,
+            $expected,
+            ::core::option::Option::Some(::core::format_args!($($arg)+)),
+        )
+    };
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn roundtrip<I>(input: I, message: Option<core::fmt::Arguments<'_>>)
+where
+    I: AsRef<[u8]>,
+{
+    let input = input.as_ref();
+
+    let doc = match crate::yaml::from_slice(input) {
+        Ok(doc) => doc,
+        Err(error) => fail(format_args!("input failed to parse: {error}"), message),
+    };
+
+    doc_eq(&doc, input, message);
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn doc_eq<E>(doc: &Document, expected: E, message: Option<core::fmt::Arguments<'_>>)
+where
+    E: AsRef<[u8]>,
+{
+    let expected = expected.as_ref();
+
+    let mut actual = Vec::new();
+
+    if let Err(error) = doc.write_to(&mut actual) {
+        fail(format_args!("document failed to write: {error}"), message);
+    }
+
+    if actual == expected {
+        return;
+    }
+
+    fail(
+        format_args!(
+            "document differs from expected\n{}",
+            diff(expected, &actual)
+        ),
+        message,
+    );
+}
+
+#[allow(clippy::panic)]
+#[track_caller]
+fn fail(reason: core::fmt::Arguments<'_>, message: Option<core::fmt::Arguments<'_>>) -> ! {
+    match message {
+        Some(message) => panic!("assertion failed: {message}: {reason}"),
+        None => panic!("assertion failed: {reason}"),
+    }
+}
+
+/// Produce a unified diff from `expected` to `actual`.
+///
+/// Lines are compared including their line endings, so a missing trailing
+/// newline or a `\r\n` which has turned into `\n` shows up as a changed line.
+/// Line endings and other control characters are escaped in the output to make
+/// such differences visible.
+pub(crate) fn diff(expected: &[u8], actual: &[u8]) -> String {
+    let mut out = String::new();
+    out.push_str("--- expected\n+++ actual\n");
+
+    for hunk in diff::hunks(expected, actual) {
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            hunk.a_start(),
+            hunk.a.len(),
+            hunk.b_start(),
+            hunk.b.len(),
+        );
+
+        for (op, line) in hunk.lines {
+            out.push(match op {
+                Op::Keep => ' ',
+                Op::Remove => '-',
+                Op::Add => '+',
+            });
+
+            line_to(&mut out, line);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Write a single line, escaping its line ending and any other control
+/// characters.
+fn line_to(out: &mut String, line: &[u8]) {
+    for (start, end, c) in line.char_indices() {
+        match c {
+            '\u{fffd}' if line.get(start..end) != Some("\u{fffd}".as_bytes()) => {
+                for b in line.get(start..end).unwrap_or_default() {
+                    let _ = write!(out, "\\x{b:02x}");
+                }
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{{{:x}}}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+}
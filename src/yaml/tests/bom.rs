@@ -0,0 +1,78 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+fn utf16(bom: yaml::Bom, string: &str) -> Vec<u8> {
+    let mut out = bom.as_bytes().to_vec();
+
+    for unit in string.encode_utf16() {
+        match bom {
+            yaml::Bom::Utf16Be => out.extend_from_slice(&unit.to_be_bytes()),
+            _ => out.extend_from_slice(&unit.to_le_bytes()),
+        }
+    }
+
+    out
+}
+
+#[test]
+fn utf8_bom() -> Result<()> {
+    const INPUT: &[u8] = b"\xef\xbb\xbffirst: 1\nsecond: 2\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.bom(), Some(yaml::Bom::Utf8));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.as_ref().get("first").is_some());
+    root.insert_u32("third", 3);
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    assert_eq!(out, b"\xef\xbb\xbffirst: 1\nsecond: 2\nthird: 3\n");
+    assert_eq!(doc.to_string().as_bytes(), &out[..]);
+    Ok(())
+}
+
+#[test]
+fn utf16_bom() -> Result<()> {
+    for bom in [yaml::Bom::Utf16Le, yaml::Bom::Utf16Be] {
+        let input = utf16(bom, "crab: 🦀\nlist:\n  - å\n");
+
+        let mut doc = yaml::from_slice(&input)?;
+        assert_eq!(doc.bom(), Some(bom));
+
+        let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+        assert_eq!(
+            root.as_ref().get("crab").and_then(|v| v.as_str()),
+            Some("🦀")
+        );
+
+        let mut list = root
+            .get_mut("list")
+            .and_then(|v| v.into_sequence_mut())
+            .context("missing list")?;
+        list.push_string("ä");
+
+        let mut out = Vec::new();
+        doc.write_to(&mut out)?;
+        assert_eq!(out, utf16(bom, "crab: 🦀\nlist:\n  - å\n  - ä\n"));
+        assert_eq!(doc.to_string(), "\u{feff}crab: 🦀\nlist:\n  - å\n  - ä\n");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bom_errors() {
+    let expected = yaml::from_slice("a: [1, 2\n").unwrap_err();
+    let error = yaml::from_slice("\u{feff}a: [1, 2\n").unwrap_err();
+    assert_eq!(*error.kind(), *expected.kind());
+    assert_eq!(
+        error.span(),
+        expected.span().start + 3..expected.span().end + 3
+    );
+
+    let error = yaml::from_slice(b"\xfe\xff\x00a\x00").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadUtf16);
+    assert_eq!(error.span(), 4..5);
+}
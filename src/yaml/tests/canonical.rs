@@ -0,0 +1,107 @@
+use anyhow::Result;
+
+use crate::yaml::{self, KeyOrder};
+
+fn canonical(input: &str) -> Result<String> {
+    Ok(yaml::from_slice(input)?.to_canonical_string())
+}
+
+#[test]
+fn aliases() -> Result<()> {
+    assert_eq!(canonical("a: &x 1\nb: *x\n")?, "a: 1\nb: 1\n");
+    assert_eq!(
+        canonical("a: &x {k: [1, 2]}\nb: *x\n")?,
+        "a:\n  k:\n    - 1\n    - 2\nb:\n  k:\n    - 1\n    - 2\n"
+    );
+    assert_eq!(canonical("- &x one\n- [*x]\n")?, "- one\n- - one\n");
+
+    // The closest preceding anchor is used.
+    assert_eq!(
+        canonical("a: &x 1\nb: &x 2\nc: *x\n")?,
+        "a: 1\nb: 2\nc: 2\n"
+    );
+
+    // Expanded aliases are structurally equal to their values.
+    assert_eq!(
+        canonical("a: &x {k: v}\nb: *x\n")?,
+        canonical("a: {k: v}\nb: {k: v}\n")?
+    );
+    Ok(())
+}
+
+#[test]
+fn unresolved_aliases() -> Result<()> {
+    assert_eq!(canonical("a: *missing\n")?, "a: *missing\n");
+    assert_eq!(canonical("a: &x [1, *x]\n")?, "a:\n  - 1\n  - *x\n");
+    Ok(())
+}
+
+#[test]
+fn whitespace() -> Result<()> {
+    assert_eq!(canonical("c: ' lead'\n")?, "c: \" lead\"\n");
+    assert_eq!(canonical("c: 'trail '\n")?, "c: \"trail \"\n");
+    assert_eq!(canonical("c: \"\\ttab\"\n")?, "c: \"\\ttab\"\n");
+    assert_eq!(canonical("c: \"tab\\t\"\n")?, "c: \"tab\\t\"\n");
+    assert_eq!(canonical("' k': v\n")?, "\" k\": v\n");
+    assert_eq!(canonical("c: 'in between'\n")?, "c: in between\n");
+    Ok(())
+}
+
+#[test]
+fn scalars() -> Result<()> {
+    assert_eq!(canonical("a: ''\n")?, "a: \"\"\n");
+    assert_eq!(canonical("a: 'null'\n")?, "a: \"null\"\n");
+    assert_eq!(canonical("a: 'True'\n")?, "a: \"True\"\n");
+    assert_eq!(canonical("a: '-x'\n")?, "a: \"-x\"\n");
+    assert_eq!(canonical("a: 'x: y'\n")?, "a: \"x: y\"\n");
+    assert_eq!(canonical("a: 1.50\n")?, "a: 1.5\n");
+    assert_eq!(canonical("a: -0.0\n")?, "a: 0.0\n");
+    assert_eq!(canonical("a: 1e3\n")?, "a: 1000.0\n");
+    assert_eq!(canonical("a: .inf\n")?, "a: .inf\n");
+    Ok(())
+}
+
+#[test]
+fn big_numbers() -> Result<()> {
+    assert_eq!(
+        canonical("a: 340282366920938463463374607431768211456\n")?,
+        "a: 340282366920938463463374607431768211456\n"
+    );
+    assert_eq!(
+        canonical("a: -340282366920938463463374607431768211457\n")?,
+        "a: -340282366920938463463374607431768211457\n"
+    );
+    assert_eq!(
+        canonical("a: 0.10000000000000000000001\n")?,
+        "a: 0.10000000000000000000001\n"
+    );
+    assert_eq!(
+        canonical("a: 1.00000000000000000000001e400\n")?,
+        "a: 1.00000000000000000000001e400\n"
+    );
+    assert_eq!(canonical("a: 1e400\n")?, "a: 1e400\n");
+
+    // Equal numbers have the same canonical form.
+    assert_eq!(
+        canonical("a: 12.000000000000000000001e1\n")?,
+        canonical("a: 120.00000000000000000001\n")?
+    );
+    assert_eq!(canonical("a: ~\nb:\nc: 1\n")?, "a: null\nb: null\nc: 1\n");
+    assert_eq!(canonical("a: \"\\xe9\"\n")?, "a: é\n");
+    Ok(())
+}
+
+#[test]
+fn collections() -> Result<()> {
+    assert_eq!(canonical("{}")?, "{}\n");
+    assert_eq!(canonical("- []\n- {}\n")?, "- []\n- {}\n");
+    assert_eq!(canonical("- - 1\n  - 2\n")?, "- - 1\n  - 2\n");
+    assert_eq!(canonical("- {a: 1, b: 2}\n")?, "- a: 1\n  b: 2\n");
+
+    let doc = yaml::from_slice("b: 1\na: {d: 2, c: 3}\n")?;
+    assert_eq!(
+        doc.to_canonical_string_with(KeyOrder::Sorted),
+        "a:\n  c: 3\n  d: 2\nb: 1\n"
+    );
+    Ok(())
+}
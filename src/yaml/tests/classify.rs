@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+use crate::yaml;
+use crate::yaml::classify::Classifier;
+
+#[test]
+fn classify() -> Result<()> {
+    let doc = yaml::from_slice(
+        "%YAML 1.2\n---\njobs:\n  build:\n    steps:\n      - run: |\n          make\n      - run: 'echo hi'\n        env: {A: 1}\n  lint:\n    steps: [{run: clippy}]\n",
+    )?;
+
+    let classifier = Classifier::new()
+        .register(&["jobs", "lint", "steps", "*", "run"], "lint")
+        .register(&["jobs", "*", "steps", "*", "run"], "shell")
+        .register(&["jobs", "*", "steps", "*", "env"], "env");
+
+    let text = doc.to_string();
+    let tokens = classifier.classify(&doc);
+
+    let tokens = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.path().join("."),
+                token.class(),
+                &text[token.span()],
+                doc.value(token.id()).as_str(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        tokens,
+        [
+            (
+                "jobs.build.steps.0.run".to_owned(),
+                "shell",
+                "|\n          make",
+                Some("make\n")
+            ),
+            (
+                "jobs.build.steps.1.run".to_owned(),
+                "shell",
+                "'echo hi'",
+                Some("echo hi")
+            ),
+            (
+                "jobs.lint.steps.0.run".to_owned(),
+                "lint",
+                "clippy",
+                Some("clippy")
+            ),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn eol_comment_survives_set() -> Result<()> {
+    let cases = [
+        ("version: 1 # keep\nname: app\n", "/version"),
+        ("a:\n  version: 1   # keep\n  b: 2\n", "/a/version"),
+        ("- 1 # keep\n- 2\n", "/0"),
+        ("1 # keep\n", ""),
+        ("a: {b: 1 # keep\n}\n", "/a/b"),
+        ("a: x # keep\r\nb: 2\r\n", "/a"),
+        ("a: \"x\" # keep\n", "/a"),
+    ];
+
+    for (input, pointer) in cases {
+        let mut doc = yaml::from_slice(input)?;
+        let id = doc.resolve_json_pointer(pointer).context("missing value")?;
+
+        doc.value_mut(id).set_u32(2);
+        let expected = input.replacen("1 ", "2 ", 1);
+        let expected = expected.replacen("x ", "2 ", 1);
+        let expected = expected.replacen("\"x\" ", "2 ", 1);
+        assert_eq!(doc.to_string(), expected, "{input:?}");
+
+        doc.value_mut(id).set_string("a longer value");
+        let output = doc.to_string();
+        let line = output.lines().find(|line| line.contains("a longer value"));
+        assert!(
+            line.is_some_and(|line| line.ends_with("# keep")),
+            "{input:?}: {output:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn eol_comment_not_part_of_value() -> Result<()> {
+    let doc = yaml::from_slice("a: 1 # one\nb: x#y # two\nc: x #: y\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("x#y"));
+    assert_eq!(root.get("c").and_then(|v| v.as_str()), Some("x"));
+    assert_eq!(root.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn eol_comment_survives_remove() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1 # c\nb: 2 # d\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove("b"));
+    assert_eq!(doc.to_string(), "a: 1 # c\n");
+
+    let mut doc = yaml::from_slice("a: 1 # c\nb: 2 # d\ne: 3\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove("b"));
+    assert_eq!(doc.to_string(), "a: 1 # c\ne: 3\n");
+
+    let mut doc = yaml::from_slice("- a # x\n- b\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(root.remove(1));
+    assert_eq!(doc.to_string(), "- a # x\n");
+
+    let mut doc = yaml::from_slice("- a # x\n- b # y\n- c\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(root.remove(1));
+    assert_eq!(doc.to_string(), "- a # x\n- c\n");
+    Ok(())
+}
+
+#[test]
+fn eol_comment_stays_on_its_line() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1 # c\r\nb: 2 # d\r\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("e", 3);
+    assert_eq!(doc.to_string(), "a: 1 # c\r\nb: 2 # d\r\ne: 3\r\n");
+
+    let mut doc = yaml::from_slice("- |\n  x\n# own line\n- y # z\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.push_u32(3);
+    assert_eq!(doc.to_string(), "- |\n  x\n# own line\n- y # z\n- 3\n");
+    Ok(())
+}
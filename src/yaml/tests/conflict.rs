@@ -0,0 +1,54 @@
+use anyhow::Result;
+
+use crate::yaml;
+use crate::yaml::conflict::Side;
+
+#[test]
+fn conflict_diff3() -> Result<()> {
+    const INPUT: &str = "name: app\n<<<<<<< HEAD\ntags: [a, b]\n||||||| base\ntags: [a]\n=======\ntags: [a, c]\n>>>>>>> feature\nport: 80\n<<<<<<< HEAD\ndebug: true\n=======\n>>>>>>> feature\n";
+
+    let mut conflicted = yaml::conflict::parse(INPUT)?;
+    assert_eq!(conflicted.regions().len(), 2);
+
+    let region = &conflicted.regions()[0];
+    assert_eq!(region.ours_label(), "HEAD");
+    assert_eq!(region.theirs_label(), "feature");
+    assert_eq!(region.ours(), b"tags: [a, b]\n");
+    assert_eq!(region.base(), Some(&b"tags: [a]\n"[..]));
+    assert_eq!(region.theirs(), b"tags: [a, c]\n");
+    assert_eq!(region.span(), 10..96);
+
+    let region = &conflicted.regions()[1];
+    assert_eq!(region.base(), None);
+    assert_eq!(region.theirs(), b"");
+
+    assert!(!conflicted.is_resolved());
+    let error = conflicted.to_bytes().unwrap_err();
+    assert_eq!(error.span(), conflicted.regions()[0].span());
+
+    conflicted.regions_mut()[0].choose(Side::Base);
+    conflicted.regions_mut()[1].choose(Side::Theirs);
+    assert!(conflicted.is_resolved());
+
+    let doc = conflicted.to_document()?;
+    assert_eq!(doc.to_string(), "name: app\ntags: [a]\nport: 80\n");
+
+    conflicted.choose_all(Side::Both);
+    assert_eq!(
+        conflicted.to_bytes()?,
+        b"name: app\ntags: [a, b]\ntags: [a, c]\nport: 80\ndebug: true\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn conflict_none() -> Result<()> {
+    let conflicted = yaml::conflict::parse("a: 1\n=======\n>>>>>>> x\n")?;
+    assert!(conflicted.regions().is_empty());
+    assert!(conflicted.is_resolved());
+    assert_eq!(conflicted.to_bytes()?, b"a: 1\n=======\n>>>>>>> x\n");
+
+    let error = yaml::conflict::parse("<<<<<<< HEAD\na: 1\n=======\na: 2\n").unwrap_err();
+    assert_eq!(error.span(), 0..31);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn crlf_scalars() -> Result<()> {
+    let doc = yaml::from_slice("a: true\r\nb: ~\r\nc: plain text\r\nd: |\r\n  hello\r\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(root.get("a").and_then(|v| v.as_bool()), Some(true));
+    assert!(matches!(
+        root.get("b").map(|v| v.into_any()),
+        Some(yaml::Any::Null)
+    ));
+    assert_eq!(root.get("c").and_then(|v| v.as_str()), Some("plain text"));
+    assert_eq!(root.get("d").and_then(|v| v.as_str()), Some("hello\n"));
+    Ok(())
+}
+
+#[test]
+fn crlf_insertions() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\r\nb:\r\n  - x\r\nc: |\r\n  hello\r\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("d", 4);
+    root.insert_block("e", ["x", "y"], yaml::Block::Literal(yaml::Chomp::Clip));
+    root.insert("f", yaml::Separator::Auto)?
+        .make_mapping()?
+        .insert_u32("g", 1);
+
+    root.get_mut("b")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing b")?
+        .push_string("y");
+
+    assert!(root
+        .get_mut("c")
+        .context("missing c")?
+        .push_block_line("world"));
+    assert!(root.get_mut("e").context("missing e")?.push_block_line("z"));
+
+    assert_eq!(
+        doc.to_string(),
+        "a: 1\r\nb:\r\n  - x\r\n  - y\r\nc: |\r\n  hello\r\n  world\r\nd: 4\r\ne: |\r\n  x\r\n  y\r\n  z\r\nf:\r\n  g: 1\r\n"
+    );
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("e").and_then(|v| v.as_str()), Some("x\ny\nz\n"));
+    Ok(())
+}
+
+#[test]
+fn mostly_lf() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\r\nb: 2\nc: 3\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("d", 4);
+
+    assert_eq!(doc.to_string(), "a: 1\r\nb: 2\nc: 3\nd: 4\n");
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use miette::Diagnostic;
+
+use crate::yaml;
+
+#[test]
+fn labels_and_help() {
+    let input = "tags: [a, b\n";
+    let error = yaml::from_slice(input).unwrap_err();
+
+    let labels = error.labels().into_iter().flatten().collect::<Vec<_>>();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels[0].inner().offset(), error.span().start);
+    assert_eq!(labels[0].len(), error.span().len());
+    assert_eq!(labels[0].label(), Some(error.kind().to_string().as_str()));
+
+    let help = error.help().map(|help| help.to_string());
+    assert_eq!(help.as_deref(), Some("add a closing `]` to the sequence"));
+
+    let report = miette::Report::new(error).with_source_code(input);
+    assert!(report.source_code().is_some());
+}
+
+#[test]
+fn without_suggestion() {
+    let error = yaml::from_slice("a: 1\n  b: 2\n").unwrap_err();
+    assert!(error.suggestion().is_none());
+    assert!(error.help().is_none());
+    assert!(error.labels().is_some());
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn separate_hunks() -> Result<()> {
+    let input = (1..=12).map(|n| format!("k{n}: {n}\n")).collect::<String>();
+
+    let mut doc = yaml::from_slice(&input)?;
+    doc.snapshot_original();
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("k2").context("missing k2")?.set_u32(20);
+    root.remove("k11");
+
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n\
+         @@ -1,5 +1,5 @@\n k1: 1\n-k2: 2\n+k2: 20\n k3: 3\n k4: 4\n k5: 5\n\
+         @@ -8,5 +8,4 @@\n k8: 8\n k9: 9\n k10: 10\n-k11: 11\n k12: 12\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn merged_hunks() -> Result<()> {
+    let input = (1..=9).map(|n| format!("k{n}: {n}\n")).collect::<String>();
+
+    let mut doc = yaml::from_slice(&input)?;
+    doc.snapshot_original();
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("k1").context("missing k1")?.set_u32(10);
+    root.get_mut("k8").context("missing k8")?.set_u32(80);
+
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n\
+         @@ -1,9 +1,9 @@\n-k1: 1\n+k1: 10\n k2: 2\n k3: 3\n k4: 4\n k5: 5\n k6: 6\n k7: 7\n-k8: 8\n+k8: 80\n k9: 9\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_newline() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: 2")?;
+    doc.snapshot_original();
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("b").context("missing b")?.set_u32(3);
+
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n\
+         @@ -1,2 +1,2 @@\n a: 1\n-b: 2\n\\ No newline at end of file\n+b: 3\n\\ No newline at end of file\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn empty_ranges() -> Result<()> {
+    let mut doc = yaml::from_slice("")?;
+    doc.snapshot_original();
+    doc.as_mut().set_u32(1);
+
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n@@ -0,0 +1 @@\n+1\n\\ No newline at end of file\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn utf16() -> Result<()> {
+    let mut input = b"\xff\xfe".to_vec();
+
+    for unit in "a: 1\n".encode_utf16() {
+        input.extend_from_slice(&unit.to_le_bytes());
+    }
+
+    let mut doc = yaml::from_slice(&input)?;
+    doc.snapshot_original();
+    assert_eq!(doc.diff_against_original(), "");
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("b", 2);
+
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n@@ -1 +1,2 @@\n a: 1\n+b: 2\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn without_snapshot() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    assert_eq!(
+        doc.diff_against_original(),
+        "--- original\n+++ modified\n@@ -0,0 +1 @@\n+a: 1\n"
+    );
+
+    doc.snapshot_original();
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("b", 2);
+    doc.snapshot_original();
+    assert_eq!(doc.diff_against_original(), "");
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn round_trip() -> Result<()> {
+    const CASES: &[&str] = &[
+        "---\na: 1\n",
+        "--- a",
+        "# comment\n---\na: 1\n",
+        "%YAML 1.2\n---\na: 1\n",
+        "%YAML 1.2 # version\n\n%TAG !e! tag:example.com,2000:\n--- !e!foo bar\n",
+    ];
+
+    for &input in CASES {
+        let doc = yaml::from_slice(input)?;
+        assert_eq!(doc.to_string(), input, "{input:?}");
+
+        let mut out = Vec::new();
+        doc.write_to(&mut out)?;
+        assert_eq!(out, input.as_bytes(), "{input:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn stream_directives() -> Result<()> {
+    let mut stream = yaml::stream_from_slice("%YAML 1.1\n---\na\n...\n%YAML 1.2\n---\nb\n")?;
+
+    let versions = stream
+        .iter()
+        .map(|doc| {
+            let directive = doc.directives().next()?;
+            Some(directive.parameters().next()?.to_string())
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(versions, [Some("1.1".into()), Some("1.2".into())]);
+
+    let doc = stream.get_mut(0).context("missing document")?;
+    doc.set_yaml_directive("1.2");
+    assert_eq!(
+        stream.to_string(),
+        "%YAML 1.2\n---\na\n...\n%YAML 1.2\n---\nb\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn set_and_remove() -> Result<()> {
+    let mut doc = yaml::from_slice("# comment\n---\na: 1\n")?;
+    doc.set_yaml_directive("1.2");
+    assert_eq!(doc.to_string(), "# comment\n%YAML 1.2\n---\na: 1\n");
+
+    doc.set_yaml_directive("1.1");
+    assert_eq!(doc.to_string(), "# comment\n%YAML 1.1\n---\na: 1\n");
+
+    assert!(doc.remove_yaml_directive());
+    assert_eq!(doc.to_string(), "# comment\n---\na: 1\n");
+
+    let mut doc = yaml::from_slice("%YAML\n---\na")?;
+    doc.set_yaml_directive("1.2");
+    assert_eq!(doc.to_string(), "%YAML 1.2\n---\na");
+
+    let mut doc = yaml::from_slice("a")?;
+    doc.set_yaml_directive("1.2");
+    assert_eq!(doc.to_string(), "%YAML 1.2\n---\na");
+    assert_eq!(
+        yaml::from_slice(doc.to_string())?.as_ref().as_str(),
+        Some("a")
+    );
+
+    assert!(doc.remove_yaml_directive());
+    assert_eq!(doc.to_string(), "---\na");
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anyhow::{Context, Result};
+
+use crate::yaml::{self, Document, Id};
+
+fn id(doc: &Document, key: &str) -> Result<Id> {
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    Ok(root.get(key).context("missing key")?.id())
+}
+
+#[test]
+fn refused() -> Result<()> {
+    let mut doc = yaml::from_slice("a: {b: 1}\nc: 2\n")?;
+    let (a, c) = (id(&doc, "a")?, id(&doc, "c")?);
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let b = root
+        .get("a")
+        .and_then(|v| v.as_mapping()?.get("b"))
+        .context("missing b")?
+        .id();
+    let root = root.id();
+
+    assert!(doc.values_mut_disjoint([a, b]).is_none());
+    assert!(doc.values_mut_disjoint([b, a]).is_none());
+    assert!(doc.values_mut_disjoint([root, c]).is_none());
+    assert!(doc.values_mut_disjoint([c, c]).is_none());
+    assert!(doc.values_mut_disjoint([b, c]).is_some());
+    assert!(doc.values_mut_disjoint([a]).is_some());
+    assert!(doc.values_mut_disjoint::<0>([]).is_some());
+
+    // Identifiers which don't refer to values.
+    let missing = Id::from_u64(1000).context("bad id")?;
+    assert!(doc.values_mut_disjoint([a, missing]).is_none());
+
+    let mut values = doc.values_mut_disjoint([a, c]).context("disjoint")?;
+    assert!(values.get_mut(2).is_none());
+    assert!(values.get(2).is_none());
+    assert!(!values.swap(0, 2));
+    assert!(!values.copy(2, 0));
+
+    // Locked values.
+    assert!(doc.lock(b, "human"));
+    doc.set_actor(Some("bot"));
+    assert!(doc.values_mut_disjoint([a, c]).is_none());
+    assert!(doc.values_mut_disjoint([c]).is_some());
+    assert_eq!(doc.to_string(), "a: {b: 1}\nc: 2\n");
+    Ok(())
+}
+
+#[test]
+fn edit_in_turn() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: [x]\nc: {k: v}\n")?;
+    let ids = [id(&doc, "a")?, id(&doc, "b")?, id(&doc, "c")?];
+
+    let mut values = doc.values_mut_disjoint(ids).context("disjoint")?;
+    assert_eq!(values.ids(), ids);
+
+    values.get_mut(0).context("a")?.set_u32(2);
+    values
+        .get_mut(1)
+        .and_then(|v| v.into_sequence_mut())
+        .context("b")?
+        .push_string("y");
+    values.get_mut(2).context("c")?.set_string("z");
+    assert_eq!(values.get(0).and_then(|v| v.as_u32()), Some(2));
+
+    assert!(values.copy(1, 2));
+    assert!(values.swap(0, 1));
+    assert!(values.swap(2, 2));
+    assert!(values.copy(0, 0));
+
+    assert_eq!(doc.to_string(), "a: [x, y]\nb: 2\nc: [x, y]\n");
+    Ok(())
+}
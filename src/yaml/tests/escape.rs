@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::yaml;
 
@@ -20,10 +20,58 @@ fn double_quotes() -> Result<()> {
     test!("\"a \\f b\"", "a \x0c b");
     test!("\"a \\r b\"", "a \r b");
     test!("\"a \\e b\"", "a \x1b b");
-    test!("\"a \\\\ b\"", "a \" b");
+    test!("\"a \\\\ b\"", "a \\ b");
+    test!("\"a \\\" b\"", "a \" b");
+    test!("\"a \\/ b\"", "a / b");
+    test!("\"a \\  b\"", "a   b");
+    test!("\"a \\\t b\"", "a \t b");
+    test!("\"a \\N b\"", "a \u{85} b");
+    test!("\"a \\_ b\"", "a \u{a0} b");
+    test!("\"a \\L b\"", "a \u{2028} b");
+    test!("\"a \\P b\"", "a \u{2029} b");
 
     test!("\"a \\x77 b\"", "a \x77 b");
     test!("\"a \\u79c1 b\"", "a 私 b");
+    test!("\"a \\U0001f980 b\"", "a 🦀 b");
+    Ok(())
+}
+
+#[test]
+fn double_quotes_round_trip() -> Result<()> {
+    const CASES: &[(&str, &str)] = &[
+        ("\0", "\"\\0\""),
+        ("\x07", "\"\\a\""),
+        ("\x08", "\"\\b\""),
+        ("\t", "\"\\t\""),
+        ("\n", "\"\\n\""),
+        ("\x0b", "\"\\v\""),
+        ("\x0c", "\"\\f\""),
+        ("\r", "\"\\r\""),
+        ("\x1b", "\"\\e\""),
+        ("\"", "\"\\\"\""),
+        ("\\", "\"\\\\\""),
+        ("\u{85}", "\"\\N\""),
+        ("\u{a0}", "\"\\_\""),
+        ("\u{2028}", "\"\\L\""),
+        ("\u{2029}", "\"\\P\""),
+        ("\x01", "\"\\x01\""),
+        ("a/b c", "\"a/b c\""),
+    ];
+
+    for &(string, expected) in CASES {
+        let mut doc = yaml::from_slice("")?;
+        doc.as_mut()
+            .set_string_with(string, yaml::StringKind::Double);
+
+        let mut out = Vec::new();
+        doc.write_to(&mut out)?;
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(out, expected.as_bytes());
+
+        let doc = yaml::from_slice(expected)?;
+        assert_eq!(doc.as_ref().as_str(), Some(string));
+    }
+
     Ok(())
 }
 
@@ -33,3 +81,197 @@ fn single_quotes() -> Result<()> {
     test!("'It''s a party!'", "It's a party!");
     Ok(())
 }
+
+#[test]
+fn detected_quotes_round_trip() -> Result<()> {
+    const CASES: &[(&str, &str)] = &[
+        ("C:\\Users\\new", "C:\\Users\\new"),
+        ("\\\\server\\share\\n", "\\\\server\\share\\n"),
+        ("C:\\it's", "C:\\it's"),
+        ("'C:\\temp'", "'''C:\\temp'''"),
+        ("'quoted'", "\"'quoted'\""),
+        ("it's", "it's"),
+        ("a #b", "'a #b'"),
+        ("a:b", "a:b"),
+        ("a: b", "'a: b'"),
+        ("&x", "'&x'"),
+        ("*x", "'*x'"),
+        ("- a", "'- a'"),
+        ("-a", "-a"),
+        ("[a]", "'[a]'"),
+        ("-1", "'-1'"),
+        ("True", "'True'"),
+        ("~", "'~'"),
+        ("", "''"),
+        (" a", "' a'"),
+        ("tab\there", "\"tab\\there\""),
+    ];
+
+    for &(string, expected) in CASES {
+        let mut doc = yaml::from_slice("")?;
+        doc.as_mut().set_string(string);
+        assert_eq!(doc.to_string(), expected, "{string:?}");
+
+        let doc = yaml::from_slice(expected)?;
+        assert_eq!(doc.as_ref().as_str(), Some(string), "{expected:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn lenient_escapes() -> Result<()> {
+    const INPUT: &[u8] = b"[\"a \\q b\", \"\\\xc3\xa5\"]";
+
+    let (doc, warnings) = yaml::Parser::new(INPUT)
+        .lenient_escapes(true)
+        .parse_with_warnings()?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("a \\q b"));
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("\\\u{e5}"));
+    assert_eq!(doc.to_string().as_bytes(), INPUT);
+
+    let spans = warnings.iter().map(yaml::Error::span).collect::<Vec<_>>();
+    assert_eq!(spans, [4..6, 12..14]);
+
+    let error = yaml::Parser::new(b"\"\\xZZ\"")
+        .lenient_escapes(true)
+        .parse()
+        .unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadHexEscape);
+    Ok(())
+}
+
+#[test]
+fn surrogates() -> Result<()> {
+    test!("\"\\uD83E\\uDD80\"", "🦀");
+    test!("\"\\U0001F980\"", "🦀");
+
+    const ERRORS: &[(&str, &str, yaml::ErrorKind)] = &[
+        (
+            "\"a \\ud800\"",
+            "\\ud800",
+            yaml::ErrorKind::UnpairedSurrogate,
+        ),
+        (
+            "\"a \\udc00 b\"",
+            "\\udc00",
+            yaml::ErrorKind::UnpairedSurrogate,
+        ),
+        (
+            "\"\\ud83e\\ud83e\"",
+            "\\ud83e",
+            yaml::ErrorKind::UnpairedSurrogate,
+        ),
+        (
+            "\"\\ud83e\\udz80\"",
+            "\\udz",
+            yaml::ErrorKind::BadUnicodeEscape,
+        ),
+        (
+            "\"\\U0000d800\"",
+            "\\U0000d800",
+            yaml::ErrorKind::BadUnicodeEscape,
+        ),
+        (
+            "\"\\U0001f98\"",
+            "\\U0001f98\"",
+            yaml::ErrorKind::BadUnicodeEscape,
+        ),
+    ];
+
+    for (input, span, kind) in ERRORS {
+        let error = yaml::from_slice(input).unwrap_err();
+        assert_eq!(error.kind(), kind, "{input}");
+        assert_eq!(&input[error.span()], *span, "{input}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn unterminated_strings() {
+    for input in [
+        "a: 'hello",
+        "a: 'it''s",
+        "a: \"hello",
+        "a: \"hello\\n",
+        "- 'a\n- b\n",
+    ] {
+        let error = yaml::from_slice(input).unwrap_err();
+        assert_eq!(
+            *error.kind(),
+            yaml::ErrorKind::UnterminatedString,
+            "{input:?}"
+        );
+        assert_eq!(
+            &input[error.span()],
+            &input[input.find(['\'', '"']).unwrap_or_default()..]
+        );
+        assert!(error.suggestion().is_some());
+    }
+}
+
+#[test]
+fn configured_quoting() -> Result<()> {
+    const CASES: &[(&str, &str)] = &[
+        ("1.0", "\"1.0\""),
+        ("v1.2.3", "\"v1.2.3\""),
+        ("it's", "it's"),
+        ("'quoted'", "\"'quoted'\""),
+        ("C:\\ #1", "'C:\\ #1'"),
+        ("say \"hi\": now", "'say \"hi\": now'"),
+        ("tab\there", "\"tab\\there\""),
+        ("plain", "plain"),
+    ];
+
+    let mut doc = yaml::from_slice("")?;
+
+    doc.set_quoting(
+        yaml::Quoting::new()
+            .prefer(yaml::QuoteStyle::Double)
+            .always_quote(|s| {
+                s.starts_with('v') && s[1..].starts_with(|c: char| c.is_ascii_digit())
+            }),
+    );
+
+    for &(string, expected) in CASES {
+        doc.as_mut().set_string(string);
+        assert_eq!(doc.to_string(), expected, "{string:?}");
+
+        let reparsed = yaml::from_slice(expected)?;
+        assert_eq!(reparsed.as_ref().as_str(), Some(string), "{expected:?}");
+    }
+
+    doc.set_quoting(yaml::Quoting::new());
+    doc.as_mut().set_string("1.0");
+    assert_eq!(doc.to_string(), "'1.0'");
+    Ok(())
+}
+
+#[test]
+fn escaped_line_breaks() -> Result<()> {
+    const CASES: &[(&str, &str)] = &[
+        ("\"a\\\nb\"", "ab"),
+        ("\"a \\\n    b\"", "a b"),
+        ("\"a\\\r\n \tb\"", "ab"),
+        ("\"a\\\n\\ b\"", "a b"),
+        ("\"a\\\n\"", "a"),
+    ];
+
+    for &(input, expected) in CASES {
+        let doc = yaml::from_slice(input)?;
+        assert_eq!(doc.as_ref().as_str(), Some(expected), "{input:?}");
+        assert_eq!(doc.to_string(), input);
+    }
+
+    let doc = yaml::from_slice("key: \"one \\\n  two\"\nother: 1\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("key").and_then(|v| v.as_str()), Some("one two"));
+    assert_eq!(root.get("other").and_then(|v| v.as_u32()), Some(1));
+
+    let error = yaml::from_slice("\"a\\\n").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::UnterminatedString);
+    Ok(())
+}
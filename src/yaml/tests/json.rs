@@ -0,0 +1,132 @@
+use std::io;
+
+use anyhow::Result;
+
+use crate::yaml;
+
+fn json(input: &str) -> Result<String> {
+    let doc = yaml::from_slice(input)?;
+    let mut out = Vec::new();
+    doc.write_json_to(&mut out)?;
+    Ok(String::from_utf8(out)?)
+}
+
+fn json_error(input: &[u8]) -> Result<io::Error> {
+    let doc = yaml::from_slice(input)?;
+
+    match doc.write_json_to(Vec::new()) {
+        Ok(()) => Err(anyhow::anyhow!("expected an error")),
+        Err(error) => Ok(error),
+    }
+}
+
+#[test]
+fn scalars() -> Result<()> {
+    assert_eq!(json("~")?, "null");
+    assert_eq!(json("true")?, "true");
+    assert_eq!(json("false")?, "false");
+    assert_eq!(json("0042")?, "42");
+    assert_eq!(json("-17")?, "-17");
+    assert_eq!(json("340282366920938463463374607431768211455")?, {
+        "340282366920938463463374607431768211455"
+    });
+    assert_eq!(json("1e3")?, "1000.0");
+    assert_eq!(json("-0.5")?, "-0.5");
+    assert_eq!(json("-340282366920938463463374607431768211457")?, {
+        "-340282366920938463463374607431768211457"
+    });
+    assert_eq!(json("0.1000000000000000000000000001")?, {
+        "0.1000000000000000000000000001"
+    });
+    assert_eq!(json("1e400")?, "1e400");
+    assert_eq!(json("hello world")?, "\"hello world\"");
+    assert_eq!(json("'42'")?, "\"42\"");
+    assert_eq!(json("")?, "\"\"");
+    Ok(())
+}
+
+#[test]
+fn collections() -> Result<()> {
+    assert_eq!(json("[]")?, "[]");
+    assert_eq!(json("{}")?, "{}");
+    assert_eq!(json("- [1, 2]\n- {a: [], b: {}}\n")?, {
+        "[[1,2],{\"a\":[],\"b\":{}}]"
+    });
+    assert_eq!(json("b: 1\na: 2\n")?, "{\"b\":1,\"a\":2}");
+    assert_eq!(
+        json("1: one\ntrue: two\n")?,
+        "{\"1\":\"one\",\"true\":\"two\"}"
+    );
+    Ok(())
+}
+
+#[test]
+fn escapes() -> Result<()> {
+    assert_eq!(
+        json(r#""quote \" backslash \\ tab \t break \n return \r""#)?,
+        r#""quote \" backslash \\ tab \t break \n return \r""#
+    );
+    assert_eq!(json(r#""\b\f\x01\x1f""#)?, r#""\b\f\u0001\u001f""#);
+    assert_eq!(json("\"\\x7f é ☃\"")?, "\"\u{7f} é ☃\"");
+    assert_eq!(json("\"a\\\\b\": c")?, r#"{"a\\b":"c"}"#);
+    Ok(())
+}
+
+#[test]
+fn aliases() -> Result<()> {
+    assert_eq!(json("a: &a 1\nb: *a\n")?, "{\"a\":1,\"b\":1}");
+    assert_eq!(
+        json("base: &a {x: [1, 2]}\nother: [*a, *a]\n")?,
+        "{\"base\":{\"x\":[1,2]},\"other\":[{\"x\":[1,2]},{\"x\":[1,2]}]}"
+    );
+    assert_eq!(json("- &a 1\n- &a 2\n- *a\n")?, "[1,2,2]");
+    Ok(())
+}
+
+#[test]
+fn unrepresentable() -> Result<()> {
+    let error = json_error(b"a: &a [1, *a]\n")?;
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        error.to_string(),
+        "recursive alias `*a` cannot be represented in JSON"
+    );
+
+    let error = json_error(b"a: *a\n")?;
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        error.to_string(),
+        "alias `*a` doesn't refer to an anchored value"
+    );
+
+    let error = json_error(b"[\xff]")?;
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    let error = json_error(b"\xff: 1")?;
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    Ok(())
+}
+
+#[test]
+fn output_errors() -> Result<()> {
+    struct Failing;
+
+    impl io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let doc = yaml::from_slice("a: [1, 2]")?;
+
+    let Err(error) = doc.write_json_to(Failing) else {
+        return Err(anyhow::anyhow!("expected an error"));
+    };
+
+    assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn prefix_search() -> Result<()> {
+    let doc = yaml::from_slice(
+        "spec:\n  serviceName: a\n  server: {port: 80}\n  \"se\\xffq\": 1\nitems:\n- server: b\n- {ser: c}\n",
+    )?;
+
+    let index = doc.key_index();
+    assert_eq!(index.len(), 7);
+
+    let keys = index
+        .with_prefix("ser")
+        .map(|(key, ids)| (key.to_string(), ids.len()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        keys,
+        [
+            ("ser".to_owned(), 1),
+            ("server".to_owned(), 2),
+            ("serviceName".to_owned(), 1)
+        ]
+    );
+
+    assert_eq!(index.with_prefix("x").count(), 0);
+    assert_eq!(index.with_prefix("").count(), index.len());
+    assert!(index.get("missing").is_empty());
+
+    let servers = index
+        .get("server")
+        .iter()
+        .map(|id| doc.json_pointer(*id))
+        .collect::<Option<Vec<_>>>()
+        .context("missing pointer")?;
+
+    assert_eq!(servers, ["/spec/server", "/items/0/server"]);
+    Ok(())
+}
+
+#[test]
+fn mapping_prefix() -> Result<()> {
+    let doc = yaml::from_slice("{alpha: 1, beta: 2, alphabet: 3}")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let values = root
+        .keys_with_prefix("alpha")
+        .flat_map(|(_, v)| v.as_u32())
+        .collect::<Vec<_>>();
+
+    assert_eq!(values, [1, 3]);
+    Ok(())
+}
@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+
+use crate::yaml::{self, Document, ErrorKind};
+
+/// Parse `input`, lock the value at `key` of the root mapping for `human`
+/// and continue editing as `bot`.
+fn locked_key(input: &str, key: &str) -> Result<Document> {
+    let mut doc = yaml::from_slice(input)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let id = root.get(key).context("missing key")?.id();
+    assert!(doc.lock(id, "human"));
+    doc.set_actor(Some("bot"));
+    Ok(doc)
+}
+
+/// Parse `input`, lock the item at `index` of the root sequence for `human`
+/// and continue editing as `bot`.
+fn locked_index(input: &str, index: usize) -> Result<Document> {
+    let mut doc = yaml::from_slice(input)?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let id = root.get(index).context("missing item")?.id();
+    assert!(doc.lock(id, "human"));
+    doc.set_actor(Some("bot"));
+    Ok(doc)
+}
+
+fn assert_locked<T>(result: Result<T, yaml::Error>) {
+    let kind = result.err().map(|error| error.kind().clone());
+    assert_eq!(kind, Some(ErrorKind::Locked));
+}
+
+#[test]
+fn mapping_insert() -> Result<()> {
+    const INPUT: &str = "manual:\n  replicas: 3\nauto: 1\n";
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert_locked(root.try_insert_u32("manual", 5));
+    assert_locked(root.try_insert_str("manual", "x"));
+    assert_locked(root.try_insert_value("manual", true));
+    assert_locked(root.insert("manual", yaml::Separator::Auto));
+    assert_locked(root.insert_with("manual", yaml::StringKind::Single, yaml::Separator::Auto));
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("manual", 5);
+    root.insert_str("manual", "x");
+    assert_eq!(doc.to_string(), INPUT);
+
+    // Nothing can be inserted into a locked mapping.
+    let root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.get_into_mut("manual").is_none());
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    let manual = doc.as_ref().as_mapping().and_then(|m| m.get("manual"));
+    let manual = manual.context("missing manual")?.id();
+    let mut manual = doc
+        .value_mut(manual)
+        .into_mapping_mut()
+        .context("missing manual")?;
+    assert_locked(manual.try_insert_u32("replicas", 4));
+    assert_locked(manual.insert("other", yaml::Separator::Auto));
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("auto", 2);
+    root.insert_u32("other", 3);
+    assert_eq!(
+        doc.to_string(),
+        "manual:\n  replicas: 3\nauto: 2\nother: 3\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn mapping_clear() -> Result<()> {
+    const INPUT: &str = "outer:\n  manual: 1\n  auto: 2\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let outer = doc.as_ref().as_mapping().and_then(|m| m.get("outer"));
+    let outer = outer
+        .and_then(|v| v.as_mapping())
+        .context("missing outer")?;
+    let manual = outer.get("manual").context("missing manual")?.id();
+    assert!(doc.lock(manual, "human"));
+    doc.set_actor(Some("bot"));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert_locked(root.clear());
+    assert_locked(root.clear_with(yaml::Clear::Flow));
+
+    let outer = root.get_into_mut("outer").context("missing outer")?;
+    let mut outer = outer.into_mapping_mut().context("missing outer")?;
+    assert_locked(outer.clear_with(yaml::Clear::Null));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn replace_ancestor() -> Result<()> {
+    const INPUT: &str = "outer: {manual: 1, auto: 2}\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let outer = doc.as_ref().as_mapping().and_then(|m| m.get("outer"));
+    let outer = outer
+        .and_then(|v| v.as_mapping())
+        .context("missing outer")?;
+    let manual = outer.get("manual").context("missing manual")?.id();
+    assert!(doc.lock(manual, "human"));
+    doc.set_actor(Some("bot"));
+
+    // Values containing a locked value can be reached, but not replaced.
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut outer = root.get_mut("outer").context("missing outer")?;
+    outer.set_u32(1);
+    outer.set_string("x");
+    outer.set_value(true);
+    outer.set_null(yaml::Null::Keyword);
+    outer.set_block(["x"], yaml::Block::Literal(yaml::Chomp::Clip));
+    assert!(!outer.set_block_indented(["x"], yaml::Block::Literal(yaml::Chomp::Clip), 2));
+    assert_locked(outer.make_sequence());
+
+    let outer = root.get_mut("outer").context("missing outer")?;
+    outer.make_mapping()?.insert_u32("auto", 3);
+    assert_eq!(doc.to_string(), "outer: {manual: 1, auto: 3}\n");
+
+    doc.as_mut().set_u32(1);
+    assert_locked(doc.as_mut().make_sequence());
+    assert_eq!(doc.to_string(), "outer: {manual: 1, auto: 3}\n");
+
+    // The locked value itself is not handed out.
+    let root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let outer = root.get_into_mut("outer").context("missing outer")?;
+    let mut outer = outer.into_mapping_mut().context("missing outer")?;
+    assert!(outer.get_mut("manual").is_none());
+
+    let mut manual = doc.value_mut(manual);
+    manual.set_u32(2);
+    assert_locked(manual.set_tag("!x"));
+    assert_locked(manual.set_anchor("x"));
+    assert_eq!(doc.to_string(), "outer: {manual: 1, auto: 3}\n");
+    Ok(())
+}
+
+#[test]
+fn fill_placeholders() -> Result<()> {
+    const INPUT: &str = "manual: <A>\nauto: <B>\n";
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    let mut seen = Vec::new();
+
+    let count = doc.fill_placeholders(|name| {
+        seen.push(name.to_owned());
+        Some(name.to_lowercase())
+    });
+
+    assert_eq!(count, 1);
+    assert_eq!(seen, ["B"]);
+    assert_eq!(doc.to_string(), "manual: <A>\nauto: b\n");
+    Ok(())
+}
+
+#[test]
+fn sequence_truncate() -> Result<()> {
+    const INPUT: &str = "[a, b, c]";
+
+    let mut doc = locked_index(INPUT, 2)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_locked(root.truncate(2));
+    assert_locked(root.clear());
+    assert_locked(root.clear_with(yaml::Clear::Null));
+    assert_eq!(root.truncate(3)?, 0);
+    assert_eq!(root.keep_last(2)?, 1);
+    assert_eq!(doc.to_string(), "[b, c]");
+
+    let mut doc = locked_index(INPUT, 0)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_locked(root.keep_last(2));
+    assert_eq!(root.truncate(1)?, 2);
+    assert_eq!(doc.to_string(), "[a]");
+    Ok(())
+}
+
+#[test]
+fn sequence_sort() -> Result<()> {
+    const INPUT: &str = "[c, b, a]";
+
+    let mut doc = locked_index(INPUT, 1)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_locked(root.sort());
+    assert_locked(root.sort_by(|a, b| a.as_bytes().cmp(&b.as_bytes())));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn sequence_dedup() -> Result<()> {
+    const INPUT: &str = "[a, a, b, a]";
+
+    // The locked value is a duplicate which would be removed.
+    let mut doc = locked_index(INPUT, 1)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_locked(root.dedup());
+    assert_locked(root.dedup_by(|a, b| a.as_bytes() == b.as_bytes()));
+    assert_locked(root.dedup_all());
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut doc = locked_index(INPUT, 3)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_eq!(root.dedup()?, 1);
+    assert_locked(root.dedup_all());
+    assert_eq!(doc.to_string(), "[a, b, a]");
+
+    // Retained values may be locked.
+    let mut doc = locked_index(INPUT, 0)?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert_eq!(root.dedup_all()?, 2);
+    assert_eq!(doc.to_string(), "[a, b]");
+    Ok(())
+}
+
+#[test]
+fn replace_in_strings() -> Result<()> {
+    const INPUT: &str = "manual: old\nauto: old\n";
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    assert_locked(doc.replace_in_strings("old", "new"));
+    assert_locked(doc.replace_in_strings_with(|_| Some(b"new".to_vec())));
+    assert_eq!(doc.to_string(), INPUT);
+
+    // Locked strings which are left unchanged don't prevent replacements.
+    let mut doc = locked_key("manual: keep\nauto: old\n", "manual")?;
+    assert_eq!(doc.replace_in_strings("old", "new")?, 1);
+    assert_eq!(doc.to_string(), "manual: keep\nauto: new\n");
+    Ok(())
+}
+
+#[test]
+fn prune_empty() -> Result<()> {
+    const INPUT: &str = "name: app\nmanual: {}\nauto: []\n";
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    assert_locked(doc.prune_empty(yaml::Prune::Collections));
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut doc = locked_key(INPUT, "name")?;
+    assert_eq!(doc.prune_empty(yaml::Prune::Collections)?, 2);
+    assert_eq!(doc.to_string(), "name: app\n");
+    Ok(())
+}
+
+#[test]
+fn reindent_all() -> Result<()> {
+    const INPUT: &str = "manual:\n  a: 1\nauto:\n  b: 2\n";
+
+    let mut doc = locked_key(INPUT, "manual")?;
+    assert_locked(doc.reindent_all(2, 4));
+    assert_eq!(doc.to_string(), INPUT);
+
+    doc.set_actor(Some("human"));
+    doc.reindent_all(2, 4)?;
+    assert_eq!(doc.to_string(), "manual:\n    a: 1\nauto:\n    b: 2\n");
+    Ok(())
+}
+
+#[test]
+fn forced() -> Result<()> {
+    let mut doc = locked_key("manual: 3\nauto: [b, a, a]\n", "manual")?;
+    doc.set_forced(true);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("manual", 5);
+
+    let auto = root.get_into_mut("auto").context("missing auto")?;
+    let mut auto = auto.into_sequence_mut().context("missing auto")?;
+    assert_eq!(auto.dedup()?, 1);
+    auto.sort()?;
+    assert_eq!(doc.to_string(), "manual: 5\nauto: [a, b]\n");
+
+    doc.reindent_all(2, 4)?;
+    doc.as_mut()
+        .into_mapping_mut()
+        .context("missing root")?
+        .clear()?;
+    Ok(())
+}
+
+#[test]
+fn normalize_keys() -> Result<()> {
+    const INPUT: &str = "maxReplicas:\n  minValue: 3\nautoScale: 1\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let id = root.get("maxReplicas").context("missing key")?.id();
+    assert!(doc.lock(id, "human"));
+    doc.set_actor(Some("bot"));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert_eq!(root.normalize_keys_recursive(yaml::Case::Snake), 1);
+    assert_eq!(
+        doc.to_string(),
+        "maxReplicas:\n  minValue: 3\nauto_scale: 1\n"
+    );
+    Ok(())
+}
@@ -85,7 +85,7 @@ fn make_preserve_whitespace() -> Result<()> {
         ",
     )?;
 
-    let mut mapping = doc.as_mut().make_mapping();
+    let mut mapping = doc.as_mut().make_mapping()?;
     mapping.insert_u32("first", 1);
     mapping.insert_u32("second", 2);
 
@@ -112,7 +112,7 @@ fn edit_element() -> Result<()> {
 
     let id = a.id();
 
-    let _ = doc.value_mut(id).make_mapping();
+    doc.value_mut(id).make_mapping()?;
 
     assert_eq!(doc.to_string(), "a:\nb:\nc:");
 
@@ -122,11 +122,742 @@ fn edit_element() -> Result<()> {
         .context("Missing mapping")?;
 
     let mut sequence = mapping
-        .insert("inner", yaml::Separator::Auto)
-        .make_sequence();
+        .insert("inner", yaml::Separator::Auto)?
+        .make_sequence()?;
 
     sequence.push_string("value");
 
     assert_eq!(doc.to_string(), "a:\n  inner:\n    - value\nb:\nc:");
     Ok(())
 }
+
+#[test]
+fn inline_mapping_without_space() -> Result<()> {
+    let doc = yaml::from_slice("{a:1, b:two, c: http://example.com}")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("two"));
+    assert_eq!(
+        root.get("c").and_then(|v| v.as_str()),
+        Some("http://example.com")
+    );
+
+    assert_eq!(doc.to_string(), "{a:1, b:two, c: http://example.com}");
+
+    let doc = yaml::from_slice(r#"{"a b": 1, 'c':2}"#)?;
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a b").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("c").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(doc.to_string(), r#"{"a b": 1, 'c':2}"#);
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_strict_flow_keys() -> Result<()> {
+    let parse = |input: &str| {
+        yaml::Parser::new(input.as_bytes())
+            .strict_flow_keys(true)
+            .parse()
+    };
+
+    let doc = parse(r#"{a: 1, "b":2, 'c':3, d: http://example.com}"#)?;
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("b").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(root.get("c").and_then(|v| v.as_u32()), Some(3));
+    assert_eq!(
+        root.get("d").and_then(|v| v.as_str()),
+        Some("http://example.com")
+    );
+
+    assert_eq!(
+        doc.to_string(),
+        r#"{a: 1, "b":2, 'c':3, d: http://example.com}"#
+    );
+
+    let doc = parse(r#"{"a:b, c": 1}"#)?;
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a:b, c").and_then(|v| v.as_u32()), Some(1));
+
+    let doc = parse(r#"{"a\tb":1, 'it''s': 2}"#)?;
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a\tb").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("it's").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(doc.to_string(), r#"{"a\tb":1, 'it''s': 2}"#);
+
+    let mut json = Vec::new();
+    doc.write_json_to(&mut json)?;
+    assert_eq!(json, br#"{"a\tb":1,"it's":2}"#);
+
+    for input in ["{a:1}", "{a:1, b: 2}", "{a, b: 2}"] {
+        let error = parse(input).unwrap_err();
+        assert_eq!(*error.kind(), yaml::ErrorKind::BadMappingSeparator);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_comments() -> Result<()> {
+    const INPUT: &str = "{a: 1, # one\n  b: two # two\n  , c: 3 # three\n}";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("two"));
+    assert_eq!(root.get("c").and_then(|v| v.as_u32()), Some(3));
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    root.get_mut("b").context("missing b")?.set_string("deux");
+    assert!(root.remove("a"));
+    assert_eq!(
+        doc.to_string(),
+        "{ # one\n  b: deux # two\n  , c: 3 # three\n}"
+    );
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_comments_insert() -> Result<()> {
+    for (input, expected) in [
+        ("{a: 1, # c\n b: 2}", "{a: 1, # c\n b: 2,\n x: 3}"),
+        ("{a: 1, b: 2 # c\n}", "{a: 1, b: 2, x: 3 # c\n}"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_mapping_mut()
+            .context("missing root mapping")?;
+
+        root.insert_u32("x", 3);
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(yaml::from_slice(expected)?.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_trailing_comma() -> Result<()> {
+    let mut doc = yaml::from_slice("{a: 1, b: 2,}")?;
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    root.insert_u32("c", 3);
+    assert!(doc.to_string().ends_with("c: 3,}"));
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    root.set_trailing_comma(false);
+    assert!(doc.to_string().ends_with("c: 3}"));
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    root.set_trailing_comma(true);
+    root.clear()?;
+    assert_eq!(doc.to_string(), "{}");
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_insert() -> Result<()> {
+    for (input, expected) in [
+        ("{a: 1}", "{a: 1, b: 2}"),
+        ("{a:1,c:3}", "{a:1,c:3,b:2}"),
+        ("{ a: 1 }", "{ a: 1, b: 2 }"),
+        ("{\n  a: 1,\n  c: 3,\n}", "{\n  a: 1,\n  c: 3,\n  b: 2,\n}"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_mapping_mut()
+            .context("missing root mapping")?;
+
+        root.insert_u32("b", 2);
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_insert_empty() -> Result<()> {
+    for (input, expected) in [
+        ("{}", "{a: 1, b: two}"),
+        ("{ }", "{ a: 1, b: two }"),
+        ("{\n}", "{a: 1, b: two\n}"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_mapping_mut()
+            .context("missing root mapping")?;
+
+        root.insert_u32("a", 1);
+        root.insert_str("b", "two");
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    let mut doc = yaml::from_slice("map: {}\nother: 1")?;
+
+    let mut map = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("map"))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing map")?;
+
+    map.insert_str("key", "value");
+    assert_eq!(doc.to_string(), "map: {key: value}\nother: 1");
+    Ok(())
+}
+
+#[test]
+fn inline_mapping_insert_collections() -> Result<()> {
+    let mut doc = yaml::from_slice("a: {x: y}\n")?;
+
+    let mut a = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("a"))
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing a")?;
+
+    let mut sequence = a.insert("n", yaml::Separator::Auto)?.make_sequence()?;
+    sequence.push_u32(1);
+    sequence.push_u32(2);
+
+    let mut mapping = a.insert("m", yaml::Separator::Auto)?.make_mapping()?;
+    mapping.insert_u32("k", 3);
+
+    let expected = "a: {x: y, n: [1, 2], m: {k: 3}}\n";
+    assert_eq!(doc.to_string(), expected);
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    assert_eq!(doc.to_string(), expected);
+
+    let a = doc
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("a")?.as_mapping())
+        .context("missing a")?;
+    let n = a
+        .get("n")
+        .and_then(|v| v.as_sequence())
+        .context("missing n")?;
+    assert_eq!(n.get(1).and_then(|v| v.as_u32()), Some(2));
+    Ok(())
+}
+
+#[test]
+fn normalize_keys() -> Result<()> {
+    for (case, expected) in [
+        (
+            yaml::Case::Snake,
+            "{http_server: 1, v2_api: 2, 'already_snake': 3, _: 4, a_b: 5, a-b: 6}",
+        ),
+        (
+            yaml::Case::Kebab,
+            "{http-server: 1, v2-api: 2, 'already-snake': 3, _: 4, a_b: 5, a-b: 6}",
+        ),
+        (
+            yaml::Case::Camel,
+            "{httpServer: 1, v2Api: 2, 'alreadySnake': 3, _: 4, aB: 5, a-b: 6}",
+        ),
+    ] {
+        let mut doc = yaml::from_slice(
+            "{HTTPServer: 1, v2Api: 2, 'already_snake': 3, _: 4, a_b: 5, a-b: 6}",
+        )?;
+
+        let mut root = doc
+            .as_mut()
+            .into_mapping_mut()
+            .context("missing root mapping")?;
+
+        root.normalize_keys(case);
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn normalize_complex_keys() -> Result<()> {
+    let mut doc = yaml::from_slice("? fooBar\n: 1\nbazQux: 2\n")?;
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    assert_eq!(root.normalize_keys(yaml::Case::Snake), 1);
+    assert_eq!(doc.to_string(), "? fooBar\n: 1\nbaz_qux: 2\n");
+    Ok(())
+}
+
+#[test]
+fn iter_double_ended() -> Result<()> {
+    let doc = yaml::from_slice("{a: 1, b: 2, c: 3, d: 4}")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let mut it = root.iter();
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.next().map(|(k, _)| k), Some("a".into()));
+    assert_eq!(it.nth_back(1).map(|(k, _)| k), Some("c".into()));
+    assert_eq!(it.len(), 1);
+    assert_eq!(it.next_back().map(|(k, _)| k), Some("b".into()));
+    assert!(it.next().is_none());
+    assert!(it.next_back().is_none());
+
+    let keys = root
+        .iter()
+        .rev()
+        .map(|(k, _)| k.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["d", "c", "b", "a"]);
+    Ok(())
+}
+
+#[test]
+fn default_separator() -> Result<()> {
+    let mut doc = yaml::from_slice("a:    1\nb: {c:   2}\n")?;
+    doc.set_separator(yaml::Separator::spaces(2));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("d", 3);
+
+    let mut b = root
+        .get_mut("b")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing b")?;
+    b.insert_u32("e", 4);
+
+    doc.set_separator(yaml::Separator::Auto);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("f", 5);
+
+    assert_eq!(
+        doc.to_string(),
+        "a:    1\nb: {c:   2, e:   4}\nd:  3\nf: 5\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn complex_keys_round_trip() -> Result<()> {
+    const CASES: &[&str] = &[
+        "? - a\n  - b\n: value\n? simple\n: v2\nplain: 3\n",
+        "? [a, b]\n: 1\n",
+        "? |\n  block\n  key\n: 1\n",
+        "?\n: empty\n",
+        "?\n? a\n",
+        "- ? x: 1\n    y: 2\n  : z\n",
+        "? {a: 1}\n? - b\n",
+        "?   \"quoted\" # comment\n:   value # trailing\n",
+        "? a\n:\n  nested: 1\n",
+        "? &key [a]\n: *key\n",
+    ];
+
+    for &input in CASES {
+        let doc = yaml::from_slice(input)?;
+        assert_eq!(doc.to_string(), input, "{input:?}");
+
+        let mut out = Vec::new();
+        doc.write_to(&mut out)?;
+        assert_eq!(out, input.as_bytes(), "{input:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn complex_keys_edit() -> Result<()> {
+    let mut doc = yaml::from_slice("? - a\n  - b\n: 1\n? |\n  block\n: 2\nc: 3\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let keys = root
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["- a\n  - b", "block\n", "c"]);
+
+    let (key, _) = root.complex_keys().next().context("missing key")?;
+    let key = key.as_sequence().context("missing sequence")?;
+    let b = key.get(1).context("missing b")?.id();
+    let (block, _) = root.complex_keys().nth(1).context("missing block")?;
+    let block = block.id();
+    doc.value_mut(b).set_string("changed");
+    assert_eq!(doc.json_pointer(b), None);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("block\n")
+        .context("missing block")?
+        .set_u32(20);
+    assert_eq!(
+        doc.to_string(),
+        "? - a\n  - changed\n: 1\n? |\n  block\n: 20\nc: 3\n"
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove("block\n"));
+    assert_eq!(doc.to_string(), "? - a\n  - changed\n: 1\nc: 3\n");
+    assert!(doc.contains(b));
+    assert!(!doc.contains(block));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.clear()?;
+    assert_eq!(doc.to_string(), "\n");
+    Ok(())
+}
+
+#[test]
+fn complex_keys_copy() -> Result<()> {
+    let mut doc = yaml::from_slice("a:\n  ? [x]\n  : 1\nb:\n  ? - y\n  : 2\nc: {}\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?.id();
+    let c = root.get("c").context("missing c")?.id();
+    assert!(doc.copy_value(a, c));
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root
+        .get("a")
+        .and_then(|v| v.as_mapping())
+        .context("missing a")?;
+    let c = root
+        .get("c")
+        .and_then(|v| v.as_mapping())
+        .context("missing c")?;
+    assert_eq!(c.get("[x]").and_then(|v| v.as_u32()), Some(1));
+    assert_ne!(
+        a.complex_keys().next().map(|(k, _)| k.id()),
+        c.complex_keys().next().map(|(k, _)| k.id())
+    );
+    Ok(())
+}
+
+#[test]
+fn get_value() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r#"
+        ~: tilde
+        True: bool
+        "a b": quoted
+        10: ten
+        {x: 1}: ignored
+        ? {x: 1}
+        : complex
+        "#,
+    )?;
+
+    let keys = yaml::from_slice(r#"[null, true, 'a b', 1e1, {x: 1}, "10", false]"#)?;
+    let keys = keys.as_ref().as_sequence().context("missing keys")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let values = keys
+        .iter()
+        .map(|key| root.get_value(&key).and_then(|v| v.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        values,
+        [
+            Some("tilde"),
+            Some("bool"),
+            Some("quoted"),
+            Some("ten"),
+            Some("complex"),
+            None,
+            None
+        ]
+    );
+
+    let key = keys.get(3).context("missing key")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_value_mut(&key)
+        .context("missing value")?
+        .set_u32(10);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("10").and_then(|v| v.as_u32()), Some(10));
+    Ok(())
+}
+
+#[test]
+fn get_value_schema() -> Result<()> {
+    const INPUT: &[u8] = b"1_000: thousand\nyes: bool\n'no': string\n";
+
+    let keys = yaml::from_slice("[1000, true, false]")?;
+    let keys = keys.as_ref().as_sequence().context("missing keys")?;
+
+    let doc = yaml::Parser::new(INPUT)
+        .schema(yaml::Schema::Yaml11)
+        .parse()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let values = keys
+        .iter()
+        .map(|key| root.get_value(&key).and_then(|v| v.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(values, [Some("thousand"), Some("bool"), None]);
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let values = keys
+        .iter()
+        .map(|key| root.get_value(&key).and_then(|v| v.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(values, [None, None, None]);
+    Ok(())
+}
+
+#[test]
+fn insert_many_keys() -> Result<()> {
+    let mut doc = yaml::from_slice("root:\n  first: 1\nother: {}\n")?;
+
+    let root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut mapping = root
+        .get_into_mut("root")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing mapping")?;
+
+    for n in 0..100 {
+        mapping.insert_u32(format!("key{n}"), n);
+    }
+
+    // Overwriting keeps the existing items.
+    mapping.insert_u32("key10", 1000);
+    mapping.insert_u32("first", 2);
+    assert_eq!(mapping.as_ref().len(), 101);
+
+    // Removed keys are inserted again at the end.
+    assert!(mapping.remove("key20"));
+    mapping.insert_u32("key20", 2000);
+    mapping.insert_u32("key21", 2100);
+    assert_eq!(mapping.as_ref().len(), 101);
+
+    let root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut other = root
+        .get_into_mut("other")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing other")?;
+    other.insert_u32("key10", 3);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let mapping = root
+        .get("root")
+        .and_then(|v| v.as_mapping())
+        .context("missing mapping")?;
+
+    assert_eq!(mapping.get("first").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(mapping.get("key10").and_then(|v| v.as_u32()), Some(1000));
+    assert_eq!(mapping.get("key21").and_then(|v| v.as_u32()), Some(2100));
+
+    let keys = mapping
+        .iter()
+        .map(|(k, _)| k.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(keys.last().map(String::as_str), Some("key20"));
+    assert_eq!(keys.iter().filter(|k| *k == "key21").count(), 1);
+
+    assert!(doc
+        .to_string()
+        .ends_with("  key99: 99\n  key20: 2000\nother: {key10: 3}\n"));
+    Ok(())
+}
+
+#[test]
+fn tab_indentation() -> Result<()> {
+    let mut doc = yaml::from_slice("a:\n\tb: 1\n\tc:\n\t\t- x\n\td: v\n")?;
+
+    let mut a = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("a")?.into_mapping_mut())
+        .context("missing a")?;
+
+    a.insert_u32("e", 4);
+    a.get_mut("c")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing c")?
+        .push_string("y");
+    a.get_mut("d")
+        .context("missing d")?
+        .make_mapping()?
+        .insert_u32("f", 1);
+
+    assert_eq!(
+        doc.to_string(),
+        "a:\n\tb: 1\n\tc:\n\t\t- x\n\t\t- y\n\td:\n\t\tf: 1\n\te: 4\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn indentation_width() -> Result<()> {
+    let mut doc = yaml::from_slice("a:\n    b: 1\n    d: v\n")?;
+
+    let mut a = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("a")?.into_mapping_mut())
+        .context("missing a")?;
+
+    a.get_mut("d")
+        .context("missing d")?
+        .make_mapping()?
+        .insert_u32("f", 1);
+    a.insert("g", yaml::Separator::Auto)?
+        .make_sequence()?
+        .push_u32(1);
+
+    assert_eq!(
+        doc.to_string(),
+        "a:\n    b: 1\n    d:\n        f: 1\n    g:\n        - 1\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn quoted_keys() -> Result<()> {
+    const INPUT: &str =
+        "plain: 0\n\"foo bar\": 1\n'a:b': 2\nnested:\n  \"x\\ty\": 3\n  'it''s': 4\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("foo bar").and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get("a:b").and_then(|v| v.as_u32()), Some(2));
+
+    let nested = root
+        .get("nested")
+        .and_then(|v| v.as_mapping())
+        .context("missing nested")?;
+    assert_eq!(nested.get("x\ty").and_then(|v| v.as_u32()), Some(3));
+    assert_eq!(nested.get("it's").and_then(|v| v.as_u32()), Some(4));
+
+    let keys = nested
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["x\ty", "it's"]);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a:b").context("missing a:b")?.set_u32(20);
+    assert!(root.remove("foo bar"));
+    root.insert_with(
+        "key: value",
+        yaml::StringKind::Double,
+        yaml::Separator::Auto,
+    )?
+    .set_u32(5);
+
+    assert_eq!(
+        doc.to_string(),
+        "plain: 0\n'a:b': 20\nnested:\n  \"x\\ty\": 3\n  'it''s': 4\n\"key: value\": 5\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("key: value").and_then(|v| v.as_u32()), Some(5));
+    Ok(())
+}
+
+#[test]
+fn clear_styles() -> Result<()> {
+    let input = "\n    a: 1\n    b: 2\n";
+
+    for (style, cleared, inserted) in [
+        (yaml::Clear::Preserve, "\n", "\n    c: 3\n"),
+        (yaml::Clear::Flow, "\n    {}\n", "\n    {c: 3}\n"),
+        (yaml::Clear::Null, "\n", "\n    c: 3\n"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+        let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+        root.clear_with(style)?;
+        assert_eq!(doc.to_string(), cleared, "{style:?}");
+
+        let reparsed = yaml::from_slice(doc.to_string())?;
+        assert_eq!(reparsed.to_string(), cleared, "{style:?}");
+
+        if let yaml::Clear::Flow = style {
+            let mapping = reparsed.as_ref().as_mapping().context("missing mapping")?;
+            assert!(mapping.is_empty());
+        }
+
+        let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+        root.insert_u32("c", 3);
+        assert_eq!(doc.to_string(), inserted, "{style:?}");
+    }
+
+    // Comments in front of the root are kept.
+    let mut doc = yaml::from_slice("# head\na: 1\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.clear()?;
+    assert_eq!(doc.to_string(), "# head\n\n");
+
+    // Mappings nested in inline collections can't become blocks.
+    let mut doc = yaml::from_slice("[{a: 1}, 2]\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    let mut first = root
+        .get_mut(0)
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing first")?;
+    first.clear_with(yaml::Clear::Null)?;
+    assert_eq!(doc.to_string(), "[{}, 2]\n");
+    Ok(())
+}
+
+#[test]
+fn flow_indicators() -> Result<()> {
+    let mut doc = yaml::from_slice("{a: 1}")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("b", "x, y");
+    root.insert_str("c", "x}");
+    root.insert_bytes("d", b"[x");
+    root.insert_value("e", "x]");
+    assert_eq!(
+        doc.to_string(),
+        "{a: 1, b: 'x, y', c: 'x}', d: '[x', e: 'x]'}"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("x, y"));
+    assert_eq!(root.get("c").and_then(|v| v.as_str()), Some("x}"));
+    assert_eq!(root.get("d").and_then(|v| v.as_str()), Some("[x"));
+    assert_eq!(root.get("e").and_then(|v| v.as_str()), Some("x]"));
+
+    // Block mappings don't need the indicators quoted.
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("b", "x, y");
+    assert_eq!(doc.to_string(), "a: 1\nb: x, y\n");
+    Ok(())
+}
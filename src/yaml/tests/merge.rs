@@ -0,0 +1,156 @@
+use anyhow::Result;
+
+use crate::yaml;
+
+#[test]
+fn merge_nested() -> Result<()> {
+    let base = yaml::from_slice(
+        r"
+        server:
+          host: localhost
+          port: 80
+          legacy: true
+        tags: [a]
+        ",
+    )?;
+
+    let ours = yaml::from_slice(
+        r"
+        server:
+          host: example.com
+          port: 80
+          legacy: true
+          workers: 4
+        tags: [a, b]
+        ",
+    )?;
+
+    let theirs = yaml::from_slice(
+        r"
+        server: {host: localhost, port: 8080, workers: 8}
+        tags: [a]
+        ",
+    )?;
+
+    let mut merge = yaml::merge3(&base, &ours, &theirs);
+
+    let conflicts = merge
+        .conflicts()
+        .iter()
+        .map(|c| (c.path().join("."), c.ours(), c.theirs()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        conflicts,
+        [("server.workers".to_owned(), Some("4"), Some("8"))]
+    );
+
+    assert_eq!(
+        merge.document().to_string(),
+        r"
+        server:
+          host: example.com
+          port: 8080
+          workers: 4
+        tags: [a, b]
+        "
+    );
+
+    assert_eq!(merge.annotate(), 1);
+
+    assert_eq!(
+        merge.document().to_string(),
+        r"
+        server:
+          host: example.com
+          port: 8080
+          # conflict: ours: 4, theirs: 8
+          workers: 4
+        tags: [a, b]
+        "
+    );
+
+    Ok(())
+}
+
+#[test]
+fn merge_removals() -> Result<()> {
+    let base = yaml::from_slice("a: 1\nb: 2\nc: 3\n")?;
+    let ours = yaml::from_slice("a: 10\nb: 2\n")?;
+    let theirs = yaml::from_slice("b: 2\nc: 30\n")?;
+
+    let mut merge = yaml::merge3(&base, &ours, &theirs);
+
+    let conflicts = merge
+        .conflicts()
+        .iter()
+        .map(|c| (c.path().join("."), c.ours(), c.theirs()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        conflicts,
+        [
+            ("c".to_owned(), None, Some("30")),
+            ("a".to_owned(), Some("10"), None),
+        ]
+    );
+
+    assert_eq!(merge.annotate(), 1);
+    assert_eq!(
+        merge.document().to_string(),
+        "# conflict: ours: 10, theirs: (deleted)\na: 10\nb: 2\n"
+    );
+
+    let base = yaml::from_slice("a: 1\nb: 2\n")?;
+    let ours = yaml::from_slice("a: 1\nb: 2\n")?;
+    let theirs = yaml::from_slice("b: 2\n")?;
+
+    let merge = yaml::merge3(&base, &ours, &theirs);
+    assert!(merge.is_clean());
+    assert_eq!(merge.document().to_string(), "b: 2\n");
+    Ok(())
+}
+
+#[test]
+fn merge_block_scalars() -> Result<()> {
+    let base = yaml::from_slice("a: 1\nb: 2\n")?;
+    let ours = yaml::from_slice("a: 10\nb: 2\n")?;
+    let theirs = yaml::from_slice("a: |\n  multi\n  line\nb: |-\n  x\n  y\n")?;
+
+    let mut merge = yaml::merge3(&base, &ours, &theirs);
+
+    assert_eq!(merge.document().to_string(), "a: 10\nb: |-\n  x\n  y\n");
+
+    assert_eq!(merge.annotate(), 1);
+
+    let annotated = merge.document().to_string();
+
+    assert_eq!(
+        annotated,
+        "# conflict: ours: 10, theirs: (block scalar)\na: 10\nb: |-\n  x\n  y\n"
+    );
+
+    let doc = yaml::from_slice(&annotated)?;
+    assert_eq!(
+        doc.as_ref()
+            .as_mapping()
+            .and_then(|m| m.get("b"))
+            .and_then(|v| v.as_str()),
+        Some("x\ny")
+    );
+
+    let base = yaml::from_slice("a: 1\n")?;
+    let ours = yaml::from_slice("a: \"x\n\n  y\"\n")?;
+    let theirs = yaml::from_slice("a: 2\n")?;
+
+    let mut merge = yaml::merge3(&base, &ours, &theirs);
+    assert_eq!(merge.annotate(), 1);
+
+    let annotated = merge.document().to_string();
+    assert_eq!(
+        annotated,
+        "# conflict: ours: \"x y\", theirs: 2\na: \"x\n\n  y\"\n"
+    );
+    assert!(yaml::from_slice(&annotated).is_ok());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+
+use crate::yaml;
+use crate::yaml::migrate::{ChangeKind, Migration};
+
+#[test]
+fn move_collections() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        services:
+          web:
+            ports: [80, 443]
+            env:
+              DEBUG: 1
+          db:
+            env:
+              USER: admin
+        ",
+    )?;
+
+    let report = Migration::new()
+        .move_to(&["services", "*", "env"], &["environment", "*"])
+        .move_to(
+            &["services", "web", "ports"],
+            &["services", "web", "network", "ports"],
+        )
+        .apply(&mut doc);
+
+    assert_eq!(
+        doc.to_string(),
+        r"
+        services:
+          web:
+            network:
+              ports: [80, 443]
+          db:
+        environment:
+          web:
+            DEBUG: 1
+          db:
+            USER: admin
+        "
+    );
+
+    let changes = report
+        .changes()
+        .iter()
+        .map(|c| (c.rule(), c.path().join("."), c.kind()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        changes,
+        [
+            (0, "services.web.env".to_owned(), ChangeKind::Moved),
+            (0, "services.db.env".to_owned(), ChangeKind::Moved),
+            (1, "services.web.ports".to_owned(), ChangeKind::Moved),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rename_collision() -> Result<()> {
+    let mut doc = yaml::from_slice("{a: 1, b: 2, c: 3}")?;
+
+    let report = Migration::new()
+        .rename(&["a"], "b")
+        .rename(&["c"], "'quoted'")
+        .rename(&["missing"], "d")
+        .apply(&mut doc);
+
+    assert_eq!(doc.to_string(), "{a: 1, b: 2, \"'quoted'\": 3}");
+    assert_eq!(report.changes().len(), 1);
+    Ok(())
+}
+
+#[test]
+fn default_collections() -> Result<()> {
+    let mut doc = yaml::from_slice("{name: app}")?;
+    let defaults = yaml::from_slice("{limits: {cpu: 1}, tags: [a, b]}")?;
+
+    Migration::new()
+        .default(&["resources"], defaults.as_ref())
+        .apply(&mut doc);
+
+    assert_eq!(
+        doc.to_string(),
+        "{name: app, resources: {limits: {cpu: 1}, tags: [a, b]}}"
+    );
+
+    let mut doc = yaml::from_slice("name: app\n")?;
+    let defaults = yaml::from_slice("limits:\n  cpu: 1\ntags:\n  - a\n")?;
+
+    let report = Migration::new()
+        .default(&["resources"], defaults.as_ref())
+        .default(&["name"], defaults.as_ref())
+        .apply(&mut doc);
+
+    assert_eq!(
+        doc.to_string(),
+        "name: app\nresources:\n  limits:\n    cpu: 1\n  tags:\n    - a\n"
+    );
+    assert_eq!(report.changes().len(), 1);
+    Ok(())
+}
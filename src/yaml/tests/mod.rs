@@ -1,8 +1,48 @@
+#![allow(clippy::indexing_slicing)]
+
 mod actions;
+mod bom;
+mod canonical;
+mod classify;
+mod comments;
+mod conflict;
+mod crlf;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod diff;
+mod directive;
+mod disjoint;
 mod escape;
+mod json;
+mod key_index;
+mod lock;
 mod mapping;
+mod merge;
+mod migrate;
 mod multiline;
+mod number;
+mod outline;
+mod overlay;
+mod placeholder;
+mod pointer;
+mod project;
+mod prune;
+mod recipes;
+mod refs;
+mod reload;
+mod replace;
+mod schema;
 mod sequence;
+#[cfg(feature = "serde-edits")]
+mod session;
+mod stream;
+mod stream_parser;
+mod structural;
+mod tags;
+#[cfg(feature = "test-support")]
+mod testing;
+#[cfg(feature = "time")]
+mod timestamp;
 
 use anyhow::{Context, Result};
 
@@ -18,7 +58,7 @@ fn make_mapping() -> Result<()> {
     let mut mapping = doc
         .as_mut()
         .into_mapping_mut()
-        .and_then(|m| Some(m.get_into_mut("first")?.make_mapping()))
+        .and_then(|m| m.get_into_mut("first")?.make_mapping().ok())
         .context("missing first")?;
     mapping.insert_u32("second", 2);
     mapping.insert_u32("third", 3);
@@ -98,3 +138,615 @@ fn test_sequences() -> Result<()> {
     assert_eq!(root.get(3).and_then(|v| v.as_str()), Some("six"));
     Ok(())
 }
+
+#[test]
+fn dump_tree_spans() -> Result<()> {
+    let doc = yaml::from_slice(
+        r#"
+        # comment
+        name: "app"
+        set: !!set
+          ? a
+        list:
+          - one
+          -   {a: 1 , b: [x,y,],}
+          - - nested
+          - |
+            block
+        "#,
+    )?;
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+
+    let dump = doc.dump_tree();
+    let mut values = 0;
+
+    for line in dump.lines() {
+        let mut parts = line.split_whitespace();
+        let id = parts.next().context("missing id")?;
+        let kind = parts.next().context("missing kind")?;
+        let span = parts.next().context("missing span")?;
+
+        if kind.ends_with("-item") {
+            continue;
+        }
+
+        let id = u64::from_str_radix(id, 16)?;
+        let id = yaml::Id::from_u64(id).context("bad id")?;
+        let (start, end) = span.split_once("..").context("bad span")?;
+        let span = &out[start.parse::<usize>()?..end.parse::<usize>()?];
+
+        assert_eq!(span, doc.value(id).to_string().as_bytes(), "{line}");
+        values += 1;
+    }
+
+    assert_eq!(values, 14);
+    Ok(())
+}
+
+#[test]
+fn metadata_lifecycle() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        server:
+          host: localhost
+          ports: [80, 443]
+        name: app
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let server = root.get("server").context("missing server")?;
+    let ports = server
+        .as_mapping()
+        .and_then(|m| m.get("ports"))
+        .context("missing ports")?;
+    let port = ports
+        .as_sequence()
+        .and_then(|s| Some(s.get(1)?.id()))
+        .context("missing port")?;
+    let (server, ports) = (server.id(), ports.id());
+    let name = root.get("name").context("missing name")?.id();
+
+    for (id, label) in [
+        (server, "server"),
+        (ports, "ports"),
+        (port, "port"),
+        (name, "name"),
+    ] {
+        doc.metadata_mut::<&'static str>().insert(id, label);
+    }
+
+    doc.metadata_mut::<u32>().insert(port, 443);
+
+    let copy = doc.clone();
+
+    let reload = doc.reload_from("name: other\nserver:\n  ports: [8080, 8443]\n")?;
+    let labels = doc.metadata::<&'static str>().context("missing labels")?;
+    assert_eq!(labels.len(), 4);
+    assert_eq!(labels.get(reload.get(port).context("port")?), Some(&"port"));
+    assert_eq!(doc.metadata::<u32>().map(|m| m.len()), Some(1));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.remove("server");
+
+    let labels = doc.metadata::<&'static str>().context("missing labels")?;
+    assert_eq!(labels.iter().map(|(_, l)| *l).collect::<Vec<_>>(), ["name"]);
+    assert_eq!(doc.metadata::<u32>().map(|m| m.len()), Some(0));
+
+    let labels = copy.metadata::<&'static str>().context("missing labels")?;
+    assert_eq!(labels.len(), 4);
+    assert_eq!(labels.get(port), Some(&"port"));
+    Ok(())
+}
+
+#[test]
+fn reload_settings() -> Result<()> {
+    let mut doc = yaml::from_slice("a:  1\n")?;
+    doc.set_separator(yaml::Separator::spaces(3));
+    doc.set_quoting(yaml::Quoting::new().prefer(yaml::QuoteStyle::Double));
+    doc.set_placeholder(yaml::Placeholder::new().delimiters("%", "%"));
+    doc.reload_from("a: 1\nb: 2\ne: '%E%'\nf: <F>\n")?;
+
+    let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+    assert_eq!(names, ["E"]);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("c", 3);
+    root.insert_str("d", "1.0");
+    assert_eq!(
+        doc.to_string(),
+        "a: 1\nb: 2\ne: '%E%'\nf: <F>\nc:   3\nd:   \"1.0\"\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn swap_block_values() -> Result<()> {
+    let mut doc = yaml::from_slice("a:\n  x: 1\n  y: 2\nb:\n- one\n- two\nc: [3, 4]\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?.id();
+    let b = root.get("b").context("missing b")?.id();
+    let c = root.get("c").context("missing c")?;
+    let three = c
+        .as_sequence()
+        .and_then(|s| Some(s.get(0)?.id()))
+        .context("missing 3")?;
+    let c = c.id();
+
+    assert!(!doc.swap(c, three));
+    assert!(!doc.swap(a, a));
+    assert!(doc.swap(a, b));
+    assert_eq!(
+        doc.to_string(),
+        "a:\n  - one\n  - two\nb:\n  x: 1\n  y: 2\nc: [3, 4]\n"
+    );
+
+    assert!(doc.swap(b, three));
+    assert_eq!(
+        doc.to_string(),
+        "a:\n  - one\n  - two\nb: 3\nc: [{x: 1, y: 2}, 4]\n"
+    );
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+    Ok(())
+}
+
+#[test]
+fn swap_moves_payloads() -> Result<()> {
+    let mut doc = yaml::from_slice("a:   [1, {x: 2}]\nb:\nc:\t3\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?;
+    let x = a
+        .as_sequence()
+        .and_then(|s| Some(s.get(1)?.as_mapping()?.get("x")?.id()))
+        .context("missing x")?;
+    let a = a.id();
+    let b = root.get("b").context("missing b")?.id();
+    let c = root.get("c").context("missing c")?.id();
+
+    assert!(doc.swap(a, c));
+    assert_eq!(doc.to_string(), "a:   3\nb:\nc:\t[1, {x: 2}]\n");
+    assert_eq!(doc.value(x).as_u32(), Some(2));
+
+    assert!(doc.copy_value(c, b));
+    assert_eq!(doc.to_string(), "a:   3\nb: [1, {x: 2}]\nc:\t[1, {x: 2}]\n");
+
+    doc.value_mut(x).set_u32(4);
+    assert_eq!(doc.to_string(), "a:   3\nb: [1, {x: 2}]\nc:\t[1, {x: 4}]\n");
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+    Ok(())
+}
+
+#[test]
+fn write_to_with_defaults() -> Result<()> {
+    const INPUT: &str = "# comment\nsteps: !!omap\n  - run: [a, {b: c} ]\n  - ? d\nset: !!set {e, f}\nrun: |\n  line\n";
+
+    let doc = yaml::from_slice(INPUT)?;
+
+    let mut out = Vec::new();
+    doc.write_to_with(&mut out, yaml::WriteOptions::new())?;
+    assert_eq!(out, INPUT.as_bytes());
+
+    let mut paths = Vec::new();
+
+    let options = yaml::WriteOptions::new().scalar_renderer(|value, path| {
+        paths.push(path.join("."));
+        value.as_str().map(str::to_uppercase)
+    });
+
+    let mut out = Vec::new();
+    doc.write_to_with(&mut out, options)?;
+
+    assert_eq!(
+        std::str::from_utf8(&out)?,
+        "# comment\nsteps: !!omap\n  - run: [A, {b: C} ]\n  - ? d\nset: !!set {e, f}\nrun: LINE\n\n"
+    );
+    assert_eq!(
+        paths,
+        [
+            "steps.0.run.0",
+            "steps.0.run.1.b",
+            "steps.1.d",
+            "set.e",
+            "set.f",
+            "run"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn reindent_round_trip() -> Result<()> {
+    const INPUT: &str = "# leading\n- a: 1\n  b:\n    - - x\n      - y\n  # between\n  c: !!set\n    ? d\n- {e: [1,\n    2]}\n- |\n  text\n   more\n# trailing\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    doc.reindent_all(2, 4)?;
+
+    assert_eq!(
+        doc.to_string(),
+        "# leading\n-   a: 1\n    b:\n        -   - x\n            - y\n    # between\n    c: !!set\n        ? d\n- {e: [1,\n        2]}\n- |\n    text\n     more\n# trailing\n"
+    );
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+
+    doc.reindent_all(4, 2)?;
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn comment_normalization() -> Result<()> {
+    const INPUT: &str = "#!shebang\n##heading\n- a: #first\n    b: 1\n- [x, #inline\n   y]\n- {c: 'd' #mapping\n  }\n#end";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.space_comments(), 5);
+    assert_eq!(
+        doc.to_string(),
+        "#!shebang\n## heading\n- a: # first\n    b: 1\n- [x, # inline\n   y]\n- {c: 'd' # mapping\n  }\n# end"
+    );
+
+    assert_eq!(doc.align_comments(8), 2);
+    assert_eq!(
+        doc.to_string(),
+        "#!shebang\n## heading\n- a:    # first\n    b: 1\n- [x,   # inline\n   y]\n- {c: 'd' # mapping\n  }\n# end"
+    );
+
+    assert_eq!(doc.align_comments(2), 2);
+    assert_eq!(
+        doc.to_string(),
+        "#!shebang\n## heading\n- a: # first\n    b: 1\n- [x, # inline\n   y]\n- {c: 'd' # mapping\n  }\n# end"
+    );
+
+    assert_eq!(doc.strip_comments(), 5);
+    assert_eq!(
+        doc.to_string(),
+        "- a:\n    b: 1\n- [x,\n   y]\n- {c: 'd'\n  }"
+    );
+    assert_eq!(doc.strip_comments(), 0);
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+    Ok(())
+}
+
+#[test]
+fn fingerprint_ignores_formatting() -> Result<()> {
+    const INPUT: &str =
+        "# generated\nname: app\nsteps:\n  - run: |\n      make\n  - !!set {a, b}\nempty: ~\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let fingerprint = doc.fingerprint();
+
+    doc.reindent_all(2, 4)?;
+    doc.strip_comments();
+    assert!(doc.matches_fingerprint(fingerprint));
+
+    for other in [
+        "name: app\nsteps: [{run: make}, !!set {a, b}]\nempty: ~\n",
+        "name: app\nsteps:\n  - run: |\n      make\n  - !!set {a, b}\nempty: null\n",
+        "name: app\nsteps:\n  - run: |-\n      make\n  - !!set {a, b}\nempty: ~\n",
+        "name: app\nsteps:\n  - run: |\n      make\n  - !!set {b, a}\nempty: ~\n",
+    ] {
+        let other = yaml::from_slice(other)?;
+        assert!(!other.matches_fingerprint(fingerprint), "{other}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn locked_regions() -> Result<()> {
+    let mut doc = yaml::from_slice("- a: {b: 1}\n- c\n- d\n")?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let first = root.get(0).context("missing first")?;
+    let b = first
+        .as_mapping()
+        .and_then(|m| m.get("a"))
+        .context("missing a")?;
+    let b = b
+        .as_mapping()
+        .and_then(|m| m.get("b"))
+        .context("missing b")?
+        .id();
+    let first = first.id();
+    let c = root.get(1).context("missing c")?.id();
+    let d = root.get(2).context("missing d")?.id();
+
+    assert!(doc.lock(first, "human"));
+    assert!(!doc.lock(b, "bot"));
+
+    doc.set_actor(Some("human"));
+    assert!(doc.lock(b, "bot"));
+    assert_eq!(doc.owner(b), Some("bot"));
+    assert!(doc.is_locked(b));
+    assert!(!doc.is_locked(first));
+
+    doc.set_actor(Some("bot"));
+    assert!(!doc.is_locked(b));
+    assert!(doc.is_locked(first));
+    assert!(!doc.swap(first, c));
+    assert!(!doc.copy_value(c, first));
+    assert!(doc.copy_value(first, d));
+    assert!(doc.swap(c, d));
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(root.get_mut(0).is_none());
+    assert!(!root.remove(0));
+    assert!(root.remove(2));
+
+    doc.set_actor(Some("human"));
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    assert!(!root.remove(0));
+
+    let reload = doc.reload_from("- a: {b: 2}\n- extra\n")?;
+    let first = reload.get(first).context("first did not survive")?;
+    let b = reload.get(b).context("b did not survive")?;
+    assert_eq!(doc.owner(first), Some("human"));
+    assert_eq!(doc.owner(b), Some("bot"));
+    assert!(doc.is_locked(b));
+    assert!(!doc.is_locked(first));
+    Ok(())
+}
+
+#[test]
+fn anchors_and_aliases() -> Result<()> {
+    const INPUT: &str = "&root\nbase: &base #shared\n  a: 1\nother:\n  <<: *base\n  b: &b 2\nlist: [&x 1, *x ]\nempty: &e\nseq:\n- &s one\n- *s\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    assert_eq!(out, INPUT.as_bytes());
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(doc.as_ref().anchor(), Some("root".into()));
+    assert_eq!(root.get("empty").and_then(|v| v.anchor()), Some("e".into()));
+
+    let seq = root
+        .get("seq")
+        .and_then(|v| v.as_sequence())
+        .context("missing seq")?;
+    assert_eq!(seq.get(0).and_then(|v| v.as_str()), Some("one"));
+    assert_eq!(seq.get(0).and_then(|v| v.anchor()), Some("s".into()));
+    assert!(seq.get(1).context("missing alias")?.is_alias());
+    assert_eq!(seq.get(1).and_then(|v| v.alias()), Some("s".into()));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut other = root
+        .get_mut("other")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing other")?;
+    other.get_mut("b").context("missing b")?.set_u32(3);
+
+    assert_eq!(doc.space_comments(), 1);
+    doc.reindent_all(2, 4)?;
+
+    assert_eq!(
+        doc.to_string(),
+        "&root\nbase: &base # shared\n    a: 1\nother:\n    <<: *base\n    b: &b 3\nlist: [&x 1, *x ]\nempty: &e\nseq:\n- &s one\n- *s\n"
+    );
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+    Ok(())
+}
+
+#[test]
+fn create_anchors_and_aliases() -> Result<()> {
+    let mut doc = yaml::from_slice("- a: 1\n  b: 2\n- c:\n  - x\n- ~\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0)
+        .context("missing first")?
+        .set_anchor("first")?;
+    root.get_mut(2)
+        .context("missing third")?
+        .set_anchor("third")?;
+
+    let mut second = root
+        .get_mut(1)
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing second")?;
+    second.get_mut("c").context("missing c")?.set_anchor("c")?;
+    second.insert_alias("d", "first")?;
+
+    assert_eq!(
+        doc.to_string(),
+        "- &first\n  a: 1\n  b: 2\n- c: &c\n  - x\n  d: *first\n- &third ~\n"
+    );
+
+    let reparsed = yaml::from_slice(doc.to_string())?;
+    assert_eq!(reparsed.to_string(), doc.to_string());
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0)
+        .context("missing first")?
+        .set_anchor("renamed")?;
+    assert!(root.get_mut(2).context("missing third")?.remove_anchor());
+
+    assert_eq!(
+        doc.to_string(),
+        "- &renamed\n  a: 1\n  b: 2\n- c: &c\n  - x\n  d: *first\n- ~\n"
+    );
+
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    doc.as_mut().set_anchor("root")?;
+    assert_eq!(doc.to_string(), "&root\na: 1\n");
+    Ok(())
+}
+
+#[test]
+fn merge_keys() -> Result<()> {
+    const INPUT: &str = "a: &a {x: 1, y: 1}\nb: &b\n  <<: *a\n  y: 2\n  z: 2\nc:\n  <<: [*b, {w: 3, x: 3}]\na2: &a {x: 4}\nd:\n  <<: *a\nloop: &loop\n  <<: *loop\n";
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let get = |name: &str, key: &str| {
+        root.get(name)
+            .and_then(|v| v.as_mapping())
+            .and_then(|m| m.get_merged(key))
+            .and_then(|v| v.as_u32())
+    };
+
+    assert_eq!(get("b", "x"), Some(1));
+    assert_eq!(get("b", "y"), Some(2));
+    assert_eq!(get("c", "x"), Some(1));
+    assert_eq!(get("c", "z"), Some(2));
+    assert_eq!(get("c", "w"), Some(3));
+    assert_eq!(get("d", "x"), Some(4));
+    assert_eq!(get("d", "y"), None);
+    assert_eq!(get("loop", "x"), None);
+
+    let c = root
+        .get("c")
+        .and_then(|v| v.as_mapping())
+        .context("missing c")?;
+    assert!(c
+        .get_merged("<<")
+        .is_some_and(|v| v.as_sequence().is_some()));
+    Ok(())
+}
+
+#[test]
+fn set_and_clear_tags() -> Result<()> {
+    let mut doc = yaml::from_slice("- a: 1\n  b: 2\n- &x c\n- [1, 2]\n- ~\n")?;
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(0).context("missing first")?.set_tag("!Map")?;
+    root.get_mut(1).context("missing second")?.set_tag("!Ref")?;
+    root.get_mut(2).context("missing third")?.set_tag("!!seq")?;
+    root.get_mut(3)
+        .context("missing fourth")?
+        .set_tag("!Null")?;
+
+    assert_eq!(
+        doc.to_string(),
+        "- !Map\n  a: 1\n  b: 2\n- &x !Ref c\n- !!seq [1, 2]\n- !Null ~\n"
+    );
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(1).and_then(|v| v.tag()), Some("!Ref".into()));
+    assert_eq!(root.get(2).and_then(|v| v.tag()), Some("!!seq".into()));
+
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.get_mut(1).context("missing second")?.set_tag("!Sub")?;
+    assert!(root.get_mut(2).context("missing third")?.clear_tag());
+    assert!(root.get_mut(3).context("missing fourth")?.clear_tag());
+    assert!(!root.get_mut(3).context("missing fourth")?.clear_tag());
+
+    assert_eq!(
+        doc.to_string(),
+        "- !Map\n  a: 1\n  b: 2\n- &x !Sub c\n- [1, 2]\n- ~\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn end_marker() -> Result<()> {
+    const CASES: &[(&str, bool)] = &[
+        ("a: 1\n...\n", true),
+        ("- a\n... # end", true),
+        ("|\n  block\n...\n", true),
+        ("...\n", true),
+        ("a: ...\n", false),
+        ("a: 1 ...\n", false),
+    ];
+
+    for &(input, end) in CASES {
+        let doc = yaml::from_slice(input)?;
+        assert_eq!(doc.has_end_marker(), end, "{input:?}");
+        assert_eq!(doc.to_string(), input, "{input:?}");
+    }
+
+    let doc = yaml::from_slice("|\n  block\n...\n")?;
+    assert_eq!(doc.as_ref().as_str(), Some("block\n"));
+
+    let error = yaml::from_slice("a\n...\nb\n").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::ExpectedEof);
+    assert_eq!(error.span(), 6..8);
+    Ok(())
+}
+
+#[test]
+fn long_lines() -> Result<()> {
+    let json = r#"{"key":"value","list":[1,2,3]},"#.repeat(1 << 14);
+    let escaped = json.replace('"', "\\\"");
+
+    let input = format!(
+        "plain: x{json}\nsingle: 'it''s {json}'\ndouble: \"\\t{escaped}\"\nblock: |\n  {json}\nflow: [{json}]\n",
+    );
+
+    let mut doc = yaml::from_slice(&input)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(
+        root.get("plain").and_then(|v| v.as_str()),
+        Some(format!("x{json}").as_str())
+    );
+    assert_eq!(
+        root.get("single").and_then(|v| v.as_str()),
+        Some(format!("it's {json}").as_str())
+    );
+    assert_eq!(
+        root.get("double").and_then(|v| v.as_str()),
+        Some(format!("\t{json}").as_str())
+    );
+    assert_eq!(
+        root.get("block").and_then(|v| v.as_str()),
+        Some(format!("{json}\n").as_str())
+    );
+
+    let flow = root
+        .get("flow")
+        .and_then(|v| v.as_sequence())
+        .context("missing flow")?;
+    assert_eq!(flow.len(), 1 << 14);
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("plain", 42);
+
+    assert_eq!(
+        doc.to_string(),
+        input.replacen(&format!("plain: x{json}\n"), "plain: 42\n", 1)
+    );
+    Ok(())
+}
+
+#[test]
+fn tree_statistics() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        a:
+          b: [1, {c: ~}]
+          d: |
+            hello
+        e:
+        ",
+    )?;
+
+    assert_eq!(doc.node_count(), 13);
+    assert_eq!(doc.max_depth(), 4);
+    assert_eq!(doc.scalar_bytes(), 7 + "hello\n".len());
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("f", "world");
+    assert_eq!(doc.node_count(), 15);
+    assert_eq!(doc.scalar_bytes(), 13 + 1 + 5);
+
+    let input = format!("{}{}", "[".repeat(256), "]".repeat(256));
+    let doc = yaml::from_slice(input)?;
+    assert_eq!(doc.node_count(), 256);
+    assert_eq!(doc.max_depth(), 256);
+    Ok(())
+}
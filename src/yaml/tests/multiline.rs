@@ -198,3 +198,331 @@ fn string_spaces() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn push_block_line() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        strip: |-
+          foo
+        keep: |+
+          foo
+
+        folded: >
+          foo
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root
+        .get_mut("strip")
+        .context("missing strip")?
+        .push_block_line("bar\n\nbaz"));
+    assert!(root
+        .get_mut("keep")
+        .context("missing keep")?
+        .push_block_line("bar"));
+    assert!(!root
+        .get_mut("folded")
+        .context("missing folded")?
+        .push_block_line("bar"));
+
+    let expected = r"
+        strip: |-
+          foo
+          bar
+
+          baz
+        keep: |+
+          foo
+
+          bar
+
+        folded: >
+          foo
+        ";
+
+    assert_eq!(doc.to_string(), expected);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("strip").and_then(|v| v.as_str()),
+        Some("foo\nbar\n\nbaz")
+    );
+    assert_eq!(
+        root.get("keep").and_then(|v| v.as_str()),
+        Some("foo\n\nbar\n\n")
+    );
+
+    let reparsed = yaml::from_slice(expected)?;
+    let reparsed = reparsed.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        reparsed.get("strip").and_then(|v| v.as_str()),
+        root.get("strip").and_then(|v| v.as_str())
+    );
+    assert_eq!(
+        reparsed.get("keep").and_then(|v| v.as_str()),
+        root.get("keep").and_then(|v| v.as_str())
+    );
+
+    let mut doc = yaml::from_slice("- x\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    let mut value = root.get_mut(0).context("missing item")?;
+    value.set_block(["foo"], yaml::Block::Literal(yaml::Chomp::Clip));
+    assert!(value.push_block_line("bar"));
+    assert_eq!(doc.to_string(), "- |\n   foo\n   bar\n");
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("foo\nbar\n"));
+    Ok(())
+}
+
+#[test]
+fn push_block_line_keep() -> Result<()> {
+    let mut doc = yaml::from_slice("a: |+\n  foo\n\n\nb: |+\n  foo\nc: |+\n  foo\n\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for key in ["a", "b", "c"] {
+        let mut value = root.get_mut(key).context("missing value")?;
+        assert!(value.push_block_line("bar\nbaz"));
+    }
+
+    let expected = "a: |+\n  foo\n\n\n  bar\n  baz\n\n\nb: |+\n  foo\n  bar\n  baz\nc: |+\n  foo\n\n  bar\n  baz\n\n";
+    assert_eq!(doc.to_string(), expected);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let reparsed = yaml::from_slice(expected)?;
+    let reparsed = reparsed.as_ref().as_mapping().context("missing root")?;
+
+    for (key, value) in [
+        ("a", "foo\n\n\nbar\nbaz\n\n\n"),
+        ("b", "foo\nbar\nbaz\n"),
+        ("c", "foo\n\nbar\nbaz\n\n"),
+    ] {
+        assert_eq!(root.get(key).and_then(|v| v.as_str()), Some(value));
+        assert_eq!(reparsed.get(key).and_then(|v| v.as_str()), Some(value));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn indentation_indicators() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        first: |2
+            indented
+          text
+        second: >-1
+          folded
+        third: 3
+        ",
+    )?;
+
+    let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+
+    assert_eq!(
+        root.get("first").and_then(|v| v.as_str()),
+        Some("  indented\ntext\n")
+    );
+    assert_eq!(root.get("second").and_then(|v| v.as_str()), Some(" folded"));
+    assert_eq!(root.get("third").and_then(|v| v.as_u32()), Some(3));
+
+    let doc = yaml::from_slice("- |1\n  explicit\n- |\n detected\n")?;
+    let root = doc
+        .as_ref()
+        .as_sequence()
+        .context("missing root sequence")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some(" explicit\n"));
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("detected\n"));
+    Ok(())
+}
+
+#[test]
+fn block_roundtrip_leading_whitespace() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        script: ~
+        name: ci
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut script = root.get_mut("script").context("missing script")?;
+    script.set_block(
+        ["  indented", "first"],
+        yaml::Block::Literal(yaml::Chomp::Clip),
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(
+        root.get("script").and_then(|v| v.as_str()),
+        Some("  indented\nfirst\n")
+    );
+    assert_eq!(root.get("name").and_then(|v| v.as_str()), Some("ci"));
+
+    let mut doc = yaml::from_slice("- x\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.push_block([" one", "two"], yaml::Block::Literal(yaml::Chomp::Strip));
+    assert!(root
+        .get_mut(1)
+        .context("missing block")?
+        .push_block_line("  three"));
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(
+        root.get(1).and_then(|v| v.as_str()),
+        Some(" one\ntwo\n  three")
+    );
+    Ok(())
+}
+
+#[test]
+fn set_string_preserving_style() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        r"
+        literal: |+
+            first
+
+        folded: >-
+          one
+          two
+        explicit: |2
+            indented
+        quoted: 'single'
+        double: double
+        ",
+    )?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("literal")
+        .context("missing literal")?
+        .set_string_preserving_style("a\n\nb");
+    root.get_mut("folded")
+        .context("missing folded")?
+        .set_string_preserving_style("one two three\n");
+    root.get_mut("explicit")
+        .context("missing explicit")?
+        .set_string_preserving_style("  more\nless\n");
+    root.get_mut("quoted")
+        .context("missing quoted")?
+        .set_string_preserving_style("it's\nhere");
+    root.get_mut("double")
+        .context("missing double")?
+        .set_string_preserving_style("true");
+
+    assert_eq!(
+        doc.to_string(),
+        r#"
+        literal: |-
+            a
+
+            b
+
+        folded: >
+          one two three
+        explicit: |2
+            more
+          less
+        quoted: "it's\nhere"
+        double: 'true'
+        "#
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let get = |key| root.get(key).and_then(|v| v.as_str());
+
+    assert_eq!(get("literal"), Some("a\n\nb"));
+    assert_eq!(get("folded"), Some("one two three\n"));
+    assert_eq!(get("explicit"), Some("  more\nless\n"));
+    assert_eq!(get("quoted"), Some("it's\nhere"));
+    assert_eq!(get("double"), Some("true"));
+    Ok(())
+}
+
+#[test]
+fn flow_plain_multiline() -> Result<()> {
+    const INPUT: &str =
+        "seq: [a b\n  c d, e, f\n\n   g  \n  h, 1\n  2, 3 ]\nmap: {one\n  two: x\n   y, z: w}\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let seq = root
+        .get("seq")
+        .and_then(|v| v.as_sequence())
+        .context("missing seq")?;
+
+    assert_eq!(seq.get(0).and_then(|v| v.as_str()), Some("a b c d"));
+    assert_eq!(seq.get(1).and_then(|v| v.as_str()), Some("e"));
+    assert_eq!(seq.get(2).and_then(|v| v.as_str()), Some("f\ng h"));
+    assert_eq!(seq.get(3).and_then(|v| v.as_str()), Some("1 2"));
+    assert_eq!(seq.get(4).and_then(|v| v.as_u32()), Some(3));
+
+    let map = root
+        .get("map")
+        .and_then(|v| v.as_mapping())
+        .context("missing map")?;
+
+    assert_eq!(map.get("one two").and_then(|v| v.as_str()), Some("x y"));
+    assert_eq!(map.get("z").and_then(|v| v.as_str()), Some("w"));
+
+    let id = doc
+        .resolve_json_pointer("/seq/2")
+        .context("missing value")?;
+    doc.value_mut(id).set_string("replaced");
+
+    assert_eq!(
+        doc.to_string(),
+        "seq: [a b\n  c d, e, replaced, 1\n  2, 3 ]\nmap: {one\n  two: x\n   y, z: w}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn block_in_flow() -> Result<()> {
+    let mut doc = yaml::from_slice("a: [1]\nb: {c: 1}\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut a = root
+        .get_mut("a")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing a")?;
+    a.push(yaml::Separator::Auto)
+        .set_block(["a", "b"], yaml::Block::Literal(yaml::Chomp::Clip));
+    a.push_block(["c"], yaml::Block::Folded(yaml::Chomp::Strip));
+
+    let mut b = root
+        .get_mut("b")
+        .and_then(|v| v.into_mapping_mut())
+        .context("missing b")?;
+    b.get_mut("c")
+        .context("missing c")?
+        .set_block(["d", "e"], yaml::Block::Folded(yaml::Chomp::Keep));
+    b.insert_block("f", ["g"], yaml::Block::Literal(yaml::Chomp::Strip));
+
+    assert_eq!(
+        doc.to_string(),
+        "a: [1, \"a\\nb\\n\", \"c\"]\nb: {c: \"d e\\n\", f: \"g\"}\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").and_then(|v| v.as_sequence());
+    let a = a.context("missing a")?;
+    assert_eq!(a.get(1).and_then(|v| v.as_str()), Some("a\nb\n"));
+    assert_eq!(a.get(2).and_then(|v| v.as_str()), Some("c"));
+    let b = root.get("b").and_then(|v| v.as_mapping());
+    let b = b.context("missing b")?;
+    assert_eq!(b.get("c").and_then(|v| v.as_str()), Some("d e\n"));
+    assert_eq!(b.get("f").and_then(|v| v.as_str()), Some("g"));
+    Ok(())
+}
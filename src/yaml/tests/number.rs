@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn special_floats() -> Result<()> {
+    const INPUT: &str = "a: .inf\nb: -.Inf\nc: .NaN\nd: [.INF, -.inf, .nan]\ne: .info\nf: -.nan\n";
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    assert_eq!(root.get("a").and_then(|v| v.as_f64()), Some(f64::INFINITY));
+    assert_eq!(
+        root.get("b").and_then(|v| v.as_f32()),
+        Some(f32::NEG_INFINITY)
+    );
+    assert!(root
+        .get("c")
+        .and_then(|v| v.as_f64())
+        .is_some_and(f64::is_nan));
+    assert_eq!(root.get("c").and_then(|v| v.as_u32()), None);
+
+    let d = root
+        .get("d")
+        .and_then(|v| v.as_sequence())
+        .context("missing d")?;
+    let d = d
+        .iter()
+        .map(|v| v.as_number().is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(d, [true, true, true]);
+
+    assert_eq!(root.get("e").and_then(|v| v.as_str()), Some(".info"));
+    assert_eq!(root.get("f").and_then(|v| v.as_str()), Some("-.nan"));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn set_special_floats() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: [2]\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("a")
+        .context("missing a")?
+        .set_f64(f64::NEG_INFINITY);
+    root.get_mut("b")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing b")?
+        .push_f32(f32::NAN);
+    root.insert_f64("c", f64::INFINITY);
+    root.insert_str("d", ".inf");
+
+    assert_eq!(
+        doc.to_string(),
+        "a: -.inf\nb: [2, .nan]\nc: .inf\nd: '.inf'\n"
+    );
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(
+        root.get("a").and_then(|v| v.as_f64()),
+        Some(f64::NEG_INFINITY)
+    );
+    assert_eq!(root.get("c").and_then(|v| v.as_f64()), Some(f64::INFINITY));
+    assert_eq!(root.get("d").and_then(|v| v.as_str()), Some(".inf"));
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn deserialize_special_floats() -> Result<()> {
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    let doc = yaml::from_slice("[.inf, -.inf]")?;
+    let values = Vec::<f64>::deserialize(doc.as_ref().into_deserializer())?;
+    assert_eq!(values, [f64::INFINITY, f64::NEG_INFINITY]);
+    Ok(())
+}
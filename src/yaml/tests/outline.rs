@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+
+use crate::yaml::{self, Outline, OutlineKind};
+
+/// Collect every entry in the outline as its path joined by `.`, its kind and
+/// the text covered by its span.
+fn collect(outline: &Outline, text: &str, out: &mut Vec<(String, OutlineKind, String)>) {
+    out.push((
+        outline.path().join("."),
+        outline.kind(),
+        text[outline.span()].to_owned(),
+    ));
+
+    for child in outline.children() {
+        collect(child, text, out);
+    }
+}
+
+#[test]
+fn nested() -> Result<()> {
+    let doc = yaml::from_slice(
+        "%YAML 1.2\n---\nspec: # the spec\n  tags: [a, {b: 1}]\n  items:\n  - name: x\n    on: true\n  - ~\n",
+    )?;
+
+    let text = doc.to_string();
+    let mut entries = Vec::new();
+    collect(&doc.outline(), &text, &mut entries);
+
+    let expected = [
+        (
+            "",
+            OutlineKind::Mapping,
+            "spec: # the spec\n  tags: [a, {b: 1}]\n  items:\n  - name: x\n    on: true\n  - ~",
+        ),
+        (
+            "spec",
+            OutlineKind::Mapping,
+            "spec: # the spec\n  tags: [a, {b: 1}]\n  items:\n  - name: x\n    on: true\n  - ~",
+        ),
+        ("spec.tags", OutlineKind::Sequence, "tags: [a, {b: 1}]"),
+        ("spec.tags.0", OutlineKind::String, "a"),
+        ("spec.tags.1", OutlineKind::Mapping, "{b: 1}"),
+        ("spec.tags.1.b", OutlineKind::Number, "b: 1"),
+        (
+            "spec.items",
+            OutlineKind::Sequence,
+            "items:\n  - name: x\n    on: true\n  - ~",
+        ),
+        (
+            "spec.items.0",
+            OutlineKind::Mapping,
+            "- name: x\n    on: true",
+        ),
+        ("spec.items.0.name", OutlineKind::String, "name: x"),
+        ("spec.items.0.on", OutlineKind::Boolean, "on: true"),
+        ("spec.items.1", OutlineKind::Null, "- ~"),
+    ];
+
+    let expected = expected
+        .into_iter()
+        .map(|(path, kind, text)| (path.to_owned(), kind, text.to_owned()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(entries, expected);
+    Ok(())
+}
+
+#[test]
+fn ids() -> Result<()> {
+    let doc = yaml::from_slice("a:\n  b: 1\n")?;
+    let outline = doc.outline();
+
+    let b = outline
+        .children()
+        .first()
+        .and_then(|a| a.children().first())
+        .context("missing b")?;
+
+    assert_eq!(b.name(), Some("b"));
+    assert_eq!(doc.value(b.id()).as_u32(), Some(1));
+    assert_eq!(doc.json_pointer(b.id()).as_deref(), Some("/a/b"));
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+use crate::yaml;
+
+#[test]
+fn flatten() -> Result<()> {
+    let base = yaml::from_slice(
+        r"
+        # service configuration
+        name: app
+        limits: {cpu: 1, memory: 512}
+        hosts:
+          - a
+          - b
+        ",
+    )?;
+
+    let overrides = yaml::from_slice(
+        r#"
+        limits:
+          memory: 1024
+          "disk space": 10
+        hosts: [c]
+        tls:
+          enabled: true
+        "#,
+    )?;
+
+    let overlay = yaml::Overlay::new(base, overrides);
+
+    assert_eq!(
+        overlay.flatten().to_string(),
+        r#"
+        # service configuration
+        name: app
+        limits: {cpu: 1, memory: 1024, "disk space": 10}
+        hosts: [c]
+        tls:
+          enabled: true
+        "#
+    );
+
+    let (base, _) = overlay.into_parts();
+    let overlay = yaml::Overlay::new(base.clone(), yaml::from_slice("")?);
+    assert_eq!(overlay.flatten().to_string(), base.to_string());
+    Ok(())
+}
+
+#[test]
+fn replaced_mapping() -> Result<()> {
+    let base = yaml::from_slice("{a: {b: 1}, c: 2}")?;
+    let overrides = yaml::from_slice("{a: 3}")?;
+    let overlay = yaml::Overlay::new(base, overrides);
+
+    assert!(overlay.get(&["a", "b"]).is_none());
+    assert!(overlay.keys(&["a"]).is_none());
+    assert_eq!(overlay.get(&["c"]).and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(overlay.flatten().to_string(), "{a: 3, c: 2}");
+    Ok(())
+}
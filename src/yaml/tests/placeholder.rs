@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn two_phase() -> Result<()> {
+    let mut doc = yaml::from_slice("name: app # the name\nimage: nginx\nports: [80]\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("image")
+        .context("missing image")?
+        .set_placeholder("IMAGE");
+    root.insert("token", yaml::Separator::Auto)?
+        .set_placeholder("TOKEN");
+
+    let mut ports = root
+        .get_mut("ports")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing ports")?;
+    ports.push(yaml::Separator::Auto).set_placeholder("PORT");
+
+    let generated = doc.to_string();
+    assert_eq!(
+        generated,
+        "name: app # the name\nimage: <IMAGE>\nports: [80, <PORT>]\ntoken: <TOKEN>\n"
+    );
+
+    let mut doc = yaml::from_slice(generated)?;
+
+    let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+    assert_eq!(names, ["IMAGE", "PORT", "TOKEN"]);
+
+    let count = doc.fill_placeholders(|name| match name {
+        "IMAGE" => Some("nginx:1.25".into()),
+        "PORT" => Some("443".into()),
+        _ => None,
+    });
+
+    assert_eq!(count, 2);
+    assert_eq!(
+        doc.to_string(),
+        "name: app # the name\nimage: nginx:1.25\nports: [80, '443']\ntoken: <TOKEN>\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn not_placeholders() -> Result<()> {
+    let doc = yaml::from_slice("[<>, <a, a>, <a>b>, 1, <x>]")?;
+    let names = doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>();
+    assert_eq!(names, ["x"]);
+    Ok(())
+}
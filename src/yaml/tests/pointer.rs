@@ -0,0 +1,71 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+/// Collect the identifiers of all values reachable from `value`.
+fn collect(value: yaml::Value<'_>, out: &mut Vec<yaml::Id>) {
+    out.push(value.id());
+
+    if let Some(mapping) = value.as_mapping() {
+        for (_, value) in mapping.iter() {
+            collect(value, out);
+        }
+    }
+
+    if let Some(sequence) = value.as_sequence() {
+        for value in sequence.iter() {
+            collect(value, out);
+        }
+    }
+}
+
+#[test]
+fn round_trip() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        a: [1, {b: 2, c/d: [x, y]}]
+        e~f:
+          - - nested
+          - last
+        g: empty
+        ",
+    )?;
+
+    let mut ids = Vec::new();
+    collect(doc.as_ref(), &mut ids);
+    assert_eq!(ids.len(), 13);
+
+    for id in ids {
+        let pointer = doc.json_pointer(id).context("missing pointer")?;
+        assert_eq!(doc.resolve_json_pointer(&pointer), Some(id), "{pointer}");
+    }
+
+    let pointers = [
+        ("/a/1/c~1d/1", Some("y")),
+        ("/e~0f/0/0", Some("nested")),
+        ("/g", Some("empty")),
+        ("/", None),
+        ("/a/1/c~1d/-", None),
+        ("/a/1/c~1d/+1", None),
+        ("/e~0f/1/0", None),
+    ];
+
+    for (pointer, expected) in pointers {
+        let value = doc.resolve_json_pointer(pointer).map(|id| doc.value(id));
+        assert_eq!(value.and_then(|v| v.as_str()), expected, "{pointer}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn removed_values() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+    let b = doc.resolve_json_pointer("/b").context("missing b")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.remove("b");
+
+    assert_eq!(doc.json_pointer(b), None);
+    Ok(())
+}
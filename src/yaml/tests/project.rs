@@ -0,0 +1,101 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("nondestructive-{name}-{}", process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn save_only_modified() -> Result<()> {
+    let dir = TempDir::new("project")?;
+    let a = dir.0.join("a.yaml");
+    let b = dir.0.join("b.yaml");
+
+    fs::write(&a, "# first\nversion: 1\n")?;
+    fs::write(&b, "version: 1\nname: b\n")?;
+
+    let mut project = yaml::Project::new();
+    project.open(&a)?;
+    project.open(&b)?;
+
+    assert_eq!(project.modified().count(), 0);
+    assert_eq!(project.save()?, 0);
+
+    let mut value = project
+        .get_mut(&a)
+        .and_then(|doc| {
+            doc.resolve_json_pointer("/version")
+                .map(|id| doc.value_mut(id))
+        })
+        .context("missing version")?;
+    value.set_u32(2);
+
+    assert!(project.is_modified(&a));
+    assert!(!project.is_modified(&b));
+
+    // Remove the untouched file to prove that it isn't written back.
+    fs::remove_file(&b)?;
+
+    assert_eq!(project.save()?, 1);
+    assert_eq!(fs::read_to_string(&a)?, "# first\nversion: 2\n");
+    assert!(!b.exists());
+
+    assert!(!project.is_modified(&a));
+    assert_eq!(project.save()?, 0);
+
+    // Setting a value back to what it was saved as is not a modification.
+    let count = project.update_json_pointer("/version", |_, mut value| {
+        value.set_u32(1);
+    });
+
+    assert_eq!(count, 2);
+    assert_eq!(
+        project.modified().collect::<Vec<_>>(),
+        [a.as_path()],
+        "only a.yaml differs from what was saved"
+    );
+
+    let c = dir.0.join("c.yaml");
+    project.insert(&c, yaml::from_slice("version: 3\n")?);
+    assert!(project.is_modified(&c));
+
+    assert_eq!(project.save()?, 2);
+    assert_eq!(fs::read_to_string(&c)?, "version: 3\n");
+    Ok(())
+}
+
+#[test]
+fn open_errors() -> Result<()> {
+    let dir = TempDir::new("project-errors")?;
+    let path = dir.0.join("bad.yaml");
+    fs::write(&path, "a: [1, 2\n")?;
+
+    let mut project = yaml::Project::new();
+
+    let error = project.open(&path).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    let error = project.open(dir.0.join("missing.yaml")).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+    assert_eq!(project.iter().count(), 0);
+    Ok(())
+}
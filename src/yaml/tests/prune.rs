@@ -0,0 +1,108 @@
+use anyhow::{Context, Result};
+
+use crate::yaml::{self, Prune};
+
+#[test]
+fn empty_helpers() -> Result<()> {
+    let doc = yaml::from_slice("a: {}\nb: []\nc:\n  d: 1\ne:\n  - 1\nf:\ng: ''\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let get = |key| root.get(key).context("missing key");
+
+    assert!(get("a")?.is_empty_mapping());
+    assert!(!get("a")?.is_empty_sequence());
+    assert!(get("a")?.is_empty_collection());
+
+    assert!(!get("b")?.is_empty_mapping());
+    assert!(get("b")?.is_empty_sequence());
+    assert!(get("b")?.is_empty_collection());
+
+    for key in ["c", "e", "f", "g"] {
+        assert!(!get(key)?.is_empty_collection(), "{key}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn empty_after_clear() -> Result<()> {
+    let mut doc = yaml::from_slice("a:\n  b: 1\nc: [1, 2]\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    let a = root.get_mut("a").context("missing a")?;
+    a.into_mapping_mut().context("missing a")?.clear()?;
+
+    let c = root.get_mut("c").context("missing c")?;
+    c.into_sequence_mut().context("missing c")?.clear()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert!(root.get("a").is_some_and(|v| v.is_empty_mapping()));
+    assert!(root.get("c").is_some_and(|v| v.is_empty_sequence()));
+    Ok(())
+}
+
+#[test]
+fn root_is_kept() -> Result<()> {
+    for input in ["{}", "[]", "~", "a: {}\n", "- []\n"] {
+        let mut doc = yaml::from_slice(input)?;
+        doc.prune_empty(Prune::CollectionsAndNulls)?;
+        assert!(doc.as_ref().is_empty_collection() || doc.as_ref().is_null());
+    }
+
+    let mut doc = yaml::from_slice("~")?;
+    assert_eq!(doc.prune_empty(Prune::CollectionsAndNulls)?, 0);
+    assert_eq!(doc.to_string(), "~");
+    Ok(())
+}
+
+#[test]
+fn nested() -> Result<()> {
+    let mut doc = yaml::from_slice("a: {b: {c: []}, d: 1}\ne: [[], [[]], 2]\n")?;
+    assert_eq!(doc.prune_empty(Prune::Collections)?, 5);
+    assert_eq!(doc.to_string(), "a: {d: 1}\ne: [2]\n");
+
+    let mut doc = yaml::from_slice("a:\n  b:\n    c: []\nd: 1\n")?;
+    assert_eq!(doc.prune_empty(Prune::Collections)?, 3);
+    assert_eq!(doc.to_string(), "d: 1\n");
+    Ok(())
+}
+
+#[test]
+fn nulls() -> Result<()> {
+    const INPUT: &str = "a: ~\nb: null\nc:\nd: ''\ne: [~, 1, null]\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.prune_empty(Prune::Collections)?, 0);
+    assert_eq!(doc.to_string(), INPUT);
+
+    assert_eq!(doc.prune_empty(Prune::CollectionsAndNulls)?, 5);
+    assert_eq!(doc.to_string(), "d: ''\ne: [1]\n");
+
+    // A collection which only contains nulls is removed along with them.
+    let mut doc = yaml::from_slice("a:\n  b:\n  c: ~\nd: 1\n")?;
+    assert_eq!(doc.prune_empty(Prune::CollectionsAndNulls)?, 3);
+    assert_eq!(doc.to_string(), "d: 1\n");
+    Ok(())
+}
+
+#[test]
+fn nothing_to_prune() -> Result<()> {
+    const INPUT: &str = "a: 1 # one\nb: [x, {y: z}]\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.prune_empty(Prune::CollectionsAndNulls)?, 0);
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn eol_comment_survives_prune() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1 # c\nb: [] # d\n")?;
+    assert_eq!(doc.prune_empty(Prune::Collections)?, 1);
+    assert_eq!(doc.to_string(), "a: 1 # c\n");
+
+    let mut doc = yaml::from_slice("- a # x\n- ~\n- b\n")?;
+    assert_eq!(doc.prune_empty(Prune::CollectionsAndNulls)?, 1);
+    assert_eq!(doc.to_string(), "- a # x\n- b\n");
+    Ok(())
+}
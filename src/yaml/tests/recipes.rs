@@ -0,0 +1,126 @@
+use anyhow::Result;
+
+use crate::yaml::{self, recipes};
+
+#[test]
+fn get() -> Result<()> {
+    let doc = yaml::from_slice("a:\n  - {b: 1}\n  - two\nc: scalar\n")?;
+
+    assert_eq!(
+        recipes::get(&doc, &[]).map(|v| v.id()),
+        Some(doc.as_ref().id())
+    );
+    assert_eq!(
+        recipes::get(&doc, &["a", "0", "b"]).and_then(|v| v.as_u32()),
+        Some(1)
+    );
+    assert_eq!(
+        recipes::get(&doc, &["a", "1"]).and_then(|v| v.as_str()),
+        Some("two")
+    );
+
+    for path in [
+        &["missing"][..],
+        &["a", "2"],
+        &["a", "-1"],
+        &["a", "b"],
+        &["a", "0", "b", "c"],
+        &["c", "0"],
+    ] {
+        assert!(recipes::get(&doc, path).is_none(), "{path:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn bump_mapping_number() -> Result<()> {
+    const INPUT: &str = "a: 10 # ten\nb: '10'\nc: 1.5\nd: 9223372036854775807\ne: [1]\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+
+    assert_eq!(recipes::bump_mapping_number(&mut doc, &["b"], 1), None);
+    assert_eq!(recipes::bump_mapping_number(&mut doc, &["c"], 1), None);
+    assert_eq!(recipes::bump_mapping_number(&mut doc, &["d"], 1), None);
+    assert_eq!(recipes::bump_mapping_number(&mut doc, &["e"], 1), None);
+    assert_eq!(recipes::bump_mapping_number(&mut doc, &["f"], 1), None);
+    assert_eq!(doc.to_string(), INPUT);
+
+    assert_eq!(
+        recipes::bump_mapping_number(&mut doc, &["a"], -15),
+        Some(-5)
+    );
+    assert_eq!(
+        recipes::bump_mapping_number(&mut doc, &["e", "0"], 0),
+        Some(1)
+    );
+    assert_eq!(
+        doc.to_string(),
+        "a: -5 # ten\nb: '10'\nc: 1.5\nd: 9223372036854775807\ne: [1]\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn append_unique_to_sequence() -> Result<()> {
+    let mut doc = yaml::from_slice("a: []\nb:\n  - x # comment\nc: {x: 1}\nd: [1, 'x']\n")?;
+
+    assert!(recipes::append_unique_to_sequence(&mut doc, &["a"], "x"));
+    assert!(!recipes::append_unique_to_sequence(&mut doc, &["a"], "x"));
+    assert!(recipes::append_unique_to_sequence(&mut doc, &["b"], "y"));
+    assert!(!recipes::append_unique_to_sequence(&mut doc, &["c"], "x"));
+    assert!(!recipes::append_unique_to_sequence(
+        &mut doc,
+        &["missing"],
+        "x"
+    ));
+    assert!(!recipes::append_unique_to_sequence(&mut doc, &["d"], "x"));
+
+    // Only strings are compared, so the number is not considered a duplicate
+    // and the string is quoted to keep it a string.
+    assert!(recipes::append_unique_to_sequence(&mut doc, &["d"], "1"));
+
+    assert_eq!(
+        doc.to_string(),
+        "a: [x]\nb:\n  - x # comment\n  - y\nc: {x: 1}\nd: [1, 'x', '1']\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn set_image_tag() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "a: app\nb: 'registry.io:443/team/app:1.0'\nc: app@sha256:abcd\nd: registry.io:443/app\ne: 10\nf: |\n  app:1.0\ng: [app]\n",
+    )?;
+
+    assert!(recipes::set_image_tag(&mut doc, &["a"], "1"));
+    assert!(recipes::set_image_tag(&mut doc, &["b"], "2"));
+    assert!(recipes::set_image_tag(&mut doc, &["c"], "3"));
+    assert!(recipes::set_image_tag(&mut doc, &["d"], "4"));
+    assert!(!recipes::set_image_tag(&mut doc, &["e"], "5"));
+    assert!(!recipes::set_image_tag(&mut doc, &["f"], "6"));
+    assert!(!recipes::set_image_tag(&mut doc, &["g"], "7"));
+    assert!(!recipes::set_image_tag(&mut doc, &["missing"], "8"));
+
+    assert_eq!(
+        doc.to_string(),
+        "a: app:1\nb: 'registry.io:443/team/app:2'\nc: app:3\nd: registry.io:443/app:4\ne: 10\nf: |\n  app:1.0\ng: [app]\n",
+    );
+    Ok(())
+}
+
+#[test]
+fn set_image_tag_locked() -> Result<()> {
+    const INPUT: &str = "image: app:1.0\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let id = doc
+        .resolve_json_pointer("/image")
+        .map(|id| doc.lock(id, "human"));
+    assert_eq!(id, Some(true));
+    doc.set_actor(Some("bot"));
+
+    assert!(!recipes::set_image_tag(&mut doc, &["image"], "2.0"));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
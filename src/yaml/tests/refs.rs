@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+use crate::yaml::refs::Resolver;
+
+#[test]
+fn cyclical_references() -> Result<()> {
+    let root = yaml::from_slice("a: {$ref: '#/b'}\nb: {$ref: '#/a'}\nc: {$ref: '#/c/x'}")?;
+    let mut resolver = Resolver::new("root.yaml", root, |_| None);
+
+    assert!(resolver.get(&["a"]).is_none());
+    assert!(resolver.get(&["c"]).is_none());
+    Ok(())
+}
+
+#[test]
+fn nested_references() -> Result<()> {
+    let root = yaml::from_slice(
+        r"
+        env: {$ref: 'env.yaml#/prod'}
+        name: root
+        ",
+    )?;
+
+    let mut loaded = Vec::new();
+
+    let mut resolver = Resolver::new("root.yaml", root, |name| {
+        loaded.push(name.to_owned());
+
+        match name {
+            "env.yaml" => yaml::from_slice(
+                "prod:\n  hosts: [{$ref: 'hosts.yaml#/a~1b'}, {$ref: 'root.yaml#/name'}]\n",
+            )
+            .ok(),
+            "hosts.yaml" => yaml::from_slice("a/b: host-1\n").ok(),
+            _ => None,
+        }
+    });
+
+    let host = resolver
+        .get(&["env", "hosts", "0"])
+        .and_then(|v| v.as_str());
+    assert_eq!(host, Some("host-1"));
+
+    let (name, _) = resolver
+        .resolve(&["env", "hosts", "1"])
+        .context("missing host")?;
+    assert_eq!(name, "root.yaml");
+
+    resolver
+        .get_mut(&["env", "hosts", "0"])
+        .context("missing host")?
+        .set_string("host-2");
+
+    assert!(resolver.get(&["env", "missing"]).is_none());
+
+    let documents = resolver
+        .documents()
+        .map(|(name, doc)| (name.to_owned(), doc.to_string()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        documents,
+        [
+            (
+                "root.yaml".to_owned(),
+                "\n        env: {$ref: 'env.yaml#/prod'}\n        name: root\n        ".to_owned()
+            ),
+            (
+                "env.yaml".to_owned(),
+                "prod:\n  hosts: [{$ref: 'hosts.yaml#/a~1b'}, {$ref: 'root.yaml#/name'}]\n"
+                    .to_owned()
+            ),
+            ("hosts.yaml".to_owned(), "a/b: host-2\n".to_owned()),
+        ]
+    );
+
+    drop(resolver);
+    assert_eq!(loaded, ["env.yaml", "hosts.yaml"]);
+    Ok(())
+}
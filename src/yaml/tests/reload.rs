@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn reordered_keys() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb:\n  c: [x, y]\n")?;
+    let y = doc.resolve_json_pointer("/b/c/1").context("missing y")?;
+    let a = doc.resolve_json_pointer("/a").context("missing a")?;
+
+    let reload = doc.reload_from("b:\n  d: 2\n  c: [z, w]\na: 3\n")?;
+
+    let y = reload.get(y).context("y did not survive")?;
+    assert_eq!(doc.value(y).as_str(), Some("w"));
+
+    let a = reload.get(a).context("a did not survive")?;
+    assert_eq!(doc.value(a).as_u32(), Some(3));
+    assert_eq!(doc.to_string(), "b:\n  d: 2\n  c: [z, w]\na: 3\n");
+    Ok(())
+}
+
+#[test]
+fn changed_kinds() -> Result<()> {
+    let mut doc = yaml::from_slice("a: {b: 1}\nc: [1, 2]\n")?;
+    let root = doc.as_ref().id();
+    let a = doc.resolve_json_pointer("/a").context("missing a")?;
+    let b = doc.resolve_json_pointer("/a/b").context("missing b")?;
+    let c = doc.resolve_json_pointer("/c").context("missing c")?;
+    let c0 = doc.resolve_json_pointer("/c/0").context("missing c0")?;
+    let c1 = doc.resolve_json_pointer("/c/1").context("missing c1")?;
+
+    // Values survive as long as their path does, even if their kind changes,
+    // but what was inside of them doesn't.
+    let reload = doc.reload_from("a: [b]\nc: {0: 1}\n")?;
+    assert!(reload.get(root).is_some());
+    assert!(reload.get(a).is_some());
+    assert!(reload.get(c).is_some());
+    assert_eq!(reload.get(b), None);
+    assert_eq!(reload.get(c0), None);
+    assert_eq!(reload.get(c1), None);
+
+    let mut lost = reload.lost().collect::<Vec<_>>();
+    lost.sort();
+    let mut expected = vec![b, c0, c1];
+    expected.sort();
+    assert_eq!(lost, expected);
+    assert_eq!(reload.survived().count(), 3);
+    Ok(())
+}
+
+#[test]
+fn empty_document() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    let root = doc.as_ref().id();
+    let a = doc.resolve_json_pointer("/a").context("missing a")?;
+
+    let reload = doc.reload_from("")?;
+    let root = reload.get(root).context("root did not survive")?;
+    assert_eq!(doc.value(root).to_string(), "");
+    assert_eq!(reload.get(a), None);
+    assert_eq!(doc.to_string(), "");
+    Ok(())
+}
+
+#[test]
+fn invalid_input() -> Result<()> {
+    const INPUT: &str = "a: [1, 2]\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let one = doc.resolve_json_pointer("/a/0").context("missing one")?;
+
+    assert!(doc.reload_from("a: [1, 2\n").is_err());
+    assert_eq!(doc.to_string(), INPUT);
+    assert_eq!(doc.value(one).as_u32(), Some(1));
+    Ok(())
+}
+
+#[test]
+fn foreign_ids() -> Result<()> {
+    let other = yaml::from_slice("[1, 2, 3, 4, 5, 6, 7, 8]")?;
+    let last = other.resolve_json_pointer("/7").context("missing last")?;
+
+    let mut doc = yaml::from_slice("[1]")?;
+    let reload = doc.reload_from("[2]")?;
+    assert_eq!(reload.get(last), None);
+    Ok(())
+}
+
+#[test]
+fn repeated() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    let mut a = doc.resolve_json_pointer("/a").context("missing a")?;
+
+    for n in 2..5u32 {
+        let reload = doc.reload_from(format!("a: {n}\n"))?;
+        a = reload.get(a).context("a did not survive")?;
+        assert_eq!(doc.value(a).as_u32(), Some(n));
+    }
+
+    doc.value_mut(a).set_u32(10);
+    assert_eq!(doc.to_string(), "a: 10\n");
+    Ok(())
+}
+
+#[test]
+fn parser_options() -> Result<()> {
+    let mut doc = yaml::Parser::new(b"a: yes\nb: \"\\q\"\n")
+        .schema(yaml::Schema::Yaml11)
+        .lenient_escapes(true)
+        .parse()?;
+
+    doc.reload_from("a: no\nb: \"\\q\\w\"\nc: {d:1}\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_bool()), Some(false));
+    assert_eq!(root.get("b").and_then(|v| v.as_str()), Some("\\q\\w"));
+
+    let mut doc = yaml::Parser::new(b"a: 1\n")
+        .strict_flow_keys(true)
+        .parse()?;
+
+    let error = doc.reload_from("a: {b:1}\n").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadMappingSeparator);
+    assert_eq!(doc.to_string(), "a: 1\n");
+    Ok(())
+}
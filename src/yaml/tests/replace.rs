@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+/// Replace `pattern` with `replacement` in `input`, check that the output
+/// reads back as the replaced strings and return the number of replaced
+/// strings and the output.
+fn replace(input: &str, pattern: &str, replacement: &str) -> Result<(usize, String)> {
+    let mut doc = yaml::from_slice(input)?;
+    let count = doc.replace_in_strings(pattern, replacement)?;
+    let output = doc.to_string();
+
+    let reparsed = yaml::from_slice(&output)?;
+    assert_eq!(
+        reparsed.as_ref().structural_hash(),
+        doc.as_ref().structural_hash(),
+        "{output:?} does not read back the same"
+    );
+
+    Ok((count, output))
+}
+
+#[test]
+fn quoting_styles() -> Result<()> {
+    assert_eq!(
+        replace("[a-x, 'a-x', \"a-x\"]", "x", "y")?,
+        (3, "[a-y, 'a-y', \"a-y\"]".to_string())
+    );
+
+    // Quoted strings keep their quotes even if they're no longer needed.
+    assert_eq!(
+        replace("['true', \"null\"]", "true", "yes")?,
+        (1, "['yes', \"null\"]".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn bare_strings_are_quoted_when_needed() -> Result<()> {
+    assert_eq!(replace("a: x", "x", "true")?, (1, "a: 'true'".to_string()));
+    assert_eq!(replace("a: x", "x", "10")?, (1, "a: '10'".to_string()));
+    assert_eq!(replace("a: x", "x", "b: c")?, (1, "a: 'b: c'".to_string()));
+    assert_eq!(replace("a: x", "x", "")?, (1, "a: ''".to_string()));
+    assert_eq!(replace("a: x", "x", "it's #1")?, {
+        (1, "a: \"it's #1\"".to_string())
+    });
+    assert_eq!(
+        replace("a: x", "x", "1\n2")?,
+        (1, "a: \"1\\n2\"".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn bare_strings_keep_their_type() -> Result<()> {
+    assert_eq!(replace("v: 1.0.x", "x", "y")?, (1, "v: 1.0.y".to_string()));
+    assert_eq!(replace("v: 1.0.x", ".x", "")?, (1, "v: '1.0'".to_string()));
+    assert_eq!(replace("v: .0.x", ".0.x", "1")?, (1, "v: '1'".to_string()));
+    assert_eq!(replace("v: 1.0.x", "1.0.x", "1.0 # x")?, {
+        (1, "v: '1.0 # x'".to_string())
+    });
+    Ok(())
+}
+
+#[test]
+fn flow_indicators() -> Result<()> {
+    assert_eq!(
+        replace("[a, b]", "a", "c, d")?,
+        (1, "['c, d', b]".to_string())
+    );
+    assert_eq!(
+        replace("{k: a}", "a", "[c]")?,
+        (1, "{k: '[c]'}".to_string())
+    );
+    assert_eq!(replace("k: a", "a", "c, d")?, (1, "k: c, d".to_string()));
+    Ok(())
+}
+
+#[test]
+fn quoted_strings_are_escaped() -> Result<()> {
+    assert_eq!(
+        replace("a: 'x'", "x", "it's")?,
+        (1, "a: 'it''s'".to_string())
+    );
+    assert_eq!(replace("a: \"x\"", "x", "say \"hi\"")?, {
+        (1, "a: \"say \\\"hi\\\"\"".to_string())
+    });
+    assert_eq!(replace("a: 'x'", "x", "1\n2")?.0, 1);
+    assert_eq!(
+        replace("a: \"x\"", "x", "1\t2")?,
+        (1, "a: \"1\\t2\"".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn untouched() -> Result<()> {
+    const INPUT: &str = "x: 1 # x\nkey-x: |\n  x\nlist: [1, true, ~, &x a, *x]\n";
+
+    // Keys, block strings, comments and other kinds of values are left as is.
+    assert_eq!(replace(INPUT, "x", "y")?, (0, INPUT.to_string()));
+    assert_eq!(replace(INPUT, "1", "2")?, (0, INPUT.to_string()));
+    assert_eq!(replace(INPUT, "true", "false")?, (0, INPUT.to_string()));
+    assert_eq!(replace(INPUT, "", "y")?, (0, INPUT.to_string()));
+    assert_eq!(replace(INPUT, "missing", "y")?, (0, INPUT.to_string()));
+    Ok(())
+}
+
+#[test]
+fn nested_and_repeated() -> Result<()> {
+    assert_eq!(
+        replace("a:\n  b: [aa, {c: a}]\n  d: a a # a\n", "a", "bb")?,
+        (3, "a:\n  b: [bbbb, {c: bb}]\n  d: bb bb # a\n".to_string())
+    );
+    Ok(())
+}
+
+#[test]
+fn with_closure() -> Result<()> {
+    let mut doc = yaml::from_slice("[one, 'two', three]")?;
+    let mut seen = Vec::new();
+
+    let count = doc.replace_in_strings_with(|string| {
+        seen.push(string.to_vec());
+        (string != b"two").then(|| string.to_ascii_uppercase())
+    })?;
+
+    assert_eq!(count, 2);
+    assert_eq!(seen, [&b"one"[..], b"two", b"three"]);
+    assert_eq!(doc.to_string(), "[ONE, 'two', THREE]");
+
+    // Returning the same contents still counts as a modification.
+    let count = doc.replace_in_strings_with(|string| Some(string.to_vec()))?;
+    assert_eq!(count, 3);
+    assert_eq!(doc.to_string(), "[ONE, 'two', THREE]");
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("two"));
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use anyhow::{Context, Result};
+
+use crate::yaml::{self, Schema};
+
+#[test]
+fn yaml11_booleans() -> Result<()> {
+    const INPUT: &str = "on:\n  push: Yes\nflags: [on, OFF, no, y, true]\nname: yes please\n";
+
+    let doc = yaml::Parser::new(INPUT.as_bytes())
+        .schema(Schema::Yaml11)
+        .parse()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let on = root
+        .get("on")
+        .and_then(|v| v.as_mapping())
+        .context("missing on")?;
+    assert_eq!(on.get("push").and_then(|v| v.as_bool()), Some(true));
+
+    let flags = root
+        .get("flags")
+        .and_then(|v| v.as_sequence())
+        .context("missing flags")?;
+
+    let flags = flags.iter().map(|v| v.as_bool()).collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        [Some(true), Some(false), Some(false), None, Some(true)]
+    );
+
+    assert_eq!(
+        root.get("name").and_then(|v| v.as_str()),
+        Some("yes please")
+    );
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn yaml11_stream_parser() -> Result<()> {
+    let mut parser = yaml::StreamParser::new().schema(Schema::Yaml11);
+    parser.feed(b"- n")?;
+    parser.feed(b"o\n")?;
+
+    let docs = parser.finish()?;
+    let doc = docs.first().context("missing document")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_bool()), Some(false));
+    Ok(())
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn yaml11_deserialize() -> Result<()> {
+    use serde::de::IntoDeserializer;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Config {
+        enabled: bool,
+        verbose: bool,
+    }
+
+    let doc = yaml::Parser::new(b"enabled: on\nverbose: No\n")
+        .schema(Schema::Yaml11)
+        .parse()?;
+
+    let config = Config::deserialize(doc.as_ref().into_deserializer())?;
+    assert!(config.enabled);
+    assert!(!config.verbose);
+    Ok(())
+}
+
+#[test]
+fn yaml11_underscores() -> Result<()> {
+    const INPUT: &str =
+        "size: 1_000_000\nratio: 1_000.5\nlist: [-2_000, 3_0]\nid: _100\nversion: 1_2_\n";
+
+    let doc = yaml::from_slice(INPUT)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("size").and_then(|v| v.as_u32()), None);
+    assert_eq!(root.get("size").and_then(|v| v.as_str()), Some("1_000_000"));
+
+    let mut doc = yaml::Parser::new(INPUT.as_bytes())
+        .schema(Schema::Yaml11)
+        .parse()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("size").and_then(|v| v.as_u32()), Some(1_000_000));
+    assert_eq!(root.get("ratio").and_then(|v| v.as_f64()), Some(1_000.5));
+    assert_eq!(root.get("id").and_then(|v| v.as_str()), Some("_100"));
+    assert_eq!(root.get("version").and_then(|v| v.as_u32()), Some(12));
+
+    let list = root
+        .get("list")
+        .and_then(|v| v.as_sequence())
+        .context("missing list")?;
+    let list = list.iter().map(|v| v.as_i32()).collect::<Vec<_>>();
+    assert_eq!(list, [Some(-2_000), Some(30)]);
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut out = Vec::new();
+    doc.write_json_to(&mut out)?;
+    assert_eq!(
+        std::str::from_utf8(&out)?,
+        r#"{"size":1000000,"ratio":1000.5,"list":[-2000,30],"id":"_100","version":12}"#
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("size")
+        .context("missing size")?
+        .set_u32(2_000_000);
+    assert_eq!(
+        doc.to_string(),
+        "size: 2000000\nratio: 1_000.5\nlist: [-2_000, 3_0]\nid: _100\nversion: 1_2_\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn core_schema() -> Result<()> {
+    const INPUT: &str = "version: 1.6.2\nscalars: [0123, +1e2, 1e-3, 0o17, 0x1f, -.inf, +.Inf, .NaN, ., 1., .5, 1e, tRUE, True, NULL, nUll, ~]\n";
+
+    let doc = yaml::Parser::new(INPUT.as_bytes())
+        .schema(Schema::Core)
+        .parse()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("version").and_then(|v| v.as_str()), Some("1.6.2"));
+
+    let scalars = root
+        .get("scalars")
+        .and_then(|v| v.as_sequence())
+        .context("missing scalars")?;
+
+    let kinds = scalars
+        .iter()
+        .map(|v| match v.into_any() {
+            yaml::Any::Null => "null",
+            yaml::Any::Bool(..) => "bool",
+            yaml::Any::Number(..) => "number",
+            yaml::Any::String(..) => "string",
+            _ => "other",
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        [
+            "number", "number", "number", "number", "number", "number", "number", "number",
+            "string", "number", "number", "string", "string", "bool", "null", "string", "null"
+        ]
+    );
+
+    let get = |n| scalars.get(n).context("missing scalar");
+    assert_eq!(get(0)?.as_u32(), Some(123));
+    assert_eq!(get(1)?.as_f64(), Some(100.0));
+    assert_eq!(get(2)?.as_f64(), Some(0.001));
+    assert_eq!(get(3)?.as_u32(), Some(15));
+    assert_eq!(get(4)?.as_u32(), Some(31));
+    assert_eq!(get(5)?.as_f64(), Some(f64::NEG_INFINITY));
+    assert_eq!(get(6)?.as_f64(), Some(f64::INFINITY));
+    assert!(get(7)?.as_f64().is_some_and(f64::is_nan));
+    assert_eq!(get(9)?.as_f64(), Some(1.0));
+    assert_eq!(get(10)?.as_f64(), Some(0.5));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn yaml12_and_core() -> Result<()> {
+    const INPUT: &str = "[tRuE, NuLL, True, +1, 1e-3, 0o17, 0x1f, 1e3, -1, 1.6.2]";
+
+    let kinds = |schema| -> Result<Vec<&'static str>> {
+        let doc = yaml::Parser::new(INPUT.as_bytes()).schema(schema).parse()?;
+        assert_eq!(doc.to_string(), INPUT);
+
+        let root = doc.as_ref().as_sequence().context("missing root")?;
+
+        let kinds = root.iter().map(|v| match v.into_any() {
+            yaml::Any::Null => "null",
+            yaml::Any::Bool(..) => "bool",
+            yaml::Any::Number(..) => "number",
+            yaml::Any::String(..) => "string",
+            _ => "other",
+        });
+
+        Ok(kinds.collect())
+    };
+
+    assert_eq!(
+        kinds(Schema::Yaml12)?,
+        [
+            "bool", "null", "bool", "string", "string", "string", "string", "number", "number",
+            "string"
+        ]
+    );
+
+    assert_eq!(
+        kinds(Schema::Core)?,
+        [
+            "string", "string", "bool", "number", "number", "number", "number", "number", "number",
+            "string"
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn json_schema() -> Result<()> {
+    const INPUT: &str =
+        "scalars: [true, True, null, NULL, ~, 12, -0.5e3, 012, +1, 0x1f, .inf, 1.]\n";
+
+    let doc = yaml::Parser::new(INPUT.as_bytes())
+        .schema(Schema::Json)
+        .parse()?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let scalars = root
+        .get("scalars")
+        .and_then(|v| v.as_sequence())
+        .context("missing scalars")?;
+
+    let kinds = scalars
+        .iter()
+        .map(|v| match v.into_any() {
+            yaml::Any::Null => "null",
+            yaml::Any::Bool(..) => "bool",
+            yaml::Any::Number(..) => "number",
+            yaml::Any::String(..) => "string",
+            _ => "other",
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        [
+            "bool", "string", "null", "string", "string", "number", "number", "string", "string",
+            "string", "string", "number"
+        ]
+    );
+
+    assert_eq!(scalars.get(5).and_then(|v| v.as_u32()), Some(12));
+    assert_eq!(scalars.get(6).and_then(|v| v.as_f64()), Some(-500.0));
+    assert_eq!(scalars.get(4).and_then(|v| v.as_str()), Some("~"));
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn null_spellings() -> Result<()> {
+    const INPUT: &str =
+        "a: null\nb: Null \nc: NULL # none\nd: ~\t\ne: [~ , NULL]\nf: {g: Null}\nh: ~x\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let nulls = ["/a", "/b", "/c", "/d", "/e/0", "/e/1", "/f/g"];
+
+    for pointer in nulls {
+        let id = doc.resolve_json_pointer(pointer).context(pointer)?;
+        assert!(doc.value(id).is_null(), "{pointer}");
+    }
+
+    let h = doc.resolve_json_pointer("/h").context("missing h")?;
+    assert!(!doc.value(h).is_null());
+    assert_eq!(doc.value(h).as_str(), Some("~x"));
+
+    for pointer in nulls {
+        let id = doc.resolve_json_pointer(pointer).context(pointer)?;
+        doc.value_mut(id).set_u32(1);
+    }
+
+    assert_eq!(
+        doc.to_string(),
+        "a: 1\nb: 1 \nc: 1 # none\nd: 1\t\ne: [1 , 1]\nf: {g: 1}\nh: ~x\n"
+    );
+    Ok(())
+}
@@ -75,3 +75,356 @@ fn mutable_nested_sequence() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn inline_sequence_colon() -> Result<()> {
+    let doc = yaml::from_slice("[a:1, 1:2, http://example.com]")?;
+
+    let root = doc
+        .as_ref()
+        .as_sequence()
+        .context("missing root sequence")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_str()), Some("a:1"));
+    assert_eq!(root.get(1).and_then(|v| v.as_str()), Some("1:2"));
+    assert_eq!(
+        root.get(2).and_then(|v| v.as_str()),
+        Some("http://example.com")
+    );
+
+    assert_eq!(doc.to_string(), "[a:1, 1:2, http://example.com]");
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_comments() -> Result<()> {
+    const INPUT: &str = "[1, # one\n  2 # two\n  , three # three\n]";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+
+    let root = doc
+        .as_ref()
+        .as_sequence()
+        .context("missing root sequence")?;
+    assert_eq!(root.get(0).and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(root.get(1).and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(root.get(2).and_then(|v| v.as_str()), Some("three"));
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut root = doc
+        .as_mut()
+        .into_sequence_mut()
+        .context("missing root sequence")?;
+
+    root.get_mut(1).context("missing second")?.set_u32(42);
+    assert_eq!(
+        doc.to_string(),
+        "[1, # one\n  42 # two\n  , three # three\n]"
+    );
+
+    let mut root = doc
+        .as_mut()
+        .into_sequence_mut()
+        .context("missing root sequence")?;
+
+    assert!(root.remove(2));
+    assert_eq!(doc.to_string(), "[1, # one\n  42 # two\n   # three\n]");
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_comments_push() -> Result<()> {
+    for (input, expected) in [
+        ("[a, # c\n b]", "[a, # c\n b,\n x]"),
+        ("[a, # c\n  # d\n  b]", "[a, # c\n  # d\n  b,\n  x]"),
+        ("[ # c\n a]", "[ # c\n a,\n x]"),
+        ("[a, b # c\n]", "[a, b, x # c\n]"),
+        ("[a, # c\r\n b]", "[a, # c\r\n b,\r\n x]"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        root.push_string("x");
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(yaml::from_slice(expected)?.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_trailing_comma() -> Result<()> {
+    let mut doc = yaml::from_slice("[1, 2,]")?;
+
+    let mut root = doc
+        .as_mut()
+        .into_sequence_mut()
+        .context("missing root sequence")?;
+
+    root.push_u32(3);
+    assert!(doc.to_string().ends_with("3,]"));
+
+    let mut root = doc
+        .as_mut()
+        .into_sequence_mut()
+        .context("missing root sequence")?;
+
+    root.set_trailing_comma(false);
+    root.push_u32(4);
+    assert!(doc.to_string().ends_with("3, 4]"));
+
+    let mut root = doc
+        .as_mut()
+        .into_sequence_mut()
+        .context("missing root sequence")?;
+
+    root.set_trailing_comma(true);
+    root.clear()?;
+    assert_eq!(doc.to_string(), "[]");
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_push() -> Result<()> {
+    for (input, expected) in [
+        ("[1]", "[1, 2]"),
+        ("[1,2]", "[1,2,2]"),
+        ("[ 1 ]", "[ 1, 2 ]"),
+        ("[\n  1,\n  1,\n]", "[\n  1,\n  1,\n  2,\n]"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        root.push_u32(2);
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_push_empty() -> Result<()> {
+    for (input, expected) in [
+        ("[]", "[1, two]"),
+        ("[ ]", "[ 1, two ]"),
+        ("[\n]", "[1, two\n]"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        root.push_u32(1);
+        root.push_string("two");
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    let mut doc = yaml::from_slice("list: []\nother: 1")?;
+
+    let mut list = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("list"))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing list")?;
+
+    list.push_string("x");
+    assert_eq!(doc.to_string(), "list: [x]\nother: 1");
+    Ok(())
+}
+
+#[test]
+fn inline_sequence_push_collections() -> Result<()> {
+    let mut doc = yaml::from_slice("a: [1]\n")?;
+
+    let mut a = doc
+        .as_mut()
+        .into_mapping_mut()
+        .and_then(|m| m.get_into_mut("a"))
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing a")?;
+
+    let mut mapping = a.push(yaml::Separator::Auto).make_mapping()?;
+    mapping.insert_u32("k", 1);
+    mapping.insert_u32("j", 2);
+
+    let mut sequence = a.push(yaml::Separator::Auto).make_sequence()?;
+    sequence.push_u32(3);
+
+    let expected = "a: [1, {k: 1, j: 2}, [3]]\n";
+    assert_eq!(doc.to_string(), expected);
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    assert_eq!(doc.to_string(), expected);
+
+    let a = doc
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("a")?.as_sequence())
+        .context("missing a")?;
+    let mapping = a
+        .get(1)
+        .and_then(|v| v.as_mapping())
+        .context("missing mapping")?;
+    assert_eq!(mapping.get("j").and_then(|v| v.as_u32()), Some(2));
+    Ok(())
+}
+
+#[test]
+fn sort_keeps_layout() -> Result<()> {
+    for (input, expected) in [
+        ("[3, 1 # one\n, 2,]", "[1 # one\n, 2, 3,]"),
+        ("[ '3', \"1\", 2 ]", "[ 2, \"1\", '3' ]"),
+        ("- 3\n-   1\n- 2\n", "-   1\n- 2\n- 3\n"),
+        ("- b # bee\n- a # ay\n", "- a # ay\n- b # bee\n"),
+        (
+            "- c\n\n# about b\n- b # bee\n- a\n",
+            "- a\n\n# about b\n- b # bee\n- c\n",
+        ),
+        ("[b, # bee\n  a, c]", "[a,\n  b, # bee\n c]"),
+        ("[0.5, -1, 1e3, 1, -0.0, 0]", "[-1, -0.0, 0, 0.5, 1, 1e3]"),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        root.sort()?;
+        assert_eq!(doc.to_string(), expected);
+        assert_eq!(yaml::from_slice(expected)?.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn sort_moves_comments() -> Result<()> {
+    let mut doc = yaml::from_slice("x:\n  - b # bee\n  # about a\n  - a\n")?;
+
+    let mut root = doc
+        .as_mut()
+        .into_mapping_mut()
+        .context("missing root mapping")?;
+
+    let mut x = root
+        .get_mut("x")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing x")?;
+
+    x.sort()?;
+    assert_eq!(doc.to_string(), "x:\n  # about a\n  - a\n  - b # bee\n");
+    Ok(())
+}
+
+#[test]
+fn dedup_keeps_first() -> Result<()> {
+    for (input, expected, removed) in [
+        ("[1, 1 # dup\n, 2, 2,]", "[1, 2,]", 2),
+        (
+            "[{a: [1]}, {a: [1.0]}, {a: [2]}]",
+            "[{a: [1]}, {a: [2]}]",
+            1,
+        ),
+        ("- x\n-   x\n- y\n", "- x\n- y\n", 1),
+        ("[]", "[]", 0),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        assert_eq!(root.dedup()?, removed);
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dedup_block_comments() -> Result<()> {
+    for (input, expected, removed) in [
+        ("- 'a' # first\n- a\n- b\n", "- 'a' # first\n- b\n", 1),
+        (
+            "- 'a' # first\n- 'a' # second\n- a\n- b\n",
+            "- 'a' # first\n- b\n",
+            2,
+        ),
+        ("- a\n# above\n- a\n- b\n", "- a\n- b\n", 1),
+    ] {
+        let mut doc = yaml::from_slice(input)?;
+
+        let mut root = doc
+            .as_mut()
+            .into_sequence_mut()
+            .context("missing root sequence")?;
+
+        assert_eq!(root.dedup()?, removed);
+        assert_eq!(doc.to_string(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn iter_double_ended() -> Result<()> {
+    let doc = yaml::from_slice("[1, 2, 3, 4]")?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+
+    let mut it = root.iter();
+    assert_eq!(it.len(), 4);
+    assert_eq!(it.next().and_then(|v| v.as_u32()), Some(1));
+    assert_eq!(it.nth_back(1).and_then(|v| v.as_u32()), Some(3));
+    assert_eq!(it.len(), 1);
+    assert_eq!(it.next_back().and_then(|v| v.as_u32()), Some(2));
+    assert!(it.next().is_none());
+    assert!(it.next_back().is_none());
+
+    let values = root
+        .iter()
+        .rev()
+        .flat_map(|v| v.as_u32())
+        .collect::<Vec<_>>();
+    assert_eq!(values, [4, 3, 2, 1]);
+    Ok(())
+}
+
+#[test]
+fn flow_indicators() -> Result<()> {
+    let mut doc = yaml::from_slice("[1, 2]")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.push_string("a, b");
+    root.push_string("c]");
+    root.push_bytes(b"{d}");
+    root.push_value("e,f");
+    root.push(yaml::Separator::Auto).set_string("[g");
+    assert_eq!(doc.to_string(), "[1, 2, 'a, b', 'c]', '{d}', 'e,f', '[g']");
+
+    let doc = yaml::from_slice(doc.to_string())?;
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let values = root
+        .iter()
+        .skip(2)
+        .flat_map(|v| v.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(values, ["a, b", "c]", "{d}", "e,f", "[g"]);
+
+    // Block sequences don't need the indicators quoted.
+    let mut doc = yaml::from_slice("- 1\n")?;
+    let mut root = doc.as_mut().into_sequence_mut().context("missing root")?;
+    root.push_string("a, b");
+    assert_eq!(doc.to_string(), "- 1\n- a, b\n");
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use serde_yaml::Value;
+
+use crate::yaml::{self, Compression, Document};
+
+const INPUT: &str = "# config\nname: app\nsteps:\n  - run: |\n      cargo build\n      cargo test\n  - {uses: checkout, with: [a, b]}\n";
+
+fn load(state: Value) -> Result<Document, serde_yaml::Error> {
+    serde_yaml::from_value(state)
+}
+
+#[test]
+fn compressed_roundtrip() -> Result<()> {
+    let mut doc = yaml::from_slice(INPUT.repeat(16))?;
+    let plain = serde_yaml::to_string(&doc)?;
+
+    doc.set_compression(Compression::Deflate);
+    let packed = serde_yaml::to_value(&doc)?;
+    assert!(packed["data"].get("strings").is_none());
+    assert!(packed["data"].get("packed").is_some());
+    assert!(serde_yaml::to_string(&packed)?.len() < plain.len());
+
+    let mut doc = load(packed)?;
+    assert_eq!(doc.to_string(), INPUT.repeat(16));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_str("version", "1.0");
+
+    let state = serde_yaml::to_value(&doc)?;
+    assert!(state["data"].get("packed").is_some());
+
+    let doc = load(state)?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("version").and_then(|v| v.as_str()), Some("1.0"));
+    Ok(())
+}
+
+#[test]
+fn without_checksum() -> Result<()> {
+    let doc = yaml::from_slice(INPUT)?;
+
+    let mut state = serde_yaml::to_value(&doc)?;
+    let map = state.as_mapping_mut().context("expected mapping")?;
+    assert!(map.remove("checksum").is_some());
+
+    let doc = load(state)?;
+    assert_eq!(doc.to_string(), INPUT);
+    Ok(())
+}
+
+#[test]
+fn corrupt_sessions() -> Result<()> {
+    let mut doc = yaml::from_slice(INPUT)?;
+    let state = serde_yaml::to_value(&doc)?;
+
+    let mut corrupt = state.clone();
+    corrupt["checksum"] = Value::from(42u64);
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("checksum mismatch"), "{error}");
+
+    let mut corrupt = state.clone();
+    corrupt["root"] = Value::from(10_000u64);
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("missing value"), "{error}");
+
+    let mut corrupt = state.clone();
+    let strings = corrupt["data"]["strings"]
+        .as_mapping_mut()
+        .context("missing strings")?;
+    let first = strings.values_mut().next().context("missing string")?;
+    *first = Value::Sequence(vec![Value::from(120u8)]);
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("doesn't match"), "{error}");
+
+    let mut corrupt = state.clone();
+    corrupt["data"]["strings"] = Value::Mapping(serde_yaml::Mapping::new());
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("missing string"), "{error}");
+
+    let mut corrupt = state.clone();
+    corrupt["data"]["slab"][0]["layout"]["parent"] = corrupt["root"].clone();
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("unexpected parent"), "{error}");
+
+    let mut corrupt = state.clone();
+    let item = corrupt["data"]["slab"][0]["raw"]["value"]["items"][0].clone();
+    corrupt["data"]["slab"][0]["layout"]["parent"] = item;
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("unexpected parent"), "{error}");
+
+    doc.set_compression(Compression::Deflate);
+    let mut corrupt = serde_yaml::to_value(&doc)?;
+    let bytes = corrupt["data"]["packed"]["bytes"]
+        .as_sequence_mut()
+        .context("missing bytes")?;
+    bytes.truncate(bytes.len() / 2);
+    let error = load(corrupt).unwrap_err();
+    assert!(error.to_string().contains("corrupt"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn bookmarks() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?.id();
+    let b = root.get("b").context("missing b")?.id();
+
+    assert!(doc.bookmark("first", a));
+    assert!(doc.bookmark("second", b));
+    assert!(doc.bookmark("other", b));
+    assert!(doc.bookmark("first", b));
+    assert_eq!(doc.bookmarked("first"), Some(b));
+    assert!(doc.bookmark("first", a));
+    assert_eq!(doc.bookmarked("first"), Some(a));
+
+    let doc: Document = serde_yaml::from_str(&serde_yaml::to_string(&doc)?)?;
+    let bookmarks = doc.bookmarks().collect::<Vec<_>>();
+    assert_eq!(bookmarks, [("first", a), ("other", b), ("second", b)]);
+    Ok(())
+}
+
+#[test]
+fn bookmarks_to_removed_values() -> Result<()> {
+    let mut doc = yaml::from_slice("a: {x: 1}\nb: 2\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let a = root.get("a").context("missing a")?.id();
+    assert!(doc.bookmark("a", a));
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove("a"));
+    root.insert_str("c", "reused");
+    root.insert_u32("d", 3);
+
+    // The identifier of the removed value is reused by the inserted ones.
+    assert!(doc.data.contains(a));
+
+    assert_eq!(doc.bookmarked("a"), None);
+    assert_eq!(doc.bookmarks().count(), 0);
+
+    // Removed values can't be bookmarked.
+    let mut doc = yaml::from_slice("a: 1\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let a = root.as_ref().get("a").context("missing a")?.id();
+    assert!(root.remove("a"));
+    assert!(!doc.bookmark("a", a));
+    assert_eq!(doc.bookmarked("a"), None);
+
+    // A failed bookmark leaves an existing one with the same name alone.
+    let mut doc = yaml::from_slice("a: 1\nb: 2\n")?;
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    let b = root.get("b").context("missing b")?.id();
+    assert!(doc.bookmark("b", b));
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let a = root.as_ref().get("a").context("missing a")?.id();
+    assert!(root.remove("a"));
+    assert!(!doc.bookmark("b", a));
+    assert_eq!(doc.bookmarked("b"), Some(b));
+    Ok(())
+}
+
+#[test]
+fn parser_options() -> Result<()> {
+    let doc = yaml::Parser::new(b"a: yes\n")
+        .schema(yaml::Schema::Yaml11)
+        .parse()?;
+
+    let state = serde_yaml::to_value(&doc)?;
+    assert!(state.get("options").is_some());
+
+    let mut doc = load(state)?;
+    doc.reload_from("a: off\n")?;
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+    assert_eq!(root.get("a").and_then(|v| v.as_bool()), Some(false));
+
+    let state = serde_yaml::to_value(yaml::from_slice(INPUT)?)?;
+    assert!(state.get("options").is_none());
+    Ok(())
+}
+
+#[test]
+fn original() -> Result<()> {
+    let mut doc = yaml::from_slice(INPUT)?;
+    assert!(serde_yaml::to_value(&doc)?.get("original").is_none());
+
+    doc.snapshot_original();
+    let state = serde_yaml::to_value(&doc)?;
+    assert!(state.get("original").is_some());
+
+    let mut doc = load(state)?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.insert_u32("version", 2);
+    assert!(doc.diff_against_original().ends_with("+version: 2\n"));
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn round_trip() -> Result<()> {
+    const CASES: &[(&str, usize)] = &[
+        ("", 1),
+        ("a: 1\n", 1),
+        ("---\na: 1\n", 1),
+        ("# header\n%YAML 1.2\n---\na: 1\n", 1),
+        ("a: 1\n---\nb: 2\n", 2),
+        ("--- # first\na: 1\n--- !!set\n? b\n---\n", 3),
+        ("--- a\n---\tb\n", 2),
+        ("a: |\n  ---x\n  text\n---\nb", 2),
+        ("a\n...\n%YAML 1.2\n---\nb\n...\n", 2),
+        ("--- a\n... # end\n# next\n--- b\n", 2),
+    ];
+
+    for &(input, len) in CASES {
+        let stream = yaml::stream_from_slice(input)?;
+        assert_eq!(stream.len(), len, "{input:?}");
+        assert!(stream
+            .iter()
+            .all(|doc| doc.has_end_marker() == doc.to_string().contains("...")));
+        assert_eq!(stream.to_string(), input, "{input:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn edit_documents() -> Result<()> {
+    let mut stream = yaml::stream_from_slice("a: 1")?;
+
+    stream.push_document(yaml::from_slice("b: 2")?);
+    stream.push_document(yaml::from_slice(" c")?);
+    assert_eq!(stream.to_string(), "a: 1\n---\nb: 2\n--- c");
+
+    stream.insert(0, yaml::from_slice("z: 0\n")?);
+    assert_eq!(stream.to_string(), "---\nz: 0\n---\na: 1\n---\nb: 2\n--- c");
+
+    assert!(stream.remove(2).is_some());
+    assert!(stream.remove(0).is_some());
+    assert_eq!(stream.to_string(), "---\na: 1\n--- c");
+
+    let reparsed = yaml::stream_from_slice(stream.to_string())?;
+    let doc = reparsed.get(1).context("missing document")?;
+    assert_eq!(doc.as_ref().as_str(), Some("c"));
+
+    assert!(stream.remove(0).is_some());
+    assert!(stream.remove(0).is_some());
+    assert!(stream.is_empty());
+    assert_eq!(stream.to_string(), "");
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+
+use crate::yaml::{self, Document, StreamParser};
+
+const INPUT: &str = "# first\nname: café # comment\ntext: |\n  --- not a marker\n---\nlist: [1, 2]\n...\n%YAML 1.2\n--- # third\n- a\n---";
+
+fn strings(documents: &[Document]) -> Vec<String> {
+    documents.iter().map(|doc| doc.to_string()).collect()
+}
+
+#[test]
+fn every_split() -> Result<()> {
+    let stream = yaml::stream_from_slice(INPUT)?;
+    let expected = stream.iter().map(|doc| doc.to_string()).collect::<Vec<_>>();
+    assert_eq!(expected.len(), 4);
+
+    for n in 0..=INPUT.len() {
+        let (head, tail) = INPUT.as_bytes().split_at(n);
+
+        let mut parser = StreamParser::new();
+        let mut documents = parser.feed(head)?;
+        documents.extend(parser.feed(tail)?);
+        documents.extend(parser.finish()?);
+
+        assert_eq!(strings(&documents), expected, "split at {n}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn byte_by_byte() -> Result<()> {
+    let mut parser = StreamParser::new();
+    let mut completed = Vec::new();
+
+    for (n, b) in INPUT.bytes().enumerate() {
+        for doc in parser.feed(&[b])? {
+            completed.push((n, doc.to_string()));
+        }
+    }
+
+    // Documents are completed as soon as the line with the next marker has
+    // been fed, and only the current document is buffered.
+    assert_eq!(
+        completed,
+        [
+            (
+                INPUT.find("---\nlist").unwrap_or_default() + 3,
+                "# first\nname: café # comment\ntext: |\n  --- not a marker\n".to_owned()
+            ),
+            (
+                INPUT.find("--- # third").unwrap_or_default() + 11,
+                "---\nlist: [1, 2]\n...\n".to_owned()
+            ),
+        ]
+    );
+    assert_eq!(parser.buffered(), "%YAML 1.2\n--- # third\n- a\n---".len());
+
+    let documents = parser.finish()?;
+    assert_eq!(
+        strings(&documents),
+        ["%YAML 1.2\n--- # third\n- a\n", "---"]
+    );
+    Ok(())
+}
+
+#[test]
+fn single_document() -> Result<()> {
+    for input in ["", "a: 1\n", "%YAML 1.2\n---\na: 1\n", "# comment\n--- a\n"] {
+        let mut parser = StreamParser::new();
+        assert!(parser.feed(input.as_bytes())?.is_empty());
+
+        let documents = parser.finish()?;
+        assert_eq!(strings(&documents), [input]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn error_spans() -> Result<()> {
+    let mut parser = StreamParser::new();
+    assert_eq!(parser.feed(b"a: 1\n---\n")?.len(), 1);
+    assert!(parser.feed(b"b: [1,")?.is_empty());
+    assert!(parser.feed(b" 2\n")?.is_empty());
+
+    let error = parser.feed(b"---\n").unwrap_err();
+    assert_eq!(*error.kind(), yaml::ErrorKind::BadSequenceTerminator);
+
+    // Spans refer to the input as if all chunks were concatenated.
+    let expected = yaml::stream_from_slice("a: 1\n---\nb: [1, 2\n---\n").unwrap_err();
+    assert_eq!(error.span(), expected.span());
+    assert_eq!(error.span(), 17..18);
+
+    let mut parser = StreamParser::new();
+    parser.feed(b"a: 1\n---\n{")?;
+    let error = parser.finish().unwrap_err();
+    assert_eq!(error.span(), 10..10);
+    Ok(())
+}
+
+#[test]
+fn options() -> Result<()> {
+    let mut parser = StreamParser::new().lenient_escapes(true);
+    parser.feed(b"a: \"\\")?;
+    parser.feed(b"q\"\n--")?;
+    assert_eq!(parser.feed(b"-\nb: \"\\w\"\n")?.len(), 1);
+
+    let (documents, warnings) = parser.finish_with_warnings()?;
+    assert_eq!(strings(&documents), ["---\nb: \"\\w\"\n"]);
+
+    let spans = warnings.iter().map(|w| w.span()).collect::<Vec<_>>();
+    assert_eq!(spans, [4..6, 16..18]);
+
+    let mut parser = StreamParser::new();
+    parser.feed(b"a: \"\\q\"\n")?;
+    assert!(parser.finish().is_err());
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+fn hash(input: &str) -> Result<u64> {
+    Ok(yaml::from_slice(input)?.as_ref().structural_hash())
+}
+
+#[test]
+fn formatting() -> Result<()> {
+    let expected = hash("{a: [1, two], b: {c: ~}}")?;
+
+    for input in [
+        "a:\n  - 1\n  - two\nb:\n  c: ~\n",
+        "# comment\na: [ 1 , 'two' ] # trailing\nb: {c: null}\n",
+        "b:\n  c: ~\na: [1, \"two\"]\n",
+        "a: [0001, two]\nb: {c: Null}\n",
+    ] {
+        assert_eq!(hash(input)?, expected, "{input:?}");
+    }
+
+    assert_eq!(hash("a: |\n  x\n  y\n")?, hash("a: \"x\\ny\\n\"")?);
+    assert_eq!(hash("a: >-\n  x\n  y\n")?, hash("a: x y")?);
+    Ok(())
+}
+
+#[test]
+fn differences() -> Result<()> {
+    const DISTINCT: &[&str] = &[
+        "~",
+        "false",
+        "true",
+        "'true'",
+        "1",
+        "'1'",
+        "1.5",
+        "[]",
+        "{}",
+        "[1, 2]",
+        "[2, 1]",
+        "[[1, 2]]",
+        "{a: 1}",
+        "{a: [1]}",
+        "{b: 1}",
+        "{a: 1, b: 1}",
+        "''",
+    ];
+
+    let hashes = DISTINCT
+        .iter()
+        .map(|input| hash(input))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (i, a) in hashes.iter().enumerate() {
+        for (j, b) in hashes.iter().enumerate().skip(i + 1) {
+            assert_ne!(a, b, "{:?} and {:?}", DISTINCT[i], DISTINCT[j]);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn numbers() -> Result<()> {
+    assert_eq!(hash("10")?, hash("0010")?);
+    assert_eq!(hash("-0.0")?, hash("0.0")?);
+    assert_eq!(hash("1e3")?, hash("1000.0")?);
+    assert_eq!(hash("340282366920938463463374607431768211455")?, {
+        hash("0340282366920938463463374607431768211455")?
+    });
+    assert_eq!(hash("10")?, hash("10.0")?);
+    assert_eq!(hash("1e400")?, hash("10e399")?);
+    assert_ne!(hash("-1")?, hash("1")?);
+    assert_ne!(hash("0.1")?, hash("0.1000000000000000000000000001")?);
+    Ok(())
+}
+
+#[test]
+fn nested_values() -> Result<()> {
+    let doc = yaml::from_slice("a: {x: 1, y: [2]}\nb:\n  y:\n    - 2\n  x: 1\nc: {x: 1}\n")?;
+
+    let get = |pointer| {
+        let id = doc.resolve_json_pointer(pointer)?;
+        Some(doc.value(id).structural_hash())
+    };
+
+    assert_eq!(get("/a"), get("/b"));
+    assert_ne!(get("/a"), get("/c"));
+    assert_eq!(get("/a/y"), get("/b/y"));
+    assert_eq!(get("/c/x"), Some(hash("1")?));
+    Ok(())
+}
+
+#[test]
+fn edits() -> Result<()> {
+    let mut doc = yaml::from_slice("a: 1 # one\nb: two\n")?;
+    let before = doc.as_ref().structural_hash();
+    let a = doc.resolve_json_pointer("/a").context("missing a")?;
+
+    doc.value_mut(a).set_u32(2);
+    assert_ne!(doc.as_ref().structural_hash(), before);
+
+    doc.value_mut(a).set_u32(1);
+    assert_eq!(doc.as_ref().structural_hash(), before);
+    assert_eq!(doc.to_string(), "a: 1 # one\nb: two\n");
+    Ok(())
+}
+
+#[test]
+fn hash_agrees_with_total_cmp() -> Result<()> {
+    for (a, b) in [
+        ("{a: 1, b: 2}", "{b: 2, a: 1}"),
+        ("[1, 2.0]", "[1.0, 2]"),
+        ("{a: [10]}", "{a: [1e1]}"),
+    ] {
+        let a = yaml::from_slice(a)?;
+        let b = yaml::from_slice(b)?;
+        assert!(a.as_ref().total_cmp(&b.as_ref()).is_eq());
+        assert_eq!(a.as_ref().structural_hash(), b.as_ref().structural_hash());
+    }
+
+    // Numbers which can't be parsed don't collide with strings.
+    let a = yaml::from_slice("1e")?;
+    let b = yaml::from_slice("'1e'")?;
+    assert!(a.as_ref().total_cmp(&b.as_ref()).is_ne());
+    assert_ne!(a.as_ref().structural_hash(), b.as_ref().structural_hash());
+    Ok(())
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+
+use crate::yaml;
+
+#[test]
+fn tagged_round_trip() -> Result<()> {
+    const INPUT: &str = r"
+    set: !!set
+      ? a   # first
+      ? 'b'
+    inline: !!set {a, b , c: ~,}
+    omap: !!omap
+      - a: 1
+      - b: 2
+    nested:
+      - !!omap [{a: 1}]
+      - !custom {a: 1}
+    scalar: !foo bar
+    pairs: !!omap [a: 1, b: 2]
+    ";
+
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(doc.to_string(), INPUT);
+
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    assert_eq!(out, INPUT.as_bytes());
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let set = root
+        .get("set")
+        .and_then(|v| v.as_set())
+        .context("missing set")?;
+    assert!(set.iter().eq(["a", "b"]));
+
+    let inline = root
+        .get("inline")
+        .and_then(|v| v.as_set())
+        .context("missing inline")?;
+    assert!(inline.iter().eq(["a", "b", "c"]));
+
+    let omap = root
+        .get("omap")
+        .and_then(|v| v.as_omap())
+        .context("missing omap")?;
+    assert_eq!(omap.get("b").and_then(|v| v.as_u32()), Some(2));
+    assert_eq!(
+        omap.iter().rev().map(|(key, _)| key).collect::<Vec<_>>(),
+        ["b", "a"]
+    );
+
+    let nested = root
+        .get("nested")
+        .and_then(|v| v.as_sequence())
+        .context("missing nested")?;
+    assert_eq!(
+        nested.get(0).and_then(|v| v.as_omap()).map(|v| v.len()),
+        Some(1)
+    );
+    assert_eq!(nested.get(1).and_then(|v| v.tag()), Some("!custom".into()));
+    assert!(nested.get(1).and_then(|v| v.as_set()).is_none());
+
+    assert_eq!(
+        root.get("scalar").and_then(|v| v.as_str()),
+        Some("!foo bar")
+    );
+
+    // Flow pairs are not supported, so this is not a tagged sequence.
+    assert!(root.get("pairs").and_then(|v| v.tag()).is_none());
+    Ok(())
+}
+
+#[test]
+fn tagged_edits() -> Result<()> {
+    let mut doc = yaml::from_slice("set: !!set\n  ? a\nomap: !!omap\n  - a: 1\n")?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    let mut omap = root
+        .get_mut("omap")
+        .and_then(|v| v.into_sequence_mut())
+        .context("missing omap")?;
+
+    omap.push(yaml::Separator::Auto)
+        .make_mapping()?
+        .insert_u32("b", 2);
+
+    assert_eq!(
+        doc.to_string(),
+        "set: !!set\n  ? a\nomap: !!omap\n  - a: 1\n  - b: 2\n"
+    );
+
+    let omap = doc
+        .as_ref()
+        .as_mapping()
+        .and_then(|m| m.get("omap")?.as_omap())
+        .context("missing omap")?;
+
+    assert_eq!(omap.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn omap_requires_pairs() -> Result<()> {
+    let doc = yaml::from_slice("!!omap\n- a: 1\n- b: 2\n  c: 3\n")?;
+    assert!(doc.as_ref().as_omap().is_none());
+    assert_eq!(doc.as_ref().tag(), Some("!!omap".into()));
+    Ok(())
+}
+
+#[test]
+fn tagged_blocks() -> Result<()> {
+    const INPUT: &str = "a: |\n  x\nb: >-\n  y\n";
+
+    let mut doc = yaml::from_slice(INPUT)?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+
+    for key in ["a", "b"] {
+        let error = root
+            .get_mut(key)
+            .context("missing value")?
+            .set_tag("!y")
+            .err()
+            .map(|e| e.kind().clone());
+
+        assert_eq!(error, Some(yaml::ErrorKind::TaggedBlock));
+    }
+
+    assert_eq!(doc.to_string(), INPUT);
+
+    // Blocks set on tagged values are written as quoted strings.
+    let mut doc = yaml::from_slice("a: x\n")?;
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    let mut a = root.get_mut("a").context("missing a")?;
+    a.set_tag("!y")?;
+    a.set_block(["x"], yaml::Block::Literal(yaml::Chomp::Clip));
+
+    let output = doc.to_string();
+    assert_eq!(output, "a: !y \"x\\n\"\n");
+
+    let doc = yaml::from_slice(&output)?;
+    assert_eq!(doc.to_string(), output);
+    Ok(())
+}
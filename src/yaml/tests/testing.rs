@@ -0,0 +1,36 @@
+use crate::yaml;
+use crate::yaml::testing::diff;
+
+#[test]
+fn roundtrip() {
+    yaml::assert_roundtrip!("a: 1 # one\nb: [2, 3]\n");
+    yaml::assert_roundtrip!(b"name: Ren\xe9\n".as_slice(), "latin-1");
+}
+
+#[test]
+#[should_panic(expected = "assertion failed: root: document differs from expected")]
+fn doc_eq_fails() {
+    let doc = yaml::from_slice("a: 1\n").unwrap();
+    yaml::assert_doc_eq!(doc, "a: 2\n", "root");
+}
+
+#[test]
+fn unified_diff() {
+    let expected = "a: 1\nb: 2\nc: 3\nd: 4\ne: 5\nf: 6\ng: 7\nh: 8\ni: 9\nj: 10\n";
+    let actual = "a: 1\nb: 20\nc: 3\nd: 4\ne: 5\nf: 6\ng: 7\nh: 8\ni: 9\nj: 10\nk: 11\n";
+
+    assert_eq!(
+        diff(expected.as_bytes(), actual.as_bytes()),
+        "--- expected\n+++ actual\n\
+         @@ -1,5 +1,5 @@\n a: 1\\n\n-b: 2\\n\n+b: 20\\n\n c: 3\\n\n d: 4\\n\n e: 5\\n\n\
+         @@ -8,3 +8,4 @@\n h: 8\\n\n i: 9\\n\n j: 10\\n\n+k: 11\\n\n"
+    );
+}
+
+#[test]
+fn diff_line_endings() {
+    assert_eq!(
+        diff(b"a: 1\r\nb: \xe9", b"a: 1\nb: \xe9\n"),
+        "--- expected\n+++ actual\n@@ -1,2 +1,2 @@\n-a: 1\\r\\n\n-b: \\xe9\n+a: 1\\n\n+b: \\xe9\\n\n"
+    );
+}
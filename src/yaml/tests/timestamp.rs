@@ -0,0 +1,59 @@
+use anyhow::{Context, Result};
+use time::macros::datetime;
+
+use crate::yaml;
+
+#[test]
+fn timestamps() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        - 2024-01-01
+        - 2024-01-01T10:00:00Z
+        - 2001-12-14t21:59:43.10-05:00
+        - 2001-12-14 21:59:43.10 -5
+        - 2001-12-15 2:59:43.10
+        - 2024-02-29T00:00:00+05:30
+        ",
+    )?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+    let values = root.iter().map(|v| v.as_timestamp()).collect::<Vec<_>>();
+
+    assert_eq!(
+        values,
+        [
+            Some(datetime!(2024-01-01 0:00 UTC)),
+            Some(datetime!(2024-01-01 10:00 UTC)),
+            Some(datetime!(2001-12-14 21:59:43.1 -5)),
+            Some(datetime!(2001-12-14 21:59:43.1 -5)),
+            Some(datetime!(2001-12-15 2:59:43.1 UTC)),
+            Some(datetime!(2024-02-29 0:00 +5:30)),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn not_timestamps() -> Result<()> {
+    let doc = yaml::from_slice(
+        r"
+        - '2024-01-01'
+        - 2024-1-1
+        - 2023-02-29
+        - 2024-01-01T
+        - 2024-01-01T25:00:00
+        - 2024-01-01T10:00:00.
+        - 2024-01-01T10:00:00X
+        - 20240101
+        ",
+    )?;
+
+    let root = doc.as_ref().as_sequence().context("missing root")?;
+
+    for value in root.iter() {
+        assert_eq!(value.as_timestamp(), None, "{value}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! Recognition of YAML timestamps, like `2001-12-14t21:59:43.10-05:00`.
+//!
+//! See <https://yaml.org/type/timestamp.html>.
+
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// Parse a timestamp.
+///
+/// A date without a time, like `2002-12-14`, is at midnight UTC, and so is a
+/// timestamp without a time zone.
+pub(crate) fn parse(string: &[u8]) -> Option<OffsetDateTime> {
+    let mut p = Parser { string, n: 0 };
+
+    let year = p.digits(4, 4)?;
+    p.expect(b'-')?;
+
+    if p.string.len() == 10 {
+        let month = p.digits(2, 2)?;
+        p.expect(b'-')?;
+        let day = p.digits(2, 2)?;
+        let date = date(year, month, day)?;
+        return Some(date.midnight().assume_utc());
+    }
+
+    let month = p.digits(1, 2)?;
+    p.expect(b'-')?;
+    let day = p.digits(1, 2)?;
+    let date = date(year, month, day)?;
+
+    if !p.eat(|b| matches!(b, b'T' | b't')) && !p.spaces() {
+        return None;
+    }
+
+    let hour = p.digits(1, 2)?;
+    p.expect(b':')?;
+    let minute = p.digits(2, 2)?;
+    p.expect(b':')?;
+    let second = p.digits(2, 2)?;
+
+    let mut nanos = 0;
+
+    if p.eat(|b| b == b'.') {
+        let start = p.n;
+        let mut scale = 100_000_000;
+
+        while let Some(digit) = p.digit() {
+            nanos += digit * scale;
+            scale /= 10;
+        }
+
+        if p.n == start {
+            return None;
+        }
+    }
+
+    let time = Time::from_hms_nano(
+        u8::try_from(hour).ok()?,
+        u8::try_from(minute).ok()?,
+        u8::try_from(second).ok()?,
+        nanos,
+    )
+    .ok()?;
+
+    p.spaces();
+
+    let offset = match p.peek() {
+        None => UtcOffset::UTC,
+        Some(b'Z') => {
+            p.n += 1;
+            UtcOffset::UTC
+        }
+        Some(sign @ (b'+' | b'-')) => {
+            p.n += 1;
+            let hours = i8::try_from(p.digits(1, 2)?).ok()?;
+
+            let minutes = if p.eat(|b| b == b':') {
+                i8::try_from(p.digits(2, 2)?).ok()?
+            } else {
+                0
+            };
+
+            if sign == b'-' {
+                UtcOffset::from_hms(-hours, -minutes, 0).ok()?
+            } else {
+                UtcOffset::from_hms(hours, minutes, 0).ok()?
+            }
+        }
+        Some(..) => return None,
+    };
+
+    if p.peek().is_some() {
+        return None;
+    }
+
+    Some(PrimitiveDateTime::new(date, time).assume_offset(offset))
+}
+
+/// Construct a date.
+fn date(year: u32, month: u32, day: u32) -> Option<Date> {
+    let month = Month::try_from(u8::try_from(month).ok()?).ok()?;
+    Date::from_calendar_date(i32::try_from(year).ok()?, month, u8::try_from(day).ok()?).ok()
+}
+
+struct Parser<'a> {
+    string: &'a [u8],
+    n: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.string.get(self.n).copied()
+    }
+
+    /// Consume a byte matching the given predicate.
+    fn eat(&mut self, f: impl FnOnce(u8) -> bool) -> bool {
+        if self.peek().is_some_and(f) {
+            self.n += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        self.eat(|c| c == b).then_some(())
+    }
+
+    /// Consume spaces and tabs, returning `true` if any were consumed.
+    fn spaces(&mut self) -> bool {
+        let start = self.n;
+        while self.eat(|b| matches!(b, b' ' | b'\t')) {}
+        self.n != start
+    }
+
+    fn digit(&mut self) -> Option<u32> {
+        let b = self.peek().filter(u8::is_ascii_digit)?;
+        self.n += 1;
+        Some(u32::from(b - b'0'))
+    }
+
+    /// Consume between `min` and `max` digits.
+    fn digits(&mut self, min: usize, max: usize) -> Option<u32> {
+        let start = self.n;
+        let mut value = 0;
+
+        while self.n - start < max {
+            let Some(digit) = self.digit() else {
+                break;
+            };
+
+            value = value * 10 + digit;
+        }
+
+        (self.n - start >= min).then_some(value)
+    }
+}
@@ -1,12 +1,18 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 use bstr::{BStr, ByteSlice};
 #[cfg(feature = "serde-edits")]
 use serde::{Deserialize, Serialize};
+use twox_hash::xxh3::Hash64;
 
 use crate::yaml::data::{Data, Id};
-use crate::yaml::raw::Raw;
-use crate::yaml::{Any, Mapping, Number, Sequence, String};
+use crate::yaml::error::{Error, ErrorKind};
+use crate::yaml::number;
+use crate::yaml::raw::{self, Raw};
+use crate::yaml::{Any, Mapping, Number, Omap, OwnedScalar, Parser, Sequence, Set, String};
 
 /// The kind of a multiline string.
 #[derive(Default, Debug, Clone, Copy)]
@@ -88,7 +94,11 @@ pub enum Separator<'a> {
     /// Automatically figure out which separator to use based on the last
     /// element in the collection.
     ///
-    /// If this does not exist, a default separator of `" "` will be used.
+    /// If this does not exist, a default separator of `" "` will be used. A
+    /// document-wide default can be configured with
+    /// [`Document::set_separator`].
+    ///
+    /// [`Document::set_separator`]: crate::yaml::Document::set_separator
     Auto,
     /// A custom separator.
     ///
@@ -97,8 +107,81 @@ pub enum Separator<'a> {
     /// The only legal separator in YAML is spaces, but this can technically
     /// contain anything and will be literally embedded in the generated YAML.
     /// It is up to the caller to ensure nothing but spaces is used or suffer
-    /// the consequences.
+    /// the consequences. Use [`Separator::custom`] to construct a custom
+    /// separator which has been validated.
     Custom(&'a str),
+    /// A separator consisting of the given number of spaces.
+    ///
+    /// See [`Separator::spaces`].
+    Spaces(usize),
+}
+
+impl<'a> Separator<'a> {
+    /// Construct a separator consisting of `n` spaces.
+    ///
+    /// At least one space is always used, since a value can't immediately
+    /// follow its key or sequence marker.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("- one")?;
+    /// let mut root = doc.as_mut().into_sequence_mut().context("missing root sequence")?;
+    /// root.push(yaml::Separator::spaces(3)).set_string("two");
+    /// root.push(yaml::Separator::spaces(0)).set_string("three");
+    ///
+    /// assert_eq!(doc.to_string(), "- one\n-   two\n- three");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub const fn spaces(n: usize) -> Self {
+        Self::Spaces(n)
+    }
+
+    /// Construct a custom separator, validating that it only consists of
+    /// spaces and tabs.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::BadSeparator`] if the separator is empty or
+    /// contains anything but spaces and tabs, where the span of the error
+    /// points to the first offending character.
+    ///
+    /// [`ErrorKind::BadSeparator`]: crate::yaml::ErrorKind::BadSeparator
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: 1")?;
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.insert("b", yaml::Separator::custom(" \t")?)?.set_u32(2);
+    /// assert_eq!(doc.to_string(), "a: 1\nb: \t2");
+    ///
+    /// let error = yaml::Separator::custom("  # ").unwrap_err();
+    /// assert_eq!(*error.kind(), yaml::ErrorKind::BadSeparator);
+    /// assert_eq!(error.span(), 2..3);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn custom(separator: &'a str) -> Result<Self, Error> {
+        if separator.is_empty() {
+            return Err(Error::new(0..0, ErrorKind::BadSeparator));
+        }
+
+        if let Some((n, c)) = separator
+            .char_indices()
+            .find(|&(_, c)| !matches!(c, ' ' | '\t'))
+        {
+            return Err(Error::new(n..n + c.len_utf8(), ErrorKind::BadSeparator));
+        }
+
+        Ok(Self::Custom(separator))
+    }
 }
 
 /// The kind of a null value.
@@ -160,7 +243,7 @@ macro_rules! as_number {
             match self.data.raw(self.id) {
                 Raw::Number(raw) => {
                     let string = self.data.str(raw.string);
-                    lexical_core::parse(string).ok()
+                    number::parse(string)
                 }
                 _ => None,
             }
@@ -325,14 +408,106 @@ impl<'a> Value<'a> {
         self.id
     }
 
-    /// Get the value as a [`BStr`].
+    /// Calculate a hash of the value which only takes its structure and
+    /// contents into account.
+    ///
+    /// Formatting such as whitespace, comments, quoting and block styles do not
+    /// affect the hash. Keys in mappings are considered to be unordered, so two
+    /// mappings with the same entries in a different order hash the same.
+    ///
+    /// Numbers are hashed by their exact numerical value if they can be
+    /// parsed, so `0010`, `10` and `10.0` hash the same. Values which are
+    /// equal according to [`Value::total_cmp`] have the same hash.
     ///
     /// # Examples
     ///
     /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let a = yaml::from_slice(
+    ///     r#"
+    ///     name: "Descartes"
+    ///     books: [Musicae Compendium, Principia philosophiae]
+    ///     "#
+    /// )?;
+    ///
+    /// let b = yaml::from_slice(
+    ///     r"
+    ///     books:
+    ///       - Musicae Compendium
+    ///       - 'Principia philosophiae'
+    ///     name:   Descartes
+    ///     "
+    /// )?;
+    ///
+    /// assert_eq!(a.as_ref().structural_hash(), b.as_ref().structural_hash());
+    ///
+    /// let c = yaml::from_slice("name: Plato")?;
+    /// assert_ne!(a.as_ref().structural_hash(), c.as_ref().structural_hash());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = Hash64::default();
+        hash_structure(self.data, self.id, &mut hasher);
+        hasher.finish()
+    }
+
+    /// Compare two values using a total ordering which only takes their
+    /// structure and contents into account.
+    ///
+    /// Values of different kinds are ordered as follows:
+    /// * Nulls come first.
+    /// * Booleans, where `false` comes before `true`.
+    /// * Numbers, which are compared by their exact numerical value.
+    /// * Strings, which are compared byte-wise.
+    /// * Sequences, which are compared item by item.
+    /// * Mappings, which are compared entry by entry in the order of their
+    ///   keys, first by key and then by value. The order in which keys appear
+    ///   doesn't matter.
+    ///
+    /// Like [`Value::structural_hash`], formatting such as whitespace, comments
+    /// and quoting does not affect the ordering. This is suitable for use with
+    /// [`SequenceMut::sort_by`] to deterministically sort sequences containing
+    /// different kinds of values. Values can also be compared across documents.
+    ///
+    /// [`SequenceMut::sort_by`]: crate::yaml::SequenceMut::sort_by
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    ///
     /// use anyhow::Context;
     /// use nondestructive::yaml;
-    /// use bstr::BStr;
+    ///
+    /// let doc = yaml::from_slice("[~, true, 10, 9.5, '10', [1], {a: 1}]")?;
+    /// let root = doc.as_ref().as_sequence().context("missing root")?;
+    ///
+    /// let values = root.iter().collect::<Vec<_>>();
+    ///
+    /// assert_eq!(values[0].total_cmp(&values[1]), Ordering::Less);
+    /// assert_eq!(values[1].total_cmp(&values[2]), Ordering::Less);
+    /// assert_eq!(values[2].total_cmp(&values[3]), Ordering::Greater);
+    /// assert_eq!(values[2].total_cmp(&values[4]), Ordering::Less);
+    /// assert_eq!(values[5].total_cmp(&values[6]), Ordering::Less);
+    ///
+    /// let other = yaml::from_slice("0010")?;
+    /// assert_eq!(values[2].total_cmp(&other.as_ref()), Ordering::Equal);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn total_cmp(&self, other: &Value<'_>) -> Ordering {
+        cmp_structure(self.data, self.id, other.data, other.id)
+    }
+
+    /// Get the value as a [`BStr`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::{yaml, BStr};
     ///
     /// let doc = yaml::from_slice("string")?;
     /// assert_eq!(doc.as_ref().as_str(), Some("string"));
@@ -360,10 +535,34 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Get the value as a byte slice.
+    ///
+    /// Strings are not required to be valid UTF-8, so this provides the exact
+    /// bytes of the string after any escape sequences have been processed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// // "café" encoded as Windows-1252.
+    /// let doc = yaml::from_slice(b"caf\xe9")?;
+    /// assert_eq!(doc.as_ref().as_bytes(), Some(&b"caf\xe9"[..]));
+    /// assert_eq!(doc.as_ref().as_str(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self.data.raw(self.id) {
+            Raw::String(raw) => Some(self.data.str(raw.id).as_bytes()),
+            _ => None,
+        }
+    }
+
     /// Get the value as a [`str`]. This might fail if the underlying string is
     /// not valid UTF-8.
     ///
-    /// See [`Value::as_bstr`] for an alternative.
+    /// See [`Value::as_bstr`] or [`Value::as_str_lossy`] for alternatives.
     ///
     /// # Escape sequences and unicode
     ///
@@ -434,6 +633,37 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Get the value as a [`str`], replacing any invalid UTF-8 sequences with
+    /// the replacement character `U+FFFD`.
+    ///
+    /// This is useful for displaying values, such as when logging, where the
+    /// exact contents of the string don't matter. Use [`Value::as_bytes`] to
+    /// access the exact contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// // "café" encoded as Windows-1252.
+    /// let doc = yaml::from_slice(b"caf\xe9")?;
+    /// assert_eq!(doc.as_ref().as_str_lossy().as_deref(), Some("caf\u{fffd}"));
+    ///
+    /// let doc = yaml::from_slice("café")?;
+    /// assert_eq!(doc.as_ref().as_str_lossy().as_deref(), Some("café"));
+    ///
+    /// let doc = yaml::from_slice("42")?;
+    /// assert_eq!(doc.as_ref().as_str_lossy(), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_str_lossy(&self) -> Option<Cow<'a, str>> {
+        match self.data.raw(self.id) {
+            Raw::String(raw) => Some(self.data.str(raw.id).to_str_lossy()),
+            _ => None,
+        }
+    }
+
     /// Get the value as a boolean.
     ///
     /// # Examples
@@ -525,6 +755,294 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Get the tag of the value, like `!!set` or `!!omap`.
+    ///
+    /// When parsing, tags are only supported on mappings and sequences. Tags
+    /// on other values are treated as part of the value, unless they have
+    /// been set through [`ValueMut::set_tag`].
+    ///
+    /// [`ValueMut::set_tag`]: crate::yaml::ValueMut::set_tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     tagged: !custom {a: 1}
+    ///     untagged: {a: 1}
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("tagged").and_then(|v| v.tag()), Some("!custom".into()));
+    /// assert_eq!(root.get("untagged").and_then(|v| v.tag()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn tag(&self) -> Option<&'a BStr> {
+        let tag = match self.data.raw(self.id) {
+            Raw::Mapping(raw) => raw.tag.as_ref()?,
+            Raw::Sequence(raw) => raw.tag.as_ref()?,
+            _ => self.data.layout(self.id).tag.as_ref()?,
+        };
+
+        Some(self.data.str(tag.name))
+    }
+
+    /// Get the name of the anchor of the value, like `base` in `&base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &base
+    ///       name: app
+    ///     other: {a: 1}
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("base").and_then(|v| v.anchor()), Some("base".into()));
+    /// assert_eq!(root.get("other").and_then(|v| v.anchor()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn anchor(&self) -> Option<&'a BStr> {
+        let anchor = self.data.layout(self.id).anchor?;
+        Some(self.data.str(anchor.name))
+    }
+
+    /// Test if the value is an alias of an anchored value, like `*base`.
+    ///
+    /// Aliases are not resolved, so the name of the anchor they refer to can
+    /// be accessed through [`Value::alias`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - &first one
+    ///     - *first
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// assert!(!root.get(0).context("missing first")?.is_alias());
+    /// assert!(root.get(1).context("missing second")?.is_alias());
+    /// assert_eq!(root.get(1).and_then(|v| v.as_str()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_alias(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Alias(..))
+    }
+
+    /// Get the name of the anchor an alias refers to, like `base` in `*base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     base: &base
+    ///       name: app
+    ///     other:
+    ///       <<: *base
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let other = root.get("other").and_then(|v| v.as_mapping()).context("missing other")?;
+    /// assert_eq!(other.get("<<").and_then(|v| v.alias()), Some("base".into()));
+    /// assert_eq!(root.get("base").and_then(|v| v.alias()), None);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn alias(&self) -> Option<&'a BStr> {
+        match self.data.raw(self.id) {
+            Raw::Alias(raw) => Some(self.data.str(raw.name)),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a [`Set`], which is a mapping tagged with `!!set`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     players: !!set
+    ///       ? Mark McGwire
+    ///       ? Sammy Sosa
+    ///     teams: !!set {Cubs, Yankees}
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    ///
+    /// let players = root.get("players").and_then(|v| v.as_set()).context("missing players")?;
+    /// assert!(players.contains("Sammy Sosa"));
+    /// assert!(players.iter().eq(["Mark McGwire", "Sammy Sosa"]));
+    ///
+    /// let teams = root.get("teams").and_then(|v| v.as_set()).context("missing teams")?;
+    /// assert!(teams.iter().eq(["Cubs", "Yankees"]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_set(&self) -> Option<Set<'a>> {
+        match self.data.raw(self.id) {
+            Raw::Mapping(raw) if self.is_tagged(raw.tag.as_ref(), "!!set") => {
+                Some(Set::new(self.data, self.id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the value as an [`Omap`], which is a sequence of single-entry
+    /// mappings tagged with `!!omap`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     bests: !!omap
+    ///       - Mark McGwire: 65
+    ///       - Sammy Sosa: 63
+    ///       - Ken Griffy: 58
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// let bests = root.get("bests").and_then(|v| v.as_omap()).context("missing bests")?;
+    ///
+    /// assert_eq!(bests.get("Sammy Sosa").and_then(|v| v.as_u32()), Some(63));
+    ///
+    /// let keys = bests.iter().map(|(key, _)| key).collect::<Vec<_>>();
+    /// assert_eq!(keys, ["Mark McGwire", "Sammy Sosa", "Ken Griffy"]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn as_omap(&self) -> Option<Omap<'a>> {
+        match self.data.raw(self.id) {
+            Raw::Sequence(raw) if self.is_tagged(raw.tag.as_ref(), "!!omap") => {
+                Omap::new(self.data, self.id)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_tagged(&self, tag: Option<&raw::Tag>, name: &str) -> bool {
+        tag.is_some_and(|tag| self.data.str(tag.name) == name)
+    }
+
+    /// Test if the value is null, which is the case for `null`, `Null`,
+    /// `NULL` and `~`, or a value which is missing entirely.
+    ///
+    /// How the null is written is preserved, and which spellings are
+    /// recognized depends on the [`Schema`] the document was parsed with.
+    ///
+    /// [`Schema`]: crate::yaml::Schema
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("a: ~\nb: NULL # none\nc: [Null, ~]\nd:\ne: nil\n")?;
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    ///
+    /// assert!(root.get("a").context("missing a")?.is_null());
+    /// assert!(root.get("b").context("missing b")?.is_null());
+    /// assert!(root.get("d").context("missing d")?.is_null());
+    /// assert!(!root.get("e").context("missing e")?.is_null());
+    ///
+    /// let c = root.get("c").and_then(|v| v.as_sequence()).context("missing c")?;
+    /// assert!(c.iter().all(|v| v.is_null()));
+    ///
+    /// assert_eq!(doc.to_string(), "a: ~\nb: NULL # none\nc: [Null, ~]\nd:\ne: nil\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Null(..))
+    }
+
+    /// Test if the value is a [`Mapping`] without any entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// assert!(yaml::from_slice("{}")?.as_ref().is_empty_mapping());
+    /// assert!(!yaml::from_slice("{a: 1}")?.as_ref().is_empty_mapping());
+    /// assert!(!yaml::from_slice("[]")?.as_ref().is_empty_mapping());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_empty_mapping(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Mapping(raw) if raw.items.is_empty())
+    }
+
+    /// Test if the value is a [`Sequence`] without any items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// assert!(yaml::from_slice("[]")?.as_ref().is_empty_sequence());
+    /// assert!(!yaml::from_slice("[1]")?.as_ref().is_empty_sequence());
+    /// assert!(!yaml::from_slice("{}")?.as_ref().is_empty_sequence());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_empty_sequence(&self) -> bool {
+        matches!(self.data.raw(self.id), Raw::Sequence(raw) if raw.items.is_empty())
+    }
+
+    /// Test if the value is either an empty [`Mapping`] or an empty
+    /// [`Sequence`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// assert!(yaml::from_slice("{}")?.as_ref().is_empty_collection());
+    /// assert!(yaml::from_slice("[]")?.as_ref().is_empty_collection());
+    /// assert!(!yaml::from_slice("[[]]")?.as_ref().is_empty_collection());
+    /// assert!(!yaml::from_slice("~")?.as_ref().is_empty_collection());
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn is_empty_collection(&self) -> bool {
+        self.is_empty_mapping() || self.is_empty_sequence()
+    }
+
     /// Coerce a number to help discriminate the value type borrowing from self.
     ///
     /// # Examples
@@ -580,6 +1098,447 @@ impl<'a> Value<'a> {
     as_number!(as_i64, i64, "64-bit signed integer", -42);
     as_number!(as_u128, u128, "16-bit unsigned integer", 42);
     as_number!(as_i128, i128, "128-bit signed integer", -42);
+
+    /// Try and get the value as an arbitrary precision integer, see
+    /// [`Number::as_bigint`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::{yaml, BigInt};
+    ///
+    /// let doc = yaml::from_slice("[123456789012345678901234567890123456789012, 1.5]")?;
+    /// let values = doc.as_ref().as_sequence().map(|s| s.iter().map(|v| v.as_bigint()).collect::<Vec<_>>());
+    ///
+    /// let expected = "123456789012345678901234567890123456789012".parse::<BigInt>()?;
+    /// assert_eq!(values, Some(vec![Some(expected), None]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "bigint")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigint")))]
+    #[must_use]
+    pub fn as_bigint(&self) -> Option<num_bigint::BigInt> {
+        self.as_number()?.as_bigint()
+    }
+
+    /// Try and get the value as an arbitrary precision decimal, see
+    /// [`Number::as_bigdecimal`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::{yaml, BigDecimal};
+    ///
+    /// let doc = yaml::from_slice("3.14159265358979323846264338327950288")?;
+    /// let expected = "3.14159265358979323846264338327950288".parse::<BigDecimal>()?;
+    /// assert_eq!(doc.as_ref().as_bigdecimal(), Some(expected));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "bigdecimal")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bigdecimal")))]
+    #[must_use]
+    pub fn as_bigdecimal(&self) -> Option<bigdecimal::BigDecimal> {
+        self.as_number()?.as_bigdecimal()
+    }
+
+    /// Try and get the value as a timestamp.
+    ///
+    /// Only plain strings are recognized, following the [YAML timestamp type].
+    /// A date without a time, like `2024-01-01`, is at midnight UTC, and so is
+    /// a timestamp without a time zone.
+    ///
+    /// [YAML timestamp type]: https://yaml.org/type/timestamp.html
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::{yaml, OffsetDateTime};
+    ///
+    /// let doc = yaml::from_slice("[2024-01-01, 2024-01-01T10:00:00+02:00, '2024-01-01', today]")?;
+    /// let values = doc.as_ref().as_sequence().map(|s| s.iter().map(|v| v.as_timestamp()).collect::<Vec<_>>());
+    ///
+    /// let first = OffsetDateTime::from_unix_timestamp(1704067200)?;
+    /// let second = OffsetDateTime::from_unix_timestamp(1704096000)?;
+    /// assert_eq!(values, Some(vec![Some(first), Some(second), None, None]));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    #[must_use]
+    pub fn as_timestamp(&self) -> Option<time::OffsetDateTime> {
+        match self.data.raw(self.id) {
+            Raw::String(raw) if matches!(raw.kind, raw::RawStringKind::Bare) => {
+                crate::yaml::timestamp::parse(self.data.str(raw.id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy the value into an [`OwnedScalar`], which doesn't borrow from the
+    /// document.
+    ///
+    /// Returns `None` if the value is a collection or an alias.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    /// use nondestructive::yaml::OwnedScalar;
+    ///
+    /// let doc = yaml::from_slice(
+    ///     r"
+    ///     - hello
+    ///     - 42
+    ///     - -7
+    ///     - 1.5
+    ///     - true
+    ///     - ~
+    ///     - [1, 2]
+    ///     "
+    /// )?;
+    ///
+    /// let root = doc.as_ref().as_sequence().context("missing root sequence")?;
+    /// let values = root.iter().map(|v| v.to_owned_scalar()).collect::<Vec<_>>();
+    /// drop(doc);
+    ///
+    /// assert_eq!(
+    ///     values,
+    ///     [
+    ///         Some(OwnedScalar::String(String::from("hello"))),
+    ///         Some(OwnedScalar::Unsigned(42)),
+    ///         Some(OwnedScalar::Signed(-7)),
+    ///         Some(OwnedScalar::Float(1.5)),
+    ///         Some(OwnedScalar::Bool(true)),
+    ///         Some(OwnedScalar::Null),
+    ///         None,
+    ///     ]
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_owned_scalar(&self) -> Option<OwnedScalar> {
+        let scalar = match self.data.raw(self.id) {
+            Raw::Null(..) => OwnedScalar::Null,
+            Raw::Boolean(raw) => OwnedScalar::Bool(raw.value),
+            Raw::Number(raw) => OwnedScalar::from_number(self.data.str(raw.string)),
+            Raw::String(raw) => OwnedScalar::from_bytes(self.data.str(raw.id)),
+            _ => return None,
+        };
+
+        Some(scalar)
+    }
+}
+
+/// Feed the structure of the value `id` into `hasher`.
+fn hash_structure<H>(data: &Data, id: Id, hasher: &mut H)
+where
+    H: Hasher,
+{
+    match data.raw(id) {
+        Raw::Null(..) => {
+            0u8.hash(hasher);
+        }
+        Raw::Boolean(raw) => {
+            1u8.hash(hasher);
+            raw.value.hash(hasher);
+        }
+        Raw::Number(raw) => {
+            let string = data.str(raw.string);
+
+            if let Some(value) = number::Exact::parse(string) {
+                2u8.hash(hasher);
+                value.hash(hasher);
+            } else {
+                3u8.hash(hasher);
+                string.hash(hasher);
+            }
+        }
+        Raw::String(raw) => {
+            4u8.hash(hasher);
+            data.str(raw.id).hash(hasher);
+        }
+        Raw::Mapping(raw) => {
+            5u8.hash(hasher);
+            raw.items.len().hash(hasher);
+
+            // Combine entries in an order-independent manner.
+            let mut combined = 0u64;
+
+            for item in &raw.items {
+                let item = data.mapping_item(*item);
+                let mut entry = Hash64::default();
+                data.str(item.key.id).hash(&mut entry);
+                hash_structure(data, item.value, &mut entry);
+                combined = combined.wrapping_add(entry.finish());
+            }
+
+            combined.hash(hasher);
+        }
+        Raw::Sequence(raw) => {
+            6u8.hash(hasher);
+            raw.items.len().hash(hasher);
+
+            for item in &raw.items {
+                hash_structure(data, data.sequence_item(*item).value, hasher);
+            }
+        }
+        Raw::MappingItem(raw) => {
+            hash_structure(data, raw.value, hasher);
+        }
+        Raw::SequenceItem(raw) => {
+            hash_structure(data, raw.value, hasher);
+        }
+        Raw::Alias(raw) => {
+            7u8.hash(hasher);
+            data.str(raw.name).hash(hasher);
+        }
+    }
+}
+
+/// Calculate the fingerprint of the value `id`, which combines its structural
+/// hash with the style in which it is written.
+pub(crate) fn fingerprint(data: &Data, id: Id) -> u64 {
+    let mut hasher = Hash64::default();
+    hash_structure(data, id, &mut hasher);
+    hash_style(data, id, &mut hasher);
+    hasher.finish()
+}
+
+/// Feed the style of the value `id` into `hasher`.
+///
+/// This covers the textual representation of scalars, how strings are quoted,
+/// whether collections are inline, tags, anchors and the order of keys.
+/// Whitespace and comments are not included.
+fn hash_style<H>(data: &Data, id: Id, hasher: &mut H)
+where
+    H: Hasher,
+{
+    let layout = data.layout(id);
+    let anchor = layout.anchor.as_ref();
+    anchor.map(|anchor| data.str(anchor.name)).hash(hasher);
+    layout
+        .tag
+        .as_ref()
+        .map(|tag| data.str(tag.name))
+        .hash(hasher);
+
+    match data.raw(id) {
+        Raw::Null(raw) => match raw {
+            raw::Null::Keyword(string) => data.str(*string).hash(hasher),
+            raw::Null::Tilde => b"~".hash(hasher),
+            raw::Null::Empty => b"".hash(hasher),
+        },
+        Raw::Boolean(raw) => {
+            data.str(raw.string).hash(hasher);
+        }
+        Raw::Number(raw) => {
+            data.str(raw.string).hash(hasher);
+        }
+        Raw::String(raw) => {
+            hash_string_style(data, raw, hasher);
+        }
+        Raw::Mapping(raw) => {
+            matches!(raw.kind, raw::MappingKind::Inline { .. }).hash(hasher);
+            raw.tag.as_ref().map(|tag| data.str(tag.name)).hash(hasher);
+
+            for item in &raw.items {
+                let item = data.mapping_item(*item);
+
+                match item.kind {
+                    raw::MappingItemKind::Pair => 0u8.hash(hasher),
+                    raw::MappingItemKind::Explicit { .. } => 1u8.hash(hasher),
+                    raw::MappingItemKind::Key => 2u8.hash(hasher),
+                    raw::MappingItemKind::Complex { key, .. } => {
+                        3u8.hash(hasher);
+                        hash_style(data, key, hasher);
+                    }
+                }
+
+                data.str(item.key.id).hash(hasher);
+                hash_string_style(data, &item.key, hasher);
+                hash_style(data, item.value, hasher);
+            }
+        }
+        Raw::Sequence(raw) => {
+            matches!(raw.kind, raw::SequenceKind::Inline { .. }).hash(hasher);
+            raw.tag.as_ref().map(|tag| data.str(tag.name)).hash(hasher);
+
+            for item in &raw.items {
+                hash_style(data, data.sequence_item(*item).value, hasher);
+            }
+        }
+        Raw::MappingItem(raw) => {
+            hash_style(data, raw.value, hasher);
+        }
+        Raw::SequenceItem(raw) => {
+            hash_style(data, raw.value, hasher);
+        }
+        Raw::Alias(..) => {}
+    }
+}
+
+/// Feed how the string `raw` is quoted into `hasher`.
+fn hash_string_style<H>(data: &Data, raw: &raw::String, hasher: &mut H)
+where
+    H: Hasher,
+{
+    match raw.kind {
+        raw::RawStringKind::Bare => b"".hash(hasher),
+        raw::RawStringKind::Single => b"'".hash(hasher),
+        raw::RawStringKind::Double => b"\"".hash(hasher),
+        raw::RawStringKind::Original => data.str(raw.original).get(..1).hash(hasher),
+        raw::RawStringKind::Multiline { prefix } => data.str(prefix).hash(hasher),
+    }
+}
+
+/// Rank of a value kind used when comparing values of different kinds.
+fn rank(raw: &Raw) -> u8 {
+    match raw {
+        Raw::Null(..) => 0,
+        Raw::Boolean(..) => 1,
+        Raw::Number(..) => 2,
+        Raw::String(..) => 3,
+        Raw::Sequence(..) | Raw::SequenceItem(..) => 4,
+        Raw::Mapping(..) | Raw::MappingItem(..) => 5,
+        Raw::Alias(..) => 6,
+    }
+}
+
+/// Compare the structure of the value `a` with the value `b`.
+fn cmp_structure(a_data: &Data, a: Id, b_data: &Data, b: Id) -> Ordering {
+    match (a_data.raw(a), b_data.raw(b)) {
+        (Raw::MappingItem(a), _) => cmp_structure(a_data, a.value, b_data, b),
+        (Raw::SequenceItem(a), _) => cmp_structure(a_data, a.value, b_data, b),
+        (_, Raw::MappingItem(b)) => cmp_structure(a_data, a, b_data, b.value),
+        (_, Raw::SequenceItem(b)) => cmp_structure(a_data, a, b_data, b.value),
+        (Raw::Null(..), Raw::Null(..)) => Ordering::Equal,
+        (Raw::Boolean(a), Raw::Boolean(b)) => a.value.cmp(&b.value),
+        (Raw::Number(a), Raw::Number(b)) => cmp_numbers(a_data.str(a.string), b_data.str(b.string)),
+        (Raw::String(a), Raw::String(b)) => a_data.str(a.id).cmp(b_data.str(b.id)),
+        (Raw::Sequence(a), Raw::Sequence(b)) => {
+            let a = a.items.iter().map(|item| a_data.sequence_item(*item).value);
+            let mut b = b.items.iter().map(|item| b_data.sequence_item(*item).value);
+
+            for a in a {
+                let Some(b) = b.next() else {
+                    return Ordering::Greater;
+                };
+
+                match cmp_structure(a_data, a, b_data, b) {
+                    Ordering::Equal => {}
+                    ordering => return ordering,
+                }
+            }
+
+            if b.next().is_some() {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }
+        }
+        (Raw::Mapping(a), Raw::Mapping(b)) => {
+            let a = sorted_entries(a_data, &a.items);
+            let mut b = sorted_entries(b_data, &b.items).into_iter();
+
+            for a in a {
+                let Some(b) = b.next() else {
+                    return Ordering::Greater;
+                };
+
+                match a_data.str(a.key.id).cmp(b_data.str(b.key.id)) {
+                    Ordering::Equal => {}
+                    ordering => return ordering,
+                }
+
+                match cmp_structure(a_data, a.value, b_data, b.value) {
+                    Ordering::Equal => {}
+                    ordering => return ordering,
+                }
+            }
+
+            if b.next().is_some() {
+                Ordering::Less
+            } else {
+                Ordering::Equal
+            }
+        }
+        (Raw::Alias(a), Raw::Alias(b)) => a_data.str(a.name).cmp(b_data.str(b.name)),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Get the entries of a mapping ordered by their keys, and then by their values
+/// in case keys are duplicated, so that mappings are compared regardless of the
+/// order in which their keys appear.
+fn sorted_entries<'a>(data: &'a Data, items: &[Id]) -> Vec<&'a raw::MappingItem> {
+    let mut entries = items
+        .iter()
+        .map(|item| data.mapping_item(*item))
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| {
+        data.str(a.key.id)
+            .cmp(data.str(b.key.id))
+            .then_with(|| cmp_structure(data, a.value, data, b.value))
+    });
+
+    entries
+}
+
+/// Test if the key of the mapping item `item` is structurally equal to the
+/// value `key`, which doesn't have to belong to the same document.
+pub(crate) fn key_eq(data: &Data, item: &raw::MappingItem, key: &Value<'_>) -> bool {
+    if let raw::MappingItemKind::Complex { key: id, .. } = item.kind {
+        return cmp_structure(data, id, key.data, key.id).is_eq();
+    }
+
+    // Other keys are only stored as text. Quoted keys are always strings,
+    // while bare keys which might be equal to `key` are parsed with the options
+    // of the document to determine which kind of scalar they are, so that
+    // every other key can be skipped without parsing it.
+    let text = data.str(item.key.id);
+    let bare = matches!(item.key.kind, raw::RawStringKind::Bare);
+
+    match key.data.raw(key.id) {
+        Raw::String(string) => {
+            if key.data.str(string.id) != text {
+                return false;
+            }
+
+            if !bare {
+                return true;
+            }
+        }
+        _ if !bare => return false,
+        Raw::Number(..)
+            if !text
+                .first()
+                .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.')) =>
+        {
+            return false;
+        }
+        Raw::Null(..) | Raw::Boolean(..) if text.len() > 5 => return false,
+        _ => {}
+    }
+
+    let Ok(doc) = Parser::new(text).with_options(data.options()).parse() else {
+        return false;
+    };
+
+    cmp_structure(&doc.data, doc.root, key.data, key.id).is_eq()
+}
+
+/// Compare two numbers by their exact numerical value.
+///
+/// Numbers which can't be parsed are ordered after all other numbers.
+fn cmp_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    match (number::Exact::parse(a), number::Exact::parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(..), None) => Ordering::Less,
+        (None, Some(..)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
 }
 
 impl fmt::Display for Value<'_> {
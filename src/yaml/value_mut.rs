@@ -1,8 +1,15 @@
+use crate::yaml::clear;
 use crate::yaml::data::{Data, Id};
+use crate::yaml::lock;
 use crate::yaml::raw::{self, Raw};
-use crate::yaml::{AnyMut, Block, MappingMut, Null, SequenceMut, StringKind, Value};
+use crate::yaml::{
+    AnyMut, Block, Error, ErrorKind, MappingMut, Null, Scalar, SequenceMut, StringKind, Value,
+};
 
 /// A mutable value inside of a document.
+///
+/// A value which is, or contains, a value [locked][crate::yaml::Document::lock]
+/// by someone else is left untouched by the methods which replace it.
 pub struct ValueMut<'a> {
     data: &'a mut Data,
     pub(crate) id: Id,
@@ -14,6 +21,12 @@ impl<'a> ValueMut<'a> {
         Self { data, id }
     }
 
+    /// Test if the value can't be replaced, since it is or contains a value
+    /// locked by someone else.
+    fn is_locked(&self) -> bool {
+        lock::contains_locked(self.data, self.id)
+    }
+
     /// Coerce into [`AnyMut`] to help discriminate the value type.
     ///
     /// # Examples
@@ -319,6 +332,8 @@ macro_rules! set_float {
     ($name:ident, $ty:ty, $string:literal, $lit:literal, $hint:ident) => {
         #[doc = concat!("Set the value as a ", $string, ".")]
         ///
+        /// Values which are not finite are written as `.inf`, `-.inf` or `.nan`.
+        ///
         /// # Examples
         ///
         /// ```
@@ -332,8 +347,12 @@ macro_rules! set_float {
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         pub fn $name(&mut self, value: $ty) {
+            if self.is_locked() {
+                return;
+            }
+
             let mut buffer = ryu::Buffer::new();
-            let string = self.data.insert_str(buffer.format(value));
+            let string = self.data.insert_str(crate::yaml::number::format_float(&mut buffer, value));
             self.data.replace(self.id, Raw::Number(raw::Number::new(string, crate::yaml::serde_hint::$hint)));
         }
     };
@@ -356,6 +375,10 @@ macro_rules! set_number {
         /// # Ok::<_, anyhow::Error>(())
         /// ```
         pub fn $name(&mut self, value: $ty) {
+            if self.is_locked() {
+                return;
+            }
+
             let mut buffer = itoa::Buffer::new();
             let string = self.data.insert_str(buffer.format(value));
             self.data.replace(self.id, Raw::Number(raw::Number::new(string, crate::yaml::serde_hint::$hint)));
@@ -387,6 +410,10 @@ impl<'a> ValueMut<'a> {
     /// ```
     #[inline]
     pub fn set_null(&mut self, kind: Null) {
+        if self.is_locked() {
+            return;
+        }
+
         let raw_kind = match kind {
             Null::Keyword => raw::Null::Keyword(self.data.insert_str("null")),
             Null::Tilde => raw::Null::Tilde,
@@ -402,10 +429,20 @@ impl<'a> ValueMut<'a> {
     /// used.
     ///
     /// The heuristic used is:
-    /// * [`StringKind::Single`] will be used if the leading digit is numeric.
-    /// * [`StringKind::Double`] will be used if either a single `'` is
-    ///   encounted, or a non-graphical component that requires escaping.
-    /// * Otherwise, [`StringKind::Bare`] is used.
+    /// * [`StringKind::Double`] will be used if the string contains a
+    ///   non-graphical component that requires escaping.
+    /// * The string is quoted if it would otherwise be read back as something
+    ///   else, like a number, a keyword such as `null`, or a string starting
+    ///   with an indicator such as `&` or `- `. [`StringKind::Double`] is used
+    ///   if the string contains a `'` but no backslashes, otherwise
+    ///   [`StringKind::Single`] is used.
+    /// * Otherwise, [`StringKind::Bare`] is used, even if the string contains
+    ///   backslashes, since they are not escape characters in bare strings.
+    ///
+    /// The preferred quote style and which strings are always quoted can be
+    /// configured for the whole document through [`Document::set_quoting`].
+    ///
+    /// [`Document::set_quoting`]: crate::yaml::Document::set_quoting
     ///
     /// # Examples
     ///
@@ -435,6 +472,9 @@ impl<'a> ValueMut<'a> {
     ///
     /// doc.as_mut().set_string("rust@1.65");
     /// assert_eq!(doc.to_string(), "  rust@1.65");
+    ///
+    /// doc.as_mut().set_string("C:\\Users\\new");
+    /// assert_eq!(doc.to_string(), "  C:\\Users\\new");
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[inline]
@@ -442,7 +482,102 @@ impl<'a> ValueMut<'a> {
     where
         S: AsRef<str>,
     {
-        let value = raw::new_string(self.data, string);
+        if self.is_locked() {
+            return;
+        }
+
+        let value = raw::new_string(self.data, string, clear::in_flow(self.data, self.id));
+        self.data.replace(self.id, value);
+    }
+
+    /// Set the value as a string, keeping the style of the existing string
+    /// where the new string permits it.
+    ///
+    /// Literal and folded blocks keep their indicator and indentation, with the
+    /// chomping indicator adjusted to the trailing line breaks of the new
+    /// string. Folded blocks can only hold a single line, so they are turned
+    /// into literal blocks otherwise. Quoted strings keep their quotes, and bare
+    /// strings stay bare unless the new string has to be quoted.
+    ///
+    /// If the existing value is not a string, or the new string can't be
+    /// written in its style, this behaves like [`ValueMut::set_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     run: |
+    ///         cargo build
+    ///     name: 'ci'
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.get_mut("run").context("missing run")?.set_string_preserving_style("cargo build\ncargo test\n");
+    /// root.get_mut("name").context("missing name")?.set_string_preserving_style("test");
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     run: |
+    ///         cargo build
+    ///         cargo test
+    ///     name: 'test'
+    ///     "
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_string_preserving_style<S>(&mut self, string: S)
+    where
+        S: AsRef<str>,
+    {
+        if self.is_locked() {
+            return;
+        }
+
+        let value = raw::new_string_preserving(self.data, self.id, string.as_ref());
+        self.data.replace(self.id, value);
+    }
+
+    /// Set the value as a string which is not required to be valid UTF-8.
+    ///
+    /// This uses the same heuristics as [`ValueMut::set_string`] to determine
+    /// which [`StringKind`] to use, and the bytes are written exactly as
+    /// provided through [`Document::write_to`].
+    ///
+    /// [`Document::write_to`]: crate::yaml::Document::write_to
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("  string")?;
+    ///
+    /// // "café" encoded as Windows-1252.
+    /// doc.as_mut().set_bytes(b"caf\xe9");
+    /// assert_eq!(doc.as_ref().as_bytes(), Some(&b"caf\xe9"[..]));
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to(&mut out)?;
+    /// assert_eq!(out, b"  caf\xe9");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[inline]
+    pub fn set_bytes<B>(&mut self, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        if self.is_locked() {
+            return;
+        }
+
+        let value = raw::new_bytes(self.data, bytes, clear::in_flow(self.data, self.id));
         self.data.replace(self.id, value);
     }
 
@@ -468,16 +603,65 @@ impl<'a> ValueMut<'a> {
     where
         S: AsRef<str>,
     {
+        if self.is_locked() {
+            return;
+        }
+
         let value = raw::new_string_with(self.data, string, kind);
         self.data.replace(self.id, value);
     }
 
+    /// Set the value as a placeholder with the given name, like
+    /// `<REPLACE_ME>`.
+    ///
+    /// The style of the placeholder is set through
+    /// [`Document::set_placeholder`], and it can later be filled in through
+    /// [`Document::fill_placeholders`].
+    ///
+    /// [`Document::set_placeholder`]: crate::yaml::Document::set_placeholder
+    /// [`Document::fill_placeholders`]: crate::yaml::Document::fill_placeholders
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("image: nginx")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// root.get_mut("image").context("missing image")?.set_placeholder("IMAGE");
+    /// assert_eq!(doc.to_string(), "image: <IMAGE>");
+    ///
+    /// let doc = yaml::from_slice(doc.to_string())?;
+    /// assert_eq!(doc.placeholders().map(|(_, name)| name).collect::<Vec<_>>(), ["IMAGE"]);
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_placeholder(&mut self, name: &str) {
+        if self.is_locked() {
+            return;
+        }
+
+        let string = self.data.placeholder().format(name);
+        let value = raw::new_string(self.data, string, clear::in_flow(self.data, self.id));
+        self.data.replace(self.id, value);
+    }
+
     /// Set the value as a literal block.
     ///
     /// This takes an iterator, which will be used to construct the block. The
     /// underlying value type produced is in fact a string, and can be read
     /// through methods such as [`Value::as_str`].
     ///
+    /// If the first line with any content starts with whitespace, the block is
+    /// written with an explicit indentation indicator like `|2`, since its
+    /// indentation can't be detected otherwise. See
+    /// [`ValueMut::set_block_indented`] to request a specific indicator.
+    ///
+    /// Blocks can't be written inside of inline collections like `[a, b]` or
+    /// after a tag like `!Ref`, so there the value is written as a
+    /// double-quoted string instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -574,8 +758,116 @@ impl<'a> ValueMut<'a> {
         I: IntoIterator,
         I::Item: AsRef<str>,
     {
-        let value = raw::new_block(self.data, self.id, iter, block);
+        if self.is_locked() {
+            return;
+        }
+
+        let value = raw::new_block(self.data, self.id, iter, block, None, false);
+        self.data.replace(self.id, value);
+    }
+
+    /// Set the value as a block with an explicit indentation indicator, like
+    /// `|2`, returning `false` if `indent` is not in the range `1..=9`.
+    ///
+    /// The content of the block is indented by `indent` relative to the
+    /// collection the value belongs to, which allows lines to start with
+    /// whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     script: string
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let mut script = root.get_mut("script").context("missing script")?;
+    /// assert!(script.set_block_indented(["  indented", "first"], yaml::Block::Literal(yaml::Chomp::Strip), 4));
+    /// assert!(!script.set_block_indented(["first"], yaml::Block::Literal(yaml::Chomp::Strip), 10));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     script: |4-
+    ///           indented
+    ///         first
+    ///     "
+    /// );
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// assert_eq!(root.get("script").and_then(|v| v.as_str()), Some("  indented\nfirst"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_block_indented<I>(&mut self, iter: I, block: Block, indent: u8) -> bool
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        if !(1..=9).contains(&indent) || self.is_locked() {
+            return false;
+        }
+
+        let value = raw::new_block(self.data, self.id, iter, block, Some(indent), false);
         self.data.replace(self.id, value);
+        true
+    }
+
+    /// Append a line to an existing literal block, returning `false` if the
+    /// value is not a literal block.
+    ///
+    /// The line is written with the indentation detected from the existing
+    /// block, and its chomping indicator is respected, so that the rest of the
+    /// block is left untouched. A line containing line breaks is appended as
+    /// multiple lines. Trailing blank lines are part of the value of a block
+    /// which keeps its line breaks, like `|+`, so the line is appended after
+    /// them and the trailing line breaks are kept as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice(
+    ///     r"
+    ///     run: |
+    ///         cargo build
+    ///         cargo test
+    ///     name: ci
+    ///     "
+    /// )?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    /// let mut run = root.get_mut("run").context("missing run")?;
+    /// assert!(run.push_block_line("cargo clippy"));
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r"
+    ///     run: |
+    ///         cargo build
+    ///         cargo test
+    ///         cargo clippy
+    ///     name: ci
+    ///     "
+    /// );
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let run = root.get("run").and_then(|v| v.as_str());
+    /// assert_eq!(run, Some("cargo build\ncargo test\ncargo clippy\n"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn push_block_line(&mut self, line: &str) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+
+        raw::push_block_line(self.data, self.id, line)
     }
 
     /// Set the value as a boolean.
@@ -593,6 +885,10 @@ impl<'a> ValueMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     pub fn set_bool(&mut self, value: bool) {
+        if self.is_locked() {
+            return;
+        }
+
         let value = raw::new_bool(self.data, value);
         self.data.replace(self.id, value);
     }
@@ -610,8 +906,202 @@ impl<'a> ValueMut<'a> {
     set_number!(set_u128, u128, "128-bit unsigned integer", 42, U128);
     set_number!(set_i128, i128, "128-bit signed integer", -42, I128);
 
+    /// Set the value to anything which can be converted into a [`Scalar`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("  string")?;
+    ///
+    /// doc.as_mut().set_value(42i64);
+    /// assert_eq!(doc.to_string(), "  42");
+    ///
+    /// doc.as_mut().set_value(yaml::Null::Keyword);
+    /// assert_eq!(doc.to_string(), "  null");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_value<'s, T>(&mut self, value: T)
+    where
+        T: Into<Scalar<'s>>,
+    {
+        if self.is_locked() {
+            return;
+        }
+
+        let value = value
+            .into()
+            .into_raw(self.data, clear::in_flow(self.data, self.id));
+        self.data.replace(self.id, value);
+    }
+
+    /// Set the anchor of the value, like `&name`, so that it can be referenced
+    /// by aliases such as those inserted through [`MappingMut::insert_alias`].
+    ///
+    /// If the value already has an anchor, it is renamed. A block collection
+    /// is moved to the line following its anchor.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::BadAnchor`] if `name` is empty or contains
+    /// whitespace or a flow indicator like `,` or `[`, and with
+    /// [`ErrorKind::Locked`] if the value is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::BadAnchor`]: crate::yaml::ErrorKind::BadAnchor
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("web:\n  image: app\n  ports: [80]\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.get_mut("web").context("missing web")?.set_anchor("defaults")?;
+    ///
+    /// let mut web = root.get_mut("web").and_then(|v| v.into_mapping_mut()).context("missing web")?;
+    /// web.get_mut("ports").context("missing ports")?.set_anchor("ports")?;
+    ///
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "web: &defaults\n  image: app\n  ports: &ports [80]\n"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_anchor(&mut self, name: &str) -> Result<(), Error> {
+        raw::check_anchor_name(name)?;
+        lock::check_inside(self.data, self.id)?;
+        let name = self.data.insert_str(name);
+        raw::set_anchor(self.data, self.id, name);
+        Ok(())
+    }
+
+    /// Remove the anchor of the value, returning `true` if it had one.
+    ///
+    /// Aliases referencing the anchor are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: &x 1\nb: &y\n  c: 2\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert!(root.get_mut("a").context("missing a")?.remove_anchor());
+    /// assert!(root.get_mut("b").context("missing b")?.remove_anchor());
+    /// assert!(!root.get_mut("b").context("missing b")?.remove_anchor());
+    ///
+    /// assert_eq!(doc.to_string(), "a: 1\nb:\n  c: 2\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn remove_anchor(&mut self) -> bool {
+        if lock::is_locked(self.data, self.id) {
+            return false;
+        }
+
+        raw::remove_anchor(self.data, self.id)
+    }
+
+    /// Set the tag of the value, like `!Ref`, replacing any existing tag.
+    ///
+    /// Tags can be set on both scalars and collections. A block collection is
+    /// moved to the line following its tag.
+    ///
+    /// Note that tags on values other than collections are treated as part of
+    /// the value when a document is parsed, see [`Value::tag`].
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::BadTag`] if `tag` doesn't start with `!`, or
+    /// if it contains whitespace or a flow indicator like `,` or `[`, with
+    /// [`ErrorKind::TaggedBlock`] if the value is a block scalar like `|`,
+    /// and with [`ErrorKind::Locked`] if the value is
+    /// [locked][crate::yaml::Document::lock] by someone else.
+    ///
+    /// [`ErrorKind::BadTag`]: crate::yaml::ErrorKind::BadTag
+    /// [`ErrorKind::TaggedBlock`]: crate::yaml::ErrorKind::TaggedBlock
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("bucket: logs # name\nitems:\n  - a\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// root.get_mut("bucket").context("missing bucket")?.set_tag("!Ref")?;
+    /// root.get_mut("items").context("missing items")?.set_tag("!!set")?;
+    ///
+    /// assert_eq!(doc.to_string(), "bucket: !Ref logs # name\nitems: !!set\n  - a\n");
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root mapping")?;
+    /// assert_eq!(root.get("bucket").and_then(|v| v.tag()), Some("!Ref".into()));
+    /// assert_eq!(root.get("bucket").and_then(|v| v.as_str()), Some("logs"));
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn set_tag(&mut self, tag: &str) -> Result<(), Error> {
+        raw::check_tag_name(tag)?;
+        lock::check_inside(self.data, self.id)?;
+
+        // The parser only reads tags of scalars from the same line, which
+        // would be followed by the content of the block.
+        if let Raw::String(string) = self.data.raw(self.id) {
+            if raw::block_style(self.data, string).is_some() {
+                return Err(Error::new(0..0, ErrorKind::TaggedBlock));
+            }
+        }
+
+        let tag = self.data.insert_str(tag);
+        raw::set_tag(self.data, self.id, tag);
+        Ok(())
+    }
+
+    /// Remove the tag of the value, returning `true` if it had one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let mut doc = yaml::from_slice("a: !!set\n  ? b\nc: [1]\n")?;
+    ///
+    /// let mut root = doc.as_mut().into_mapping_mut().context("missing root mapping")?;
+    /// assert!(root.get_mut("a").context("missing a")?.clear_tag());
+    /// assert!(!root.get_mut("c").context("missing c")?.clear_tag());
+    ///
+    /// assert_eq!(doc.to_string(), "a:\n  ? b\nc: [1]\n");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    pub fn clear_tag(&mut self) -> bool {
+        if lock::is_locked(self.data, self.id) {
+            return false;
+        }
+
+        raw::remove_tag(self.data, self.id)
+    }
+
     /// Make the value into a mapping, unless it already is one.
     ///
+    /// Values nested inside of an inline collection are made into an inline
+    /// mapping, like `{k: v}`, since a block mapping can't be written there.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if the value has to be replaced and
+    /// it is, or contains, a value [locked][crate::yaml::Document::lock] by
+    /// someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
     /// # Examples
     ///
     /// ```
@@ -624,7 +1114,7 @@ impl<'a> ValueMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().make_mapping();
+    /// let mut mapping = doc.as_mut().make_mapping()?;
     /// mapping.insert_u32("first", 1);
     /// mapping.insert_u32("second", 2);
     ///
@@ -642,7 +1132,7 @@ impl<'a> ValueMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| Some(m.get_into_mut("first")?.make_mapping())).context("missing first")?;
+    /// let mut mapping = doc.as_mut().into_mapping_mut().and_then(|m| m.get_into_mut("first")?.make_mapping().ok()).context("missing first")?;
     /// mapping.insert_u32("second", 2);
     /// mapping.insert_u32("third", 3);
     ///
@@ -671,7 +1161,7 @@ impl<'a> ValueMut<'a> {
     /// )?;
     ///
     /// let mut seq = doc.as_mut().into_sequence_mut().context("not a sequence")?;
-    /// let mut mapping = seq.push(yaml::Separator::Auto).make_mapping();
+    /// let mut mapping = seq.push(yaml::Separator::Auto).make_mapping()?;
     ///
     /// mapping.insert_u32("three", 3);
     /// mapping.insert_u32("four", 4);
@@ -688,27 +1178,53 @@ impl<'a> ValueMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[inline]
-    #[must_use]
-    pub fn make_mapping(self) -> MappingMut<'a> {
+    pub fn make_mapping(self) -> Result<MappingMut<'a>, Error> {
         if !matches!(self.data.raw(self.id), Raw::Mapping(..)) {
-            let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+            lock::check(self.data, self.id)?;
+
+            // Block collections can't be nested inside of inline collections,
+            // so an inline collection is made instead.
+            let (indent, prefix, kind) = if clear::in_flow(self.data, self.id) {
+                let prefix = self.data.layout(self.id).prefix;
+                let suffix = self.data.insert_str("");
+                let kind = raw::MappingKind::Inline {
+                    trailing: false,
+                    suffix,
+                };
+                (0, prefix, kind)
+            } else {
+                let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+                (indent, prefix, raw::MappingKind::Mapping)
+            };
 
             self.data.replace_with(
                 self.id,
                 prefix,
                 Raw::Mapping(raw::Mapping {
                     indent,
-                    kind: raw::MappingKind::Mapping,
+                    kind,
                     items: Vec::new(),
+                    tag: None,
                 }),
             );
         }
 
-        MappingMut::new(self.data, self.id)
+        Ok(MappingMut::new(self.data, self.id))
     }
 
     /// Make the value into a sequence, unless it already is one.
     ///
+    /// Values nested inside of an inline collection are made into an inline
+    /// sequence, like `[a, b]`, since a block sequence can't be written there.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`ErrorKind::Locked`] if the value has to be replaced and
+    /// it is, or contains, a value [locked][crate::yaml::Document::lock] by
+    /// someone else.
+    ///
+    /// [`ErrorKind::Locked`]: crate::yaml::ErrorKind::Locked
+    ///
     /// # Examples
     ///
     /// ```
@@ -721,7 +1237,7 @@ impl<'a> ValueMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut sequence = doc.as_mut().make_sequence();
+    /// let mut sequence = doc.as_mut().make_sequence()?;
     /// sequence.push_u32(1);
     /// sequence.push_u32(2);
     ///
@@ -739,7 +1255,7 @@ impl<'a> ValueMut<'a> {
     ///     "
     /// )?;
     ///
-    /// let mut sequence = doc.as_mut().into_mapping_mut().and_then(|m| Some(m.get_into_mut("first")?.make_sequence())).context("missing first")?;
+    /// let mut sequence = doc.as_mut().into_mapping_mut().and_then(|m| m.get_into_mut("first")?.make_sequence().ok()).context("missing first")?;
     /// sequence.push_u32(2);
     /// sequence.push_u32(3);
     ///
@@ -754,22 +1270,37 @@ impl<'a> ValueMut<'a> {
     /// # Ok::<_, anyhow::Error>(())
     /// ```
     #[inline]
-    #[must_use]
-    pub fn make_sequence(self) -> SequenceMut<'a> {
+    pub fn make_sequence(self) -> Result<SequenceMut<'a>, Error> {
         if !matches!(self.data.raw(self.id), Raw::Sequence(..)) {
-            let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+            lock::check(self.data, self.id)?;
+
+            // Block collections can't be nested inside of inline collections,
+            // so an inline collection is made instead.
+            let (indent, prefix, kind) = if clear::in_flow(self.data, self.id) {
+                let prefix = self.data.layout(self.id).prefix;
+                let suffix = self.data.insert_str("");
+                let kind = raw::SequenceKind::Inline {
+                    trailing: false,
+                    suffix,
+                };
+                (0, prefix, kind)
+            } else {
+                let (indent, prefix) = raw::make_indent(self.data, self.id, 0);
+                (indent, prefix, raw::SequenceKind::Mapping)
+            };
 
             self.data.replace_with(
                 self.id,
                 prefix,
                 Raw::Sequence(raw::Sequence {
                     indent,
-                    kind: raw::SequenceKind::Mapping,
+                    kind,
                     items: Vec::new(),
+                    tag: None,
                 }),
             );
         }
 
-        SequenceMut::new(self.data, self.id)
+        Ok(SequenceMut::new(self.data, self.id))
     }
 }
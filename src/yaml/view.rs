@@ -0,0 +1,123 @@
+//! Typed views over the mappings of a [`Document`].
+//!
+//! With the `derive` feature enabled, the [`YamlView`] derive generates typed
+//! accessors for the fields of a struct. For a struct named `Config` it
+//! generates a `ConfigView` wrapping a [`Value`], and a `ConfigViewMut`
+//! wrapping a [`ValueMut`]. Each field gets a getter with the same name, and
+//! the mutable view also gets a `set_<field>` method.
+//!
+//! Getters return `None` if the value isn't a mapping, if the key is missing,
+//! or if its value can't be converted through [`FromValue`]. Setters replace
+//! the value of an existing key in place and append missing keys to the end of
+//! the mapping, so that the formatting of the rest of the document is left
+//! intact. They return `false` if the value isn't a mapping.
+//!
+//! The key of a field can be changed with `#[yaml(rename = "...")]`.
+//!
+//! [`Document`]: crate::yaml::Document
+//! [`Value`]: crate::yaml::Value
+//! [`ValueMut`]: crate::yaml::ValueMut
+//! [`YamlView`]: crate::yaml::YamlView
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use nondestructive::yaml;
+//!
+//! #[derive(yaml::YamlView)]
+//! struct Config {
+//!     replicas: u32,
+//!     #[yaml(rename = "image-name")]
+//!     image: String,
+//! }
+//!
+//! let mut doc = yaml::from_slice(
+//!     r"
+//!     name: app
+//!     replicas: 2
+//!     "
+//! )?;
+//!
+//! let view = ConfigView::new(doc.as_ref());
+//! assert_eq!(view.replicas(), Some(2));
+//! assert_eq!(view.image(), None);
+//!
+//! let mut view = ConfigViewMut::new(doc.as_mut());
+//! assert!(view.set_replicas(3));
+//! assert!(view.set_image(String::from("nginx")));
+//!
+//! assert_eq!(
+//!     doc.to_string(),
+//!     r"
+//!     name: app
+//!     replicas: 3
+//!     image-name: nginx
+//!     "
+//! );
+//! # }
+//! # Ok::<_, anyhow::Error>(())
+//! ```
+
+use crate::yaml::Value;
+
+/// Conversion from a [`Value`], used by the getters generated by the
+/// [`YamlView`] derive.
+///
+/// [`YamlView`]: crate::yaml::YamlView
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+/// use nondestructive::yaml::view::FromValue;
+///
+/// let doc = yaml::from_slice("[42, true, hello]")?;
+/// let root = doc.as_ref().as_sequence().unwrap();
+///
+/// assert_eq!(root.get(0).and_then(|v| u32::from_value(&v)), Some(42));
+/// assert_eq!(root.get(1).and_then(|v| bool::from_value(&v)), Some(true));
+/// assert_eq!(root.get(2).and_then(|v| String::from_value(&v)).as_deref(), Some("hello"));
+/// assert_eq!(root.get(2).and_then(|v| u32::from_value(&v)), None);
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+pub trait FromValue: Sized {
+    /// Convert the given value, returning `None` if it's of the wrong type.
+    fn from_value(value: &Value<'_>) -> Option<Self>;
+}
+
+macro_rules! from_value {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        $(
+            impl FromValue for $ty {
+                #[inline]
+                fn from_value(value: &Value<'_>) -> Option<Self> {
+                    value.$method()
+                }
+            }
+        )*
+    };
+}
+
+from_value! {
+    bool => as_bool,
+    f32 => as_f32,
+    f64 => as_f64,
+    u8 => as_u8,
+    i8 => as_i8,
+    u16 => as_u16,
+    i16 => as_i16,
+    u32 => as_u32,
+    i32 => as_i32,
+    u64 => as_u64,
+    i64 => as_i64,
+    u128 => as_u128,
+    i128 => as_i128,
+}
+
+impl FromValue for std::string::String {
+    #[inline]
+    fn from_value(value: &Value<'_>) -> Option<Self> {
+        Some(value.as_str()?.to_owned())
+    }
+}
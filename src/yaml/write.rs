@@ -0,0 +1,148 @@
+use std::io;
+
+use bstr::ByteSlice;
+
+use crate::yaml::data::{Data, Id};
+use crate::yaml::raw::Raw;
+use crate::yaml::Value;
+
+/// A callback used to render scalars, see [`WriteOptions::scalar_renderer`].
+type Renderer<'a> = dyn FnMut(Value<'_>, &[String]) -> Option<String> + 'a;
+
+/// Options used when writing a document through [`Document::write_to_with`].
+///
+/// [`Document::write_to_with`]: crate::yaml::Document::write_to_with
+///
+/// # Examples
+///
+/// ```
+/// use nondestructive::yaml;
+///
+/// let doc = yaml::from_slice("user: admin\npassword: hunter2\n")?;
+///
+/// let options = yaml::WriteOptions::new().scalar_renderer(|_, path| {
+///     (path == ["password"]).then(|| String::from("'***'"))
+/// });
+///
+/// let mut out = Vec::new();
+/// doc.write_to_with(&mut out, options)?;
+///
+/// assert_eq!(String::from_utf8(out)?, "user: admin\npassword: '***'\n");
+/// assert_eq!(doc.to_string(), "user: admin\npassword: hunter2\n");
+/// # Ok::<_, anyhow::Error>(())
+/// ```
+#[derive(Default)]
+pub struct WriteOptions<'a> {
+    scalar_renderer: Option<Box<Renderer<'a>>>,
+}
+
+impl<'a> WriteOptions<'a> {
+    /// Construct default write options, which write the document exactly as
+    /// [`Document::write_to`] does.
+    ///
+    /// [`Document::write_to`]: crate::yaml::Document::write_to
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intercept how scalars are written.
+    ///
+    /// The `renderer` is called with every scalar in the document and its
+    /// path, where mapping keys and sequence indexes are used as segments. If
+    /// it returns `Some`, the returned text is written verbatim instead of the
+    /// scalar, following the whitespace which precedes it. The renderer is
+    /// responsible for making sure that the text is valid YAML in its
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// Values can also be matched by their [`Id`]:
+    ///
+    /// ```
+    /// use anyhow::Context;
+    /// use nondestructive::yaml;
+    ///
+    /// let doc = yaml::from_slice("tokens: [abc, def]\nname: app")?;
+    ///
+    /// let root = doc.as_ref().as_mapping().context("missing root")?;
+    /// let token = root
+    ///     .get("tokens")
+    ///     .and_then(|v| Some(v.as_sequence()?.get(1)?.id()))
+    ///     .context("missing token")?;
+    ///
+    /// let options = yaml::WriteOptions::new().scalar_renderer(move |value, _| {
+    ///     (value.id() == token).then(|| String::from("~"))
+    /// });
+    ///
+    /// let mut out = Vec::new();
+    /// doc.write_to_with(&mut out, options)?;
+    /// assert_eq!(String::from_utf8(out)?, "tokens: [abc, ~]\nname: app");
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn scalar_renderer<F>(mut self, renderer: F) -> Self
+    where
+        F: 'a + FnMut(Value<'_>, &[String]) -> Option<String>,
+    {
+        self.scalar_renderer = Some(Box::new(renderer));
+        self
+    }
+
+    /// Construct the hook used while writing.
+    pub(crate) fn hook(&mut self) -> Hook<'_> {
+        Hook {
+            renderer: match &mut self.scalar_renderer {
+                Some(renderer) => Some(&mut **renderer),
+                None => None,
+            },
+            path: Vec::new(),
+        }
+    }
+}
+
+/// State used to intercept how values are written.
+#[derive(Default)]
+pub(crate) struct Hook<'a> {
+    renderer: Option<&'a mut Renderer<'a>>,
+    path: Vec<String>,
+}
+
+impl Hook<'_> {
+    /// Write the value `id`, which excludes its prefix.
+    pub(crate) fn write_value<O>(&mut self, data: &Data, id: Id, o: &mut O) -> io::Result<()>
+    where
+        O: ?Sized + io::Write,
+    {
+        let raw = data.raw(id);
+
+        if let Some(renderer) = &mut self.renderer {
+            if !matches!(raw, Raw::Mapping(..) | Raw::Sequence(..)) {
+                if let Some(text) = renderer(Value::new(data, id), &self.path) {
+                    return o.write_all(text.as_bytes());
+                }
+            }
+        }
+
+        raw.write_with(data, o, self)
+    }
+
+    /// Enter a sequence item with the given index.
+    pub(crate) fn enter_index(&mut self, index: usize) {
+        if self.renderer.is_some() {
+            self.path.push(index.to_string());
+        }
+    }
+
+    /// Enter a mapping item with the given key.
+    pub(crate) fn enter_key(&mut self, key: &[u8]) {
+        if self.renderer.is_some() {
+            self.path.push(key.to_str_lossy().into_owned());
+        }
+    }
+
+    /// Leave the last entered item.
+    pub(crate) fn leave(&mut self) {
+        self.path.pop();
+    }
+}
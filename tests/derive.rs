@@ -0,0 +1,53 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use nondestructive::yaml;
+
+#[derive(yaml::YamlView)]
+#[allow(dead_code)]
+struct Config {
+    replicas: u32,
+    enabled: bool,
+    ratio: f64,
+    #[yaml(rename = "image-name")]
+    image: String,
+    r#type: String,
+}
+
+#[test]
+fn get_and_set() -> Result<()> {
+    let mut doc = yaml::from_slice(
+        "# deployment\nreplicas: 2\nenabled: yes\nimage-name: \"nginx\"\ntype: web\n",
+    )?;
+
+    let view = ConfigView::new(doc.as_ref());
+    assert_eq!(view.replicas(), Some(2));
+    assert_eq!(view.enabled(), None);
+    assert_eq!(view.ratio(), None);
+    assert_eq!(view.image().as_deref(), Some("nginx"));
+    assert_eq!(view.r#type().as_deref(), Some("web"));
+
+    let mut view = ConfigViewMut::new(doc.as_mut());
+    assert!(view.set_replicas(3));
+    assert!(view.set_enabled(true));
+    assert!(view.set_ratio(0.5));
+    assert!(view.set_type(String::from("worker")));
+    assert_eq!(view.replicas(), Some(3));
+    assert_eq!(view.as_ref().enabled(), Some(true));
+
+    assert_eq!(
+        doc.to_string(),
+        "# deployment\nreplicas: 3\nenabled: true\nimage-name: \"nginx\"\ntype: worker\nratio: 0.5\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn not_a_mapping() -> Result<()> {
+    let mut doc = yaml::from_slice("[1, 2]")?;
+
+    assert_eq!(ConfigView::new(doc.as_ref()).replicas(), None);
+    assert!(!ConfigViewMut::new(doc.as_mut()).set_replicas(1));
+    assert_eq!(doc.to_string(), "[1, 2]");
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anyhow::{Context, Result};
+use nondestructive::yaml;
+
+/// A document encoded as Windows-1252.
+const INPUT: &[u8] = b"
+caf\xe9: cr\xe8me br\xfbl\xe9e
+\xe0 la carte:
+  - \"d\xe9j\xe0 vu\"
+  - na\xefve
+number: 42
+";
+
+fn write(doc: &yaml::Document) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    doc.write_to(&mut out)?;
+    Ok(out)
+}
+
+#[test]
+fn roundtrip() -> Result<()> {
+    let doc = yaml::from_slice(INPUT)?;
+    assert_eq!(write(&doc)?, INPUT);
+
+    let root = doc.as_ref().as_mapping().context("missing root")?;
+
+    let value = root.get_bytes(b"caf\xe9").context("missing key")?;
+    assert_eq!(value.as_bytes(), Some(&b"cr\xe8me br\xfbl\xe9e"[..]));
+    assert_eq!(value.as_str(), None);
+
+    let seq = root
+        .get_bytes(b"\xe0 la carte")
+        .and_then(|v| v.as_sequence())
+        .context("missing sequence")?;
+
+    assert_eq!(
+        seq.get(0).and_then(|v| v.as_bytes()),
+        Some(&b"d\xe9j\xe0 vu"[..])
+    );
+    assert_eq!(
+        seq.get(1).and_then(|v| v.as_bytes()),
+        Some(&b"na\xefve"[..])
+    );
+    Ok(())
+}
+
+#[test]
+fn edits_preserve_bytes() -> Result<()> {
+    let mut doc = yaml::from_slice(INPUT)?;
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    root.get_mut("number")
+        .context("missing number")?
+        .set_u32(43);
+    root.insert_bytes(b"gar\xe7on", b"\xe9t\xe9");
+
+    let mut seq = root
+        .get_bytes_into_mut(b"\xe0 la carte")
+        .and_then(yaml::ValueMut::into_sequence_mut)
+        .context("missing sequence")?;
+
+    seq.push_bytes(b"fa\xe7ade");
+
+    assert_eq!(
+        write(&doc)?,
+        b"
+caf\xe9: cr\xe8me br\xfbl\xe9e
+\xe0 la carte:
+  - \"d\xe9j\xe0 vu\"
+  - na\xefve
+  - fa\xe7ade
+number: 43
+gar\xe7on: \xe9t\xe9
+"
+    );
+
+    let mut root = doc.as_mut().into_mapping_mut().context("missing root")?;
+    assert!(root.remove_bytes(b"\xe0 la carte"));
+    assert!(!root.remove_bytes(b"\xe0 la carte"));
+
+    assert_eq!(
+        write(&doc)?,
+        b"
+caf\xe9: cr\xe8me br\xfbl\xe9e
+number: 43
+gar\xe7on: \xe9t\xe9
+"
+    );
+    Ok(())
+}
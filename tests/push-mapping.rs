@@ -11,11 +11,13 @@ fn push_mapping() -> Result<()> {
     )?;
 
     let mut seq = doc.as_mut().into_sequence_mut().context("not a sequence")?;
-    let mut mapping = seq.push(yaml::Separator::Auto).make_mapping();
+    let mut mapping = seq.push(yaml::Separator::Auto).make_mapping()?;
 
     mapping.insert_u32("three", 3);
     mapping.insert_u32("four", 4);
-    let mut mapping2 = mapping.insert("five", yaml::Separator::Auto).make_mapping();
+    let mut mapping2 = mapping
+        .insert("five", yaml::Separator::Auto)?
+        .make_mapping()?;
 
     mapping2.insert_str("six", "six");
     mapping2.insert_str("seven", "seven");